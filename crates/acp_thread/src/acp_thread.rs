@@ -1,5 +1,6 @@
 mod connection;
 mod diff;
+mod elicitation;
 mod mention;
 mod terminal;
 
@@ -33,6 +34,7 @@ pub fn meta_with_tool_name(tool_name: &str) -> acp::Meta {
 use collections::HashSet;
 pub use connection::*;
 pub use diff::*;
+pub use elicitation::*;
 use language::language_settings::FormatOnSave;
 pub use mention::*;
 use project::lsp_store::{FormatTrigger, LspFormatTarget};
@@ -45,6 +47,7 @@ pub use terminal::*;
 use action_log::{ActionLog, ActionLogTelemetry};
 use agent_client_protocol::{self as acp};
 use anyhow::{Context as _, Result, anyhow};
+use context_server::types::ElicitResult;
 use futures::{FutureExt, channel::oneshot, future::BoxFuture};
 use gpui::{AppContext, AsyncApp, Context, Entity, EventEmitter, SharedString, Task, WeakEntity};
 use itertools::Itertools;
@@ -79,6 +82,13 @@ pub struct Checkpoint {
     pub show: bool,
 }
 
+impl Checkpoint {
+    /// The state of the project's repositories before the message was sent.
+    pub fn git_checkpoint(&self) -> &GitStoreCheckpoint {
+        &self.git_checkpoint
+    }
+}
+
 impl UserMessage {
     fn to_markdown(&self, cx: &App) -> String {
         let mut markdown = String::new();
@@ -489,6 +499,11 @@ pub enum ToolCallStatus {
         options: PermissionOptions,
         respond_tx: oneshot::Sender<acp::PermissionOptionId>,
     },
+    /// The tool call is running and waiting for input requested by the tool.
+    WaitingForInput {
+        elicitation: Entity<Elicitation>,
+        respond_tx: oneshot::Sender<ElicitResult>,
+    },
     /// The tool call is currently running.
     InProgress,
     /// The tool call completed successfully.
@@ -521,6 +536,7 @@ impl Display for ToolCallStatus {
             match self {
                 ToolCallStatus::Pending => "Pending",
                 ToolCallStatus::WaitingForConfirmation { .. } => "Waiting for confirmation",
+                ToolCallStatus::WaitingForInput { .. } => "Waiting for input",
                 ToolCallStatus::InProgress => "In Progress",
                 ToolCallStatus::Completed => "Completed",
                 ToolCallStatus::Failed => "Failed",
@@ -978,6 +994,7 @@ pub enum AcpThreadEvent {
     EntryUpdated(usize),
     EntriesRemoved(Range<usize>),
     ToolAuthorizationRequired,
+    ToolInputRequired,
     Retry(RetryStatus),
     SubagentSpawned(acp::SessionId),
     Stopped,
@@ -1247,7 +1264,9 @@ impl AcpThread {
             match entry {
                 AgentThreadEntry::UserMessage(_) => return false,
                 AgentThreadEntry::ToolCall(ToolCall {
-                    status: ToolCallStatus::WaitingForConfirmation { .. },
+                    status:
+                        ToolCallStatus::WaitingForConfirmation { .. }
+                        | ToolCallStatus::WaitingForInput { .. },
                     ..
                 }) => return true,
                 AgentThreadEntry::ToolCall(_) | AgentThreadEntry::AssistantMessage(_) => {}
@@ -1800,6 +1819,60 @@ impl AcpThread {
         cx.emit(AcpThreadEvent::EntryUpdated(ix));
     }
 
+    pub fn request_tool_call_input(
+        &mut self,
+        id: acp::ToolCallId,
+        elicitation: Elicitation,
+        cx: &mut Context<Self>,
+    ) -> Result<BoxFuture<'static, ElicitResult>> {
+        let (ix, call) = self
+            .tool_call_mut(&id)
+            .context("tool call not found while requesting input")?;
+        anyhow::ensure!(
+            matches!(
+                call.status,
+                ToolCallStatus::Pending | ToolCallStatus::InProgress
+            ),
+            "tool call is not running"
+        );
+
+        let (tx, rx) = oneshot::channel();
+        call.status = ToolCallStatus::WaitingForInput {
+            elicitation: cx.new(|_| elicitation),
+            respond_tx: tx,
+        };
+        cx.emit(AcpThreadEvent::EntryUpdated(ix));
+        cx.emit(AcpThreadEvent::ToolInputRequired);
+
+        Ok(async {
+            // If the request is dropped (e.g. the turn was canceled), the server
+            // is told the user canceled the request.
+            rx.await.unwrap_or_else(|_| ElicitResult::cancel())
+        }
+        .boxed())
+    }
+
+    pub fn respond_to_tool_call_input(
+        &mut self,
+        id: &acp::ToolCallId,
+        result: ElicitResult,
+        cx: &mut Context<Self>,
+    ) {
+        let Some((ix, call)) = self.tool_call_mut(id) else {
+            return;
+        };
+        if !matches!(call.status, ToolCallStatus::WaitingForInput { .. }) {
+            return;
+        }
+
+        let curr_status = mem::replace(&mut call.status, ToolCallStatus::InProgress);
+        if let ToolCallStatus::WaitingForInput { respond_tx, .. } = curr_status {
+            respond_tx.send(result).log_err();
+        }
+
+        cx.emit(AcpThreadEvent::EntryUpdated(ix));
+    }
+
     pub fn first_tool_awaiting_confirmation(&self) -> Option<&ToolCall> {
         let mut first_tool_call = None;
 
@@ -2071,6 +2144,7 @@ impl AcpThread {
                     call.status,
                     ToolCallStatus::Pending
                         | ToolCallStatus::WaitingForConfirmation { .. }
+                        | ToolCallStatus::WaitingForInput { .. }
                         | ToolCallStatus::InProgress
                 );
 
@@ -11,6 +11,7 @@ use std::{
     any::Any,
     error::Error,
     fmt,
+    ops::Range,
     path::{Path, PathBuf},
     rc::Rc,
     sync::Arc,
@@ -25,6 +26,16 @@ impl UserMessageId {
     pub fn new() -> Self {
         Self(Uuid::new_v4().to_string().into())
     }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<Arc<str>> for UserMessageId {
+    fn from(id: Arc<str>) -> Self {
+        Self(id)
+    }
 }
 
 pub trait AgentConnection {
@@ -255,6 +266,18 @@ impl AgentSessionInfo {
     }
 }
 
+/// A session whose content matches a search query.
+#[derive(Debug, Clone)]
+pub struct AgentSessionSearchMatch {
+    pub session_id: acp::SessionId,
+    /// The user message that started the turn containing the match, if known.
+    pub user_message_id: Option<UserMessageId>,
+    /// An excerpt of the matching content.
+    pub snippet: SharedString,
+    /// The byte ranges in `snippet` that matched the query.
+    pub highlights: Vec<Range<usize>>,
+}
+
 #[derive(Debug, Clone)]
 pub enum SessionListUpdate {
     Refresh,
@@ -283,6 +306,19 @@ pub trait AgentSessionList {
         Task::ready(Err(anyhow::anyhow!("delete_sessions not supported")))
     }
 
+    fn supports_search(&self) -> bool {
+        false
+    }
+
+    /// Searches the content of the sessions, returning the best matches first.
+    fn search_sessions(
+        &self,
+        _query: String,
+        _cx: &mut App,
+    ) -> Task<Result<Vec<AgentSessionSearchMatch>>> {
+        Task::ready(Err(anyhow::anyhow!("search_sessions not supported")))
+    }
+
     fn watch(&self, _cx: &mut App) -> Option<smol::channel::Receiver<SessionListUpdate>> {
         None
     }
@@ -0,0 +1,33 @@
+use context_server::types::{ElicitRequestParams, ElicitationSchema};
+use gpui::SharedString;
+
+/// A request for structured input from the user, sent by an MCP server while
+/// one of its tools is running (`elicitation/create`).
+#[derive(Debug)]
+pub struct Elicitation {
+    server_name: SharedString,
+    message: SharedString,
+    schema: ElicitationSchema,
+}
+
+impl Elicitation {
+    pub fn new(server_name: impl Into<SharedString>, request: ElicitRequestParams) -> Self {
+        Self {
+            server_name: server_name.into(),
+            message: request.message.into(),
+            schema: request.requested_schema,
+        }
+    }
+
+    pub fn server_name(&self) -> &SharedString {
+        &self.server_name
+    }
+
+    pub fn message(&self) -> &SharedString {
+        &self.message
+    }
+
+    pub fn schema(&self) -> &ElicitationSchema {
+        &self.schema
+    }
+}
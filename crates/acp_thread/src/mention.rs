@@ -60,6 +60,11 @@ pub enum MentionUri {
     GitDiff {
         base_ref: String,
     },
+    McpResource {
+        server_id: String,
+        uri: Url,
+        name: String,
+    },
 }
 
 impl MentionUri {
@@ -215,6 +220,24 @@ impl MentionUri {
                     let base_ref =
                         single_query_param(&url, "base")?.unwrap_or_else(|| "main".to_string());
                     Ok(Self::GitDiff { base_ref })
+                } else if path.starts_with("/agent/mcp-resource") {
+                    let mut server_id = None;
+                    let mut uri = None;
+                    let mut name = None;
+                    for (key, value) in url.query_pairs() {
+                        match key.as_ref() {
+                            "server" => server_id = Some(value.into_owned()),
+                            "uri" => uri = Some(Url::parse(&value)?),
+                            "name" => name = Some(value.into_owned()),
+                            _ => bail!("invalid query parameter"),
+                        }
+                    }
+                    let uri = uri.context("Missing uri for MCP resource")?;
+                    Ok(Self::McpResource {
+                        server_id: server_id.context("Missing server for MCP resource")?,
+                        name: name.unwrap_or_else(|| uri.to_string()),
+                        uri,
+                    })
                 } else {
                     bail!("invalid zed url: {:?}", input);
                 }
@@ -251,6 +274,7 @@ impl MentionUri {
                 ..
             } => selection_name(path.as_deref(), line_range),
             MentionUri::Fetch { url } => url.to_string(),
+            MentionUri::McpResource { name, .. } => name.clone(),
         }
     }
 
@@ -271,6 +295,7 @@ impl MentionUri {
             MentionUri::Selection { .. } => IconName::Reader.path().into(),
             MentionUri::Fetch { .. } => IconName::ToolWeb.path().into(),
             MentionUri::GitDiff { .. } => IconName::GitBranch.path().into(),
+            MentionUri::McpResource { .. } => IconName::ToolHammer.path().into(),
         }
     }
 
@@ -374,6 +399,18 @@ impl MentionUri {
                 url.query_pairs_mut().append_pair("base", base_ref);
                 url
             }
+            MentionUri::McpResource {
+                server_id,
+                uri,
+                name,
+            } => {
+                let mut url = Url::parse("zed:///agent/mcp-resource").unwrap();
+                url.query_pairs_mut()
+                    .append_pair("server", server_id)
+                    .append_pair("uri", uri.as_str())
+                    .append_pair("name", name);
+                url
+            }
         }
     }
 }
@@ -697,4 +734,32 @@ mod tests {
         let parsed_single = MentionUri::parse(single_line_uri, PathStyle::local()).unwrap();
         assert_eq!(parsed_single.name(), "Terminal (1 line)");
     }
+
+    #[test]
+    fn test_parse_mcp_resource_uri() {
+        let mcp_uri = "zed:///agent/mcp-resource?server=github&uri=repo%3A%2F%2Fzed-industries%2Fzed%2FREADME.md&name=README";
+        let parsed = MentionUri::parse(mcp_uri, PathStyle::local()).unwrap();
+        match &parsed {
+            MentionUri::McpResource {
+                server_id,
+                uri,
+                name,
+            } => {
+                assert_eq!(server_id, "github");
+                assert_eq!(uri.as_str(), "repo://zed-industries/zed/README.md");
+                assert_eq!(name, "README");
+            }
+            _ => panic!("Expected McpResource variant"),
+        }
+        assert_eq!(parsed.to_uri().to_string(), mcp_uri);
+        assert_eq!(parsed.name(), "README");
+
+        assert!(
+            MentionUri::parse(
+                "zed:///agent/mcp-resource?server=github",
+                PathStyle::local()
+            )
+            .is_err()
+        );
+    }
 }
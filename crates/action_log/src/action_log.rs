@@ -50,6 +50,8 @@ pub struct ActionLog {
     project: Entity<Project>,
     /// Stores undo information for the most recent reject operation
     last_reject_undo: Option<LastRejectUndo>,
+    /// The number of times the agent has created or edited a buffer.
+    edit_count: usize,
 }
 
 impl ActionLog {
@@ -59,6 +61,7 @@ impl ActionLog {
             tracked_buffers: BTreeMap::default(),
             project,
             last_reject_undo: None,
+            edit_count: 0,
         }
     }
 
@@ -139,6 +142,7 @@ impl ActionLog {
                     snapshot: text_snapshot,
                     status,
                     version: buffer.read(cx).version(),
+                    last_edit: None,
                     diff,
                     diff_update: diff_update_tx,
                     _open_lsp_handle: open_lsp_handle,
@@ -501,18 +505,23 @@ impl ActionLog {
 
     /// Mark a buffer as created by agent, so we can refresh it in the context
     pub fn buffer_created(&mut self, buffer: Entity<Buffer>, cx: &mut Context<Self>) {
-        self.track_buffer_internal(buffer, true, cx);
+        self.edit_count += 1;
+        let edit_count = self.edit_count;
+        self.track_buffer_internal(buffer, true, cx).last_edit = Some(edit_count);
     }
 
     /// Mark a buffer as edited by agent, so we can refresh it in the context
     pub fn buffer_edited(&mut self, buffer: Entity<Buffer>, cx: &mut Context<Self>) {
         let new_version = buffer.read(cx).version();
+        self.edit_count += 1;
+        let edit_count = self.edit_count;
         let tracked_buffer = self.track_buffer_internal(buffer, false, cx);
         if let TrackedBufferStatus::Deleted = tracked_buffer.status {
             tracked_buffer.status = TrackedBufferStatus::Modified;
         }
 
         tracked_buffer.version = new_version;
+        tracked_buffer.last_edit = Some(edit_count);
         tracked_buffer.schedule_diff_update(ChangeAuthor::Agent, cx);
     }
 
@@ -526,6 +535,7 @@ impl ActionLog {
             TrackedBufferStatus::Modified => {
                 buffer.update(cx, |buffer, cx| buffer.set_text("", cx));
                 tracked_buffer.status = TrackedBufferStatus::Deleted;
+                tracked_buffer.last_edit = None;
                 tracked_buffer.schedule_diff_update(ChangeAuthor::Agent, cx);
             }
             TrackedBufferStatus::Deleted => {}
@@ -905,6 +915,21 @@ impl ActionLog {
         })
     }
 
+    /// The number of times the agent has created or edited a buffer, to pass to
+    /// [`Self::buffers_edited_since`] later.
+    pub fn edit_count(&self) -> usize {
+        self.edit_count
+    }
+
+    /// Returns the buffers the agent created or edited after [`Self::edit_count`]
+    /// returned `edit_count`.
+    pub fn buffers_edited_since(&self, edit_count: usize) -> impl Iterator<Item = &Entity<Buffer>> {
+        self.tracked_buffers
+            .iter()
+            .filter(move |(_, tracked)| tracked.last_edit.is_some_and(|edit| edit > edit_count))
+            .map(|(buffer, _)| buffer)
+    }
+
     /// Returns the set of buffers that contain edits that haven't been reviewed by the user.
     pub fn changed_buffers(&self, cx: &App) -> BTreeMap<Entity<Buffer>, Entity<BufferDiff>> {
         self.tracked_buffers
@@ -1128,6 +1153,9 @@ pub struct TrackedBuffer {
     unreviewed_edits: Patch<u32>,
     status: TrackedBufferStatus,
     version: clock::Global,
+    /// The value of [`ActionLog::edit_count`] after the agent last created or edited
+    /// the buffer.
+    last_edit: Option<usize>,
     diff: Entity<BufferDiff>,
     snapshot: text::BufferSnapshot,
     diff_update: mpsc::UnboundedSender<(ChangeAuthor, text::BufferSnapshot)>,
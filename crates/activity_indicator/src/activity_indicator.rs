@@ -213,6 +213,9 @@ impl ActivityIndicator {
             )
             .detach();
 
+            cx.observe(project.read(cx).upload_store(), |_, _, cx| cx.notify())
+                .detach();
+
             Self {
                 statuses: Vec::new(),
                 project: project.clone(),
@@ -416,6 +419,33 @@ impl ActivityIndicator {
             });
         }
 
+        // Show any upload to a remote project
+        if let Some(upload) = self
+            .project
+            .read(cx)
+            .upload_store()
+            .read(cx)
+            .uploads()
+            .first()
+        {
+            let mut message = upload.label.to_string();
+            if upload.total_bytes > 0 {
+                let percentage = upload.uploaded_bytes * 100 / upload.total_bytes;
+                write!(&mut message, " ({}%)", percentage).unwrap();
+            }
+            return Some(Content {
+                icon: Some(
+                    Icon::new(IconName::ArrowCircle)
+                        .size(IconSize::Small)
+                        .with_rotate_animation(2)
+                        .into_any_element(),
+                ),
+                message,
+                on_click: None,
+                tooltip_message: None,
+            });
+        }
+
         let current_job = self
             .project
             .read(cx)
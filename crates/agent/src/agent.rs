@@ -1,5 +1,6 @@
 mod db;
 mod edit_agent;
+mod hooks;
 mod legacy_thread;
 mod native_agent_server;
 pub mod outline;
@@ -25,7 +26,7 @@ pub use tools::*;
 
 use acp_thread::{
     AcpThread, AgentModelSelector, AgentSessionInfo, AgentSessionList, AgentSessionListRequest,
-    AgentSessionListResponse, UserMessageId,
+    AgentSessionListResponse, AgentSessionSearchMatch, UserMessageId,
 };
 use agent_client_protocol as acp;
 use anyhow::{Context as _, Result, anyhow};
@@ -836,6 +837,47 @@ impl NativeAgent {
         })
     }
 
+    /// Saves the history of a thread before the given user message as a new thread,
+    /// so the conversation can diverge from there while the original is kept.
+    pub fn fork_thread(
+        &mut self,
+        id: acp::SessionId,
+        message_id: UserMessageId,
+        git_worktree_info: Option<AgentGitWorktreeInfo>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<AgentSessionInfo>> {
+        let db_thread = match self.sessions.get(&id) {
+            Some(session) => {
+                let db_thread = session.thread.read(cx).to_db(cx);
+                cx.background_spawn(async move { anyhow::Ok(Some(db_thread.await)) })
+            }
+            None => self
+                .thread_store
+                .update(cx, |store, cx| store.load_thread(id.clone(), cx)),
+        };
+        let thread_store = self.thread_store.clone();
+        cx.spawn(async move |_, cx| {
+            let db_thread = db_thread.await?.context("thread not found")?;
+            let mut fork = db_thread.fork(id, message_id)?;
+            if git_worktree_info.is_some() {
+                fork.git_worktree_info = git_worktree_info;
+            }
+
+            let fork_id = acp::SessionId::new(uuid::Uuid::new_v4().to_string());
+            let session_info = AgentSessionInfo {
+                session_id: fork_id.clone(),
+                cwd: None,
+                title: Some(fork.title.clone()),
+                updated_at: Some(fork.updated_at),
+                meta: None,
+            };
+            thread_store
+                .update(cx, |store, cx| store.save_thread(fork_id, fork, cx))
+                .await?;
+            Ok(session_info)
+        })
+    }
+
     fn save_thread(&mut self, thread: Entity<Thread>, cx: &mut Context<Self>) {
         if thread.read(cx).is_empty() {
             return;
@@ -1052,6 +1094,29 @@ impl NativeAgentConnection {
                                 })
                                 .detach();
                             }
+                            ThreadEvent::ToolCallElicitation(ToolCallElicitation {
+                                tool_call_id,
+                                elicitation,
+                                response,
+                            }) => {
+                                let result_task = acp_thread.update(cx, |thread, cx| {
+                                    thread.request_tool_call_input(tool_call_id, elicitation, cx)
+                                })?;
+                                match result_task {
+                                    Ok(result_task) => {
+                                        cx.background_spawn(async move {
+                                            response.send(result_task.await).ok();
+                                        })
+                                        .detach();
+                                    }
+                                    Err(error) => {
+                                        log::error!("Failed to request tool call input: {error:?}");
+                                        response
+                                            .send(context_server::types::ElicitResult::cancel())
+                                            .ok();
+                                    }
+                                }
+                            }
                             ThreadEvent::ToolCall(tool_call) => {
                                 acp_thread.update(cx, |thread, cx| {
                                     thread.upsert_tool_call(tool_call, cx)
@@ -1507,6 +1572,32 @@ impl AgentSessionList for NativeAgentSessionList {
             .update(cx, |store, cx| store.delete_threads(cx))
     }
 
+    fn supports_search(&self) -> bool {
+        true
+    }
+
+    fn search_sessions(
+        &self,
+        query: String,
+        cx: &mut App,
+    ) -> Task<Result<Vec<AgentSessionSearchMatch>>> {
+        let search = self
+            .thread_store
+            .update(cx, |store, cx| store.search_threads(query, cx));
+        cx.background_spawn(async move {
+            Ok(search
+                .await?
+                .into_iter()
+                .map(|search_match| AgentSessionSearchMatch {
+                    session_id: search_match.id,
+                    user_message_id: search_match.user_message_id,
+                    snippet: search_match.snippet.into(),
+                    highlights: search_match.highlights,
+                })
+                .collect())
+        })
+    }
+
     fn watch(
         &self,
         _cx: &mut App,
@@ -1710,12 +1801,13 @@ impl ThreadEnvironment for NativeThreadEnvironment {
     fn create_terminal(
         &self,
         command: String,
+        extra_env: Vec<acp::EnvVariable>,
         cwd: Option<PathBuf>,
         output_byte_limit: Option<u64>,
         cx: &mut AsyncApp,
     ) -> Task<Result<Rc<dyn TerminalHandle>>> {
         let task = self.acp_thread.update(cx, |thread, cx| {
-            thread.create_terminal(command, vec![], vec![], cwd, output_byte_limit, cx)
+            thread.create_terminal(command, vec![], extra_env, cwd, output_byte_limit, cx)
         });
 
         let acp_thread = self.acp_thread.clone();
@@ -8,6 +8,7 @@ use collections::{HashMap, IndexMap};
 use futures::{FutureExt, future::Shared};
 use gpui::{BackgroundExecutor, Global, Task};
 use indoc::indoc;
+use language_model::LanguageModelToolResultContent;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use sqlez::{
@@ -15,7 +16,7 @@ use sqlez::{
     connection::Connection,
     statement::Statement,
 };
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc};
 use ui::{App, SharedString};
 use zed_env_vars::ZED_STATELESS;
 
@@ -34,6 +35,15 @@ pub struct AgentGitWorktreeInfo {
     pub base_ref: String,
 }
 
+/// Metadata about the thread an agent thread was forked from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentThreadForkInfo {
+    /// The thread this one was forked from.
+    pub session_id: acp::SessionId,
+    /// The user message the fork diverges at. The fork contains the history before it.
+    pub message_id: UserMessageId,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DbThreadMetadata {
     pub id: acp::SessionId,
@@ -70,6 +80,8 @@ pub struct DbThread {
     pub subagent_context: Option<crate::SubagentContext>,
     #[serde(default)]
     pub git_worktree_info: Option<AgentGitWorktreeInfo>,
+    #[serde(default)]
+    pub forked_from: Option<AgentThreadForkInfo>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -109,6 +121,7 @@ impl SharedThread {
             imported: true,
             subagent_context: None,
             git_worktree_info: None,
+            forked_from: None,
         }
     }
 
@@ -284,6 +297,53 @@ impl DbThread {
             imported: false,
             subagent_context: None,
             git_worktree_info: None,
+            forked_from: None,
+        })
+    }
+
+    /// Returns a new thread with the history before the given user message, which can
+    /// diverge from this one.
+    pub fn fork(self, session_id: acp::SessionId, message_id: UserMessageId) -> Result<Self> {
+        let ix = self
+            .messages
+            .iter()
+            .position(|message| {
+                matches!(message, crate::Message::User(message) if message.id == message_id)
+            })
+            .ok_or_else(|| anyhow!("message not found"))?;
+
+        let mut messages = self.messages;
+        messages.truncate(ix);
+        let mut request_token_usage = self.request_token_usage;
+        request_token_usage.retain(|id, _| {
+            messages.iter().any(
+                |message| matches!(message, crate::Message::User(message) if &message.id == id),
+            )
+        });
+        // The fork only includes the requests made before the message it's forked from.
+        let cumulative_token_usage = request_token_usage
+            .values()
+            .fold(language_model::TokenUsage::default(), |total, usage| {
+                total + *usage
+            });
+
+        Ok(Self {
+            title: format!("{} (fork)", self.title).into(),
+            messages,
+            updated_at: Utc::now(),
+            detailed_summary: None,
+            initial_project_snapshot: self.initial_project_snapshot,
+            cumulative_token_usage,
+            request_token_usage,
+            model: self.model,
+            profile: self.profile,
+            imported: false,
+            subagent_context: None,
+            git_worktree_info: self.git_worktree_info,
+            forked_from: Some(AgentThreadForkInfo {
+                session_id,
+                message_id,
+            }),
         })
     }
 }
@@ -318,6 +378,24 @@ impl Column for DataType {
     }
 }
 
+/// A saved thread matching a full-text search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DbThreadSearchMatch {
+    pub id: acp::SessionId,
+    /// The user message that started the turn the match is in.
+    pub user_message_id: Option<UserMessageId>,
+    /// An excerpt of the matching text.
+    pub snippet: String,
+    /// The byte ranges of the matched terms in `snippet`.
+    pub highlights: Vec<Range<usize>>,
+}
+
+/// The maximum number of threads returned by a full-text search.
+const MAX_SEARCH_MATCHES: usize = 100;
+/// Marks the start and end of each matched term in the snippets returned by SQLite.
+const HIGHLIGHT_START: char = '\u{1}';
+const HIGHLIGHT_END: char = '\u{2}';
+
 pub(crate) struct ThreadsDatabase {
     executor: BackgroundExecutor,
     connection: Arc<Mutex<Connection>>,
@@ -395,6 +473,23 @@ impl ThreadsDatabase {
             s().ok();
         }
 
+        let search_index_exists = connection.select_row::<i64>(indoc! {"
+            SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'thread_search'
+        "})?()?
+        .is_some();
+        connection.exec(indoc! {"
+            CREATE VIRTUAL TABLE IF NOT EXISTS thread_search USING fts5(
+                thread_id UNINDEXED,
+                user_message_id UNINDEXED,
+                content,
+                tokenize = 'porter unicode61'
+            )
+        "})?()
+        .map_err(|e| anyhow!("Failed to create thread search table: {}", e))?;
+        if !search_index_exists {
+            Self::index_saved_threads(&connection)?;
+        }
+
         let db = Self {
             executor,
             connection: Arc::new(Mutex::new(connection)),
@@ -403,6 +498,47 @@ impl ThreadsDatabase {
         Ok(db)
     }
 
+    /// Indexes the threads saved before full-text search was introduced.
+    fn index_saved_threads(connection: &Connection) -> Result<()> {
+        let threads = connection.select::<(Arc<str>, DataType, Vec<u8>)>(indoc! {"
+            SELECT id, data_type, data FROM threads
+        "})?()?;
+        connection.with_savepoint("index_saved_threads", || {
+            for (id, data_type, data) in threads {
+                match decode_thread(data_type, data) {
+                    Ok(thread) => {
+                        Self::index_thread_sync(connection, &id, &search_index_rows(&thread))?
+                    }
+                    Err(error) => log::error!("Failed to index thread {id}: {error:#}"),
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn index_thread_sync(
+        connection: &Connection,
+        id: &Arc<str>,
+        rows: &[(Option<UserMessageId>, String)],
+    ) -> Result<()> {
+        let mut delete = connection.exec_bound::<Arc<str>>(indoc! {"
+            DELETE FROM thread_search WHERE thread_id = ?
+        "})?;
+        delete(id.clone())?;
+
+        let mut insert = connection.exec_bound::<(Arc<str>, Option<Arc<str>>, &str)>(indoc! {"
+            INSERT INTO thread_search (thread_id, user_message_id, content) VALUES (?, ?, ?)
+        "})?;
+        for (user_message_id, content) in rows {
+            insert((
+                id.clone(),
+                user_message_id.as_ref().map(|id| id.as_str().into()),
+                content.as_str(),
+            ))?;
+        }
+        Ok(())
+    }
+
     fn save_thread_sync(
         connection: &Arc<Mutex<Connection>>,
         id: acp::SessionId,
@@ -427,6 +563,7 @@ impl ThreadsDatabase {
             .git_worktree_info
             .as_ref()
             .map(|info| info.branch.clone());
+        let search_index_rows = search_index_rows(&thread);
         let json_data = serde_json::to_string(&SerializedThread {
             thread,
             version: DbThread::VERSION,
@@ -438,21 +575,23 @@ impl ThreadsDatabase {
         let data_type = DataType::Zstd;
         let data = compressed;
 
-        let mut insert = connection.exec_bound::<(Arc<str>, Option<Arc<str>>, Option<String>, String, String, DataType, Vec<u8>)>(indoc! {"
-            INSERT OR REPLACE INTO threads (id, parent_id, worktree_branch, summary, updated_at, data_type, data) VALUES (?, ?, ?, ?, ?, ?, ?)
-        "})?;
-
-        insert((
-            id.0,
-            parent_id,
-            worktree_branch,
-            title,
-            updated_at,
-            data_type,
-            data,
-        ))?;
+        connection.with_savepoint("save_thread", || {
+            let mut insert = connection.exec_bound::<(Arc<str>, Option<Arc<str>>, Option<String>, String, String, DataType, Vec<u8>)>(indoc! {"
+                INSERT OR REPLACE INTO threads (id, parent_id, worktree_branch, summary, updated_at, data_type, data) VALUES (?, ?, ?, ?, ?, ?, ?)
+            "})?;
 
-        Ok(())
+            insert((
+                id.0.clone(),
+                parent_id,
+                worktree_branch,
+                title,
+                updated_at,
+                data_type,
+                data,
+            ))?;
+
+            Self::index_thread_sync(&connection, &id.0, &search_index_rows)
+        })
     }
 
     pub fn list_threads(&self) -> Task<Result<Vec<DbThreadMetadata>>> {
@@ -494,21 +633,51 @@ impl ThreadsDatabase {
 
             let rows = select(id.0)?;
             if let Some((data_type, data)) = rows.into_iter().next() {
-                let json_data = match data_type {
-                    DataType::Zstd => {
-                        let decompressed = zstd::decode_all(&data[..])?;
-                        String::from_utf8(decompressed)?
-                    }
-                    DataType::Json => String::from_utf8(data)?,
-                };
-                let thread = DbThread::from_json(json_data.as_bytes())?;
-                Ok(Some(thread))
+                Ok(Some(decode_thread(data_type, data)?))
             } else {
                 Ok(None)
             }
         })
     }
 
+    /// Searches the text of the saved threads, returning the best match in each
+    /// matching thread, best matches first.
+    pub fn search_threads(&self, query: String) -> Task<Result<Vec<DbThreadSearchMatch>>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let Some(query) = fts_query(&query) else {
+                return Ok(Vec::new());
+            };
+            let connection = connection.lock();
+            let mut select = connection
+                .select_bound::<&str, (Arc<str>, Option<Arc<str>>, String)>(indoc! {"
+                SELECT thread_id, user_message_id, snippet(thread_search, 2, char(1), char(2), '…', 16)
+                FROM thread_search
+                WHERE thread_search MATCH ?
+                ORDER BY rank
+            "})?;
+
+            let mut matches = Vec::<DbThreadSearchMatch>::new();
+            for (id, user_message_id, snippet) in select(query.as_str())? {
+                if matches.iter().any(|m| *m.id.0 == *id) {
+                    continue;
+                }
+                let (snippet, highlights) = parse_snippet(&snippet);
+                matches.push(DbThreadSearchMatch {
+                    id: acp::SessionId::new(id),
+                    user_message_id: user_message_id.map(UserMessageId::from),
+                    snippet,
+                    highlights,
+                });
+                if matches.len() == MAX_SEARCH_MATCHES {
+                    break;
+                }
+            }
+            Ok(matches)
+        })
+    }
+
     pub fn save_thread(&self, id: acp::SessionId, thread: DbThread) -> Task<Result<()>> {
         let connection = self.connection.clone();
 
@@ -525,8 +694,12 @@ impl ThreadsDatabase {
             let mut delete = connection.exec_bound::<Arc<str>>(indoc! {"
                 DELETE FROM threads WHERE id = ?
             "})?;
+            let mut delete_search_index = connection.exec_bound::<Arc<str>>(indoc! {"
+                DELETE FROM thread_search WHERE thread_id = ?
+            "})?;
 
-            delete(id.0)?;
+            delete(id.0.clone())?;
+            delete_search_index(id.0)?;
 
             Ok(())
         })
@@ -541,20 +714,159 @@ impl ThreadsDatabase {
             let mut delete = connection.exec_bound::<()>(indoc! {"
                 DELETE FROM threads
             "})?;
+            let mut delete_search_index = connection.exec_bound::<()>(indoc! {"
+                DELETE FROM thread_search
+            "})?;
 
             delete(())?;
+            delete_search_index(())?;
 
             Ok(())
         })
     }
 }
 
+fn decode_thread(data_type: DataType, data: Vec<u8>) -> Result<DbThread> {
+    let json_data = match data_type {
+        DataType::Zstd => {
+            let decompressed = zstd::decode_all(&data[..])?;
+            String::from_utf8(decompressed)?
+        }
+        DataType::Json => String::from_utf8(data)?,
+    };
+    DbThread::from_json(json_data.as_bytes())
+}
+
+/// Returns the text to index for each message in the thread, along with the user message
+/// that started the message's turn.
+///
+/// This covers the text of the messages, the mentions in user messages, and the inputs
+/// and outputs of tool calls, which include the paths of the files the agent edited.
+fn search_index_rows(thread: &DbThread) -> Vec<(Option<UserMessageId>, String)> {
+    let mut rows = Vec::new();
+    let mut user_message_id = None;
+    for message in &thread.messages {
+        let mut content = String::new();
+        match message {
+            DbMessage::User(UserMessage {
+                id,
+                content: chunks,
+            }) => {
+                user_message_id = Some(id.clone());
+                for chunk in chunks {
+                    match chunk {
+                        UserMessageContent::Text(text) => push_line(&mut content, text),
+                        UserMessageContent::Mention { uri, .. } => {
+                            push_line(&mut content, &uri.as_link().to_string())
+                        }
+                        UserMessageContent::Image(_) => {}
+                    }
+                }
+            }
+            DbMessage::Agent(AgentMessage {
+                content: chunks,
+                tool_results,
+                ..
+            }) => {
+                for chunk in chunks {
+                    match chunk {
+                        AgentMessageContent::Text(text) => push_line(&mut content, text),
+                        AgentMessageContent::ToolUse(tool_use) => {
+                            push_line(&mut content, &tool_use.name);
+                            push_json_strings(&mut content, &tool_use.input);
+                        }
+                        AgentMessageContent::Thinking { .. }
+                        | AgentMessageContent::RedactedThinking(_) => {}
+                    }
+                }
+                for tool_result in tool_results.values() {
+                    if let LanguageModelToolResultContent::Text(text) = &tool_result.content {
+                        push_line(&mut content, text);
+                    }
+                }
+            }
+            DbMessage::Resume => {}
+        }
+        if !content.is_empty() {
+            rows.push((user_message_id.clone(), content));
+        }
+    }
+    rows
+}
+
+fn push_line(content: &mut String, line: &str) {
+    if line.trim().is_empty() {
+        return;
+    }
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    content.push_str(line);
+}
+
+fn push_json_strings(content: &mut String, value: &serde_json::Value) {
+    match value {
+        serde_json::Value::String(string) => push_line(content, string),
+        serde_json::Value::Array(values) => {
+            for value in values {
+                push_json_strings(content, value);
+            }
+        }
+        serde_json::Value::Object(values) => {
+            for value in values.values() {
+                push_json_strings(content, value);
+            }
+        }
+        serde_json::Value::Null | serde_json::Value::Bool(_) | serde_json::Value::Number(_) => {}
+    }
+}
+
+/// Converts a user's search query into an FTS5 query that matches threads containing
+/// every word, treating the last word as a prefix since it may still be being typed.
+fn fts_query(query: &str) -> Option<String> {
+    let terms = query
+        .split_whitespace()
+        .map(|term| term.replace('"', ""))
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{term}\""))
+        .collect::<Vec<_>>();
+    if terms.is_empty() {
+        return None;
+    }
+    Some(format!("{}*", terms.join(" ")))
+}
+
+/// Removes the highlight markers from a snippet, returning the marked ranges.
+fn parse_snippet(snippet: &str) -> (String, Vec<Range<usize>>) {
+    let mut text = String::with_capacity(snippet.len());
+    let mut highlights = Vec::new();
+    let mut highlight_start = None;
+    for char in snippet.chars() {
+        match char {
+            HIGHLIGHT_START => highlight_start = Some(text.len()),
+            HIGHLIGHT_END => {
+                if let Some(start) = highlight_start.take() {
+                    highlights.push(start..text.len());
+                }
+            }
+            '\n' | '\r' | '\t' => {
+                if !text.ends_with(' ') {
+                    text.push(' ');
+                }
+            }
+            char => text.push(char),
+        }
+    }
+    (text, highlights)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use chrono::{DateTime, TimeZone, Utc};
     use collections::HashMap;
     use gpui::TestAppContext;
+    use language_model::LanguageModelToolUse;
     use std::sync::Arc;
 
     #[test]
@@ -610,6 +922,7 @@ mod tests {
             imported: false,
             subagent_context: None,
             git_worktree_info: None,
+            forked_from: None,
         }
     }
 
@@ -843,4 +1156,165 @@ mod tests {
             "plain thread should have no worktree_branch"
         );
     }
+
+    #[gpui::test]
+    async fn test_search_threads(cx: &mut TestAppContext) {
+        let database = ThreadsDatabase::new(cx.executor()).unwrap();
+
+        let first_message_id = UserMessageId::new();
+        let second_message_id = UserMessageId::new();
+        let mut thread = make_thread(
+            "Refactoring",
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+        );
+        thread.messages = vec![
+            DbMessage::User(UserMessage {
+                id: first_message_id.clone(),
+                content: vec![UserMessageContent::Text(
+                    "Rename the parser module".to_string(),
+                )],
+            }),
+            DbMessage::Agent(AgentMessage {
+                content: vec![AgentMessageContent::Text("Done.".to_string())],
+                ..Default::default()
+            }),
+            DbMessage::User(UserMessage {
+                id: second_message_id.clone(),
+                content: vec![UserMessageContent::Text("Now fix the tests".to_string())],
+            }),
+            DbMessage::Agent(AgentMessage {
+                content: vec![AgentMessageContent::ToolUse(LanguageModelToolUse {
+                    id: "tool-1".into(),
+                    name: "edit_file".into(),
+                    raw_input: String::new(),
+                    input: serde_json::json!({
+                        "display_description": "Update assertions",
+                        "path": "project/src/tokenizer_tests.rs",
+                    }),
+                    is_input_complete: true,
+                    thought_signature: None,
+                })],
+                ..Default::default()
+            }),
+        ];
+        let thread_id = session_id("thread-a");
+        database
+            .save_thread(thread_id.clone(), thread)
+            .await
+            .unwrap();
+
+        let matches = database
+            .search_threads("parser module".to_string())
+            .await
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].id, thread_id);
+        assert_eq!(matches[0].user_message_id, Some(first_message_id));
+        assert_eq!(matches[0].snippet, "Rename the parser module");
+        assert_eq!(matches[0].highlights, vec![11..17, 18..24]);
+
+        // Tool inputs, including the paths of edited files, are indexed along with the
+        // turn they belong to.
+        let matches = database
+            .search_threads("tokenizer_tests".to_string())
+            .await
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].user_message_id, Some(second_message_id));
+
+        assert!(
+            database
+                .search_threads("lexer".to_string())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        database.delete_thread(thread_id).await.unwrap();
+        assert!(
+            database
+                .search_threads("parser".to_string())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_fork_thread() {
+        let first_message_id = UserMessageId::new();
+        let second_message_id = UserMessageId::new();
+        let mut thread = make_thread(
+            "Refactoring",
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+        );
+        thread.messages = vec![
+            DbMessage::User(UserMessage {
+                id: first_message_id.clone(),
+                content: vec![UserMessageContent::Text("Rename the parser".to_string())],
+            }),
+            DbMessage::Agent(AgentMessage {
+                content: vec![AgentMessageContent::Text("Done.".to_string())],
+                ..Default::default()
+            }),
+            DbMessage::User(UserMessage {
+                id: second_message_id.clone(),
+                content: vec![UserMessageContent::Text("Now fix the tests".to_string())],
+            }),
+        ];
+        let first_usage = language_model::TokenUsage {
+            input_tokens: 100,
+            output_tokens: 20,
+            ..Default::default()
+        };
+        let second_usage = language_model::TokenUsage {
+            input_tokens: 300,
+            output_tokens: 50,
+            ..Default::default()
+        };
+        thread.request_token_usage = HashMap::from_iter([
+            (first_message_id.clone(), first_usage),
+            (second_message_id.clone(), second_usage),
+        ]);
+        thread.cumulative_token_usage = first_usage + second_usage;
+
+        let fork = thread
+            .fork(session_id("thread-a"), second_message_id.clone())
+            .unwrap();
+        assert_eq!(fork.title, "Refactoring (fork)");
+        assert_eq!(fork.messages.len(), 2);
+        assert_eq!(
+            fork.request_token_usage.keys().collect::<Vec<_>>(),
+            vec![&first_message_id]
+        );
+        assert_eq!(fork.cumulative_token_usage, first_usage);
+        let forked_from = fork.forked_from.unwrap();
+        assert_eq!(forked_from.session_id, session_id("thread-a"));
+        assert_eq!(forked_from.message_id, second_message_id);
+
+        let thread = make_thread("Empty", Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+        assert!(
+            thread
+                .fork(session_id("thread-b"), first_message_id)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_fts_query() {
+        assert_eq!(fts_query("  "), None);
+        assert_eq!(fts_query("parser"), Some("\"parser\"*".to_string()));
+        assert_eq!(
+            fts_query("fix \"the\" tests"),
+            Some("\"fix\" \"the\" \"tests\"*".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_snippet() {
+        assert_eq!(
+            parse_snippet("…the \u{1}parser\u{2}\nmodule \u{1}tests\u{2}"),
+            ("…the parser module tests".to_string(), vec![7..13, 21..26])
+        );
+    }
 }
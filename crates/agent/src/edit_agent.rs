@@ -21,7 +21,8 @@ use gpui::{AppContext, AsyncApp, Entity, Task};
 use language::{Anchor, Buffer, BufferSnapshot, LineIndent, Point, TextBufferSnapshot};
 use language_model::{
     LanguageModel, LanguageModelCompletionError, LanguageModelRequest, LanguageModelRequestMessage,
-    LanguageModelToolChoice, MessageContent, Role,
+    LanguageModelToolChoice, LanguageModelUsageFeature, LanguageModelUsageTracker, MessageContent,
+    Role,
 };
 use project::{AgentLocation, Project};
 use schemars::JsonSchema;
@@ -735,7 +736,17 @@ impl EditAgent {
             thinking_effort: None,
         };
 
-        Ok(self.model.stream_completion_text(request, cx).await?.stream)
+        let usage_tracker = cx.update(|cx| {
+            LanguageModelUsageTracker::new(
+                self.model.provider_id(),
+                self.model.id(),
+                LanguageModelUsageFeature::Agent,
+                self.project.read(cx).display_name(cx),
+                cx,
+            )
+        });
+        let stream = self.model.stream_completion_text(request, cx).await?;
+        Ok(usage_tracker.track_text(stream).stream)
     }
 }
 
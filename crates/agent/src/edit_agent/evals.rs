@@ -14,6 +14,7 @@ use indoc::{formatdoc, indoc};
 use language_model::{
     LanguageModelRegistry, LanguageModelToolResult, LanguageModelToolResultContent,
     LanguageModelToolUse, LanguageModelToolUseId, SelectedModel,
+    replay_provider::LanguageModelRecordings,
 };
 use project::Project;
 use prompt_store::{ProjectContext, WorktreeContext};
@@ -1447,16 +1448,36 @@ impl EditAgentTest {
                     .collect::<Vec<_>>()
             })
         });
-        let (agent_model, judge_model) = cx
-            .update(|cx| {
+        // Completions can be recorded to a directory with `ZED_EVAL_RECORD`, and replayed
+        // from it with `ZED_EVAL_REPLAY` to run the evals offline.
+        let (agent_model, judge_model) = if let Ok(dir) = std::env::var("ZED_EVAL_REPLAY") {
+            let recordings = LanguageModelRecordings::new(dir);
+            (
+                recordings
+                    .replay(&agent_model.provider, &agent_model.model)
+                    .unwrap(),
+                recordings
+                    .replay(&judge_model.provider, &judge_model.model)
+                    .unwrap(),
+            )
+        } else {
+            let recordings = std::env::var("ZED_EVAL_RECORD")
+                .ok()
+                .map(LanguageModelRecordings::new);
+            cx.update(|cx| {
                 cx.spawn(async move |cx| {
                     futures::future::join_all(authenticate_provider_tasks).await;
-                    let agent_model = Self::load_model(&agent_model, cx).await;
-                    let judge_model = Self::load_model(&judge_model, cx).await;
-                    (agent_model.unwrap(), judge_model.unwrap())
+                    let mut agent_model = Self::load_model(&agent_model, cx).await.unwrap();
+                    let mut judge_model = Self::load_model(&judge_model, cx).await.unwrap();
+                    if let Some(recordings) = recordings {
+                        agent_model = recordings.record(agent_model).unwrap();
+                        judge_model = recordings.record(judge_model).unwrap();
+                    }
+                    (agent_model, judge_model)
                 })
             })
-            .await;
+            .await
+        };
         let action_log = cx.new(|_| ActionLog::new(project.clone()));
 
         let edit_format = EditFormat::from_env(agent_model.clone()).unwrap();
@@ -0,0 +1,360 @@
+//! Runs the shell commands configured in the agent's `hooks` setting.
+//!
+//! Each hook receives a JSON payload describing the event on stdin, and can
+//! print a JSON response to stdout to block a tool call, rewrite its input, or
+//! give the model feedback.
+
+use agent_settings::AgentHook;
+use anyhow::{Result, anyhow};
+use futures::{AsyncWriteExt as _, FutureExt as _};
+use gpui::BackgroundExecutor;
+use language_model::LanguageModelToolResultContent;
+use serde::Deserialize;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use util::{
+    command::{Stdio, new_command},
+    shell::ShellKind,
+};
+
+use crate::AgentToolOutput;
+
+/// Describes the thread that triggered a hook.
+#[derive(Clone, Debug)]
+pub(crate) struct HookContext {
+    pub session_id: String,
+    /// The root of the project's first worktree, which hooks are run in.
+    pub cwd: Option<PathBuf>,
+}
+
+impl HookContext {
+    fn payload(&self, event: &str) -> serde_json::Value {
+        json!({
+            "event": event,
+            "session_id": self.session_id,
+            "cwd": self.cwd,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum HookOutcome {
+    Continue {
+        input: Option<serde_json::Value>,
+        message: Option<String>,
+    },
+    Block {
+        reason: String,
+    },
+    /// The hook exited with a non-zero status, couldn't be run or timed out.
+    Failed {
+        error: String,
+    },
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct HookResponse {
+    decision: Option<HookDecision>,
+    reason: Option<String>,
+    input: Option<serde_json::Value>,
+    message: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum HookDecision {
+    Allow,
+    Block,
+}
+
+/// Runs the hooks before a tool call, in order, each seeing the input as rewritten by
+/// the ones before it. Returns the input to call the tool with, or why the call was
+/// blocked.
+pub(crate) async fn run_pre_tool_call(
+    hooks: &[AgentHook],
+    context: &HookContext,
+    tool_name: &str,
+    mut input: serde_json::Value,
+    executor: &BackgroundExecutor,
+) -> Result<serde_json::Value, String> {
+    for hook in hooks {
+        if let Some(invalid) = &hook.invalid_matcher {
+            return Err(format!(
+                "Hook `{}` has an invalid matcher '{}': {}. Fix it in settings to allow tool calls.",
+                hook.command, invalid.pattern, invalid.error
+            ));
+        }
+        if !hook.matches(tool_name) {
+            continue;
+        }
+
+        let mut payload = context.payload("pre_tool_call");
+        payload["tool_name"] = tool_name.into();
+        payload["tool_input"] = input.clone();
+        match run_hook(hook, &payload, context.cwd.as_deref(), executor).await {
+            HookOutcome::Continue {
+                input: new_input, ..
+            } => {
+                if let Some(new_input) = new_input {
+                    input = new_input;
+                }
+            }
+            HookOutcome::Block { reason } | HookOutcome::Failed { error: reason } => {
+                return Err(reason);
+            }
+        }
+    }
+    Ok(input)
+}
+
+/// Runs the hooks after a tool call, returning their feedback for the model.
+pub(crate) async fn run_post_tool_call(
+    hooks: &[AgentHook],
+    context: &HookContext,
+    tool_name: &str,
+    input: &serde_json::Value,
+    output: &AgentToolOutput,
+    is_error: bool,
+    executor: &BackgroundExecutor,
+) -> Vec<String> {
+    let mut feedback = Vec::new();
+    for hook in hooks.iter().filter(|hook| hook.matches(tool_name)) {
+        let mut payload = context.payload("post_tool_call");
+        payload["tool_name"] = tool_name.into();
+        payload["tool_input"] = input.clone();
+        payload["tool_output"] = output.raw_output.clone();
+        payload["is_error"] = is_error.into();
+        let outcome = run_hook(hook, &payload, context.cwd.as_deref(), executor).await;
+        feedback.extend(feedback_for_outcome(hook, outcome));
+    }
+    feedback
+}
+
+/// Runs the hooks after the agent edits a file, returning their feedback for the model.
+pub(crate) async fn run_post_edit(
+    hooks: &[AgentHook],
+    context: &HookContext,
+    tool_name: &str,
+    abs_path: &Path,
+    executor: &BackgroundExecutor,
+) -> Vec<String> {
+    let mut feedback = Vec::new();
+    let path = abs_path.to_string_lossy();
+    for hook in hooks.iter().filter(|hook| hook.matches(&path)) {
+        let mut payload = context.payload("post_edit");
+        payload["tool_name"] = tool_name.into();
+        payload["path"] = path.as_ref().into();
+        let outcome = run_hook(hook, &payload, context.cwd.as_deref(), executor).await;
+        feedback.extend(feedback_for_outcome(hook, outcome));
+    }
+    feedback
+}
+
+/// Runs the hooks at the end of a turn, returning the reasons of those that blocked the
+/// turn from ending, for the model to address. Hooks that fail are only logged, so that a
+/// broken hook doesn't send the model back to work.
+pub(crate) async fn run_turn_complete(
+    hooks: &[AgentHook],
+    context: &HookContext,
+    title: &str,
+    executor: &BackgroundExecutor,
+) -> Vec<String> {
+    let mut feedback = Vec::new();
+    for hook in hooks.iter().filter(|hook| hook.invalid_matcher.is_none()) {
+        let mut payload = context.payload("turn_complete");
+        payload["title"] = title.into();
+        match run_hook(hook, &payload, context.cwd.as_deref(), executor).await {
+            HookOutcome::Block { reason } => {
+                feedback.push(format!("Hook `{}`: {reason}", hook.command));
+            }
+            HookOutcome::Continue { message, .. } => {
+                if let Some(message) = message {
+                    log::info!("Hook `{}`: {message}", hook.command);
+                }
+            }
+            HookOutcome::Failed { error } => log::error!("{error}"),
+        }
+    }
+    feedback
+}
+
+/// Adds feedback from hooks to the end of a tool's output.
+pub(crate) fn append_feedback(mut output: AgentToolOutput, feedback: &[String]) -> AgentToolOutput {
+    match &output.llm_output {
+        LanguageModelToolResultContent::Text(text) => {
+            let mut text = text.to_string();
+            for message in feedback {
+                text.push_str("\n\n");
+                text.push_str(message);
+            }
+            output.llm_output = LanguageModelToolResultContent::Text(text.into());
+        }
+        LanguageModelToolResultContent::Image(_) => {
+            log::warn!("Dropping hook feedback for a tool that returned an image");
+        }
+    }
+    output
+}
+
+fn feedback_for_outcome(hook: &AgentHook, outcome: HookOutcome) -> Option<String> {
+    match outcome {
+        HookOutcome::Continue { message, .. } => {
+            message.map(|message| format!("Hook `{}`: {message}", hook.command))
+        }
+        HookOutcome::Block { reason } | HookOutcome::Failed { error: reason } => Some(reason),
+    }
+}
+
+async fn run_hook(
+    hook: &AgentHook,
+    payload: &serde_json::Value,
+    cwd: Option<&Path>,
+    executor: &BackgroundExecutor,
+) -> HookOutcome {
+    let output = futures::select_biased! {
+        output = run_command(&hook.command, payload, cwd).fuse() => output,
+        _ = executor.timer(hook.timeout).fuse() => Err(anyhow!(
+            "timed out after {} seconds",
+            hook.timeout.as_secs()
+        )),
+    };
+
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_response(&String::from_utf8_lossy(&output.stdout))
+        }
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let details = if stderr.trim().is_empty() {
+                String::from_utf8_lossy(&output.stdout)
+            } else {
+                stderr
+            };
+            HookOutcome::Failed {
+                error: format!(
+                    "Hook `{}` failed with {}: {}",
+                    hook.command,
+                    output.status,
+                    details.trim()
+                ),
+            }
+        }
+        Err(error) => HookOutcome::Failed {
+            error: format!("Hook `{}` failed: {error}", hook.command),
+        },
+    }
+}
+
+async fn run_command(
+    command: &str,
+    payload: &serde_json::Value,
+    cwd: Option<&Path>,
+) -> Result<std::process::Output> {
+    let shell = util::get_system_shell();
+    let args = ShellKind::new(&shell, cfg!(windows)).args_for_shell(false, command.to_string());
+    let mut command = new_command(&shell);
+    command
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(cwd) = cwd {
+        command.current_dir(cwd);
+    }
+
+    let input = serde_json::to_vec(payload)?;
+    let mut child = command.spawn()?;
+    // The input is written while the output is read, so that hooks writing a lot
+    // before reading their input don't block on a full pipe.
+    let write_input = child.stdin.take().map(|mut stdin| async move {
+        // Hooks don't have to read their input, in which case writing it fails.
+        stdin.write_all(&input).await.ok();
+        stdin.close().await.ok();
+    });
+    let (output, _) = futures::join!(
+        child.output(),
+        futures::future::OptionFuture::from(write_input)
+    );
+    Ok(output?)
+}
+
+fn parse_response(stdout: &str) -> HookOutcome {
+    let stdout = stdout.trim();
+    if stdout.is_empty() {
+        return HookOutcome::Continue {
+            input: None,
+            message: None,
+        };
+    }
+
+    match serde_json::from_str::<HookResponse>(stdout) {
+        Ok(HookResponse {
+            decision: Some(HookDecision::Block),
+            reason,
+            ..
+        }) => HookOutcome::Block {
+            reason: reason.unwrap_or_else(|| "Blocked by a hook".to_string()),
+        },
+        Ok(HookResponse { input, message, .. }) => HookOutcome::Continue { input, message },
+        Err(_) => HookOutcome::Continue {
+            input: None,
+            message: Some(stdout.to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_run_command_with_large_input_and_output() {
+        let payload = json!({ "tool_input": "a".repeat(1 << 20) });
+        let output = smol::block_on(run_command(
+            "head -c 1048576 /dev/zero; wc -c",
+            &payload,
+            None,
+        ))
+        .unwrap();
+        assert!(output.status.success());
+        let input_len = String::from_utf8_lossy(&output.stdout[1 << 20..])
+            .trim()
+            .parse::<usize>()
+            .unwrap();
+        assert_eq!(input_len, serde_json::to_vec(&payload).unwrap().len());
+    }
+
+    #[test]
+    fn test_parse_response() {
+        assert_eq!(
+            parse_response("  \n"),
+            HookOutcome::Continue {
+                input: None,
+                message: None
+            }
+        );
+        assert_eq!(
+            parse_response(r#"{"decision": "block", "reason": "generated file"}"#),
+            HookOutcome::Block {
+                reason: "generated file".into()
+            }
+        );
+        assert_eq!(
+            parse_response(r#"{"decision": "allow", "input": {"path": "a.rs"}}"#),
+            HookOutcome::Continue {
+                input: Some(json!({"path": "a.rs"})),
+                message: None
+            }
+        );
+        assert_eq!(
+            parse_response("Formatted 1 file\n"),
+            HookOutcome::Continue {
+                input: None,
+                message: Some("Formatted 1 file".into())
+            }
+        );
+    }
+}
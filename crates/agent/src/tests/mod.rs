@@ -21,7 +21,7 @@ use futures::{
     future::{Fuse, Shared},
 };
 use gpui::{
-    App, AppContext, AsyncApp, Entity, Task, TestAppContext, UpdateGlobal,
+    App, AppContext, AsyncApp, Entity, Task, TestAppContext, UpdateGlobal, WeakEntity,
     http_client::FakeHttpClient,
 };
 use indoc::indoc;
@@ -176,6 +176,7 @@ impl SubagentHandle for FakeSubagentHandle {
 struct FakeThreadEnvironment {
     terminal_handle: Option<Rc<FakeTerminalHandle>>,
     subagent_handle: Option<Rc<FakeSubagentHandle>>,
+    terminal_commands: std::cell::RefCell<Vec<String>>,
 }
 
 impl FakeThreadEnvironment {
@@ -190,11 +191,13 @@ impl FakeThreadEnvironment {
 impl crate::ThreadEnvironment for FakeThreadEnvironment {
     fn create_terminal(
         &self,
-        _command: String,
+        command: String,
+        _extra_env: Vec<acp::EnvVariable>,
         _cwd: Option<std::path::PathBuf>,
         _output_byte_limit: Option<u64>,
         _cx: &mut AsyncApp,
     ) -> Task<Result<Rc<dyn crate::TerminalHandle>>> {
+        self.terminal_commands.borrow_mut().push(command);
         let handle = self
             .terminal_handle
             .clone()
@@ -239,6 +242,7 @@ impl crate::ThreadEnvironment for MultiTerminalEnvironment {
     fn create_terminal(
         &self,
         _command: String,
+        _extra_env: Vec<acp::EnvVariable>,
         _cwd: Option<std::path::PathBuf>,
         _output_byte_limit: Option<u64>,
         cx: &mut AsyncApp,
@@ -358,6 +362,201 @@ async fn test_terminal_tool_timeout_kills_handle(cx: &mut TestAppContext) {
     }
 }
 
+#[gpui::test]
+#[cfg(not(target_os = "windows"))]
+async fn test_command_tool(cx: &mut TestAppContext) {
+    init_test(cx);
+    always_allow_tools(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/root"), json!({})).await;
+    let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+
+    let environment = Rc::new(cx.update(|cx| {
+        FakeThreadEnvironment::default()
+            .with_terminal(FakeTerminalHandle::new_with_immediate_exit(cx, 0))
+    }));
+    let definition = serde_json::from_value::<settings::CommandToolContent>(json!({
+        "description": "Runs a package's tests",
+        "command": "cargo test -p {package}",
+    }))
+    .unwrap();
+    #[allow(clippy::arc_with_non_send_sync)]
+    let tool = Arc::new(CommandTool::new(
+        "test_package".into(),
+        definition,
+        project.clone(),
+        environment.clone(),
+    ));
+
+    let (event_stream, _rx) = crate::ToolCallEventStream::test();
+    let output = cx
+        .update(|cx| {
+            tool.clone()
+                .run(json!({ "package": "my crate" }), event_stream, cx)
+        })
+        .await
+        .unwrap_or_else(|output| panic!("unexpected error: {:?}", output.raw_output));
+    assert_eq!(
+        environment.terminal_commands.borrow().as_slice(),
+        ["cargo test -p 'my crate'"]
+    );
+    assert!(
+        output
+            .raw_output
+            .as_str()
+            .is_some_and(|output| output.contains("command output")),
+        "unexpected output: {:?}",
+        output.raw_output
+    );
+
+    // With the sandbox enabled, the command runs inside it, just like terminal commands.
+    cx.update(|cx| {
+        let mut settings = agent_settings::AgentSettings::get_global(cx).clone();
+        settings.terminal_sandbox.enabled = true;
+        agent_settings::AgentSettings::override_global(settings, cx);
+    });
+    let (event_stream, _rx) = crate::ToolCallEventStream::test();
+    let result = cx
+        .update(|cx| {
+            tool.clone()
+                .run(json!({ "package": "my crate" }), event_stream, cx)
+        })
+        .await;
+    if sandbox::is_supported() {
+        assert!(result.is_ok());
+        let commands = environment.terminal_commands.borrow();
+        assert_eq!(commands.len(), 2);
+        assert!(
+            commands[1].contains("--sandbox-policy"),
+            "command wasn't sandboxed: {}",
+            commands[1]
+        );
+    } else {
+        assert!(result.is_err());
+        assert_eq!(environment.terminal_commands.borrow().len(), 1);
+    }
+
+    // Templates that quote placeholders themselves are rejected before anything runs.
+    let definition = serde_json::from_value::<settings::CommandToolContent>(json!({
+        "description": "Searches the project",
+        "command": "grep -r '{pattern}' .",
+    }))
+    .unwrap();
+    #[allow(clippy::arc_with_non_send_sync)]
+    let tool = Arc::new(CommandTool::new(
+        "search".into(),
+        definition,
+        project,
+        environment.clone(),
+    ));
+    let (event_stream, _rx) = crate::ToolCallEventStream::test();
+    let result = cx
+        .update(|cx| tool.run(json!({ "pattern": "fn main" }), event_stream, cx))
+        .await;
+    assert!(result.is_err());
+    let commands_run = if sandbox::is_supported() { 2 } else { 1 };
+    assert_eq!(environment.terminal_commands.borrow().len(), commands_run);
+}
+
+#[gpui::test]
+#[cfg(not(target_os = "windows"))]
+async fn test_list_and_run_tasks(cx: &mut TestAppContext) {
+    init_test(cx);
+    always_allow_tools(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/root"),
+        json!({
+            ".zed": {
+                "tasks.json": r#"[
+                    {
+                        "label": "test crate",
+                        "command": "cargo",
+                        "args": ["test", "my crate"]
+                    },
+                    {
+                        "label": "build in sh",
+                        "command": "cargo",
+                        "args": ["build"],
+                        "shell": { "program": "sh" }
+                    }
+                ]"#,
+            },
+        }),
+    )
+    .await;
+    let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+    cx.run_until_parked();
+
+    let tool = Arc::new(crate::ListTasksTool::new(project.clone()));
+    let (event_stream, _rx) = crate::ToolCallEventStream::test();
+    let output = cx
+        .update(|cx| tool.run(crate::ListTasksToolInput { path: None }, event_stream, cx))
+        .await
+        .unwrap();
+    assert!(
+        output.contains("- `test crate` (project tasks file)")
+            && output.contains("- `build in sh` (project tasks file)"),
+        "unexpected output: {output}"
+    );
+
+    let environment = Rc::new(cx.update(|cx| {
+        FakeThreadEnvironment::default()
+            .with_terminal(FakeTerminalHandle::new_with_immediate_exit(cx, 0))
+    }));
+    #[allow(clippy::arc_with_non_send_sync)]
+    let tool = Arc::new(crate::RunTaskTool::new(
+        project,
+        environment.clone(),
+        WeakEntity::new_invalid(),
+    ));
+    for label in ["test crate", "build in sh"] {
+        let (event_stream, _rx) = crate::ToolCallEventStream::test();
+        let output = cx
+            .update(|cx| {
+                tool.clone().run(
+                    crate::RunTaskToolInput {
+                        label: label.to_string(),
+                        path: None,
+                        timeout_ms: None,
+                    },
+                    event_stream,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert!(
+            output.contains("command output"),
+            "unexpected output: {output}"
+        );
+    }
+    // Tasks that configure a shell run through it.
+    assert_eq!(
+        environment.terminal_commands.borrow().as_slice(),
+        ["cargo test 'my crate'", "sh -c 'cargo build'"]
+    );
+
+    let (event_stream, _rx) = crate::ToolCallEventStream::test();
+    let result = cx
+        .update(|cx| {
+            tool.run(
+                crate::RunTaskToolInput {
+                    label: "missing".to_string(),
+                    path: None,
+                    timeout_ms: None,
+                },
+                event_stream,
+                cx,
+            )
+        })
+        .await;
+    assert!(result.is_err());
+    assert_eq!(environment.terminal_commands.borrow().len(), 2);
+}
+
 #[gpui::test]
 #[ignore]
 async fn test_terminal_tool_without_timeout_does_not_kill_handle(cx: &mut TestAppContext) {
@@ -1267,6 +1466,7 @@ async fn test_profiles(cx: &mut TestAppContext) {
                         "name": "Test Profile 1",
                         "tools": {
                             EchoTool::NAME: true,
+                            TouchTool::NAME: true,
                             DelayTool::NAME: true,
                         }
                     },
@@ -1490,6 +1690,302 @@ async fn test_mcp_tools(cx: &mut TestAppContext) {
     events.collect::<Vec<_>>().await;
 }
 
+#[gpui::test]
+async fn test_mcp_tool_elicitation(cx: &mut TestAppContext) {
+    let ThreadTest {
+        model,
+        thread,
+        context_server_store,
+        fs,
+        ..
+    } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    fs.insert_file(
+        paths::settings_file(),
+        json!({
+            "agent": {
+                "always_allow_tool_actions": true,
+                "profiles": {
+                    "test": {
+                        "name": "Test Profile",
+                        "enable_all_context_servers": true,
+                        "tools": {}
+                    },
+                }
+            }
+        })
+        .to_string()
+        .into_bytes(),
+    )
+    .await;
+    cx.run_until_parked();
+    thread.update(cx, |thread, cx| {
+        thread.set_profile(AgentProfileId("test".into()), cx)
+    });
+
+    let (fake_transport, mut mcp_tool_calls) = fake_context_server_transport(
+        "deploy_server",
+        vec![context_server::types::Tool {
+            name: "deploy".into(),
+            description: Some("Deploy the project".into()),
+            input_schema: json!({"type": "object", "properties": {}}),
+            output_schema: None,
+            annotations: None,
+        }],
+        cx,
+    );
+    let server_requests = fake_transport.server_requests();
+    start_context_server("deploy_server", fake_transport, &context_server_store, cx);
+
+    let mut events = thread.update(cx, |thread, cx| {
+        thread.send(UserMessageId::new(), ["Deploy"], cx).unwrap()
+    });
+    cx.run_until_parked();
+
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "tool_1".into(),
+            name: "deploy".into(),
+            raw_input: json!({}).to_string(),
+            input: json!({}),
+            is_input_complete: true,
+            thought_signature: None,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // While the tool call is pending, the server asks the user for input.
+    let (_, tool_call_response) = mcp_tool_calls.next().await.unwrap();
+    let elicitation_response = cx.background_spawn(
+        server_requests.request::<context_server::types::requests::ElicitationCreate>(
+            serde_json::from_value(json!({
+                "message": "Which environment?",
+                "requestedSchema": {
+                    "type": "object",
+                    "properties": {
+                        "environment": {
+                            "type": "string",
+                            "enum": ["staging", "production"]
+                        }
+                    },
+                    "required": ["environment"]
+                }
+            }))
+            .unwrap(),
+        ),
+    );
+
+    let elicitation = loop {
+        if let ThreadEvent::ToolCallElicitation(elicitation) = events.next().await.unwrap().unwrap()
+        {
+            break elicitation;
+        }
+    };
+    assert_eq!(elicitation.tool_call_id.to_string(), "tool_1");
+    assert_eq!(elicitation.elicitation.server_name(), "deploy_server");
+    assert_eq!(elicitation.elicitation.message(), "Which environment?");
+    assert!(elicitation.elicitation.schema().is_required("environment"));
+
+    let mut content = serde_json::Map::new();
+    content.insert("environment".into(), json!("staging"));
+    elicitation
+        .response
+        .send(context_server::types::ElicitResult::accept(content.clone()))
+        .unwrap();
+    let result = elicitation_response.await.unwrap();
+    assert_eq!(
+        result.action,
+        context_server::types::ElicitationAction::Accept
+    );
+    assert_eq!(result.content, Some(content));
+
+    tool_call_response
+        .send(context_server::types::CallToolResponse {
+            content: vec![context_server::types::ToolResponseContent::Text {
+                text: "Deployed to staging".into(),
+            }],
+            is_error: None,
+            meta: None,
+            structured_content: None,
+        })
+        .unwrap();
+    cx.run_until_parked();
+
+    fake_model.send_last_completion_stream_text_chunk("Done!");
+    fake_model.end_last_completion_stream();
+    events.collect::<Vec<_>>().await;
+}
+
+#[gpui::test]
+async fn test_mcp_tool_elicitation_with_concurrent_calls(cx: &mut TestAppContext) {
+    let ThreadTest {
+        model,
+        thread,
+        context_server_store,
+        fs,
+        ..
+    } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    fs.insert_file(
+        paths::settings_file(),
+        json!({
+            "agent": {
+                "always_allow_tool_actions": true,
+                "profiles": {
+                    "test": {
+                        "name": "Test Profile",
+                        "enable_all_context_servers": true,
+                        "tools": {}
+                    },
+                }
+            }
+        })
+        .to_string()
+        .into_bytes(),
+    )
+    .await;
+    cx.run_until_parked();
+    thread.update(cx, |thread, cx| {
+        thread.set_profile(AgentProfileId("test".into()), cx)
+    });
+
+    let (fake_transport, mut mcp_tool_calls) = fake_context_server_transport(
+        "deploy_server",
+        vec![context_server::types::Tool {
+            name: "deploy".into(),
+            description: Some("Deploy the project".into()),
+            input_schema: json!({"type": "object", "properties": {}}),
+            output_schema: None,
+            annotations: None,
+        }],
+        cx,
+    );
+    let server_requests = fake_transport.server_requests();
+    start_context_server("deploy_server", fake_transport, &context_server_store, cx);
+
+    let mut events = thread.update(cx, |thread, cx| {
+        thread
+            .send(UserMessageId::new(), ["Deploy twice"], cx)
+            .unwrap()
+    });
+    cx.run_until_parked();
+
+    for id in ["tool_1", "tool_2"] {
+        fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+            LanguageModelToolUse {
+                id: id.into(),
+                name: "deploy".into(),
+                raw_input: json!({}).to_string(),
+                input: json!({}),
+                is_input_complete: true,
+                thought_signature: None,
+            },
+        ));
+    }
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let mut tool_call_responses = Vec::new();
+    let mut progress_tokens = Vec::new();
+    for _ in 0..2 {
+        let (params, response) = mcp_tool_calls.next().await.unwrap();
+        progress_tokens.push(params.meta.unwrap()["progressToken"].clone());
+        tool_call_responses.push(response);
+    }
+    progress_tokens.sort_by_key(|token| token.to_string());
+    assert_eq!(progress_tokens, [json!("tool_1"), json!("tool_2")]);
+
+    // Without a progress token, the client can't tell which call the request is for.
+    let unattributed = server_requests
+        .request::<context_server::types::requests::ElicitationCreate>(
+            serde_json::from_value(json!({
+                "message": "Which environment?",
+                "requestedSchema": {"type": "object", "properties": {}}
+            }))
+            .unwrap(),
+        )
+        .await;
+    assert!(unattributed.is_err());
+
+    let elicitation_response = cx.background_spawn(
+        server_requests.request::<context_server::types::requests::ElicitationCreate>(
+            serde_json::from_value(json!({
+                "message": "Which environment?",
+                "requestedSchema": {"type": "object", "properties": {}},
+                "_meta": {"progressToken": "tool_2"}
+            }))
+            .unwrap(),
+        ),
+    );
+    let elicitation = loop {
+        if let ThreadEvent::ToolCallElicitation(elicitation) = events.next().await.unwrap().unwrap()
+        {
+            break elicitation;
+        }
+    };
+    assert_eq!(elicitation.tool_call_id.to_string(), "tool_2");
+    elicitation
+        .response
+        .send(context_server::types::ElicitResult::decline())
+        .unwrap();
+    assert_eq!(
+        elicitation_response.await.unwrap().action,
+        context_server::types::ElicitationAction::Decline
+    );
+
+    for response in tool_call_responses {
+        response
+            .send(context_server::types::CallToolResponse {
+                content: vec![context_server::types::ToolResponseContent::Text {
+                    text: "Deployed".into(),
+                }],
+                is_error: None,
+                meta: None,
+                structured_content: None,
+            })
+            .unwrap();
+    }
+    cx.run_until_parked();
+
+    fake_model.send_last_completion_stream_text_chunk("Done!");
+    fake_model.end_last_completion_stream();
+    events.collect::<Vec<_>>().await;
+}
+
+#[gpui::test]
+async fn test_tool_call_elicitation_declined(cx: &mut TestAppContext) {
+    let (event_stream, mut rx) = crate::ToolCallEventStream::test();
+    let request = serde_json::from_value(json!({
+        "message": "Which environment?",
+        "requestedSchema": {
+            "type": "object",
+            "properties": {
+                "environment": { "type": "string" }
+            }
+        }
+    }))
+    .unwrap();
+    let response = cx.background_spawn(event_stream.elicit("deploy_server".into(), request));
+
+    let elicitation = rx.expect_elicitation().await;
+    assert_eq!(elicitation.elicitation.server_name(), "deploy_server");
+    assert_eq!(elicitation.elicitation.message(), "Which environment?");
+    elicitation
+        .response
+        .send(context_server::types::ElicitResult::decline())
+        .unwrap();
+
+    let result = response.await.unwrap();
+    assert_eq!(
+        result.action,
+        context_server::types::ElicitationAction::Decline
+    );
+    assert_eq!(result.content, None);
+}
+
 #[gpui::test]
 async fn test_mcp_tool_result_displayed_when_server_disconnected(cx: &mut TestAppContext) {
     let ThreadTest {
@@ -3706,6 +4202,17 @@ fn setup_context_server(
     context_server::types::CallToolParams,
     oneshot::Sender<context_server::types::CallToolResponse>,
 )> {
+    let (fake_transport, mcp_tool_calls_rx) = fake_context_server_transport(name, tools, cx);
+    start_context_server(name, fake_transport, context_server_store, cx);
+    mcp_tool_calls_rx
+}
+
+fn start_context_server(
+    name: &'static str,
+    fake_transport: context_server::test::FakeTransport,
+    context_server_store: &Entity<ContextServerStore>,
+    cx: &mut TestAppContext,
+) {
     cx.update(|cx| {
         let mut settings = ProjectSettings::get_global(cx).clone();
         settings.context_servers.insert(
@@ -3724,6 +4231,29 @@ fn setup_context_server(
         ProjectSettings::override_global(settings, cx);
     });
 
+    context_server_store.update(cx, |store, cx| {
+        store.start_server(
+            Arc::new(ContextServer::new(
+                ContextServerId(name.into()),
+                Arc::new(fake_transport),
+            )),
+            cx,
+        );
+    });
+    cx.run_until_parked();
+}
+
+fn fake_context_server_transport(
+    name: &'static str,
+    tools: Vec<context_server::types::Tool>,
+    cx: &mut TestAppContext,
+) -> (
+    context_server::test::FakeTransport,
+    mpsc::UnboundedReceiver<(
+        context_server::types::CallToolParams,
+        oneshot::Sender<context_server::types::CallToolResponse>,
+    )>,
+) {
     let (mcp_tool_calls_tx, mcp_tool_calls_rx) = mpsc::unbounded();
     let fake_transport = context_server::test::create_fake_transport(name, cx.executor())
         .on_request::<context_server::types::requests::Initialize, _>(move |_params| async move {
@@ -3764,17 +4294,7 @@ fn setup_context_server(
                 response_rx.await.unwrap()
             }
         });
-    context_server_store.update(cx, |store, cx| {
-        store.start_server(
-            Arc::new(ContextServer::new(
-                ContextServerId(name.into()),
-                Arc::new(fake_transport),
-            )),
-            cx,
-        );
-    });
-    cx.run_until_parked();
-    mcp_tool_calls_rx
+    (fake_transport, mcp_tool_calls_rx)
 }
 
 #[gpui::test]
@@ -5656,3 +6176,306 @@ async fn test_queued_message_ends_turn_at_boundary(cx: &mut TestAppContext) {
         );
     });
 }
+
+#[cfg(not(target_os = "windows"))]
+#[gpui::test]
+async fn test_pre_tool_call_hooks(cx: &mut TestAppContext) {
+    let (thread, model, _temp_dir) = setup_with_hooks(
+        cx,
+        agent_settings::AgentHooks {
+            pre_tool_call: vec![hook(
+                r#"grep -q blocked && echo '{"decision": "block", "reason": "Not allowed"}' || echo '{"input": {"text": "rewritten"}}'"#,
+                None,
+            )],
+            ..Default::default()
+        },
+    )
+    .await;
+    let fake_model = model.as_fake();
+
+    let mut events = thread
+        .update(cx, |thread, cx| {
+            thread.add_tool(EchoTool);
+            thread.send(UserMessageId::new(), ["Echo something"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "tool_1".into(),
+            name: EchoTool::NAME.into(),
+            raw_input: json!({"text": "original"}).to_string(),
+            input: json!({"text": "original"}),
+            is_input_complete: true,
+            thought_signature: None,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    let result = next_tool_result(&mut events, fake_model, cx).await;
+    assert!(!result.is_error);
+    assert_eq!(result.content, "rewritten".into());
+
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "tool_2".into(),
+            name: EchoTool::NAME.into(),
+            raw_input: json!({"text": "blocked"}).to_string(),
+            input: json!({"text": "blocked"}),
+            is_input_complete: true,
+            thought_signature: None,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    let result = next_tool_result(&mut events, fake_model, cx).await;
+    assert!(result.is_error);
+    assert_eq!(result.content, "Not allowed".into());
+}
+
+#[cfg(not(target_os = "windows"))]
+#[gpui::test]
+async fn test_post_edit_hooks(cx: &mut TestAppContext) {
+    let (thread, model, _temp_dir) = setup_with_hooks(
+        cx,
+        agent_settings::AgentHooks {
+            post_edit: vec![
+                hook("echo Formatted", Some(r"\.rs$")),
+                hook("echo Linted", Some(r"\.txt$")),
+            ],
+            ..Default::default()
+        },
+    )
+    .await;
+    let fake_model = model.as_fake();
+
+    let mut events = thread
+        .update(cx, |thread, cx| {
+            thread.add_tool(TouchTool {
+                project: thread.project().clone(),
+                action_log: thread.action_log().clone(),
+            });
+            thread.send(UserMessageId::new(), ["Edit a.txt"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+
+    // A single tool call may edit several files, whatever its input looks like.
+    let input = json!({"paths": ["a.txt", "b.rs"]});
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "tool_1".into(),
+            name: TouchTool::NAME.into(),
+            raw_input: input.to_string(),
+            input,
+            is_input_complete: true,
+            thought_signature: None,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    let result = next_tool_result(&mut events, fake_model, cx).await;
+    assert!(!result.is_error);
+    assert_eq!(
+        result.content,
+        "Edited a.txt, b.rs\n\nHook `echo Linted`: Linted\n\nHook `echo Formatted`: Formatted"
+            .into()
+    );
+
+    // Only the files edited by the call run the hooks.
+    let input = json!({"paths": ["b.rs"]});
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "tool_2".into(),
+            name: TouchTool::NAME.into(),
+            raw_input: input.to_string(),
+            input,
+            is_input_complete: true,
+            thought_signature: None,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    let result = next_tool_result(&mut events, fake_model, cx).await;
+    assert!(!result.is_error);
+    assert_eq!(
+        result.content,
+        "Edited b.rs\n\nHook `echo Formatted`: Formatted".into()
+    );
+}
+
+#[cfg(not(target_os = "windows"))]
+#[gpui::test]
+async fn test_turn_complete_hooks(cx: &mut TestAppContext) {
+    // Only asks for more work the first time it runs, so the turn can end.
+    let (thread, model, _temp_dir) = setup_with_hooks(
+        cx,
+        agent_settings::AgentHooks {
+            turn_complete: vec![hook(
+                r#"test -e checked || { touch checked; echo '{"decision": "block", "reason": "Run the tests"}'; }"#,
+                None,
+            )],
+            ..Default::default()
+        },
+    )
+    .await;
+    let fake_model = model.as_fake();
+
+    let mut events = thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Fix the bug"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Fixed it");
+    fake_model.end_last_completion_stream();
+
+    loop {
+        match events.next().await.expect("no hook feedback received") {
+            Ok(ThreadEvent::UserMessage(message)) => {
+                assert_eq!(
+                    message.content,
+                    vec![UserMessageContent::Text(
+                        r#"Hook `test -e checked || { touch checked; echo '{"decision": "block", "reason": "Run the tests"}'; }`: Run the tests"#
+                            .into()
+                    )]
+                );
+                break;
+            }
+            Ok(ThreadEvent::Stop(reason)) => {
+                panic!("turn ended with {reason:?} before hook feedback")
+            }
+            _ => {}
+        }
+    }
+    cx.run_until_parked();
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(
+        completion.messages.last().unwrap().string_contents(),
+        r#"Hook `test -e checked || { touch checked; echo '{"decision": "block", "reason": "Run the tests"}'; }`: Run the tests"#
+    );
+
+    fake_model.send_last_completion_stream_text_chunk("All tests pass");
+    fake_model.end_last_completion_stream();
+    let events = events.collect::<Vec<_>>().await;
+    assert_eq!(stop_events(events), vec![acp::StopReason::EndTurn]);
+    thread.read_with(cx, |thread, _cx| {
+        assert_eq!(
+            thread.last_message(),
+            Some(Message::Agent(AgentMessage {
+                content: vec![AgentMessageContent::Text("All tests pass".into())],
+                tool_results: IndexMap::default(),
+                reasoning_details: None,
+            }))
+        );
+    });
+}
+
+#[cfg(not(target_os = "windows"))]
+#[gpui::test]
+async fn test_turn_complete_hooks_only_continue_when_blocking(cx: &mut TestAppContext) {
+    let (thread, model, _temp_dir) = setup_with_hooks(
+        cx,
+        agent_settings::AgentHooks {
+            turn_complete: vec![
+                hook("echo 'Agent is done'", None),
+                hook("echo 'Run the tests' && exit 1", None),
+                hook("command-that-does-not-exist", None),
+            ],
+            ..Default::default()
+        },
+    )
+    .await;
+    let fake_model = model.as_fake();
+
+    let events = thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Fix the bug"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Fixed it");
+    fake_model.end_last_completion_stream();
+
+    let events = events.collect::<Vec<_>>().await;
+    assert!(
+        !events
+            .iter()
+            .any(|event| matches!(event, Ok(ThreadEvent::UserMessage(_)))),
+        "hooks that didn't block sent feedback to the model"
+    );
+    assert_eq!(stop_events(events), vec![acp::StopReason::EndTurn]);
+    assert!(fake_model.pending_completions().is_empty());
+}
+
+/// Sets up a thread in a project that exists on disk, since hooks run in its first worktree.
+async fn setup_with_hooks(
+    cx: &mut TestAppContext,
+    hooks: agent_settings::AgentHooks,
+) -> (Entity<Thread>, Arc<dyn LanguageModel>, tempfile::TempDir) {
+    let ThreadTest {
+        model,
+        project_context,
+        fs,
+        ..
+    } = setup(cx, TestModel::Fake).await;
+    cx.run_until_parked();
+    cx.update(|cx| {
+        let mut settings = agent_settings::AgentSettings::get_global(cx).clone();
+        settings.hooks = hooks;
+        agent_settings::AgentSettings::override_global(settings, cx);
+    });
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    fs.insert_tree(temp_dir.path(), json!({ "a.txt": "", "b.rs": "" }))
+        .await;
+    let project = Project::test(fs, [temp_dir.path()], cx).await;
+    let context_server_registry =
+        cx.new(|cx| ContextServerRegistry::new(project.read(cx).context_server_store(), cx));
+    let thread = cx.new(|cx| {
+        Thread::new(
+            project,
+            project_context,
+            context_server_registry,
+            Templates::new(),
+            Some(model.clone()),
+            cx,
+        )
+    });
+    (thread, model, temp_dir)
+}
+
+fn hook(command: &str, matcher: Option<&str>) -> agent_settings::AgentHook {
+    agent_settings::AgentHook {
+        command: command.into(),
+        matcher: matcher.map(|matcher| agent_settings::CompiledRegex::new(matcher, true).unwrap()),
+        invalid_matcher: None,
+        timeout: agent_settings::AgentHook::DEFAULT_TIMEOUT,
+    }
+}
+
+/// Waits for a tool call to finish, and returns the result the thread sends back to the model.
+async fn next_tool_result(
+    events: &mut UnboundedReceiver<Result<ThreadEvent>>,
+    fake_model: &FakeLanguageModel,
+    cx: &mut TestAppContext,
+) -> LanguageModelToolResult {
+    loop {
+        let event = events
+            .next()
+            .await
+            .expect("no tool call result received")
+            .unwrap();
+        if let ThreadEvent::ToolCallUpdate(acp_thread::ToolCallUpdate::UpdateFields(update)) = event
+            && matches!(
+                update.fields.status,
+                Some(acp::ToolCallStatus::Completed | acp::ToolCallStatus::Failed)
+            )
+        {
+            break;
+        }
+    }
+    cx.run_until_parked();
+    let completion = fake_model.pending_completions().pop().unwrap();
+    match &completion.messages.last().unwrap().content[..] {
+        [MessageContent::ToolResult(result)] => result.clone(),
+        content => panic!("expected a tool result, got {content:?}"),
+    }
+}
@@ -41,6 +41,66 @@ impl AgentTool for EchoTool {
     }
 }
 
+/// A tool that adds a line to the start of files
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct TouchToolInput {
+    /// The paths of the files to edit.
+    pub paths: Vec<String>,
+}
+
+pub struct TouchTool {
+    pub project: Entity<Project>,
+    pub action_log: Entity<action_log::ActionLog>,
+}
+
+impl AgentTool for TouchTool {
+    type Input = TouchToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "touch";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Edit
+    }
+
+    fn initial_title(
+        &self,
+        _input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        "Touch".into()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        _event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<String, String>> {
+        cx.spawn(async move |cx| {
+            for path in &input.paths {
+                let project_path = self
+                    .project
+                    .read_with(cx, |project, cx| project.find_project_path(path, cx))
+                    .ok_or_else(|| format!("{path} not found"))?;
+                let buffer = self
+                    .project
+                    .update(cx, |project, cx| project.open_buffer(project_path, cx))
+                    .await
+                    .map_err(|error| error.to_string())?;
+                self.action_log
+                    .update(cx, |log, cx| log.buffer_read(buffer.clone(), cx));
+                buffer.update(cx, |buffer, cx| {
+                    buffer.edit([(0..0, "touched\n")], None, cx)
+                });
+                self.action_log
+                    .update(cx, |log, cx| log.buffer_edited(buffer.clone(), cx));
+            }
+            Ok(format!("Edited {}", input.paths.join(", ")))
+        })
+    }
+}
+
 /// A tool that waits for a specified delay
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct DelayToolInput {
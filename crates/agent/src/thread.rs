@@ -1,10 +1,13 @@
 use crate::{
-    AgentGitWorktreeInfo, ContextServerRegistry, CopyPathTool, CreateDirectoryTool,
-    DbLanguageModel, DbThread, DeletePathTool, DiagnosticsTool, EditFileTool, FetchTool,
-    FindPathTool, GrepTool, ListDirectoryTool, MovePathTool, NowTool, OpenTool, ProjectSnapshot,
-    ReadFileTool, RestoreFileFromDiskTool, SaveFileTool, SpawnAgentTool, StreamingEditFileTool,
-    SystemPromptTemplate, Template, Templates, TerminalTool, ToolPermissionDecision, WebSearchTool,
+    AgentGitWorktreeInfo, AgentThreadForkInfo, ApplyCodeActionTool, CodeActionsTool, CommandTool,
+    ContextServerRegistry, CopyPathTool, CreateDirectoryTool, DbLanguageModel, DbThread,
+    DeletePathTool, DiagnosticsTool, EditFileTool, FetchTool, FindPathTool, FindReferencesTool,
+    GoToDefinitionTool, GrepTool, ListDirectoryTool, ListTasksTool, MovePathTool, NowTool,
+    OpenTool, ProjectSnapshot, ReadFileTool, RenameSymbolTool, RestoreFileFromDiskTool,
+    RunTaskTool, SaveFileTool, SpawnAgentTool, StreamingEditFileTool, SystemPromptTemplate,
+    Template, Templates, TerminalTool, ToolPermissionDecision, WebSearchTool, WorkspaceSymbolsTool,
     decide_permission_from_settings,
+    hooks::{self, HookContext},
 };
 use acp_thread::{MentionUri, UserMessageId};
 use action_log::ActionLog;
@@ -12,8 +15,8 @@ use feature_flags::{FeatureFlagAppExt as _, SubagentsFeatureFlag};
 
 use agent_client_protocol as acp;
 use agent_settings::{
-    AgentProfileId, AgentProfileSettings, AgentSettings, SUMMARIZE_THREAD_DETAILED_PROMPT,
-    SUMMARIZE_THREAD_PROMPT,
+    AgentHooks, AgentProfileId, AgentProfileSettings, AgentSettings,
+    SUMMARIZE_THREAD_DETAILED_PROMPT, SUMMARIZE_THREAD_PROMPT,
 };
 use anyhow::{Context as _, Result, anyhow};
 use chrono::{DateTime, Utc};
@@ -38,9 +41,10 @@ use language_model::{
     LanguageModelImage, LanguageModelProviderId, LanguageModelRegistry, LanguageModelRequest,
     LanguageModelRequestMessage, LanguageModelRequestTool, LanguageModelToolResult,
     LanguageModelToolResultContent, LanguageModelToolSchemaFormat, LanguageModelToolUse,
-    LanguageModelToolUseId, Role, SelectedModel, StopReason, TokenUsage, ZED_CLOUD_PROVIDER_ID,
+    LanguageModelToolUseId, LanguageModelUsageFeature, LanguageModelUsageTracker, Role,
+    SelectedModel, StopReason, TokenUsage, ZED_CLOUD_PROVIDER_ID,
 };
-use project::Project;
+use project::{Project, ProjectPath};
 use prompt_store::ProjectContext;
 use schemars::{JsonSchema, Schema};
 use serde::{Deserialize, Serialize};
@@ -61,6 +65,8 @@ use uuid::Uuid;
 const TOOL_CANCELED_MESSAGE: &str = "Tool canceled by user";
 pub const MAX_TOOL_NAME_LENGTH: usize = 64;
 pub const MAX_SUBAGENT_DEPTH: u8 = 1;
+/// How many times `turn_complete` hooks can send the model back to work in a single turn.
+const MAX_HOOK_CONTINUATIONS: usize = 3;
 
 /// Context passed to a subagent thread for lifecycle management
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -216,6 +222,7 @@ impl UserMessage {
             "<rules>\nThe user has specified the following rules that should be applied:\n";
         const OPEN_DIAGNOSTICS_TAG: &str = "<diagnostics>";
         const OPEN_DIFFS_TAG: &str = "<diffs>";
+        const OPEN_MCP_RESOURCES_TAG: &str = "<mcp_resources>";
 
         let mut file_context = OPEN_FILES_TAG.to_string();
         let mut directory_context = OPEN_DIRECTORIES_TAG.to_string();
@@ -226,6 +233,7 @@ impl UserMessage {
         let mut rules_context = OPEN_RULES_TAG.to_string();
         let mut diagnostics_context = OPEN_DIAGNOSTICS_TAG.to_string();
         let mut diffs_context = OPEN_DIFFS_TAG.to_string();
+        let mut mcp_resources_context = OPEN_MCP_RESOURCES_TAG.to_string();
 
         for chunk in &self.content {
             let chunk = match chunk {
@@ -333,6 +341,19 @@ impl UserMessage {
                             )
                             .ok();
                         }
+                        MentionUri::McpResource { server_id, uri, .. } => {
+                            write!(
+                                &mut mcp_resources_context,
+                                "\nResource {} from MCP server {}:\n{}",
+                                uri,
+                                server_id,
+                                MarkdownCodeBlock {
+                                    tag: "",
+                                    text: content
+                                }
+                            )
+                            .ok();
+                        }
                     }
 
                     language_model::MessageContent::Text(uri.as_link().to_string())
@@ -407,6 +428,13 @@ impl UserMessage {
                 .push(language_model::MessageContent::Text(diagnostics_context));
         }
 
+        if mcp_resources_context.len() > OPEN_MCP_RESOURCES_TAG.len() {
+            mcp_resources_context.push_str("</mcp_resources>\n");
+            message
+                .content
+                .push(language_model::MessageContent::Text(mcp_resources_context));
+        }
+
         if message.content.len() > len_before_context {
             message.content.insert(
                 len_before_context,
@@ -608,6 +636,7 @@ pub trait ThreadEnvironment {
     fn create_terminal(
         &self,
         command: String,
+        extra_env: Vec<acp::EnvVariable>,
         cwd: Option<PathBuf>,
         output_byte_limit: Option<u64>,
         cx: &mut AsyncApp,
@@ -644,6 +673,7 @@ pub enum ThreadEvent {
     ToolCall(acp::ToolCall),
     ToolCallUpdate(acp_thread::ToolCallUpdate),
     ToolCallAuthorization(ToolCallAuthorization),
+    ToolCallElicitation(ToolCallElicitation),
     SubagentSpawned(acp::SessionId),
     Retry(acp_thread::RetryStatus),
     Stop(acp::StopReason),
@@ -848,6 +878,13 @@ pub struct ToolCallAuthorization {
     pub context: Option<ToolPermissionContext>,
 }
 
+#[derive(Debug)]
+pub struct ToolCallElicitation {
+    pub tool_call_id: acp::ToolCallId,
+    pub elicitation: acp_thread::Elicitation,
+    pub response: oneshot::Sender<context_server::types::ElicitResult>,
+}
+
 #[derive(Debug, thiserror::Error)]
 enum CompletionError {
     #[error("max tokens")]
@@ -904,6 +941,10 @@ pub struct Thread {
     running_subagents: Vec<WeakEntity<Thread>>,
     /// Git worktree info if this thread is running in an agent worktree.
     git_worktree_info: Option<AgentGitWorktreeInfo>,
+    /// The thread and message this thread was forked from, if any.
+    forked_from: Option<AgentThreadForkInfo>,
+    /// The environment tools run commands in, used by the current profile's command tools.
+    environment: Option<Rc<dyn ThreadEnvironment>>,
 }
 
 impl Thread {
@@ -995,6 +1036,8 @@ impl Thread {
             subagent_context: None,
             running_subagents: Vec::new(),
             git_worktree_info: None,
+            forked_from: None,
+            environment: None,
         }
     }
 
@@ -1007,6 +1050,11 @@ impl Thread {
         self.imported
     }
 
+    /// Returns the thread and message this thread was forked from, if any.
+    pub fn forked_from(&self) -> Option<&AgentThreadForkInfo> {
+        self.forked_from.as_ref()
+    }
+
     pub fn replay(
         &mut self,
         cx: &mut Context<Self>,
@@ -1220,6 +1268,8 @@ impl Thread {
             subagent_context: db_thread.subagent_context,
             running_subagents: Vec::new(),
             git_worktree_info: db_thread.git_worktree_info,
+            forked_from: db_thread.forked_from,
+            environment: None,
         }
     }
 
@@ -1241,6 +1291,7 @@ impl Thread {
             imported: self.imported,
             subagent_context: self.subagent_context.clone(),
             git_worktree_info: self.git_worktree_info.clone(),
+            forked_from: self.forked_from.clone(),
         };
 
         cx.background_spawn(async move {
@@ -1274,6 +1325,22 @@ impl Thread {
         &self.project
     }
 
+    /// Tracks the token usage of a request made with the given model on behalf of this thread.
+    fn usage_tracker(
+        &self,
+        model: &Arc<dyn LanguageModel>,
+        feature: LanguageModelUsageFeature,
+        cx: &App,
+    ) -> LanguageModelUsageTracker {
+        LanguageModelUsageTracker::new(
+            model.provider_id(),
+            model.id(),
+            feature,
+            self.project.read(cx).display_name(cx),
+            cx,
+        )
+    }
+
     pub fn action_log(&self) -> &Entity<ActionLog> {
         &self.action_log
     }
@@ -1343,6 +1410,12 @@ impl Thread {
         cx: &mut Context<Self>,
     ) {
         let language_registry = self.project.read(cx).languages().clone();
+        self.add_tool(ApplyCodeActionTool::new(
+            cx.weak_entity(),
+            self.project.clone(),
+            self.action_log.clone(),
+        ));
+        self.add_tool(CodeActionsTool::new(self.project.clone()));
         self.add_tool(CopyPathTool::new(self.project.clone()));
         self.add_tool(CreateDirectoryTool::new(self.project.clone()));
         self.add_tool(DeletePathTool::new(
@@ -1364,8 +1437,11 @@ impl Thread {
         ));
         self.add_tool(FetchTool::new(self.project.read(cx).client().http_client()));
         self.add_tool(FindPathTool::new(self.project.clone()));
+        self.add_tool(FindReferencesTool::new(self.project.clone()));
+        self.add_tool(GoToDefinitionTool::new(self.project.clone()));
         self.add_tool(GrepTool::new(self.project.clone()));
         self.add_tool(ListDirectoryTool::new(self.project.clone()));
+        self.add_tool(ListTasksTool::new(self.project.clone()));
         self.add_tool(MovePathTool::new(self.project.clone()));
         self.add_tool(NowTool);
         self.add_tool(OpenTool::new(self.project.clone()));
@@ -1374,14 +1450,29 @@ impl Thread {
             self.project.clone(),
             self.action_log.clone(),
         ));
+        self.add_tool(RenameSymbolTool::new(
+            cx.weak_entity(),
+            self.project.clone(),
+            self.action_log.clone(),
+        ));
         self.add_tool(SaveFileTool::new(self.project.clone()));
         self.add_tool(RestoreFileFromDiskTool::new(self.project.clone()));
-        self.add_tool(TerminalTool::new(self.project.clone(), environment.clone()));
+        self.add_tool(RunTaskTool::new(
+            self.project.clone(),
+            environment.clone(),
+            cx.weak_entity(),
+        ));
+        self.add_tool(
+            TerminalTool::new(self.project.clone(), environment.clone())
+                .with_thread(cx.weak_entity()),
+        );
         self.add_tool(WebSearchTool);
+        self.add_tool(WorkspaceSymbolsTool::new(self.project.clone()));
 
         if cx.has_flag::<SubagentsFeatureFlag>() && self.depth() < MAX_SUBAGENT_DEPTH {
-            self.add_tool(SpawnAgentTool::new(cx.weak_entity(), environment));
+            self.add_tool(SpawnAgentTool::new(cx.weak_entity(), environment.clone()));
         }
+        self.environment = Some(environment);
     }
 
     pub fn add_tool<T: AgentTool>(&mut self, tool: T) {
@@ -1667,14 +1758,46 @@ impl Thread {
             _task: cx.spawn(async move |this, cx| {
                 log::debug!("Starting agent turn execution");
 
-                let turn_result = Self::run_turn_internal(
-                    &this,
-                    model,
-                    &event_stream,
-                    cancellation_rx.clone(),
-                    cx,
-                )
-                .await;
+                let mut hook_continuations = 0;
+                let turn_result = loop {
+                    let turn_result = Self::run_turn_internal(
+                        &this,
+                        model.clone(),
+                        &event_stream,
+                        cancellation_rx.clone(),
+                        cx,
+                    )
+                    .await;
+                    if turn_result.is_err() || *cancellation_rx.borrow() {
+                        break turn_result;
+                    }
+
+                    let Ok(feedback) =
+                        this.update(cx, |this, cx| this.run_turn_complete_hooks(cx))
+                    else {
+                        break turn_result;
+                    };
+                    let feedback = feedback.await;
+                    if feedback.is_empty() || *cancellation_rx.borrow() {
+                        break turn_result;
+                    }
+                    if hook_continuations == MAX_HOOK_CONTINUATIONS {
+                        log::warn!(
+                            "Ignoring turn_complete hook feedback after {MAX_HOOK_CONTINUATIONS} continuations: {}",
+                            feedback.join("\n\n")
+                        );
+                        break turn_result;
+                    }
+                    hook_continuations += 1;
+                    if this
+                        .update(cx, |this, cx| {
+                            this.push_hook_feedback(feedback, &event_stream, cx)
+                        })
+                        .is_err()
+                    {
+                        break turn_result;
+                    }
+                };
 
                 // Check if we were cancelled - if so, cancel() already took running_turn
                 // and we shouldn't touch it (it might be a NEW turn now)
@@ -1738,8 +1861,11 @@ impl Thread {
 
             log::debug!("Calling model.stream_completion, attempt {}", attempt);
 
+            let usage_tracker = this.read_with(cx, |this, cx| {
+                this.usage_tracker(&model, LanguageModelUsageFeature::Agent, cx)
+            })?;
             let (mut events, mut error) = match model.stream_completion(request, cx).await {
-                Ok(events) => (events.fuse(), None),
+                Ok(events) => (usage_tracker.track(events).fuse(), None),
                 Err(err) => (stream::empty().boxed().fuse(), Some(err)),
             };
             let mut tool_results = FuturesUnordered::new();
@@ -2094,7 +2220,22 @@ impl Thread {
             acp::ToolCallUpdateFields::new().status(acp::ToolCallStatus::InProgress),
         );
         let supports_images = self.model().is_some_and(|model| model.supports_images());
-        let tool_result = tool.run(tool_use.input, tool_event_stream, cx);
+        let hooks = AgentSettings::get_global(cx).hooks.clone();
+        let tool_result = if hooks.pre_tool_call.is_empty()
+            && hooks.post_tool_call.is_empty()
+            && hooks.post_edit.is_empty()
+        {
+            tool.run(tool_use.input, tool_event_stream, cx)
+        } else {
+            self.run_tool_with_hooks(
+                tool,
+                tool_use.name.clone(),
+                tool_use.input,
+                tool_event_stream,
+                hooks,
+                cx,
+            )
+        };
         log::debug!("Running tool {}", tool_use.name);
         Some(cx.foreground_executor().spawn(async move {
             let (is_error, output) = match tool_result.await {
@@ -2123,6 +2264,144 @@ impl Thread {
         }))
     }
 
+    /// Runs a tool, giving the configured hooks a chance to block the call or rewrite
+    /// its input beforehand, and adding their feedback to its output afterwards.
+    fn run_tool_with_hooks(
+        &self,
+        tool: Arc<dyn AnyAgentTool>,
+        tool_name: Arc<str>,
+        input: serde_json::Value,
+        event_stream: ToolCallEventStream,
+        hooks: AgentHooks,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<AgentToolOutput, AgentToolOutput>> {
+        let context = self.hook_context(cx);
+        let project = self.project.clone();
+        let action_log = self.action_log.clone();
+        cx.spawn(async move |_, cx| {
+            let executor = cx.background_executor().clone();
+            let original_input = input.clone();
+            let input = hooks::run_pre_tool_call(
+                &hooks.pre_tool_call,
+                &context,
+                &tool_name,
+                input,
+                &executor,
+            )
+            .await
+            .map_err(AgentToolOutput::from_error)?;
+            if input != original_input {
+                event_stream
+                    .update_fields(acp::ToolCallUpdateFields::new().raw_input(input.clone()));
+            }
+
+            // Tools such as `rename_symbol` edit several files, so the edited files are
+            // taken from the action log rather than from the input.
+            let edit_count = cx.update(|cx| action_log.read(cx).edit_count());
+            let result = cx
+                .update(|cx| tool.run(input.clone(), event_stream, cx))
+                .await;
+
+            let (output, is_error) = match &result {
+                Ok(output) => (output, false),
+                Err(output) => (output, true),
+            };
+            let mut feedback = hooks::run_post_tool_call(
+                &hooks.post_tool_call,
+                &context,
+                &tool_name,
+                &input,
+                output,
+                is_error,
+                &executor,
+            )
+            .await;
+            let edited_paths = if is_error {
+                Vec::new()
+            } else {
+                cx.update(|cx| {
+                    let project = project.read(cx);
+                    action_log
+                        .read(cx)
+                        .buffers_edited_since(edit_count)
+                        .filter_map(|buffer| {
+                            let file = buffer.read(cx).file()?;
+                            project.absolute_path(
+                                &ProjectPath {
+                                    worktree_id: file.worktree_id(cx),
+                                    path: file.path().clone(),
+                                },
+                                cx,
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                })
+            };
+            for abs_path in edited_paths {
+                feedback.extend(
+                    hooks::run_post_edit(
+                        &hooks.post_edit,
+                        &context,
+                        &tool_name,
+                        &abs_path,
+                        &executor,
+                    )
+                    .await,
+                );
+            }
+
+            if feedback.is_empty() {
+                return result;
+            }
+            match result {
+                Ok(output) => Ok(hooks::append_feedback(output, &feedback)),
+                Err(output) => Err(hooks::append_feedback(output, &feedback)),
+            }
+        })
+    }
+
+    /// Runs the `turn_complete` hooks, returning their feedback for the model.
+    fn run_turn_complete_hooks(&self, cx: &mut Context<Self>) -> Task<Vec<String>> {
+        let hooks = AgentSettings::get_global(cx).hooks.turn_complete.clone();
+        if hooks.is_empty() {
+            return Task::ready(Vec::new());
+        }
+        let context = self.hook_context(cx);
+        let title = self.title();
+        let executor = cx.background_executor().clone();
+        cx.background_spawn(async move {
+            hooks::run_turn_complete(&hooks, &context, &title, &executor).await
+        })
+    }
+
+    /// Sends the feedback from `turn_complete` hooks to the model as a new user message.
+    fn push_hook_feedback(
+        &mut self,
+        feedback: Vec<String>,
+        event_stream: &ThreadEventStream,
+        cx: &mut Context<Self>,
+    ) {
+        let message = UserMessage {
+            id: UserMessageId::new(),
+            content: vec![UserMessageContent::Text(feedback.join("\n\n"))],
+        };
+        event_stream.send_user_message(&message);
+        self.messages.push(Message::User(message));
+        cx.notify();
+    }
+
+    fn hook_context(&self, cx: &App) -> HookContext {
+        HookContext {
+            session_id: self.id.to_string(),
+            cwd: self
+                .project
+                .read(cx)
+                .visible_worktrees(cx)
+                .next()
+                .map(|worktree| worktree.read(cx).abs_path().to_path_buf()),
+        }
+    }
+
     fn handle_tool_use_json_parse_error_event(
         &mut self,
         tool_use_id: LanguageModelToolUseId,
@@ -2241,10 +2520,13 @@ impl Thread {
             reasoning_details: None,
         });
 
+        let usage_tracker =
+            self.usage_tracker(&model, LanguageModelUsageFeature::ThreadSummary, cx);
         let task = cx
             .spawn(async move |this, cx| {
                 let mut summary = String::new();
-                let mut messages = model.stream_completion(request, cx).await.log_err()?;
+                let messages = model.stream_completion(request, cx).await.log_err()?;
+                let mut messages = usage_tracker.track(messages);
                 while let Some(event) = messages.next().await {
                     let event = event.log_err()?;
                     let text = match event {
@@ -2298,11 +2580,14 @@ impl Thread {
             cache: false,
             reasoning_details: None,
         });
+        let usage_tracker =
+            self.usage_tracker(&model, LanguageModelUsageFeature::ThreadSummary, cx);
         self.pending_title_generation = Some(cx.spawn(async move |this, cx| {
             let mut title = String::new();
 
             let generate = async {
-                let mut messages = model.stream_completion(request, cx).await?;
+                let messages = model.stream_completion(request, cx).await?;
+                let mut messages = usage_tracker.track(messages);
                 while let Some(event) = messages.next().await {
                     let event = event?;
                     let text = match event {
@@ -2447,7 +2732,7 @@ impl Thread {
         &self,
         profile: &AgentProfileSettings,
         model: &Arc<dyn LanguageModel>,
-        cx: &App,
+        cx: &Context<Self>,
     ) -> BTreeMap<SharedString, Arc<dyn AnyAgentTool>> {
         fn truncate(tool_name: &SharedString) -> SharedString {
             if tool_name.len() > MAX_TOOL_NAME_LENGTH {
@@ -2525,6 +2810,27 @@ impl Thread {
             }
         }
 
+        if let Some(environment) = &self.environment {
+            for (tool_name, definition) in &profile.command_tools {
+                let tool_name = truncate(&SharedString::from(tool_name.to_string()));
+                if tools.contains_key(&tool_name) {
+                    log::warn!("Command tool `{tool_name}` conflicts with an existing tool");
+                    continue;
+                }
+                #[allow(clippy::arc_with_non_send_sync)]
+                let tool = Arc::new(
+                    CommandTool::new(
+                        tool_name.to_string().into(),
+                        definition.clone(),
+                        self.project.clone(),
+                        environment.clone(),
+                    )
+                    .with_thread(cx.weak_entity()),
+                );
+                tools.insert(tool_name, tool);
+            }
+        }
+
         tools
     }
 
@@ -3183,6 +3489,29 @@ impl ToolCallEventStream {
             .ok();
     }
 
+    /// Ask the user to fill in the input requested by an MCP server while this
+    /// tool call is running.
+    pub fn elicit(
+        &self,
+        server_name: SharedString,
+        request: context_server::types::ElicitRequestParams,
+    ) -> impl Future<Output = Result<context_server::types::ElicitResult>> + use<> {
+        let (response_tx, response_rx) = oneshot::channel();
+        let sent = self
+            .stream
+            .0
+            .unbounded_send(Ok(ThreadEvent::ToolCallElicitation(ToolCallElicitation {
+                tool_call_id: acp::ToolCallId::new(self.tool_use_id.to_string()),
+                elicitation: acp_thread::Elicitation::new(server_name, request),
+                response: response_tx,
+            })))
+            .map_err(|error| anyhow!("Failed to send tool call elicitation: {error}"));
+        async move {
+            sent?;
+            Ok(response_rx.await?)
+        }
+    }
+
     pub fn subagent_spawned(&self, id: acp::SessionId) {
         self.stream
             .0
@@ -3442,6 +3771,15 @@ impl ToolCallEventStreamReceiver {
         }
     }
 
+    pub async fn expect_elicitation(&mut self) -> ToolCallElicitation {
+        let event = self.0.next().await;
+        if let Some(Ok(ThreadEvent::ToolCallElicitation(elicitation))) = event {
+            elicitation
+        } else {
+            panic!("Expected ToolCallElicitation but got: {:?}", event);
+        }
+    }
+
     pub async fn expect_update_fields(&mut self) -> acp::ToolCallUpdateFields {
         let event = self.0.next().await;
         if let Some(Ok(ThreadEvent::ToolCallUpdate(acp_thread::ToolCallUpdate::UpdateFields(
@@ -1,4 +1,4 @@
-use crate::{DbThread, DbThreadMetadata, ThreadsDatabase};
+use crate::{DbThread, DbThreadMetadata, DbThreadSearchMatch, ThreadsDatabase};
 use agent_client_protocol as acp;
 use anyhow::{Result, anyhow};
 use gpui::{App, Context, Entity, Global, Task, prelude::*};
@@ -101,6 +101,18 @@ impl ThreadStore {
         })
     }
 
+    pub fn search_threads(
+        &self,
+        query: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<DbThreadSearchMatch>>> {
+        let database_future = ThreadsDatabase::connect(cx);
+        cx.background_spawn(async move {
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            database.search_threads(query).await
+        })
+    }
+
     pub fn delete_threads(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
         let database_future = ThreadsDatabase::connect(cx);
         cx.spawn(async move |this, cx| {
@@ -163,6 +175,7 @@ mod tests {
             imported: false,
             subagent_context: None,
             git_worktree_info: None,
+            forked_from: None,
         }
     }
 
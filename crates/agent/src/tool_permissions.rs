@@ -560,6 +560,10 @@ mod tests {
             message_editor_min_lines: 1,
             tool_permissions,
             show_turn_stats: false,
+            hooks: Default::default(),
+            terminal_sandbox: Default::default(),
+            model_pricing: vec![],
+            usage_budget: Default::default(),
         }
     }
 
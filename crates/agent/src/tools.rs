@@ -1,3 +1,7 @@
+mod apply_code_action_tool;
+mod code_actions_tool;
+mod code_navigation;
+mod command_tool;
 mod context_server_registry;
 mod copy_path_tool;
 mod create_directory_tool;
@@ -6,23 +10,34 @@ mod diagnostics_tool;
 mod edit_file_tool;
 mod fetch_tool;
 mod find_path_tool;
+mod find_references_tool;
+mod go_to_definition_tool;
 mod grep_tool;
 mod list_directory_tool;
+mod list_tasks_tool;
 mod move_path_tool;
 mod now_tool;
 mod open_tool;
+mod project_tasks;
 mod read_file_tool;
+mod rename_symbol_tool;
 mod restore_file_from_disk_tool;
+mod run_task_tool;
 mod save_file_tool;
 mod spawn_agent_tool;
 mod streaming_edit_file_tool;
 mod terminal_tool;
+mod test_output;
 mod tool_permissions;
 mod web_search_tool;
+mod workspace_symbols_tool;
 
 use crate::AgentTool;
 use language_model::{LanguageModelRequestTool, LanguageModelToolSchemaFormat};
 
+pub use apply_code_action_tool::*;
+pub use code_actions_tool::*;
+pub use command_tool::*;
 pub use context_server_registry::*;
 pub use copy_path_tool::*;
 pub use create_directory_tool::*;
@@ -31,19 +46,25 @@ pub use diagnostics_tool::*;
 pub use edit_file_tool::*;
 pub use fetch_tool::*;
 pub use find_path_tool::*;
+pub use find_references_tool::*;
+pub use go_to_definition_tool::*;
 pub use grep_tool::*;
 pub use list_directory_tool::*;
+pub use list_tasks_tool::*;
 pub use move_path_tool::*;
 pub use now_tool::*;
 pub use open_tool::*;
 pub use read_file_tool::*;
+pub use rename_symbol_tool::*;
 pub use restore_file_from_disk_tool::*;
+pub use run_task_tool::*;
 pub use save_file_tool::*;
 pub use spawn_agent_tool::*;
 pub use streaming_edit_file_tool::*;
 pub use terminal_tool::*;
 pub use tool_permissions::*;
 pub use web_search_tool::*;
+pub use workspace_symbols_tool::*;
 
 macro_rules! tools {
     ($($tool:ty),* $(,)?) => {
@@ -113,6 +134,8 @@ macro_rules! tools {
 }
 
 tools! {
+    ApplyCodeActionTool,
+    CodeActionsTool,
     CopyPathTool,
     CreateDirectoryTool,
     DeletePathTool,
@@ -120,15 +143,21 @@ tools! {
     EditFileTool,
     FetchTool,
     FindPathTool,
+    FindReferencesTool,
+    GoToDefinitionTool,
     GrepTool,
     ListDirectoryTool,
+    ListTasksTool,
     MovePathTool,
     NowTool,
     OpenTool,
     ReadFileTool,
+    RenameSymbolTool,
     RestoreFileFromDiskTool,
+    RunTaskTool,
     SaveFileTool,
     SpawnAgentTool,
     TerminalTool,
     WebSearchTool,
+    WorkspaceSymbolsTool,
 }
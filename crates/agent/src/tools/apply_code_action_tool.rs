@@ -0,0 +1,155 @@
+use crate::{AgentTool, EditFileTool, Thread, ToolCallEventStream};
+use action_log::ActionLog;
+use agent_client_protocol as acp;
+use anyhow::Result;
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task, WeakEntity};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{path::Path, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+use super::code_actions_tool::format_code_action;
+use super::code_navigation::{buffers_will_be_edited, find_lines, finish_project_transaction};
+use super::tool_permissions::authorize_file_edit;
+
+/// Applies one of the code actions listed by the `code_actions` tool.
+///
+/// Pass the same path and lines that were given to `code_actions`, along with the exact title of the action to apply. The edited files are saved.
+///
+/// <example>
+/// To import a missing type on line 12 of `src/main.rs`:
+/// {
+///     "path": "project/src/main.rs",
+///     "start_line": 12,
+///     "title": "Import `std::collections::HashMap`"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ApplyCodeActionToolInput {
+    /// The path of the file the code action was listed for.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The first line of the range the code action was listed for (1-based index).
+    pub start_line: u32,
+    /// The last line of the range the code action was listed for (1-based index, inclusive). Defaults to `start_line`.
+    #[serde(default)]
+    pub end_line: Option<u32>,
+    /// The exact title of the code action to apply.
+    pub title: String,
+}
+
+pub struct ApplyCodeActionTool {
+    thread: WeakEntity<Thread>,
+    project: Entity<Project>,
+    action_log: Entity<ActionLog>,
+}
+
+impl ApplyCodeActionTool {
+    pub fn new(
+        thread: WeakEntity<Thread>,
+        project: Entity<Project>,
+        action_log: Entity<ActionLog>,
+    ) -> Self {
+        Self {
+            thread,
+            project,
+            action_log,
+        }
+    }
+}
+
+impl AgentTool for ApplyCodeActionTool {
+    type Input = ApplyCodeActionToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "apply_code_action";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Edit
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        if let Ok(input) = input {
+            format!("Apply {}", MarkdownInlineCode(&input.title)).into()
+        } else {
+            "Apply code action".into()
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        // Code actions are configured under `edit_file` in the tool permission settings,
+        // since they edit files just like it does.
+        let authorize = authorize_file_edit(
+            EditFileTool::NAME,
+            Path::new(&input.path),
+            &format!("Apply `{}`", input.title),
+            &self.thread,
+            &event_stream,
+            cx,
+        );
+        let project = self.project.clone();
+        let action_log = self.action_log.clone();
+        cx.spawn(async move |cx| {
+            authorize.await.map_err(|e| e.to_string())?;
+
+            let (buffer, range, _lsp_handle) =
+                find_lines(&project, &input.path, input.start_line, input.end_line, cx)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            let actions = project
+                .update(cx, |project, cx| {
+                    project.code_actions(&buffer, range, None, cx)
+                })
+                .await
+                .map_err(|e| e.to_string())?
+                .unwrap_or_default();
+            let Some(action) = actions
+                .iter()
+                .find(|action| action.lsp_action.title() == input.title)
+                .cloned()
+            else {
+                let mut message = format!(
+                    "No code action titled `{}` is available for these lines.",
+                    input.title
+                );
+                if !actions.is_empty() {
+                    message.push_str(" Available code actions:\n");
+                    for action in &actions {
+                        message.push_str(&format_code_action(action));
+                        message.push('\n');
+                    }
+                }
+                return Err(message);
+            };
+
+            buffers_will_be_edited([buffer.clone()], &action_log, cx);
+            let apply = project.update(cx, |project, cx| {
+                project.apply_code_action(buffer, action, true, cx)
+            });
+            let transaction = futures::select! {
+                result = apply.fuse() => result.map_err(|e| e.to_string())?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Code action cancelled by user".to_string());
+                }
+            };
+
+            let summary = finish_project_transaction(transaction, &project, &action_log, cx)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(format!("Applied `{}`. {summary}", input.title))
+        })
+    }
+}
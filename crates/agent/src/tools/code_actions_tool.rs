@@ -0,0 +1,110 @@
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use project::{CodeAction, Project};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+use super::code_navigation::find_lines;
+
+/// Lists the code actions the project's language servers offer for a range of lines, such as quick fixes for diagnostics, adding missing imports, or extracting a function.
+///
+/// Apply one of the listed actions with the `apply_code_action` tool, passing the same path and lines along with the action's title.
+///
+/// <example>
+/// To list the code actions for an error reported on line 12 of `src/main.rs`:
+/// {
+///     "path": "project/src/main.rs",
+///     "start_line": 12
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CodeActionsToolInput {
+    /// The path of the file to get code actions for.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The first line of the range (1-based index).
+    pub start_line: u32,
+    /// The last line of the range (1-based index, inclusive). Defaults to `start_line`.
+    #[serde(default)]
+    pub end_line: Option<u32>,
+}
+
+pub struct CodeActionsTool {
+    project: Entity<Project>,
+}
+
+impl CodeActionsTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for CodeActionsTool {
+    type Input = CodeActionsToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "code_actions";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Read
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        if let Ok(input) = input {
+            format!("List code actions in {}", MarkdownInlineCode(&input.path)).into()
+        } else {
+            "List code actions".into()
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let (buffer, range, _lsp_handle) =
+                find_lines(&project, &input.path, input.start_line, input.end_line, cx)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            let actions = project.update(cx, |project, cx| {
+                project.code_actions(&buffer, range, None, cx)
+            });
+            let actions = futures::select! {
+                result = actions.fuse() => result.map_err(|e| e.to_string())?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Listing code actions cancelled by user".to_string());
+                }
+            };
+
+            let Some(actions) = actions.filter(|actions| !actions.is_empty()) else {
+                return Ok("No code actions available for these lines.".to_string());
+            };
+            let mut output = String::from("Available code actions:\n");
+            for action in &actions {
+                writeln!(output, "{}", format_code_action(action)).ok();
+            }
+            Ok(output)
+        })
+    }
+}
+
+pub(crate) fn format_code_action(action: &CodeAction) -> String {
+    match action.lsp_action.action_kind() {
+        Some(kind) => format!("- `{}` ({})", action.lsp_action.title(), kind.as_str()),
+        None => format!("- `{}`", action.lsp_action.title()),
+    }
+}
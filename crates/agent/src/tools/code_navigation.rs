@@ -0,0 +1,292 @@
+//! Helpers shared by the tools that query the project's language servers.
+
+use action_log::ActionLog;
+use anyhow::{Context as _, Result};
+use gpui::{App, AsyncApp, Entity};
+use language::{Anchor, Buffer, OffsetRangeExt as _, Point};
+use project::{Project, ProjectTransaction, WorktreeSettings, lsp_store::OpenLspBufferHandle};
+use settings::{Settings as _, SettingsLocation};
+use std::{cmp, fmt::Write as _, ops::Range, path::PathBuf};
+
+/// The number of lines shown before and after each location.
+const CONTEXT_LINES: u32 = 1;
+/// The number of locations listed before the rest are only counted.
+const MAX_LOCATIONS: usize = 50;
+
+/// A symbol in a buffer that's open for language server requests.
+pub(crate) struct SymbolPosition {
+    pub buffer: Entity<Buffer>,
+    pub position: Point,
+    /// Keeps the buffer registered with its language servers during the request.
+    _lsp_handle: OpenLspBufferHandle,
+}
+
+/// Opens the file at `path` and finds `symbol` on the given one-based line.
+///
+/// Tools identify symbols by their name and line rather than by column, because
+/// models are unreliable at counting characters.
+pub(crate) async fn find_symbol(
+    project: &Entity<Project>,
+    path: &str,
+    line: u32,
+    symbol: &str,
+    cx: &mut AsyncApp,
+) -> Result<SymbolPosition> {
+    let (buffer, lsp_handle) = open_buffer(project, path, cx).await?;
+    let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+    let row = line_to_row(line, snapshot.max_point().row, path)?;
+    let line_text = snapshot
+        .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+        .collect::<String>();
+    let column = symbol_column(&line_text, symbol)
+        .with_context(|| format!("Could not find `{symbol}` on line {line} of {path}"))?;
+
+    Ok(SymbolPosition {
+        buffer,
+        position: Point::new(row, column as u32),
+        _lsp_handle: lsp_handle,
+    })
+}
+
+/// Opens the file at `path` and returns the given one-based, inclusive range of lines.
+pub(crate) async fn find_lines(
+    project: &Entity<Project>,
+    path: &str,
+    start_line: u32,
+    end_line: Option<u32>,
+    cx: &mut AsyncApp,
+) -> Result<(Entity<Buffer>, Range<Point>, OpenLspBufferHandle)> {
+    let (buffer, lsp_handle) = open_buffer(project, path, cx).await?;
+    let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+    let max_row = snapshot.max_point().row;
+    let start_row = line_to_row(start_line, max_row, path)?;
+    let end_row = match end_line {
+        Some(end_line) => line_to_row(end_line, max_row, path)?.max(start_row),
+        None => start_row,
+    };
+    let range = Point::new(start_row, 0)..Point::new(end_row, snapshot.line_len(end_row));
+    Ok((buffer, range, lsp_handle))
+}
+
+async fn open_buffer(
+    project: &Entity<Project>,
+    path: &str,
+    cx: &mut AsyncApp,
+) -> Result<(Entity<Buffer>, OpenLspBufferHandle)> {
+    let project_path = project
+        .read_with(cx, |project, cx| project.find_project_path(path, cx))
+        .with_context(|| format!("Could not find path {path} in project"))?;
+    let buffer = project
+        .update(cx, |project, cx| project.open_buffer(project_path, cx))
+        .await?;
+    let lsp_handle = project.update(cx, |project, cx| {
+        project.register_buffer_with_language_servers(&buffer, cx)
+    });
+    Ok((buffer, lsp_handle))
+}
+
+fn line_to_row(line: u32, max_row: u32, path: &str) -> Result<u32> {
+    line.checked_sub(1)
+        .filter(|row| *row <= max_row)
+        .with_context(|| {
+            format!(
+                "Line {line} is out of range, {path} has {} lines",
+                max_row + 1
+            )
+        })
+}
+
+/// Returns the byte offset of `symbol` in `line`, preferring an occurrence that isn't
+/// part of a longer identifier.
+fn symbol_column(line: &str, symbol: &str) -> Option<usize> {
+    if symbol.is_empty() {
+        return None;
+    }
+
+    let is_identifier_char = |c: char| c.is_alphanumeric() || c == '_';
+    let mut first_match = None;
+    for (ix, _) in line.match_indices(symbol) {
+        first_match.get_or_insert(ix);
+        let before = line[..ix].chars().next_back();
+        let after = line[ix + symbol.len()..].chars().next();
+        if !before.is_some_and(is_identifier_char) && !after.is_some_and(is_identifier_char) {
+            return Some(ix);
+        }
+    }
+    first_match
+}
+
+/// Formats locations grouped by file, with a line of context around each one.
+/// Locations in excluded or private files are skipped.
+pub(crate) fn format_locations(
+    locations: impl IntoIterator<Item = (Entity<Buffer>, Range<Anchor>)>,
+    cx: &App,
+) -> String {
+    let mut entries = Vec::new();
+    for (buffer, range) in locations {
+        let buffer = buffer.read(cx);
+        let Some(file) = buffer.file() else {
+            continue;
+        };
+        let settings = WorktreeSettings::get(
+            Some(SettingsLocation {
+                worktree_id: file.worktree_id(cx),
+                path: file.path(),
+            }),
+            cx,
+        );
+        if settings.is_path_excluded(file.path()) || settings.is_path_private(file.path()) {
+            continue;
+        }
+
+        let snapshot = buffer.snapshot();
+        let range = range.to_point(&snapshot);
+        let context_start = range.start.row.saturating_sub(CONTEXT_LINES);
+        let context_end = cmp::min(snapshot.max_point().row, range.end.row + CONTEXT_LINES);
+        let context = snapshot
+            .text_for_range(
+                Point::new(context_start, 0)
+                    ..Point::new(context_end, snapshot.line_len(context_end)),
+            )
+            .collect::<String>();
+        entries.push((file.full_path(cx), range.start.row, context));
+    }
+    entries.sort_by(|(a_path, a_row, _), (b_path, b_row, _)| {
+        a_path.cmp(b_path).then(a_row.cmp(b_row))
+    });
+    entries.dedup_by(|(a_path, a_row, _), (b_path, b_row, _)| a_path == b_path && a_row == b_row);
+
+    let total = entries.len();
+    let mut output = String::new();
+    let mut current_path: Option<PathBuf> = None;
+    for (path, row, context) in entries.into_iter().take(MAX_LOCATIONS) {
+        if current_path.as_ref() != Some(&path) {
+            writeln!(output, "\n## {}", path.display()).ok();
+            current_path = Some(path);
+        }
+        writeln!(output, "\n### L{}\n```\n{context}\n```", row + 1).ok();
+    }
+    if total > MAX_LOCATIONS {
+        writeln!(
+            output,
+            "\n{} more locations not shown.",
+            total - MAX_LOCATIONS
+        )
+        .ok();
+    }
+    output
+}
+
+/// Marks the buffers the agent is about to edit as read, so the action log can
+/// show what changed in them.
+pub(crate) fn buffers_will_be_edited(
+    buffers: impl IntoIterator<Item = Entity<Buffer>>,
+    action_log: &Entity<ActionLog>,
+    cx: &mut AsyncApp,
+) {
+    action_log.update(cx, |log, cx| {
+        for buffer in buffers {
+            log.buffer_read(buffer, cx);
+        }
+    });
+}
+
+/// Records the buffers changed by a language server edit in the action log and saves
+/// them, returning a summary for the model.
+pub(crate) async fn finish_project_transaction(
+    transaction: ProjectTransaction,
+    project: &Entity<Project>,
+    action_log: &Entity<ActionLog>,
+    cx: &mut AsyncApp,
+) -> Result<String> {
+    let buffers = transaction.0.into_keys().collect::<Vec<_>>();
+    if buffers.is_empty() {
+        return Ok("The language server didn't make any changes.".to_string());
+    }
+
+    action_log.update(cx, |log, cx| {
+        for buffer in &buffers {
+            log.buffer_edited(buffer.clone(), cx);
+        }
+    });
+    project
+        .update(cx, |project, cx| {
+            project.save_buffers(buffers.iter().cloned().collect(), cx)
+        })
+        .await?;
+
+    let mut paths = cx.update(|cx| {
+        buffers
+            .iter()
+            .filter_map(|buffer| Some(buffer.read(cx).file()?.full_path(cx)))
+            .collect::<Vec<_>>()
+    });
+    paths.sort();
+    let mut output = format!("Edited {} files:\n", paths.len());
+    for path in paths {
+        writeln!(output, "- {}", path.display()).ok();
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use futures::StreamExt as _;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use settings::SettingsStore;
+    use util::path;
+
+    /// Creates a project with `files` at `/root` and starts a fake Rust language server
+    /// with the given capabilities for `/root/src/lib.rs`, which has to exist.
+    pub(crate) async fn project_with_fake_language_server(
+        files: serde_json::Value,
+        capabilities: lsp::ServerCapabilities,
+        cx: &mut TestAppContext,
+    ) -> (
+        Entity<Project>,
+        lsp::FakeLanguageServer,
+        OpenLspBufferHandle,
+    ) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/root"), files).await;
+        let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        language_registry.add(language::rust_lang());
+        let mut fake_language_servers = language_registry.register_fake_lsp(
+            "Rust",
+            language::FakeLspAdapter {
+                capabilities,
+                ..Default::default()
+            },
+        );
+
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/root/src/lib.rs"), cx)
+            })
+            .await
+            .unwrap();
+        let handle = project.update(cx, |project, cx| {
+            project.register_buffer_with_language_servers(&buffer, cx)
+        });
+        let fake_language_server = fake_language_servers.next().await.unwrap();
+        (project, fake_language_server, handle)
+    }
+
+    #[test]
+    fn test_symbol_column() {
+        assert_eq!(symbol_column("let name = name_len(name);", "name"), Some(4));
+        assert_eq!(symbol_column("let len = name_len(name);", "name"), Some(19));
+        assert_eq!(symbol_column("let len = name_len(x);", "name"), Some(10));
+        assert_eq!(symbol_column("impl Foo for Bar {}", "Baz"), None);
+        assert_eq!(symbol_column("impl Foo for Bar {}", ""), None);
+    }
+}
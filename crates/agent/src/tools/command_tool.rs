@@ -0,0 +1,381 @@
+use crate::{
+    AgentToolOutput, AnyAgentTool, Thread, ThreadEnvironment, ToolCallEventStream,
+    ToolPermissionDecision, decide_permission_from_settings,
+};
+use agent_client_protocol::{self as acp, ToolKind};
+use agent_settings::AgentSettings;
+use anyhow::Result;
+use gpui::{App, Entity, SharedString, Task, WeakEntity};
+use language_model::LanguageModelToolSchemaFormat;
+use project::Project;
+use settings::{CommandToolContent, Settings as _};
+use std::{path::PathBuf, rc::Rc, sync::Arc, time::Duration};
+use util::shell::ShellKind;
+
+use super::terminal_tool::{
+    COMMAND_OUTPUT_LIMIT, CommandOutput, process_content, run_command, sandbox_policy,
+    sandboxed_command,
+};
+
+/// A tool declared in an agent profile's `command_tools` that runs a shell command
+/// built from the tool's parameters.
+pub struct CommandTool {
+    name: Arc<str>,
+    definition: CommandToolContent,
+    project: Entity<Project>,
+    environment: Rc<dyn ThreadEnvironment>,
+    thread: Option<WeakEntity<Thread>>,
+}
+
+impl CommandTool {
+    pub fn new(
+        name: Arc<str>,
+        definition: CommandToolContent,
+        project: Entity<Project>,
+        environment: Rc<dyn ThreadEnvironment>,
+    ) -> Self {
+        Self {
+            name,
+            definition,
+            project,
+            environment,
+            thread: None,
+        }
+    }
+
+    /// Uses the thread's profile to decide how commands are sandboxed.
+    pub fn with_thread(mut self, thread: WeakEntity<Thread>) -> Self {
+        self.thread = Some(thread);
+        self
+    }
+
+    fn working_dir(&self, cx: &App) -> Option<PathBuf> {
+        let root = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf());
+        match &self.definition.cwd {
+            Some(cwd) if cwd.is_absolute() => Some(cwd.clone()),
+            Some(cwd) => root.map(|root| root.join(cwd)),
+            None => root,
+        }
+    }
+}
+
+impl AnyAgentTool for CommandTool {
+    fn name(&self) -> SharedString {
+        self.name.to_string().into()
+    }
+
+    fn description(&self) -> SharedString {
+        self.definition.description.clone().into()
+    }
+
+    fn kind(&self) -> ToolKind {
+        ToolKind::Execute
+    }
+
+    fn initial_title(&self, _input: serde_json::Value, _cx: &mut App) -> SharedString {
+        format!("Run `{}`", self.name).into()
+    }
+
+    fn input_schema(&self, format: LanguageModelToolSchemaFormat) -> Result<serde_json::Value> {
+        let mut schema = self.definition.parameters.clone().unwrap_or_default();
+        language_model::tool_schema::adapt_schema_to_format(&mut schema, format)?;
+        Ok(match schema {
+            serde_json::Value::Null => {
+                serde_json::json!({ "type": "object", "properties": {} })
+            }
+            serde_json::Value::Object(map) if map.is_empty() => {
+                serde_json::json!({ "type": "object", "properties": {} })
+            }
+            _ => schema,
+        })
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<AgentToolOutput, AgentToolOutput>> {
+        let shell = ShellKind::new(
+            util::get_default_system_shell_preferring_bash(),
+            cfg!(windows),
+        );
+        let command = match render_command(
+            &self.definition.command,
+            self.definition.parameters.as_ref(),
+            &input,
+            shell,
+        ) {
+            Ok(command) => command,
+            Err(error) => return Task::ready(Err(AgentToolOutput::from_error(error))),
+        };
+        let sandbox_policy = match sandbox_policy(self.thread.as_ref(), &self.project, cx) {
+            Ok(policy) => policy,
+            Err(error) => return Task::ready(Err(AgentToolOutput::from_error(error.to_string()))),
+        };
+
+        let authorize = match decide_permission_from_settings(
+            &self.name,
+            std::slice::from_ref(&command),
+            AgentSettings::get_global(cx),
+        ) {
+            ToolPermissionDecision::Allow => None,
+            ToolPermissionDecision::Deny(reason) => {
+                return Task::ready(Err(AgentToolOutput::from_error(reason)));
+            }
+            ToolPermissionDecision::Confirm => {
+                let context =
+                    crate::ToolPermissionContext::new(self.name.as_ref(), vec![command.clone()]);
+                Some(event_stream.authorize(format!("Run `{command}`"), context, cx))
+            }
+        };
+
+        let working_dir = self.working_dir(cx);
+        let extra_env = self
+            .definition
+            .env
+            .iter()
+            .flatten()
+            .map(|(name, value)| acp::EnvVariable::new(name.clone(), value.clone()))
+            .collect::<Vec<_>>();
+        let timeout = self.definition.timeout.map(Duration::from_secs);
+
+        cx.spawn(async move |cx| {
+            if let Some(authorize) = authorize {
+                authorize
+                    .await
+                    .map_err(|e| AgentToolOutput::from_error(e.to_string()))?;
+            }
+
+            let sandboxed = sandboxed_command(sandbox_policy.as_ref(), &command)
+                .map_err(AgentToolOutput::from_error)?;
+            let CommandOutput {
+                output,
+                timed_out,
+                user_stopped,
+            } = run_command(
+                self.environment.as_ref(),
+                sandboxed.command.clone(),
+                extra_env,
+                working_dir,
+                timeout,
+                COMMAND_OUTPUT_LIMIT,
+                &event_stream,
+                cx,
+            )
+            .await
+            .map_err(|e| AgentToolOutput::from_error(e.to_string()))?;
+            sandboxed
+                .check_setup()
+                .map_err(AgentToolOutput::from_error)?;
+
+            let content = process_content(output, &command, timed_out, user_stopped);
+            Ok(AgentToolOutput {
+                raw_output: content.clone().into(),
+                llm_output: content.into(),
+            })
+        })
+    }
+
+    fn replay(
+        &self,
+        _input: serde_json::Value,
+        _output: serde_json::Value,
+        _event_stream: ToolCallEventStream,
+        _cx: &mut App,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Replaces each `{name}` in `template` with the quoted value of the `name` parameter,
+/// or with nothing if the parameter was omitted. Placeholders that don't name a parameter
+/// declared in `parameters` or given in `input` are left as they are. Fails if a required
+/// parameter is missing.
+///
+/// Values are quoted for `shell`, so templates can't put placeholders inside quotes of
+/// their own, where the value's quotes would end them instead.
+fn render_command(
+    template: &str,
+    parameters: Option<&serde_json::Value>,
+    input: &serde_json::Value,
+    shell: ShellKind,
+) -> Result<String, String> {
+    let no_input = serde_json::Map::new();
+    let input = match input {
+        serde_json::Value::Object(input) => input,
+        serde_json::Value::Null => &no_input,
+        _ => return Err("The tool's input must be an object.".to_string()),
+    };
+    let declared = parameters
+        .and_then(|parameters| parameters.get("properties"))
+        .and_then(|properties| properties.as_object());
+    let required = parameters
+        .and_then(|parameters| parameters.get("required"))
+        .and_then(|required| required.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|name| name.as_str());
+    for name in required {
+        if input.get(name).is_none_or(|value| value.is_null()) {
+            return Err(format!("The required parameter `{name}` is missing."));
+        }
+    }
+
+    let mut command = String::with_capacity(template.len());
+    let mut open_quote = None;
+    let mut escaped = false;
+    let mut rest = template;
+    while let Some(c) = rest.chars().next() {
+        if c == '{'
+            && !escaped
+            && let Some((name, len)) = placeholder(rest)
+            && (input.contains_key(name)
+                || declared.is_some_and(|declared| declared.contains_key(name)))
+        {
+            if let Some(quote) = open_quote {
+                return Err(format!(
+                    "The command template puts `{{{name}}}` inside {quote} quotes. \
+                    Ask the user to remove them, since parameter values are quoted automatically."
+                ));
+            }
+            let value = match input.get(name) {
+                None | Some(serde_json::Value::Null) => None,
+                Some(serde_json::Value::String(value)) => Some(value.clone()),
+                Some(value) => Some(value.to_string()),
+            };
+            if let Some(value) = value {
+                let Some(quoted) = shell.try_quote(&value) else {
+                    return Err(format!(
+                        "The value of `{name}` can't be quoted for {shell}."
+                    ));
+                };
+                command.push_str(&quoted);
+            }
+            rest = &rest[len..];
+            continue;
+        }
+
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if open_quote != Some('\'') => escaped = true,
+            '\'' | '"' if open_quote.is_none() => open_quote = Some(c),
+            _ if open_quote == Some(c) => open_quote = None,
+            _ => {}
+        }
+        command.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    Ok(command)
+}
+
+/// Returns the name in the placeholder `text` starts with, and the placeholder's length.
+fn placeholder(text: &str) -> Option<(&str, usize)> {
+    let end = text.find('}')?;
+    Some((&text[1..end], end + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parameters() -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "package": { "type": "string" },
+                "filter": { "type": "string" },
+                "jobs": { "type": "integer" },
+                "release": { "type": "boolean" },
+            },
+            "required": ["package"],
+        })
+    }
+
+    #[test]
+    fn test_render_command() {
+        let parameters = parameters();
+        let input = json!({
+            "package": "agent",
+            "filter": "thread tests",
+            "jobs": 4,
+            "release": false,
+        });
+        let render =
+            |template| render_command(template, Some(&parameters), &input, ShellKind::Posix);
+        assert_eq!(
+            render("cargo test -p {package} -j {jobs} -- {filter}"),
+            Ok("cargo test -p agent -j 4 -- 'thread tests'".to_string())
+        );
+        assert_eq!(
+            render("echo {release} {undeclared} {}"),
+            Ok("echo false {undeclared} {}".to_string())
+        );
+        assert_eq!(
+            render("awk '{print $1}' {package}"),
+            Ok("awk '{print $1}' agent".to_string())
+        );
+        assert_eq!(
+            render(r"echo it\'s {package}"),
+            Ok(r"echo it\'s agent".to_string())
+        );
+    }
+
+    #[test]
+    fn test_render_command_omitted_parameters() {
+        let parameters = parameters();
+        assert_eq!(
+            render_command(
+                "cargo test -p {package} -- {filter}",
+                Some(&parameters),
+                &json!({ "package": "agent" }),
+                ShellKind::Posix
+            ),
+            Ok("cargo test -p agent -- ".to_string())
+        );
+        assert_eq!(
+            render_command(
+                "cargo test -p {package} -- {filter}",
+                Some(&parameters),
+                &json!({ "package": "agent", "filter": null }),
+                ShellKind::Posix
+            ),
+            Ok("cargo test -p agent -- ".to_string())
+        );
+
+        let error = render_command(
+            "cargo test -p {package} -- {filter}",
+            Some(&parameters),
+            &json!({ "filter": "thread" }),
+            ShellKind::Posix,
+        )
+        .unwrap_err();
+        assert!(error.contains("`package`"), "unexpected error: {error}");
+    }
+
+    #[test]
+    fn test_render_command_rejects_quoted_placeholders() {
+        let input = json!({ "filter": "it's" });
+        for template in [
+            "grep '{filter}' .",
+            "grep \"{filter}\" .",
+            r#"grep "\"{filter}" ."#,
+            "grep 'a'\"b {filter}\" .",
+        ] {
+            let error = render_command(template, None, &input, ShellKind::Posix).unwrap_err();
+            assert!(error.contains("`{filter}`"), "unexpected error: {error}");
+        }
+    }
+
+    #[test]
+    fn test_render_command_unquotable_value() {
+        let input = json!({ "filter": "a\0b" });
+        assert!(render_command("grep {filter} .", None, &input, ShellKind::Posix).is_err());
+    }
+}
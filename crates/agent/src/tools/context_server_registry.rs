@@ -336,6 +336,7 @@ impl AnyAgentTool for ContextServerTool {
         let Some(server) = self.store.read(cx).get_running_server(&self.server_id) else {
             return Task::ready(Err(AgentToolOutput::from_error("Context server not found")));
         };
+        let server_name = SharedString::from(self.server_id.0.clone());
         let tool_name = self.tool.name.clone();
         let tool_id = mcp_tool_id(&self.server_id.0, &self.tool.name);
         let display_name = self.tool.name.clone();
@@ -353,6 +354,23 @@ impl AnyAgentTool for ContextServerTool {
                 return Err(AgentToolOutput::from_error("Context server not initialized"));
             };
 
+            // Servers may ask the user for more input while the tool is running. The
+            // progress token ties their requests to this call rather than to another
+            // call running on the same server.
+            let progress_token = context_server::types::ProgressToken::String(
+                event_stream.tool_use_id().to_string(),
+            );
+            let _elicitation_subscription = protocol
+                .on_request_for_progress_token::<context_server::types::requests::ElicitationCreate>(
+                    progress_token.clone(),
+                    {
+                        let event_stream = event_stream.clone();
+                        move |request, cx| {
+                            cx.background_spawn(event_stream.elicit(server_name.clone(), request))
+                        }
+                    },
+                );
+
             let arguments = if let serde_json::Value::Object(map) = input {
                 Some(map.into_iter().collect())
             } else {
@@ -369,7 +387,10 @@ impl AnyAgentTool for ContextServerTool {
                 context_server::types::CallToolParams {
                     name: tool_name,
                     arguments,
-                    meta: None,
+                    meta: Some(HashMap::from_iter([(
+                        "progressToken".to_string(),
+                        serde_json::to_value(progress_token).unwrap_or_default(),
+                    )])),
                 },
             );
 
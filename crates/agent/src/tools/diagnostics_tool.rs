@@ -168,3 +168,90 @@ impl AgentTool for DiagnosticsTool {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::code_navigation::tests::project_with_fake_language_server;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_diagnostics(cx: &mut TestAppContext) {
+        let (project, fake_language_server, _handle) = project_with_fake_language_server(
+            json!({
+                "src": {
+                    "lib.rs": "pub fn load() {}\n",
+                    "main.rs": "fn main() {\n    lib::lod();\n}\n",
+                }
+            }),
+            lsp::ServerCapabilities::default(),
+            cx,
+        )
+        .await;
+        fake_language_server.notify::<lsp::notification::PublishDiagnostics>(
+            lsp::PublishDiagnosticsParams {
+                uri: lsp::Uri::from_file_path(path!("/root/src/main.rs")).unwrap(),
+                diagnostics: vec![
+                    lsp::Diagnostic {
+                        range: lsp::Range::new(lsp::Position::new(1, 9), lsp::Position::new(1, 12)),
+                        severity: Some(lsp::DiagnosticSeverity::ERROR),
+                        message: "cannot find function `lod` in crate `lib`".into(),
+                        ..Default::default()
+                    },
+                    lsp::Diagnostic {
+                        range: lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 7)),
+                        severity: Some(lsp::DiagnosticSeverity::HINT),
+                        message: "consider documenting `main`".into(),
+                        ..Default::default()
+                    },
+                ],
+                version: None,
+            },
+        );
+        cx.run_until_parked();
+
+        let tool = Arc::new(DiagnosticsTool::new(project));
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let output = cx
+            .update(|cx| {
+                tool.clone().run(
+                    DiagnosticsToolInput {
+                        path: Some("root/src/main.rs".into()),
+                    },
+                    event_stream.clone(),
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            "error at line 2: cannot find function `lod` in crate `lib`\n"
+        );
+
+        let output = cx
+            .update(|cx| {
+                tool.clone().run(
+                    DiagnosticsToolInput {
+                        path: Some("root/src/lib.rs".into()),
+                    },
+                    event_stream.clone(),
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert_eq!(output, "File doesn't have errors or warnings!");
+
+        let output = cx
+            .update(|cx| tool.run(DiagnosticsToolInput { path: None }, event_stream, cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            format!("{}: 1 error(s), 0 warning(s)\n", path!("/root/src/main.rs"))
+        );
+    }
+}
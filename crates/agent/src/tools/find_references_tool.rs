@@ -0,0 +1,190 @@
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use util::markdown::MarkdownInlineCode;
+
+use super::code_navigation::{find_symbol, format_locations};
+
+/// Finds all references to a symbol across the project, using the project's language servers.
+///
+/// Prefer this to `grep` before changing a symbol's signature or behavior: it finds trait implementations, calls through re-exports and aliases, and skips unrelated symbols that happen to share the name.
+///
+/// <example>
+/// To find the references to the `Config` struct defined on line 10 of `src/config.rs`:
+/// {
+///     "path": "project/src/config.rs",
+///     "line": 10,
+///     "symbol": "Config"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindReferencesToolInput {
+    /// The path of a file containing the symbol's definition or one of its usages.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The line the symbol appears on (1-based index).
+    pub line: u32,
+    /// The symbol's name, exactly as it appears on the line.
+    pub symbol: String,
+}
+
+pub struct FindReferencesTool {
+    project: Entity<Project>,
+}
+
+impl FindReferencesTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for FindReferencesTool {
+    type Input = FindReferencesToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "find_references";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        if let Ok(input) = input {
+            format!("Find references to {}", MarkdownInlineCode(&input.symbol)).into()
+        } else {
+            "Find references".into()
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let symbol = find_symbol(&project, &input.path, input.line, &input.symbol, cx)
+                .await
+                .map_err(|e| e.to_string())?;
+            let references = project.update(cx, |project, cx| {
+                project.references(&symbol.buffer, symbol.position, cx)
+            });
+            let references = futures::select! {
+                result = references.fuse() => result.map_err(|e| e.to_string())?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Find references cancelled by user".to_string());
+                }
+            };
+
+            let Some(references) = references else {
+                return Err(format!(
+                    "No language server for {} supports finding references.",
+                    input.path
+                ));
+            };
+            if references.is_empty() {
+                return Ok(format!("No references found for `{}`.", input.symbol));
+            }
+
+            let count = references.len();
+            let output = cx.update(|cx| {
+                format_locations(
+                    references
+                        .into_iter()
+                        .map(|location| (location.buffer, location.range)),
+                    cx,
+                )
+            });
+            Ok(format!(
+                "Found {count} references to `{}`:\n{output}",
+                input.symbol
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::code_navigation::tests::project_with_fake_language_server;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_find_references(cx: &mut TestAppContext) {
+        let (project, fake_language_server, _handle) = project_with_fake_language_server(
+            json!({
+                "src": {
+                    "lib.rs": "pub fn load() {}\n",
+                    "main.rs": "fn main() {\n    lib::load();\n}\n",
+                }
+            }),
+            lsp::ServerCapabilities {
+                references_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        fake_language_server.set_request_handler::<lsp::request::References, _, _>(
+            |params, _| async move {
+                assert_eq!(
+                    params.text_document_position.position,
+                    lsp::Position::new(0, 7)
+                );
+                Ok(Some(vec![
+                    lsp::Location {
+                        uri: lsp::Uri::from_file_path(path!("/root/src/lib.rs")).unwrap(),
+                        range: lsp::Range::new(lsp::Position::new(0, 7), lsp::Position::new(0, 11)),
+                    },
+                    lsp::Location {
+                        uri: lsp::Uri::from_file_path(path!("/root/src/main.rs")).unwrap(),
+                        range: lsp::Range::new(lsp::Position::new(1, 9), lsp::Position::new(1, 13)),
+                    },
+                ]))
+            },
+        );
+
+        let tool = Arc::new(FindReferencesTool::new(project));
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let output = cx
+            .update(|cx| {
+                tool.run(
+                    FindReferencesToolInput {
+                        path: "root/src/lib.rs".into(),
+                        line: 1,
+                        symbol: "load".into(),
+                    },
+                    event_stream,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+
+        assert!(
+            output.starts_with("Found 2 references to `load`:"),
+            "unexpected output: {output}"
+        );
+        assert!(output.contains("## root/src/lib.rs\n\n### L1\n```\npub fn load() {}\n\n```"));
+        assert!(
+            output.contains(
+                "## root/src/main.rs\n\n### L2\n```\nfn main() {\n    lib::load();\n}\n```"
+            )
+        );
+    }
+}
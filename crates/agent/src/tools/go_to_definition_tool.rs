@@ -0,0 +1,113 @@
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use util::markdown::MarkdownInlineCode;
+
+use super::code_navigation::{find_symbol, format_locations};
+
+/// Finds where a symbol is defined, using the project's language servers.
+///
+/// Unlike `grep`, this resolves the symbol like the compiler does, so it finds the right definition even when the name is shadowed, imported under another name, or defined in a dependency.
+///
+/// <example>
+/// To find the definition of `parse_config` called on line 42 of `src/main.rs`:
+/// {
+///     "path": "project/src/main.rs",
+///     "line": 42,
+///     "symbol": "parse_config"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GoToDefinitionToolInput {
+    /// The path of the file containing a usage of the symbol.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The line the symbol appears on (1-based index).
+    pub line: u32,
+    /// The symbol's name, exactly as it appears on the line.
+    pub symbol: String,
+}
+
+pub struct GoToDefinitionTool {
+    project: Entity<Project>,
+}
+
+impl GoToDefinitionTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for GoToDefinitionTool {
+    type Input = GoToDefinitionToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "go_to_definition";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        if let Ok(input) = input {
+            format!("Go to definition of {}", MarkdownInlineCode(&input.symbol)).into()
+        } else {
+            "Go to definition".into()
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let symbol = find_symbol(&project, &input.path, input.line, &input.symbol, cx)
+                .await
+                .map_err(|e| e.to_string())?;
+            let definitions = project.update(cx, |project, cx| {
+                project.definitions(&symbol.buffer, symbol.position, cx)
+            });
+            let definitions = futures::select! {
+                result = definitions.fuse() => result.map_err(|e| e.to_string())?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Go to definition cancelled by user".to_string());
+                }
+            };
+
+            let Some(definitions) = definitions else {
+                return Err(format!(
+                    "No language server for {} supports going to definitions.",
+                    input.path
+                ));
+            };
+            if definitions.is_empty() {
+                return Ok(format!("No definition found for `{}`.", input.symbol));
+            }
+
+            let output = cx.update(|cx| {
+                format_locations(
+                    definitions
+                        .into_iter()
+                        .map(|link| (link.target.buffer, link.target.range)),
+                    cx,
+                )
+            });
+            Ok(format!("Definitions of `{}`:\n{output}", input.symbol))
+        })
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+use super::project_tasks::project_tasks;
+
+const MAX_TASKS: usize = 100;
+
+/// Lists the tasks that can be run with the `run_task` tool, such as the project's build and test commands.
+///
+/// - Without a path, this lists the tasks defined in the project's and the user's tasks files.
+/// - With a path, it also lists the tasks provided by the file's language, along with a task for each runnable in the file, such as a single test or test module. Use this to find the task that runs the tests you're working on.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListTasksToolInput {
+    /// The path of a file to include language and runnable tasks for.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+pub struct ListTasksTool {
+    project: Entity<Project>,
+}
+
+impl ListTasksTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for ListTasksTool {
+    type Input = ListTasksToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "list_tasks";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Read
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(ListTasksToolInput { path: Some(path) }) => {
+                format!("List tasks for {}", MarkdownInlineCode(&path)).into()
+            }
+            _ => "List tasks".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        _event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let tasks = project_tasks(&project, input.path.as_deref(), cx)
+                .await
+                .map_err(|e| e.to_string())?;
+            if tasks.is_empty() {
+                return Ok("No tasks found.".to_string());
+            }
+
+            let mut output = String::from("Tasks that can be run with `run_task`:\n");
+            for task in tasks.iter().take(MAX_TASKS) {
+                writeln!(
+                    output,
+                    "- {} ({})",
+                    MarkdownInlineCode(task.label()),
+                    task.source_description()
+                )
+                .ok();
+            }
+            if tasks.len() > MAX_TASKS {
+                writeln!(
+                    output,
+                    "\n{} more tasks not shown.",
+                    tasks.len() - MAX_TASKS
+                )
+                .ok();
+            }
+            Ok(output)
+        })
+    }
+}
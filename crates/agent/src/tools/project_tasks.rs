@@ -0,0 +1,207 @@
+//! Helpers shared by the tools that list and run the project's tasks.
+
+use anyhow::{Context as _, Result};
+use gpui::{AsyncApp, Entity};
+use language::{Buffer, Point};
+use project::{Location, Project, TaskSourceKind};
+use std::path::Path;
+use task::{ResolvedTask, TaskContext, TaskTemplate, TaskVariables, VariableName};
+
+/// A task that's resolved and ready to run.
+pub(crate) struct ProjectTask {
+    pub source: TaskSourceKind,
+    pub task: ResolvedTask,
+    /// The one-based line of the runnable the task was resolved for, such as a test function.
+    pub line: Option<u32>,
+}
+
+impl ProjectTask {
+    pub fn label(&self) -> &str {
+        &self.task.resolved_label
+    }
+
+    /// Describes where the task is defined.
+    pub fn source_description(&self) -> String {
+        match (&self.source, self.line) {
+            (_, Some(line)) => format!("runnable on line {line}"),
+            (TaskSourceKind::Worktree { .. }, None) => "project tasks file".to_string(),
+            (TaskSourceKind::AbsPath { .. }, None) => "global tasks file".to_string(),
+            (TaskSourceKind::Language { name }, None) => format!("{name} language tasks"),
+            (TaskSourceKind::Lsp { language_name, .. }, None) => {
+                format!("{language_name} language server")
+            }
+            (TaskSourceKind::UserInput, None) => "previously run command".to_string(),
+        }
+    }
+}
+
+/// Resolves the tasks defined in the project's and the user's tasks files.
+///
+/// When a `path` is given, the tasks provided by the file's language are included too,
+/// along with the tasks for each runnable in the file, such as `cargo test` for a single
+/// Rust test.
+pub(crate) async fn project_tasks(
+    project: &Entity<Project>,
+    path: Option<&str>,
+    cx: &mut AsyncApp,
+) -> Result<Vec<ProjectTask>> {
+    let inventory = project
+        .read_with(cx, |project, cx| {
+            project.task_store().read(cx).task_inventory().cloned()
+        })
+        .context("Tasks aren't available in this project")?;
+
+    let buffer = match path {
+        Some(path) => Some(open_buffer(project, path, cx).await?),
+        None => None,
+    };
+    let (file, language, worktree) = match &buffer {
+        Some(buffer) => buffer.read_with(cx, |buffer, cx| {
+            let file = buffer.file().cloned();
+            let worktree_id = file.as_ref().map(|file| file.worktree_id(cx));
+            let worktree = worktree_id
+                .and_then(|worktree_id| project.read(cx).worktree_for_id(worktree_id, cx));
+            (file, buffer.language().cloned(), worktree)
+        }),
+        None => (
+            None,
+            None,
+            project.read_with(cx, |project, cx| project.visible_worktrees(cx).next()),
+        ),
+    };
+    let worktree_id = worktree
+        .as_ref()
+        .map(|worktree| worktree.read_with(cx, |worktree, _| worktree.id()));
+    let worktree_root = worktree
+        .as_ref()
+        .map(|worktree| worktree.read_with(cx, |worktree, _| worktree.abs_path()));
+
+    let templates = inventory
+        .update(cx, |inventory, cx| {
+            inventory.list_tasks(file, language, worktree_id, cx)
+        })
+        .await;
+
+    let mut context = match &buffer {
+        Some(buffer) => {
+            task_context_at(project, buffer, Point::zero(), TaskVariables::default(), cx).await
+        }
+        None => None,
+    };
+    if context.is_none() {
+        context = worktree_root.as_deref().map(worktree_context);
+    }
+    let context = context.unwrap_or_default();
+
+    let mut tasks = templates
+        .iter()
+        .filter_map(|(source, template)| {
+            Some(ProjectTask {
+                source: source.clone(),
+                task: template.resolve_task(&source.to_id_base(), &context)?,
+                line: None,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(buffer) = &buffer {
+        tasks.extend(runnable_tasks(project, buffer, &templates, cx).await);
+    }
+    Ok(tasks)
+}
+
+/// Resolves the tasks tagged for each runnable in the buffer, such as a test function.
+async fn runnable_tasks(
+    project: &Entity<Project>,
+    buffer: &Entity<Buffer>,
+    templates: &[(TaskSourceKind, TaskTemplate)],
+    cx: &mut AsyncApp,
+) -> Vec<ProjectTask> {
+    let runnables = buffer.read_with(cx, |buffer, _| {
+        let snapshot = buffer.snapshot();
+        snapshot
+            .runnable_ranges(0..snapshot.len())
+            .map(|runnable| {
+                let position = snapshot.offset_to_point(runnable.run_range.start);
+                (position, runnable.runnable.tags, runnable.extra_captures)
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let mut tasks = Vec::new();
+    for (position, tags, captures) in runnables {
+        let mut variables = TaskVariables::default();
+        for (name, value) in captures {
+            variables.insert(VariableName::Custom(name.into()), value);
+        }
+        let Some(context) = task_context_at(project, buffer, position, variables, cx).await else {
+            continue;
+        };
+        for (source, template) in templates {
+            let tagged = tags.iter().any(|tag| {
+                template
+                    .tags
+                    .iter()
+                    .any(|template_tag| template_tag.as_str() == tag.0.as_ref())
+            });
+            if !tagged {
+                continue;
+            }
+            if let Some(task) = template.resolve_task(&source.to_id_base(), &context) {
+                tasks.push(ProjectTask {
+                    source: source.clone(),
+                    task,
+                    line: Some(position.row + 1),
+                });
+            }
+        }
+    }
+    tasks
+}
+
+async fn open_buffer(
+    project: &Entity<Project>,
+    path: &str,
+    cx: &mut AsyncApp,
+) -> Result<Entity<Buffer>> {
+    let project_path = project
+        .read_with(cx, |project, cx| project.find_project_path(path, cx))
+        .with_context(|| format!("Could not find path {path} in project"))?;
+    project
+        .update(cx, |project, cx| project.open_buffer(project_path, cx))
+        .await
+}
+
+async fn task_context_at(
+    project: &Entity<Project>,
+    buffer: &Entity<Buffer>,
+    position: Point,
+    variables: TaskVariables,
+    cx: &mut AsyncApp,
+) -> Option<TaskContext> {
+    let anchor = buffer.read_with(cx, |buffer, _| buffer.anchor_before(position));
+    let location = Location {
+        buffer: buffer.clone(),
+        range: anchor..anchor,
+    };
+    project
+        .update(cx, |project, cx| {
+            project.task_store().update(cx, |task_store, cx| {
+                task_store.task_context_for_location(variables, location, cx)
+            })
+        })
+        .await
+}
+
+fn worktree_context(worktree_root: &Path) -> TaskContext {
+    let mut task_variables = TaskVariables::default();
+    task_variables.insert(
+        VariableName::WorktreeRoot,
+        worktree_root.to_string_lossy().into_owned(),
+    );
+    TaskContext {
+        cwd: Some(worktree_root.to_path_buf()),
+        task_variables,
+        project_env: Default::default(),
+    }
+}
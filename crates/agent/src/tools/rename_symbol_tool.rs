@@ -0,0 +1,484 @@
+use crate::{AgentTool, EditFileTool, Thread, ToolCallEventStream};
+use action_log::ActionLog;
+use agent_client_protocol as acp;
+use anyhow::Result;
+use collections::HashSet;
+use futures::FutureExt as _;
+use gpui::{App, AsyncApp, Entity, SharedString, Task, WeakEntity};
+use project::{Project, ProjectPath};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{path::Path, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+use super::code_navigation::{buffers_will_be_edited, find_symbol, finish_project_transaction};
+use super::tool_permissions::authorize_file_edit;
+
+/// Renames a symbol everywhere it's used, using the project's language servers.
+///
+/// Always prefer this to editing each usage by hand when renaming a variable, function, type, field, or module. The language server updates every reference, including trait implementations and re-exports, and leaves unrelated symbols with the same name alone.
+///
+/// The edited files are saved. Use the `diagnostics` tool afterwards if you want to double-check the result.
+///
+/// <example>
+/// To rename the `load` method defined on line 25 of `src/config.rs` to `load_from_disk`:
+/// {
+///     "path": "project/src/config.rs",
+///     "line": 25,
+///     "symbol": "load",
+///     "new_name": "load_from_disk"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RenameSymbolToolInput {
+    /// The path of a file containing the symbol's definition or one of its usages.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The line the symbol appears on (1-based index).
+    pub line: u32,
+    /// The symbol's current name, exactly as it appears on the line.
+    pub symbol: String,
+    /// The symbol's new name.
+    pub new_name: String,
+}
+
+pub struct RenameSymbolTool {
+    thread: WeakEntity<Thread>,
+    project: Entity<Project>,
+    action_log: Entity<ActionLog>,
+}
+
+impl RenameSymbolTool {
+    pub fn new(
+        thread: WeakEntity<Thread>,
+        project: Entity<Project>,
+        action_log: Entity<ActionLog>,
+    ) -> Self {
+        Self {
+            thread,
+            project,
+            action_log,
+        }
+    }
+}
+
+impl AgentTool for RenameSymbolTool {
+    type Input = RenameSymbolToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "rename_symbol";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Edit
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        if let Ok(input) = input {
+            format!(
+                "Rename {} to {}",
+                MarkdownInlineCode(&input.symbol),
+                MarkdownInlineCode(&input.new_name)
+            )
+            .into()
+        } else {
+            "Rename symbol".into()
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        // Renames are configured under `edit_file` in the tool permission settings,
+        // since they edit files just like it does.
+        let authorize = authorize_file_edit(
+            EditFileTool::NAME,
+            Path::new(&input.path),
+            &format!("Rename `{}` to `{}`", input.symbol, input.new_name),
+            &self.thread,
+            &event_stream,
+            cx,
+        );
+        let project = self.project.clone();
+        let action_log = self.action_log.clone();
+        let thread = self.thread.clone();
+        cx.spawn(async move |cx| {
+            authorize.await.map_err(|e| e.to_string())?;
+
+            let symbol = find_symbol(&project, &input.path, input.line, &input.symbol, cx)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            // The rename edits the same places the references are in, so mark those
+            // as read first to show the rename's edits in the action log.
+            let references = project.update(cx, |project, cx| {
+                project.references(&symbol.buffer, symbol.position, cx)
+            });
+            let references = references.await.ok().flatten().unwrap_or_default();
+            let buffers = references
+                .into_iter()
+                .map(|location| location.buffer)
+                .chain([symbol.buffer.clone()])
+                .collect::<Vec<_>>();
+
+            let preview = project
+                .update(cx, |project, cx| {
+                    project.preview_rename(
+                        &symbol.buffer,
+                        symbol.position,
+                        input.new_name.clone(),
+                        cx,
+                    )
+                })
+                .await
+                .map_err(|e| e.to_string())?;
+            // Every file the rename touches has to be authorized before it's applied, not
+            // just the one the symbol is in, which was authorized above. Remote projects
+            // can't preview renames, so there the symbol's references are authorized first,
+            // and any other file the rename edited once it's been performed.
+            let (symbol_path, symbol_abs_path) = cx.update(|cx| {
+                let file = symbol.buffer.read(cx).file();
+                (
+                    file.map(|file| file.full_path(cx)),
+                    file.and_then(|file| {
+                        project.read(cx).absolute_path(
+                            &ProjectPath {
+                                worktree_id: file.worktree_id(cx),
+                                path: file.path().clone(),
+                            },
+                            cx,
+                        )
+                    }),
+                )
+            });
+            let mut authorized_paths =
+                HashSet::from_iter(symbol_path.into_iter().chain(symbol_abs_path));
+            let paths = match &preview {
+                Some(preview) => preview.paths(),
+                None => cx.update(|cx| {
+                    buffers
+                        .iter()
+                        .filter_map(|buffer| Some(buffer.read(cx).file()?.full_path(cx)))
+                        .collect()
+                }),
+            };
+            for path in paths {
+                if authorized_paths.insert(path.clone()) {
+                    authorize_rename_in(&path, &input, &thread, &event_stream, cx)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+            buffers_will_be_edited(buffers, &action_log, cx);
+
+            let is_previewed = preview.is_some();
+            let rename = project.update(cx, |project, cx| match preview {
+                Some(preview) => project.apply_rename_preview(preview, cx),
+                None => project.perform_rename(
+                    symbol.buffer.clone(),
+                    symbol.position,
+                    input.new_name.clone(),
+                    cx,
+                ),
+            });
+            let transaction = futures::select! {
+                result = rename.fuse() => result.map_err(|e| e.to_string())?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Rename cancelled by user".to_string());
+                }
+            };
+
+            if !is_previewed {
+                let unauthorized_paths = cx.update(|cx| {
+                    transaction
+                        .0
+                        .keys()
+                        .filter_map(|buffer| Some(buffer.read(cx).file()?.full_path(cx)))
+                        .filter(|path| !authorized_paths.contains(path))
+                        .collect::<Vec<_>>()
+                });
+                for path in unauthorized_paths {
+                    let authorize = authorize_rename_in(&path, &input, &thread, &event_stream, cx);
+                    if let Err(error) = authorize.await {
+                        cx.update(|cx| {
+                            for (buffer, transaction) in &transaction.0 {
+                                buffer.update(cx, |buffer, cx| {
+                                    buffer.undo_transaction(transaction.id, cx);
+                                });
+                            }
+                        });
+                        return Err(error.to_string());
+                    }
+                }
+            }
+
+            let summary = finish_project_transaction(transaction, &project, &action_log, cx)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(format!(
+                "Renamed `{}` to `{}`. {summary}",
+                input.symbol, input.new_name
+            ))
+        })
+    }
+}
+
+/// Asks to authorize the rename's edits to the file at `path`.
+fn authorize_rename_in(
+    path: &Path,
+    input: &RenameSymbolToolInput,
+    thread: &WeakEntity<Thread>,
+    event_stream: &ToolCallEventStream,
+    cx: &mut AsyncApp,
+) -> Task<Result<()>> {
+    cx.update(|cx| {
+        authorize_file_edit(
+            EditFileTool::NAME,
+            path,
+            &format!(
+                "Rename `{}` to `{}` in {}",
+                input.symbol,
+                input.new_name,
+                MarkdownInlineCode(&path.to_string_lossy())
+            ),
+            thread,
+            event_stream,
+            cx,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::code_navigation::tests::project_with_fake_language_server;
+    use crate::{ContextServerRegistry, Templates};
+    use fs::Fs as _;
+    use gpui::{AppContext as _, TestAppContext};
+    use language_model::fake_provider::FakeLanguageModel;
+    use prompt_store::ProjectContext;
+    use serde_json::json;
+    use settings::Settings as _;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_rename_symbol(cx: &mut TestAppContext) {
+        let (project, _fake_language_server, _handle) = setup(cx).await;
+        let (tool, _thread) = rename_tool(&project, cx);
+
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let output = cx
+            .update(|cx| tool.run(rename_input(), event_stream, cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            "Renamed `load` to `load_from_disk`. Edited 2 files:\n- root/src/lib.rs\n- root/src/main.rs\n"
+        );
+
+        let fs = project.read_with(cx, |project, _| project.fs().clone());
+        assert_eq!(
+            fs.load(path!("/root/src/lib.rs").as_ref()).await.unwrap(),
+            "pub fn load_from_disk() {}\n"
+        );
+        assert_eq!(
+            fs.load(path!("/root/src/main.rs").as_ref()).await.unwrap(),
+            "fn main() {\n    lib::load_from_disk();\n}\n"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_rename_symbol_denied_in_one_file(cx: &mut TestAppContext) {
+        let (project, _fake_language_server, _handle) = setup(cx).await;
+        let (tool, _thread) = rename_tool(&project, cx);
+
+        // The symbol is defined in an allowed file, but the rename also edits one that
+        // isn't, so none of it should be applied.
+        cx.update(|cx| {
+            let mut settings = agent_settings::AgentSettings::get_global(cx).clone();
+            settings.tool_permissions.tools.insert(
+                EditFileTool::NAME.into(),
+                agent_settings::ToolRules {
+                    default: Some(settings::ToolPermissionMode::Allow),
+                    always_allow: vec![],
+                    always_deny: vec![
+                        agent_settings::CompiledRegex::new(r"main\.rs$", false).unwrap(),
+                    ],
+                    always_confirm: vec![],
+                    invalid_patterns: vec![],
+                },
+            );
+            agent_settings::AgentSettings::override_global(settings, cx);
+        });
+
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let result = cx
+            .update(|cx| tool.run(rename_input(), event_stream, cx))
+            .await;
+        assert!(result.is_err(), "unexpected result: {result:?}");
+
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/root/src/lib.rs"), cx)
+            })
+            .await
+            .unwrap();
+        buffer.read_with(cx, |buffer, _| {
+            assert_eq!(buffer.text(), "pub fn load() {}\n");
+            assert!(!buffer.is_dirty());
+        });
+        let fs = project.read_with(cx, |project, _| project.fs().clone());
+        assert_eq!(
+            fs.load(path!("/root/src/main.rs").as_ref()).await.unwrap(),
+            "fn main() {\n    lib::load();\n}\n"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_rename_symbol_asks_once_per_file(cx: &mut TestAppContext) {
+        let (project, _fake_language_server, _handle) = setup(cx).await;
+        let (tool, _thread) = rename_tool(&project, cx);
+        cx.update(|cx| {
+            let mut settings = agent_settings::AgentSettings::get_global(cx).clone();
+            settings.tool_permissions.tools.insert(
+                EditFileTool::NAME.into(),
+                agent_settings::ToolRules {
+                    default: Some(settings::ToolPermissionMode::Confirm),
+                    always_allow: vec![],
+                    always_deny: vec![],
+                    always_confirm: vec![],
+                    invalid_patterns: vec![],
+                },
+            );
+            agent_settings::AgentSettings::override_global(settings, cx);
+        });
+
+        // The file the symbol is in was already authorized, so only the other file
+        // the rename edits is asked about.
+        let (event_stream, mut event_rx) = ToolCallEventStream::test();
+        let task = cx.update(|cx| tool.run(rename_input(), event_stream, cx));
+        let mut titles = Vec::new();
+        for _ in 0..2 {
+            let authorization = event_rx.expect_authorization().await;
+            titles.push(authorization.tool_call.fields.title.unwrap());
+            authorization
+                .response
+                .send(acp::PermissionOptionId::new("allow"))
+                .unwrap();
+        }
+        task.await.unwrap();
+        assert!(!titles[0].contains("main.rs"), "{titles:?}");
+        assert!(titles[1].contains("main.rs"), "{titles:?}");
+    }
+
+    async fn setup(
+        cx: &mut TestAppContext,
+    ) -> (
+        Entity<Project>,
+        lsp::FakeLanguageServer,
+        project::lsp_store::OpenLspBufferHandle,
+    ) {
+        let (project, fake_language_server, handle) = project_with_fake_language_server(
+            json!({
+                "src": {
+                    "lib.rs": "pub fn load() {}\n",
+                    "main.rs": "fn main() {\n    lib::load();\n}\n",
+                }
+            }),
+            lsp::ServerCapabilities {
+                references_provider: Some(lsp::OneOf::Left(true)),
+                rename_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        let lib_uri = lsp::Uri::from_file_path(path!("/root/src/lib.rs")).unwrap();
+        let main_uri = lsp::Uri::from_file_path(path!("/root/src/main.rs")).unwrap();
+        let definition = lsp::Range::new(lsp::Position::new(0, 7), lsp::Position::new(0, 11));
+        let usage = lsp::Range::new(lsp::Position::new(1, 9), lsp::Position::new(1, 13));
+        fake_language_server.set_request_handler::<lsp::request::References, _, _>({
+            let lib_uri = lib_uri.clone();
+            let main_uri = main_uri.clone();
+            move |_, _| {
+                let lib_uri = lib_uri.clone();
+                let main_uri = main_uri.clone();
+                async move {
+                    Ok(Some(vec![
+                        lsp::Location::new(lib_uri, definition),
+                        lsp::Location::new(main_uri, usage),
+                    ]))
+                }
+            }
+        });
+        fake_language_server.set_request_handler::<lsp::request::Rename, _, _>(move |params, _| {
+            let lib_uri = lib_uri.clone();
+            let main_uri = main_uri.clone();
+            async move {
+                assert_eq!(
+                    params.text_document_position.position,
+                    lsp::Position::new(0, 7)
+                );
+                Ok(Some(lsp::WorkspaceEdit {
+                    changes: Some(
+                        [
+                            (
+                                lib_uri,
+                                vec![lsp::TextEdit::new(definition, params.new_name.clone())],
+                            ),
+                            (main_uri, vec![lsp::TextEdit::new(usage, params.new_name)]),
+                        ]
+                        .into_iter()
+                        .collect(),
+                    ),
+                    ..Default::default()
+                }))
+            }
+        });
+
+        (project, fake_language_server, handle)
+    }
+
+    fn rename_tool(
+        project: &Entity<Project>,
+        cx: &mut TestAppContext,
+    ) -> (Arc<RenameSymbolTool>, Entity<Thread>) {
+        let context_server_registry =
+            cx.new(|cx| ContextServerRegistry::new(project.read(cx).context_server_store(), cx));
+        let thread = cx.new(|cx| {
+            Thread::new(
+                project.clone(),
+                cx.new(|_cx| ProjectContext::default()),
+                context_server_registry,
+                Templates::new(),
+                Some(Arc::new(FakeLanguageModel::default())),
+                cx,
+            )
+        });
+        let action_log = thread.read_with(cx, |thread, _| thread.action_log().clone());
+        let tool = Arc::new(RenameSymbolTool::new(
+            thread.downgrade(),
+            project.clone(),
+            action_log,
+        ));
+        (tool, thread)
+    }
+
+    fn rename_input() -> RenameSymbolToolInput {
+        RenameSymbolToolInput {
+            path: "root/src/lib.rs".into(),
+            line: 1,
+            symbol: "load".into(),
+            new_name: "load_from_disk".into(),
+        }
+    }
+}
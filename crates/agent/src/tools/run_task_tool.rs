@@ -0,0 +1,257 @@
+use crate::{
+    AgentTool, TerminalTool, Thread, ThreadEnvironment, ToolCallEventStream,
+    ToolPermissionDecision, decide_permission_from_settings,
+};
+use agent_client_protocol as acp;
+use agent_settings::AgentSettings;
+use anyhow::Result;
+use gpui::{App, Entity, SharedString, Task, WeakEntity};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings as _;
+use std::{rc::Rc, sync::Arc, time::Duration};
+use task::SpawnInTerminal;
+use util::{
+    markdown::MarkdownInlineCode,
+    shell::{Shell, ShellKind},
+    shell_builder::ShellBuilder,
+};
+
+use super::project_tasks::project_tasks;
+use super::terminal_tool::{
+    COMMAND_OUTPUT_LIMIT, CommandOutput, process_content, run_command, sandbox_policy,
+    sandboxed_command,
+};
+use super::test_output::parse_test_output;
+
+/// How much of a task's output is read, so that test results printed at the end of a
+/// long run can still be summarized.
+const TASK_OUTPUT_LIMIT: u64 = 1024 * 1024;
+
+/// Runs one of the tasks listed by the `list_tasks` tool and waits for it to finish.
+///
+/// When the task runs tests with `cargo test`, `pytest`, `go test` or `jest`, the result is a summary of the test run: the number of passed, failed and skipped tests, the names of the failing tests, and the (truncated) output of each failure. Otherwise, the result is the task's output, as with the `terminal` tool.
+///
+/// Prefer running a runnable task for the tests you're working on over running the whole test suite, and rerun it after each fix.
+///
+/// <example>
+/// To run a single Rust test listed for `src/parser.rs`:
+/// {
+///     "label": "cargo test -p parser -- tests::test_parse_empty --exact --nocapture",
+///     "path": "project/src/parser.rs"
+/// }
+/// </example>
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RunTaskToolInput {
+    /// The label of the task, exactly as listed by `list_tasks`.
+    pub label: String,
+    /// The path that was passed to `list_tasks` when the task was listed, if any.
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Optional maximum runtime (in milliseconds). If exceeded, the task is killed.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+pub struct RunTaskTool {
+    project: Entity<Project>,
+    environment: Rc<dyn ThreadEnvironment>,
+    thread: WeakEntity<Thread>,
+}
+
+impl RunTaskTool {
+    pub fn new(
+        project: Entity<Project>,
+        environment: Rc<dyn ThreadEnvironment>,
+        thread: WeakEntity<Thread>,
+    ) -> Self {
+        Self {
+            project,
+            environment,
+            thread,
+        }
+    }
+}
+
+impl AgentTool for RunTaskTool {
+    type Input = RunTaskToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "run_task";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Execute
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        if let Ok(input) = input {
+            format!("Run task {}", MarkdownInlineCode(&input.label)).into()
+        } else {
+            "Run task".into()
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let sandbox_policy = match sandbox_policy(Some(&self.thread), &self.project, cx) {
+            Ok(policy) => policy,
+            Err(err) => return Task::ready(Err(err.to_string())),
+        };
+        let shell = ShellKind::new(
+            util::get_default_system_shell_preferring_bash(),
+            cfg!(windows),
+        );
+
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let tasks = project_tasks(&project, input.path.as_deref(), cx)
+                .await
+                .map_err(|e| e.to_string())?;
+            let task = tasks
+                .into_iter()
+                .find(|task| task.label() == input.label)
+                .ok_or_else(|| {
+                    format!(
+                        "No task labeled `{}` was found. Use `list_tasks` to see the available tasks.",
+                        input.label
+                    )
+                })?;
+            let spawn = task.task.resolved;
+            let command = task_command(&spawn, shell)
+                .ok_or_else(|| format!("Task `{}` has no command to run.", input.label))?;
+
+            // Tasks run their commands in a terminal, so they're governed by the terminal's
+            // permission rules.
+            let authorize = cx.update(|cx| {
+                match decide_permission_from_settings(
+                    TerminalTool::NAME,
+                    std::slice::from_ref(&command),
+                    AgentSettings::get_global(cx),
+                ) {
+                    ToolPermissionDecision::Allow => Ok(None),
+                    ToolPermissionDecision::Deny(reason) => Err(reason),
+                    ToolPermissionDecision::Confirm => {
+                        let context = crate::ToolPermissionContext::new(
+                            TerminalTool::NAME,
+                            vec![command.clone()],
+                        );
+                        Ok(Some(event_stream.authorize(
+                            format!("Run task `{}`", input.label),
+                            context,
+                            cx,
+                        )))
+                    }
+                }
+            })?;
+            if let Some(authorize) = authorize {
+                authorize.await.map_err(|e| e.to_string())?;
+            }
+
+            let extra_env = spawn
+                .env
+                .iter()
+                .map(|(name, value)| acp::EnvVariable::new(name.clone(), value.clone()))
+                .collect::<Vec<_>>();
+            let sandboxed = sandboxed_command(sandbox_policy.as_ref(), &command)?;
+            let CommandOutput {
+                mut output,
+                timed_out,
+                user_stopped,
+            } = run_command(
+                self.environment.as_ref(),
+                sandboxed.command.clone(),
+                extra_env,
+                spawn.cwd,
+                input.timeout_ms.map(Duration::from_millis),
+                TASK_OUTPUT_LIMIT,
+                &event_stream,
+                cx,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+            sandboxed.check_setup()?;
+
+            if !timed_out
+                && !user_stopped
+                && let Some(summary) = parse_test_output(&output.output)
+            {
+                let status = match output.exit_status.as_ref().and_then(|s| s.exit_code) {
+                    Some(exit_code) => format!("exited with code {exit_code}"),
+                    None => "terminated unexpectedly".to_string(),
+                };
+                return Ok(format!(
+                    "Task `{}` {status}.\n\n{}",
+                    input.label,
+                    summary.to_markdown()
+                ));
+            }
+
+            truncate_output(&mut output, COMMAND_OUTPUT_LIMIT as usize);
+            Ok(process_content(output, &command, timed_out, user_stopped))
+        })
+    }
+}
+
+/// Returns the command line that runs the task in the given shell, quoting its arguments.
+///
+/// Tasks that configure their own shell are run through it, with the whole invocation
+/// quoted for the terminal's shell.
+fn task_command(spawn: &SpawnInTerminal, shell: ShellKind) -> Option<String> {
+    let command = spawn.command.clone()?;
+    if !matches!(spawn.shell, Shell::System) {
+        let (program, args) = ShellBuilder::new(&spawn.shell, cfg!(windows))
+            .non_interactive()
+            .build(Some(command), &spawn.args);
+        return Some(quote_command_line(program, &args, shell));
+    }
+    if spawn.args.is_empty() {
+        return Some(command);
+    }
+
+    let mut command = shell
+        .try_quote_prefix_aware(&command)
+        .map(|command| command.into_owned())
+        .unwrap_or(command);
+    for arg in &spawn.args {
+        let arg = shell.to_shell_variable(arg);
+        command.push(' ');
+        command.push_str(&shell.try_quote(&arg).unwrap_or(arg.as_str().into()));
+    }
+    Some(command)
+}
+
+fn quote_command_line(program: String, args: &[String], shell: ShellKind) -> String {
+    let mut command = shell
+        .try_quote_prefix_aware(&program)
+        .map(|program| program.into_owned())
+        .unwrap_or(program);
+    for arg in args {
+        command.push(' ');
+        command.push_str(&shell.try_quote(arg).unwrap_or(arg.as_str().into()));
+    }
+    command
+}
+
+/// Truncates the output to its first `limit` bytes, at a line boundary, as the terminal
+/// does for commands run with the `terminal` tool.
+fn truncate_output(output: &mut acp::TerminalOutputResponse, limit: usize) {
+    if output.output.len() <= limit {
+        return;
+    }
+    let mut end_ix = limit;
+    while !output.output.is_char_boundary(end_ix) {
+        end_ix -= 1;
+    }
+    end_ix = output.output[..end_ix].rfind('\n').unwrap_or(end_ix);
+    output.output.truncate(end_ix);
+    output.truncated = true;
+}
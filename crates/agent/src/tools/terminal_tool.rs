@@ -2,8 +2,9 @@ use agent_client_protocol as acp;
 use agent_settings::AgentSettings;
 use anyhow::Result;
 use futures::FutureExt as _;
-use gpui::{App, Entity, SharedString, Task};
+use gpui::{App, AsyncApp, Entity, SharedString, Task, WeakEntity};
 use project::Project;
+use sandbox::{SandboxPolicy, SetupErrorFile};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
@@ -15,11 +16,11 @@ use std::{
 };
 
 use crate::{
-    AgentTool, ThreadEnvironment, ToolCallEventStream, ToolPermissionDecision,
+    AgentTool, Thread, ThreadEnvironment, ToolCallEventStream, ToolPermissionDecision,
     decide_permission_from_settings,
 };
 
-const COMMAND_OUTPUT_LIMIT: u64 = 16 * 1024;
+pub(crate) const COMMAND_OUTPUT_LIMIT: u64 = 16 * 1024;
 
 /// Executes a shell one-liner and returns the combined output.
 ///
@@ -50,6 +51,7 @@ pub struct TerminalToolInput {
 pub struct TerminalTool {
     project: Entity<Project>,
     environment: Rc<dyn ThreadEnvironment>,
+    thread: Option<WeakEntity<Thread>>,
 }
 
 impl TerminalTool {
@@ -57,8 +59,15 @@ impl TerminalTool {
         Self {
             project,
             environment,
+            thread: None,
         }
     }
+
+    /// Uses the thread's profile to decide how commands are sandboxed.
+    pub fn with_thread(mut self, thread: WeakEntity<Thread>) -> Self {
+        self.thread = Some(thread);
+        self
+    }
 }
 
 impl AgentTool for TerminalTool {
@@ -93,6 +102,10 @@ impl AgentTool for TerminalTool {
             Ok(dir) => dir,
             Err(err) => return Task::ready(Err(err.to_string())),
         };
+        let sandbox_policy = match sandbox_policy(self.thread.as_ref(), &self.project, cx) {
+            Ok(policy) => policy,
+            Err(err) => return Task::ready(Err(err.to_string())),
+        };
 
         let settings = AgentSettings::get_global(cx);
         let decision = decide_permission_from_settings(
@@ -117,81 +130,202 @@ impl AgentTool for TerminalTool {
                 authorize.await.map_err(|e| e.to_string())?;
             }
 
-            let terminal = self
-                .environment
-                .create_terminal(
-                    input.command.clone(),
-                    working_dir,
-                    Some(COMMAND_OUTPUT_LIMIT),
-                    cx,
-                )
-                .await
-                .map_err(|e| e.to_string())?;
-
-            let terminal_id = terminal.id(cx).map_err(|e| e.to_string())?;
-            event_stream.update_fields(acp::ToolCallUpdateFields::new().content(vec![
-                acp::ToolCallContent::Terminal(acp::Terminal::new(terminal_id)),
-            ]));
-
-            let timeout = input.timeout_ms.map(Duration::from_millis);
-
-            let mut timed_out = false;
-            let mut user_stopped_via_signal = false;
-            let wait_for_exit = terminal.wait_for_exit(cx).map_err(|e| e.to_string())?;
-
-            match timeout {
-                Some(timeout) => {
-                    let timeout_task = cx.background_executor().timer(timeout);
-
-                    futures::select! {
-                        _ = wait_for_exit.clone().fuse() => {},
-                        _ = timeout_task.fuse() => {
-                            timed_out = true;
-                            terminal.kill(cx).map_err(|e| e.to_string())?;
-                            wait_for_exit.await;
-                        }
-                        _ = event_stream.cancelled_by_user().fuse() => {
-                            user_stopped_via_signal = true;
-                            terminal.kill(cx).map_err(|e| e.to_string())?;
-                            wait_for_exit.await;
-                        }
-                    }
-                }
-                None => {
-                    futures::select! {
-                        _ = wait_for_exit.clone().fuse() => {},
-                        _ = event_stream.cancelled_by_user().fuse() => {
-                            user_stopped_via_signal = true;
-                            terminal.kill(cx).map_err(|e| e.to_string())?;
-                            wait_for_exit.await;
-                        }
-                    }
-                }
-            };
-
-            // Check if user stopped - we check both:
-            // 1. The cancellation signal from RunningTurn::cancel (e.g. user pressed main Stop button)
-            // 2. The terminal's user_stopped flag (e.g. user clicked Stop on the terminal card)
-            // Note: user_stopped_via_signal is already set above if we detected cancellation in the select!
-            // but we also check was_cancelled_by_user() for cases where cancellation happened after wait_for_exit completed
-            let user_stopped_via_signal =
-                user_stopped_via_signal || event_stream.was_cancelled_by_user();
-            let user_stopped_via_terminal = terminal.was_stopped_by_user(cx).unwrap_or(false);
-            let user_stopped = user_stopped_via_signal || user_stopped_via_terminal;
-
-            let output = terminal.current_output(cx).map_err(|e| e.to_string())?;
+            let command = sandboxed_command(sandbox_policy.as_ref(), &input.command)?;
 
-            Ok(process_content(
+            let CommandOutput {
                 output,
-                &input.command,
                 timed_out,
                 user_stopped,
-            ))
+            } = run_command(
+                self.environment.as_ref(),
+                command.command.clone(),
+                Vec::new(),
+                working_dir,
+                input.timeout_ms.map(Duration::from_millis),
+                COMMAND_OUTPUT_LIMIT,
+                &event_stream,
+                cx,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+            command.check_setup()?;
+            let failed = output
+                .exit_status
+                .as_ref()
+                .is_none_or(|status| status.exit_code != Some(0));
+            let sandbox_note = sandbox_policy
+                .as_ref()
+                .filter(|_| failed && !timed_out && !user_stopped)
+                .map(|policy| sandbox_note(policy.allow_network));
+
+            let content = process_content(output, &input.command, timed_out, user_stopped);
+            Ok(match sandbox_note {
+                Some(note) => format!("{content}\n\n{note}"),
+                None => content,
+            })
         })
     }
 }
 
-fn process_content(
+/// Returns the policy to run commands with, if the terminal sandbox is enabled for the
+/// thread's profile.
+pub(crate) fn sandbox_policy(
+    thread: Option<&WeakEntity<Thread>>,
+    project: &Entity<Project>,
+    cx: &App,
+) -> Result<Option<SandboxPolicy>> {
+    let profile_id = thread
+        .and_then(|thread| thread.upgrade())
+        .map(|thread| thread.read(cx).profile().clone());
+    let settings = AgentSettings::get_global(cx).terminal_sandbox(profile_id.as_ref());
+    if !settings.enabled {
+        return Ok(None);
+    }
+
+    let project = project.read(cx);
+    anyhow::ensure!(
+        sandbox::is_supported() && project.is_local(),
+        "The terminal sandbox is enabled, but it's only supported for local projects on x86_64 and aarch64 Linux. \
+        Ask the user to disable `agent.terminal_sandbox` to run commands."
+    );
+    let worktree_paths = project
+        .visible_worktrees(cx)
+        .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+        .collect::<Vec<_>>();
+    Ok(Some(SandboxPolicy::new(
+        settings.read_only_paths,
+        worktree_paths.into_iter().chain(settings.read_write_paths),
+        settings.allow_network,
+    )))
+}
+
+/// A command line returned by [`sandboxed_command`].
+pub(crate) struct SandboxedCommand {
+    pub command: String,
+    setup_error: Option<SetupErrorFile>,
+}
+
+impl SandboxedCommand {
+    /// Returns an error if the sandbox failed to start, in which case the command never
+    /// ran. Call this after the command exits.
+    pub fn check_setup(&self) -> Result<(), String> {
+        match self.setup_error.as_ref().and_then(SetupErrorFile::error) {
+            Some(error) => Err(format!(
+                "The terminal sandbox failed to start, so the command didn't run: {error}. \
+                Ask the user to check their `agent.terminal_sandbox` setting."
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Wraps `command` so that it runs inside the sandbox, if there's a policy.
+pub(crate) fn sandboxed_command(
+    policy: Option<&SandboxPolicy>,
+    command: &str,
+) -> Result<SandboxedCommand, String> {
+    let Some(policy) = policy else {
+        return Ok(SandboxedCommand {
+            command: command.to_string(),
+            setup_error: None,
+        });
+    };
+    let wrap = || {
+        let setup_error = SetupErrorFile::new()?;
+        let command = sandbox::wrap_command(
+            &std::env::current_exe()?,
+            policy,
+            &setup_error,
+            &util::get_default_system_shell_preferring_bash(),
+            command,
+        )?;
+        anyhow::Ok(SandboxedCommand {
+            command,
+            setup_error: Some(setup_error),
+        })
+    };
+    wrap().map_err(|e| format!("Failed to sandbox command: {e}"))
+}
+
+/// The output of a command run with [`run_command`].
+pub(crate) struct CommandOutput {
+    pub output: acp::TerminalOutputResponse,
+    pub timed_out: bool,
+    pub user_stopped: bool,
+}
+
+/// Runs a command in a terminal shown in the tool call, waiting for it to exit, time
+/// out, or be stopped by the user.
+pub(crate) async fn run_command(
+    environment: &dyn ThreadEnvironment,
+    command: String,
+    extra_env: Vec<acp::EnvVariable>,
+    working_dir: Option<PathBuf>,
+    timeout: Option<Duration>,
+    output_byte_limit: u64,
+    event_stream: &ToolCallEventStream,
+    cx: &mut AsyncApp,
+) -> Result<CommandOutput> {
+    let terminal = environment
+        .create_terminal(command, extra_env, working_dir, Some(output_byte_limit), cx)
+        .await?;
+
+    let terminal_id = terminal.id(cx)?;
+    event_stream.update_fields(acp::ToolCallUpdateFields::new().content(vec![
+        acp::ToolCallContent::Terminal(acp::Terminal::new(terminal_id)),
+    ]));
+
+    let mut timed_out = false;
+    let mut user_stopped_via_signal = false;
+    let wait_for_exit = terminal.wait_for_exit(cx)?;
+
+    match timeout {
+        Some(timeout) => {
+            let timeout_task = cx.background_executor().timer(timeout);
+
+            futures::select! {
+                _ = wait_for_exit.clone().fuse() => {},
+                _ = timeout_task.fuse() => {
+                    timed_out = true;
+                    terminal.kill(cx)?;
+                    wait_for_exit.await;
+                }
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    user_stopped_via_signal = true;
+                    terminal.kill(cx)?;
+                    wait_for_exit.await;
+                }
+            }
+        }
+        None => {
+            futures::select! {
+                _ = wait_for_exit.clone().fuse() => {},
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    user_stopped_via_signal = true;
+                    terminal.kill(cx)?;
+                    wait_for_exit.await;
+                }
+            }
+        }
+    };
+
+    // Check if user stopped - we check both:
+    // 1. The cancellation signal from RunningTurn::cancel (e.g. user pressed main Stop button)
+    // 2. The terminal's user_stopped flag (e.g. user clicked Stop on the terminal card)
+    // Note: user_stopped_via_signal is already set above if we detected cancellation in the select!
+    // but we also check was_cancelled_by_user() for cases where cancellation happened after wait_for_exit completed
+    let user_stopped_via_signal = user_stopped_via_signal || event_stream.was_cancelled_by_user();
+    let user_stopped_via_terminal = terminal.was_stopped_by_user(cx).unwrap_or(false);
+    let user_stopped = user_stopped_via_signal || user_stopped_via_terminal;
+
+    Ok(CommandOutput {
+        output: terminal.current_output(cx)?,
+        timed_out,
+        user_stopped,
+    })
+}
+
+pub(crate) fn process_content(
     output: acp::TerminalOutputResponse,
     command: &str,
     timed_out: bool,
@@ -267,6 +401,20 @@ fn process_content(
     content
 }
 
+/// Tells the model about the sandbox when a sandboxed command fails, since it may be why.
+fn sandbox_note(allow_network: bool) -> String {
+    let network = if allow_network {
+        ""
+    } else {
+        " and has no network access"
+    };
+    format!(
+        "This command ran in a sandbox that can only write to the project's worktrees{network}. \
+        If it failed because of the sandbox, don't try to work around it. \
+        If the command needs more access, ask the user to adjust the `agent.terminal_sandbox` setting."
+    )
+}
+
 fn working_dir(
     input: &TerminalToolInput,
     project: &Entity<Project>,
@@ -582,6 +730,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sandbox_setup_errors() {
+        let setup_error = SetupErrorFile::new().unwrap();
+        let path = setup_error.path().to_path_buf();
+        let command = SandboxedCommand {
+            command: "ls".into(),
+            setup_error: Some(setup_error),
+        };
+        assert_eq!(command.check_setup(), Ok(()));
+
+        std::fs::write(&path, "failed to restrict file system access").unwrap();
+        let error = command.check_setup().unwrap_err();
+        assert!(
+            error.contains("failed to restrict file system access"),
+            "unexpected error: {error}"
+        );
+    }
+
     #[test]
     fn test_process_content_unexpected_termination() {
         let output = acp::TerminalOutputResponse::new("some output".to_string(), false);
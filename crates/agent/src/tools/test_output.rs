@@ -0,0 +1,544 @@
+//! Parsers that summarize the output of common test runners, so that tools can report
+//! which tests failed without returning the runner's full output.
+
+use collections::HashMap;
+use regex::Regex;
+use std::{fmt::Write as _, sync::LazyLock};
+
+/// The number of failing tests whose output is included in a summary.
+const MAX_FAILURES: usize = 10;
+/// The number of lines of each failing test's output included in a summary.
+const MAX_FAILURE_OUTPUT_LINES: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TestRunner {
+    Cargo,
+    Pytest,
+    GoTest,
+    Jest,
+}
+
+impl TestRunner {
+    fn name(self) -> &'static str {
+        match self {
+            TestRunner::Cargo => "cargo test",
+            TestRunner::Pytest => "pytest",
+            TestRunner::GoTest => "go test",
+            TestRunner::Jest => "jest",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct TestSummary {
+    pub runner: TestRunner,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub failures: Vec<TestFailure>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct TestFailure {
+    pub name: String,
+    /// The output the runner printed for the test, such as its panic message or assertion.
+    pub output: String,
+}
+
+impl TestSummary {
+    /// Formats the summary for the model, truncating the output of each failing test.
+    pub fn to_markdown(&self) -> String {
+        let mut output = format!(
+            "{}: {} passed, {} failed, {} skipped.\n",
+            self.runner.name(),
+            self.passed,
+            self.failed,
+            self.skipped
+        );
+        if self.failures.is_empty() {
+            return output;
+        }
+
+        output.push_str("\nFailing tests:\n");
+        for failure in &self.failures {
+            writeln!(output, "- `{}`", failure.name).ok();
+        }
+        for failure in self
+            .failures
+            .iter()
+            .filter(|failure| !failure.output.is_empty())
+            .take(MAX_FAILURES)
+        {
+            let lines = failure.output.lines().collect::<Vec<_>>();
+            let shown = lines
+                .iter()
+                .take(MAX_FAILURE_OUTPUT_LINES)
+                .copied()
+                .collect::<Vec<_>>()
+                .join("\n");
+            writeln!(output, "\n## `{}`\n```\n{shown}\n```", failure.name).ok();
+            if lines.len() > MAX_FAILURE_OUTPUT_LINES {
+                writeln!(
+                    output,
+                    "{} more lines not shown.",
+                    lines.len() - MAX_FAILURE_OUTPUT_LINES
+                )
+                .ok();
+            }
+        }
+        if self.failures.len() > MAX_FAILURES {
+            writeln!(
+                output,
+                "\nThe output of {} more failing tests is not shown.",
+                self.failures.len() - MAX_FAILURES
+            )
+            .ok();
+        }
+        output
+    }
+}
+
+/// Summarizes the output of `cargo test`, `pytest`, `go test` or `jest`, returning `None`
+/// if the output wasn't produced by any of them.
+pub(crate) fn parse_test_output(output: &str) -> Option<TestSummary> {
+    parse_cargo_test(output)
+        .or_else(|| parse_pytest(output))
+        .or_else(|| parse_jest(output))
+        .or_else(|| parse_go_test(output))
+}
+
+/// Returns the number before each of the given words in a line such as
+/// `3 passed, 1 failed, 2 skipped`.
+fn count_before(line: &str, words: &[&str]) -> usize {
+    static COUNT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(\d+) ([a-z]+)").unwrap());
+    COUNT
+        .captures_iter(line)
+        .filter(|captures| words.contains(&&captures[2]))
+        .filter_map(|captures| captures[1].parse::<usize>().ok())
+        .sum()
+}
+
+fn parse_cargo_test(output: &str) -> Option<TestSummary> {
+    static RESULT: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^test result: (ok|FAILED)\. ").unwrap());
+    static FAILED_TEST: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^test (\S+)(?: - .*)? \.\.\. FAILED$").unwrap());
+    static OUTPUT_HEADER: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^---- (\S+)(?: - .*)? std(?:out|err) ----$").unwrap());
+
+    let mut summary = TestSummary {
+        runner: TestRunner::Cargo,
+        passed: 0,
+        failed: 0,
+        skipped: 0,
+        failures: Vec::new(),
+    };
+    let mut found_result = false;
+    let mut outputs = HashMap::<String, Vec<&str>>::default();
+    let mut current_output: Option<String> = None;
+    for line in output.lines() {
+        let line = line.trim_end();
+        if RESULT.is_match(line) {
+            found_result = true;
+            summary.passed += count_before(line, &["passed"]);
+            summary.failed += count_before(line, &["failed"]);
+            summary.skipped += count_before(line, &["ignored"]);
+            current_output = None;
+        } else if let Some(captures) = FAILED_TEST.captures(line) {
+            summary.failures.push(TestFailure {
+                name: captures[1].to_string(),
+                output: String::new(),
+            });
+        } else if let Some(captures) = OUTPUT_HEADER.captures(line) {
+            current_output = Some(captures[1].to_string());
+        } else if line == "failures:" || line == "successes:" {
+            current_output = None;
+        } else if let Some(name) = &current_output {
+            outputs.entry(name.clone()).or_default().push(line);
+        }
+    }
+    if !found_result {
+        return None;
+    }
+
+    for failure in &mut summary.failures {
+        if let Some(lines) = outputs.remove(&failure.name) {
+            failure.output = lines.join("\n").trim().to_string();
+        }
+    }
+    Some(summary)
+}
+
+fn parse_pytest(output: &str) -> Option<TestSummary> {
+    static RESULT: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"^=+ (.*\d+ (?:passed|failed|skipped|errors?|deselected).*) in [\d.]+s.* =+$")
+            .unwrap()
+    });
+    static FAILED_TEST: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^(?:FAILED|ERROR) (\S+)").unwrap());
+    static SECTION_HEADER: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^_{3,} (.+?) _{3,}$").unwrap());
+    static SEPARATOR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^={3,}").unwrap());
+
+    let mut summary = TestSummary {
+        runner: TestRunner::Pytest,
+        passed: 0,
+        failed: 0,
+        skipped: 0,
+        failures: Vec::new(),
+    };
+    let mut found_result = false;
+    let mut sections = HashMap::<String, Vec<&str>>::default();
+    let mut current_section: Option<String> = None;
+    for line in output.lines() {
+        let line = line.trim_end();
+        if let Some(captures) = RESULT.captures(line) {
+            found_result = true;
+            summary.passed += count_before(&captures[1], &["passed"]);
+            summary.failed += count_before(&captures[1], &["failed", "error", "errors"]);
+            summary.skipped += count_before(&captures[1], &["skipped"]);
+            current_section = None;
+        } else if let Some(captures) = SECTION_HEADER.captures(line) {
+            current_section = Some(captures[1].to_string());
+        } else if SEPARATOR.is_match(line) {
+            current_section = None;
+        } else if let Some(captures) = FAILED_TEST.captures(line) {
+            let name = captures[1].to_string();
+            if !summary.failures.iter().any(|failure| failure.name == name) {
+                summary.failures.push(TestFailure {
+                    name,
+                    output: String::new(),
+                });
+            }
+        } else if let Some(section) = &current_section {
+            sections.entry(section.clone()).or_default().push(line);
+        }
+    }
+    if !found_result {
+        return None;
+    }
+
+    for failure in &mut summary.failures {
+        // Sections are titled by the test's name within its file, such as
+        // `TestClass.test_method` for `tests/test_file.py::TestClass::test_method`.
+        let section_title = failure
+            .name
+            .split_once("::")
+            .map_or(failure.name.as_str(), |(_, name)| name)
+            .replace("::", ".");
+        if let Some(lines) = sections.remove(&section_title) {
+            failure.output = lines.join("\n").trim().to_string();
+        }
+    }
+    Some(summary)
+}
+
+fn parse_jest(output: &str) -> Option<TestSummary> {
+    static RESULT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^Tests:\s+(.*total)$").unwrap());
+    static FAILED_TEST: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*● (.+)$").unwrap());
+    static SUITE_RESULT: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^\s*(?:PASS|FAIL) \s*\S").unwrap());
+
+    let mut summary = TestSummary {
+        runner: TestRunner::Jest,
+        passed: 0,
+        failed: 0,
+        skipped: 0,
+        failures: Vec::new(),
+    };
+    let mut found_result = false;
+    let mut current_failure: Option<usize> = None;
+    let mut failure_lines = Vec::<Vec<&str>>::new();
+    for line in output.lines() {
+        let line = line.trim_end();
+        if let Some(captures) = RESULT.captures(line) {
+            found_result = true;
+            summary.passed += count_before(&captures[1], &["passed"]);
+            summary.failed += count_before(&captures[1], &["failed"]);
+            summary.skipped += count_before(&captures[1], &["skipped", "todo"]);
+            current_failure = None;
+        } else if let Some(captures) = FAILED_TEST.captures(line) {
+            let name = &captures[1];
+            // Jest repeats each failure in its summary of failing tests.
+            current_failure = if name == "Console" {
+                None
+            } else if summary.failures.iter().any(|failure| failure.name == name) {
+                None
+            } else {
+                summary.failures.push(TestFailure {
+                    name: name.to_string(),
+                    output: String::new(),
+                });
+                failure_lines.push(Vec::new());
+                Some(summary.failures.len() - 1)
+            };
+        } else if SUITE_RESULT.is_match(line) || line.starts_with("Test Suites:") {
+            current_failure = None;
+        } else if let Some(ix) = current_failure {
+            failure_lines[ix].push(line);
+        }
+    }
+    if !found_result {
+        return None;
+    }
+
+    for (failure, lines) in summary.failures.iter_mut().zip(failure_lines) {
+        failure.output = unindent(&lines);
+    }
+    Some(summary)
+}
+
+fn parse_go_test(output: &str) -> Option<TestSummary> {
+    static RESULT: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^\s*--- (PASS|FAIL|SKIP): (\S+) \(").unwrap());
+    static RUN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^=== RUN\s+(\S+)$").unwrap());
+    static PACKAGE_RESULT: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^(?:ok|FAIL|PASS)(?:\s|$)").unwrap());
+
+    let mut summary = TestSummary {
+        runner: TestRunner::GoTest,
+        passed: 0,
+        failed: 0,
+        skipped: 0,
+        failures: Vec::new(),
+    };
+    let mut found_result = false;
+    // With `-v`, a test's output is printed between its `=== RUN` line and its result,
+    // and without it, the output follows the result.
+    let mut running_output = HashMap::<String, Vec<&str>>::default();
+    let mut current_test: Option<String> = None;
+    let mut current_failure: Option<usize> = None;
+    let mut failure_lines = Vec::<Vec<&str>>::new();
+    for line in output.lines() {
+        let line = line.trim_end();
+        if let Some(captures) = RESULT.captures(line) {
+            found_result = true;
+            let name = captures[2].to_string();
+            current_test = None;
+            current_failure = None;
+            let output = running_output.remove(&name).unwrap_or_default();
+            match &captures[1] {
+                "PASS" => summary.passed += 1,
+                "SKIP" => summary.skipped += 1,
+                _ => {
+                    summary.failed += 1;
+                    summary.failures.push(TestFailure {
+                        name,
+                        output: String::new(),
+                    });
+                    failure_lines.push(output);
+                    current_failure = Some(summary.failures.len() - 1);
+                }
+            }
+        } else if let Some(captures) = RUN.captures(line) {
+            current_test = Some(captures[1].to_string());
+            current_failure = None;
+        } else if PACKAGE_RESULT.is_match(line) || line.starts_with("===") {
+            current_test = None;
+            current_failure = None;
+        } else if let Some(ix) = current_failure {
+            failure_lines[ix].push(line);
+        } else if let Some(test) = &current_test {
+            running_output.entry(test.clone()).or_default().push(line);
+        }
+    }
+    if !found_result {
+        return None;
+    }
+
+    for (failure, lines) in summary.failures.iter_mut().zip(failure_lines) {
+        failure.output = unindent(&lines);
+    }
+    Some(summary)
+}
+
+/// Joins the lines, removing the indentation they have in common and any surrounding
+/// blank lines.
+fn unindent(lines: &[&str]) -> String {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches('\n')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_cargo_test() {
+        let output = indoc! {"
+            running 3 tests
+            test db::tests::test_fork_thread ... ok
+            test thread::tests::test_truncate ... FAILED
+            test thread::tests::test_slow ... ignored
+
+            failures:
+
+            ---- thread::tests::test_truncate stdout ----
+
+            thread 'thread::tests::test_truncate' panicked at crates/agent/src/thread.rs:10:5:
+            assertion `left == right` failed
+              left: 1
+             right: 2
+
+
+            failures:
+                thread::tests::test_truncate
+
+            test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.01s
+        "};
+        assert_eq!(
+            parse_test_output(output),
+            Some(TestSummary {
+                runner: TestRunner::Cargo,
+                passed: 1,
+                failed: 1,
+                skipped: 1,
+                failures: vec![TestFailure {
+                    name: "thread::tests::test_truncate".into(),
+                    output: indoc! {"
+                        thread 'thread::tests::test_truncate' panicked at crates/agent/src/thread.rs:10:5:
+                        assertion `left == right` failed
+                          left: 1
+                         right: 2"}
+                    .into(),
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_pytest() {
+        let output = indoc! {"
+            ============================= test session starts ==============================
+            collected 3 items
+
+            tests/test_math.py .Fs                                                   [100%]
+
+            =================================== FAILURES ===================================
+            ___________________________ TestMath.test_division ____________________________
+
+                def test_division(self):
+            >       assert 1 / 2 == 1
+            E       assert 0.5 == 1
+
+            tests/test_math.py:8: AssertionError
+            =========================== short test summary info ============================
+            FAILED tests/test_math.py::TestMath::test_division - assert 0.5 == 1
+            ==================== 1 failed, 1 passed, 1 skipped in 0.03s ====================
+        "};
+        assert_eq!(
+            parse_test_output(output),
+            Some(TestSummary {
+                runner: TestRunner::Pytest,
+                passed: 1,
+                failed: 1,
+                skipped: 1,
+                failures: vec![TestFailure {
+                    name: "tests/test_math.py::TestMath::test_division".into(),
+                    output: indoc! {"
+                        def test_division(self):
+                        >       assert 1 / 2 == 1
+                        E       assert 0.5 == 1
+
+                        tests/test_math.py:8: AssertionError"}
+                    .into(),
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_go_test() {
+        let output = indoc! {"
+            === RUN   TestAdd
+            --- PASS: TestAdd (0.00s)
+            === RUN   TestSubtract
+                math_test.go:14: expected 1, got 2
+            --- FAIL: TestSubtract (0.00s)
+            === RUN   TestDivide
+                math_test.go:20: not implemented
+            --- SKIP: TestDivide (0.00s)
+            FAIL
+            FAIL	example.com/math	0.002s
+            --- FAIL: TestMultiply (0.00s)
+                math_test.go:26: expected 6, got 5
+            FAIL
+        "};
+        assert_eq!(
+            parse_test_output(output),
+            Some(TestSummary {
+                runner: TestRunner::GoTest,
+                passed: 1,
+                failed: 2,
+                skipped: 1,
+                failures: vec![
+                    TestFailure {
+                        name: "TestSubtract".into(),
+                        output: "math_test.go:14: expected 1, got 2".into(),
+                    },
+                    TestFailure {
+                        name: "TestMultiply".into(),
+                        output: "math_test.go:26: expected 6, got 5".into(),
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_jest() {
+        let output = indoc! {"
+             PASS  src/add.test.js
+             FAIL  src/subtract.test.js
+              ● math › subtracts numbers
+
+                expect(received).toBe(expected) // Object.is equality
+
+                Expected: 1
+                Received: 2
+
+                  at Object.<anonymous> (src/subtract.test.js:4:25)
+
+            Tests:       1 failed, 1 skipped, 2 passed, 4 total
+            Test Suites: 1 failed, 1 passed, 2 total
+        "};
+        assert_eq!(
+            parse_test_output(output),
+            Some(TestSummary {
+                runner: TestRunner::Jest,
+                passed: 2,
+                failed: 1,
+                skipped: 1,
+                failures: vec![TestFailure {
+                    name: "math › subtracts numbers".into(),
+                    output: indoc! {"
+                        expect(received).toBe(expected) // Object.is equality
+
+                        Expected: 1
+                        Received: 2
+
+                          at Object.<anonymous> (src/subtract.test.js:4:25)"}
+                    .into(),
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown_output() {
+        assert_eq!(parse_test_output("Compiling agent v0.1.0\nFinished"), None);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use project::{Project, Symbol, lsp_store::SymbolLocation};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+const MAX_SYMBOLS: usize = 50;
+
+/// Searches the project's symbols by name, using the project's language servers.
+///
+/// - Use this to find where a type, function, or constant is defined when you know (part of) its name but not its file.
+/// - Matching is fuzzy, so `cfgload` can find `ConfigLoader`.
+/// - Results include the symbol's kind and the path and line it's defined on, which can be passed to `find_references` or `go_to_definition`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WorkspaceSymbolsToolInput {
+    /// The name, or part of the name, of the symbols to search for.
+    pub query: String,
+}
+
+pub struct WorkspaceSymbolsTool {
+    project: Entity<Project>,
+}
+
+impl WorkspaceSymbolsTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for WorkspaceSymbolsTool {
+    type Input = WorkspaceSymbolsToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "workspace_symbols";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        if let Ok(input) = input {
+            format!("Search symbols for {}", MarkdownInlineCode(&input.query)).into()
+        } else {
+            "Search symbols".into()
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let symbols = self
+            .project
+            .update(cx, |project, cx| project.symbols(&input.query, cx));
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let symbols = futures::select! {
+                result = symbols.fuse() => result.map_err(|e| e.to_string())?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Symbol search cancelled by user".to_string());
+                }
+            };
+            if symbols.is_empty() {
+                return Ok(format!("No symbols found for `{}`.", input.query));
+            }
+
+            let output = project.read_with(cx, |project, cx| {
+                let mut output = String::new();
+                for symbol in symbols.iter().take(MAX_SYMBOLS) {
+                    writeln!(output, "{}", format_symbol(symbol, project, cx)).ok();
+                }
+                if symbols.len() > MAX_SYMBOLS {
+                    writeln!(
+                        output,
+                        "\n{} more symbols not shown. Use a more specific query to narrow the results.",
+                        symbols.len() - MAX_SYMBOLS
+                    )
+                    .ok();
+                }
+                output
+            });
+            Ok(format!("Found {} symbols:\n\n{output}", symbols.len()))
+        })
+    }
+}
+
+fn format_symbol(symbol: &Symbol, project: &Project, cx: &App) -> String {
+    let path = match &symbol.path {
+        SymbolLocation::InProject(project_path) => project
+            .worktree_for_id(project_path.worktree_id, cx)
+            .map(|worktree| {
+                worktree
+                    .read(cx)
+                    .root_name()
+                    .join(&project_path.path)
+                    .display(project.path_style(cx))
+                    .to_string()
+            })
+            .unwrap_or_else(|| {
+                project_path
+                    .path
+                    .display(project.path_style(cx))
+                    .to_string()
+            }),
+        SymbolLocation::OutsideProject { abs_path, .. } => abs_path.display().to_string(),
+    };
+    let kind = format!("{:?}", symbol.kind).to_lowercase();
+    let line = symbol.range.start.0.row + 1;
+    match &symbol.container_name {
+        Some(container) => format!(
+            "- {kind} `{}` in `{container}` at {path}:{line}",
+            symbol.name
+        ),
+        None => format!("- {kind} `{}` at {path}:{line}", symbol.name),
+    }
+}
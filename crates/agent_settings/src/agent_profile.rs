@@ -6,8 +6,8 @@ use convert_case::{Case, Casing as _};
 use fs::Fs;
 use gpui::{App, SharedString};
 use settings::{
-    AgentProfileContent, ContextServerPresetContent, LanguageModelSelection, Settings as _,
-    SettingsContent, update_settings_file,
+    AgentProfileContent, CommandToolContent, ContextServerPresetContent, LanguageModelSelection,
+    Settings as _, SettingsContent, TerminalSandboxContent, update_settings_file,
 };
 use util::ResultExt as _;
 
@@ -70,6 +70,13 @@ impl AgentProfile {
         let default_model = base_profile
             .as_ref()
             .and_then(|profile| profile.default_model.clone());
+        let terminal_sandbox = base_profile
+            .as_ref()
+            .and_then(|profile| profile.terminal_sandbox.clone());
+        let command_tools = base_profile
+            .as_ref()
+            .map(|profile| profile.command_tools.clone())
+            .unwrap_or_default();
 
         let profile_settings = AgentProfileSettings {
             name: name.into(),
@@ -77,6 +84,8 @@ impl AgentProfile {
             enable_all_context_servers,
             context_servers,
             default_model,
+            terminal_sandbox,
+            command_tools,
         };
 
         update_settings_file(fs, cx, {
@@ -109,6 +118,10 @@ pub struct AgentProfileSettings {
     pub context_servers: IndexMap<Arc<str>, ContextServerPreset>,
     /// Default language model to apply when this profile becomes active.
     pub default_model: Option<LanguageModelSelection>,
+    /// Overrides for the terminal sandbox settings.
+    pub terminal_sandbox: Option<TerminalSandboxContent>,
+    /// Tools that run a shell command, keyed by the tool's name.
+    pub command_tools: IndexMap<Arc<str>, CommandToolContent>,
 }
 
 impl AgentProfileSettings {
@@ -158,6 +171,8 @@ impl AgentProfileSettings {
                     })
                     .collect(),
                 default_model: self.default_model.clone(),
+                terminal_sandbox: self.terminal_sandbox.clone(),
+                command_tools: self.command_tools.clone(),
             },
         );
 
@@ -173,6 +188,8 @@ impl From<AgentProfileContent> for AgentProfileSettings {
             enable_all_context_servers,
             context_servers,
             default_model,
+            terminal_sandbox,
+            command_tools,
         } = content;
 
         Self {
@@ -184,6 +201,8 @@ impl From<AgentProfileContent> for AgentProfileSettings {
                 .map(|(server_id, preset)| (server_id, preset.into()))
                 .collect(),
             default_model,
+            terminal_sandbox,
+            command_tools,
         }
     }
 }
@@ -1,7 +1,8 @@
 mod agent_profile;
 
-use std::path::{Component, Path};
+use std::path::{Component, Path, PathBuf};
 use std::sync::{Arc, LazyLock};
+use std::time::Duration;
 
 use agent_client_protocol::ModelId;
 use collections::{HashSet, IndexMap};
@@ -11,8 +12,9 @@ use project::DisableAiSettings;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{
-    DefaultAgentView, DockPosition, LanguageModelParameters, LanguageModelSelection,
-    NotifyWhenAgentWaiting, RegisterSetting, Settings, ToolPermissionMode,
+    DefaultAgentView, DockPosition, LanguageModelParameters, LanguageModelPricing,
+    LanguageModelSelection, NotifyWhenAgentWaiting, RegisterSetting, Settings, ToolPermissionMode,
+    UsageBudgetContent,
 };
 
 pub use crate::agent_profile::*;
@@ -51,6 +53,10 @@ pub struct AgentSettings {
     pub message_editor_min_lines: usize,
     pub show_turn_stats: bool,
     pub tool_permissions: ToolPermissions,
+    pub hooks: AgentHooks,
+    pub terminal_sandbox: TerminalSandboxSettings,
+    pub model_pricing: Vec<LanguageModelPricing>,
+    pub usage_budget: UsageBudgetContent,
 }
 
 impl AgentSettings {
@@ -80,6 +86,18 @@ impl AgentSettings {
         self.message_editor_min_lines * 2
     }
 
+    /// Returns the terminal sandbox settings, with the given profile's overrides applied.
+    pub fn terminal_sandbox(&self, profile_id: Option<&AgentProfileId>) -> TerminalSandboxSettings {
+        let mut sandbox = self.terminal_sandbox.clone();
+        if let Some(overrides) = profile_id
+            .and_then(|profile_id| self.profiles.get(profile_id))
+            .and_then(|profile| profile.terminal_sandbox.as_ref())
+        {
+            sandbox.apply(overrides);
+        }
+        sandbox
+    }
+
     pub fn favorite_model_ids(&self) -> HashSet<ModelId> {
         self.favorite_models
             .iter()
@@ -191,6 +209,78 @@ impl CompiledRegex {
     }
 }
 
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TerminalSandboxSettings {
+    pub enabled: bool,
+    pub allow_network: bool,
+    pub read_only_paths: Vec<PathBuf>,
+    pub read_write_paths: Vec<PathBuf>,
+}
+
+impl TerminalSandboxSettings {
+    fn apply(&mut self, content: &settings::TerminalSandboxContent) {
+        if let Some(enabled) = content.enabled {
+            self.enabled = enabled;
+        }
+        if let Some(allow_network) = content.allow_network {
+            self.allow_network = allow_network;
+        }
+        self.read_only_paths.extend(
+            content
+                .read_only_paths
+                .iter()
+                .flatten()
+                .map(|path| expand_tilde(path)),
+        );
+        self.read_write_paths.extend(
+            content
+                .read_write_paths
+                .iter()
+                .flatten()
+                .map(|path| expand_tilde(path)),
+        );
+    }
+}
+
+fn expand_tilde(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(relative_path) => util::paths::home_dir().join(relative_path),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// Shell commands that run around the agent's activity.
+#[derive(Clone, Debug, Default)]
+pub struct AgentHooks {
+    pub pre_tool_call: Vec<AgentHook>,
+    pub post_tool_call: Vec<AgentHook>,
+    pub post_edit: Vec<AgentHook>,
+    pub turn_complete: Vec<AgentHook>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AgentHook {
+    pub command: String,
+    pub matcher: Option<CompiledRegex>,
+    /// Set when the matcher failed to compile. Such hooks block every tool call
+    /// before it runs, and are skipped otherwise.
+    pub invalid_matcher: Option<InvalidRegexPattern>,
+    pub timeout: Duration,
+}
+
+impl AgentHook {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+    /// Returns whether the hook applies to the given tool name or file path.
+    pub fn matches(&self, subject: &str) -> bool {
+        self.invalid_matcher.is_none()
+            && self
+                .matcher
+                .as_ref()
+                .is_none_or(|matcher| matcher.is_match(subject))
+    }
+}
+
 pub const HARDCODED_SECURITY_DENIAL_MESSAGE: &str = "Blocked by built-in security rule. This operation is considered too \
      harmful to be allowed, and cannot be overridden by settings.";
 
@@ -438,6 +528,16 @@ impl Settings for AgentSettings {
             message_editor_min_lines: agent.message_editor_min_lines.unwrap(),
             show_turn_stats: agent.show_turn_stats.unwrap(),
             tool_permissions: compile_tool_permissions(agent.tool_permissions),
+            hooks: compile_hooks(agent.hooks),
+            terminal_sandbox: {
+                let mut terminal_sandbox = TerminalSandboxSettings::default();
+                if let Some(content) = &agent.terminal_sandbox {
+                    terminal_sandbox.apply(content);
+                }
+                terminal_sandbox
+            },
+            model_pricing: agent.model_pricing,
+            usage_budget: agent.usage_budget.unwrap_or_default(),
         }
     }
 }
@@ -504,6 +604,59 @@ fn compile_tool_permissions(content: Option<settings::ToolPermissionsContent>) -
     }
 }
 
+fn compile_hooks(content: Option<settings::AgentHooksContent>) -> AgentHooks {
+    let Some(content) = content else {
+        return AgentHooks::default();
+    };
+
+    AgentHooks {
+        pre_tool_call: compile_hook_list(content.pre_tool_call, "pre_tool_call"),
+        post_tool_call: compile_hook_list(content.post_tool_call, "post_tool_call"),
+        post_edit: compile_hook_list(content.post_edit, "post_edit"),
+        turn_complete: compile_hook_list(content.turn_complete, "turn_complete"),
+    }
+}
+
+fn compile_hook_list(
+    hooks: Option<Vec<settings::AgentHookContent>>,
+    event: &str,
+) -> Vec<AgentHook> {
+    hooks
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|hook| !hook.command.trim().is_empty())
+        .map(|hook| {
+            let mut invalid_matcher = None;
+            let matcher =
+                hook.matcher
+                    .and_then(|pattern| match CompiledRegex::try_new(&pattern, true) {
+                        Ok(regex) => Some(regex),
+                        Err(error) => {
+                            log::error!(
+                                "Invalid matcher in {event} hook '{}': '{pattern}' - {error}",
+                                hook.command,
+                            );
+                            invalid_matcher = Some(InvalidRegexPattern {
+                                pattern,
+                                rule_type: event.to_string(),
+                                error: error.to_string(),
+                            });
+                            None
+                        }
+                    });
+            AgentHook {
+                command: hook.command,
+                matcher,
+                invalid_matcher,
+                timeout: hook
+                    .timeout
+                    .map(Duration::from_secs)
+                    .unwrap_or(AgentHook::DEFAULT_TIMEOUT),
+            }
+        })
+        .collect()
+}
+
 fn compile_regex_rules(
     rules: Vec<settings::ToolRegexRule>,
     rule_type: &str,
@@ -615,6 +768,64 @@ mod tests {
         assert_eq!(permissions.default, ToolPermissionMode::Confirm);
     }
 
+    #[test]
+    fn test_hooks_parsing() {
+        let json = json!({
+            "pre_tool_call": [
+                { "command": "./check-generated.sh", "matcher": "^(edit_file|streaming_edit_file)$" },
+                { "command": "  " }
+            ],
+            "post_edit": [
+                { "command": "rustfmt", "matcher": "\\.rs$", "timeout": 5 }
+            ],
+            "turn_complete": [
+                { "command": "notify-send done", "matcher": "(" }
+            ]
+        });
+        let content: settings::AgentHooksContent = serde_json::from_value(json).unwrap();
+        let hooks = compile_hooks(Some(content));
+
+        assert_eq!(hooks.pre_tool_call.len(), 1);
+        assert!(hooks.pre_tool_call[0].matches("edit_file"));
+        assert!(!hooks.pre_tool_call[0].matches("read_file"));
+        assert_eq!(hooks.pre_tool_call[0].timeout, AgentHook::DEFAULT_TIMEOUT);
+
+        assert_eq!(hooks.post_edit.len(), 1);
+        assert!(hooks.post_edit[0].matches("/project/src/main.rs"));
+        assert!(!hooks.post_edit[0].matches("/project/README.md"));
+        assert_eq!(hooks.post_edit[0].timeout, Duration::from_secs(5));
+
+        assert!(hooks.post_tool_call.is_empty());
+        assert_eq!(hooks.turn_complete.len(), 1);
+        assert!(hooks.turn_complete[0].invalid_matcher.is_some());
+        assert!(!hooks.turn_complete[0].matches("anything"));
+    }
+
+    #[test]
+    fn test_terminal_sandbox_profile_overrides() {
+        let mut settings = TerminalSandboxSettings::default();
+        settings.apply(&serde_json::from_value(json!({
+            "enabled": true,
+            "read_only_paths": ["/opt/toolchains"]
+        })).unwrap());
+        assert!(settings.enabled);
+        assert!(!settings.allow_network);
+
+        settings.apply(&serde_json::from_value(json!({
+            "allow_network": true,
+            "read_only_paths": ["~/.cargo"]
+        })).unwrap());
+        assert!(settings.enabled);
+        assert!(settings.allow_network);
+        assert_eq!(
+            settings.read_only_paths,
+            vec![
+                PathBuf::from("/opt/toolchains"),
+                util::paths::home_dir().join(".cargo")
+            ]
+        );
+    }
+
     #[test]
     fn test_tool_rules_default_returns_confirm() {
         let default_rules = ToolRules::default();
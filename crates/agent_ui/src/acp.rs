@@ -1,4 +1,5 @@
 mod config_options;
+mod elicitation_form;
 mod entry_view_state;
 mod message_editor;
 mod mode_selector;
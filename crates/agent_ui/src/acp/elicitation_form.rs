@@ -0,0 +1,237 @@
+use acp_thread::Elicitation;
+use context_server::types::{PrimitiveSchemaDefinition, PrimitiveSchemaType};
+use editor::Editor;
+use gpui::{Entity, SharedString};
+use ui::{Checkbox, ToggleState, prelude::*};
+
+/// The form filled in by the user when an MCP server requests input while one
+/// of its tools is running. Each property of the requested schema gets its own
+/// input, depending on its type.
+pub struct ElicitationForm {
+    fields: Vec<ElicitationField>,
+}
+
+struct ElicitationField {
+    name: String,
+    label: SharedString,
+    description: Option<SharedString>,
+    definition: PrimitiveSchemaDefinition,
+    required: bool,
+    input: FieldInput,
+    error: Option<SharedString>,
+}
+
+enum FieldInput {
+    Text(Entity<Editor>),
+    Boolean(bool),
+    Enum(Option<String>),
+}
+
+impl ElicitationForm {
+    pub fn new(
+        elicitation: &Entity<Elicitation>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let schema = elicitation.read(cx).schema().clone();
+        let fields = schema
+            .properties
+            .iter()
+            .map(|(name, definition)| {
+                let default = definition.default.as_ref();
+                let input = if definition.enum_values.is_some() {
+                    FieldInput::Enum(
+                        default
+                            .and_then(|value| value.as_str())
+                            .map(ToString::to_string),
+                    )
+                } else if definition.ty == PrimitiveSchemaType::Boolean {
+                    FieldInput::Boolean(default.and_then(|value| value.as_bool()).unwrap_or(false))
+                } else {
+                    let editor = cx.new(|cx| {
+                        let mut editor = Editor::single_line(window, cx);
+                        if let Some(format) = &definition.format {
+                            editor.set_placeholder_text(format, window, cx);
+                        }
+                        match default {
+                            Some(serde_json::Value::String(text)) => {
+                                editor.set_text(text.as_str(), window, cx)
+                            }
+                            Some(serde_json::Value::Number(number)) => {
+                                editor.set_text(number.to_string(), window, cx)
+                            }
+                            _ => {}
+                        }
+                        editor
+                    });
+                    FieldInput::Text(editor)
+                };
+
+                ElicitationField {
+                    name: name.clone(),
+                    label: definition
+                        .title
+                        .clone()
+                        .unwrap_or_else(|| name.clone())
+                        .into(),
+                    description: definition.description.clone().map(Into::into),
+                    definition: definition.clone(),
+                    required: schema.is_required(name),
+                    input,
+                    error: None,
+                }
+            })
+            .collect();
+
+        Self { fields }
+    }
+
+    /// Validates the values entered by the user, returning the content of an
+    /// accepted response, or `None` if some of them are invalid.
+    pub fn content(
+        &mut self,
+        cx: &mut Context<Self>,
+    ) -> Option<serde_json::Map<String, serde_json::Value>> {
+        let mut content = serde_json::Map::new();
+        let mut is_valid = true;
+
+        for field in &mut self.fields {
+            field.error = None;
+            let value = match &field.input {
+                FieldInput::Text(editor) => {
+                    let text = editor.read(cx).text(cx);
+                    if text.is_empty() {
+                        None
+                    } else {
+                        match field.definition.parse_text(&text) {
+                            Ok(value) => Some(value),
+                            Err(error) => {
+                                field.error = Some(format!("{} {error}", field.label).into());
+                                is_valid = false;
+                                continue;
+                            }
+                        }
+                    }
+                }
+                FieldInput::Boolean(value) => Some(serde_json::Value::Bool(*value)),
+                FieldInput::Enum(value) => value.clone().map(serde_json::Value::String),
+            };
+
+            match value {
+                Some(value) => {
+                    content.insert(field.name.clone(), value);
+                }
+                None if field.required => {
+                    field.error = Some(format!("{} is required", field.label).into());
+                    is_valid = false;
+                }
+                None => {}
+            }
+        }
+
+        cx.notify();
+        is_valid.then_some(content)
+    }
+
+    fn render_input(&self, ix: usize, field: &ElicitationField, cx: &Context<Self>) -> AnyElement {
+        match &field.input {
+            FieldInput::Text(editor) => div()
+                .px_2()
+                .py_1()
+                .rounded_sm()
+                .border_1()
+                .border_color(if field.error.is_some() {
+                    cx.theme().status().error_border
+                } else {
+                    cx.theme().colors().border_variant
+                })
+                .bg(cx.theme().colors().editor_background)
+                .child(editor.clone())
+                .into_any_element(),
+            FieldInput::Boolean(checked) => {
+                Checkbox::new(("elicitation-checkbox", ix), (*checked).into())
+                    .label(field.label.clone())
+                    .on_click(cx.listener(move |this, state: &ToggleState, _, cx| {
+                        if let Some(FieldInput::Boolean(checked)) =
+                            this.fields.get_mut(ix).map(|field| &mut field.input)
+                        {
+                            *checked = state.selected();
+                        }
+                        cx.notify();
+                    }))
+                    .into_any_element()
+            }
+            FieldInput::Enum(selected) => h_flex()
+                .flex_wrap()
+                .gap_1()
+                .children(
+                    field
+                        .definition
+                        .enum_options()
+                        .into_iter()
+                        .flatten()
+                        .enumerate()
+                        .map(|(option_ix, (value, name))| {
+                            let value = value.to_string();
+                            Button::new(
+                                SharedString::from(format!("elicitation-option-{ix}-{option_ix}")),
+                                name.to_string(),
+                            )
+                            .style(ButtonStyle::Outlined)
+                            .label_size(LabelSize::Small)
+                            .toggle_state(selected.as_ref() == Some(&value))
+                            .on_click(cx.listener(
+                                move |this, _, _, cx| {
+                                    if let Some(FieldInput::Enum(selected)) =
+                                        this.fields.get_mut(ix).map(|field| &mut field.input)
+                                    {
+                                        *selected = Some(value.clone());
+                                    }
+                                    cx.notify();
+                                },
+                            ))
+                        }),
+                )
+                .into_any_element(),
+        }
+    }
+}
+
+impl Render for ElicitationForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .gap_2()
+            .children(self.fields.iter().enumerate().map(|(ix, field)| {
+                let is_boolean = matches!(field.input, FieldInput::Boolean(_));
+                v_flex()
+                    .gap_1()
+                    .when(!is_boolean, |this| {
+                        this.child(
+                            h_flex()
+                                .gap_0p5()
+                                .child(Label::new(field.label.clone()).size(LabelSize::Small))
+                                .when(field.required, |this| {
+                                    this.child(
+                                        Label::new("*").size(LabelSize::Small).color(Color::Error),
+                                    )
+                                }),
+                        )
+                    })
+                    .when_some(field.description.clone(), |this, description| {
+                        this.child(
+                            Label::new(description)
+                                .size(LabelSize::XSmall)
+                                .color(Color::Muted),
+                        )
+                    })
+                    .child(self.render_input(ix, field, cx))
+                    .when_some(field.error.clone(), |this, error| {
+                        this.child(
+                            Label::new(error)
+                                .size(LabelSize::XSmall)
+                                .color(Color::Error),
+                        )
+                    })
+            }))
+    }
+}
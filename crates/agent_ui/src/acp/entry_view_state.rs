@@ -19,6 +19,7 @@ use theme::ThemeSettings;
 use ui::{Context, TextSize};
 use workspace::Workspace;
 
+use crate::acp::elicitation_form::ElicitationForm;
 use crate::acp::message_editor::{MessageEditor, MessageEditorEvent};
 
 pub struct EntryViewState {
@@ -124,6 +125,12 @@ impl EntryViewState {
                 let id = tool_call.id.clone();
                 let terminals = tool_call.terminals().cloned().collect::<Vec<_>>();
                 let diffs = tool_call.diffs().cloned().collect::<Vec<_>>();
+                let elicitation = match &tool_call.status {
+                    acp_thread::ToolCallStatus::WaitingForInput { elicitation, .. } => {
+                        Some(elicitation.clone())
+                    }
+                    _ => None,
+                };
 
                 let views = if let Some(Entry::Content(views)) = self.entries.get_mut(index) {
                     views
@@ -176,6 +183,13 @@ impl EntryViewState {
                         element
                     });
                 }
+
+                if let Some(elicitation) = elicitation {
+                    views.entry(elicitation.entity_id()).or_insert_with(|| {
+                        cx.new(|cx| ElicitationForm::new(&elicitation, window, cx))
+                            .into_any()
+                    });
+                }
             }
             AgentThreadEntry::AssistantMessage(message) => {
                 let entry = if let Some(Entry::AssistantMessage(entry)) =
@@ -302,6 +316,16 @@ impl Entry {
             .map(|entity| entity.downcast::<TerminalView>().unwrap())
     }
 
+    pub fn elicitation_form(
+        &self,
+        elicitation: &Entity<acp_thread::Elicitation>,
+    ) -> Option<Entity<ElicitationForm>> {
+        self.content_map()?
+            .get(&elicitation.entity_id())
+            .cloned()
+            .map(|entity| entity.downcast::<ElicitationForm>().unwrap())
+    }
+
     pub fn scroll_handle_for_assistant_message_chunk(
         &self,
         chunk_ix: usize,
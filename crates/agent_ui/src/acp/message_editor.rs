@@ -79,6 +79,7 @@ impl PromptCompletionProviderDelegate for Entity<MessageEditor> {
                 PromptContextType::Diagnostics,
                 PromptContextType::Fetch,
                 PromptContextType::Rules,
+                PromptContextType::McpResource,
             ]);
         }
         supported
@@ -191,8 +192,9 @@ impl MessageEditor {
                 {
                     editor.update(cx, |editor, cx| {
                         let snapshot = editor.snapshot(window, cx);
-                        this.mention_set
-                            .update(cx, |mention_set, _cx| mention_set.remove_invalid(&snapshot));
+                        this.mention_set.update(cx, |mention_set, cx| {
+                            mention_set.remove_invalid(&snapshot, cx)
+                        });
 
                         let new_hints = this
                             .command_hint(snapshot.buffer())
@@ -499,11 +501,13 @@ impl MessageEditor {
         self.editor.update(cx, |editor, cx| {
             editor.clear(window, cx);
             editor.remove_creases(
-                self.mention_set.update(cx, |mention_set, _cx| {
-                    mention_set
+                self.mention_set.update(cx, |mention_set, cx| {
+                    let creases = mention_set
                         .clear()
                         .map(|(crease_id, _)| crease_id)
-                        .collect::<Vec<_>>()
+                        .collect::<Vec<_>>();
+                    mention_set.unsubscribe_from_unmentioned_mcp_resources(cx);
+                    creases
                 }),
                 cx,
             )
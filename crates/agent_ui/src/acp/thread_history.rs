@@ -1,6 +1,9 @@
 use crate::acp::AcpServerView;
 use crate::{AgentPanel, RemoveHistory, RemoveSelectedThread};
-use acp_thread::{AgentSessionInfo, AgentSessionList, AgentSessionListRequest, SessionListUpdate};
+use acp_thread::{
+    AgentSessionInfo, AgentSessionList, AgentSessionListRequest, AgentSessionSearchMatch,
+    SessionListUpdate, UserMessageId,
+};
 use agent_client_protocol as acp;
 use chrono::{Datelike as _, Local, NaiveDate, TimeDelta, Utc};
 use editor::{Editor, EditorEvent};
@@ -53,9 +56,36 @@ enum ListItemType {
     SearchResult {
         entry: AgentSessionInfo,
         positions: Vec<usize>,
+        content_match: Option<ContentMatch>,
     },
 }
 
+/// Text in a thread's messages that matched the search query.
+struct ContentMatch {
+    user_message_id: Option<UserMessageId>,
+    snippet: SharedString,
+    positions: Vec<usize>,
+}
+
+impl ContentMatch {
+    fn new(search_match: AgentSessionSearchMatch) -> Self {
+        let positions = search_match
+            .highlights
+            .iter()
+            .flat_map(|range| {
+                search_match.snippet[range.clone()]
+                    .char_indices()
+                    .map(move |(ix, _)| range.start + ix)
+            })
+            .collect();
+        Self {
+            user_message_id: search_match.user_message_id,
+            snippet: search_match.snippet,
+            positions,
+        }
+    }
+}
+
 impl ListItemType {
     fn history_entry(&self) -> Option<&AgentSessionInfo> {
         match self {
@@ -64,10 +94,24 @@ impl ListItemType {
             _ => None,
         }
     }
+
+    fn matched_message_id(&self) -> Option<&UserMessageId> {
+        match self {
+            ListItemType::SearchResult {
+                content_match: Some(content_match),
+                ..
+            } => content_match.user_message_id.as_ref(),
+            _ => None,
+        }
+    }
 }
 
 pub enum ThreadHistoryEvent {
     Open(AgentSessionInfo),
+    OpenAtMessage {
+        thread: AgentSessionInfo,
+        message_id: UserMessageId,
+    },
 }
 
 impl EventEmitter<ThreadHistoryEvent> for AcpThreadHistory {}
@@ -122,10 +166,20 @@ impl AcpThreadHistory {
 
     fn update_visible_items(&mut self, preserve_selected_item: bool, cx: &mut Context<Self>) {
         let entries = self.sessions.clone();
-        let new_list_items = if self.search_query.is_empty() {
-            self.add_list_separators(entries, cx)
+        let (new_list_items, content_matches) = if self.search_query.is_empty() {
+            (self.add_list_separators(entries, cx), None)
         } else {
-            self.filter_search_results(entries, cx)
+            let content_matches = self
+                .session_list
+                .as_ref()
+                .filter(|session_list| session_list.supports_search())
+                .map(|session_list| {
+                    session_list.search_sessions(self.search_query.to_string(), cx)
+                });
+            (
+                self.filter_search_results(entries.clone(), cx),
+                content_matches.map(|task| (task, entries)),
+            )
         };
         let selected_history_entry = if preserve_selected_item {
             self.selected_history_entry().cloned()
@@ -134,7 +188,15 @@ impl AcpThreadHistory {
         };
 
         self._visible_items_task = cx.spawn(async move |this, cx| {
-            let new_visible_items = new_list_items.await;
+            let mut new_visible_items = new_list_items.await;
+            if let Some((content_matches, entries)) = content_matches {
+                match content_matches.await {
+                    Ok(content_matches) => {
+                        add_content_matches(&mut new_visible_items, &entries, content_matches)
+                    }
+                    Err(error) => log::error!("Failed to search threads: {error:#}"),
+                }
+            }
             this.update(cx, |this, cx| {
                 let new_selected_index = if let Some(history_entry) = selected_history_entry {
                     new_visible_items
@@ -366,6 +428,12 @@ impl AcpThreadHistory {
             .unwrap_or(false)
     }
 
+    fn supports_search(&self) -> bool {
+        self.session_list
+            .as_ref()
+            .is_some_and(|session_list| session_list.supports_search())
+    }
+
     pub(crate) fn delete_session(
         &self,
         session_id: &acp::SessionId,
@@ -444,6 +512,7 @@ impl AcpThreadHistory {
                     .map(|search_match| ListItemType::SearchResult {
                         entry: entries[search_match.candidate_id].clone(),
                         positions: search_match.positions,
+                        content_match: None,
                     })
                     .collect()
             }
@@ -538,10 +607,20 @@ impl AcpThreadHistory {
     }
 
     fn confirm_entry(&mut self, ix: usize, cx: &mut Context<Self>) {
-        let Some(entry) = self.get_history_entry(ix) else {
+        let Some(item) = self.visible_items.get(ix) else {
+            return;
+        };
+        let Some(entry) = item.history_entry() else {
             return;
         };
-        cx.emit(ThreadHistoryEvent::Open(entry.clone()));
+        if let Some(message_id) = item.matched_message_id() {
+            cx.emit(ThreadHistoryEvent::OpenAtMessage {
+                thread: entry.clone(),
+                message_id: message_id.clone(),
+            });
+        } else {
+            cx.emit(ThreadHistoryEvent::Open(entry.clone()));
+        }
     }
 
     fn remove_selected_thread(
@@ -607,13 +686,27 @@ impl AcpThreadHistory {
     fn render_list_item(&self, item: &ListItemType, ix: usize, cx: &Context<Self>) -> AnyElement {
         match item {
             ListItemType::Entry { entry, format } => self
-                .render_history_entry(entry, *format, ix, Vec::default(), cx)
+                .render_history_entry(entry, *format, ix, Vec::default(), None, cx)
                 .into_any(),
-            ListItemType::SearchResult { entry, positions } => self.render_history_entry(
+            ListItemType::SearchResult {
+                entry,
+                positions,
+                content_match,
+            } => self.render_history_entry(
                 entry,
                 EntryTimeFormat::DateAndTime,
                 ix,
                 positions.clone(),
+                // Every search result has a line for the snippet when content search
+                // is available, since the list requires its items to have the same height.
+                self.supports_search().then(|| {
+                    content_match.as_ref().map(|content_match| {
+                        HighlightedLabel::new(
+                            content_match.snippet.clone(),
+                            content_match.positions.clone(),
+                        )
+                    })
+                }),
                 cx,
             ),
             ListItemType::BucketSeparator(bucket) => div()
@@ -635,6 +728,7 @@ impl AcpThreadHistory {
         format: EntryTimeFormat,
         ix: usize,
         highlight_positions: Vec<usize>,
+        snippet: Option<Option<HighlightedLabel>>,
         cx: &Context<Self>,
     ) -> AnyElement {
         let selected = ix == self.selected_index;
@@ -670,20 +764,37 @@ impl AcpThreadHistory {
                     .toggle_state(selected)
                     .spacing(ListItemSpacing::Sparse)
                     .start_slot(
-                        h_flex()
+                        v_flex()
                             .w_full()
-                            .gap_2()
-                            .justify_between()
                             .child(
-                                HighlightedLabel::new(thread_title(entry), highlight_positions)
-                                    .size(LabelSize::Small)
-                                    .truncate(),
+                                h_flex()
+                                    .w_full()
+                                    .gap_2()
+                                    .justify_between()
+                                    .child(
+                                        HighlightedLabel::new(
+                                            thread_title(entry),
+                                            highlight_positions,
+                                        )
+                                        .size(LabelSize::Small)
+                                        .truncate(),
+                                    )
+                                    .child(
+                                        Label::new(display_text)
+                                            .color(Color::Muted)
+                                            .size(LabelSize::XSmall),
+                                    ),
                             )
-                            .child(
-                                Label::new(display_text)
-                                    .color(Color::Muted)
-                                    .size(LabelSize::XSmall),
-                            ),
+                            .when_some(snippet, |this, snippet| {
+                                this.child(h_flex().h_4().w_full().children(snippet.map(
+                                    |snippet| {
+                                        snippet
+                                            .size(LabelSize::XSmall)
+                                            .color(Color::Muted)
+                                            .truncate()
+                                    },
+                                )))
+                            }),
                     )
                     .tooltip(move |_, cx| {
                         Tooltip::with_meta(title.clone(), None, full_date.clone(), cx)
@@ -720,6 +831,37 @@ impl AcpThreadHistory {
     }
 }
 
+/// Adds the threads whose content matched the search to the results, after those whose
+/// title matched.
+fn add_content_matches(
+    items: &mut Vec<ListItemType>,
+    entries: &[AgentSessionInfo],
+    content_matches: Vec<AgentSessionSearchMatch>,
+) {
+    for search_match in content_matches {
+        let existing_item = items.iter_mut().find_map(|item| match item {
+            ListItemType::SearchResult {
+                entry,
+                content_match,
+                ..
+            } if entry.session_id == search_match.session_id => Some(content_match),
+            _ => None,
+        });
+        if let Some(content_match) = existing_item {
+            *content_match = Some(ContentMatch::new(search_match));
+        } else if let Some(entry) = entries
+            .iter()
+            .find(|entry| entry.session_id == search_match.session_id)
+        {
+            items.push(ListItemType::SearchResult {
+                entry: entry.clone(),
+                positions: Vec::new(),
+                content_match: Some(ContentMatch::new(search_match)),
+            });
+        }
+    }
+}
+
 impl Focusable for AcpThreadHistory {
     fn focus_handle(&self, cx: &App) -> FocusHandle {
         self.search_editor.focus_handle(cx)
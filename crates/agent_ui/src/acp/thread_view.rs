@@ -1,12 +1,14 @@
 use acp_thread::{
     AcpThread, AcpThreadEvent, AgentSessionInfo, AgentThreadEntry, AssistantMessage,
-    AssistantMessageChunk, AuthRequired, LoadError, MentionUri, PermissionOptionChoice,
-    PermissionOptions, RetryStatus, ThreadStatus, ToolCall, ToolCallContent, ToolCallStatus,
-    UserMessageId,
+    AssistantMessageChunk, AuthRequired, Elicitation, LoadError, MentionUri,
+    PermissionOptionChoice, PermissionOptions, RetryStatus, ThreadStatus, ToolCall,
+    ToolCallContent, ToolCallStatus, UserMessageId,
 };
 use acp_thread::{AgentConnection, Plan};
 use action_log::{ActionLog, ActionLogTelemetry};
-use agent::{NativeAgentServer, NativeAgentSessionList, SharedThread, ThreadStore};
+use agent::{
+    AgentGitWorktreeInfo, NativeAgentServer, NativeAgentSessionList, SharedThread, ThreadStore,
+};
 use agent_client_protocol::{self as acp, PromptCapabilities};
 use agent_servers::{AgentServer, AgentServerDelegate};
 use agent_settings::{AgentProfileId, AgentSettings};
@@ -16,6 +18,7 @@ use audio::{Audio, Sound};
 use buffer_diff::BufferDiff;
 use client::zed_urls;
 use collections::{HashMap, HashSet};
+use context_server::types::ElicitResult;
 use editor::scroll::Autoscroll;
 use editor::{
     Editor, EditorEvent, EditorMode, MultiBuffer, PathKey, SelectionEffects, SizingBehavior,
@@ -24,6 +27,7 @@ use feature_flags::{AgentSharingFeatureFlag, AgentV2FeatureFlag, FeatureFlagAppE
 use file_icons::FileIcons;
 use fs::Fs;
 use futures::FutureExt as _;
+use git::repository::validate_worktree_directory;
 use gpui::{
     Action, Animation, AnimationExt, AnyView, App, ClickEvent, ClipboardItem, CursorStyle,
     ElementId, Empty, Entity, FocusHandle, Focusable, Hsla, ListOffset, ListState, ObjectFit,
@@ -34,7 +38,10 @@ use gpui::{
 use language::Buffer;
 use language_model::LanguageModelRegistry;
 use markdown::{Markdown, MarkdownElement, MarkdownFont, MarkdownStyle};
-use project::{AgentServerStore, ExternalAgentServerName, Project, ProjectEntryId};
+use project::{
+    AgentServerStore, ExternalAgentServerName, Project, ProjectEntryId,
+    git_store::GitStoreCheckpoint, project_settings::ProjectSettings,
+};
 use prompt_store::{PromptId, PromptStore};
 use rope::Point;
 use settings::{NotifyWhenAgentWaiting, Settings as _, SettingsStore};
@@ -55,7 +62,8 @@ use ui::{
 use util::{ResultExt, size::format_file_size, time::duration_alt_display};
 use util::{debug_panic, defer};
 use workspace::{
-    CollaboratorId, MultiWorkspace, NewTerminal, Toast, Workspace, notifications::NotificationId,
+    CollaboratorId, MultiWorkspace, NewTerminal, Toast, Workspace,
+    notifications::{DetachAndPromptErr as _, NotificationId},
 };
 use zed_actions::agent::{Chat, ToggleModelSelector};
 use zed_actions::assistant::OpenRulesLibrary;
@@ -173,6 +181,8 @@ pub struct AcpServerView {
     notifications: Vec<WindowHandle<AgentNotification>>,
     notification_subscriptions: HashMap<WindowHandle<AgentNotification>, Vec<Subscription>>,
     auth_task: Option<Task<()>>,
+    /// The user message to scroll to once the thread has loaded.
+    pending_scroll_to_message: Option<UserMessageId>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -360,12 +370,23 @@ impl AcpServerView {
             notifications: Vec::new(),
             notification_subscriptions: HashMap::default(),
             auth_task: None,
+            pending_scroll_to_message: None,
             history,
             _subscriptions: subscriptions,
             focus_handle: cx.focus_handle(),
         }
     }
 
+    /// Scrolls to the given user message in the active thread, waiting for the
+    /// thread to load if it hasn't yet.
+    pub fn scroll_to_user_message(&mut self, id: UserMessageId, cx: &mut Context<Self>) {
+        if let Some(active) = self.active_thread() {
+            active.update(cx, |active, cx| active.scroll_to_user_message(&id, cx));
+        } else {
+            self.pending_scroll_to_message = Some(id);
+        }
+    }
+
     fn set_server_state(&mut self, state: ServerState, cx: &mut Context<Self>) {
         if let Some(connected) = self.as_connected() {
             connected.close_all_sessions(cx).detach();
@@ -568,12 +589,19 @@ impl AcpServerView {
                                 connection,
                                 auth_state: AuthState::Ok,
                                 active_id: Some(id.clone()),
-                                threads: HashMap::from_iter([(id, current)]),
+                                threads: HashMap::from_iter([(id, current.clone())]),
                             }),
                             cx,
                         );
+
+                        if let Some(message_id) = this.pending_scroll_to_message.take() {
+                            current.update(cx, |current, cx| {
+                                current.scroll_to_user_message(&message_id, cx)
+                            });
+                        }
                     }
                     Err(err) => {
+                        this.pending_scroll_to_message = None;
                         this.handle_load_error(err, window, cx);
                     }
                 };
@@ -1048,6 +1076,9 @@ impl AcpServerView {
             AcpThreadEvent::ToolAuthorizationRequired => {
                 self.notify_with_sound("Waiting for tool confirmation", IconName::Info, window, cx);
             }
+            AcpThreadEvent::ToolInputRequired => {
+                self.notify_with_sound("Waiting for tool input", IconName::Info, window, cx);
+            }
             AcpThreadEvent::Retry(retry) => {
                 if let Some(active) = self.thread_view(&thread_id) {
                     active.update(cx, |active, _cx| {
@@ -1,6 +1,6 @@
 use cloud_api_types::{SubmitAgentThreadFeedbackBody, SubmitAgentThreadFeedbackCommentsBody};
 use gpui::{Corner, List};
-use language_model::LanguageModelEffortLevel;
+use language_model::{LanguageModelEffortLevel, confirm_usage_budget};
 use settings::update_settings_file;
 use ui::{ButtonLike, SplitButton, SplitButtonStyle, Tab};
 
@@ -688,6 +688,25 @@ impl AcpThreadView {
             }
         }
 
+        if self.as_native_thread(cx).is_some()
+            && let Some(confirm) = confirm_usage_budget(
+                AgentSettings::get_global(cx).usage_budget.clone(),
+                window,
+                cx,
+            )
+        {
+            cx.spawn_in(window, async move |this, cx| {
+                if confirm.await {
+                    this.update_in(cx, |this, window, cx| {
+                        this.send_impl(message_editor, window, cx)
+                    })
+                    .ok();
+                }
+            })
+            .detach();
+            return;
+        }
+
         self.send_impl(message_editor, window, cx)
     }
 
@@ -1594,6 +1613,139 @@ impl AcpThreadView {
             .detach_and_log_err(cx);
     }
 
+    /// Forks the thread at the given user message, keeping the history before it, and
+    /// opens the fork with the message in the message editor so it can diverge.
+    ///
+    /// When `in_new_worktree` is true, the fork is given a new git worktree with the
+    /// project's files as they were before the message, and opened in a new window.
+    pub fn fork_thread(
+        &mut self,
+        message_id: UserMessageId,
+        in_new_worktree: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(connection) = self.as_native_connection(cx) else {
+            return;
+        };
+        let thread = self.thread.read(cx);
+        let session_id = thread.session_id().clone();
+        let project = thread.project().clone();
+        let Some(message) = thread.entries().iter().find_map(|entry| {
+            entry
+                .user_message()
+                .filter(|message| message.id.as_ref() == Some(&message_id))
+        }) else {
+            return;
+        };
+        let prompt = message.chunks.clone();
+        let checkpoint = message
+            .checkpoint
+            .as_ref()
+            .map(|checkpoint| checkpoint.git_checkpoint().clone());
+        let create_worktree = if in_new_worktree {
+            let Some(checkpoint) = checkpoint else {
+                return;
+            };
+            Some(Self::create_fork_worktree(&project, &checkpoint, cx))
+        } else {
+            None
+        };
+
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            let git_worktree_info = match create_worktree {
+                Some(create_worktree) => Some(create_worktree.await?),
+                None => None,
+            };
+            let worktree_path = git_worktree_info
+                .as_ref()
+                .map(|info| info.worktree_path.clone());
+            let fork = connection
+                .0
+                .update(cx, |agent, cx| {
+                    agent.fork_thread(session_id, message_id, git_worktree_info, cx)
+                })
+                .await?;
+
+            match worktree_path {
+                Some(worktree_path) => {
+                    let app_state =
+                        workspace.read_with(cx, |workspace, _| workspace.app_state().clone())?;
+                    cx.update(|_, cx| {
+                        AgentPanel::open_forked_thread_in_new_window(
+                            worktree_path,
+                            fork,
+                            prompt,
+                            app_state,
+                            cx,
+                        )
+                    })?
+                    .await?;
+                }
+                None => {
+                    workspace.update_in(cx, |workspace, window, cx| {
+                        if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                            panel.update(cx, |panel, cx| {
+                                panel.open_forked_thread(fork, prompt, window, cx);
+                            });
+                        }
+                    })?;
+                }
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to fork thread", window, cx, |_, _, _| None);
+    }
+
+    /// Creates a git worktree on a new branch, with the files of the project's active
+    /// repository as they were at the given checkpoint.
+    fn create_fork_worktree(
+        project: &Entity<Project>,
+        checkpoint: &GitStoreCheckpoint,
+        cx: &mut App,
+    ) -> Task<Result<AgentGitWorktreeInfo>> {
+        let Some(repository) = project.read(cx).active_repository(cx) else {
+            return Task::ready(Err(anyhow!("The project has no git repository")));
+        };
+        let work_directory = repository.read(cx).work_directory_abs_path.clone();
+        let Some(commit) = checkpoint
+            .repository_checkpoint(&work_directory)
+            .map(|checkpoint| checkpoint.commit_sha.to_string())
+        else {
+            return Task::ready(Err(anyhow!(
+                "No checkpoint was taken for {}",
+                work_directory.display()
+            )));
+        };
+        let directory = match validate_worktree_directory(
+            &work_directory,
+            &ProjectSettings::get_global(cx).git.worktree_directory,
+        ) {
+            Ok(directory) => directory,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        // The random suffix keeps forks made within the same second apart.
+        let branch = format!(
+            "agent-fork-{}-{}",
+            chrono::Utc::now().format("%Y%m%d-%H%M%S"),
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        );
+        let worktree_path = directory.join(&branch);
+        let create_worktree = repository.update(cx, |repository, _| {
+            repository.create_worktree(branch.clone(), directory, Some(commit.clone()))
+        });
+        cx.background_spawn(async move {
+            create_worktree.await??;
+            Ok(AgentGitWorktreeInfo {
+                branch,
+                worktree_path,
+                base_ref: commit,
+            })
+        })
+    }
+
     pub fn clear_thread_error(&mut self, cx: &mut Context<Self>) {
         self.thread_error = None;
         self.thread_error_markdown = None;
@@ -3560,9 +3712,49 @@ impl AcpThreadView {
                                             }),
                                         ),
                                     )
-                                } else if message.id.is_some() {
+                                } else if let Some(message_id) = message.id.clone() {
+                                    let can_fork = self.as_native_connection(cx).is_some();
+                                    let can_fork_into_worktree = can_fork
+                                        && message.checkpoint.is_some()
+                                        && self.thread.read(cx).project().read(cx).is_local();
                                     this.child(
                                         base_container
+                                            .when(can_fork, |this| {
+                                                this.child(
+                                                    IconButton::new("fork-thread", IconName::Split)
+                                                        .icon_color(Color::Muted)
+                                                        .icon_size(IconSize::XSmall)
+                                                        .tooltip(Tooltip::text(
+                                                            "Fork the thread from this message into a new thread."
+                                                        ))
+                                                        .on_click(cx.listener({
+                                                            let message_id = message_id.clone();
+                                                            move |this, _, window, cx| {
+                                                                this.fork_thread(
+                                                                    message_id.clone(), false, window, cx,
+                                                                );
+                                                            }
+                                                        }))
+                                                )
+                                            })
+                                            .when(can_fork_into_worktree, |this| {
+                                                this.child(
+                                                    IconButton::new("fork-thread-into-worktree", IconName::GitBranchPlus)
+                                                        .icon_color(Color::Muted)
+                                                        .icon_size(IconSize::XSmall)
+                                                        .tooltip(Tooltip::text(
+                                                            "Fork the thread from this message into a new git worktree, with the files as they were before it."
+                                                        ))
+                                                        .on_click(cx.listener({
+                                                            let message_id = message_id.clone();
+                                                            move |this, _, window, cx| {
+                                                                this.fork_thread(
+                                                                    message_id.clone(), true, window, cx,
+                                                                );
+                                                            }
+                                                        }))
+                                                )
+                                            })
                                             .child(
                                                 IconButton::new("cancel", IconName::Close)
                                                     .disabled(is_loading_contents)
@@ -4044,6 +4236,22 @@ impl AcpThreadView {
         }
     }
 
+    /// Scrolls the given user message to the top of the viewport, returning whether
+    /// the message was found.
+    pub fn scroll_to_user_message(&mut self, id: &UserMessageId, cx: &mut Context<Self>) -> bool {
+        let Some(ix) = self.thread.read(cx).entries().iter().position(|entry| {
+            matches!(entry, AgentThreadEntry::UserMessage(message) if message.id.as_ref() == Some(id))
+        }) else {
+            return false;
+        };
+        self.list_state.scroll_to(ListOffset {
+            item_ix: ix,
+            offset_in_item: px(0.0),
+        });
+        cx.notify();
+        true
+    }
+
     pub fn scroll_to_bottom(&mut self, cx: &mut Context<Self>) {
         let entry_count = self.thread.read(cx).entries().len();
         self.list_state.reset(entry_count);
@@ -4806,7 +5014,7 @@ impl AcpThreadView {
 
         let needs_confirmation = matches!(
             tool_call.status,
-            ToolCallStatus::WaitingForConfirmation { .. }
+            ToolCallStatus::WaitingForConfirmation { .. } | ToolCallStatus::WaitingForInput { .. }
         );
         let is_terminal_tool = matches!(tool_call.kind, acp::ToolKind::Execute);
 
@@ -4947,6 +5155,9 @@ impl AcpThreadView {
                         cx,
                     ))
                     .into_any(),
+                ToolCallStatus::WaitingForInput { elicitation, .. } => self
+                    .render_elicitation(entry_ix, tool_call, elicitation, cx)
+                    .into_any(),
                 ToolCallStatus::Pending | ToolCallStatus::InProgress
                     if is_edit
                         && tool_call.content.is_empty()
@@ -5155,6 +5366,113 @@ impl AcpThreadView {
             .children(tool_output_display)
     }
 
+    fn render_elicitation(
+        &self,
+        entry_ix: usize,
+        tool_call: &ToolCall,
+        elicitation: &Entity<Elicitation>,
+        cx: &Context<Self>,
+    ) -> Div {
+        let form = self
+            .entry_view_state
+            .read(cx)
+            .entry(entry_ix)
+            .and_then(|entry| entry.elicitation_form(elicitation));
+        let elicitation = elicitation.read(cx);
+
+        v_flex()
+            .w_full()
+            .child(
+                v_flex()
+                    .p_2()
+                    .gap_2()
+                    .child(
+                        Label::new(format!("{} requests input", elicitation.server_name()))
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted),
+                    )
+                    .child(Label::new(elicitation.message().clone()).size(LabelSize::Small))
+                    .children(form.clone()),
+            )
+            .child(
+                h_flex()
+                    .w_full()
+                    .p_1()
+                    .gap_0p5()
+                    .border_t_1()
+                    .border_color(self.tool_card_border_color(cx))
+                    .child(
+                        Button::new(("elicitation-accept", entry_ix), "Submit")
+                            .icon(IconName::Check)
+                            .icon_color(Color::Success)
+                            .icon_position(IconPosition::Start)
+                            .icon_size(IconSize::XSmall)
+                            .label_size(LabelSize::Small)
+                            .on_click(cx.listener({
+                                let tool_call_id = tool_call.id.clone();
+                                move |this, _, _, cx| {
+                                    let Some(form) = form.as_ref() else {
+                                        return;
+                                    };
+                                    if let Some(content) =
+                                        form.update(cx, |form, cx| form.content(cx))
+                                    {
+                                        this.respond_to_elicitation(
+                                            &tool_call_id,
+                                            ElicitResult::accept(content),
+                                            cx,
+                                        );
+                                    }
+                                }
+                            })),
+                    )
+                    .child(
+                        Button::new(("elicitation-decline", entry_ix), "Decline")
+                            .icon(IconName::Close)
+                            .icon_color(Color::Error)
+                            .icon_position(IconPosition::Start)
+                            .icon_size(IconSize::XSmall)
+                            .label_size(LabelSize::Small)
+                            .on_click(cx.listener({
+                                let tool_call_id = tool_call.id.clone();
+                                move |this, _, _, cx| {
+                                    this.respond_to_elicitation(
+                                        &tool_call_id,
+                                        ElicitResult::decline(),
+                                        cx,
+                                    );
+                                }
+                            })),
+                    )
+                    .child(
+                        Button::new(("elicitation-cancel", entry_ix), "Cancel")
+                            .label_size(LabelSize::Small)
+                            .on_click(cx.listener({
+                                let tool_call_id = tool_call.id.clone();
+                                move |this, _, _, cx| {
+                                    this.respond_to_elicitation(
+                                        &tool_call_id,
+                                        ElicitResult::cancel(),
+                                        cx,
+                                    );
+                                }
+                            })),
+                    ),
+            )
+    }
+
+    fn respond_to_elicitation(
+        &mut self,
+        tool_call_id: &acp::ToolCallId,
+        result: ElicitResult,
+        cx: &mut Context<Self>,
+    ) {
+        self.thread.update(cx, |thread, cx| {
+            thread.respond_to_tool_call_input(tool_call_id, result, cx);
+        });
+        cx.notify();
+    }
+
     fn render_permission_buttons(
         &self,
         options: &PermissionOptions,
@@ -7623,6 +7941,7 @@ pub(crate) fn open_link(
             MentionUri::Diagnostics { .. } => {}
             MentionUri::TerminalSelection { .. } => {}
             MentionUri::GitDiff { .. } => {}
+            MentionUri::McpResource { .. } => {}
         })
     } else {
         cx.open_url(&url);
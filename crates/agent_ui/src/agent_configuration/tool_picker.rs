@@ -315,6 +315,8 @@ impl PickerDelegate for ToolPickerDelegate {
                             })
                             .collect(),
                         default_model: default_profile.default_model.clone(),
+                        terminal_sandbox: default_profile.terminal_sandbox.clone(),
+                        command_tools: default_profile.command_tools.clone(),
                     });
 
                 if let Some(server_id) = server_id {
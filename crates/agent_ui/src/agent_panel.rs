@@ -1,6 +1,12 @@
-use std::{ops::Range, path::Path, rc::Rc, sync::Arc, time::Duration};
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+    time::Duration,
+};
 
-use acp_thread::{AcpThread, AgentSessionInfo, MentionUri};
+use acp_thread::{AcpThread, AgentSessionInfo, MentionUri, UserMessageId};
 use agent::{ContextServerRegistry, SharedThread, ThreadStore};
 use agent_client_protocol as acp;
 use agent_servers::AgentServer;
@@ -18,8 +24,8 @@ use crate::ui::{AcpOnboardingModal, ClaudeCodeOnboardingModal};
 use crate::{
     AddContextServer, AgentDiffPane, CopyThreadToClipboard, Follow, InlineAssistant,
     LoadThreadFromClipboard, NewTextThread, NewThread, OpenActiveThreadAsMarkdown, OpenAgentDiff,
-    OpenHistory, ResetTrialEndUpsell, ResetTrialUpsell, ToggleNavigationMenu, ToggleNewThreadMenu,
-    ToggleOptionsMenu,
+    OpenHistory, OpenUsage, ResetTrialEndUpsell, ResetTrialUpsell, ToggleNavigationMenu,
+    ToggleNewThreadMenu, ToggleOptionsMenu,
     acp::AcpServerView,
     agent_configuration::{AgentConfiguration, AssistantConfigurationEvent},
     slash_command::SlashCommandCompletionProvider,
@@ -42,14 +48,15 @@ use assistant_slash_command::SlashCommandWorkingSet;
 use assistant_text_thread::{TextThread, TextThreadEvent, TextThreadSummary};
 use client::UserStore;
 use cloud_api_types::Plan;
+use collections::HashMap;
 use editor::{Anchor, AnchorRangeExt as _, Editor, EditorEvent, MultiBuffer};
 use extension::ExtensionEvents;
 use extension_host::ExtensionStore;
 use fs::Fs;
 use gpui::{
     Action, Animation, AnimationExt, AnyElement, App, AsyncWindowContext, ClipboardItem, Corner,
-    DismissEvent, Entity, EventEmitter, ExternalPaths, FocusHandle, Focusable, KeyContext, Pixels,
-    Subscription, Task, UpdateGlobal, WeakEntity, prelude::*, pulsating_between,
+    DismissEvent, Entity, EventEmitter, ExternalPaths, FocusHandle, Focusable, Global, KeyContext,
+    Pixels, Subscription, Task, UpdateGlobal, WeakEntity, prelude::*, pulsating_between,
 };
 use language::LanguageRegistry;
 use language_model::{ConfigurationError, LanguageModelRegistry};
@@ -65,8 +72,8 @@ use ui::{
 };
 use util::ResultExt as _;
 use workspace::{
-    CollaboratorId, DraggedSelection, DraggedTab, ToggleZoom, ToolbarItemView, Workspace,
-    WorkspaceId,
+    AppState, CollaboratorId, DraggedSelection, DraggedTab, OpenOptions, ToggleZoom,
+    ToolbarItemView, Workspace, WorkspaceId,
     dock::{DockPosition, Panel, PanelEvent},
 };
 use zed_actions::{
@@ -79,6 +86,25 @@ const AGENT_PANEL_KEY: &str = "agent_panel";
 const RECENTLY_UPDATED_MENU_LIMIT: usize = 6;
 const DEFAULT_THREAD_TITLE: &str = "New Thread";
 
+/// Threads forked into a new git worktree, waiting to be opened by the agent panel of
+/// the worktree's window. Keyed by the worktree's path.
+#[derive(Default)]
+struct PendingWorktreeThreads(HashMap<PathBuf, (AgentSessionInfo, Vec<acp::ContentBlock>)>);
+
+impl Global for PendingWorktreeThreads {}
+
+impl PendingWorktreeThreads {
+    fn take(
+        worktree_path: &Path,
+        cx: &mut App,
+    ) -> Option<(AgentSessionInfo, Vec<acp::ContentBlock>)> {
+        if !cx.has_global::<Self>() {
+            return None;
+        }
+        cx.global_mut::<Self>().0.remove(worktree_path)
+    }
+}
+
 fn read_serialized_panel(workspace_id: workspace::WorkspaceId) -> Option<SerializedAgentPanel> {
     let scope = KEY_VALUE_STORE.scoped(AGENT_PANEL_KEY);
     let key = i64::from(workspace_id).to_string();
@@ -524,6 +550,8 @@ pub struct AgentPanel {
     selected_agent: AgentType,
     show_trust_workspace_message: bool,
     last_configuration_error_telemetry: Option<String>,
+    /// The message to scroll to when the thread being loaded from the history opens.
+    pending_scroll_to_message: Option<(acp::SessionId, UserMessageId)>,
 }
 
 impl AgentPanel {
@@ -618,7 +646,26 @@ impl AgentPanel {
                 panel
             })?;
 
-            if let Some(thread_info) = serialized_panel.and_then(|p| p.last_active_thread) {
+            let worktree_path = workspace.read_with(cx, |workspace, cx| {
+                workspace
+                    .project()
+                    .read(cx)
+                    .visible_worktrees(cx)
+                    .next()
+                    .map(|worktree| worktree.read(cx).abs_path())
+            })?;
+            let pending_thread = worktree_path
+                .and_then(|path| {
+                    cx.update(|_, cx| PendingWorktreeThreads::take(&path, cx))
+                        .ok()
+                })
+                .flatten();
+
+            if let Some((thread, prompt)) = pending_thread {
+                panel.update_in(cx, |panel, window, cx| {
+                    panel.open_forked_thread(thread, prompt, window, cx);
+                })?;
+            } else if let Some(thread_info) = serialized_panel.and_then(|p| p.last_active_thread) {
                 let session_id = acp::SessionId::new(thread_info.session_id.clone());
                 let load_task = panel.update(cx, |panel, cx| {
                     let thread_store = panel.thread_store.clone();
@@ -683,6 +730,11 @@ impl AgentPanel {
                 ThreadHistoryEvent::Open(thread) => {
                     this.load_agent_thread(thread.clone(), window, cx);
                 }
+                ThreadHistoryEvent::OpenAtMessage { thread, message_id } => {
+                    this.pending_scroll_to_message =
+                        Some((thread.session_id.clone(), message_id.clone()));
+                    this.load_agent_thread(thread.clone(), window, cx);
+                }
             },
         )
         .detach();
@@ -803,6 +855,7 @@ impl AgentPanel {
             selected_agent: AgentType::default(),
             show_trust_workspace_message: false,
             last_configuration_error_telemetry: None,
+            pending_scroll_to_message: None,
         };
 
         // Initial sync of agent servers from extensions
@@ -867,6 +920,55 @@ impl AgentPanel {
         );
     }
 
+    /// Opens a thread forked from another one, with the message it was forked at in the
+    /// message editor, so it can be edited and sent again.
+    pub fn open_forked_thread(
+        &mut self,
+        thread: AgentSessionInfo,
+        prompt: Vec<acp::ContentBlock>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let initial_content = (!prompt.is_empty()).then(|| AgentInitialContent::ContentBlock {
+            blocks: prompt,
+            auto_submit: false,
+        });
+        self.external_thread(
+            Some(crate::ExternalAgent::NativeAgent),
+            Some(thread),
+            initial_content,
+            window,
+            cx,
+        );
+    }
+
+    /// Opens the git worktree a thread was forked into in a new window, and shows the
+    /// thread in that window's agent panel.
+    pub fn open_forked_thread_in_new_window(
+        worktree_path: PathBuf,
+        thread: AgentSessionInfo,
+        prompt: Vec<acp::ContentBlock>,
+        app_state: Arc<AppState>,
+        cx: &mut App,
+    ) -> Task<Result<()>> {
+        cx.default_global::<PendingWorktreeThreads>()
+            .0
+            .insert(worktree_path.clone(), (thread, prompt));
+        let open = workspace::open_paths(
+            &[worktree_path],
+            app_state,
+            OpenOptions {
+                open_new_workspace: Some(true),
+                ..Default::default()
+            },
+            cx,
+        );
+        cx.spawn(async move |_| {
+            open.await?;
+            Ok(())
+        })
+    }
+
     pub(crate) fn context_server_registry(&self) -> &Entity<ContextServerRegistry> {
         &self.context_server_registry
     }
@@ -1863,9 +1965,18 @@ impl AgentPanel {
             .downcast::<agent::NativeAgentServer>()
             .is_some()
             .then(|| self.thread_store.clone());
+        let scroll_to_message = self
+            .pending_scroll_to_message
+            .take()
+            .filter(|(session_id, _)| {
+                resume_thread
+                    .as_ref()
+                    .is_some_and(|thread| &thread.session_id == session_id)
+            })
+            .map(|(_, message_id)| message_id);
 
         let server_view = cx.new(|cx| {
-            crate::acp::AcpServerView::new(
+            let mut server_view = crate::acp::AcpServerView::new(
                 server,
                 resume_thread,
                 initial_content,
@@ -1876,7 +1987,11 @@ impl AgentPanel {
                 self.acp_history.clone(),
                 window,
                 cx,
-            )
+            );
+            if let Some(message_id) = scroll_to_message {
+                server_view.scroll_to_user_message(message_id, cx);
+            }
+            server_view
         });
 
         self.set_active_view(ActiveView::AgentThread { server_view }, true, window, cx);
@@ -2271,6 +2386,7 @@ impl AgentPanel {
                             .separator()
                             .action("Rules", Box::new(OpenRulesLibrary::default()))
                             .action("Profiles", Box::new(ManageProfiles::default()))
+                            .action("Usage", Box::new(OpenUsage))
                             .action("Settings", Box::new(OpenSettings))
                             .separator()
                             .action(full_screen_label, Box::new(ToggleZoom));
@@ -12,6 +12,7 @@ mod favorite_models;
 mod inline_assistant;
 mod inline_prompt_editor;
 mod language_model_selector;
+mod language_model_usage_modal;
 mod mention_set;
 mod profile_selector;
 mod slash_command;
@@ -52,6 +53,7 @@ use crate::agent_configuration::{ConfigureContextServerModal, ManageProfilesModa
 pub use crate::agent_panel::{AgentPanel, AgentPanelEvent, ConcreteAssistantPanelDelegate};
 use crate::agent_registry_ui::AgentRegistryPage;
 pub use crate::inline_assistant::InlineAssistant;
+use crate::language_model_usage_modal::LanguageModelUsageModal;
 pub use agent_diff::{AgentDiffPane, AgentDiffToolbar};
 pub use text_thread_editor::{AgentPanelDelegate, TextThreadEditor};
 use zed_actions;
@@ -79,6 +81,8 @@ actions!(
         RemoveHistory,
         /// Opens the conversation history view.
         OpenHistory,
+        /// Opens a summary of the tokens used by language model requests and their estimated cost.
+        OpenUsage,
         /// Adds a context server to the configuration.
         AddContextServer,
         /// Removes the currently selected thread.
@@ -326,6 +330,7 @@ pub fn init(
     })
     .detach();
     cx.observe_new(ManageProfilesModal::register).detach();
+    cx.observe_new(LanguageModelUsageModal::register).detach();
 
     // Update command palette filter based on AI settings
     update_command_palette_filter(cx);
@@ -482,7 +487,9 @@ fn update_active_language_model_from_settings(cx: &mut App) {
         .iter()
         .map(to_selected_model)
         .collect::<Vec<_>>();
+    let model_pricing = settings.model_pricing.clone();
 
+    language_model::set_language_model_pricing(model_pricing, cx);
     LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
         registry.select_default_model(default.as_ref(), cx);
         registry.select_inline_assistant_model(inline_assistant.as_ref(), cx);
@@ -573,6 +580,10 @@ mod tests {
             message_editor_min_lines: 1,
             tool_permissions: Default::default(),
             show_turn_stats: false,
+            hooks: Default::default(),
+            terminal_sandbox: Default::default(),
+            model_pricing: vec![],
+            usage_budget: Default::default(),
         };
 
         cx.update(|cx| {
@@ -19,7 +19,7 @@ use language_model::{
     LanguageModel, LanguageModelCompletionError, LanguageModelCompletionEvent,
     LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
     LanguageModelRequestTool, LanguageModelTextStream, LanguageModelToolChoice,
-    LanguageModelToolUse, Role, TokenUsage,
+    LanguageModelToolUse, LanguageModelUsageFeature, LanguageModelUsageTracker, Role, TokenUsage,
 };
 use multi_buffer::MultiBufferRow;
 use parking_lot::Mutex;
@@ -174,6 +174,7 @@ impl BufferCodegen {
         primary_model: Arc<dyn LanguageModel>,
         user_prompt: String,
         context_task: Shared<Task<Option<LoadedContext>>>,
+        project: Option<String>,
         cx: &mut Context<Self>,
     ) -> Result<()> {
         let alternative_models = LanguageModelRegistry::read_global(cx)
@@ -203,7 +204,13 @@ impl BufferCodegen {
             .zip(&self.alternatives)
         {
             alternative.update(cx, |alternative, cx| {
-                alternative.start(user_prompt.clone(), context_task.clone(), model.clone(), cx)
+                alternative.start(
+                    user_prompt.clone(),
+                    context_task.clone(),
+                    model.clone(),
+                    project.clone(),
+                    cx,
+                )
             })?;
         }
 
@@ -408,6 +415,7 @@ impl CodegenAlternative {
         user_prompt: String,
         context_task: Shared<Task<Option<LoadedContext>>>,
         model: Arc<dyn LanguageModel>,
+        project: Option<String>,
         cx: &mut Context<Self>,
     ) -> Result<()> {
         // Clear the model explanation since the user has started a new generation.
@@ -421,11 +429,21 @@ impl CodegenAlternative {
 
         self.edit_position = Some(self.range.start.bias_right(&self.snapshot));
 
+        let usage_tracker = LanguageModelUsageTracker::new(
+            model.provider_id(),
+            model.id(),
+            LanguageModelUsageFeature::InlineAssistant,
+            project,
+            cx,
+        );
         if Self::use_streaming_tools(model.as_ref(), cx) {
             let request = self.build_request(&model, user_prompt, context_task, cx)?;
             let completion_events = cx.spawn({
                 let model = model.clone();
-                async move |_, cx| model.stream_completion(request.await, cx).await
+                async move |_, cx| {
+                    let events = model.stream_completion(request.await, cx).await?;
+                    Ok(usage_tracker.track(events))
+                }
             });
             self.generation = self.handle_completion(model, completion_events, cx);
         } else {
@@ -437,7 +455,8 @@ impl CodegenAlternative {
                     cx.spawn({
                         let model = model.clone();
                         async move |_, cx| {
-                            Ok(model.stream_completion_text(request.await, cx).await?)
+                            let stream = model.stream_completion_text(request.await, cx).await?;
+                            Ok(usage_tracker.track_text(stream))
                         }
                     })
                     .boxed_local()
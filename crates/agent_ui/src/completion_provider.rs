@@ -7,6 +7,8 @@ use std::sync::atomic::AtomicBool;
 use crate::acp::AcpThreadHistory;
 use acp_thread::{AgentSessionInfo, MentionUri};
 use anyhow::Result;
+use context_server::ContextServerId;
+use context_server::protocol::InitializedContextServerProtocol;
 use editor::{
     CompletionProvider, Editor, ExcerptId, code_context_menus::COMPLETION_MENU_MAX_WIDTH,
 };
@@ -63,6 +65,7 @@ pub(crate) enum PromptContextType {
     Thread,
     Rules,
     Diagnostics,
+    McpResource,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -101,6 +104,7 @@ impl TryFrom<&str> for PromptContextType {
             "thread" => Ok(Self::Thread),
             "rule" => Ok(Self::Rules),
             "diagnostics" => Ok(Self::Diagnostics),
+            "mcp" => Ok(Self::McpResource),
             _ => Err(format!("Invalid context picker mode: {}", value)),
         }
     }
@@ -115,6 +119,7 @@ impl PromptContextType {
             Self::Thread => "thread",
             Self::Rules => "rule",
             Self::Diagnostics => "diagnostics",
+            Self::McpResource => "mcp",
         }
     }
 
@@ -126,6 +131,7 @@ impl PromptContextType {
             Self::Thread => "Threads",
             Self::Rules => "Rules",
             Self::Diagnostics => "Diagnostics",
+            Self::McpResource => "MCP Resources",
         }
     }
 
@@ -137,6 +143,7 @@ impl PromptContextType {
             Self::Thread => IconName::Thread,
             Self::Rules => IconName::Reader,
             Self::Diagnostics => IconName::Warning,
+            Self::McpResource => IconName::ToolHammer,
         }
     }
 }
@@ -148,6 +155,7 @@ pub(crate) enum Match {
     RecentThread(AgentSessionInfo),
     Fetch(SharedString),
     Rules(RulesContextEntry),
+    McpResource(McpResourceMatch),
     Entry(EntryMatch),
 }
 
@@ -161,6 +169,7 @@ impl Match {
             Match::Symbol(_) => 1.,
             Match::Rules(_) => 1.,
             Match::Fetch(_) => 1.,
+            Match::McpResource(_) => 1.,
         }
     }
 }
@@ -523,6 +532,108 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
         })
     }
 
+    fn completion_for_mcp_resource(
+        mcp_match: McpResourceMatch,
+        source_range: Range<Anchor>,
+        source: Arc<T>,
+        editor: WeakEntity<Editor>,
+        mention_set: WeakEntity<MentionSet>,
+        workspace: Entity<Workspace>,
+        cx: &mut App,
+    ) -> Option<Completion> {
+        let keyword = PromptContextType::McpResource.keyword();
+        let (label, documentation, uri) = match mcp_match {
+            McpResourceMatch::Resource {
+                server_id,
+                resource,
+            } => (
+                resource.name.clone(),
+                resource.description,
+                MentionUri::McpResource {
+                    server_id: server_id.0.to_string(),
+                    uri: resource.uri,
+                    name: resource.name,
+                },
+            ),
+            McpResourceMatch::Template { template, prefix } => {
+                // Insert the beginning of the template, and keep the menu open
+                // so that its parameters can be completed.
+                return Some(Completion {
+                    replace_range: source_range,
+                    new_text: format!("@{keyword} {prefix}"),
+                    label: CodeLabel::plain(template.name, None),
+                    documentation: Some(CompletionDocumentation::SingleLine(
+                        template.uri_template.into(),
+                    )),
+                    source: project::CompletionSource::Custom,
+                    icon_path: Some(PromptContextType::McpResource.icon().path().into()),
+                    match_start: None,
+                    snippet_deduplication_key: None,
+                    insert_text_mode: None,
+                    confirm: Some(Arc::new(|_, _, _| true)),
+                });
+            }
+            McpResourceMatch::TemplateValue {
+                server_id,
+                value,
+                text,
+                is_complete,
+            } => {
+                if !is_complete {
+                    return Some(Completion {
+                        replace_range: source_range,
+                        new_text: format!("@{keyword} {text}"),
+                        label: CodeLabel::plain(value, None),
+                        documentation: None,
+                        source: project::CompletionSource::Custom,
+                        icon_path: None,
+                        match_start: None,
+                        snippet_deduplication_key: None,
+                        insert_text_mode: None,
+                        confirm: Some(Arc::new(|_, _, _| true)),
+                    });
+                }
+
+                let uri = url::Url::parse(&text).ok()?;
+                (
+                    value,
+                    None,
+                    MentionUri::McpResource {
+                        server_id: server_id.0.to_string(),
+                        name: uri.to_string(),
+                        uri,
+                    },
+                )
+            }
+        };
+
+        let new_text = format!("{} ", uri.as_link());
+        let new_text_len = new_text.len();
+        let icon_path = uri.icon_path(cx);
+        Some(Completion {
+            replace_range: source_range.clone(),
+            new_text,
+            label: CodeLabel::plain(label, None),
+            documentation: documentation
+                .map(|description| CompletionDocumentation::SingleLine(description.into())),
+            source: project::CompletionSource::Custom,
+            icon_path: Some(icon_path),
+            match_start: None,
+            snippet_deduplication_key: None,
+            insert_text_mode: None,
+            confirm: Some(confirm_completion_callback(
+                uri.name().into(),
+                source_range.start,
+                new_text_len - 1,
+                uri,
+                source,
+                editor,
+                mention_set,
+                workspace,
+            )),
+        })
+    }
+
     pub(crate) fn completion_for_action(
         action: PromptContextAction,
         source_range: Range<Anchor>,
@@ -877,6 +988,17 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
 
             Some(PromptContextType::Diagnostics) => Task::ready(Vec::new()),
 
+            Some(PromptContextType::McpResource) => {
+                let search_task = search_mcp_resources(query, cancellation_flag, &workspace, cx);
+                cx.background_spawn(async move {
+                    search_task
+                        .await
+                        .into_iter()
+                        .map(Match::McpResource)
+                        .collect()
+                })
+            }
+
             None if query.is_empty() => {
                 let recent_task = self.recent_context_picker_entries(&workspace, cx);
                 let entries = self
@@ -1075,6 +1197,14 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
             entries.push(PromptContextEntry::Mode(PromptContextType::Fetch));
         }
 
+        if self
+            .source
+            .supports_context(PromptContextType::McpResource, cx)
+            && !mcp_resource_clients(workspace, cx).is_empty()
+        {
+            entries.push(PromptContextEntry::Mode(PromptContextType::McpResource));
+        }
+
         if self
             .source
             .supports_context(PromptContextType::Diagnostics, cx)
@@ -1334,6 +1464,15 @@ impl<T: PromptCompletionProviderDelegate> CompletionProvider for PromptCompletio
                                     workspace.clone(),
                                     cx,
                                 ),
+                                Match::McpResource(mcp_match) => Self::completion_for_mcp_resource(
+                                    mcp_match,
+                                    source_range.clone(),
+                                    source.clone(),
+                                    editor.clone(),
+                                    mention_set.clone(),
+                                    workspace.clone(),
+                                    cx,
+                                ),
                                 Match::Entry(EntryMatch { entry, .. }) => {
                                     Self::completion_for_entry(
                                         entry,
@@ -1944,6 +2083,362 @@ pub(crate) fn search_rules(
     })
 }
 
+pub(crate) enum McpResourceMatch {
+    Resource {
+        server_id: ContextServerId,
+        resource: context_server::types::Resource,
+    },
+    Template {
+        template: context_server::types::ResourceTemplate,
+        /// The text of the template preceding its first parameter.
+        prefix: String,
+    },
+    TemplateValue {
+        server_id: ContextServerId,
+        value: String,
+        /// The template expanded up to (and including) this value.
+        text: String,
+        is_complete: bool,
+    },
+}
+
+fn mcp_resource_clients(
+    workspace: &Entity<Workspace>,
+    cx: &App,
+) -> Vec<(ContextServerId, Arc<InitializedContextServerProtocol>)> {
+    workspace
+        .read(cx)
+        .project()
+        .read(cx)
+        .context_server_store()
+        .read(cx)
+        .running_servers()
+        .into_iter()
+        .filter_map(|server| {
+            let client = server.client()?;
+            client
+                .capable(context_server::protocol::ServerCapability::Resources)
+                .then(|| (server.id(), client))
+        })
+        .collect()
+}
+
+/// Lists the resources and resource templates of all running MCP servers
+/// matching `query`. When `query` starts with the text of a resource template,
+/// the value of the parameter being typed is completed instead.
+pub(crate) fn search_mcp_resources(
+    query: String,
+    cancellation_flag: Arc<AtomicBool>,
+    workspace: &Entity<Workspace>,
+    cx: &mut App,
+) -> Task<Vec<McpResourceMatch>> {
+    let clients = mcp_resource_clients(workspace, cx);
+    let executor = cx.background_executor().clone();
+    cx.background_spawn(async move {
+        let mut resources = Vec::new();
+        let mut templates = Vec::new();
+        for (server_id, client) in clients {
+            if let Some(response) = client
+                .request::<context_server::types::requests::ResourcesList>(())
+                .await
+                .log_err()
+            {
+                resources.extend(
+                    response
+                        .resources
+                        .into_iter()
+                        .map(|resource| (server_id.clone(), resource)),
+                );
+            }
+            if let Some(response) = client
+                .request::<context_server::types::requests::ListResourceTemplates>(())
+                .await
+                .log_err()
+            {
+                templates.extend(
+                    response
+                        .resource_templates
+                        .into_iter()
+                        .map(|template| (server_id.clone(), client.clone(), template)),
+                );
+            }
+        }
+
+        for (server_id, client, template) in &templates {
+            let Some(uri_template) = UriTemplate::parse(&template.uri_template) else {
+                continue;
+            };
+            if let Some(values) = uri_template.match_prefix(&query) {
+                return complete_mcp_resource_template(
+                    server_id.clone(),
+                    client,
+                    template,
+                    &uri_template,
+                    values,
+                )
+                .await;
+            }
+        }
+
+        let candidates = resources
+            .iter()
+            .map(|(_, resource)| format!("{} {}", resource.name, resource.uri))
+            .chain(
+                templates
+                    .iter()
+                    .map(|(_, _, template)| format!("{} {}", template.name, template.uri_template)),
+            )
+            .enumerate()
+            .map(|(id, text)| StringMatchCandidate::new(id, &text))
+            .collect::<Vec<_>>();
+        let matches = if query.is_empty() {
+            (0..candidates.len()).collect::<Vec<_>>()
+        } else {
+            fuzzy::match_strings(
+                &candidates,
+                &query,
+                false,
+                true,
+                100,
+                &cancellation_flag,
+                executor,
+            )
+            .await
+            .into_iter()
+            .map(|mat| mat.candidate_id)
+            .collect()
+        };
+
+        let mut resources = resources.into_iter().map(Some).collect::<Vec<_>>();
+        let mut templates = templates.into_iter().map(Some).collect::<Vec<_>>();
+        matches
+            .into_iter()
+            .filter_map(|ix| {
+                if ix < resources.len() {
+                    let (server_id, resource) = resources[ix].take()?;
+                    Some(McpResourceMatch::Resource {
+                        server_id,
+                        resource,
+                    })
+                } else {
+                    let (_, _, template) = templates[ix - resources.len()].take()?;
+                    let prefix = UriTemplate::parse(&template.uri_template)?
+                        .literal_prefix()?
+                        .to_string();
+                    Some(McpResourceMatch::Template { template, prefix })
+                }
+            })
+            .collect()
+    })
+}
+
+async fn complete_mcp_resource_template(
+    server_id: ContextServerId,
+    client: &InitializedContextServerProtocol,
+    template: &context_server::types::ResourceTemplate,
+    uri_template: &UriTemplate,
+    mut values: Vec<String>,
+) -> Vec<McpResourceMatch> {
+    let Some(typed_value) = values.pop() else {
+        return Vec::new();
+    };
+    let Some((name, reserved)) = uri_template.variables().nth(values.len()) else {
+        return Vec::new();
+    };
+
+    let mut completions = Vec::new();
+    if client.initialize.capabilities.completions.is_some()
+        && let Some(response) = client
+            .request::<context_server::types::requests::CompletionComplete>(
+                context_server::types::CompletionCompleteParams {
+                    reference: context_server::types::CompletionReference::Resource(
+                        context_server::types::ResourceReference {
+                            ty: context_server::types::PromptReferenceType::Resource,
+                            uri: template.uri_template.clone(),
+                        },
+                    ),
+                    argument: context_server::types::CompletionArgument {
+                        name: name.to_string(),
+                        value: urlencoding::decode(&typed_value)
+                            .map(|value| value.into_owned())
+                            .unwrap_or_else(|_| typed_value.clone()),
+                    },
+                    meta: None,
+                },
+            )
+            .await
+            .log_err()
+    {
+        completions = response.completion.values;
+    }
+
+    // Always allow using the value as typed, in case the server doesn't
+    // suggest it.
+    if !typed_value.is_empty() && !completions.contains(&typed_value) {
+        completions.insert(0, typed_value);
+    }
+
+    completions
+        .into_iter()
+        .map(|value| {
+            let mut filled_values = values.clone();
+            filled_values.push(UriTemplate::encode_value(&value, reserved));
+            let (text, is_complete) = uri_template.expand(&filled_values);
+            McpResourceMatch::TemplateValue {
+                server_id: server_id.clone(),
+                value,
+                text,
+                is_complete,
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+enum UriTemplatePart {
+    Literal(String),
+    Variable { name: String, reserved: bool },
+}
+
+/// A minimal [RFC 6570](https://datatracker.ietf.org/doc/html/rfc6570) URI
+/// template, as used by MCP resource templates.
+#[derive(Debug, PartialEq)]
+struct UriTemplate {
+    parts: Vec<UriTemplatePart>,
+}
+
+impl UriTemplate {
+    fn parse(template: &str) -> Option<Self> {
+        fn push_literal(parts: &mut Vec<UriTemplatePart>, literal: &str) {
+            if literal.is_empty() {
+                return;
+            }
+            if let Some(UriTemplatePart::Literal(last)) = parts.last_mut() {
+                last.push_str(literal);
+            } else {
+                parts.push(UriTemplatePart::Literal(literal.to_string()));
+            }
+        }
+
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            push_literal(&mut parts, &rest[..start]);
+            let end = start + rest[start..].find('}')?;
+            let expression = &rest[start + 1..end];
+            let operator = expression
+                .chars()
+                .next()
+                .filter(|c| matches!(c, '+' | '#' | '.' | '/' | ';' | '?' | '&'));
+            let names = match operator {
+                Some(operator) => &expression[operator.len_utf8()..],
+                None => expression,
+            };
+
+            for (ix, name) in names.split(',').enumerate() {
+                let name = name.split(':').next().unwrap_or(name).trim_end_matches('*');
+                if name.is_empty() {
+                    return None;
+                }
+                let separator = match (operator, ix) {
+                    (None | Some('+'), 0) => String::new(),
+                    (Some('#'), 0) => "#".to_string(),
+                    (None | Some('+') | Some('#'), _) => ",".to_string(),
+                    (Some('.'), _) => ".".to_string(),
+                    (Some('/'), _) => "/".to_string(),
+                    (Some(';'), _) => format!(";{name}="),
+                    (Some('?'), 0) => format!("?{name}="),
+                    (Some(_), _) => format!("&{name}="),
+                };
+                push_literal(&mut parts, &separator);
+                parts.push(UriTemplatePart::Variable {
+                    name: name.to_string(),
+                    reserved: matches!(operator, Some('+' | '#')),
+                });
+            }
+            rest = &rest[end + 1..];
+        }
+        push_literal(&mut parts, rest);
+
+        Some(Self { parts })
+    }
+
+    /// The text preceding the first variable, if the template doesn't start
+    /// with a variable.
+    fn literal_prefix(&self) -> Option<&str> {
+        match self.parts.first()? {
+            UriTemplatePart::Literal(literal) => Some(literal),
+            UriTemplatePart::Variable { .. } => None,
+        }
+    }
+
+    fn variables(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.parts.iter().filter_map(|part| match part {
+            UriTemplatePart::Variable { name, reserved } => Some((name.as_str(), *reserved)),
+            UriTemplatePart::Literal(_) => None,
+        })
+    }
+
+    /// Matches text typed by the user against the template, returning the
+    /// values of the variables entered so far. The last value is the one
+    /// being typed.
+    fn match_prefix(&self, text: &str) -> Option<Vec<String>> {
+        let mut rest = text.strip_prefix(self.literal_prefix()?)?;
+        let mut values = Vec::new();
+        let mut parts = self.parts[1..].iter().peekable();
+        while let Some(part) = parts.next() {
+            match part {
+                UriTemplatePart::Literal(literal) => {
+                    rest = rest.strip_prefix(literal.as_str())?;
+                }
+                UriTemplatePart::Variable { .. } => {
+                    let next_literal = match parts.peek() {
+                        Some(UriTemplatePart::Literal(literal)) => Some(literal.as_str()),
+                        _ => None,
+                    };
+                    match next_literal.and_then(|literal| rest.find(literal)) {
+                        Some(end) => {
+                            values.push(rest[..end].to_string());
+                            rest = &rest[end..];
+                        }
+                        None => {
+                            values.push(rest.to_string());
+                            return Some(values);
+                        }
+                    }
+                }
+            }
+        }
+        (!values.is_empty() && rest.is_empty()).then_some(values)
+    }
+
+    /// Expands the template with the given (already encoded) values, stopping
+    /// before the first variable without a value. Returns whether all
+    /// variables were expanded.
+    fn expand(&self, values: &[String]) -> (String, bool) {
+        let mut text = String::new();
+        let mut values = values.iter();
+        for part in &self.parts {
+            match part {
+                UriTemplatePart::Literal(literal) => text.push_str(literal),
+                UriTemplatePart::Variable { .. } => match values.next() {
+                    Some(value) => text.push_str(value),
+                    None => return (text, false),
+                },
+            }
+        }
+        (text, true)
+    }
+
+    fn encode_value(value: &str, reserved: bool) -> String {
+        if reserved {
+            value.replace(' ', "%20")
+        } else {
+            urlencoding::encode(value).into_owned()
+        }
+    }
+}
+
 pub struct SymbolMatch {
     pub symbol: Symbol,
 }
@@ -2335,6 +2830,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_uri_template() {
+        let template = UriTemplate::parse("repo://{owner}/{name}/blob{/path}").unwrap();
+        assert_eq!(template.literal_prefix(), Some("repo://"));
+        assert_eq!(
+            template.variables().collect::<Vec<_>>(),
+            vec![("owner", false), ("name", false), ("path", false)]
+        );
+
+        assert_eq!(template.match_prefix("repo:/"), None);
+        assert_eq!(template.match_prefix("repo://"), Some(vec!["".into()]));
+        assert_eq!(
+            template.match_prefix("repo://zed"),
+            Some(vec!["zed".into()])
+        );
+        assert_eq!(
+            template.match_prefix("repo://zed-industries/z"),
+            Some(vec!["zed-industries".into(), "z".into()])
+        );
+
+        assert_eq!(
+            template.expand(&["zed-industries".into()]),
+            ("repo://zed-industries/".into(), false)
+        );
+        assert_eq!(
+            template.expand(&["zed-industries".into(), "zed".into(), "README.md".into()]),
+            ("repo://zed-industries/zed/blob/README.md".into(), true)
+        );
+
+        assert_eq!(UriTemplate::encode_value("a b/c", false), "a%20b%2Fc");
+        assert_eq!(UriTemplate::encode_value("a b/c", true), "a%20b/c");
+
+        let template = UriTemplate::parse("search://issues{?query,state}").unwrap();
+        assert_eq!(
+            template.expand(&["bug".into(), "open".into()]),
+            ("search://issues?query=bug&state=open".into(), true)
+        );
+
+        assert_eq!(UriTemplate::parse("{path}").unwrap().literal_prefix(), None);
+        assert_eq!(UriTemplate::parse("file:///{path"), None);
+    }
+
     #[gpui::test]
     async fn test_filter_sessions_by_query(cx: &mut TestAppContext) {
         let mut alpha = AgentSessionInfo::new("session-alpha");
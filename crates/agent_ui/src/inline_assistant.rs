@@ -41,7 +41,9 @@ use gpui::{
     WeakEntity, Window, point,
 };
 use language::{Buffer, Point, Selection, TransactionId};
-use language_model::{ConfigurationError, ConfiguredModel, LanguageModelRegistry};
+use language_model::{
+    ConfigurationError, ConfiguredModel, LanguageModelRegistry, confirm_usage_budget,
+};
 use multi_buffer::MultiBufferRow;
 use parking_lot::Mutex;
 use project::{CodeAction, DisableAiSettings, LspAction, Project, ProjectTransaction};
@@ -1328,6 +1330,30 @@ impl InlineAssistant {
     }
 
     pub fn start_assist(&mut self, assist_id: InlineAssistId, window: &mut Window, cx: &mut App) {
+        if let Some(confirm) = confirm_usage_budget(
+            AgentSettings::get_global(cx).usage_budget.clone(),
+            window,
+            cx,
+        ) {
+            window
+                .spawn(cx, async move |cx| {
+                    if confirm.await {
+                        cx.update(|window, cx| {
+                            InlineAssistant::update_global(cx, |assistant, cx| {
+                                assistant.start_assist_impl(assist_id, window, cx)
+                            })
+                        })
+                        .ok();
+                    }
+                })
+                .detach();
+            return;
+        }
+
+        self.start_assist_impl(assist_id, window, cx);
+    }
+
+    fn start_assist_impl(&mut self, assist_id: InlineAssistId, window: &mut Window, cx: &mut App) {
         let assist = if let Some(assist) = self.assists.get_mut(&assist_id) {
             assist
         } else {
@@ -1337,7 +1363,7 @@ impl InlineAssistant {
         let assist_group_id = assist.group_id;
         if self.assist_groups[&assist_group_id].linked {
             for assist_id in self.unlink_assist_group(assist_group_id, window, cx) {
-                self.start_assist(assist_id, window, cx);
+                self.start_assist_impl(assist_id, window, cx);
             }
             return;
         }
@@ -1360,10 +1386,14 @@ impl InlineAssistant {
         };
 
         let context_task = load_context(&mention_set, cx).shared();
+        let project = assist
+            .workspace
+            .upgrade()
+            .and_then(|workspace| workspace.read(cx).project().read(cx).display_name(cx));
         assist
             .codegen
             .update(cx, |codegen, cx| {
-                codegen.start(model, user_prompt, context_task, cx)
+                codegen.start(model, user_prompt, context_task, project, cx)
             })
             .log_err();
     }
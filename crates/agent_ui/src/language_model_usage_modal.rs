@@ -0,0 +1,431 @@
+use crate::text_thread_editor::humanize_token_count;
+use agent_settings::AgentSettings;
+use anyhow::Result;
+use collections::HashMap;
+use gpui::{
+    DismissEvent, EventEmitter, FocusHandle, Focusable, ScrollHandle, Task, Window, prelude::*,
+};
+use language_model::{LANGUAGE_MODEL_USAGE, LanguageModelUsageSummary, TokenUsage};
+use settings::Settings as _;
+use ui::{Divider, Modal, ModalFooter, ModalHeader, WithScrollbar, prelude::*};
+use util::ResultExt as _;
+use workspace::{ModalView, Workspace};
+
+use crate::OpenUsage;
+
+/// How many days of usage are shown, including today.
+const USAGE_DAYS: u32 = 30;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum UsageGrouping {
+    Day,
+    Project,
+    Feature,
+    Model,
+}
+
+impl UsageGrouping {
+    const ALL: [Self; 4] = [Self::Day, Self::Project, Self::Feature, Self::Model];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Day => "Day",
+            Self::Project => "Project",
+            Self::Feature => "Feature",
+            Self::Model => "Model",
+        }
+    }
+
+    fn key(&self, summary: &LanguageModelUsageSummary) -> SharedString {
+        match self {
+            Self::Day => summary.day.clone().into(),
+            Self::Project => summary
+                .project
+                .clone()
+                .unwrap_or_else(|| "No Project".to_string())
+                .into(),
+            Self::Feature => summary
+                .feature
+                .map_or("Other", |feature| feature.label())
+                .into(),
+            Self::Model => format!("{}/{}", summary.provider, summary.model.0).into(),
+        }
+    }
+}
+
+/// The usage of all requests in a group.
+struct UsageRow {
+    label: SharedString,
+    requests: u64,
+    usage: TokenUsage,
+    cost: Option<f64>,
+}
+
+struct UsageData {
+    summaries: Vec<LanguageModelUsageSummary>,
+    cost_today: f64,
+    cost_this_month: f64,
+}
+
+pub struct LanguageModelUsageModal {
+    focus_handle: FocusHandle,
+    scroll_handle: ScrollHandle,
+    grouping: UsageGrouping,
+    data: Option<UsageData>,
+    _load_task: Task<()>,
+}
+
+impl LanguageModelUsageModal {
+    pub fn register(
+        workspace: &mut Workspace,
+        _window: Option<&mut Window>,
+        _cx: &mut Context<Workspace>,
+    ) {
+        workspace.register_action(|workspace, _: &OpenUsage, window, cx| {
+            workspace.toggle_modal(window, cx, Self::new);
+        });
+    }
+
+    fn new(_window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let mut this = Self {
+            focus_handle: cx.focus_handle(),
+            scroll_handle: ScrollHandle::new(),
+            grouping: UsageGrouping::Day,
+            data: None,
+            _load_task: Task::ready(()),
+        };
+        this.load(cx);
+        this
+    }
+
+    fn load(&mut self, cx: &mut Context<Self>) {
+        let data = cx.background_spawn(async move {
+            anyhow::Ok(UsageData {
+                summaries: LANGUAGE_MODEL_USAGE.usage_summaries(USAGE_DAYS)?,
+                cost_today: LANGUAGE_MODEL_USAGE.cost_today()?,
+                cost_this_month: LANGUAGE_MODEL_USAGE.cost_this_month()?,
+            })
+        });
+        self._load_task = cx.spawn(async move |this, cx| {
+            let Some(data) = data.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                this.data = Some(data);
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn clear(&mut self, cx: &mut Context<Self>) {
+        let clear = cx.background_spawn(async move { LANGUAGE_MODEL_USAGE.clear_usage().await });
+        self._load_task = cx.spawn(async move |this, cx| {
+            if clear.await.log_err().is_some() {
+                this.update(cx, |this, cx| this.load(cx)).ok();
+            }
+        });
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent)
+    }
+
+    fn render_budget(&self, data: &UsageData, cx: &App) -> impl IntoElement {
+        let budget = &AgentSettings::get_global(cx).usage_budget;
+        let spent = |label: &str, cost: f64, budget: Option<f64>| {
+            let (text, color) = match budget {
+                Some(budget) => (
+                    format!("{label}: ${cost:.2} of ${budget:.2}"),
+                    if cost >= budget {
+                        Color::Warning
+                    } else {
+                        Color::Muted
+                    },
+                ),
+                None => (format!("{label}: ${cost:.2}"), Color::Muted),
+            };
+            Label::new(text).size(LabelSize::Small).color(color)
+        };
+
+        h_flex()
+            .px_2()
+            .gap_4()
+            .child(spent("Today", data.cost_today, budget.daily))
+            .child(spent("This month", data.cost_this_month, budget.monthly))
+    }
+
+    fn render_row(
+        label: impl Into<SharedString>,
+        requests: impl Into<SharedString>,
+        input: impl Into<SharedString>,
+        output: impl Into<SharedString>,
+        cached: impl Into<SharedString>,
+        cost: impl Into<SharedString>,
+        color: Color,
+    ) -> impl IntoElement {
+        let cell = |text: SharedString| {
+            div()
+                .w_20()
+                .flex()
+                .justify_end()
+                .child(Label::new(text).size(LabelSize::Small).color(color))
+        };
+        h_flex()
+            .w_full()
+            .px_2()
+            .py_0p5()
+            .gap_2()
+            .child(
+                div().flex_1().min_w_0().child(
+                    Label::new(label)
+                        .size(LabelSize::Small)
+                        .color(color)
+                        .truncate(),
+                ),
+            )
+            .child(cell(requests.into()))
+            .child(cell(input.into()))
+            .child(cell(output.into()))
+            .child(cell(cached.into()))
+            .child(cell(cost.into()))
+    }
+
+    fn render_usage(&self, window: &mut Window, cx: &mut Context<Self>) -> AnyElement {
+        let Some(data) = &self.data else {
+            return h_flex()
+                .p_2()
+                .child(Label::new("Loading usage…").color(Color::Muted))
+                .into_any_element();
+        };
+        if data.summaries.is_empty() {
+            return h_flex()
+                .p_2()
+                .child(
+                    Label::new(format!(
+                        "No language model usage in the last {USAGE_DAYS} days."
+                    ))
+                    .color(Color::Muted),
+                )
+                .into_any_element();
+        }
+
+        let rows = usage_rows(self.grouping, &data.summaries);
+        let total_requests = rows.iter().map(|row| row.requests).sum::<u64>();
+        let total_usage = rows
+            .iter()
+            .fold(TokenUsage::default(), |total, row| total + row.usage);
+        let total_cost = rows.iter().filter_map(|row| row.cost).reduce(|a, b| a + b);
+        let format_cost = |cost: Option<f64>| match cost {
+            Some(cost) => format!("${cost:.2}"),
+            None => "–".to_string(),
+        };
+        let cached = |usage: &TokenUsage| {
+            humanize_token_count(usage.cache_read_input_tokens + usage.cache_creation_input_tokens)
+        };
+
+        v_flex()
+            .gap_2()
+            .child(self.render_budget(data, cx))
+            .child(
+                h_flex()
+                    .px_2()
+                    .gap_1()
+                    .children(UsageGrouping::ALL.into_iter().map(|grouping| {
+                        Button::new(grouping.label(), grouping.label())
+                            .label_size(LabelSize::Small)
+                            .toggle_state(self.grouping == grouping)
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.grouping = grouping;
+                                cx.notify();
+                            }))
+                    })),
+            )
+            .child(
+                v_flex()
+                    .child(Self::render_row(
+                        self.grouping.label(),
+                        "Requests",
+                        "Input",
+                        "Output",
+                        "Cached",
+                        "Cost",
+                        Color::Muted,
+                    ))
+                    .child(Divider::horizontal())
+                    .child(
+                        v_flex()
+                            .id("usage-rows")
+                            .max_h_96()
+                            .overflow_y_scroll()
+                            .track_scroll(&self.scroll_handle)
+                            .children(rows.iter().map(|row| {
+                                Self::render_row(
+                                    row.label.clone(),
+                                    row.requests.to_string(),
+                                    humanize_token_count(row.usage.input_tokens),
+                                    humanize_token_count(row.usage.output_tokens),
+                                    cached(&row.usage),
+                                    format_cost(row.cost),
+                                    Color::Default,
+                                )
+                            })),
+                    )
+                    .vertical_scrollbar_for(&self.scroll_handle, window, cx)
+                    .child(Divider::horizontal())
+                    .child(Self::render_row(
+                        "Total",
+                        total_requests.to_string(),
+                        humanize_token_count(total_usage.input_tokens),
+                        humanize_token_count(total_usage.output_tokens),
+                        cached(&total_usage),
+                        format_cost(total_cost),
+                        Color::Default,
+                    )),
+            )
+            .into_any_element()
+    }
+}
+
+fn usage_rows(grouping: UsageGrouping, summaries: &[LanguageModelUsageSummary]) -> Vec<UsageRow> {
+    let mut rows = Vec::<UsageRow>::new();
+    let mut row_ix_by_label = HashMap::default();
+    for summary in summaries {
+        let label = grouping.key(summary);
+        let row_ix = *row_ix_by_label.entry(label.clone()).or_insert_with(|| {
+            rows.push(UsageRow {
+                label,
+                requests: 0,
+                usage: TokenUsage::default(),
+                cost: None,
+            });
+            rows.len() - 1
+        });
+        let row = &mut rows[row_ix];
+        row.requests += summary.requests;
+        row.usage = row.usage + summary.usage;
+        if let Some(cost) = summary.cost {
+            *row.cost.get_or_insert(0.) += cost;
+        }
+    }
+
+    // Days are already ordered from the most recent, and other groups are ordered by cost.
+    if grouping != UsageGrouping::Day {
+        rows.sort_by(|a, b| {
+            b.cost
+                .unwrap_or(0.)
+                .total_cmp(&a.cost.unwrap_or(0.))
+                .then_with(|| b.usage.total_tokens().cmp(&a.usage.total_tokens()))
+        });
+    }
+    rows
+}
+
+impl ModalView for LanguageModelUsageModal {}
+
+impl Focusable for LanguageModelUsageModal {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<DismissEvent> for LanguageModelUsageModal {}
+
+impl Render for LanguageModelUsageModal {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .key_context("LanguageModelUsageModal")
+            .occlude()
+            .elevation_3(cx)
+            .w(rems(44.))
+            .on_action(cx.listener(Self::cancel))
+            .track_focus(&self.focus_handle)
+            .child(
+                Modal::new("language-model-usage", None::<ScrollHandle>)
+                    .header(
+                        ModalHeader::new()
+                            .headline("Language Model Usage")
+                            .description(format!(
+                                "Tokens used in the last {USAGE_DAYS} days, with costs estimated from the `model_pricing` setting."
+                            ))
+                            .show_dismiss_button(true),
+                    )
+                    .child(div().pb_2().child(self.render_usage(window, cx)))
+                    .footer(
+                        ModalFooter::new().end_slot(
+                            Button::new("clear-usage", "Clear Usage")
+                                .disabled(
+                                    self.data
+                                        .as_ref()
+                                        .is_none_or(|data| data.summaries.is_empty()),
+                                )
+                                .on_click(cx.listener(|this, _, _, cx| this.clear(cx))),
+                        ),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language_model::{LanguageModelId, LanguageModelProviderId, LanguageModelUsageFeature};
+
+    fn summary(
+        day: &str,
+        project: Option<&str>,
+        model: &str,
+        input_tokens: u64,
+        cost: Option<f64>,
+    ) -> LanguageModelUsageSummary {
+        LanguageModelUsageSummary {
+            day: day.to_string(),
+            project: project.map(Into::into),
+            feature: Some(LanguageModelUsageFeature::Agent),
+            provider: LanguageModelProviderId::from("anthropic".to_string()),
+            model: LanguageModelId::from(model.to_string()),
+            requests: 1,
+            usage: TokenUsage {
+                input_tokens,
+                ..Default::default()
+            },
+            cost,
+        }
+    }
+
+    #[test]
+    fn test_usage_rows() {
+        let summaries = vec![
+            summary("2026-10-18", Some("zed"), "claude-opus", 100, Some(1.)),
+            summary("2026-10-18", None, "claude-sonnet", 200, None),
+            summary("2026-10-17", Some("zed"), "claude-sonnet", 300, Some(3.)),
+        ];
+
+        let rows = usage_rows(UsageGrouping::Day, &summaries);
+        let days = rows
+            .iter()
+            .map(|row| row.label.as_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(days, ["2026-10-18", "2026-10-17"]);
+        assert_eq!(rows[0].requests, 2);
+        assert_eq!(rows[0].usage.input_tokens, 300);
+        assert_eq!(rows[0].cost, Some(1.));
+
+        let rows = usage_rows(UsageGrouping::Project, &summaries);
+        let projects = rows
+            .iter()
+            .map(|row| row.label.as_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(projects, ["zed", "No Project"]);
+        assert_eq!(rows[0].cost, Some(4.));
+        assert_eq!(rows[1].cost, None);
+
+        let rows = usage_rows(UsageGrouping::Model, &summaries);
+        let models = rows
+            .iter()
+            .map(|row| row.label.as_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(models, ["anthropic/claude-sonnet", "anthropic/claude-opus"]);
+        assert_eq!(rows[0].usage.input_tokens, 500);
+    }
+}
@@ -5,6 +5,12 @@ use agent_servers::{AgentServer, AgentServerDelegate};
 use anyhow::{Context as _, Result, anyhow};
 use assistant_slash_commands::{codeblock_fence_for_path, collect_diagnostics_output};
 use collections::{HashMap, HashSet};
+use context_server::{
+    ContextServerId,
+    client::NotificationSubscription,
+    protocol::InitializedContextServerProtocol,
+    types::{Notification as _, ResourceContentsType},
+};
 use editor::{
     Anchor, Editor, EditorSnapshot, ExcerptId, FoldPlaceholder, ToOffset,
     display_map::{Crease, CreaseId, CreaseMetadata, FoldId},
@@ -12,8 +18,8 @@ use editor::{
 };
 use futures::{AsyncReadExt as _, FutureExt as _, future::Shared};
 use gpui::{
-    AppContext, ClipboardEntry, Context, Empty, Entity, EntityId, Image, ImageFormat, Img,
-    SharedString, Task, WeakEntity,
+    AppContext, AsyncApp, ClipboardEntry, Context, Empty, Entity, EntityId, Image, ImageFormat,
+    Img, SharedString, Subscription, Task, WeakEntity,
 };
 use http_client::{AsyncBody, HttpClientWithUrl};
 use itertools::Either;
@@ -21,7 +27,10 @@ use language::Buffer;
 use language_model::LanguageModelImage;
 use multi_buffer::MultiBufferRow;
 use postage::stream::Stream as _;
-use project::{Project, ProjectItem, ProjectPath, Worktree};
+use project::{
+    Project, ProjectItem, ProjectPath, Worktree,
+    context_server_store::{ContextServerStatus, ServerStatusChangedEvent},
+};
 use prompt_store::{PromptId, PromptStore};
 use rope::Point;
 use std::{
@@ -63,6 +72,18 @@ pub struct MentionSet {
     thread_store: Option<Entity<ThreadStore>>,
     prompt_store: Option<Entity<PromptStore>>,
     mentions: HashMap<CreaseId, (MentionUri, MentionTask)>,
+    mcp_resource_subscriptions: HashMap<ContextServerId, McpResourceSubscriptions>,
+    _context_server_subscriptions: Vec<Subscription>,
+}
+
+/// The resources of a running MCP server that we've sent `resources/subscribe` for.
+///
+/// Subscriptions belong to the server's running instance, so they're discarded when the
+/// server stops and sent again once it's running again.
+struct McpResourceSubscriptions {
+    client: Arc<InitializedContextServerProtocol>,
+    uris: HashSet<url::Url>,
+    _updated_notifications: NotificationSubscription,
 }
 
 impl MentionSet {
@@ -76,6 +97,8 @@ impl MentionSet {
             thread_store,
             prompt_store,
             mentions: HashMap::default(),
+            mcp_resource_subscriptions: HashMap::default(),
+            _context_server_subscriptions: Vec::new(),
         }
     }
 
@@ -106,12 +129,13 @@ impl MentionSet {
         })
     }
 
-    pub fn remove_invalid(&mut self, snapshot: &EditorSnapshot) {
+    pub fn remove_invalid(&mut self, snapshot: &EditorSnapshot, cx: &mut App) {
         for (crease_id, crease) in snapshot.crease_snapshot.creases() {
             if !crease.range().start.is_valid(snapshot.buffer_snapshot()) {
                 self.mentions.remove(&crease_id);
             }
         }
+        self.unsubscribe_from_unmentioned_mcp_resources(cx);
     }
 
     pub fn insert_mention(&mut self, crease_id: CreaseId, uri: MentionUri, task: MentionTask) {
@@ -147,6 +171,9 @@ impl MentionSet {
                 include_errors,
                 include_warnings,
             } => self.confirm_mention_for_diagnostics(include_errors, include_warnings, cx),
+            MentionUri::McpResource { server_id, uri, .. } => {
+                self.confirm_mention_for_mcp_resource(server_id, uri, cx)
+            }
             MentionUri::PastedImage
             | MentionUri::Selection { .. }
             | MentionUri::TerminalSelection { .. }
@@ -156,8 +183,9 @@ impl MentionSet {
         }
     }
 
-    pub fn remove_mention(&mut self, crease_id: &CreaseId) {
+    pub fn remove_mention(&mut self, crease_id: &CreaseId, cx: &mut App) {
         self.mentions.remove(crease_id);
+        self.unsubscribe_from_unmentioned_mcp_resources(cx);
     }
 
     pub fn creases(&self) -> HashSet<CreaseId> {
@@ -168,10 +196,17 @@ impl MentionSet {
         self.mentions.values().map(|(uri, _)| uri.clone()).collect()
     }
 
-    pub fn set_mentions(&mut self, mentions: HashMap<CreaseId, (MentionUri, MentionTask)>) {
+    pub fn set_mentions(
+        &mut self,
+        mentions: HashMap<CreaseId, (MentionUri, MentionTask)>,
+        cx: &mut App,
+    ) {
         self.mentions = mentions;
+        self.unsubscribe_from_unmentioned_mcp_resources(cx);
     }
 
+    /// Removes all mentions. Callers that don't put them back with [`Self::set_mentions`]
+    /// should call [`Self::unsubscribe_from_unmentioned_mcp_resources`] afterwards.
     pub fn clear(&mut self) -> impl Iterator<Item = (CreaseId, (MentionUri, MentionTask))> {
         self.mentions.drain()
     }
@@ -277,6 +312,9 @@ impl MentionSet {
                 include_errors,
                 include_warnings,
             } => self.confirm_mention_for_diagnostics(include_errors, include_warnings, cx),
+            MentionUri::McpResource { server_id, uri, .. } => {
+                self.confirm_mention_for_mcp_resource(server_id, uri, cx)
+            }
             MentionUri::PastedImage => {
                 debug_panic!("pasted image URI should not be included in completions");
                 Task::ready(Err(anyhow!(
@@ -312,7 +350,7 @@ impl MentionSet {
                         // Remove mention
                         editor.edit([(start_anchor..end_anchor, "")], cx);
                     });
-                    this.mentions.remove(&crease_id);
+                    this.remove_mention(&crease_id, cx);
                 })
                 .ok();
             }
@@ -394,6 +432,240 @@ impl MentionSet {
         })
     }
 
+    fn confirm_mention_for_mcp_resource(
+        &mut self,
+        server_id: String,
+        uri: url::Url,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Mention>> {
+        let Some(project) = self.project.upgrade() else {
+            return Task::ready(Err(anyhow!("project not found")));
+        };
+        let server_id = ContextServerId(server_id.into());
+        let Some(client) = project
+            .read(cx)
+            .context_server_store()
+            .read(cx)
+            .get_running_server(&server_id)
+            .and_then(|server| server.client())
+        else {
+            return Task::ready(Err(anyhow!("MCP server {server_id} is not running")));
+        };
+
+        self.subscribe_to_mcp_resource(&server_id, &client, &uri, cx);
+        read_mcp_resource(client, uri, cx)
+    }
+
+    /// Asks the server to notify us when the resource changes, so that the
+    /// mention can be re-read before the message is sent.
+    fn subscribe_to_mcp_resource(
+        &mut self,
+        server_id: &ContextServerId,
+        client: &Arc<InitializedContextServerProtocol>,
+        uri: &url::Url,
+        cx: &mut Context<Self>,
+    ) {
+        let supports_subscriptions = client
+            .initialize
+            .capabilities
+            .resources
+            .as_ref()
+            .and_then(|resources| resources.subscribe)
+            .unwrap_or(false);
+        if !supports_subscriptions {
+            return;
+        }
+
+        self.observe_context_servers(cx);
+
+        let is_subscribed_to_server = self
+            .mcp_resource_subscriptions
+            .get(server_id)
+            .is_some_and(|subscriptions| Arc::ptr_eq(&subscriptions.client, client));
+        if !is_subscribed_to_server {
+            let this = cx.entity().downgrade();
+            let updated_notifications = client.on_notification(
+                context_server::types::notifications::ResourcesUpdated::METHOD,
+                Box::new({
+                    let server_id = server_id.clone();
+                    move |params, cx: AsyncApp| {
+                        let Some(params) = serde_json::from_value::<
+                            context_server::types::ResourcesUpdatedParams,
+                        >(params)
+                        .log_err() else {
+                            return;
+                        };
+                        let this = this.clone();
+                        let server_id = server_id.clone();
+                        cx.spawn(async move |cx| {
+                            this.update(cx, |this, cx| {
+                                this.reload_mcp_resource(&server_id, &params.uri, cx)
+                            })
+                        })
+                        .detach();
+                    }
+                }),
+            );
+            self.mcp_resource_subscriptions.insert(
+                server_id.clone(),
+                McpResourceSubscriptions {
+                    client: client.clone(),
+                    uris: HashSet::default(),
+                    _updated_notifications: updated_notifications,
+                },
+            );
+        }
+
+        let Some(subscriptions) = self.mcp_resource_subscriptions.get_mut(server_id) else {
+            return;
+        };
+        if subscriptions.uris.insert(uri.clone()) {
+            let client = client.clone();
+            let uri = uri.clone();
+            cx.background_spawn(async move {
+                client
+                    .request::<context_server::types::requests::ResourcesSubscribe>(
+                        context_server::types::ResourcesSubscribeParams { uri, meta: None },
+                    )
+                    .await
+            })
+            .detach_and_log_err(cx);
+        }
+    }
+
+    /// Sends `resources/unsubscribe` for the resources that are no longer mentioned.
+    pub fn unsubscribe_from_unmentioned_mcp_resources(&mut self, cx: &mut App) {
+        let mentioned = self
+            .mentions
+            .values()
+            .filter_map(|(mention_uri, _)| match mention_uri {
+                MentionUri::McpResource { server_id, uri, .. } => Some((server_id.as_str(), uri)),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+
+        self.mcp_resource_subscriptions
+            .retain(|server_id, subscriptions| {
+                subscriptions.uris.retain(|uri| {
+                    let is_mentioned = mentioned.contains(&(server_id.0.as_ref(), uri));
+                    if !is_mentioned {
+                        unsubscribe_from_mcp_resource(&subscriptions.client, uri.clone(), cx);
+                    }
+                    is_mentioned
+                });
+                !subscriptions.uris.is_empty()
+            });
+    }
+
+    /// Keeps the resource subscriptions in sync with the servers' running instances, and
+    /// unsubscribes from every resource when the mention set is dropped.
+    fn observe_context_servers(&mut self, cx: &mut Context<Self>) {
+        if !self._context_server_subscriptions.is_empty() {
+            return;
+        }
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+
+        let context_server_store = project.read(cx).context_server_store();
+        self._context_server_subscriptions = vec![
+            cx.subscribe(
+                &context_server_store,
+                |this, _, event: &ServerStatusChangedEvent, cx| {
+                    this.context_server_status_changed(event, cx)
+                },
+            ),
+            cx.on_release(|this, cx| {
+                for (_, subscriptions) in this.mcp_resource_subscriptions.drain() {
+                    for uri in subscriptions.uris {
+                        unsubscribe_from_mcp_resource(&subscriptions.client, uri, cx);
+                    }
+                }
+            }),
+        ];
+    }
+
+    fn context_server_status_changed(
+        &mut self,
+        event: &ServerStatusChangedEvent,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let client = project
+            .read(cx)
+            .context_server_store()
+            .read(cx)
+            .get_running_server(&event.server_id)
+            .and_then(|server| server.client());
+
+        // The subscriptions of a server that stopped or restarted went away with it.
+        if let Some(subscriptions) = self.mcp_resource_subscriptions.get(&event.server_id)
+            && client
+                .as_ref()
+                .is_none_or(|client| !Arc::ptr_eq(client, &subscriptions.client))
+        {
+            self.mcp_resource_subscriptions.remove(&event.server_id);
+        }
+
+        let (ContextServerStatus::Running, Some(client)) = (&event.status, client) else {
+            return;
+        };
+        let uris = self
+            .mentions
+            .values()
+            .filter_map(|(mention_uri, _)| match mention_uri {
+                MentionUri::McpResource { server_id, uri, .. }
+                    if server_id.as_str() == event.server_id.0.as_ref() =>
+                {
+                    Some(uri.clone())
+                }
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        for uri in uris {
+            self.subscribe_to_mcp_resource(&event.server_id, &client, &uri, cx);
+            self.reload_mcp_resource(&event.server_id, uri.as_str(), cx);
+        }
+    }
+
+    fn reload_mcp_resource(
+        &mut self,
+        server_id: &ContextServerId,
+        updated_uri: &str,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let Some(client) = project
+            .read(cx)
+            .context_server_store()
+            .read(cx)
+            .get_running_server(server_id)
+            .and_then(|server| server.client())
+        else {
+            return;
+        };
+
+        for (mention_uri, task) in self.mentions.values_mut() {
+            if let MentionUri::McpResource {
+                server_id: mention_server_id,
+                uri,
+                ..
+            } = mention_uri
+                && mention_server_id.as_str() == server_id.0.as_ref()
+                && uri.as_str() == updated_uri
+            {
+                let read_task = read_mcp_resource(client.clone(), uri.clone(), cx);
+                *task = cx
+                    .spawn(async move |_, _| read_task.await.map_err(|e| e.to_string()))
+                    .shared();
+            }
+        }
+    }
+
     fn confirm_mention_for_symbol(
         &self,
         abs_path: PathBuf,
@@ -642,6 +914,128 @@ mod tests {
             "Unexpected error: {error:#}"
         );
     }
+
+    #[gpui::test]
+    async fn test_mcp_resource_subscriptions(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/project", json!({"file": ""})).await;
+        let project = Project::test(fs, [Path::new(path!("/project"))], cx).await;
+        let mention_set = cx.new(|_cx| MentionSet::new(project.downgrade(), None, None));
+
+        let server_id = ContextServerId("docs".into());
+        let uri = url::Url::parse("docs://guide").unwrap();
+        let mention_uri = MentionUri::McpResource {
+            server_id: server_id.0.to_string(),
+            uri: uri.clone(),
+            name: "guide".into(),
+        };
+
+        let (subscribed_tx, mut subscribed_rx) = futures::channel::mpsc::unbounded();
+        let (unsubscribed_tx, mut unsubscribed_rx) = futures::channel::mpsc::unbounded();
+        let start_server = |cx: &mut TestAppContext| {
+            let subscribed_tx = subscribed_tx.clone();
+            let unsubscribed_tx = unsubscribed_tx.clone();
+            let transport = context_server::test::create_fake_transport("docs", cx.executor())
+                .on_request::<context_server::types::requests::Initialize, _>(|_| async {
+                    context_server::types::InitializeResponse {
+                        protocol_version: context_server::types::ProtocolVersion(
+                            context_server::types::LATEST_PROTOCOL_VERSION.to_string(),
+                        ),
+                        server_info: context_server::types::Implementation {
+                            name: "docs".into(),
+                            version: "1.0.0".into(),
+                        },
+                        capabilities: context_server::types::ServerCapabilities {
+                            resources: Some(context_server::types::ResourcesCapabilities {
+                                subscribe: Some(true),
+                                list_changed: None,
+                            }),
+                            ..Default::default()
+                        },
+                        meta: None,
+                    }
+                })
+                .on_request::<context_server::types::requests::ResourcesRead, _>(
+                    |params| async move {
+                        context_server::types::ResourcesReadResponse {
+                            contents: vec![ResourceContentsType::Text(
+                                context_server::types::TextResourceContents {
+                                    uri: params.uri,
+                                    mime_type: None,
+                                    text: "Guide".into(),
+                                },
+                            )],
+                            meta: None,
+                        }
+                    },
+                )
+                .on_request::<context_server::types::requests::ResourcesSubscribe, _>(
+                    move |params| {
+                        subscribed_tx.unbounded_send(params.uri).unwrap();
+                        async {}
+                    },
+                )
+                .on_request::<context_server::types::requests::ResourcesUnsubscribe, _>(
+                    move |params| {
+                        unsubscribed_tx.unbounded_send(params.uri).unwrap();
+                        async {}
+                    },
+                );
+            project.update(cx, |project, cx| {
+                project.context_server_store().update(cx, |store, cx| {
+                    store.test_start_server(
+                        Arc::new(context_server::ContextServer::new(
+                            server_id.clone(),
+                            Arc::new(transport),
+                        )),
+                        cx,
+                    )
+                })
+            });
+            cx.run_until_parked();
+        };
+
+        let mention = |cx: &mut TestAppContext| {
+            mention_set.update(cx, |mention_set, cx| {
+                let task = mention_set.confirm_mention_for_mcp_resource(
+                    server_id.0.to_string(),
+                    uri.clone(),
+                    cx,
+                );
+                let task = cx
+                    .spawn(async move |_, _| task.await.map_err(|e| e.to_string()))
+                    .shared();
+                mention_set.insert_mention(CreaseId::default(), mention_uri.clone(), task);
+            });
+            cx.run_until_parked();
+        };
+
+        // Mentioning a resource subscribes to it, and removing the mention unsubscribes.
+        start_server(cx);
+        mention(cx);
+        assert_eq!(subscribed_rx.try_next().unwrap(), Some(uri.clone()));
+        mention_set.update(cx, |mention_set, cx| {
+            mention_set.remove_mention(&CreaseId::default(), cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(unsubscribed_rx.try_next().unwrap(), Some(uri.clone()));
+
+        // Restarting the server subscribes to the mentioned resource again.
+        mention(cx);
+        assert_eq!(subscribed_rx.try_next().unwrap(), Some(uri.clone()));
+        start_server(cx);
+        assert_eq!(subscribed_rx.try_next().unwrap(), Some(uri.clone()));
+        assert!(subscribed_rx.try_next().is_err());
+
+        // Dropping the mention set unsubscribes from the restarted server.
+        drop(mention_set);
+        cx.update(|_| {});
+        cx.run_until_parked();
+        assert_eq!(unsubscribed_rx.try_next().unwrap(), Some(uri.clone()));
+        assert!(unsubscribed_rx.try_next().is_err());
+    }
 }
 
 /// Inserts a list of images into the editor as context mentions.
@@ -733,8 +1127,8 @@ pub(crate) async fn insert_images_as_context(
             editor.update(cx, |editor, cx| {
                 editor.edit([(start_anchor..end_anchor, "")], cx);
             });
-            mention_set.update(cx, |mention_set, _cx| {
-                mention_set.remove_mention(&crease_id)
+            mention_set.update(cx, |mention_set, cx| {
+                mention_set.remove_mention(&crease_id, cx)
             });
         }
     }
@@ -1188,3 +1582,57 @@ async fn fetch_url_content(http_client: Arc<HttpClientWithUrl>, url: String) ->
         }
     }
 }
+
+fn unsubscribe_from_mcp_resource(
+    client: &Arc<InitializedContextServerProtocol>,
+    uri: url::Url,
+    cx: &App,
+) {
+    let client = client.clone();
+    cx.background_spawn(async move {
+        client
+            .request::<context_server::types::requests::ResourcesUnsubscribe>(
+                context_server::types::ResourcesUnsubscribeParams { uri, meta: None },
+            )
+            .await
+    })
+    .detach_and_log_err(cx);
+}
+
+fn read_mcp_resource(
+    client: Arc<InitializedContextServerProtocol>,
+    uri: url::Url,
+    cx: &App,
+) -> Task<Result<Mention>> {
+    cx.background_spawn(async move {
+        let response = client
+            .request::<context_server::types::requests::ResourcesRead>(
+                context_server::types::ResourcesReadParams { uri, meta: None },
+            )
+            .await?;
+
+        let mut content = String::new();
+        for resource in response.contents {
+            if !content.is_empty() {
+                content.push('\n');
+            }
+            match resource {
+                ResourceContentsType::Text(text) => content.push_str(&text.text),
+                ResourceContentsType::Blob(blob) => {
+                    write!(
+                        &mut content,
+                        "[Binary resource {} ({})]",
+                        blob.uri,
+                        blob.mime_type.as_deref().unwrap_or("unknown type")
+                    )
+                    .ok();
+                }
+            }
+        }
+
+        Ok(Mention::Text {
+            content,
+            tracked_buffers: Vec::new(),
+        })
+    })
+}
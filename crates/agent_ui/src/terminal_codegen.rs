@@ -1,7 +1,10 @@
 use crate::inline_prompt_editor::CodegenStatus;
 use futures::{SinkExt, StreamExt, channel::mpsc};
 use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Task};
-use language_model::{ConfiguredModel, LanguageModelRegistry, LanguageModelRequest};
+use language_model::{
+    ConfiguredModel, LanguageModelRegistry, LanguageModelRequest, LanguageModelUsageFeature,
+    LanguageModelUsageTracker,
+};
 use std::time::Instant;
 use terminal::Terminal;
 use uuid::Uuid;
@@ -33,7 +36,12 @@ impl TerminalCodegen {
         self.session_id
     }
 
-    pub fn start(&mut self, prompt_task: Task<LanguageModelRequest>, cx: &mut Context<Self>) {
+    pub fn start(
+        &mut self,
+        prompt_task: Task<LanguageModelRequest>,
+        project: Option<String>,
+        cx: &mut Context<Self>,
+    ) {
         let Some(ConfiguredModel { model, .. }) =
             LanguageModelRegistry::read_global(cx).inline_assistant_model()
         else {
@@ -44,12 +52,22 @@ impl TerminalCodegen {
         let session_id = self.session_id;
         let model_telemetry_id = model.telemetry_id();
         let model_provider_id = model.provider_id().to_string();
+        let usage_tracker = LanguageModelUsageTracker::new(
+            model.provider_id(),
+            model.id(),
+            LanguageModelUsageFeature::TerminalInlineAssistant,
+            project,
+            cx,
+        );
 
         self.status = CodegenStatus::Pending;
         self.transaction = Some(TerminalTransaction::start(self.terminal.clone()));
         self.generation = cx.spawn(async move |this, cx| {
             let prompt = prompt_task.await;
-            let response = model.stream_completion_text(prompt, cx).await;
+            let response = model
+                .stream_completion_text(prompt, cx)
+                .await
+                .map(|response| usage_tracker.track_text(response));
             let generate = async {
                 let message_id = response
                     .as_ref()
@@ -18,7 +18,7 @@ use gpui::{App, Entity, Focusable, Global, Subscription, Task, UpdateGlobal, Wea
 use language::Buffer;
 use language_model::{
     ConfiguredModel, LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
-    Role, report_anthropic_event,
+    Role, confirm_usage_budget, report_anthropic_event,
 };
 use project::Project;
 use prompt_store::{PromptBuilder, PromptStore};
@@ -147,7 +147,26 @@ impl TerminalInlineAssistant {
         let assist_id = prompt_editor.read(cx).id();
         match event {
             PromptEditorEvent::StartRequested => {
-                self.start_assist(assist_id, cx);
+                if let Some(confirm) = confirm_usage_budget(
+                    AgentSettings::get_global(cx).usage_budget.clone(),
+                    window,
+                    cx,
+                ) {
+                    window
+                        .spawn(cx, async move |cx| {
+                            if confirm.await {
+                                cx.update(|_, cx| {
+                                    TerminalInlineAssistant::update_global(cx, |this, cx| {
+                                        this.start_assist(assist_id, cx)
+                                    })
+                                })
+                                .ok();
+                            }
+                        })
+                        .detach();
+                } else {
+                    self.start_assist(assist_id, cx);
+                }
             }
             PromptEditorEvent::StopRequested => {
                 self.stop_assist(assist_id, cx);
@@ -199,7 +218,11 @@ impl TerminalInlineAssistant {
             return;
         };
 
-        codegen.update(cx, |codegen, cx| codegen.start(request_task, cx));
+        let project = assist
+            .workspace
+            .upgrade()
+            .and_then(|workspace| workspace.read(cx).project().read(cx).display_name(cx));
+        codegen.update(cx, |codegen, cx| codegen.start(request_task, project, cx));
     }
 
     fn stop_assist(&mut self, assist_id: TerminalInlineAssistId, cx: &mut App) {
@@ -2,6 +2,7 @@ use crate::{
     language_model_selector::{LanguageModelSelector, language_model_selector},
     ui::ModelSelectorTooltip,
 };
+use agent_settings::AgentSettings;
 use anyhow::Result;
 use assistant_slash_command::{SlashCommand, SlashCommandOutputSection, SlashCommandWorkingSet};
 use assistant_slash_commands::{DefaultSlashCommand, FileSlashCommand, selections_creases};
@@ -33,6 +34,7 @@ use language::{
 };
 use language_model::{
     ConfigurationError, IconOrSvg, LanguageModelImage, LanguageModelRegistry, Role,
+    confirm_usage_budget,
 };
 use multi_buffer::MultiBufferRow;
 use picker::{Picker, popover_menu::PickerPopoverMenu};
@@ -407,11 +409,25 @@ impl TextThreadEditor {
         if self.sending_disabled(cx) {
             return;
         }
-        telemetry::event!("Agent Message Sent", agent = "zed-text");
+        if let Some(confirm) = confirm_usage_budget(
+            AgentSettings::get_global(cx).usage_budget.clone(),
+            window,
+            cx,
+        ) {
+            cx.spawn_in(window, async move |this, cx| {
+                if confirm.await {
+                    this.update_in(cx, |this, window, cx| this.send_to_model(window, cx))
+                        .ok();
+                }
+            })
+            .detach();
+            return;
+        }
         self.send_to_model(window, cx);
     }
 
     fn send_to_model(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        telemetry::event!("Agent Message Sent", agent = "zed-text");
         self.last_error = None;
         if let Some(user_message) = self
             .text_thread
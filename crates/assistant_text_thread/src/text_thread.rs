@@ -21,8 +21,8 @@ use language_model::{
     AnthropicCompletionType, AnthropicEventData, AnthropicEventType, LanguageModel,
     LanguageModelCacheConfiguration, LanguageModelCompletionEvent, LanguageModelImage,
     LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
-    LanguageModelToolUseId, MessageContent, PaymentRequiredError, Role, StopReason,
-    report_anthropic_event,
+    LanguageModelToolUseId, LanguageModelUsageFeature, LanguageModelUsageTracker, MessageContent,
+    PaymentRequiredError, Role, StopReason, report_anthropic_event,
 };
 use open_ai::Model as OpenAiModel;
 use paths::text_threads_dir;
@@ -1398,10 +1398,12 @@ impl TextThread {
         };
 
         let model = Arc::clone(model);
+        let usage_tracker = Self::usage_tracker(&model, LanguageModelUsageFeature::TextThread, cx);
         self.pending_cache_warming_task = cx.spawn(async move |this, cx| {
             async move {
                 match model.stream_completion(request, cx).await {
-                    Ok(mut stream) => {
+                    Ok(stream) => {
+                        let mut stream = usage_tracker.track(stream);
                         stream.next().await;
                         log::info!("Cache warming completed successfully");
                     }
@@ -1988,6 +1990,16 @@ impl TextThread {
         })
     }
 
+    /// Tracks the token usage of a request. Text threads aren't tied to a project, so the
+    /// usage is recorded without one.
+    fn usage_tracker(
+        model: &Arc<dyn LanguageModel>,
+        feature: LanguageModelUsageFeature,
+        cx: &App,
+    ) -> LanguageModelUsageTracker {
+        LanguageModelUsageTracker::new(model.provider_id(), model.id(), feature, None, cx)
+    }
+
     pub fn assist(&mut self, cx: &mut Context<Self>) -> Option<MessageAnchor> {
         let model_registry = LanguageModelRegistry::read_global(cx);
         let model = model_registry.default_model()?;
@@ -2015,6 +2027,7 @@ impl TextThread {
             .unwrap();
 
         let pending_completion_id = post_inc(&mut self.completion_count);
+        let usage_tracker = Self::usage_tracker(&model, LanguageModelUsageFeature::TextThread, cx);
 
         let task = cx.spawn({
             async move |this, cx| {
@@ -2023,7 +2036,7 @@ impl TextThread {
                 let mut response_latency = None;
                 let stream_completion = async {
                     let request_start = Instant::now();
-                    let mut events = stream.await?;
+                    let mut events = usage_tracker.track(stream.await?);
                     let mut stop_reason = StopReason::EndTurn;
                     let mut thought_process_stack = Vec::new();
 
@@ -2667,10 +2680,12 @@ impl TextThread {
                 TextThreadSummary::Content(_) => {}
             }
 
+            let usage_tracker =
+                Self::usage_tracker(&model.model, LanguageModelUsageFeature::ThreadSummary, cx);
             self.summary_task = cx.spawn(async move |this, cx| {
                 let result = async {
                     let stream = model.model.stream_completion_text(request, cx);
-                    let mut messages = stream.await?;
+                    let mut messages = usage_tracker.track_text(stream.await?);
 
                     let mut replaced = !replace_old;
                     while let Some(message) = messages.stream.next().await {
@@ -1,11 +1,15 @@
 mod channel_buffer;
 mod channel_store;
+mod persistence;
 
 use client::{Client, UserStore};
 use gpui::{App, Entity};
 use std::sync::Arc;
 
-pub use channel_buffer::{ACKNOWLEDGE_DEBOUNCE_INTERVAL, ChannelBuffer, ChannelBufferEvent};
+pub use channel_buffer::{
+    ACKNOWLEDGE_DEBOUNCE_INTERVAL, ChannelBuffer, ChannelBufferEvent, ChannelBufferRevision,
+    ChannelBufferSyncStatus,
+};
 pub use channel_store::{Channel, ChannelEvent, ChannelMembership, ChannelStore};
 
 #[cfg(test)]
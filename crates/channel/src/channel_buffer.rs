@@ -1,18 +1,20 @@
-use crate::{Channel, ChannelStore};
+use crate::{Channel, ChannelStore, persistence::CHANNEL_NOTES_DB};
 use anyhow::Result;
-use client::{ChannelId, Client, Collaborator, UserStore, ZED_ALWAYS_ACTIVE};
+use client::{ChannelId, Client, Collaborator, User, UserStore, ZED_ALWAYS_ACTIVE};
 use collections::HashMap;
 use gpui::{App, AppContext as _, AsyncApp, Context, Entity, EventEmitter, Task};
-use language::proto::serialize_version;
+use language::proto::{deserialize_version, serialize_version};
 use rpc::{
     AnyProtoClient, TypedEnvelope,
     proto::{self, PeerId},
 };
-use std::{sync::Arc, time::Duration};
+use std::{future::Future, sync::Arc, time::Duration};
 use text::{BufferId, ReplicaId};
+use time::OffsetDateTime;
 use util::ResultExt;
 
 pub const ACKNOWLEDGE_DEBOUNCE_INTERVAL: Duration = Duration::from_millis(250);
+const OFFLINE_EDITS_SAVE_DEBOUNCE_INTERVAL: Duration = Duration::from_millis(500);
 
 pub(crate) fn init(client: &AnyProtoClient) {
     client.add_entity_message_handler(ChannelBuffer::handle_update_channel_buffer);
@@ -30,6 +32,12 @@ pub struct ChannelBuffer {
     client: Arc<Client>,
     subscription: Option<client::Subscription>,
     acknowledge_task: Option<Task<Result<()>>>,
+    /// The version of the buffer that the server is known to have.
+    synced_version: clock::Global,
+    /// Whether there are edits that haven't reached the server, which are saved
+    /// locally until they can be merged.
+    has_offline_edits: bool,
+    save_offline_edits_task: Option<Task<()>>,
 }
 
 pub enum ChannelBufferEvent {
@@ -38,6 +46,28 @@ pub enum ChannelBufferEvent {
     Connected,
     BufferEdited,
     ChannelChanged,
+    SyncStatusChanged,
+    /// The buffer couldn't be rejoined after reconnecting, because the server
+    /// compacted its history in the meantime. Reopening the channel notes merges any
+    /// offline edits into the server's version.
+    RejoinFailed,
+}
+
+/// A run of consecutive edits to the channel notes made by the same user.
+#[derive(Clone, Debug)]
+pub struct ChannelBufferRevision {
+    pub epoch: u64,
+    pub version: clock::Global,
+    pub user: Option<Arc<User>>,
+    pub timestamp: Option<OffsetDateTime>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelBufferSyncStatus {
+    Synced,
+    Offline,
+    /// Edits were made while offline, which will be merged once reconnected.
+    UnsyncedEdits,
 }
 
 impl EventEmitter<ChannelBufferEvent> for ChannelBuffer {}
@@ -72,11 +102,27 @@ impl ChannelBuffer {
                 base_text,
             )
         });
-        buffer.update(cx, |buffer, cx| buffer.apply_ops(operations, cx));
+        let buffer_version = buffer.update(cx, |buffer, cx| {
+            buffer.apply_ops(operations, cx);
+            buffer.version()
+        });
 
         let subscription = client.subscribe_to_entity(channel.id.0)?;
 
-        anyhow::Ok(cx.new(|cx| {
+        let user_id = user_store.read_with(cx, |user_store, _| {
+            user_store.current_user().map(|user| user.id)
+        });
+        let channel_id = channel.id.0;
+        let offline_edits = if let Some(user_id) = user_id {
+            cx.background_spawn(async move { CHANNEL_NOTES_DB.offline_edits(user_id, channel_id) })
+                .await
+                .log_err()
+                .flatten()
+        } else {
+            None
+        };
+
+        let this = cx.new(|cx| {
             cx.subscribe(&buffer, Self::on_buffer_update).detach();
             cx.on_release(Self::release).detach();
             let mut this = Self {
@@ -90,13 +136,35 @@ impl ChannelBuffer {
                 subscription: Some(subscription.set_entity(&cx.entity(), &cx.to_async())),
                 user_store,
                 channel_store,
+                synced_version: buffer_version,
+                has_offline_edits: false,
+                save_offline_edits_task: None,
             };
             this.replace_collaborators(response.collaborators, cx);
             this
-        }))
+        });
+
+        if let Some(user_id) = user_id
+            && let Some((base_text, offline_text)) = offline_edits
+        {
+            this.update(cx, |this, cx| {
+                this.apply_offline_edits(&base_text, &offline_text, cx)
+            });
+            cx.background_spawn(async move {
+                CHANNEL_NOTES_DB
+                    .delete_offline_edits(user_id, channel_id)
+                    .await
+            })
+            .detach_and_log_err(cx);
+        }
+
+        anyhow::Ok(this)
     }
 
     fn release(&mut self, _: &mut App) {
+        if let Some(task) = self.save_offline_edits_task.take() {
+            task.detach();
+        }
         if self.connected {
             if let Some(task) = self.acknowledge_task.take() {
                 task.detach();
@@ -111,6 +179,23 @@ impl ChannelBuffer {
 
     pub fn connected(&mut self, cx: &mut Context<Self>) {
         self.connected = true;
+        // Rejoining the buffer sent the operations the server was missing.
+        self.synced_version = self.buffer.read(cx).version();
+        if self.has_offline_edits {
+            self.has_offline_edits = false;
+            self.save_offline_edits_task = None;
+            if let Some(user_id) = self.user_id(cx) {
+                let channel_id = self.channel_id.0;
+                cx.background_spawn(async move {
+                    CHANNEL_NOTES_DB
+                        .delete_offline_edits(user_id, channel_id)
+                        .await
+                })
+                .detach_and_log_err(cx);
+            }
+            cx.emit(ChannelBufferEvent::SyncStatusChanged);
+            cx.notify();
+        }
         if self.subscription.is_none() {
             let Ok(subscription) = self.client.subscribe_to_entity(self.channel_id.0) else {
                 return;
@@ -167,7 +252,10 @@ impl ChannelBuffer {
         this.update(&mut cx, |this, cx| {
             cx.notify();
             this.buffer
-                .update(cx, |buffer, cx| buffer.apply_ops(ops, cx))
+                .update(cx, |buffer, cx| buffer.apply_ops(ops, cx));
+            if !this.has_offline_edits {
+                this.synced_version = this.buffer.read(cx).version();
+            }
         });
 
         Ok(())
@@ -203,13 +291,22 @@ impl ChannelBuffer {
                 {
                     return;
                 }
+                let is_edit = matches!(operation, language::Operation::Buffer(_));
                 let operation = language::proto::serialize_operation(operation);
-                self.client
-                    .send(proto::UpdateChannelBuffer {
-                        channel_id: self.channel_id.0,
-                        operations: vec![operation],
-                    })
-                    .log_err();
+                let sent = self.connected
+                    && self
+                        .client
+                        .send(proto::UpdateChannelBuffer {
+                            channel_id: self.channel_id.0,
+                            operations: vec![operation],
+                        })
+                        .log_err()
+                        .is_some();
+                if sent && !self.has_offline_edits {
+                    self.synced_version = self.buffer.read(cx).version();
+                } else if is_edit {
+                    self.schedule_offline_edits_save(cx);
+                }
             }
             language::BufferEvent::Edited => {
                 cx.emit(ChannelBufferEvent::BufferEdited);
@@ -240,6 +337,61 @@ impl ChannelBuffer {
         }));
     }
 
+    fn user_id(&self, cx: &App) -> Option<u64> {
+        Some(self.user_store.read(cx).current_user()?.id)
+    }
+
+    /// Saves the edits that haven't reached the server yet, once no more edits have
+    /// been made for a moment.
+    fn schedule_offline_edits_save(&mut self, cx: &mut Context<Self>) {
+        if !self.has_offline_edits {
+            self.has_offline_edits = true;
+            cx.emit(ChannelBufferEvent::SyncStatusChanged);
+            cx.notify();
+        }
+        let Some(save) = self.save_offline_edits(cx) else {
+            return;
+        };
+        let executor = cx.background_executor().clone();
+        self.save_offline_edits_task = Some(cx.background_spawn(async move {
+            executor.timer(OFFLINE_EDITS_SAVE_DEBOUNCE_INTERVAL).await;
+            save.await;
+        }));
+    }
+
+    fn save_offline_edits(&self, cx: &App) -> Option<impl Future<Output = ()> + use<>> {
+        let user_id = self.user_id(cx)?;
+        let channel_id = self.channel_id.0;
+        let snapshot = self.buffer.read(cx).text_snapshot();
+        let synced_version = self.synced_version.clone();
+        Some(async move {
+            let base_text = snapshot.rope_for_version(&synced_version).to_string();
+            let text = snapshot.text();
+            CHANNEL_NOTES_DB
+                .save_offline_edits(user_id, channel_id, base_text, text)
+                .await
+                .log_err();
+        })
+    }
+
+    fn apply_offline_edits(&mut self, base_text: &str, offline_text: &str, cx: &mut Context<Self>) {
+        let server_text = self.buffer.read(cx).text();
+        let merged_text = merge_offline_edits(base_text, offline_text, &server_text);
+        let edits = language::text_diff(&server_text, &merged_text);
+        self.buffer
+            .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+    }
+
+    pub fn sync_status(&self) -> ChannelBufferSyncStatus {
+        if self.has_offline_edits {
+            ChannelBufferSyncStatus::UnsyncedEdits
+        } else if self.connected {
+            ChannelBufferSyncStatus::Synced
+        } else {
+            ChannelBufferSyncStatus::Offline
+        }
+    }
+
     pub fn epoch(&self) -> u64 {
         self.buffer_epoch
     }
@@ -252,6 +404,64 @@ impl ChannelBuffer {
         &self.collaborators
     }
 
+    /// Fetches the history of the notes, oldest revision first.
+    pub fn history(&self, cx: &mut Context<Self>) -> Task<Result<Vec<ChannelBufferRevision>>> {
+        let request = self.client.request(proto::GetChannelBufferHistory {
+            channel_id: self.channel_id.0,
+        });
+        let user_store = self.user_store.clone();
+        cx.spawn(async move |_, cx| {
+            let revisions = request.await?.revisions;
+            let mut user_ids = revisions
+                .iter()
+                .filter_map(|revision| revision.user_id)
+                .collect::<Vec<_>>();
+            user_ids.sort_unstable();
+            user_ids.dedup();
+            let users = user_store
+                .update(cx, |user_store, cx| user_store.get_users(user_ids, cx))
+                .await?;
+
+            Ok(revisions
+                .into_iter()
+                .map(|revision| ChannelBufferRevision {
+                    epoch: revision.epoch,
+                    version: deserialize_version(&revision.version),
+                    user: revision
+                        .user_id
+                        .and_then(|user_id| users.iter().find(|user| user.id == user_id).cloned()),
+                    timestamp: revision.timestamp.and_then(|timestamp| {
+                        OffsetDateTime::from_unix_timestamp(timestamp as i64).ok()
+                    }),
+                })
+                .collect())
+        })
+    }
+
+    /// Fetches the text of the notes as of the given revision.
+    pub fn revision_text(
+        &self,
+        revision: &ChannelBufferRevision,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<String>> {
+        let request = self.client.request(proto::GetChannelBufferRevision {
+            channel_id: self.channel_id.0,
+            epoch: revision.epoch,
+            version: serialize_version(&revision.version),
+        });
+        cx.background_spawn(async move { Ok(request.await?.text) })
+    }
+
+    /// Replaces the contents of the notes with the given text, as a regular edit
+    /// that collaborators see and that can be undone.
+    pub fn restore_text(&mut self, text: &str, cx: &mut Context<Self>) {
+        let current_text = self.buffer.read(cx).text();
+        let edits = language::text_diff(&current_text, text);
+        self.buffer.update(cx, |buffer, cx| {
+            buffer.edit(edits, None, cx);
+        });
+    }
+
     pub fn channel(&self, cx: &App) -> Option<Arc<Channel>> {
         self.channel_store
             .read(cx)
@@ -269,6 +479,26 @@ impl ChannelBuffer {
         }
     }
 
+    /// Called when the buffer couldn't be rejoined after reconnecting. Offline edits
+    /// are saved right away, so that reopening the notes can merge them.
+    pub(crate) fn rejoin_failed(&mut self, cx: &mut Context<Self>) {
+        self.disconnect(cx);
+        self.save_offline_edits_task = None;
+        let save = if self.has_offline_edits {
+            self.save_offline_edits(cx)
+        } else {
+            None
+        };
+        cx.spawn(async move |this, cx| {
+            if let Some(save) = save {
+                save.await;
+            }
+            this.update(cx, |_, cx| cx.emit(ChannelBufferEvent::RejoinFailed))
+                .ok();
+        })
+        .detach();
+    }
+
     pub(crate) fn channel_changed(&mut self, cx: &mut Context<Self>) {
         cx.emit(ChannelBufferEvent::ChannelChanged);
         cx.notify()
@@ -282,3 +512,46 @@ impl ChannelBuffer {
         self.buffer.read(cx).replica_id()
     }
 }
+
+/// Merges the edits made to `base_text` while offline, which produced
+/// `offline_text`, with the edits collaborators made to it in the meantime, which
+/// produced `server_text`.
+///
+/// Both sets of edits are applied to `base_text` as concurrent operations of a text
+/// CRDT, so they merge the same way live edits would.
+pub(crate) fn merge_offline_edits(
+    base_text: &str,
+    offline_text: &str,
+    server_text: &str,
+) -> String {
+    let buffer_id = BufferId::new(1).unwrap();
+    let mut offline_buffer = text::Buffer::new(ReplicaId::LOCAL, buffer_id, base_text);
+    let mut server_buffer = text::Buffer::new(ReplicaId::FIRST_COLLAB_ID, buffer_id, base_text);
+    offline_buffer.edit(language::text_diff(base_text, offline_text));
+    let server_operation = server_buffer.edit(language::text_diff(base_text, server_text));
+    offline_buffer.apply_ops([server_operation]);
+    offline_buffer.text()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_offline_edits() {
+        assert_eq!(
+            merge_offline_edits(
+                "one\ntwo\nthree\n",
+                "one\n2\nthree\n",
+                "zero\none\ntwo\nthree!\n"
+            ),
+            "zero\none\n2\nthree!\n"
+        );
+
+        // Without concurrent edits, the offline text is kept as is.
+        assert_eq!(
+            merge_offline_edits("one\n", "one\ntwo\n", "one\n"),
+            "one\ntwo\n"
+        );
+    }
+}
@@ -933,7 +933,7 @@ impl ChannelStore {
                                 }
                             }
 
-                            channel_buffer.disconnect(cx);
+                            channel_buffer.rejoin_failed(cx);
                             false
                         })
                     }
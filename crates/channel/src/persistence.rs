@@ -0,0 +1,57 @@
+use anyhow::Result;
+use db::{
+    query,
+    sqlez::{domain::Domain, thread_safe_connection::ThreadSafeConnection},
+    sqlez_macros::sql,
+};
+
+/// Edits made to channel notes while disconnected, stored as the text of the notes
+/// when the connection was lost along with their text after the edits, so they can be
+/// merged into the notes once the connection returns, even across restarts.
+pub(crate) struct ChannelNotesDb(ThreadSafeConnection);
+
+impl Domain for ChannelNotesDb {
+    const NAME: &str = stringify!(ChannelNotesDb);
+    const MIGRATIONS: &[&str] = &[sql!(
+        CREATE TABLE offline_channel_notes(
+            user_id INTEGER NOT NULL,
+            channel_id INTEGER NOT NULL,
+            base_text TEXT NOT NULL,
+            text TEXT NOT NULL,
+            PRIMARY KEY(user_id, channel_id)
+        ) STRICT;
+    )];
+}
+
+db::static_connection!(CHANNEL_NOTES_DB, ChannelNotesDb, []);
+
+impl ChannelNotesDb {
+    query! {
+        pub(crate) async fn save_offline_edits(
+            user_id: u64,
+            channel_id: u64,
+            base_text: String,
+            text: String
+        ) -> Result<()> {
+            INSERT INTO offline_channel_notes(user_id, channel_id, base_text, text)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT DO UPDATE SET
+                base_text = ?3,
+                text = ?4
+        }
+    }
+
+    query! {
+        pub(crate) fn offline_edits(user_id: u64, channel_id: u64) -> Result<Option<(String, String)>> {
+            SELECT base_text, text FROM offline_channel_notes
+            WHERE user_id = ? AND channel_id = ?
+        }
+    }
+
+    query! {
+        pub(crate) async fn delete_offline_edits(user_id: u64, channel_id: u64) -> Result<()> {
+            DELETE FROM offline_channel_notes
+            WHERE user_id = ? AND channel_id = ?
+        }
+    }
+}
@@ -22,6 +22,16 @@ pub enum CliRequest {
         env: Option<HashMap<String, String>>,
         user_data_dir: Option<String>,
     },
+    /// Serve the project containing `cwd` over the Model Context Protocol.
+    /// Messages are exchanged as [`CliRequest::McpMessage`] and
+    /// [`CliResponse::McpMessage`], unless `http_port` is set.
+    McpServer {
+        cwd: String,
+        http_port: Option<u16>,
+    },
+    McpMessage {
+        message: String,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -30,6 +40,7 @@ pub enum CliResponse {
     Stdout { message: String },
     Stderr { message: String },
     Exit { status: i32 },
+    McpMessage { message: String },
 }
 
 /// When Zed started not as an *.app but as a binary (e.g. local development),
@@ -9,13 +9,17 @@
 
 use anyhow::{Context as _, Result};
 use clap::Parser;
-use cli::{CliRequest, CliResponse, IpcHandshake, ipc::IpcOneShotServer};
+use cli::{
+    CliRequest, CliResponse, IpcHandshake,
+    ipc::{self, IpcOneShotServer},
+};
 use parking_lot::Mutex;
 use std::{
     collections::{BTreeMap, BTreeSet},
     env,
     ffi::OsStr,
-    fs, io,
+    fs,
+    io::{self, BufRead as _, Write as _},
     path::{Path, PathBuf},
     process::ExitStatus,
     sync::Arc,
@@ -130,6 +134,14 @@ struct Args {
     #[arg(long)]
     uninstall: bool,
 
+    /// Serve the project in the current directory from a running Zed instance over the
+    /// Model Context Protocol, exchanging messages on stdin and stdout.
+    #[arg(long, conflicts_with_all = ["paths_with_position", "diff", "mcp_port"])]
+    mcp: bool,
+    /// Like `--mcp`, but serve over HTTP on the given localhost port instead.
+    #[arg(long, value_name = "PORT", conflicts_with_all = ["paths_with_position", "diff"])]
+    mcp_port: Option<u16>,
+
     /// Used for SSH/Git password authentication, to remove the need for netcat as a dependency,
     /// by having Zed act like netcat communicating over a Unix socket.
     #[arg(long, hide = true)]
@@ -654,6 +666,10 @@ fn main() -> Result<()> {
                 let (_, handshake) = server.accept().context("Handshake after Zed spawn")?;
                 let (tx, rx) = (handshake.requests, handshake.responses);
 
+                if args.mcp || args.mcp_port.is_some() {
+                    return serve_mcp(tx, rx, args.mcp_port, &exit_status);
+                }
+
                 #[cfg(target_os = "windows")]
                 let wsl = args.wsl;
                 #[cfg(not(target_os = "windows"))]
@@ -681,6 +697,7 @@ fn main() -> Result<()> {
                             exit_status.lock().replace(status);
                             return Ok(());
                         }
+                        CliResponse::McpMessage { .. } => {}
                     }
                 }
 
@@ -732,6 +749,64 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Bridges the Model Context Protocol between Zed and stdio, or keeps the
+/// connection open while Zed serves it over HTTP.
+fn serve_mcp(
+    tx: ipc::IpcSender<CliRequest>,
+    rx: ipc::IpcReceiver<CliResponse>,
+    http_port: Option<u16>,
+    exit_status: &Mutex<Option<i32>>,
+) -> anyhow::Result<()> {
+    let cwd = env::current_dir().context("Failed to get current directory")?;
+    tx.send(CliRequest::McpServer {
+        cwd: cwd.to_string_lossy().into_owned(),
+        http_port,
+    })?;
+
+    if http_port.is_none() {
+        thread::Builder::new()
+            .name("CliMcpStdin".to_string())
+            .spawn(move || {
+                for line in io::stdin().lock().lines() {
+                    let Ok(message) = line else {
+                        break;
+                    };
+                    if message.trim().is_empty() {
+                        continue;
+                    }
+                    if tx.send(CliRequest::McpMessage { message }).is_err() {
+                        break;
+                    }
+                }
+                // Closing stdin ends the session. Dropping `tx` tells Zed, which answers
+                // the requests it already received and then sends `Exit`.
+                drop(tx);
+            })?;
+    }
+
+    let mut stdout = io::stdout().lock();
+    while let Ok(response) = rx.recv() {
+        match response {
+            CliResponse::Ping => {}
+            // When serving over stdio, stdout is reserved for protocol messages.
+            CliResponse::Stdout { message } if http_port.is_some() => println!("{message}"),
+            CliResponse::Stdout { message } | CliResponse::Stderr { message } => {
+                eprintln!("{message}")
+            }
+            CliResponse::McpMessage { message } => {
+                writeln!(stdout, "{message}")?;
+                stdout.flush()?;
+            }
+            CliResponse::Exit { status } => {
+                exit_status.lock().replace(status);
+                return Ok(());
+            }
+        }
+    }
+
+    Ok(())
+}
+
 fn anonymous_fd(path: &str) -> Option<fs::File> {
     #[cfg(target_os = "linux")]
     {
@@ -308,6 +308,8 @@ CREATE TABLE "buffer_operations" (
     "replica_id" INTEGER NOT NULL,
     "lamport_timestamp" INTEGER NOT NULL,
     "value" BLOB NOT NULL,
+    "user_id" INTEGER REFERENCES users (id) ON DELETE SET NULL,
+    "created_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
     PRIMARY KEY (buffer_id, epoch, lamport_timestamp, replica_id)
 );
 
@@ -25,7 +25,9 @@ CREATE TABLE public.buffer_operations (
     epoch integer NOT NULL,
     replica_id integer NOT NULL,
     lamport_timestamp integer NOT NULL,
-    value bytea NOT NULL
+    value bytea NOT NULL,
+    user_id integer,
+    created_at timestamp without time zone DEFAULT now() NOT NULL
 );
 
 CREATE TABLE public.buffer_snapshots (
@@ -714,6 +716,9 @@ ALTER TABLE ONLY public.breakpoints
 ALTER TABLE ONLY public.buffer_operations
     ADD CONSTRAINT buffer_operations_buffer_id_fkey FOREIGN KEY (buffer_id) REFERENCES public.buffers(id) ON DELETE CASCADE;
 
+ALTER TABLE ONLY public.buffer_operations
+    ADD CONSTRAINT buffer_operations_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE SET NULL;
+
 ALTER TABLE ONLY public.buffer_snapshots
     ADD CONSTRAINT buffer_snapshots_buffer_id_fkey FOREIGN KEY (buffer_id) REFERENCES public.buffers(id) ON DELETE CASCADE;
 
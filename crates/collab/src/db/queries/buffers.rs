@@ -3,6 +3,10 @@ use anyhow::Context as _;
 use prost::Message;
 use text::{EditOperation, UndoOperation};
 
+/// Consecutive edits by the same user are grouped into a single revision of the
+/// channel notes' history, as long as they're made within this interval.
+const REVISION_INTERVAL: time::Duration = time::Duration::minutes(10);
+
 pub struct LeftChannelBuffer {
     pub channel_id: ChannelId,
     pub collaborators: Vec<proto::Collaborator>,
@@ -484,7 +488,7 @@ impl Database {
 
             let operations = operations
                 .iter()
-                .filter_map(|op| operation_to_storage(op, &buffer, serialization_version))
+                .filter_map(|op| operation_to_storage(op, &buffer, user, serialization_version))
                 .collect::<Vec<_>>();
 
             let max_version;
@@ -681,6 +685,8 @@ impl Database {
                 lamport_timestamp: row.lamport_timestamp,
                 replica_id: row.replica_id,
                 value: Default::default(),
+                user_id: row.user_id,
+                created_at: row.created_at,
             });
             operations.push(proto::Operation {
                 variant: Some(operation_from_storage(row, version)?),
@@ -735,6 +741,125 @@ impl Database {
         Ok(())
     }
 
+    /// Returns the history of a channel buffer, as runs of consecutive edits made by
+    /// the same user, oldest first.
+    pub async fn get_channel_buffer_history(
+        &self,
+        channel_id: ChannelId,
+        user_id: UserId,
+    ) -> Result<Vec<proto::ChannelBufferRevision>> {
+        self.transaction(|tx| async move {
+            let channel = self.get_channel_internal(channel_id, &tx).await?;
+            self.check_user_is_channel_participant(&channel, user_id, &tx)
+                .await?;
+            let buffer = self.get_channel_buffer(channel_id, &tx).await?;
+
+            let mut rows = buffer_operation::Entity::find()
+                .filter(buffer_operation::Column::BufferId.eq(buffer.id))
+                .order_by_asc(buffer_operation::Column::Epoch)
+                .order_by_asc(buffer_operation::Column::LamportTimestamp)
+                .order_by_asc(buffer_operation::Column::ReplicaId)
+                .stream(&*tx)
+                .await?;
+
+            let mut revisions = Vec::<proto::ChannelBufferRevision>::new();
+            let mut epoch = None;
+            let mut version = clock::Global::new();
+            let mut revision_start = None;
+            while let Some(row) = rows.next().await {
+                let row = row?;
+                if epoch != Some(row.epoch) {
+                    epoch = Some(row.epoch);
+                    version = clock::Global::new();
+                    revision_start = None;
+                }
+                version.observe(clock::Lamport {
+                    replica_id: clock::ReplicaId::new(row.replica_id as u16),
+                    value: row.lamport_timestamp as u32,
+                });
+
+                let continues_revision = revision_start.is_some_and(|start| {
+                    revisions.last().is_some_and(|revision| {
+                        revision.user_id == row.user_id.map(|id| id.to_proto())
+                    }) && row.created_at - start < REVISION_INTERVAL
+                });
+                if !continues_revision {
+                    revision_start = Some(row.created_at);
+                    revisions.push(proto::ChannelBufferRevision {
+                        epoch: row.epoch as u64,
+                        user_id: row.user_id.map(|id| id.to_proto()),
+                        ..Default::default()
+                    });
+                }
+                if let Some(revision) = revisions.last_mut() {
+                    revision.version = version_to_wire(&version);
+                    revision.timestamp = Some(row.created_at.assume_utc().unix_timestamp() as u64);
+                }
+            }
+
+            Ok(revisions)
+        })
+        .await
+    }
+
+    /// Returns the text of a channel buffer at the given version of one of its
+    /// epochs.
+    pub async fn get_channel_buffer_revision(
+        &self,
+        channel_id: ChannelId,
+        user_id: UserId,
+        epoch: i32,
+        version: &[proto::VectorClockEntry],
+    ) -> Result<String> {
+        self.transaction(|tx| async move {
+            let channel = self.get_channel_internal(channel_id, &tx).await?;
+            self.check_user_is_channel_participant(&channel, user_id, &tx)
+                .await?;
+            let buffer = self.get_channel_buffer(channel_id, &tx).await?;
+
+            let snapshot = buffer_snapshot::Entity::find()
+                .filter(
+                    buffer_snapshot::Column::BufferId
+                        .eq(buffer.id)
+                        .and(buffer_snapshot::Column::Epoch.eq(epoch)),
+                )
+                .one(&*tx)
+                .await?
+                .context("no such snapshot")?;
+
+            let mut rows = buffer_operation::Entity::find()
+                .filter(
+                    buffer_operation::Column::BufferId
+                        .eq(buffer.id)
+                        .and(buffer_operation::Column::Epoch.eq(epoch)),
+                )
+                .order_by_asc(buffer_operation::Column::LamportTimestamp)
+                .order_by_asc(buffer_operation::Column::ReplicaId)
+                .stream(&*tx)
+                .await?;
+            let mut operations = Vec::new();
+            while let Some(row) = rows.next().await {
+                operations.push(proto::Operation {
+                    variant: Some(operation_from_storage(
+                        row?,
+                        snapshot.operation_serialization_version,
+                    )?),
+                });
+            }
+
+            let mut text_buffer = text::Buffer::new(
+                clock::ReplicaId::LOCAL,
+                text::BufferId::new(1).unwrap(),
+                snapshot.text,
+            );
+            text_buffer.apply_ops(operations.into_iter().filter_map(operation_from_wire));
+            Ok(text_buffer
+                .rope_for_version(&version_from_wire(version))
+                .to_string())
+        })
+        .await
+    }
+
     pub async fn observe_buffer_version(
         &self,
         buffer_id: BufferId,
@@ -821,6 +946,7 @@ impl Database {
 fn operation_to_storage(
     operation: &proto::Operation,
     buffer: &buffer::Model,
+    user_id: UserId,
     _format: i32,
 ) -> Option<buffer_operation::ActiveModel> {
     let (replica_id, lamport_timestamp, value) = match operation.variant.as_ref()? {
@@ -870,6 +996,8 @@ fn operation_to_storage(
         replica_id: ActiveValue::Set(replica_id as i32),
         lamport_timestamp: ActiveValue::Set(lamport_timestamp as i32),
         value: ActiveValue::Set(value.encode_to_vec()),
+        user_id: ActiveValue::Set(Some(user_id)),
+        created_at: ActiveValue::NotSet,
     })
 }
 
@@ -1,5 +1,6 @@
-use crate::db::BufferId;
+use crate::db::{BufferId, UserId};
 use sea_orm::entity::prelude::*;
+use time::PrimitiveDateTime;
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
 #[sea_orm(table_name = "buffer_operations")]
@@ -13,6 +14,9 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub replica_id: i32,
     pub value: Vec<u8>,
+    /// The user who made the edit, used to attribute edits in the notes' history.
+    pub user_id: Option<UserId>,
+    pub created_at: PrimitiveDateTime,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
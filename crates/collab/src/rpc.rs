@@ -359,6 +359,7 @@ impl Server {
             .add_request_handler(forward_mutating_project_request::<proto::RestartLanguageServers>)
             .add_request_handler(forward_mutating_project_request::<proto::StopLanguageServers>)
             .add_request_handler(forward_mutating_project_request::<proto::LinkedEditingRange>)
+            .add_request_handler(forward_mutating_project_request::<proto::WriteToSharedTerminal>)
             .add_message_handler(create_buffer_for_peer)
             .add_message_handler(create_image_for_peer)
             .add_request_handler(update_buffer)
@@ -388,6 +389,8 @@ impl Server {
             .add_request_handler(set_channel_visibility)
             .add_request_handler(rename_channel)
             .add_request_handler(join_channel_buffer)
+            .add_request_handler(get_channel_buffer_history)
+            .add_request_handler(get_channel_buffer_revision)
             .add_request_handler(leave_channel_buffer)
             .add_message_handler(update_channel_buffer)
             .add_request_handler(rejoin_channel_buffers)
@@ -3356,6 +3359,39 @@ async fn join_channel_buffer(
     Ok(())
 }
 
+/// Fetch the history of the channel notes
+async fn get_channel_buffer_history(
+    request: proto::GetChannelBufferHistory,
+    response: Response<proto::GetChannelBufferHistory>,
+    session: MessageContext,
+) -> Result<()> {
+    let db = session.db().await;
+    let revisions = db
+        .get_channel_buffer_history(ChannelId::from_proto(request.channel_id), session.user_id())
+        .await?;
+    response.send(proto::GetChannelBufferHistoryResponse { revisions })?;
+    Ok(())
+}
+
+/// Fetch the channel notes as they were at a revision in their history
+async fn get_channel_buffer_revision(
+    request: proto::GetChannelBufferRevision,
+    response: Response<proto::GetChannelBufferRevision>,
+    session: MessageContext,
+) -> Result<()> {
+    let db = session.db().await;
+    let text = db
+        .get_channel_buffer_revision(
+            ChannelId::from_proto(request.channel_id),
+            session.user_id(),
+            request.epoch as i32,
+            &request.version,
+        )
+        .await?;
+    response.send(proto::GetChannelBufferRevisionResponse { text })?;
+    Ok(())
+}
+
 /// Edit the channel notes
 async fn update_channel_buffer(
     request: proto::UpdateChannelBuffer,
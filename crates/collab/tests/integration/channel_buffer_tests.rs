@@ -1,6 +1,6 @@
 use crate::{TestServer, test_server::open_channel_notes};
 use call::ActiveCall;
-use channel::ACKNOWLEDGE_DEBOUNCE_INTERVAL;
+use channel::{ACKNOWLEDGE_DEBOUNCE_INTERVAL, ChannelBufferSyncStatus};
 use client::{Collaborator, ParticipantIndex, UserId};
 use collab::rpc::{CLEANUP_TIMEOUT, RECONNECT_TIMEOUT};
 use collab_ui::channel_view::ChannelView;
@@ -534,6 +534,80 @@ async fn test_rejoin_channel_buffer(
     });
 }
 
+#[gpui::test]
+async fn test_channel_buffer_offline_edits(
+    deterministic: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(deterministic.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+
+    let channel_id = server
+        .make_channel(
+            "the-channel",
+            None,
+            (&client_a, cx_a),
+            &mut [(&client_b, cx_b)],
+        )
+        .await;
+
+    let channel_buffer_a = client_a
+        .channel_store()
+        .update(cx_a, |store, cx| store.open_channel_buffer(channel_id, cx))
+        .await
+        .unwrap();
+    let channel_buffer_b = client_b
+        .channel_store()
+        .update(cx_b, |store, cx| store.open_channel_buffer(channel_id, cx))
+        .await
+        .unwrap();
+
+    channel_buffer_a.update(cx_a, |buffer, cx| {
+        buffer.buffer().update(cx, |buffer, cx| {
+            buffer.edit([(0..0, "a\nb\n")], None, cx);
+        })
+    });
+    deterministic.run_until_parked();
+
+    // Client A stays disconnected past the reconnect timeout, and keeps editing.
+    server.forbid_connections();
+    server.disconnect_client(client_a.peer_id().unwrap());
+    deterministic.advance_clock(RECEIVE_TIMEOUT + RECONNECT_TIMEOUT);
+    channel_buffer_a.read_with(cx_a, |buffer, _| {
+        assert_eq!(buffer.sync_status(), ChannelBufferSyncStatus::Offline);
+    });
+
+    channel_buffer_a.update(cx_a, |buffer, cx| {
+        buffer.buffer().update(cx, |buffer, cx| {
+            buffer.edit([(2..2, "offline\n")], None, cx);
+        })
+    });
+    channel_buffer_b.update(cx_b, |buffer, cx| {
+        buffer.buffer().update(cx, |buffer, cx| {
+            buffer.edit([(0..0, "online\n")], None, cx);
+        })
+    });
+    deterministic.run_until_parked();
+    channel_buffer_a.read_with(cx_a, |buffer, cx| {
+        assert_eq!(buffer.sync_status(), ChannelBufferSyncStatus::UnsyncedEdits);
+        assert_eq!(buffer.buffer().read(cx).text(), "a\noffline\nb\n");
+    });
+
+    // Once client A reconnects, the edits of both clients are merged.
+    server.allow_connections();
+    deterministic.advance_clock(RECEIVE_TIMEOUT);
+    deterministic.run_until_parked();
+    channel_buffer_a.read_with(cx_a, |buffer, cx| {
+        assert_eq!(buffer.sync_status(), ChannelBufferSyncStatus::Synced);
+        assert_eq!(buffer.buffer().read(cx).text(), "online\na\noffline\nb\n");
+    });
+    channel_buffer_b.read_with(cx_b, |buffer, cx| {
+        assert_eq!(buffer.buffer().read(cx).text(), "online\na\noffline\nb\n");
+    });
+}
+
 #[gpui::test]
 async fn test_channel_buffers_and_server_restarts(
     deterministic: BackgroundExecutor,
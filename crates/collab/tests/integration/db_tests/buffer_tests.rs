@@ -346,6 +346,108 @@ async fn test_channel_buffers_last_operations(db: &Database) {
     );
 }
 
+test_both_dbs!(
+    test_channel_buffer_history,
+    test_channel_buffer_history_postgres,
+    test_channel_buffer_history_sqlite
+);
+
+async fn test_channel_buffer_history(db: &Database) {
+    let a_id = db
+        .create_user(
+            "user_a@example.com",
+            None,
+            false,
+            NewUserParams {
+                github_login: "user_a".into(),
+                github_user_id: 101,
+            },
+        )
+        .await
+        .unwrap()
+        .user_id;
+    let b_id = db
+        .create_user(
+            "user_b@example.com",
+            None,
+            false,
+            NewUserParams {
+                github_login: "user_b".into(),
+                github_user_id: 102,
+            },
+        )
+        .await
+        .unwrap()
+        .user_id;
+    let owner_id = db.create_server("production").await.unwrap().0 as u32;
+
+    let channel_id = db.create_root_channel("zed", a_id).await.unwrap();
+    db.invite_channel_member(channel_id, b_id, a_id, ChannelRole::Member)
+        .await
+        .unwrap();
+    db.respond_to_channel_invite(channel_id, b_id, true)
+        .await
+        .unwrap();
+    db.join_channel_buffer(channel_id, a_id, ConnectionId { owner_id, id: 1 })
+        .await
+        .unwrap();
+
+    let buffer_id = text::BufferId::new(1).unwrap();
+    let mut buffer_a = Buffer::new(ReplicaId::new(0), buffer_id, "".to_string());
+    let mut buffer_b = Buffer::new(ReplicaId::new(1), buffer_id, "".to_string());
+
+    let operations = vec![
+        buffer_a.edit([(0..0, "hello")]),
+        buffer_a.edit([(5..5, "!")]),
+    ];
+    buffer_b.apply_ops(operations.clone());
+    update_buffer(channel_id, a_id, db, operations).await;
+
+    let operations = vec![buffer_b.edit([(5..5, " world")])];
+    buffer_a.apply_ops(operations.clone());
+    update_buffer(channel_id, b_id, db, operations).await;
+
+    let operations = vec![buffer_a.edit([(0..5, "goodbye")])];
+    update_buffer(channel_id, a_id, db, operations).await;
+
+    // Consecutive edits by the same user are grouped into one revision.
+    let revisions = db
+        .get_channel_buffer_history(channel_id, b_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        revisions
+            .iter()
+            .map(|revision| revision.user_id)
+            .collect::<Vec<_>>(),
+        [
+            Some(a_id.to_proto()),
+            Some(b_id.to_proto()),
+            Some(a_id.to_proto())
+        ]
+    );
+    assert!(
+        revisions
+            .iter()
+            .all(|revision| revision.timestamp.is_some())
+    );
+
+    let mut texts = Vec::new();
+    for revision in &revisions {
+        texts.push(
+            db.get_channel_buffer_revision(
+                channel_id,
+                b_id,
+                revision.epoch as i32,
+                &revision.version,
+            )
+            .await
+            .unwrap(),
+        );
+    }
+    assert_eq!(texts, ["hello!", "hello world!", "goodbye world!"]);
+}
+
 async fn update_buffer(
     channel_id: ChannelId,
     user_id: UserId,
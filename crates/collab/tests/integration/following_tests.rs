@@ -6,17 +6,28 @@ use collab_ui::{
     channel_view::ChannelView,
     notifications::project_shared_notification::ProjectSharedNotification,
 };
+use dap::requests::{Scopes, StackTrace, Threads};
+use debugger_ui::debugger_panel::DebugPanel;
 use editor::{Editor, MultiBuffer, MultiBufferOffset, PathKey, SelectionEffects};
+use git_ui::project_diff::ProjectDiff;
 use gpui::{
     AppContext as _, BackgroundExecutor, BorrowAppContext, Entity, SharedString, TestAppContext,
     VisualContext, VisualTestContext, point,
 };
 use language::Capability;
+use project::ProjectPath;
 use rpc::proto::PeerId;
+use search::project_search::{ProjectSearch, ProjectSearchView, perform_project_search};
 use serde_json::json;
 use settings::SettingsStore;
+use std::path::Path;
+use terminal::{
+    Terminal, TerminalBuilder,
+    terminal_settings::{AlternateScroll, CursorShape},
+};
+use terminal_view::{ShareTerminal, TerminalView};
 use text::{Point, ToPoint};
-use util::{path, rel_path::rel_path, test::sample_text};
+use util::{path, paths::PathStyle, rel_path::rel_path, test::sample_text};
 use workspace::{CollaboratorId, MultiWorkspace, SplitDirection, Workspace, item::ItemHandle as _};
 
 use super::TestClient;
@@ -2365,3 +2376,336 @@ async fn test_following_while_deactivated(cx_a: &mut TestAppContext, cx_b: &mut
         assert_eq!(editor.tab_content_text(0, cx), "2.js");
     });
 }
+
+#[gpui::test]
+async fn test_following_shared_terminal(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext) {
+    let (_server, client_a, client_b, channel_id) = TestServer::start2(cx_a, cx_b).await;
+    let (workspace_a, cx_a) = client_a.build_test_workspace(cx_a).await;
+    client_a
+        .host_workspace(&workspace_a, channel_id, cx_a)
+        .await;
+    let (workspace_b, cx_b) = client_b.join_workspace(channel_id, cx_b).await;
+    for cx in [&mut *cx_a, &mut *cx_b] {
+        cx.update(|_, cx| workspace::FollowableViewRegistry::register::<TerminalView>(cx));
+    }
+
+    // Client A shares a terminal.
+    let terminal_a = workspace_a.update_in(cx_a, |workspace, window, cx| {
+        let terminal = cx.new(|cx| {
+            TerminalBuilder::new_display_only(
+                CursorShape::default(),
+                AlternateScroll::On,
+                None,
+                0,
+                cx.background_executor(),
+                PathStyle::local(),
+            )
+            .unwrap()
+            .subscribe(cx)
+        });
+        terminal.update(cx, |terminal, cx| terminal.write_output(b"$ ls\n1.txt", cx));
+        let view = cx.new(|cx| {
+            TerminalView::new(
+                terminal.clone(),
+                workspace.weak_handle(),
+                None,
+                workspace.project().downgrade(),
+                window,
+                cx,
+            )
+        });
+        workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+        terminal
+    });
+    cx_a.dispatch_action(ShareTerminal);
+    cx_a.run_until_parked();
+
+    // Client B follows client A and sees the terminal's screen.
+    let peer_id_a = client_a.peer_id().unwrap();
+    workspace_b.update_in(cx_b, |workspace, window, cx| {
+        workspace.follow(peer_id_a, window, cx)
+    });
+    cx_b.run_until_parked();
+    let terminal_b = workspace_b.update(cx_b, |workspace, cx| {
+        workspace
+            .active_item_as::<TerminalView>(cx)
+            .unwrap()
+            .read(cx)
+            .terminal()
+            .clone()
+    });
+    let screen = |terminal: &Entity<Terminal>, cx: &mut VisualTestContext| {
+        terminal.read_with(cx, |terminal, _| terminal.screen())
+    };
+    assert_eq!(screen(&terminal_b, cx_b), screen(&terminal_a, cx_a));
+
+    // Output is sent to followers after the update interval, with only the lines that changed.
+    terminal_a.update(cx_a, |terminal, cx| {
+        terminal.write_output(b"\n$ echo hi", cx);
+        terminal.write_output(b"\nhi", cx);
+    });
+    cx_a.run_until_parked();
+    assert_ne!(screen(&terminal_b, cx_b), screen(&terminal_a, cx_a));
+
+    cx_a.executor()
+        .advance_clock(workspace::item::LEADER_UPDATE_THROTTLE);
+    cx_b.run_until_parked();
+    let screen_b = screen(&terminal_b, cx_b);
+    assert_eq!(screen_b, screen(&terminal_a, cx_a));
+    assert_eq!(&screen_b.lines[..4], ["$ ls", "1.txt", "$ echo hi", "hi"]);
+}
+
+#[gpui::test]
+async fn test_following_project_search(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext) {
+    let (_server, client_a, client_b, channel_id) = TestServer::start2(cx_a, cx_b).await;
+    for cx in [&mut *cx_a, &mut *cx_b] {
+        cx.update(search::init);
+    }
+    let (workspace_a, cx_a) = client_a.build_test_workspace(cx_a).await;
+    client_a
+        .host_workspace(&workspace_a, channel_id, cx_a)
+        .await;
+    let (workspace_b, cx_b) = client_b.join_workspace(channel_id, cx_b).await;
+
+    // Client A searches the project.
+    let search_view_a = workspace_a.update_in(cx_a, |workspace, window, cx| {
+        let search = cx.new(|cx| ProjectSearch::new(workspace.project().clone(), cx));
+        let search_view =
+            cx.new(|cx| ProjectSearchView::new(workspace.weak_handle(), search, window, cx, None));
+        workspace.add_item_to_active_pane(Box::new(search_view.clone()), None, true, window, cx);
+        search_view
+    });
+    perform_project_search(&search_view_a, "one", cx_a);
+
+    // Client B follows client A and sees the same results.
+    let peer_id_a = client_a.peer_id().unwrap();
+    workspace_b.update_in(cx_b, |workspace, window, cx| {
+        workspace.follow(peer_id_a, window, cx)
+    });
+    cx_b.run_until_parked();
+    let search_view_b = workspace_b.update(cx_b, |workspace, cx| {
+        workspace.active_item_as::<ProjectSearchView>(cx).unwrap()
+    });
+    let results = |search_view: &Entity<ProjectSearchView>, cx: &mut VisualTestContext| {
+        search_view.update(cx, |search_view, cx| {
+            let text = search_view
+                .results_editor()
+                .update(cx, |editor, cx| editor.display_text(cx));
+            (text, search_view.get_matches(cx).len())
+        })
+    };
+    let results_a = results(&search_view_a, cx_a);
+    assert_eq!(results_a.1, 3);
+    assert_eq!(results(&search_view_b, cx_b), results_a);
+
+    // When client A searches again, client B sees the new results.
+    perform_project_search(&search_view_a, "two", cx_a);
+    cx_a.executor()
+        .advance_clock(workspace::item::LEADER_UPDATE_THROTTLE);
+    cx_b.run_until_parked();
+    let results_a = results(&search_view_a, cx_a);
+    assert_eq!(results_a.1, 1);
+    assert_eq!(results(&search_view_b, cx_b), results_a);
+    assert_eq!(
+        workspace_b.update(cx_b, |workspace, cx| {
+            workspace.active_item(cx).unwrap().item_id()
+        }),
+        search_view_b.entity_id()
+    );
+}
+
+#[gpui::test]
+async fn test_following_project_diff(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext) {
+    let (_server, client_a, client_b, channel_id) = TestServer::start2(cx_a, cx_b).await;
+    for cx in [&mut *cx_a, &mut *cx_b] {
+        cx.update(git_ui::init);
+    }
+    client_a
+        .fs()
+        .insert_tree(path!("/a"), json!({ ".git": {} }))
+        .await;
+    client_a.fs().set_head_and_index_for_repo(
+        Path::new(path!("/a/.git")),
+        &[
+            ("1.txt", "one\n".to_string()),
+            ("2.js", "function two() { return 1; }".to_string()),
+        ],
+    );
+    let (workspace_a, cx_a) = client_a.build_test_workspace(cx_a).await;
+    client_a
+        .host_workspace(&workspace_a, channel_id, cx_a)
+        .await;
+    let (workspace_b, cx_b) = client_b.join_workspace(channel_id, cx_b).await;
+    let worktree_id = workspace_a.update(cx_a, |workspace, cx| {
+        workspace
+            .project()
+            .read(cx)
+            .worktrees(cx)
+            .next()
+            .unwrap()
+            .read(cx)
+            .id()
+    });
+
+    // Client A opens the project diff, at the first changed file.
+    workspace_a.update_in(cx_a, |workspace, window, cx| {
+        ProjectDiff::deploy_at(workspace, None, window, cx)
+    });
+    cx_a.run_until_parked();
+    let diff_a = workspace_a.update(cx_a, |workspace, cx| {
+        workspace.active_item_as::<ProjectDiff>(cx).unwrap()
+    });
+    diff_a.update_in(cx_a, |diff, window, cx| {
+        diff.move_to_project_path(&(worktree_id, rel_path("1.txt")).into(), window, cx)
+    });
+    cx_a.run_until_parked();
+
+    // Client B follows client A into the diff.
+    let peer_id_a = client_a.peer_id().unwrap();
+    workspace_b.update_in(cx_b, |workspace, window, cx| {
+        workspace.follow(peer_id_a, window, cx)
+    });
+    cx_b.run_until_parked();
+    let diff_b = workspace_b.update(cx_b, |workspace, cx| {
+        workspace.active_item_as::<ProjectDiff>(cx).unwrap()
+    });
+    let active_path = |diff: &Entity<ProjectDiff>, cx: &mut VisualTestContext| {
+        diff.read_with(cx, |diff, cx| diff.active_path(cx))
+    };
+    assert_eq!(
+        active_path(&diff_b, cx_b),
+        Some((worktree_id, rel_path("1.txt")).into())
+    );
+
+    // Client B follows client A to another file in the diff.
+    diff_a.update_in(cx_a, |diff, window, cx| {
+        diff.move_to_project_path(&(worktree_id, rel_path("2.js")).into(), window, cx)
+    });
+    cx_a.executor()
+        .advance_clock(workspace::item::LEADER_UPDATE_THROTTLE);
+    cx_b.run_until_parked();
+    assert_eq!(
+        active_path(&diff_b, cx_b),
+        Some((worktree_id, rel_path("2.js")).into())
+    );
+}
+
+#[gpui::test]
+async fn test_following_debug_session(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext) {
+    for cx in [&mut *cx_a, &mut *cx_b] {
+        cx.update(|cx| {
+            command_palette_hooks::init(cx);
+            dap_adapters::init(cx);
+        });
+    }
+    let (_server, client_a, client_b, channel_id) = TestServer::start2(cx_a, cx_b).await;
+    for cx in [&mut *cx_a, &mut *cx_b] {
+        cx.update(debugger_ui::init);
+    }
+    let (workspace_a, cx_a) = client_a.build_test_workspace(cx_a).await;
+    client_a
+        .host_workspace(&workspace_a, channel_id, cx_a)
+        .await;
+    let (workspace_b, cx_b) = client_b.join_workspace(channel_id, cx_b).await;
+
+    // Client A starts debugging.
+    let debug_panel = workspace_a
+        .update_in(cx_a, |_, window, cx| cx.spawn_in(window, DebugPanel::load))
+        .await
+        .unwrap();
+    workspace_a.update_in(cx_a, |workspace, window, cx| {
+        workspace.add_panel(debug_panel, window, cx);
+    });
+    let window_a = cx_a.window_handle().downcast::<MultiWorkspace>().unwrap();
+    let session = debugger_ui::tests::start_debug_session(&window_a, cx_a, |_| {}).unwrap();
+    let client = session.update(cx_a, |session, _| session.adapter_client().unwrap());
+    client.on_request::<Threads, _>(|_, _| {
+        Ok(dap::ThreadsResponse {
+            threads: vec![dap::Thread {
+                id: 1,
+                name: "main".into(),
+            }],
+        })
+    });
+    client.on_request::<StackTrace, _>(|_, _| {
+        Ok(dap::StackTraceResponse {
+            stack_frames: vec![dap::StackFrame {
+                id: 1,
+                name: "test".into(),
+                source: Some(dap::Source {
+                    name: Some("3.rs".into()),
+                    path: Some(path!("/a/3.rs").into()),
+                    source_reference: None,
+                    presentation_hint: None,
+                    origin: None,
+                    sources: None,
+                    adapter_data: None,
+                    checksums: None,
+                }),
+                line: 1,
+                column: 1,
+                end_line: None,
+                end_column: None,
+                can_restart: None,
+                instruction_pointer_reference: None,
+                module_id: None,
+                presentation_hint: None,
+            }],
+            total_frames: None,
+        })
+    });
+    client.on_request::<Scopes, _>(|_, _| Ok(dap::ScopesResponse { scopes: vec![] }));
+    workspace_a.update_in(cx_a, |workspace, window, cx| {
+        workspace.focus_panel::<DebugPanel>(window, cx);
+    });
+    cx_a.run_until_parked();
+
+    // Client B follows client A into the debug session.
+    let peer_id_a = client_a.peer_id().unwrap();
+    workspace_b.update_in(cx_b, |workspace, window, cx| {
+        workspace.follow(peer_id_a, window, cx)
+    });
+    cx_b.run_until_parked();
+    let followed_session = |cx: &mut VisualTestContext| {
+        cx.read(|cx| debugger_ui::tests::followed_debug_session(&workspace_b, cx))
+            .unwrap()
+    };
+    let session_id = session.read_with(cx_a, |session, _| session.session_id().to_proto());
+    assert_eq!(followed_session(cx_b).session_id, session_id);
+    assert!(followed_session(cx_b).stack_frames.is_empty());
+
+    // When client A's program stops, client B sees where.
+    client
+        .fake_event(dap::messages::Events::Stopped(dap::StoppedEvent {
+            reason: dap::StoppedEventReason::Pause,
+            description: None,
+            thread_id: Some(1),
+            preserve_focus_hint: None,
+            text: None,
+            all_threads_stopped: None,
+            hit_breakpoint_ids: None,
+        }))
+        .await;
+    cx_a.run_until_parked();
+    cx_a.executor()
+        .advance_clock(workspace::item::LEADER_UPDATE_THROTTLE);
+    cx_b.run_until_parked();
+    let state = followed_session(cx_b);
+    assert_eq!(
+        state
+            .stack_frames
+            .iter()
+            .map(|stack_frame| (stack_frame.name.as_str(), stack_frame.line))
+            .collect::<Vec<_>>(),
+        [("test", 1)]
+    );
+    assert_eq!(
+        state.stack_frames[0]
+            .path
+            .clone()
+            .and_then(ProjectPath::from_proto)
+            .map(|project_path| project_path.path),
+        Some(rel_path("3.rs").into_arc())
+    );
+    assert_eq!(state.selected_stack_frame_id, Some(1));
+}
@@ -0,0 +1,338 @@
+use buffer_diff::BufferDiff;
+use channel::{ChannelBuffer, ChannelBufferRevision};
+use editor::{Editor, EditorEvent, MultiBuffer};
+use gpui::{
+    AnyElement, App, Context, Entity, EventEmitter, FocusHandle, Focusable, Render, Subscription,
+    Task, UniformListScrollHandle, Window, uniform_list,
+};
+use language::{Buffer, Capability};
+use std::sync::Arc;
+use time::{OffsetDateTime, UtcOffset};
+use ui::{Avatar, ListItem, ListItemSpacing, Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::{
+    Workspace,
+    item::{Item, TabContentParams},
+};
+
+/// Lists the revisions of a channel's notes, showing the notes as of the selected
+/// revision, diffed against their current version.
+pub struct ChannelNotesHistory {
+    channel_buffer: Entity<ChannelBuffer>,
+    revisions: Vec<ChannelBufferRevision>,
+    selected_revision: Option<usize>,
+    revision_text: Option<String>,
+    revision_buffer: Entity<Buffer>,
+    diff: Entity<BufferDiff>,
+    editor: Entity<Editor>,
+    scroll_handle: UniformListScrollHandle,
+    local_timezone: UtcOffset,
+    focus_handle: FocusHandle,
+    load_revision_task: Task<()>,
+    update_diff_task: Task<()>,
+    _load_history: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ChannelNotesHistory {
+    pub fn open(
+        channel_buffer: Entity<ChannelBuffer>,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items_of_type::<Self>()
+            .find(|history| history.read(cx).channel_buffer == channel_buffer);
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            return;
+        }
+
+        let history = cx.new(|cx| Self::new(channel_buffer, window, cx));
+        workspace.add_item_to_active_pane(Box::new(history), None, true, window, cx);
+    }
+
+    fn new(
+        channel_buffer: Entity<ChannelBuffer>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let notes_buffer = channel_buffer.read(cx).buffer();
+        let language = notes_buffer.read(cx).language().cloned();
+        let revision_buffer = cx.new(|cx| {
+            let mut buffer = Buffer::local("", cx);
+            buffer.set_language(language, cx);
+            buffer.set_capability(Capability::ReadOnly, cx);
+            buffer
+        });
+        let diff = cx.new(|cx| BufferDiff::new(&revision_buffer.read(cx).text_snapshot(), cx));
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer = MultiBuffer::singleton(revision_buffer.clone(), cx);
+            multibuffer.add_diff(diff.clone(), cx);
+            multibuffer
+        });
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer, None, window, cx);
+            editor.set_read_only(true);
+            editor.start_temporary_diff_override();
+            editor.disable_diagnostics(cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor.set_render_diff_hunk_controls(
+                Arc::new(|_, _, _, _, _, _, _, _| gpui::Empty.into_any_element()),
+                cx,
+            );
+            editor
+        });
+
+        let subscriptions = vec![
+            cx.subscribe(&editor, |_, _, event: &EditorEvent, cx| {
+                cx.emit(event.clone())
+            }),
+            cx.subscribe(&notes_buffer, |this, _, event, cx| {
+                if let language::BufferEvent::Edited = event {
+                    this.update_diff(cx);
+                }
+            }),
+        ];
+
+        let load_history =
+            channel_buffer.update(cx, |channel_buffer, cx| channel_buffer.history(cx));
+        let _load_history = cx.spawn(async move |this, cx| {
+            let Some(revisions) = load_history.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                this.revisions = revisions;
+                if let Some(latest) = this.revisions.len().checked_sub(1) {
+                    this.select_revision(latest, cx);
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+
+        let local_offset = chrono::Local::now().offset().local_minus_utc();
+        Self {
+            channel_buffer,
+            revisions: Vec::new(),
+            selected_revision: None,
+            revision_text: None,
+            revision_buffer,
+            diff,
+            editor,
+            scroll_handle: UniformListScrollHandle::new(),
+            local_timezone: UtcOffset::from_whole_seconds(local_offset).unwrap(),
+            focus_handle: cx.focus_handle(),
+            load_revision_task: Task::ready(()),
+            update_diff_task: Task::ready(()),
+            _load_history,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn select_revision(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let Some(revision) = self.revisions.get(ix) else {
+            return;
+        };
+        self.selected_revision = Some(ix);
+        self.revision_text = None;
+        let load_text = self.channel_buffer.update(cx, |channel_buffer, cx| {
+            channel_buffer.revision_text(revision, cx)
+        });
+        self.load_revision_task = cx.spawn(async move |this, cx| {
+            let Some(text) = load_text.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                this.revision_buffer
+                    .update(cx, |buffer, cx| buffer.set_text(text.as_str(), cx));
+                this.revision_text = Some(text);
+                this.update_diff(cx);
+                cx.notify();
+            })
+            .ok();
+        });
+        cx.notify();
+    }
+
+    /// Diffs the selected revision against the current version of the notes.
+    fn update_diff(&mut self, cx: &mut Context<Self>) {
+        let current_text = self.channel_buffer.read(cx).buffer().read(cx).text();
+        let language = self.revision_buffer.read(cx).language().cloned();
+        let revision_snapshot = self.revision_buffer.read(cx).text_snapshot();
+        let update = self.diff.update(cx, |diff, cx| {
+            diff.set_base_text(Some(current_text.into()), language, revision_snapshot, cx)
+        });
+        self.update_diff_task = cx.background_spawn(async move {
+            update.await.ok();
+        });
+    }
+
+    fn restore_selected_revision(&mut self, cx: &mut Context<Self>) {
+        let Some(text) = self.revision_text.clone() else {
+            return;
+        };
+        self.channel_buffer.update(cx, |channel_buffer, cx| {
+            channel_buffer.restore_text(&text, cx);
+        });
+    }
+
+    fn render_revision(&self, ix: usize, cx: &Context<Self>) -> AnyElement {
+        let revision = &self.revisions[ix];
+        let author = revision
+            .user
+            .as_ref()
+            .map_or_else(|| "Unknown".into(), |user| user.github_login.clone());
+        let (relative_timestamp, absolute_timestamp) = revision
+            .timestamp
+            .map(|timestamp| {
+                let now = OffsetDateTime::now_utc();
+                (
+                    time_format::format_localized_timestamp(
+                        timestamp,
+                        now,
+                        self.local_timezone,
+                        time_format::TimestampFormat::Relative,
+                    ),
+                    time_format::format_localized_timestamp(
+                        timestamp,
+                        now,
+                        self.local_timezone,
+                        time_format::TimestampFormat::Absolute,
+                    ),
+                )
+            })
+            .unzip();
+
+        ListItem::new(ix)
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(self.selected_revision == Some(ix))
+            .start_slot::<Avatar>(
+                revision
+                    .user
+                    .as_ref()
+                    .map(|user| Avatar::new(user.avatar_uri.clone())),
+            )
+            .child(
+                v_flex()
+                    .child(Label::new(author))
+                    .children(relative_timestamp.map(|timestamp| {
+                        Label::new(timestamp)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                    })),
+            )
+            .when_some(absolute_timestamp, |item, timestamp| {
+                item.tooltip(Tooltip::text(timestamp))
+            })
+            .on_click(cx.listener(move |this, _, _, cx| this.select_revision(ix, cx)))
+            .into_any_element()
+    }
+}
+
+impl EventEmitter<EditorEvent> for ChannelNotesHistory {}
+
+impl Focusable for ChannelNotesHistory {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for ChannelNotesHistory {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let revision_count = self.revisions.len();
+        h_flex()
+            .size_full()
+            .track_focus(&self.focus_handle)
+            .child(
+                v_flex()
+                    .w(rems(16.))
+                    .h_full()
+                    .border_r_1()
+                    .border_color(cx.theme().colors().border)
+                    .when(revision_count == 0, |this| {
+                        this.child(
+                            div()
+                                .p_2()
+                                .child(Label::new("No history yet").color(Color::Muted)),
+                        )
+                    })
+                    .child(
+                        // Newest revisions are listed first.
+                        uniform_list(
+                            "channel-notes-revisions",
+                            revision_count,
+                            cx.processor(move |this, range: std::ops::Range<usize>, _, cx| {
+                                range
+                                    .map(|ix| this.render_revision(revision_count - 1 - ix, cx))
+                                    .collect()
+                            }),
+                        )
+                        .flex_grow()
+                        .track_scroll(&self.scroll_handle),
+                    ),
+            )
+            .child(
+                v_flex()
+                    .size_full()
+                    .child(
+                        h_flex()
+                            .p_1()
+                            .justify_end()
+                            .border_b_1()
+                            .border_color(cx.theme().colors().border)
+                            .child(
+                                Button::new("restore-revision", "Restore This Version")
+                                    .disabled(
+                                        self.revision_text.is_none()
+                                            || self
+                                                .channel_buffer
+                                                .read(cx)
+                                                .buffer()
+                                                .read(cx)
+                                                .read_only(),
+                                    )
+                                    .tooltip(Tooltip::text(
+                                        "Replace the notes with this version, as a new edit",
+                                    ))
+                                    .on_click(cx.listener(|this, _, _, cx| {
+                                        this.restore_selected_revision(cx)
+                                    })),
+                            ),
+                    )
+                    .child(self.editor.clone()),
+            )
+    }
+}
+
+impl Item for ChannelNotesHistory {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _: &Window, _: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun).color(Color::Muted))
+    }
+
+    fn tab_content_text(&self, _detail: usize, cx: &App) -> SharedString {
+        match self.channel_buffer.read(cx).channel(cx) {
+            Some(channel) => format!("{} - history", channel.name).into(),
+            None => "Channel notes history".into(),
+        }
+    }
+
+    fn tab_content(&self, params: TabContentParams, _: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(0, cx))
+            .color(params.text_color())
+            .into_any_element()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn to_item_events(event: &EditorEvent, f: &mut dyn FnMut(workspace::item::ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+}
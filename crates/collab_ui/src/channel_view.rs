@@ -1,6 +1,7 @@
+use crate::channel_notes_history::ChannelNotesHistory;
 use anyhow::Result;
 use call::ActiveCall;
-use channel::{Channel, ChannelBuffer, ChannelBufferEvent, ChannelStore};
+use channel::{Channel, ChannelBuffer, ChannelBufferEvent, ChannelBufferSyncStatus, ChannelStore};
 use client::{
     ChannelId, Collaborator, ParticipantIndex,
     proto::{self, PeerId},
@@ -34,7 +35,9 @@ actions!(
     collab,
     [
         /// Copies a link to the current position in the channel buffer.
-        CopyLink
+        CopyLink,
+        /// Shows the history of the channel notes.
+        ShowHistory
     ]
 );
 
@@ -208,11 +211,18 @@ impl ChannelView {
             editor.set_custom_context_menu(move |_, position, window, cx| {
                 let this = this.clone();
                 Some(ui::ContextMenu::build(window, cx, move |menu, _, _| {
-                    menu.entry("Copy link to section", None, move |window, cx| {
-                        this.update(cx, |this, cx| {
-                            this.copy_link_for_position(position, window, cx)
-                        })
-                        .ok();
+                    menu.entry("Copy link to section", None, {
+                        let this = this.clone();
+                        move |window, cx| {
+                            this.update(cx, |this, cx| {
+                                this.copy_link_for_position(position, window, cx)
+                            })
+                            .ok();
+                        }
+                    })
+                    .entry("View History", None, move |window, cx| {
+                        this.update(cx, |this, cx| this.show_history(&ShowHistory, window, cx))
+                            .ok();
                     })
                 }))
             });
@@ -296,6 +306,15 @@ impl ChannelView {
         self.copy_link_for_position(position, window, cx)
     }
 
+    fn show_history(&mut self, _: &ShowHistory, window: &mut Window, cx: &mut Context<Self>) {
+        let channel_buffer = self.channel_buffer.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                ChannelNotesHistory::open(channel_buffer, workspace, window, cx)
+            })
+            .ok();
+    }
+
     fn copy_link_for_position(
         &self,
         position: DisplayPoint,
@@ -350,15 +369,27 @@ impl ChannelView {
         cx: &mut Context<Self>,
     ) {
         match event {
-            ChannelBufferEvent::Disconnected => self.editor.update(cx, |editor, cx| {
-                editor.set_read_only(true);
-                cx.notify();
-            }),
             ChannelBufferEvent::Connected => self.editor.update(cx, |editor, cx| {
                 editor.set_read_only(false);
+                cx.emit(editor::EditorEvent::TitleChanged);
                 cx.notify();
             }),
-            ChannelBufferEvent::ChannelChanged => {
+            ChannelBufferEvent::RejoinFailed => {
+                // Reopen the notes in place of this view, which merges any edits made
+                // while offline into the server's version.
+                let channel_id = self.channel_buffer.read(cx).channel_id;
+                let Some(workspace) = self.workspace.upgrade() else {
+                    return;
+                };
+                let Some(pane) = workspace.read(cx).pane_for(&cx.entity()) else {
+                    return;
+                };
+                Self::open_in_pane(channel_id, None, pane, workspace, window, cx)
+                    .detach_and_log_err(cx);
+            }
+            ChannelBufferEvent::Disconnected
+            | ChannelBufferEvent::SyncStatusChanged
+            | ChannelBufferEvent::ChannelChanged => {
                 self.editor.update(cx, |_, cx| {
                     cx.emit(editor::EditorEvent::TitleChanged);
                     cx.notify()
@@ -400,13 +431,15 @@ impl ChannelView {
 
     fn get_channel(&self, cx: &App) -> (SharedString, Option<SharedString>) {
         if let Some(channel) = self.channel(cx) {
-            let status = match (
-                self.channel_buffer.read(cx).buffer().read(cx).read_only(),
-                self.channel_buffer.read(cx).is_connected(),
-            ) {
-                (false, true) => None,
-                (true, true) => Some("read-only"),
-                (_, false) => Some("disconnected"),
+            let channel_buffer = self.channel_buffer.read(cx);
+            let status = if channel_buffer.buffer().read(cx).read_only() {
+                Some("read-only")
+            } else {
+                match channel_buffer.sync_status() {
+                    ChannelBufferSyncStatus::Synced => None,
+                    ChannelBufferSyncStatus::Offline => Some("offline"),
+                    ChannelBufferSyncStatus::UnsyncedEdits => Some("offline, edits saved locally"),
+                }
             };
 
             (channel.name.clone(), status.map(Into::into))
@@ -423,6 +456,7 @@ impl Render for ChannelView {
         div()
             .size_full()
             .on_action(cx.listener(Self::copy_link))
+            .on_action(cx.listener(Self::show_history))
             .child(self.editor.clone())
     }
 }
@@ -1,3 +1,4 @@
+pub mod channel_notes_history;
 pub mod channel_view;
 pub mod collab_panel;
 pub mod notification_panel;
@@ -22,7 +22,10 @@ use util::{ResultExt, TryFutureExt};
 
 use crate::{
     transport::{StdioTransport, Transport},
-    types::{CancelledParams, ClientNotification, Notification as _, notifications::Cancelled},
+    types::{
+        CancelledParams, ClientNotification, Notification as _, ProgressToken,
+        notifications::Cancelled,
+    },
 };
 
 const JSON_RPC_VERSION: &str = "2.0";
@@ -52,6 +55,7 @@ pub(crate) struct Client {
     outbound_tx: channel::Sender<String>,
     name: Arc<str>,
     subscription_set: Arc<Mutex<NotificationSubscriptionSet>>,
+    request_handlers: Arc<Mutex<RequestHandlerSet>>,
     response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
     #[allow(clippy::type_complexity)]
     #[allow(dead_code)]
@@ -195,19 +199,21 @@ impl Client {
         let subscription_set = Arc::new(Mutex::new(NotificationSubscriptionSet::default()));
         let response_handlers =
             Arc::new(Mutex::new(Some(HashMap::<_, ResponseHandler>::default())));
-        let request_handlers = Arc::new(Mutex::new(HashMap::<_, RequestHandler>::default()));
+        let request_handlers = Arc::new(Mutex::new(RequestHandlerSet::default()));
 
         let receive_input_task = cx.spawn({
             let subscription_set = subscription_set.clone();
             let response_handlers = response_handlers.clone();
             let request_handlers = request_handlers.clone();
             let transport = transport.clone();
+            let outbound_tx = outbound_tx.clone();
             async move |cx| {
                 Self::handle_input(
                     transport,
                     subscription_set,
                     request_handlers,
                     response_handlers,
+                    outbound_tx,
                     cx,
                 )
                 .log_err()
@@ -237,6 +243,7 @@ impl Client {
         Ok(Self {
             server_id,
             subscription_set,
+            request_handlers,
             response_handlers,
             name: server_name,
             next_id: Default::default(),
@@ -252,14 +259,15 @@ impl Client {
     /// Handles input from the server's stdout.
     ///
     /// This function continuously reads lines from the provided stdout stream,
-    /// parses them as JSON-RPC responses or notifications, and dispatches them
-    /// to the appropriate handlers. It processes both responses (which are matched
-    /// to pending requests) and notifications (which trigger registered handlers).
+    /// parses them as JSON-RPC requests, responses or notifications, and dispatches
+    /// them to the appropriate handlers. Requests initiated by the server are answered
+    /// by the registered request handlers, or rejected when nobody handles them.
     async fn handle_input(
         transport: Arc<dyn Transport>,
         subscription_set: Arc<Mutex<NotificationSubscriptionSet>>,
-        request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
+        request_handlers: Arc<Mutex<RequestHandlerSet>>,
         response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+        outbound_tx: channel::Sender<String>,
         cx: &mut AsyncApp,
     ) -> anyhow::Result<()> {
         let mut receiver = transport.receive();
@@ -267,13 +275,24 @@ impl Client {
         while let Some(message) = receiver.next().await {
             log::trace!("recv: {}", &message);
             if let Ok(request) = serde_json::from_str::<AnyRequest>(&message) {
-                let mut request_handlers = request_handlers.lock();
-                if let Some(handler) = request_handlers.get_mut(request.method) {
-                    handler(
-                        request.id,
-                        request.params.unwrap_or(RawValue::NULL),
-                        cx.clone(),
+                let handled = request_handlers.lock().handle(
+                    request.method,
+                    request.id.clone(),
+                    request.params.unwrap_or(RawValue::NULL),
+                    cx,
+                );
+                if let Err(error) = handled {
+                    log::debug!(
+                        "Unhandled request from context_server: {}: {}",
+                        request.method,
+                        error.message
                     );
+                    let response = serde_json::to_string(&Response::<()> {
+                        jsonrpc: JSON_RPC_VERSION,
+                        id: request.id,
+                        value: CspResult::Error(Some(error)),
+                    })?;
+                    outbound_tx.try_send(response).log_err();
                 }
             } else if let Ok(response) = serde_json::from_str::<AnyResponse>(&message) {
                 if let Some(handlers) = response_handlers.lock().as_mut()
@@ -465,6 +484,64 @@ impl Client {
             set: self.subscription_set.clone(),
         }
     }
+
+    /// Registers a handler for requests sent by the context server to the client.
+    ///
+    /// The task returned by the handler is awaited in the background, and its
+    /// result is sent back to the server as the response to the request. When
+    /// `progress_token` is set, the handler only receives requests the server
+    /// makes on behalf of the client request carrying that token; see
+    /// `RequestHandlerSet::handle`.
+    #[must_use]
+    pub fn on_request<P, R>(
+        &self,
+        method: &'static str,
+        progress_token: Option<ProgressToken>,
+        mut f: impl 'static + Send + FnMut(P, AsyncApp) -> Task<Result<R>>,
+    ) -> RequestSubscription
+    where
+        P: DeserializeOwned,
+        R: Serialize + Send + 'static,
+    {
+        let outbound_tx = self.outbound_tx.clone();
+        let handler: RequestHandler = Box::new(move |id, params, cx| {
+            let task = serde_json::from_str::<P>(params.get()).map(|params| f(params, cx.clone()));
+            let outbound_tx = outbound_tx.clone();
+            cx.background_spawn(
+                async move {
+                    let value = match task {
+                        Ok(task) => match task.await {
+                            Ok(result) => CspResult::Ok(Some(result)),
+                            Err(error) => CspResult::Error(Some(Error {
+                                message: error.to_string(),
+                                code: INTERNAL_ERROR,
+                            })),
+                        },
+                        Err(error) => CspResult::Error(Some(Error {
+                            message: error.to_string(),
+                            code: INVALID_PARAMS,
+                        })),
+                    };
+                    let response = serde_json::to_string(&Response {
+                        jsonrpc: JSON_RPC_VERSION,
+                        id,
+                        value,
+                    })?;
+                    outbound_tx
+                        .try_send(response)
+                        .context("failed to write to context server's stdin")
+                }
+                .log_err(),
+            )
+            .detach();
+        });
+
+        let mut request_handlers = self.request_handlers.lock();
+        RequestSubscription {
+            id: request_handlers.add_handler(method, progress_token, handler),
+            set: self.request_handlers.clone(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -562,3 +639,110 @@ impl Drop for NotificationSubscription {
         });
     }
 }
+
+slotmap::new_key_type! {
+    struct RequestSubscriptionId;
+}
+
+#[derive(Default)]
+pub struct RequestHandlerSet {
+    methods: Vec<(&'static str, Vec<RequestSubscriptionId>)>,
+    handlers: SlotMap<RequestSubscriptionId, (Option<ProgressToken>, RequestHandler)>,
+}
+
+impl RequestHandlerSet {
+    #[must_use]
+    fn add_handler(
+        &mut self,
+        method: &'static str,
+        progress_token: Option<ProgressToken>,
+        handler: RequestHandler,
+    ) -> RequestSubscriptionId {
+        let id = self.handlers.insert((progress_token, handler));
+        if let Some((_, handler_ids)) = self
+            .methods
+            .iter_mut()
+            .find(|(probe_method, _)| method == *probe_method)
+        {
+            handler_ids.push(id);
+        } else {
+            self.methods.push((method, vec![id]));
+        };
+        id
+    }
+
+    /// Dispatches a request to one of the handlers registered for its method.
+    ///
+    /// A request whose `_meta.progressToken` matches the token of a handler goes to
+    /// that handler. Otherwise it goes to the most recently registered handler that
+    /// isn't tied to a progress token, or to the only handler registered for the
+    /// method. Requests that can't be attributed to a single handler are rejected
+    /// rather than guessed, so that one tool call never answers another's request.
+    fn handle(
+        &mut self,
+        method: &str,
+        id: RequestId,
+        params: &RawValue,
+        cx: &mut AsyncApp,
+    ) -> Result<(), Error> {
+        let handler_ids = self
+            .methods
+            .iter()
+            .find(|(probe_method, _)| method == *probe_method)
+            .map(|(_, handler_ids)| handler_ids.as_slice())
+            .unwrap_or_default();
+        if handler_ids.is_empty() {
+            return Err(Error {
+                message: format!("Method not found: {method}"),
+                code: METHOD_NOT_FOUND,
+            });
+        }
+
+        let progress_token = serde_json::from_str::<Value>(params.get())
+            .ok()
+            .and_then(|params| params.pointer("/_meta/progressToken").cloned())
+            .and_then(|token| serde_json::from_value::<ProgressToken>(token).ok());
+        let handler_with_token = |token: Option<&ProgressToken>| {
+            handler_ids.iter().rev().copied().find(|handler_id| {
+                self.handlers
+                    .get(*handler_id)
+                    .is_some_and(|(handler_token, _)| handler_token.as_ref() == token)
+            })
+        };
+        let handler_id = progress_token
+            .as_ref()
+            .and_then(|token| handler_with_token(Some(token)))
+            .or_else(|| handler_with_token(None))
+            .or_else(|| match handler_ids {
+                [handler_id] if progress_token.is_none() => Some(*handler_id),
+                _ => None,
+            });
+
+        let Some((_, handler)) =
+            handler_id.and_then(|handler_id| self.handlers.get_mut(handler_id))
+        else {
+            return Err(Error {
+                message: format!("Cannot tell which request {method} was sent for"),
+                code: INVALID_REQUEST,
+            });
+        };
+        handler(id, params, cx.clone());
+        Ok(())
+    }
+}
+
+pub struct RequestSubscription {
+    id: RequestSubscriptionId,
+    set: Arc<Mutex<RequestHandlerSet>>,
+}
+
+impl Drop for RequestSubscription {
+    fn drop(&mut self) {
+        let mut set = self.set.lock();
+        set.handlers.remove(self.id);
+        set.methods.retain_mut(|(_, handler_ids)| {
+            handler_ids.retain(|id| *id != self.id);
+            !handler_ids.is_empty()
+        });
+    }
+}
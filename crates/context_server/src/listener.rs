@@ -1,9 +1,12 @@
 use ::serde::{Deserialize, Serialize};
-use anyhow::{Context as _, Result};
+use anyhow::{Context as _, Result, anyhow};
 use collections::HashMap;
 use futures::{
     AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, FutureExt,
-    channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded},
+    channel::{
+        mpsc::{UnboundedReceiver, UnboundedSender, unbounded},
+        oneshot,
+    },
     io::BufReader,
     select_biased,
 };
@@ -16,8 +19,12 @@ use smol::stream::StreamExt;
 use std::{
     any::TypeId,
     cell::RefCell,
+    io::Read as _,
+    net::SocketAddr,
     path::{Path, PathBuf},
     rc::Rc,
+    sync::Arc,
+    thread,
 };
 use util::ResultExt;
 
@@ -202,12 +209,69 @@ impl McpServer {
         cx: &mut AsyncApp,
     ) {
         let (read, write) = smol::io::split(stream);
-        let (incoming_tx, mut incoming_rx) = unbounded();
+        let (incoming_tx, incoming_rx) = unbounded();
         let (outgoing_tx, outgoing_rx) = unbounded();
 
         cx.background_spawn(Self::handle_io(outgoing_rx, incoming_tx, write, read))
             .detach();
 
+        Self::serve_requests(incoming_rx, outgoing_tx, tools, handlers, cx);
+    }
+
+    /// Opens an in-process connection to the server, for transports other than
+    /// its Unix socket. Each message sent to [`McpConnection::incoming`] is a
+    /// JSON-RPC message, and responses are received from [`McpConnection::outgoing`].
+    pub fn connect(&self, cx: &mut AsyncApp) -> McpConnection {
+        let (incoming_tx, mut incoming_rx) = unbounded::<String>();
+        let (requests_tx, requests_rx) = unbounded();
+        let (outgoing_tx, outgoing_rx) = unbounded();
+
+        cx.background_spawn({
+            let outgoing_tx = outgoing_tx.clone();
+            async move {
+                while let Some(message) = incoming_rx.next().await {
+                    log::trace!("recv: {}", &message);
+                    match serde_json::from_str(&message) {
+                        Ok(request) => {
+                            if requests_tx.unbounded_send(request).is_err() {
+                                break;
+                            }
+                        }
+                        Err(error) => {
+                            outgoing_tx
+                                .unbounded_send(parse_error_message(&error))
+                                .log_err();
+                            log::error!(
+                                "failed to parse incoming message: {error}. Raw: {message}"
+                            );
+                        }
+                    }
+                }
+            }
+        })
+        .detach();
+
+        Self::serve_requests(
+            requests_rx,
+            outgoing_tx,
+            self.tools.clone(),
+            self.handlers.clone(),
+            cx,
+        );
+
+        McpConnection {
+            incoming: incoming_tx,
+            outgoing: outgoing_rx,
+        }
+    }
+
+    fn serve_requests(
+        mut incoming_rx: UnboundedReceiver<RawRequest>,
+        outgoing_tx: UnboundedSender<String>,
+        tools: Rc<RefCell<HashMap<&'static str, RegisteredTool>>>,
+        handlers: Rc<RefCell<HashMap<&'static str, RequestHandler>>>,
+        cx: &mut AsyncApp,
+    ) {
         cx.spawn(async move |cx| {
             while let Some(request) = incoming_rx.next().await {
                 let Some(request_id) = request.id.clone() else {
@@ -378,13 +442,7 @@ impl McpServer {
                             incoming_tx.unbounded_send(message).log_err();
                         }
                         Err(error) => {
-                            outgoing_bytes.write_all(serde_json::to_string(&json!({
-                                "jsonrpc": "2.0",
-                                "error": json!({
-                                    "code": -32603,
-                                    "message": format!("Failed to parse: {error}"),
-                                }),
-                            }))?.as_bytes()).await?;
+                            outgoing_bytes.write_all(parse_error_message(&error).as_bytes()).await?;
                             outgoing_bytes.write_all(&[b'\n']).await?;
                             log::error!("failed to parse incoming message: {error}. Raw: {incoming_line}");
                         }
@@ -397,6 +455,257 @@ impl McpServer {
     }
 }
 
+/// An in-process connection to an [`McpServer`].
+pub struct McpConnection {
+    pub incoming: UnboundedSender<String>,
+    pub outgoing: UnboundedReceiver<String>,
+}
+
+fn parse_error_message(error: &serde_json::Error) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "error": {
+            "code": -32603,
+            "message": format!("Failed to parse: {error}"),
+        },
+    })
+    .to_string()
+}
+
+/// Serves an [`McpConnection`] over HTTP, following the "Streamable HTTP"
+/// transport without server-sent events: each POST request carries a single
+/// JSON-RPC message, and the response to it is returned in the body.
+///
+/// Requests must carry the server's token as a bearer token, and must come from
+/// a local origin, so that web pages can't reach the server through DNS rebinding.
+pub struct McpHttpServer {
+    address: SocketAddr,
+    token: String,
+    server: Arc<tiny_http::Server>,
+    _dispatch_task: Task<()>,
+}
+
+impl McpHttpServer {
+    pub fn new(connection: McpConnection, port: u16, cx: &AsyncApp) -> Result<Self> {
+        let server = Arc::new(
+            tiny_http::Server::http(("127.0.0.1", port))
+                .map_err(|error| anyhow!("failed to start MCP HTTP server: {error}"))?,
+        );
+        let address = server.server_addr();
+        let token = uuid::Uuid::new_v4().simple().to_string();
+
+        let (requests_tx, requests_rx) = unbounded::<(String, oneshot::Sender<Option<String>>)>();
+        thread::Builder::new()
+            .name("McpHttpServer".to_string())
+            .spawn({
+                let server = server.clone();
+                let token = token.clone();
+                move || Self::accept_requests(&server, &token, requests_tx)
+            })?;
+
+        let dispatch_task = cx.background_spawn(Self::dispatch(connection, requests_rx));
+        Ok(Self {
+            address,
+            token,
+            server,
+            _dispatch_task: dispatch_task,
+        })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// The bearer token that clients must send in the `Authorization` header.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    fn accept_requests(
+        server: &tiny_http::Server,
+        token: &str,
+        requests_tx: UnboundedSender<(String, oneshot::Sender<Option<String>>)>,
+    ) {
+        for mut request in server.incoming_requests() {
+            if let Err(status) = Self::authorize(&request, token) {
+                request
+                    .respond(tiny_http::Response::empty(status))
+                    .log_err();
+                continue;
+            }
+            if *request.method() != tiny_http::Method::Post {
+                request.respond(tiny_http::Response::empty(405)).log_err();
+                continue;
+            }
+
+            let mut body = String::new();
+            if let Err(error) = request.as_reader().read_to_string(&mut body) {
+                request
+                    .respond(
+                        tiny_http::Response::from_string(error.to_string()).with_status_code(400),
+                    )
+                    .log_err();
+                continue;
+            }
+
+            let (response_tx, response_rx) = oneshot::channel();
+            if requests_tx.unbounded_send((body, response_tx)).is_err() {
+                break;
+            }
+
+            // Respond on a separate thread, so that slow requests don't block
+            // the ones that follow.
+            thread::spawn(move || match smol::block_on(response_rx) {
+                Ok(Some(response)) => {
+                    let content_type =
+                        tiny_http::Header::from_bytes("Content-Type", "application/json").unwrap();
+                    request
+                        .respond(
+                            tiny_http::Response::from_string(response).with_header(content_type),
+                        )
+                        .log_err();
+                }
+                Ok(None) => {
+                    request.respond(tiny_http::Response::empty(202)).log_err();
+                }
+                Err(_) => {
+                    request.respond(tiny_http::Response::empty(500)).log_err();
+                }
+            });
+        }
+    }
+
+    fn authorize(request: &tiny_http::Request, token: &str) -> Result<(), u16> {
+        let header = |name: &'static str| {
+            request
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv(name))
+                .map(|header| header.value.as_str())
+        };
+        authorize_http_request(
+            header("Host"),
+            header("Origin"),
+            header("Authorization"),
+            token,
+        )
+    }
+
+    /// Forwards requests from HTTP clients to the connection, and routes each
+    /// response back to the client that sent the request.
+    ///
+    /// Clients pick their request ids independently, so two of them may use the same
+    /// id at once. Requests are given ids of the server's own before they are sent,
+    /// and responses get the client's id back before they are returned.
+    async fn dispatch(
+        mut connection: McpConnection,
+        mut requests_rx: UnboundedReceiver<(String, oneshot::Sender<Option<String>>)>,
+    ) {
+        let mut next_id = 0;
+        let mut pending_responses =
+            HashMap::<u64, (serde_json::Value, oneshot::Sender<Option<String>>)>::default();
+        loop {
+            select_biased! {
+                message = connection.outgoing.next().fuse() => {
+                    let Some(message) = message else {
+                        break;
+                    };
+                    let Ok(mut message) = serde_json::from_str::<serde_json::Value>(&message)
+                    else {
+                        log::debug!("dropping malformed MCP message: {message}");
+                        continue;
+                    };
+                    let pending_response = message
+                        .get("id")
+                        .and_then(|id| id.as_u64())
+                        .and_then(|id| pending_responses.remove(&id));
+                    match pending_response {
+                        Some((client_id, response_tx)) => {
+                            message["id"] = client_id;
+                            response_tx.send(Some(message.to_string())).ok();
+                        }
+                        None => {
+                            log::debug!("dropping MCP message without a pending request: {message}")
+                        }
+                    }
+                }
+                request = requests_rx.next().fuse() => {
+                    let Some((body, response_tx)) = request else {
+                        break;
+                    };
+                    let mut message = match serde_json::from_str::<serde_json::Value>(&body) {
+                        Ok(message) => message,
+                        Err(error) => {
+                            response_tx.send(Some(parse_error_message(&error))).ok();
+                            continue;
+                        }
+                    };
+                    // Notifications and responses don't get a response.
+                    let client_id = message
+                        .get("id")
+                        .filter(|_| message.get("method").is_some())
+                        .cloned();
+                    match client_id {
+                        Some(client_id) => {
+                            let id = next_id;
+                            next_id += 1;
+                            message["id"] = json!(id);
+                            pending_responses.insert(id, (client_id, response_tx));
+                        }
+                        None => {
+                            response_tx.send(None).ok();
+                        }
+                    }
+                    if connection.incoming.unbounded_send(message.to_string()).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Drop for McpHttpServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+    }
+}
+
+/// Returns the HTTP status to respond with when a request doesn't come from a
+/// local client that knows the token.
+fn authorize_http_request(
+    host: Option<&str>,
+    origin: Option<&str>,
+    authorization: Option<&str>,
+    token: &str,
+) -> Result<(), u16> {
+    if !host.is_some_and(is_local_host) {
+        return Err(403);
+    }
+    if origin.is_some_and(|origin| !is_local_origin(origin)) {
+        return Err(403);
+    }
+    if authorization.and_then(|value| value.strip_prefix("Bearer ")) != Some(token) {
+        return Err(401);
+    }
+    Ok(())
+}
+
+fn is_local_host(host: &str) -> bool {
+    let host = match host.rsplit_once(':') {
+        Some((host, port)) if port.bytes().all(|byte| byte.is_ascii_digit()) => host,
+        _ => host,
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+fn is_local_origin(origin: &str) -> bool {
+    origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+        .is_some_and(is_local_host)
+}
+
 pub trait McpServerTool {
     type Input: DeserializeOwned + JsonSchema;
     type Output: Serialize + JsonSchema;
@@ -434,3 +743,121 @@ struct RawRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     params: Option<Box<serde_json::value::RawValue>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_http_dispatch_with_clashing_request_ids(cx: &mut gpui::TestAppContext) {
+        let (incoming_tx, mut incoming_rx) = unbounded::<String>();
+        let (outgoing_tx, outgoing_rx) = unbounded::<String>();
+        let (requests_tx, requests_rx) = unbounded();
+        let _dispatch_task = cx.background_spawn(McpHttpServer::dispatch(
+            McpConnection {
+                incoming: incoming_tx,
+                outgoing: outgoing_rx,
+            },
+            requests_rx,
+        ));
+
+        // Two clients send a request with the same id.
+        let mut response_rxs = Vec::new();
+        for method in ["tools/list", "ping"] {
+            let (response_tx, response_rx) = oneshot::channel();
+            requests_tx
+                .unbounded_send((
+                    json!({"jsonrpc": "2.0", "id": 1, "method": method}).to_string(),
+                    response_tx,
+                ))
+                .unwrap();
+            response_rxs.push(response_rx);
+        }
+
+        let mut forwarded = Vec::new();
+        for _ in 0..2 {
+            let message = incoming_rx.next().await.unwrap();
+            forwarded.push(serde_json::from_str::<serde_json::Value>(&message).unwrap());
+        }
+        assert_ne!(forwarded[0]["id"], forwarded[1]["id"]);
+
+        // Answer them in reverse order.
+        for request in forwarded.iter().rev() {
+            outgoing_tx
+                .unbounded_send(
+                    json!({"jsonrpc": "2.0", "id": request["id"], "result": request["method"]})
+                        .to_string(),
+                )
+                .unwrap();
+        }
+
+        for (response_rx, method) in response_rxs.into_iter().zip(["tools/list", "ping"]) {
+            let response = response_rx.await.unwrap().unwrap();
+            assert_eq!(
+                serde_json::from_str::<serde_json::Value>(&response).unwrap(),
+                json!({"jsonrpc": "2.0", "id": 1, "result": method})
+            );
+        }
+    }
+
+    #[test]
+    fn test_authorize_http_request() {
+        let token = "secret";
+        let bearer = Some("Bearer secret");
+        assert_eq!(
+            authorize_http_request(Some("127.0.0.1:8080"), None, bearer, token),
+            Ok(())
+        );
+        assert_eq!(
+            authorize_http_request(
+                Some("localhost:8080"),
+                Some("http://localhost:3000"),
+                bearer,
+                token
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            authorize_http_request(Some("[::1]:8080"), None, bearer, token),
+            Ok(())
+        );
+
+        // DNS rebinding: the request reaches the loopback address under another host name.
+        assert_eq!(
+            authorize_http_request(Some("attacker.example:8080"), None, bearer, token),
+            Err(403)
+        );
+        assert_eq!(authorize_http_request(None, None, bearer, token), Err(403));
+        assert_eq!(
+            authorize_http_request(
+                Some("127.0.0.1:8080"),
+                Some("https://attacker.example"),
+                bearer,
+                token
+            ),
+            Err(403)
+        );
+        assert_eq!(
+            authorize_http_request(
+                Some("127.0.0.1:8080"),
+                Some("http://localhost.attacker.example"),
+                bearer,
+                token
+            ),
+            Err(403)
+        );
+
+        assert_eq!(
+            authorize_http_request(Some("127.0.0.1:8080"), None, None, token),
+            Err(401)
+        );
+        assert_eq!(
+            authorize_http_request(Some("127.0.0.1:8080"), None, Some("Bearer wrong"), token),
+            Err(401)
+        );
+        assert_eq!(
+            authorize_http_request(Some("127.0.0.1:8080"), None, Some("secret"), token),
+            Err(401)
+        );
+    }
+}
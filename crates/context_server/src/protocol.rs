@@ -9,11 +9,11 @@ use std::time::Duration;
 
 use anyhow::Result;
 use futures::channel::oneshot;
-use gpui::AsyncApp;
+use gpui::{AsyncApp, Task};
 use serde_json::Value;
 
-use crate::client::{Client, NotificationSubscription};
-use crate::types::{self, Notification, Request};
+use crate::client::{Client, NotificationSubscription, RequestSubscription};
+use crate::types::{self, Notification, ProgressToken, Request};
 
 pub struct ModelContextProtocol {
     inner: Client,
@@ -41,6 +41,7 @@ impl ModelContextProtocol {
                 experimental: None,
                 sampling: None,
                 roots: None,
+                elicitation: Some(serde_json::json!({})),
             },
             meta: None,
             client_info,
@@ -122,4 +123,23 @@ impl InitializedContextServerProtocol {
     ) -> NotificationSubscription {
         self.inner.on_notification(method, f)
     }
+
+    /// Handle requests of type `T` sent by the server, such as `elicitation/create`.
+    /// The handler is unregistered when the returned subscription is dropped.
+    pub fn on_request<T: Request>(
+        &self,
+        f: impl 'static + Send + FnMut(T::Params, AsyncApp) -> Task<Result<T::Response>>,
+    ) -> RequestSubscription {
+        self.inner.on_request(T::METHOD, None, f)
+    }
+
+    /// Like [`Self::on_request`], but only handles requests the server sends while
+    /// answering the client request whose `_meta` carries `progress_token`.
+    pub fn on_request_for_progress_token<T: Request>(
+        &self,
+        progress_token: ProgressToken,
+        f: impl 'static + Send + FnMut(T::Params, AsyncApp) -> Task<Result<T::Response>>,
+    ) -> RequestSubscription {
+        self.inner.on_request(T::METHOD, Some(progress_token), f)
+    }
 }
@@ -1,8 +1,17 @@
 use anyhow::Context as _;
 use collections::HashMap;
-use futures::{FutureExt, Stream, StreamExt as _, future::BoxFuture, lock::Mutex};
+use futures::{
+    FutureExt, Stream, StreamExt as _, channel::oneshot, future::BoxFuture, lock::Mutex,
+};
 use gpui::BackgroundExecutor;
-use std::{pin::Pin, sync::Arc};
+use std::{
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicI32, Ordering::SeqCst},
+    },
+};
+use util::ResultExt as _;
 
 use crate::{
     transport::Transport,
@@ -41,20 +50,83 @@ pub struct FakeTransport {
     >,
     tx: futures::channel::mpsc::UnboundedSender<String>,
     rx: Arc<Mutex<futures::channel::mpsc::UnboundedReceiver<String>>>,
+    server_requests: FakeServerRequests,
     executor: BackgroundExecutor,
 }
 
+/// Sends requests from the fake server to the client, like `elicitation/create`.
+#[derive(Clone)]
+pub struct FakeServerRequests {
+    tx: futures::channel::mpsc::UnboundedSender<String>,
+    next_id: Arc<AtomicI32>,
+    pending_responses: Arc<parking_lot::Mutex<HashMap<i64, oneshot::Sender<serde_json::Value>>>>,
+}
+
+impl FakeServerRequests {
+    pub fn request<T: crate::types::Request>(
+        &self,
+        params: T::Params,
+    ) -> BoxFuture<'static, anyhow::Result<T::Response>> {
+        let id = self.next_id.fetch_add(1, SeqCst);
+        let (response_tx, response_rx) = oneshot::channel();
+        self.pending_responses.lock().insert(id as i64, response_tx);
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": T::METHOD,
+            "params": params,
+        });
+        let sent = self
+            .tx
+            .unbounded_send(request.to_string())
+            .context("sending a request");
+        async move {
+            sent?;
+            let response = response_rx.await?;
+            if let Some(error) = response.get("error") {
+                anyhow::bail!("request failed: {error}");
+            }
+            let result = response
+                .get("result")
+                .cloned()
+                .unwrap_or(serde_json::Value::Null);
+            Ok(serde_json::from_value(result)?)
+        }
+        .boxed()
+    }
+
+    fn handle_response(&self, message: &serde_json::Value) -> bool {
+        let Some(id) = message.get("id").and_then(|id| id.as_i64()) else {
+            return false;
+        };
+        let Some(response_tx) = self.pending_responses.lock().remove(&id) else {
+            return false;
+        };
+        response_tx.send(message.clone()).ok();
+        true
+    }
+}
+
 impl FakeTransport {
     pub fn new(executor: BackgroundExecutor) -> Self {
         let (tx, rx) = futures::channel::mpsc::unbounded();
         Self {
             request_handlers: Default::default(),
+            server_requests: FakeServerRequests {
+                tx: tx.clone(),
+                next_id: Default::default(),
+                pending_responses: Default::default(),
+            },
             tx,
             rx: Arc::new(Mutex::new(rx)),
             executor,
         }
     }
 
+    pub fn server_requests(&self) -> FakeServerRequests {
+        self.server_requests.clone()
+    }
+
     pub fn on_request<T, Fut>(
         mut self,
         handler: impl 'static + Send + Sync + Fn(T::Params) -> Fut,
@@ -88,19 +160,28 @@ impl Transport for FakeTransport {
 
             if let Some(method) = msg.get("method") {
                 let method = method.as_str().expect("Invalid method received");
-                if let Some(handler) = self.request_handlers.get(method) {
-                    let payload = handler(msg).await;
-                    let response = serde_json::json!({
-                        "jsonrpc": "2.0",
-                        "id": id,
-                        "result": payload
-                    });
-                    self.tx
-                        .unbounded_send(response.to_string())
-                        .context("sending a message")?;
+                if let Some(handler) = self.request_handlers.get(method).cloned() {
+                    // Handle requests concurrently, so that a pending request doesn't
+                    // block the client from answering requests initiated by the server.
+                    let tx = self.tx.clone();
+                    self.executor
+                        .spawn(async move {
+                            let payload = handler(msg).await;
+                            let response = serde_json::json!({
+                                "jsonrpc": "2.0",
+                                "id": id,
+                                "result": payload
+                            });
+                            tx.unbounded_send(response.to_string())
+                                .context("sending a message")
+                                .log_err();
+                        })
+                        .detach();
                 } else {
                     log::debug!("No handler registered for MCP request '{method}'");
                 }
+            } else if !self.server_requests.handle_response(&msg) {
+                log::debug!("Unexpected MCP response: {message}");
             }
         }
         Ok(())
@@ -1,4 +1,5 @@
-use collections::HashMap;
+use anyhow::{Result, anyhow};
+use collections::{HashMap, IndexMap};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -77,6 +78,12 @@ pub mod requests {
         ListResourceTemplatesResponse
     );
     request!("roots/list", ListRoots, (), ListRootsResponse);
+    request!(
+        "elicitation/create",
+        ElicitationCreate,
+        ElicitRequestParams,
+        ElicitResult
+    );
 }
 
 pub trait Request {
@@ -233,7 +240,8 @@ pub struct PromptReference {
 pub struct ResourceReference {
     #[serde(rename = "type")]
     pub ty: PromptReferenceType,
-    pub uri: Url,
+    /// The URI, or URI template, of the resource.
+    pub uri: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -446,6 +454,8 @@ pub struct ClientCapabilities {
     pub sampling: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub roots: Option<RootsCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elicitation: Option<serde_json::Value>,
 }
 
 #[derive(Default, Debug, Serialize, Deserialize)]
@@ -633,7 +643,7 @@ pub struct CancelledParams {
     pub reason: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ProgressToken {
     String(String),
@@ -756,3 +766,248 @@ pub struct Root {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitRequestParams {
+    pub message: String,
+    pub requested_schema: ElicitationSchema,
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<HashMap<String, serde_json::Value>>,
+}
+
+/// The restricted subset of JSON Schema servers use to describe the input they
+/// request: a flat object whose properties are all primitive values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitationSchema {
+    #[serde(default)]
+    pub properties: IndexMap<String, PrimitiveSchemaDefinition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required: Vec<String>,
+}
+
+impl ElicitationSchema {
+    pub fn is_required(&self, property: &str) -> bool {
+        self.required.iter().any(|required| required == property)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrimitiveSchemaDefinition {
+    #[serde(rename = "type")]
+    pub ty: PrimitiveSchemaType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<f64>,
+    /// The allowed values of an enum property, which has type `string`.
+    #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
+    pub enum_values: Option<Vec<String>>,
+    /// Display names for `enum_values`, in the same order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enum_names: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrimitiveSchemaType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+}
+
+impl PrimitiveSchemaDefinition {
+    /// Converts text entered by the user into a value matching this definition.
+    pub fn parse_text(&self, text: &str) -> Result<serde_json::Value> {
+        match self.ty {
+            PrimitiveSchemaType::String => {
+                let len = text.chars().count();
+                if let Some(min_length) = self.min_length
+                    && len < min_length
+                {
+                    return Err(anyhow!("must be at least {min_length} characters"));
+                }
+                if let Some(max_length) = self.max_length
+                    && len > max_length
+                {
+                    return Err(anyhow!("must be at most {max_length} characters"));
+                }
+                if let Some(enum_values) = &self.enum_values
+                    && !enum_values.iter().any(|value| value == text)
+                {
+                    return Err(anyhow!("must be one of {}", enum_values.join(", ")));
+                }
+                Ok(serde_json::Value::String(text.to_string()))
+            }
+            PrimitiveSchemaType::Number | PrimitiveSchemaType::Integer => {
+                let number = text
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| anyhow!("must be a number"))?;
+                if let Some(minimum) = self.minimum
+                    && number < minimum
+                {
+                    return Err(anyhow!("must be at least {minimum}"));
+                }
+                if let Some(maximum) = self.maximum
+                    && number > maximum
+                {
+                    return Err(anyhow!("must be at most {maximum}"));
+                }
+                if self.ty == PrimitiveSchemaType::Integer {
+                    if number.fract() != 0.0 {
+                        return Err(anyhow!("must be a whole number"));
+                    }
+                    Ok(serde_json::Value::from(number as i64))
+                } else {
+                    Ok(serde_json::Value::from(number))
+                }
+            }
+            PrimitiveSchemaType::Boolean => match text.trim() {
+                "true" => Ok(serde_json::Value::Bool(true)),
+                "false" => Ok(serde_json::Value::Bool(false)),
+                _ => Err(anyhow!("must be true or false")),
+            },
+        }
+    }
+
+    /// Returns the allowed values of an enum property paired with their display names.
+    pub fn enum_options(&self) -> Option<impl Iterator<Item = (&str, &str)>> {
+        let values = self.enum_values.as_ref()?;
+        let names = self.enum_names.as_deref().unwrap_or_default();
+        Some(values.iter().enumerate().map(move |(ix, value)| {
+            let name = names.get(ix).unwrap_or(value);
+            (value.as_str(), name.as_str())
+        }))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitResult {
+    pub action: ElicitationAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<serde_json::Map<String, serde_json::Value>>,
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<HashMap<String, serde_json::Value>>,
+}
+
+impl ElicitResult {
+    pub fn accept(content: serde_json::Map<String, serde_json::Value>) -> Self {
+        Self {
+            action: ElicitationAction::Accept,
+            content: Some(content),
+            meta: None,
+        }
+    }
+
+    pub fn decline() -> Self {
+        Self {
+            action: ElicitationAction::Decline,
+            content: None,
+            meta: None,
+        }
+    }
+
+    pub fn cancel() -> Self {
+        Self {
+            action: ElicitationAction::Cancel,
+            content: None,
+            meta: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ElicitationAction {
+    Accept,
+    Decline,
+    Cancel,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_elicitation_schema() {
+        let params: ElicitRequestParams = serde_json::from_value(json!({
+            "message": "Configure the deployment",
+            "requestedSchema": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "title": "Name", "minLength": 2 },
+                    "replicas": { "type": "integer", "minimum": 1, "maximum": 5 },
+                    "region": {
+                        "type": "string",
+                        "enum": ["us", "eu"],
+                        "enumNames": ["United States", "Europe"]
+                    },
+                    "confirm": { "type": "boolean", "default": false }
+                },
+                "required": ["name", "region"]
+            }
+        }))
+        .unwrap();
+
+        let schema = &params.requested_schema;
+        assert_eq!(
+            schema.properties.keys().collect::<Vec<_>>(),
+            ["name", "replicas", "region", "confirm"]
+        );
+        assert!(schema.is_required("name"));
+        assert!(!schema.is_required("replicas"));
+
+        let name = &schema.properties["name"];
+        assert_eq!(name.parse_text("Zed").unwrap(), json!("Zed"));
+        assert!(name.parse_text("Z").is_err());
+
+        let replicas = &schema.properties["replicas"];
+        assert_eq!(replicas.parse_text("3").unwrap(), json!(3));
+        assert!(replicas.parse_text("2.5").is_err());
+        assert!(replicas.parse_text("10").is_err());
+        assert!(replicas.parse_text("many").is_err());
+
+        let region = &schema.properties["region"];
+        assert_eq!(
+            region.enum_options().unwrap().collect::<Vec<_>>(),
+            [("us", "United States"), ("eu", "Europe")]
+        );
+        assert!(region.parse_text("asia").is_err());
+
+        let confirm = &schema.properties["confirm"];
+        assert_eq!(confirm.ty, PrimitiveSchemaType::Boolean);
+        assert_eq!(confirm.parse_text("true").unwrap(), json!(true));
+    }
+
+    #[test]
+    fn test_serialize_elicit_result() {
+        let mut content = serde_json::Map::new();
+        content.insert("name".into(), json!("Zed"));
+        assert_eq!(
+            serde_json::to_value(ElicitResult::accept(content)).unwrap(),
+            json!({ "action": "accept", "content": { "name": "Zed" } })
+        );
+        assert_eq!(
+            serde_json::to_value(ElicitResult::decline()).unwrap(),
+            json!({ "action": "decline" })
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use crate::{
     adapters::DebugAdapterBinary,
+    debugger_settings::DebuggerSettings,
+    recording::DapRecorder,
     transport::{IoKind, LogKind, TransportDelegate},
 };
 use anyhow::Result;
@@ -9,10 +11,15 @@ use dap_types::{
 };
 use futures::channel::oneshot;
 use gpui::AsyncApp;
+use settings::Settings as _;
 use std::{
     hash::Hash,
-    sync::atomic::{AtomicU64, Ordering},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
 };
+use util::ResultExt as _;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
@@ -69,7 +76,23 @@ impl DebugAdapterClient {
         message_handler: DapMessageHandler,
         cx: &mut AsyncApp,
     ) -> Result<()> {
-        self.transport_delegate.connect(message_handler, cx).await
+        let recorder = if cx.update(|cx| DebuggerSettings::get_global(cx).record_sessions) {
+            DapRecorder::for_session(self.id)
+                .inspect(|recorder| {
+                    log::info!(
+                        "recording debug session {} to {}",
+                        self.id.0,
+                        recorder.path().display()
+                    )
+                })
+                .log_err()
+                .map(Arc::new)
+        } else {
+            None
+        };
+        self.transport_delegate
+            .connect(message_handler, recorder, cx)
+            .await
     }
 
     pub async fn create_child_connection(
@@ -205,6 +228,25 @@ impl DebugAdapterClient {
             });
     }
 
+    /// Answers requests with the adapter's messages from a recorded session.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn replay(&self, trace: Vec<crate::recording::DapTraceEntry>) {
+        self.transport_delegate
+            .transport
+            .lock()
+            .as_fake()
+            .replay(trace);
+    }
+
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn replay_finished(&self) -> bool {
+        self.transport_delegate
+            .transport
+            .lock()
+            .as_fake()
+            .replay_finished()
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub fn on_request_ext<R: dap_types::requests::Request, F>(&self, handler: F)
     where
@@ -263,7 +305,7 @@ mod tests {
         Capabilities, InitializeRequestArguments, InitializeRequestArgumentsPathFormat,
         RunInTerminalRequestArguments, StartDebuggingRequestArguments,
         messages::Events,
-        requests::{Initialize, Request, RunInTerminal},
+        requests::{Initialize, Request, RunInTerminal, Threads},
     };
     use gpui::TestAppContext;
     use serde_json::json;
@@ -461,4 +503,88 @@ mod tests {
             "Event handler was not called"
         );
     }
+
+    #[gpui::test]
+    pub async fn test_replay_recorded_session(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let client = DebugAdapterClient::start(
+            crate::client::SessionId(1),
+            DebugAdapterBinary {
+                command: Some("command".into()),
+                arguments: Default::default(),
+                envs: Default::default(),
+                connection: None,
+                cwd: None,
+                request_args: StartDebuggingRequestArguments {
+                    configuration: serde_json::Value::Null,
+                    request: dap_types::StartDebuggingRequestArgumentsRequest::Launch,
+                },
+            },
+            Box::new({
+                let events = events.clone();
+                move |event| events.lock().unwrap().push(event)
+            }),
+            &mut cx.to_async(),
+        )
+        .await
+        .unwrap();
+
+        let trace = [
+            json!({"direction": "sent", "timestamp_ms": 0, "message": {"seq": 10, "type": "request", "command": "initialize"}}),
+            json!({"direction": "received", "timestamp_ms": 1, "message": {"seq": 1, "type": "response", "request_seq": 10, "success": true, "command": "initialize", "body": {"supportsConfigurationDoneRequest": true}}}),
+            json!({"direction": "received", "timestamp_ms": 2, "message": {"seq": 2, "type": "event", "event": "initialized", "body": {}}}),
+            json!({"direction": "sent", "timestamp_ms": 3, "message": {"seq": 11, "type": "request", "command": "threads"}}),
+            json!({"direction": "received", "timestamp_ms": 4, "message": {"seq": 3, "type": "response", "request_seq": 11, "success": true, "command": "threads", "body": {"threads": [{"id": 1, "name": "main"}]}}}),
+        ]
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+        client.replay(crate::recording::parse_trace(&trace).unwrap());
+        cx.run_until_parked();
+
+        let capabilities = client
+            .request::<Initialize>(InitializeRequestArguments {
+                client_id: Some("zed".to_owned()),
+                client_name: Some("Zed".to_owned()),
+                adapter_id: "fake-adapter".to_owned(),
+                locale: None,
+                path_format: None,
+                supports_variable_type: None,
+                supports_variable_paging: None,
+                supports_run_in_terminal_request: None,
+                supports_memory_references: None,
+                supports_progress_reporting: None,
+                supports_invalidated_event: None,
+                lines_start_at1: None,
+                columns_start_at1: None,
+                supports_memory_event: None,
+                supports_args_can_be_interpreted_by_shell: None,
+                supports_start_debugging_request: None,
+                supports_ansistyling: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(capabilities.supports_configuration_done_request, Some(true));
+        cx.run_until_parked();
+        assert_eq!(
+            *events.lock().unwrap(),
+            [Message::Event(Box::new(Events::Initialized(Some(
+                Capabilities::default()
+            ))))]
+        );
+        assert!(!client.replay_finished());
+
+        let threads = client.request::<Threads>(()).await.unwrap();
+        assert_eq!(threads.threads.len(), 1);
+        assert_eq!(threads.threads[0].name, "main");
+        assert!(client.replay_finished());
+
+        // Requests that aren't in the recording fall back to the request handlers.
+        client.on_request::<Threads, _>(|_, _| Ok(dap_types::ThreadsResponse { threads: vec![] }));
+        let threads = client.request::<Threads>(()).await.unwrap();
+        assert!(threads.threads.is_empty());
+    }
 }
@@ -27,6 +27,10 @@ pub struct DebuggerSettings {
     ///
     /// Default: true
     pub format_dap_log_messages: bool,
+    /// Whether to record the messages of each debug session to a trace file
+    ///
+    /// Default: false
+    pub record_sessions: bool,
     /// The dock position of the debug panel
     ///
     /// Default: Bottom
@@ -45,6 +49,7 @@ impl Settings for DebuggerSettings {
             timeout: content.timeout.unwrap(),
             log_dap_communications: content.log_dap_communications.unwrap(),
             format_dap_log_messages: content.format_dap_log_messages.unwrap(),
+            record_sessions: content.record_sessions.unwrap(),
             dock: content.dock.unwrap(),
         }
     }
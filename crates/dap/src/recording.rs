@@ -0,0 +1,237 @@
+//! Recording and replay of the messages exchanged with a debug adapter.
+//!
+//! When `debugger.record_sessions` is enabled, every DAP message of a session is
+//! appended to a trace file in [`paths::debug_recordings_dir`], one JSON
+//! [`DapTraceEntry`] per line. Traces can be inspected with the recording viewer
+//! in `debugger_tools`, and replayed against a [`crate::transport::FakeTransport`]
+//! to reproduce session handling bugs without the original adapter.
+
+use anyhow::{Context as _, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufWriter, Write as _},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use crate::client::SessionId;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DapTraceDirection {
+    /// A message sent from Zed to the debug adapter.
+    Sent,
+    /// A message sent from the debug adapter to Zed.
+    Received,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DapTraceEntry {
+    pub direction: DapTraceDirection,
+    /// Milliseconds elapsed since the start of the recording.
+    pub timestamp_ms: u64,
+    pub message: serde_json::Value,
+}
+
+impl DapTraceEntry {
+    pub fn message_type(&self) -> Option<&str> {
+        self.message.get("type")?.as_str()
+    }
+
+    /// The request's command, a response's command, or an event's name.
+    pub fn command(&self) -> Option<&str> {
+        self.message
+            .get("command")
+            .or_else(|| self.message.get("event"))?
+            .as_str()
+    }
+
+    pub fn seq(&self) -> Option<u64> {
+        self.message.get("seq")?.as_u64()
+    }
+}
+
+/// Parses a trace written by [`DapRecorder`].
+pub fn parse_trace(contents: &str) -> Result<Vec<DapTraceEntry>> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(ix, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("invalid trace entry on line {}", ix + 1))
+        })
+        .collect()
+}
+
+pub struct DapRecorder {
+    path: PathBuf,
+    writer: Mutex<BufWriter<File>>,
+    started_at: Instant,
+}
+
+impl DapRecorder {
+    /// Creates a trace file for the given session in [`paths::debug_recordings_dir`].
+    pub fn for_session(session_id: SessionId) -> Result<Self> {
+        let dir = paths::debug_recordings_dir();
+        std::fs::create_dir_all(dir).context("creating debug recordings directory")?;
+        let timestamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+        Self::create(&dir.join(format!("{timestamp}_session-{}.jsonl", session_id.0)))
+    }
+
+    pub fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("creating debug recording {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: Mutex::new(BufWriter::new(file)),
+            started_at: Instant::now(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, direction: DapTraceDirection, message: &str) -> Result<()> {
+        let entry = DapTraceEntry {
+            direction,
+            timestamp_ms: self.started_at.elapsed().as_millis() as u64,
+            message: serde_json::from_str(message)?,
+        };
+        let mut writer = self.writer.lock();
+        serde_json::to_writer(&mut *writer, &entry)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Plays back the adapter side of a recorded session.
+///
+/// Each request Zed sends is matched against the next request in the trace, and
+/// answered with the messages the adapter sent after it, up to the next recorded
+/// request. Sequence numbers in responses are rewritten to refer to the replayed
+/// requests.
+#[cfg(any(test, feature = "test-support"))]
+pub struct DapReplay {
+    entries: std::collections::VecDeque<DapTraceEntry>,
+    request_seqs: collections::HashMap<u64, u64>,
+}
+
+#[cfg(any(test, feature = "test-support"))]
+impl DapReplay {
+    pub fn new(entries: Vec<DapTraceEntry>) -> Self {
+        Self {
+            entries: entries.into(),
+            request_seqs: Default::default(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.entries.iter().any(|entry| {
+            entry.direction == DapTraceDirection::Sent && entry.message_type() == Some("request")
+        })
+    }
+
+    /// Returns the recorded replies to `request`, or `None` if the session has
+    /// diverged from the trace.
+    pub(crate) fn respond_to(
+        &mut self,
+        request: &dap_types::messages::Request,
+    ) -> Option<Vec<dap_types::messages::Message>> {
+        let recorded_request = loop {
+            let entry = self.entries.pop_front()?;
+            if entry.direction == DapTraceDirection::Sent && entry.message_type() == Some("request")
+            {
+                break entry;
+            }
+        };
+        if recorded_request.command() != Some(request.command.as_str()) {
+            log::error!(
+                "replayed session diverged: expected `{}` request, got `{}`",
+                recorded_request.command().unwrap_or_default(),
+                request.command
+            );
+            self.entries.push_front(recorded_request);
+            return None;
+        }
+        if let Some(seq) = recorded_request.seq() {
+            self.request_seqs.insert(seq, request.seq);
+        }
+
+        let mut replies = Vec::new();
+        while let Some(entry) = self.entries.front() {
+            if entry.direction == DapTraceDirection::Sent {
+                if entry.message_type() == Some("request") {
+                    break;
+                }
+                self.entries.pop_front();
+                continue;
+            }
+
+            let mut message = self.entries.pop_front()?.message;
+            if let Some(request_seq) = message.get_mut("request_seq")
+                && let Some(seq) = request_seq
+                    .as_u64()
+                    .and_then(|seq| self.request_seqs.get(&seq))
+            {
+                *request_seq = (*seq).into();
+            }
+            match serde_json::from_value(message) {
+                Ok(message) => replies.push(message),
+                Err(error) => log::error!("invalid message in replayed session: {error}"),
+            }
+        }
+        Some(replies)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dap_types::messages::{Message, Request};
+    use serde_json::json;
+
+    #[test]
+    fn test_replay_rewrites_request_seqs() {
+        let trace = [
+            json!({"direction": "sent", "timestamp_ms": 0, "message": {"seq": 5, "type": "request", "command": "initialize"}}),
+            json!({"direction": "received", "timestamp_ms": 1, "message": {"seq": 1, "type": "response", "request_seq": 5, "success": true, "command": "initialize"}}),
+            json!({"direction": "received", "timestamp_ms": 2, "message": {"seq": 2, "type": "event", "event": "initialized"}}),
+            json!({"direction": "sent", "timestamp_ms": 3, "message": {"seq": 6, "type": "request", "command": "launch"}}),
+            json!({"direction": "received", "timestamp_ms": 4, "message": {"seq": 3, "type": "response", "request_seq": 6, "success": true, "command": "launch"}}),
+        ]
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+        let mut replay = DapReplay::new(parse_trace(&trace).unwrap());
+
+        let request = |seq, command: &str| Request {
+            seq,
+            command: command.to_string(),
+            arguments: None,
+        };
+
+        assert!(replay.respond_to(&request(1, "launch")).is_none());
+
+        let replies = replay.respond_to(&request(1, "initialize")).unwrap();
+        assert_eq!(replies.len(), 2);
+        let Message::Response(response) = &replies[0] else {
+            panic!("expected a response");
+        };
+        assert_eq!(response.request_seq, 1);
+        assert!(matches!(replies[1], Message::Event(_)));
+        assert!(!replay.is_finished());
+
+        let replies = replay.respond_to(&request(2, "launch")).unwrap();
+        let Message::Response(response) = &replies[0] else {
+            panic!("expected a response");
+        };
+        assert_eq!(response.request_seq, 2);
+        assert!(replay.is_finished());
+    }
+}
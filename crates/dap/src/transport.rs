@@ -26,10 +26,13 @@ use std::{
 use task::TcpArgumentsTemplate;
 use util::{ConnectionResult, ResultExt, process::Child};
 
+#[cfg(any(test, feature = "test-support"))]
+use crate::recording::{DapReplay, DapTraceEntry};
 use crate::{
     adapters::{DebugAdapterBinary, TcpArguments},
     client::DapMessageHandler,
     debugger_settings::DebuggerSettings,
+    recording::{DapRecorder, DapTraceDirection},
 };
 
 pub(crate) type IoMessage = str;
@@ -172,6 +175,7 @@ impl TransportDelegate {
     pub async fn connect(
         &self,
         message_handler: DapMessageHandler,
+        recorder: Option<Arc<DapRecorder>>,
         cx: &mut AsyncApp,
     ) -> Result<()> {
         let (server_tx, client_rx) = unbounded::<Message>();
@@ -191,6 +195,7 @@ impl TransportDelegate {
 
         let pending_requests = self.pending_requests.clone();
         let output_log_handler = log_handler.clone();
+        let output_recorder = recorder.clone();
         {
             let mut tasks = self.tasks.lock();
             tasks.push(cx.background_spawn(async move {
@@ -199,6 +204,7 @@ impl TransportDelegate {
                     message_handler,
                     pending_requests.clone(),
                     output_log_handler,
+                    output_recorder,
                 )
                 .await
                 {
@@ -214,7 +220,7 @@ impl TransportDelegate {
             }));
 
             tasks.push(cx.background_spawn(async move {
-                match Self::send_to_server(input, client_rx, log_handler).await {
+                match Self::send_to_server(input, client_rx, log_handler, recorder).await {
                     Ok(()) => {}
                     Err(e) => log::error!("Error handling debugger input: {e}"),
                 }
@@ -279,6 +285,7 @@ impl TransportDelegate {
         mut server_stdin: Stdin,
         client_rx: Receiver<Message>,
         log_handlers: Option<LogHandlers>,
+        recorder: Option<Arc<DapRecorder>>,
     ) -> Result<()>
     where
         Stdin: AsyncWrite + Unpin + Send + 'static,
@@ -305,6 +312,10 @@ impl TransportDelegate {
                         }
                     }
 
+                    if let Some(recorder) = recorder.as_ref() {
+                        recorder.record(DapTraceDirection::Sent, &message).log_err();
+                    }
+
                     if let Err(e) = server_stdin
                         .write_all(Self::build_rpc_message(message).as_bytes())
                         .await
@@ -330,6 +341,7 @@ impl TransportDelegate {
         mut message_handler: DapMessageHandler,
         pending_requests: Arc<Mutex<PendingRequests>>,
         log_handlers: Option<LogHandlers>,
+        recorder: Option<Arc<DapRecorder>>,
     ) -> Result<()>
     where
         Stdout: AsyncRead + Unpin + Send + 'static,
@@ -338,9 +350,13 @@ impl TransportDelegate {
         let mut reader = BufReader::new(server_stdout);
 
         let result = loop {
-            let result =
-                Self::receive_server_message(&mut reader, &mut recv_buffer, log_handlers.as_ref())
-                    .await;
+            let result = Self::receive_server_message(
+                &mut reader,
+                &mut recv_buffer,
+                log_handlers.as_ref(),
+                recorder.as_deref(),
+            )
+            .await;
             match result {
                 ConnectionResult::Timeout => anyhow::bail!("Timed out when connecting to debugger"),
                 ConnectionResult::ConnectionReset => {
@@ -392,6 +408,7 @@ impl TransportDelegate {
         reader: &mut BufReader<Stdout>,
         buffer: &mut String,
         log_handlers: Option<&LogHandlers>,
+        recorder: Option<&DapRecorder>,
     ) -> ConnectionResult<Message>
     where
         Stdout: AsyncRead + Unpin + Send + 'static,
@@ -439,6 +456,12 @@ impl TransportDelegate {
         let message =
             serde_json::from_str::<Message>(message_str).context("deserializing server message");
 
+        if let Some(recorder) = recorder {
+            recorder
+                .record(DapTraceDirection::Received, message_str)
+                .log_err();
+        }
+
         if let Some(log_handlers) = log_handlers {
             let command = match &message {
                 Ok(Message::Request(request)) => Some(request.command.as_str()),
@@ -741,6 +764,8 @@ pub struct FakeTransport {
     request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
     // for reverse request responses
     response_handlers: Arc<Mutex<HashMap<&'static str, ResponseHandler>>>,
+    // recorded session to answer requests from, before falling back to the request handlers
+    replay: Arc<Mutex<Option<DapReplay>>>,
     message_handler: Option<Task<Result<()>>>,
     kind: FakeTransportKind,
 }
@@ -795,6 +820,20 @@ impl FakeTransport {
         );
     }
 
+    /// Answers requests with the adapter's messages from a recorded session.
+    /// Requests that diverge from the recording fall back to the request handlers.
+    pub fn replay(&self, trace: Vec<DapTraceEntry>) {
+        *self.replay.lock() = Some(DapReplay::new(trace));
+    }
+
+    /// Whether every request in the replayed session has been sent.
+    pub fn replay_finished(&self) -> bool {
+        self.replay
+            .lock()
+            .as_ref()
+            .is_none_or(|replay| replay.is_finished())
+    }
+
     pub fn on_response<R: dap_types::requests::Request, F>(&self, handler: F)
     where
         F: 'static + Send + Fn(Response),
@@ -808,6 +847,7 @@ impl FakeTransport {
         Ok(Self {
             request_handlers: Arc::new(Mutex::new(HashMap::default())),
             response_handlers: Arc::new(Mutex::new(HashMap::default())),
+            replay: Default::default(),
             message_handler: None,
             kind: FakeTransportKind::Tcp {
                 connection,
@@ -819,6 +859,7 @@ impl FakeTransport {
     async fn handle_messages(
         request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
         response_handlers: Arc<Mutex<HashMap<&'static str, ResponseHandler>>>,
+        replay: Arc<Mutex<Option<DapReplay>>>,
         stdin_reader: PipeReader,
         stdout_writer: PipeWriter,
     ) -> Result<()> {
@@ -830,7 +871,9 @@ impl FakeTransport {
         let mut buffer = String::new();
 
         loop {
-            match TransportDelegate::receive_server_message(&mut reader, &mut buffer, None).await {
+            match TransportDelegate::receive_server_message(&mut reader, &mut buffer, None, None)
+                .await
+            {
                 ConnectionResult::Timeout => {
                     anyhow::bail!("Timed out when connecting to debugger");
                 }
@@ -840,6 +883,26 @@ impl FakeTransport {
                 }
                 ConnectionResult::Result(Err(e)) => break Err(e),
                 ConnectionResult::Result(Ok(message)) => {
+                    let replies = match &message {
+                        Message::Request(request) => replay
+                            .lock()
+                            .as_mut()
+                            .and_then(|replay| replay.respond_to(request)),
+                        _ => None,
+                    };
+                    if let Some(replies) = replies {
+                        let mut writer = stdout_writer.lock().await;
+                        for reply in replies {
+                            let message = serde_json::to_string(&reply).unwrap();
+                            writer
+                                .write_all(TransportDelegate::build_rpc_message(message).as_bytes())
+                                .await
+                                .unwrap();
+                        }
+                        writer.flush().await.unwrap();
+                        continue;
+                    }
+
                     match message {
                         Message::Request(request) => {
                             // redirect reverse requests to stdout writer/reader
@@ -940,16 +1003,19 @@ impl FakeTransport {
         let mut this = Self {
             request_handlers: Arc::new(Mutex::new(HashMap::default())),
             response_handlers: Arc::new(Mutex::new(HashMap::default())),
+            replay: Default::default(),
             message_handler: None,
             kind,
         };
 
         let request_handlers = this.request_handlers.clone();
         let response_handlers = this.response_handlers.clone();
+        let replay = this.replay.clone();
 
         this.message_handler = Some(cx.background_spawn(Self::handle_messages(
             request_handlers,
             response_handlers,
+            replay,
             stdin_reader,
             stdout_writer,
         )));
@@ -991,10 +1057,12 @@ impl Transport for FakeTransport {
 
                 let request_handlers = self.request_handlers.clone();
                 let response_handlers = self.response_handlers.clone();
+                let replay = self.replay.clone();
 
                 self.message_handler = Some(executor.spawn(Self::handle_messages(
                     request_handlers,
                     response_handlers,
+                    replay,
                     stdin_reader,
                     stdout_writer,
                 )));
@@ -0,0 +1,146 @@
+use anyhow::Context as _;
+use dap::recording::{DapTraceDirection, DapTraceEntry, parse_trace};
+use editor::{Editor, MultiBuffer};
+use fs::Fs as _;
+use gpui::{App, AppContext as _, PathPromptOptions, actions};
+use language::Capability;
+use project::DirectoryLister;
+use std::{fmt::Write as _, path::Path};
+use util::ResultExt as _;
+use workspace::Workspace;
+
+actions!(
+    dev,
+    [
+        /// Opens a recorded debug adapter session for inspection.
+        OpenDebugAdapterRecording
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &OpenDebugAdapterRecording, window, cx| {
+            let prompt = workspace.prompt_for_open_path(
+                PathPromptOptions {
+                    files: true,
+                    directories: false,
+                    multiple: false,
+                    prompt: None,
+                },
+                DirectoryLister::Local(
+                    workspace.project().clone(),
+                    workspace.app_state().fs.clone(),
+                ),
+                window,
+                cx,
+            );
+            let fs = workspace.app_state().fs.clone();
+            let language = workspace
+                .project()
+                .read(cx)
+                .languages()
+                .language_for_name("JSON");
+
+            cx.spawn_in(window, async move |workspace, cx| {
+                let Some(path) = prompt.await?.and_then(|mut paths| paths.pop()) else {
+                    return anyhow::Ok(());
+                };
+                let contents = fs.load(&path).await?;
+                let trace = parse_trace(&contents)
+                    .with_context(|| format!("parsing debug recording {}", path.display()))?;
+                let title = recording_title(&path, &trace);
+                let text = format_trace(&trace);
+                let language = language.await.log_err();
+
+                let buffer = workspace
+                    .update(cx, |workspace, cx| {
+                        workspace
+                            .project()
+                            .update(cx, |project, cx| project.create_buffer(language, false, cx))
+                    })?
+                    .await?;
+                buffer.update(cx, |buffer, cx| {
+                    buffer.set_text(text, cx);
+                    buffer.set_capability(Capability::ReadOnly, cx);
+                });
+                let buffer = cx.new(|cx| MultiBuffer::singleton(buffer, cx).with_title(title));
+
+                workspace.update_in(cx, |workspace, window, cx| {
+                    let project = workspace.project().clone();
+                    workspace.add_item_to_active_pane(
+                        Box::new(cx.new(|cx| {
+                            let mut editor =
+                                Editor::for_multibuffer(buffer, Some(project), window, cx);
+                            editor.set_read_only(true);
+                            editor.set_should_serialize(false, cx);
+                            editor
+                        })),
+                        None,
+                        true,
+                        window,
+                        cx,
+                    );
+                })?;
+                anyhow::Ok(())
+            })
+            .detach_and_log_err(cx);
+        });
+    })
+    .detach();
+}
+
+fn recording_title(path: &Path, trace: &[DapTraceEntry]) -> String {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    format!("{file_name} ({} messages)", trace.len())
+}
+
+/// Formats each message of the trace as a comment summarizing it, followed by
+/// its pretty-printed JSON.
+fn format_trace(trace: &[DapTraceEntry]) -> String {
+    let mut output = String::new();
+    for entry in trace {
+        let direction = match entry.direction {
+            DapTraceDirection::Sent => "Zed → adapter",
+            DapTraceDirection::Received => "adapter → Zed",
+        };
+        let command = entry.command().unwrap_or("<unknown>");
+        let summary = match entry.message_type() {
+            Some("request") => format!("`{command}` request"),
+            Some("response") => {
+                let success = entry
+                    .message
+                    .get("success")
+                    .and_then(|success| success.as_bool())
+                    .unwrap_or_default();
+                let request_seq = entry
+                    .message
+                    .get("request_seq")
+                    .and_then(|seq| seq.as_u64())
+                    .unwrap_or_default();
+                format!(
+                    "`{command}` response to {request_seq} ({})",
+                    if success { "success" } else { "failure" }
+                )
+            }
+            Some("event") => format!("`{command}` event"),
+            _ => "unknown message".to_string(),
+        };
+        writeln!(
+            output,
+            "// +{:.3}s {direction}: {summary}, seq {}",
+            entry.timestamp_ms as f64 / 1000.0,
+            entry.seq().unwrap_or_default()
+        )
+        .ok();
+        writeln!(
+            output,
+            "{}\n",
+            serde_json::to_string_pretty(&entry.message).unwrap_or_default()
+        )
+        .ok();
+    }
+    output
+}
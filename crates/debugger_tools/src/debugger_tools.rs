@@ -1,8 +1,11 @@
 mod dap_log;
+mod dap_recording;
 pub use dap_log::*;
+pub use dap_recording::OpenDebugAdapterRecording;
 
 use gpui::App;
 
 pub fn init(cx: &mut App) {
     dap_log::init(cx);
+    dap_recording::init(cx);
 }
@@ -40,7 +40,7 @@ use util::{ResultExt, debug_panic, maybe};
 use workspace::SplitDirection;
 use workspace::item::SaveOptions;
 use workspace::{
-    Item, Pane, Workspace,
+    Item, ItemHandle, Pane, Workspace,
     dock::{DockPosition, Panel, PanelEvent},
 };
 use zed_actions::ToggleFocus;
@@ -1599,6 +1599,10 @@ impl Panel for DebugPanel {
         None
     }
 
+    fn followable_item(&self, _cx: &App) -> Option<Box<dyn ItemHandle>> {
+        Some(Box::new(self.active_session.clone()?))
+    }
+
     fn activation_priority(&self) -> u32 {
         9
     }
@@ -111,6 +111,7 @@ actions!(
 
 pub fn init(cx: &mut App) {
     workspace::FollowableViewRegistry::register::<DebugSession>(cx);
+    workspace::FollowableViewRegistry::register::<session::FollowedDebugSession>(cx);
 
     cx.observe_new(|workspace: &mut Workspace, _, _| {
         workspace
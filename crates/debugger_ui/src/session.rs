@@ -1,27 +1,39 @@
+mod followed;
 pub mod running;
 
 use crate::{StackTraceView, persistence::SerializedLayout, session::running::DebugTerminal};
 use dap::client::SessionId;
-use gpui::{App, Axis, Entity, EventEmitter, FocusHandle, Focusable, Task, WeakEntity};
+use gpui::{
+    App, Axis, Entity, EventEmitter, FocusHandle, Focusable, Subscription, Task, WeakEntity,
+};
 use project::debugger::session::Session;
 use project::worktree_store::WorktreeStore;
-use project::{Project, debugger::session::SessionQuirks};
+use project::{Project, ProjectPath, debugger::session::SessionQuirks};
 use rpc::proto;
-use running::RunningState;
-use std::cell::OnceCell;
+use running::{
+    RunningState,
+    stack_frame_list::{StackFrameEntry, StackFrameList, StackFrameListEvent},
+};
+use std::{cell::OnceCell, time::Duration};
 use ui::prelude::*;
 use workspace::{
     CollaboratorId, FollowableItem, ViewId, Workspace,
     item::{self, Item},
 };
 
+pub(crate) use followed::FollowedDebugSession;
+
+const FOLLOWER_UPDATE_DEBOUNCE: Duration = Duration::from_millis(100);
+
 pub struct DebugSession {
     remote_id: Option<workspace::ViewId>,
     pub(crate) running_state: Entity<RunningState>,
     pub(crate) quirks: SessionQuirks,
     stack_trace_view: OnceCell<Entity<StackTraceView>>,
-    _worktree_store: WeakEntity<WorktreeStore>,
+    worktree_store: WeakEntity<WorktreeStore>,
     workspace: WeakEntity<Workspace>,
+    update_followers_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl DebugSession {
@@ -49,13 +61,32 @@ impl DebugSession {
         });
         let quirks = session.read(cx).quirks();
 
-        cx.new(|cx| Self {
-            remote_id: None,
-            running_state,
-            quirks,
-            stack_trace_view: OnceCell::new(),
-            _worktree_store: project.read(cx).worktree_store().downgrade(),
-            workspace,
+        cx.new(|cx| {
+            let stack_frame_list = running_state.read(cx).stack_frame_list().clone();
+            let variable_list = running_state.read(cx).variable_list().clone();
+            let subscriptions = vec![
+                cx.subscribe_in(
+                    &stack_frame_list,
+                    window,
+                    |this, _, _: &StackFrameListEvent, window, cx| {
+                        this.update_followers(window, cx)
+                    },
+                ),
+                cx.observe_in(&variable_list, window, |this, _, window, cx| {
+                    this.update_followers(window, cx)
+                }),
+            ];
+
+            Self {
+                remote_id: None,
+                running_state,
+                quirks,
+                stack_trace_view: OnceCell::new(),
+                worktree_store: project.read(cx).worktree_store().downgrade(),
+                workspace,
+                update_followers_task: Task::ready(()),
+                _subscriptions: subscriptions,
+            }
         })
     }
 
@@ -99,26 +130,119 @@ impl DebugSession {
     pub(crate) fn label(&self, cx: &mut App) -> Option<SharedString> {
         let session = self.running_state.read(cx).session().clone();
         session.update(cx, |session, cx| {
-            let session_label = session.label();
-            let quirks = session.quirks();
-            let mut single_thread_name = || {
-                let threads = session.threads(cx);
-                match threads.as_slice() {
-                    [(thread, _)] => Some(SharedString::from(&thread.name)),
-                    _ => None,
-                }
-            };
-            if quirks.prefer_thread_name {
-                single_thread_name().or(session_label)
-            } else {
-                session_label.or_else(single_thread_name)
+            if session.quirks().prefer_thread_name || session.label().is_none() {
+                session.threads(cx);
             }
+            Self::fetched_label(session)
         })
     }
 
+    /// The session's label, using the threads that have already been fetched.
+    fn fetched_label(session: &Session) -> Option<SharedString> {
+        let single_thread_name = || {
+            let mut threads = session.fetched_threads();
+            match (threads.next(), threads.next()) {
+                (Some(thread), None) => Some(SharedString::from(&thread.name)),
+                _ => None,
+            }
+        };
+        if session.quirks().prefer_thread_name {
+            single_thread_name().or_else(|| session.label())
+        } else {
+            session.label().or_else(single_thread_name)
+        }
+    }
+
     pub fn running_state(&self) -> &Entity<RunningState> {
         &self.running_state
     }
+
+    /// The state shown to collaborators following this session: the selected thread, its
+    /// stack frames and the variables of the selected frame. Only uses what the session has
+    /// already fetched from the debug adapter.
+    fn follow_state(&self, cx: &App) -> proto::DebugSessionView {
+        let running_state = self.running_state.read(cx);
+        let session = running_state.session().read(cx);
+        let label = Self::fetched_label(session).map(|label| label.to_string());
+        let thread_name = running_state.selected_thread_id().and_then(|thread_id| {
+            session
+                .fetched_threads()
+                .find(|thread| thread.id == thread_id.0)
+                .map(|thread| thread.name.clone())
+        });
+
+        let worktree_store = self.worktree_store.upgrade();
+        let stack_frame_list = running_state.stack_frame_list().read(cx);
+        let variable_list = running_state.variable_list();
+        let stack_frames = stack_frame_list
+            .entries()
+            .iter()
+            .flat_map(|entry| match entry {
+                StackFrameEntry::Normal(stack_frame) => vec![stack_frame],
+                StackFrameEntry::Label(_) => Vec::new(),
+                StackFrameEntry::Collapsed(stack_frames) => stack_frames.iter().collect(),
+            })
+            .map(|stack_frame| proto::DebugStackFrame {
+                id: stack_frame.id,
+                name: stack_frame.name.clone(),
+                path: StackFrameList::abs_path_from_stack_frame(stack_frame).and_then(|abs_path| {
+                    let (worktree, path) = worktree_store
+                        .as_ref()?
+                        .read(cx)
+                        .find_worktree(&abs_path, cx)?;
+                    let worktree_id = worktree.read(cx).id();
+                    Some(ProjectPath { worktree_id, path }.to_proto())
+                }),
+                line: stack_frame.line as u32,
+            })
+            .collect();
+        let variables = variable_list
+            .read(cx)
+            .listed_variables()
+            .map(|(variable, depth, selected)| proto::DebugVariable {
+                name: variable.name.clone(),
+                value: variable.value.clone(),
+                r#type: variable.type_.clone(),
+                depth: depth as u32,
+                selected,
+            })
+            .collect();
+
+        proto::DebugSessionView {
+            session_id: session.session_id().to_proto(),
+            label,
+            thread_name,
+            stack_frames,
+            selected_stack_frame_id: stack_frame_list.opened_stack_frame_id(),
+            variables,
+        }
+    }
+
+    /// The session lives in the debug panel rather than in a pane, so its followers aren't
+    /// updated by the workspace.
+    fn update_followers(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.update_followers_task = cx.spawn_in(window, async move |this, cx| {
+            cx.background_executor()
+                .timer(FOLLOWER_UPDATE_DEBOUNCE)
+                .await;
+            let Some(this) = this.upgrade() else {
+                return;
+            };
+            let (workspace, state) = this.update(cx, |this, cx| {
+                (this.workspace.clone(), this.follow_state(cx))
+            });
+            workspace
+                .update_in(cx, |workspace, window, cx| {
+                    workspace.update_followers_for_panel_item(
+                        &this,
+                        proto::update_view::Variant::DebugSession(state),
+                        window,
+                        cx,
+                    )
+                })
+                .ok();
+        });
+    }
 }
 
 impl EventEmitter<()> for DebugSession {}
@@ -141,8 +265,8 @@ impl FollowableItem for DebugSession {
         self.remote_id
     }
 
-    fn to_state_proto(&self, _window: &mut Window, _cx: &mut App) -> Option<proto::view::Variant> {
-        None
+    fn to_state_proto(&self, _window: &mut Window, cx: &mut App) -> Option<proto::view::Variant> {
+        Some(proto::view::Variant::DebugSession(self.follow_state(cx)))
     }
 
     fn from_state_proto(
@@ -152,6 +276,7 @@ impl FollowableItem for DebugSession {
         _window: &mut Window,
         _cx: &mut App,
     ) -> Option<gpui::Task<anyhow::Result<Entity<Self>>>> {
+        // Guests can't debug, so they follow sessions with a `FollowedDebugSession`.
         None
     }
 
@@ -162,9 +287,7 @@ impl FollowableItem for DebugSession {
         _window: &mut Window,
         _cx: &mut App,
     ) -> bool {
-        // update.get_or_insert_with(|| proto::update_view::Variant::DebugPanel(Default::default()));
-
-        true
+        false
     }
 
     fn apply_update_proto(
@@ -0,0 +1,263 @@
+use anyhow::{Context as _, Result};
+use editor::Editor;
+use gpui::{App, AppContext as _, Entity, EventEmitter, FocusHandle, Focusable, Task, WeakEntity};
+use project::{Project, ProjectPath};
+use rpc::proto;
+use ui::{ListItem, prelude::*};
+use workspace::{
+    CollaboratorId, ViewId, Workspace,
+    item::{Dedup, FollowEvent, FollowableItem, Item},
+};
+
+/// A read-only mirror of a debug session that a followed collaborator is looking at.
+///
+/// Guests of a project can't start debug sessions of their own, so instead of the debug
+/// panel they see the leader's current thread, stack frames and variables in this view.
+pub(crate) struct FollowedDebugSession {
+    remote_id: Option<ViewId>,
+    workspace: WeakEntity<Workspace>,
+    state: proto::DebugSessionView,
+    focus_handle: FocusHandle,
+}
+
+impl FollowedDebugSession {
+    #[cfg(any(test, feature = "test-support"))]
+    pub(crate) fn state(&self) -> &proto::DebugSessionView {
+        &self.state
+    }
+
+    fn open_stack_frame(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(stack_frame) = self.state.stack_frames.get(ix) else {
+            return;
+        };
+        let Some(project_path) = stack_frame.path.clone().and_then(ProjectPath::from_proto) else {
+            return;
+        };
+        let row = stack_frame.line.saturating_sub(1);
+        let Ok(open_path) = self.workspace.update(cx, |workspace, cx| {
+            workspace.open_path(project_path, None, true, window, cx)
+        }) else {
+            return;
+        };
+        cx.spawn_in(window, async move |_, cx| {
+            let item = open_path.await?;
+            let editor = cx
+                .update(|_, cx| item.act_as::<Editor>(cx))?
+                .context("expected editor")?;
+            editor.update_in(cx, |editor, window, cx| {
+                editor.go_to_singleton_buffer_point(text::Point::new(row, 0), window, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn render_stack_frame(
+        &self,
+        ix: usize,
+        stack_frame: &proto::DebugStackFrame,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let location = stack_frame
+            .path
+            .as_ref()
+            .map(|path| format!("{}:{}", path.path, stack_frame.line));
+        ListItem::new(("stack-frame", ix))
+            .toggle_state(self.state.selected_stack_frame_id == Some(stack_frame.id))
+            .disabled(stack_frame.path.is_none())
+            .on_click(cx.listener(move |this, _, window, cx| {
+                this.open_stack_frame(ix, window, cx);
+            }))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(stack_frame.name.clone()).size(LabelSize::Small))
+                    .when_some(location, |this, location| {
+                        this.child(
+                            Label::new(location)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .truncate(),
+                        )
+                    }),
+            )
+    }
+
+    fn render_variable(&self, ix: usize, variable: &proto::DebugVariable) -> impl IntoElement {
+        ListItem::new(("variable", ix))
+            .indent_level(variable.depth as usize)
+            .indent_step_size(px(10.))
+            .toggle_state(variable.selected)
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(Label::new(variable.name.clone()).size(LabelSize::Small))
+                    .child(
+                        Label::new(variable.value.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .truncate(),
+                    ),
+            )
+    }
+}
+
+impl EventEmitter<()> for FollowedDebugSession {}
+
+impl Focusable for FollowedDebugSession {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for FollowedDebugSession {
+    type Event = ();
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        match &self.state.label {
+            Some(label) => format!("Debugger: {label}").into(),
+            None => "Debugger".into(),
+        }
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Debug).color(Color::Muted))
+    }
+}
+
+impl FollowableItem for FollowedDebugSession {
+    fn remote_id(&self) -> Option<ViewId> {
+        self.remote_id
+    }
+
+    fn to_state_proto(&self, _window: &mut Window, _cx: &mut App) -> Option<proto::view::Variant> {
+        None
+    }
+
+    fn from_state_proto(
+        workspace: Entity<Workspace>,
+        remote_id: ViewId,
+        state: &mut Option<proto::view::Variant>,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        let Some(proto::view::Variant::DebugSession(_)) = state else {
+            return None;
+        };
+        let Some(proto::view::Variant::DebugSession(state)) = state.take() else {
+            unreachable!()
+        };
+
+        let view = cx.new(|cx| Self {
+            remote_id: Some(remote_id),
+            workspace: workspace.downgrade(),
+            state,
+            focus_handle: cx.focus_handle(),
+        });
+        Some(Task::ready(Ok(view)))
+    }
+
+    fn to_follow_event(_event: &Self::Event) -> Option<FollowEvent> {
+        None
+    }
+
+    fn add_event_to_update_proto(
+        &self,
+        _event: &Self::Event,
+        _update: &mut Option<proto::update_view::Variant>,
+        _window: &mut Window,
+        _cx: &mut App,
+    ) -> bool {
+        false
+    }
+
+    fn apply_update_proto(
+        &mut self,
+        _project: &Entity<Project>,
+        message: proto::update_view::Variant,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        if let proto::update_view::Variant::DebugSession(state) = message {
+            self.state = state;
+            cx.notify();
+        }
+        Task::ready(Ok(()))
+    }
+
+    fn is_project_item(&self, _window: &Window, _cx: &App) -> bool {
+        true
+    }
+
+    fn set_leader_id(
+        &mut self,
+        _leader_id: Option<CollaboratorId>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) {
+    }
+
+    fn dedup(&self, existing: &Self, _window: &Window, _cx: &App) -> Option<Dedup> {
+        if existing.state.session_id == self.state.session_id {
+            Some(Dedup::ReplaceExisting)
+        } else {
+            None
+        }
+    }
+}
+
+impl Render for FollowedDebugSession {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let stack_frames = self
+            .state
+            .stack_frames
+            .iter()
+            .enumerate()
+            .map(|(ix, stack_frame)| {
+                self.render_stack_frame(ix, stack_frame, cx)
+                    .into_any_element()
+            })
+            .collect::<Vec<_>>();
+        let variables = self
+            .state
+            .variables
+            .iter()
+            .enumerate()
+            .map(|(ix, variable)| self.render_variable(ix, variable).into_any_element())
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .id("followed-debug-session")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .p_2()
+            .gap_2()
+            .overflow_y_scroll()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(self.tab_content_text(0, cx)))
+                    .when_some(self.state.thread_name.clone(), |this, thread_name| {
+                        this.child(Label::new(thread_name).color(Color::Muted))
+                    }),
+            )
+            .child(
+                v_flex()
+                    .child(
+                        Label::new("Stack Frames")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .children(stack_frames),
+            )
+            .child(
+                v_flex()
+                    .child(
+                        Label::new("Variables")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .children(variables),
+            )
+    }
+}
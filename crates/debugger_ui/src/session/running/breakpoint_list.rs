@@ -24,8 +24,9 @@ use project::{
     worktree_store::WorktreeStore,
 };
 use ui::{
-    Divider, DividerColor, FluentBuilder as _, Indicator, IntoElement, ListItem, Render,
-    ScrollAxes, StatefulInteractiveElement, Tooltip, WithScrollbar, prelude::*,
+    ContextMenu, Divider, DividerColor, FluentBuilder as _, Indicator, IntoElement, ListItem,
+    PopoverMenu, Render, ScrollAxes, StatefulInteractiveElement, Tooltip, WithScrollbar,
+    prelude::*,
 };
 use util::rel_path::RelPath;
 use workspace::Workspace;
@@ -37,7 +38,9 @@ actions!(
         /// Navigates to the previous breakpoint property in the list.
         PreviousBreakpointProperty,
         /// Navigates to the next breakpoint property in the list.
-        NextBreakpointProperty
+        NextBreakpointProperty,
+        /// Saves the project's breakpoints as a named set in `.zed/breakpoints.json`.
+        SaveBreakpointSet
     ]
 );
 #[derive(Clone, Copy, PartialEq)]
@@ -59,6 +62,7 @@ pub(crate) struct BreakpointList {
     max_width_index: Option<usize>,
     input: Entity<Editor>,
     strip_mode: Option<ActiveBreakpointStripMode>,
+    breakpoint_set_name_input: Option<Entity<Editor>>,
     serialize_exception_breakpoints_task: Option<Task<anyhow::Result<()>>>,
 }
 
@@ -105,11 +109,15 @@ impl BreakpointList {
                 selected_ix: None,
                 input: cx.new(|cx| Editor::single_line(window, cx)),
                 strip_mode: None,
+                breakpoint_set_name_input: None,
                 serialize_exception_breakpoints_task: None,
             };
             if let Some(name) = adapter_name {
                 _ = this.deserialize_exception_breakpoints(name, cx);
             }
+            this.breakpoint_store
+                .update(cx, |store, cx| store.refresh_breakpoint_sets(cx))
+                .detach_and_log_err(cx);
             this
         })
     }
@@ -308,8 +316,27 @@ impl BreakpointList {
         self.select_ix(ix, window, cx);
     }
 
+    fn save_breakpoint_set(
+        &mut self,
+        _: &SaveBreakpointSet,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let input = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Breakpoint Set Name", window, cx);
+            editor
+        });
+        input.focus_handle(cx).focus(window, cx);
+        self.breakpoint_set_name_input = Some(input);
+        cx.notify();
+    }
+
     fn dismiss(&mut self, _: &menu::Cancel, window: &mut Window, cx: &mut Context<Self>) {
-        if self.input.focus_handle(cx).contains_focused(window, cx) {
+        if self.breakpoint_set_name_input.take().is_some() {
+            self.focus_handle.focus(window, cx);
+            cx.notify();
+        } else if self.input.focus_handle(cx).contains_focused(window, cx) {
             self.focus_handle.focus(window, cx);
         } else if self.strip_mode.is_some() {
             self.strip_mode.take();
@@ -319,6 +346,18 @@ impl BreakpointList {
         }
     }
     fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(input) = self.breakpoint_set_name_input.take() {
+            let name = input.read(cx).text(cx).trim().to_string();
+            if !name.is_empty() {
+                self.breakpoint_store
+                    .update(cx, |store, cx| store.save_breakpoint_set(name, cx))
+                    .detach_and_log_err(cx);
+            }
+            self.focus_handle.focus(window, cx);
+            cx.notify();
+            return;
+        }
+
         let Some(entry) = self.selected_ix.and_then(|ix| self.breakpoints.get_mut(ix)) else {
             return;
         };
@@ -653,14 +692,49 @@ impl BreakpointList {
                         selection_kind.map(|kind| kind.0) != Some(SelectedBreakpointKind::Source),
                     )
                     .on_click({
+                        let focus_handle = focus_handle.clone();
                         move |_, window, cx| {
                             focus_handle.focus(window, cx);
                             window.dispatch_action(UnsetBreakpoint.boxed_clone(), cx)
                         }
                     }),
             )
+            .child(self.render_breakpoint_sets_menu(focus_handle))
             .into_any_element()
     }
+
+    fn render_breakpoint_sets_menu(&self, focus_handle: FocusHandle) -> impl IntoElement {
+        let breakpoint_store = self.breakpoint_store.clone();
+        PopoverMenu::new("breakpoint-sets-menu")
+            .trigger_with_tooltip(
+                IconButton::new("breakpoint-sets-menu-trigger", IconName::ListTree)
+                    .icon_size(IconSize::Small),
+                Tooltip::text("Breakpoint Sets"),
+            )
+            .menu(move |window, cx| {
+                let breakpoint_store = breakpoint_store.clone();
+                let focus_handle = focus_handle.clone();
+                Some(ContextMenu::build(window, cx, move |mut menu, _, cx| {
+                    menu = menu.context(focus_handle);
+                    for (name, is_active) in breakpoint_store.read(cx).breakpoint_sets() {
+                        let breakpoint_store = breakpoint_store.clone();
+                        menu = menu.toggleable_entry(
+                            name.clone(),
+                            is_active,
+                            IconPosition::Start,
+                            None,
+                            move |_, cx| {
+                                breakpoint_store
+                                    .update(cx, |store, cx| store.toggle_breakpoint_set(&name, cx))
+                                    .detach_and_log_err(cx);
+                            },
+                        );
+                    }
+                    menu.separator()
+                        .action("Save Breakpoints as Set…", SaveBreakpointSet.boxed_clone())
+                }))
+            })
+    }
 }
 
 impl Render for BreakpointList {
@@ -771,6 +845,7 @@ impl Render for BreakpointList {
             .on_action(cx.listener(Self::unset_breakpoint))
             .on_action(cx.listener(Self::next_breakpoint_property))
             .on_action(cx.listener(Self::previous_breakpoint_property))
+            .on_action(cx.listener(Self::save_breakpoint_set))
             .size_full()
             .pt_1()
             .child(self.render_list(cx))
@@ -807,6 +882,18 @@ impl Render for BreakpointList {
                             .child(self.input.clone()),
                     )
             })
+            .when_some(self.breakpoint_set_name_input.clone(), |this, input| {
+                this.child(Divider::horizontal().color(DividerColor::Border))
+                    .child(
+                        h_flex()
+                            .p_1()
+                            .rounded_sm()
+                            .bg(cx.theme().colors().editor_background)
+                            .border_1()
+                            .border_color(cx.theme().colors().border_transparent)
+                            .child(input),
+                    )
+            })
     }
 }
 
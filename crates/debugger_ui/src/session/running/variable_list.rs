@@ -429,6 +429,24 @@ impl VariableList {
             .collect()
     }
 
+    /// The variables currently listed, with their depth and whether they're selected.
+    pub(crate) fn listed_variables(
+        &self,
+    ) -> impl Iterator<Item = (&dap::Variable, usize, bool)> + '_ {
+        self.entries.iter().filter_map(|entry| {
+            let variable = entry.as_variable()?;
+            let depth = self
+                .entry_states
+                .get(&entry.path)
+                .map_or(0, |state| state.depth);
+            Some((
+                variable,
+                depth,
+                self.selection.as_ref() == Some(&entry.path),
+            ))
+        })
+    }
+
     fn render_entries(
         &mut self,
         ix: Range<usize>,
@@ -3,14 +3,18 @@ use std::sync::Arc;
 use anyhow::{Context as _, Result};
 use dap::adapters::DebugTaskDefinition;
 use dap::client::DebugAdapterClient;
-use gpui::{Entity, TestAppContext, WindowHandle};
+use gpui::{App, Entity, TestAppContext, WindowHandle};
 use project::{Project, debugger::session::Session};
+use rpc::proto;
 use settings::SettingsStore;
 use task::SharedTaskContext;
 use terminal_view::terminal_panel::TerminalPanel;
-use workspace::MultiWorkspace;
+use workspace::{MultiWorkspace, Workspace};
 
-use crate::{debugger_panel::DebugPanel, session::DebugSession};
+use crate::{
+    debugger_panel::DebugPanel,
+    session::{DebugSession, FollowedDebugSession},
+};
 
 #[cfg(test)]
 mod attach_modal;
@@ -107,6 +111,17 @@ pub fn active_debug_session_panel(
         .unwrap()
 }
 
+/// The debug session shown to a collaborator following someone who is debugging.
+pub fn followed_debug_session(
+    workspace: &Entity<Workspace>,
+    cx: &App,
+) -> Option<proto::DebugSessionView> {
+    let session = workspace
+        .read(cx)
+        .active_item_as::<FollowedDebugSession>(cx)?;
+    Some(session.read(cx).state().clone())
+}
+
 pub fn start_debug_session_with<T: Fn(&Arc<DebugAdapterClient>) + 'static>(
     workspace: &WindowHandle<MultiWorkspace>,
     cx: &mut gpui::TestAppContext,
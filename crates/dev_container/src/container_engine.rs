@@ -0,0 +1,95 @@
+use async_trait::async_trait;
+
+use crate::devcontainer_api::DevContainerError;
+
+/// A container engine CLI that dev containers are built and run with.
+#[async_trait]
+pub(crate) trait ContainerEngine: Send + Sync {
+    /// Runs the engine's CLI with the given arguments, returning its trimmed stdout.
+    async fn run(&self, args: &[String]) -> Result<String, DevContainerError>;
+}
+
+/// Runs `docker` or `podman` from `$PATH`.
+pub(crate) struct CliContainerEngine {
+    pub(crate) use_podman: bool,
+}
+
+impl CliContainerEngine {
+    fn cli(&self) -> &'static str {
+        if self.use_podman { "podman" } else { "docker" }
+    }
+}
+
+#[async_trait]
+impl ContainerEngine for CliContainerEngine {
+    async fn run(&self, args: &[String]) -> Result<String, DevContainerError> {
+        let mut command = util::command::new_command(self.cli());
+        command.args(args);
+        log::info!("Running {} {}", self.cli(), args.join(" "));
+
+        match command.output().await {
+            Ok(output) => {
+                if output.status.success() {
+                    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+                } else {
+                    let message = format!(
+                        "Non-success status running {} {}: out: {}, err: {}",
+                        self.cli(),
+                        args.first().map(String::as_str).unwrap_or_default(),
+                        String::from_utf8_lossy(&output.stdout),
+                        String::from_utf8_lossy(&output.stderr)
+                    );
+                    log::error!("{}", &message);
+                    Err(DevContainerError::DevContainerUpFailed(message))
+                }
+            }
+            Err(e) => {
+                let message = format!("Error running {}: {:?}", self.cli(), e);
+                log::error!("{}", &message);
+                Err(DevContainerError::DevContainerUpFailed(message))
+            }
+        }
+    }
+}
+
+/// Records the commands it's asked to run, answering them from canned responses.
+#[cfg(test)]
+pub(crate) struct FakeContainerEngine {
+    commands: std::sync::Mutex<Vec<String>>,
+    responses: Vec<(String, String)>,
+}
+
+#[cfg(test)]
+impl FakeContainerEngine {
+    /// Creates an engine that answers commands starting with each prefix with the
+    /// corresponding output, and any other command with no output.
+    pub(crate) fn new(responses: &[(&str, &str)]) -> Self {
+        Self {
+            commands: Default::default(),
+            responses: responses
+                .iter()
+                .map(|(prefix, output)| (prefix.to_string(), output.to_string()))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn commands(&self) -> Vec<String> {
+        self.commands.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl ContainerEngine for FakeContainerEngine {
+    async fn run(&self, args: &[String]) -> Result<String, DevContainerError> {
+        let command = args.join(" ");
+        let output = self
+            .responses
+            .iter()
+            .find(|(prefix, _)| command.starts_with(prefix))
+            .map(|(_, output)| output.clone())
+            .unwrap_or_default();
+        self.commands.lock().unwrap().push(command);
+        Ok(output)
+    }
+}
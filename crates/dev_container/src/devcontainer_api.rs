@@ -13,7 +13,12 @@ use util::rel_path::RelPath;
 use workspace::Workspace;
 use worktree::Snapshot;
 
-use crate::{DevContainerContext, DevContainerFeature, DevContainerTemplate};
+use crate::{
+    DevContainerContext, DevContainerFeature, DevContainerTemplate,
+    container_engine::CliContainerEngine,
+    devcontainer_json::DevContainerJson,
+    devcontainer_up::{DevContainerPlan, DevContainerUp, devcontainer_up, fetch_features},
+};
 
 /// Represents a discovered devcontainer configuration
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -40,32 +45,12 @@ impl DevContainerConfig {
     }
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct DevContainerUp {
-    _outcome: String,
-    container_id: String,
-    remote_user: String,
-    remote_workspace_folder: String,
-}
-
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct DevContainerApply {
     pub(crate) files: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub(crate) struct DevContainerConfiguration {
-    name: Option<String>,
-}
-
-#[derive(Debug, Deserialize)]
-pub(crate) struct DevContainerConfigurationOutput {
-    configuration: DevContainerConfiguration,
-}
-
 pub(crate) struct DevContainerCli {
     pub path: PathBuf,
     node_runtime_path: Option<PathBuf>,
@@ -238,32 +223,51 @@ pub fn find_configs_in_snapshot(snapshot: &Snapshot) -> Vec<DevContainerConfig>
     configs
 }
 
+/// Brings up the dev container described by `config`, or by the project's default
+/// configuration, using the container engine directly.
 pub async fn start_dev_container_with_config(
     context: DevContainerContext,
     config: Option<DevContainerConfig>,
 ) -> Result<(DevContainerConnection, String), DevContainerError> {
     check_for_docker(context.use_podman).await?;
-    let cli = ensure_devcontainer_cli(&context.node_runtime).await?;
-    let config_path = config.map(|c| context.project_directory.join(&c.config_path));
+    let config_path = match config {
+        Some(config) => context.project_directory.join(&config.config_path),
+        None => default_config_path(&context.project_directory).await?,
+    };
+    let contents = match fs::read_to_string(&config_path).await {
+        Ok(contents) => contents,
+        Err(e) => {
+            log::error!("Unable to read {}: {:?}", config_path.display(), e);
+            return Err(DevContainerError::DevContainerNotFound);
+        }
+    };
+    let plan = DevContainerPlan {
+        project_directory: context.project_directory.to_path_buf(),
+        config_path,
+        config: DevContainerJson::parse(&contents)?,
+        local_env: std::env::vars().collect(),
+    };
+    let features_context = fetch_features(
+        &plan.config,
+        &paths::devcontainer_dir()
+            .join("features")
+            .join(plan.image_tag()),
+        &context.http_client,
+    )
+    .await?;
+    let engine = CliContainerEngine {
+        use_podman: context.use_podman,
+    };
 
-    match devcontainer_up(&context, &cli, config_path.as_deref()).await {
+    match devcontainer_up(&engine, &plan, features_context.as_deref()).await {
         Ok(DevContainerUp {
             container_id,
             remote_workspace_folder,
             remote_user,
-            ..
         }) => {
-            let project_name =
-                match read_devcontainer_configuration(&context, &cli, config_path.as_deref()).await
-                {
-                    Ok(DevContainerConfigurationOutput {
-                        configuration:
-                            DevContainerConfiguration {
-                                name: Some(project_name),
-                            },
-                    }) => project_name,
-                    _ => get_backup_project_name(&remote_workspace_folder, &container_id),
-                };
+            let project_name = plan.config.name.clone().unwrap_or_else(|| {
+                get_backup_project_name(&remote_workspace_folder, &container_id)
+            });
 
             let connection = DevContainerConnection {
                 name: project_name,
@@ -281,6 +285,22 @@ pub async fn start_dev_container_with_config(
     }
 }
 
+/// The project's `.devcontainer/devcontainer.json`, or its `.devcontainer.json`.
+pub(crate) async fn default_config_path(
+    project_directory: &Path,
+) -> Result<PathBuf, DevContainerError> {
+    for config in [
+        DevContainerConfig::default_config(),
+        DevContainerConfig::root_config(),
+    ] {
+        let path = project_directory.join(&config.config_path);
+        if fs::metadata(&path).await.is_ok() {
+            return Ok(path);
+        }
+    }
+    Err(DevContainerError::DevContainerNotFound)
+}
+
 #[cfg(not(target_os = "windows"))]
 fn dev_container_cli() -> String {
     "devcontainer".to_string()
@@ -409,85 +429,6 @@ pub(crate) async fn ensure_devcontainer_cli(
     }
 }
 
-async fn devcontainer_up(
-    context: &DevContainerContext,
-    cli: &DevContainerCli,
-    config_path: Option<&Path>,
-) -> Result<DevContainerUp, DevContainerError> {
-    let mut command = cli.command(context.use_podman);
-    command.arg("up");
-    command.arg("--workspace-folder");
-    command.arg(context.project_directory.display().to_string());
-
-    if let Some(config) = config_path {
-        command.arg("--config");
-        command.arg(config.display().to_string());
-    }
-
-    log::info!("Running full devcontainer up command: {:?}", command);
-
-    match command.output().await {
-        Ok(output) => {
-            if output.status.success() {
-                let raw = String::from_utf8_lossy(&output.stdout);
-                parse_json_from_cli(&raw)
-            } else {
-                let message = format!(
-                    "Non-success status running devcontainer up for workspace: out: {}, err: {}",
-                    String::from_utf8_lossy(&output.stdout),
-                    String::from_utf8_lossy(&output.stderr)
-                );
-
-                log::error!("{}", &message);
-                Err(DevContainerError::DevContainerUpFailed(message))
-            }
-        }
-        Err(e) => {
-            let message = format!("Error running devcontainer up: {:?}", e);
-            log::error!("{}", &message);
-            Err(DevContainerError::DevContainerUpFailed(message))
-        }
-    }
-}
-
-pub(crate) async fn read_devcontainer_configuration(
-    context: &DevContainerContext,
-    cli: &DevContainerCli,
-    config_path: Option<&Path>,
-) -> Result<DevContainerConfigurationOutput, DevContainerError> {
-    let mut command = cli.command(context.use_podman);
-    command.arg("read-configuration");
-    command.arg("--workspace-folder");
-    command.arg(context.project_directory.display().to_string());
-
-    if let Some(config) = config_path {
-        command.arg("--config");
-        command.arg(config.display().to_string());
-    }
-
-    match command.output().await {
-        Ok(output) => {
-            if output.status.success() {
-                let raw = String::from_utf8_lossy(&output.stdout);
-                parse_json_from_cli(&raw)
-            } else {
-                let message = format!(
-                    "Non-success status running devcontainer read-configuration for workspace: out: {:?}, err: {:?}",
-                    String::from_utf8_lossy(&output.stdout),
-                    String::from_utf8_lossy(&output.stderr)
-                );
-                log::error!("{}", &message);
-                Err(DevContainerError::DevContainerNotFound)
-            }
-        }
-        Err(e) => {
-            let message = format!("Error running devcontainer read-configuration: {:?}", e);
-            log::error!("{}", &message);
-            Err(DevContainerError::DevContainerNotFound)
-        }
-    }
-}
-
 pub(crate) async fn apply_dev_container_template(
     template: &DevContainerTemplate,
     template_options: &HashMap<String, String>,
@@ -605,7 +546,7 @@ mod tests {
     use std::path::PathBuf;
 
     use crate::devcontainer_api::{
-        DevContainerConfig, DevContainerUp, find_configs_in_snapshot, parse_json_from_cli,
+        DevContainerApply, DevContainerConfig, find_configs_in_snapshot, parse_json_from_cli,
     };
     use fs::FakeFs;
     use gpui::TestAppContext;
@@ -623,26 +564,14 @@ mod tests {
 
     #[test]
     fn should_parse_from_devcontainer_json() {
-        let json = r#"{"outcome":"success","containerId":"826abcac45afd412abff083ab30793daff2f3c8ce2c831df728baf39933cb37a","remoteUser":"vscode","remoteWorkspaceFolder":"/workspaces/zed"}"#;
-        let up: DevContainerUp = parse_json_from_cli(json).unwrap();
-        assert_eq!(up._outcome, "success");
-        assert_eq!(
-            up.container_id,
-            "826abcac45afd412abff083ab30793daff2f3c8ce2c831df728baf39933cb37a"
-        );
-        assert_eq!(up.remote_user, "vscode");
-        assert_eq!(up.remote_workspace_folder, "/workspaces/zed");
+        let json = r#"{"files":["./.devcontainer/devcontainer.json"]}"#;
+        let apply: DevContainerApply = parse_json_from_cli(json).unwrap();
+        assert_eq!(apply.files, vec!["./.devcontainer/devcontainer.json"]);
 
         let json_in_plaintext = r#"[2026-01-22T16:19:08.802Z] @devcontainers/cli 0.80.1. Node.js v22.21.1. darwin 24.6.0 arm64.
-            {"outcome":"success","containerId":"826abcac45afd412abff083ab30793daff2f3c8ce2c831df728baf39933cb37a","remoteUser":"vscode","remoteWorkspaceFolder":"/workspaces/zed"}"#;
-        let up: DevContainerUp = parse_json_from_cli(json_in_plaintext).unwrap();
-        assert_eq!(up._outcome, "success");
-        assert_eq!(
-            up.container_id,
-            "826abcac45afd412abff083ab30793daff2f3c8ce2c831df728baf39933cb37a"
-        );
-        assert_eq!(up.remote_user, "vscode");
-        assert_eq!(up.remote_workspace_folder, "/workspaces/zed");
+            {"files":["./.devcontainer/devcontainer.json"]}"#;
+        let apply: DevContainerApply = parse_json_from_cli(json_in_plaintext).unwrap();
+        assert_eq!(apply.files, vec!["./.devcontainer/devcontainer.json"]);
     }
 
     #[gpui::test]
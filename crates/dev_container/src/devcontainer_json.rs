@@ -0,0 +1,436 @@
+//! The subset of the [devcontainer.json spec](https://containers.dev/implementors/json_reference/)
+//! that Zed implements natively.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::devcontainer_api::DevContainerError;
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DevContainerJson {
+    pub(crate) name: Option<String>,
+    pub(crate) image: Option<String>,
+    pub(crate) build: Option<BuildConfig>,
+    /// Legacy top level alternative to `build.dockerfile`.
+    pub(crate) docker_file: Option<String>,
+    pub(crate) docker_compose_file: Option<OneOrMany>,
+    pub(crate) service: Option<String>,
+    #[serde(default)]
+    pub(crate) run_services: Vec<String>,
+    pub(crate) workspace_folder: Option<String>,
+    pub(crate) workspace_mount: Option<String>,
+    #[serde(default)]
+    pub(crate) features: BTreeMap<String, FeatureOptions>,
+    #[serde(default)]
+    pub(crate) forward_ports: Vec<ForwardPort>,
+    pub(crate) post_create_command: Option<LifecycleCommand>,
+    pub(crate) remote_user: Option<String>,
+    pub(crate) container_user: Option<String>,
+    #[serde(default)]
+    pub(crate) mounts: Vec<Mount>,
+    #[serde(default)]
+    pub(crate) container_env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub(crate) remote_env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub(crate) run_args: Vec<String>,
+    pub(crate) override_command: Option<bool>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BuildConfig {
+    pub(crate) dockerfile: Option<String>,
+    pub(crate) context: Option<String>,
+    #[serde(default)]
+    pub(crate) args: BTreeMap<String, String>,
+    pub(crate) target: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    pub(crate) fn to_vec(&self) -> Vec<String> {
+        match self {
+            OneOrMany::One(value) => vec![value.clone()],
+            OneOrMany::Many(values) => values.clone(),
+        }
+    }
+}
+
+/// A feature's options: either just a version, or an object of option values.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum FeatureOptions {
+    Version(String),
+    Options(BTreeMap<String, serde_json::Value>),
+}
+
+impl FeatureOptions {
+    /// The options as the environment variables passed to the feature's `install.sh`.
+    pub(crate) fn to_env(&self) -> Vec<(String, String)> {
+        match self {
+            FeatureOptions::Version(version) => vec![("VERSION".to_string(), version.clone())],
+            FeatureOptions::Options(options) => options
+                .iter()
+                .map(|(name, value)| {
+                    let value = match value {
+                        serde_json::Value::String(value) => value.clone(),
+                        value => value.to_string(),
+                    };
+                    (feature_option_env_name(name), value)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Options are exposed to features as upper-cased environment variables, with any
+/// character that isn't valid in a variable name replaced by an underscore.
+fn feature_option_env_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum ForwardPort {
+    Port(u16),
+    /// A `"host:port"` pair, where the host is usually a compose service.
+    HostPort(String),
+}
+
+impl ForwardPort {
+    pub(crate) fn port(&self) -> Option<u16> {
+        match self {
+            ForwardPort::Port(port) => Some(*port),
+            ForwardPort::HostPort(host_port) => host_port.rsplit_once(':')?.1.parse().ok(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum LifecycleCommand {
+    /// Run with `/bin/sh -c`.
+    Shell(String),
+    /// Run directly, without a shell.
+    Exec(Vec<String>),
+    /// Named commands, which are all run.
+    Parallel(BTreeMap<String, LifecycleCommand>),
+}
+
+impl LifecycleCommand {
+    /// The argument vectors of the commands to run.
+    pub(crate) fn to_argvs(&self) -> Vec<Vec<String>> {
+        match self {
+            LifecycleCommand::Shell(command) => {
+                vec![vec!["/bin/sh".into(), "-c".into(), command.clone()]]
+            }
+            LifecycleCommand::Exec(argv) => vec![argv.clone()],
+            LifecycleCommand::Parallel(commands) => commands
+                .values()
+                .flat_map(LifecycleCommand::to_argvs)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum Mount {
+    /// A mount in the `--mount` flag syntax, e.g. `source=x,target=y,type=volume`.
+    Flag(String),
+    Object {
+        source: Option<String>,
+        target: String,
+        #[serde(rename = "type")]
+        kind: String,
+    },
+}
+
+impl Mount {
+    pub(crate) fn to_flag(&self) -> String {
+        match self {
+            Mount::Flag(flag) => flag.clone(),
+            Mount::Object {
+                source: Some(source),
+                target,
+                kind,
+            } => format!("type={kind},source={source},target={target}"),
+            Mount::Object {
+                source: None,
+                target,
+                kind,
+            } => format!("type={kind},target={target}"),
+        }
+    }
+}
+
+/// How the dev container is created.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ContainerSource {
+    Image(String),
+    Dockerfile {
+        dockerfile: PathBuf,
+        context: PathBuf,
+    },
+    Compose {
+        files: Vec<PathBuf>,
+        service: String,
+    },
+}
+
+impl DevContainerJson {
+    /// Parses the contents of a `devcontainer.json`, which may contain comments and
+    /// trailing commas.
+    pub(crate) fn parse(contents: &str) -> Result<Self, DevContainerError> {
+        settings::parse_json_with_comments(contents).map_err(|e| {
+            log::error!("Unable to parse devcontainer.json: {:?}", e);
+            DevContainerError::DevContainerParseFailed
+        })
+    }
+
+    /// Resolves how the container is created. Relative paths are resolved against
+    /// `config_dir`, the directory containing the `devcontainer.json`.
+    pub(crate) fn source(&self, config_dir: &Path) -> Result<ContainerSource, DevContainerError> {
+        if let Some(compose_files) = &self.docker_compose_file {
+            let Some(service) = self.service.clone() else {
+                log::error!("devcontainer.json uses docker compose but doesn't name a service");
+                return Err(DevContainerError::DevContainerParseFailed);
+            };
+            return Ok(ContainerSource::Compose {
+                files: compose_files
+                    .to_vec()
+                    .iter()
+                    .map(|file| config_dir.join(file))
+                    .collect(),
+                service,
+            });
+        }
+
+        let dockerfile = self
+            .build
+            .as_ref()
+            .and_then(|build| build.dockerfile.as_ref())
+            .or(self.docker_file.as_ref());
+        if let Some(dockerfile) = dockerfile {
+            let context = self
+                .build
+                .as_ref()
+                .and_then(|build| build.context.as_deref())
+                .unwrap_or(".");
+            return Ok(ContainerSource::Dockerfile {
+                dockerfile: config_dir.join(dockerfile),
+                context: config_dir.join(context),
+            });
+        }
+
+        match &self.image {
+            Some(image) => Ok(ContainerSource::Image(image.clone())),
+            None => {
+                log::error!("devcontainer.json has no image, Dockerfile or compose file");
+                Err(DevContainerError::DevContainerParseFailed)
+            }
+        }
+    }
+
+    /// The folder the project is mounted at inside the container.
+    pub(crate) fn container_workspace_folder(&self, project_directory: &Path) -> String {
+        self.workspace_folder.clone().unwrap_or_else(|| {
+            format!(
+                "/workspaces/{}",
+                project_directory
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            )
+        })
+    }
+}
+
+/// Substitutes the `${...}` variables the spec allows in string properties.
+pub(crate) fn substitute_variables(
+    value: &str,
+    project_directory: &Path,
+    container_workspace_folder: &str,
+    local_env: &HashMap<String, String>,
+) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let variable = &rest[start + 2..start + end];
+        let replacement = match variable {
+            "localWorkspaceFolder" => Some(project_directory.to_string_lossy().into_owned()),
+            "localWorkspaceFolderBasename" => project_directory
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+            "containerWorkspaceFolder" => Some(container_workspace_folder.to_string()),
+            "containerWorkspaceFolderBasename" => Path::new(container_workspace_folder)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+            _ => variable.strip_prefix("localEnv:").map(|name| {
+                let (name, default) = name.split_once(':').unwrap_or((name, ""));
+                local_env
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| default.to_string())
+            }),
+        };
+        match replacement {
+            Some(replacement) => result.push_str(&replacement),
+            None => result.push_str(&rest[start..start + end + 1]),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_devcontainer_json_with_comments() {
+        let config = DevContainerJson::parse(
+            r#"{
+                // The image to use
+                "name": "Rust",
+                "image": "mcr.microsoft.com/devcontainers/rust:1",
+                "features": {
+                    "ghcr.io/devcontainers/features/node:1": { "version": "lts", "nodeGypDependencies": false },
+                    "ghcr.io/devcontainers/features/go:1": "1.22",
+                },
+                "forwardPorts": [3000, "db:5432"],
+                "postCreateCommand": { "deps": "cargo fetch", "tools": ["rustup", "update"] },
+                "mounts": [
+                    "source=cargo-cache,target=/usr/local/cargo,type=volume",
+                    { "source": "/tmp", "target": "/host-tmp", "type": "bind" },
+                ],
+                "remoteUser": "vscode",
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.name.as_deref(), Some("Rust"));
+        assert_eq!(
+            config.source(Path::new("/project/.devcontainer")).unwrap(),
+            ContainerSource::Image("mcr.microsoft.com/devcontainers/rust:1".to_string())
+        );
+        assert_eq!(
+            config.features["ghcr.io/devcontainers/features/node:1"].to_env(),
+            vec![
+                ("NODEGYPDEPENDENCIES".to_string(), "false".to_string()),
+                ("VERSION".to_string(), "lts".to_string()),
+            ]
+        );
+        assert_eq!(
+            config.features["ghcr.io/devcontainers/features/go:1"].to_env(),
+            vec![("VERSION".to_string(), "1.22".to_string())]
+        );
+        assert_eq!(
+            config
+                .forward_ports
+                .iter()
+                .map(ForwardPort::port)
+                .collect::<Vec<_>>(),
+            vec![Some(3000), Some(5432)]
+        );
+        assert_eq!(
+            config.post_create_command.unwrap().to_argvs(),
+            vec![
+                vec!["/bin/sh".to_string(), "-c".into(), "cargo fetch".into()],
+                vec!["rustup".to_string(), "update".into()],
+            ]
+        );
+        assert_eq!(
+            config.mounts.iter().map(Mount::to_flag).collect::<Vec<_>>(),
+            vec![
+                "source=cargo-cache,target=/usr/local/cargo,type=volume",
+                "type=bind,source=/tmp,target=/host-tmp",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_container_source() {
+        let config_dir = Path::new("/project/.devcontainer");
+
+        let config = DevContainerJson::parse(
+            r#"{ "build": { "dockerfile": "Dockerfile", "context": ".." } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.source(config_dir).unwrap(),
+            ContainerSource::Dockerfile {
+                dockerfile: config_dir.join("Dockerfile"),
+                context: config_dir.join(".."),
+            }
+        );
+
+        let config = DevContainerJson::parse(
+            r#"{ "dockerComposeFile": ["compose.yml", "compose.dev.yml"], "service": "app" }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.source(config_dir).unwrap(),
+            ContainerSource::Compose {
+                files: vec![
+                    config_dir.join("compose.yml"),
+                    config_dir.join("compose.dev.yml")
+                ],
+                service: "app".to_string(),
+            }
+        );
+
+        let config = DevContainerJson::parse(r#"{ "dockerComposeFile": "compose.yml" }"#).unwrap();
+        assert!(config.source(config_dir).is_err());
+        assert!(DevContainerJson::default().source(config_dir).is_err());
+    }
+
+    #[test]
+    fn test_substitute_variables() {
+        let local_env = HashMap::from_iter([("HOME".to_string(), "/home/me".to_string())]);
+        assert_eq!(
+            substitute_variables(
+                "source=${localEnv:HOME}/.ssh,target=${containerWorkspaceFolder}/.ssh,other=${localEnv:MISSING:fallback}",
+                Path::new("/code/zed"),
+                "/workspaces/zed",
+                &local_env,
+            ),
+            "source=/home/me/.ssh,target=/workspaces/zed/.ssh,other=fallback"
+        );
+        assert_eq!(
+            substitute_variables(
+                "${localWorkspaceFolderBasename} ${unknown} ${unterminated",
+                Path::new("/code/zed"),
+                "/workspaces/zed",
+                &local_env,
+            ),
+            "zed ${unknown} ${unterminated"
+        );
+    }
+}
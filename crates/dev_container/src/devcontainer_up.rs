@@ -0,0 +1,755 @@
+//! Brings dev containers up without the `devcontainer` CLI, by generating the
+//! container engine commands for a parsed `devcontainer.json`.
+
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash as _, Hasher as _},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use http::Request;
+use http_client::{AsyncBody, HttpClient};
+use smol::fs;
+
+use crate::{
+    DockerManifestsResponse, GithubTokenResponse,
+    container_engine::ContainerEngine,
+    devcontainer_api::DevContainerError,
+    devcontainer_json::{ContainerSource, DevContainerJson, FeatureOptions, substitute_variables},
+    get_deserialized_response,
+};
+
+const CONTAINER_FEATURES_DIR: &str = "/tmp/zed-dev-container-features";
+const LOCAL_FOLDER_LABEL: &str = "devcontainer.local_folder";
+const CONFIG_FILE_LABEL: &str = "devcontainer.config_file";
+
+/// Keeps the container alive when the image's command would exit immediately.
+const KEEP_ALIVE_SCRIPT: &str =
+    "echo Container started\ntrap \"exit 0\" 15\nwhile sleep 1 & wait $!; do :; done";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DevContainerUp {
+    pub(crate) container_id: String,
+    pub(crate) remote_user: String,
+    pub(crate) remote_workspace_folder: String,
+}
+
+/// A feature downloaded into the directory named after its index in the features
+/// build context.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ResolvedFeature {
+    pub(crate) id: String,
+    pub(crate) options: FeatureOptions,
+}
+
+/// The engine commands needed to bring up the dev container described by a
+/// `devcontainer.json`.
+pub(crate) struct DevContainerPlan {
+    pub(crate) project_directory: PathBuf,
+    /// The absolute path of the `devcontainer.json`.
+    pub(crate) config_path: PathBuf,
+    pub(crate) config: DevContainerJson,
+    pub(crate) local_env: HashMap<String, String>,
+}
+
+impl DevContainerPlan {
+    fn config_dir(&self) -> &Path {
+        self.config_path.parent().unwrap_or(&self.project_directory)
+    }
+
+    fn workspace_folder(&self) -> String {
+        self.substitute(
+            &self
+                .config
+                .container_workspace_folder(&self.project_directory),
+        )
+    }
+
+    fn substitute(&self, value: &str) -> String {
+        let workspace_folder = self
+            .config
+            .container_workspace_folder(&self.project_directory);
+        substitute_variables(
+            value,
+            &self.project_directory,
+            &workspace_folder,
+            &self.local_env,
+        )
+    }
+
+    /// The tag of the images built for this dev container.
+    pub(crate) fn image_tag(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.config_path.hash(&mut hasher);
+        let basename = self
+            .project_directory
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            .collect::<String>();
+        format!("zed-dev-container-{basename}-{:x}", hasher.finish())
+    }
+
+    fn label_filters(&self) -> Vec<String> {
+        vec![
+            "--filter".into(),
+            format!(
+                "label={LOCAL_FOLDER_LABEL}={}",
+                self.project_directory.display()
+            ),
+            "--filter".into(),
+            format!("label={CONFIG_FILE_LABEL}={}", self.config_path.display()),
+        ]
+    }
+
+    /// Lists the containers previously created for this configuration, as
+    /// `<id> <state>` lines.
+    pub(crate) fn find_container_args(&self) -> Vec<String> {
+        let mut args = vec!["ps".into(), "-a".into()];
+        args.extend(self.label_filters());
+        args.extend(["--format".into(), "{{.ID}} {{.State}}".into()]);
+        args
+    }
+
+    pub(crate) fn build_args(&self, dockerfile: &Path, context: &Path) -> Vec<String> {
+        let mut args = vec![
+            "build".into(),
+            "-f".into(),
+            dockerfile.display().to_string(),
+            "-t".into(),
+            self.image_tag(),
+        ];
+        if let Some(build) = &self.config.build {
+            for (name, value) in &build.args {
+                args.push("--build-arg".into());
+                args.push(format!("{name}={}", self.substitute(value)));
+            }
+            if let Some(target) = &build.target {
+                args.push("--target".into());
+                args.push(target.clone());
+            }
+        }
+        args.push(context.display().to_string());
+        args
+    }
+
+    /// Builds an image installing the configuration's features on top of `base_image`,
+    /// from a context prepared by [`fetch_features`].
+    pub(crate) fn features_build_args(
+        &self,
+        base_image: &str,
+        base_image_user: &str,
+        features_context: &Path,
+    ) -> Vec<String> {
+        vec![
+            "build".into(),
+            "-f".into(),
+            features_context.join("Dockerfile").display().to_string(),
+            "-t".into(),
+            format!("{}-features", self.image_tag()),
+            "--build-arg".into(),
+            format!("_DEV_CONTAINERS_BASE_IMAGE={base_image}"),
+            "--build-arg".into(),
+            format!("_DEV_CONTAINERS_IMAGE_USER={base_image_user}"),
+            features_context.display().to_string(),
+        ]
+    }
+
+    pub(crate) fn run_args(&self, image: &str) -> Vec<String> {
+        let workspace_mount = match &self.config.workspace_mount {
+            Some(mount) => self.substitute(mount),
+            None => format!(
+                "type=bind,source={},target={}",
+                self.project_directory.display(),
+                self.workspace_folder()
+            ),
+        };
+        let mut args = vec![
+            "run".into(),
+            "-d".into(),
+            "--label".into(),
+            format!("{LOCAL_FOLDER_LABEL}={}", self.project_directory.display()),
+            "--label".into(),
+            format!("{CONFIG_FILE_LABEL}={}", self.config_path.display()),
+            "--mount".into(),
+            workspace_mount,
+        ];
+        for port in self
+            .config
+            .forward_ports
+            .iter()
+            .filter_map(|port| port.port())
+        {
+            args.push("-p".into());
+            args.push(format!("127.0.0.1:{port}:{port}"));
+        }
+        for mount in &self.config.mounts {
+            args.push("--mount".into());
+            args.push(self.substitute(&mount.to_flag()));
+        }
+        for (name, value) in &self.config.container_env {
+            args.push("-e".into());
+            args.push(format!("{name}={}", self.substitute(value)));
+        }
+        if let Some(user) = &self.config.container_user {
+            args.push("-u".into());
+            args.push(user.clone());
+        }
+        args.extend(self.config.run_args.iter().map(|arg| self.substitute(arg)));
+        if self.config.override_command.unwrap_or(true) {
+            args.extend([
+                "--entrypoint".into(),
+                "/bin/sh".into(),
+                image.to_string(),
+                "-c".into(),
+                KEEP_ALIVE_SCRIPT.into(),
+            ]);
+        } else {
+            args.push(image.to_string());
+        }
+        args
+    }
+
+    fn compose_project_name(&self) -> String {
+        let basename = self
+            .project_directory
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+            .collect::<String>();
+        format!("{basename}_devcontainer")
+    }
+
+    fn compose_args(&self, files: &[PathBuf]) -> Vec<String> {
+        let mut args = vec!["compose".into(), "-p".into(), self.compose_project_name()];
+        for file in files {
+            args.push("-f".into());
+            args.push(file.display().to_string());
+        }
+        args
+    }
+
+    pub(crate) fn compose_up_args(&self, files: &[PathBuf], service: &str) -> Vec<String> {
+        let mut args = self.compose_args(files);
+        args.extend(["up".into(), "-d".into(), service.to_string()]);
+        args.extend(self.config.run_services.iter().cloned());
+        args
+    }
+
+    pub(crate) fn compose_container_args(&self, files: &[PathBuf], service: &str) -> Vec<String> {
+        let mut args = self.compose_args(files);
+        args.extend(["ps".into(), "-q".into(), service.to_string()]);
+        args
+    }
+
+    /// The `exec` invocations running the `postCreateCommand`.
+    pub(crate) fn post_create_args(
+        &self,
+        container_id: &str,
+        remote_user: &str,
+    ) -> Vec<Vec<String>> {
+        let Some(command) = &self.config.post_create_command else {
+            return Vec::new();
+        };
+        command
+            .to_argvs()
+            .into_iter()
+            .map(|argv| {
+                let mut args = vec![
+                    "exec".into(),
+                    "-u".into(),
+                    remote_user.to_string(),
+                    "-w".into(),
+                    self.workspace_folder(),
+                ];
+                for (name, value) in &self.config.remote_env {
+                    args.push("-e".into());
+                    args.push(format!("{name}={}", self.substitute(value)));
+                }
+                args.push(container_id.to_string());
+                args.extend(argv);
+                args
+            })
+            .collect()
+    }
+}
+
+/// The Dockerfile installing `features` on top of the base image passed as a build
+/// argument, restoring the image's user afterwards.
+pub(crate) fn features_dockerfile(
+    features: &[ResolvedFeature],
+    remote_user: Option<&str>,
+    container_user: Option<&str>,
+) -> String {
+    let user_or_image_user =
+        |user: Option<&str>| user.map_or("$_DEV_CONTAINERS_IMAGE_USER".to_string(), shell_quote);
+    let mut dockerfile = String::from(
+        "ARG _DEV_CONTAINERS_BASE_IMAGE=placeholder\n\
+         FROM $_DEV_CONTAINERS_BASE_IMAGE\n\
+         ARG _DEV_CONTAINERS_IMAGE_USER=root\n\
+         USER root\n",
+    );
+    for (ix, feature) in features.iter().enumerate() {
+        let dir = format!("{CONTAINER_FEATURES_DIR}/{ix}");
+        let env = feature
+            .options
+            .to_env()
+            .into_iter()
+            .map(|(name, value)| format!("{name}={}", shell_quote(&value)))
+            .chain([
+                format!("_REMOTE_USER={}", user_or_image_user(remote_user)),
+                format!("_CONTAINER_USER={}", user_or_image_user(container_user)),
+            ])
+            .collect::<Vec<_>>()
+            .join(" ");
+        dockerfile.push_str(&format!(
+            "# {}\nCOPY {ix} {dir}\nRUN cd {dir} && chmod +x ./install.sh && env {env} ./install.sh\n",
+            feature.id
+        ));
+    }
+    dockerfile.push_str("USER $_DEV_CONTAINERS_IMAGE_USER\n");
+    dockerfile
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Creates the dev container, or starts the one previously created for this
+/// configuration, running the `postCreateCommand` when a container is created.
+///
+/// `features_context` is the build context prepared by [`fetch_features`], if the
+/// configuration uses any features.
+pub(crate) async fn devcontainer_up(
+    engine: &dyn ContainerEngine,
+    plan: &DevContainerPlan,
+    features_context: Option<&Path>,
+) -> Result<DevContainerUp, DevContainerError> {
+    let (container_id, created) = match plan.config.source(plan.config_dir())? {
+        ContainerSource::Compose { files, service } => {
+            if features_context.is_some() {
+                return Err(DevContainerError::DevContainerUpFailed(
+                    "Dev container features aren't supported with Docker Compose yet".into(),
+                ));
+            }
+            let existing = engine
+                .run(&plan.compose_container_args(&files, &service))
+                .await?;
+            engine.run(&plan.compose_up_args(&files, &service)).await?;
+            let container_id = engine
+                .run(&plan.compose_container_args(&files, &service))
+                .await?;
+            if container_id.is_empty() {
+                return Err(DevContainerError::DevContainerUpFailed(format!(
+                    "No container was started for the {service} service"
+                )));
+            }
+            (container_id, existing.is_empty())
+        }
+        source => {
+            let existing = engine.run(&plan.find_container_args()).await?;
+            if let Some((container_id, state)) = existing
+                .lines()
+                .next()
+                .and_then(|line| line.split_once(' '))
+            {
+                if !state.eq_ignore_ascii_case("running") {
+                    engine
+                        .run(&["start".into(), container_id.to_string()])
+                        .await?;
+                }
+                (container_id.to_string(), false)
+            } else {
+                let mut image = match source {
+                    ContainerSource::Image(image) => {
+                        engine.run(&["pull".into(), image.clone()]).await?;
+                        image
+                    }
+                    ContainerSource::Dockerfile {
+                        dockerfile,
+                        context,
+                    } => {
+                        engine.run(&plan.build_args(&dockerfile, &context)).await?;
+                        plan.image_tag()
+                    }
+                    ContainerSource::Compose { .. } => unreachable!(),
+                };
+                if let Some(features_context) = features_context {
+                    let image_user = engine
+                        .run(&[
+                            "image".into(),
+                            "inspect".into(),
+                            "--format".into(),
+                            "{{.Config.User}}".into(),
+                            image.clone(),
+                        ])
+                        .await?;
+                    let image_user = if image_user.is_empty() {
+                        "root"
+                    } else {
+                        &image_user
+                    };
+                    engine
+                        .run(&plan.features_build_args(&image, image_user, features_context))
+                        .await?;
+                    image = format!("{}-features", plan.image_tag());
+                }
+                (engine.run(&plan.run_args(&image)).await?, true)
+            }
+        }
+    };
+
+    let remote_user = match plan
+        .config
+        .remote_user
+        .clone()
+        .or_else(|| plan.config.container_user.clone())
+    {
+        Some(user) => user,
+        None => {
+            let user = engine
+                .run(&[
+                    "inspect".into(),
+                    "--format".into(),
+                    "{{.Config.User}}".into(),
+                    container_id.clone(),
+                ])
+                .await?;
+            if user.is_empty() {
+                "root".to_string()
+            } else {
+                user
+            }
+        }
+    };
+
+    if created {
+        for args in plan.post_create_args(&container_id, &remote_user) {
+            engine.run(&args).await?;
+        }
+    }
+
+    Ok(DevContainerUp {
+        container_id,
+        remote_user,
+        remote_workspace_folder: plan.workspace_folder(),
+    })
+}
+
+/// Downloads the configuration's features into a build context under `context_dir`,
+/// next to the Dockerfile installing them. Returns `None` if there are no features.
+pub(crate) async fn fetch_features(
+    config: &DevContainerJson,
+    context_dir: &Path,
+    client: &Arc<dyn HttpClient>,
+) -> Result<Option<PathBuf>, DevContainerError> {
+    if config.features.is_empty() {
+        return Ok(None);
+    }
+
+    let fetch_failed = |message: String| {
+        log::error!("{}", &message);
+        DevContainerError::DevContainerUpFailed(message)
+    };
+    if fs::metadata(context_dir).await.is_ok() {
+        fs::remove_dir_all(context_dir)
+            .await
+            .map_err(|e| fetch_failed(format!("Unable to clear features directory: {e}")))?;
+    }
+
+    let mut features = Vec::new();
+    for (ix, (id, options)) in config.features.iter().enumerate() {
+        let Some(reference) = FeatureReference::parse(id) else {
+            return Err(fetch_failed(format!(
+                "Unsupported dev container feature {id}, only features published to an OCI registry are supported"
+            )));
+        };
+        let feature_dir = context_dir.join(ix.to_string());
+        fs::create_dir_all(&feature_dir)
+            .await
+            .map_err(|e| fetch_failed(format!("Unable to create features directory: {e}")))?;
+        download_feature(&reference, &feature_dir, client)
+            .await
+            .map_err(|e| fetch_failed(format!("Unable to download feature {id}: {e}")))?;
+        features.push(ResolvedFeature {
+            id: id.clone(),
+            options: options.clone(),
+        });
+    }
+
+    let dockerfile = features_dockerfile(
+        &features,
+        config.remote_user.as_deref(),
+        config.container_user.as_deref(),
+    );
+    fs::write(context_dir.join("Dockerfile"), dockerfile)
+        .await
+        .map_err(|e| fetch_failed(format!("Unable to write features Dockerfile: {e}")))?;
+    Ok(Some(context_dir.to_path_buf()))
+}
+
+/// A reference to a feature published to an OCI registry, e.g.
+/// `ghcr.io/devcontainers/features/node:1`.
+#[derive(Debug, PartialEq, Eq)]
+struct FeatureReference {
+    registry: String,
+    repository: String,
+    /// A tag or a digest.
+    reference: String,
+}
+
+impl FeatureReference {
+    fn parse(id: &str) -> Option<Self> {
+        let (registry, rest) = id.split_once('/')?;
+        if !registry.contains('.') && !registry.contains(':') {
+            return None;
+        }
+        let (repository, reference) = if let Some((repository, digest)) = rest.split_once('@') {
+            (repository, digest)
+        } else if let Some((repository, tag)) = rest.rsplit_once(':') {
+            (repository, tag)
+        } else {
+            (rest, "latest")
+        };
+        Some(Self {
+            registry: registry.to_string(),
+            repository: repository.to_string(),
+            reference: reference.to_string(),
+        })
+    }
+}
+
+async fn download_feature(
+    feature: &FeatureReference,
+    dir: &Path,
+    client: &Arc<dyn HttpClient>,
+) -> Result<(), String> {
+    let token: GithubTokenResponse = get_deserialized_response(
+        "",
+        &format!(
+            "https://{}/token?service={}&scope=repository:{}:pull",
+            feature.registry, feature.registry, feature.repository
+        ),
+        client,
+    )
+    .await?;
+    let manifest: DockerManifestsResponse = get_deserialized_response(
+        &token.token,
+        &format!(
+            "https://{}/v2/{}/manifests/{}",
+            feature.registry, feature.repository, feature.reference
+        ),
+        client,
+    )
+    .await?;
+    let layer = manifest.layers.first().ok_or("feature has no layers")?;
+
+    let request = Request::get(format!(
+        "https://{}/v2/{}/blobs/{}",
+        feature.registry, feature.repository, layer.digest
+    ))
+    .header("Authorization", format!("Bearer {}", token.token))
+    .body(AsyncBody::default())
+    .map_err(|e| format!("Failed to create request: {}", e))?;
+    let response = client
+        .send(request)
+        .await
+        .map_err(|e| format!("Failed to send request: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to download feature: {}", response.status()));
+    }
+    async_tar::Archive::new(response.into_body())
+        .unpack(dir)
+        .await
+        .map_err(|e| format!("Failed to extract feature: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container_engine::FakeContainerEngine;
+
+    fn plan(config: &str) -> DevContainerPlan {
+        DevContainerPlan {
+            project_directory: PathBuf::from("/code/zed"),
+            config_path: PathBuf::from("/code/zed/.devcontainer/devcontainer.json"),
+            config: DevContainerJson::parse(config).unwrap(),
+            local_env: HashMap::from_iter([("HOME".to_string(), "/home/me".to_string())]),
+        }
+    }
+
+    #[test]
+    fn test_feature_reference() {
+        assert_eq!(
+            FeatureReference::parse("ghcr.io/devcontainers/features/node:1"),
+            Some(FeatureReference {
+                registry: "ghcr.io".into(),
+                repository: "devcontainers/features/node".into(),
+                reference: "1".into(),
+            })
+        );
+        assert_eq!(
+            FeatureReference::parse("localhost:5000/features/go@sha256:abc"),
+            Some(FeatureReference {
+                registry: "localhost:5000".into(),
+                repository: "features/go".into(),
+                reference: "sha256:abc".into(),
+            })
+        );
+        assert_eq!(FeatureReference::parse("./local-feature"), None);
+    }
+
+    #[test]
+    fn test_features_dockerfile() {
+        let features = [ResolvedFeature {
+            id: "ghcr.io/devcontainers/features/node:1".into(),
+            options: FeatureOptions::Version("it's lts".into()),
+        }];
+        assert_eq!(
+            features_dockerfile(&features, Some("vscode"), None),
+            "ARG _DEV_CONTAINERS_BASE_IMAGE=placeholder\n\
+             FROM $_DEV_CONTAINERS_BASE_IMAGE\n\
+             ARG _DEV_CONTAINERS_IMAGE_USER=root\n\
+             USER root\n\
+             # ghcr.io/devcontainers/features/node:1\n\
+             COPY 0 /tmp/zed-dev-container-features/0\n\
+             RUN cd /tmp/zed-dev-container-features/0 && chmod +x ./install.sh && env VERSION='it'\\''s lts' _REMOTE_USER='vscode' _CONTAINER_USER=$_DEV_CONTAINERS_IMAGE_USER ./install.sh\n\
+             USER $_DEV_CONTAINERS_IMAGE_USER\n"
+        );
+    }
+
+    #[test]
+    fn test_up_image_with_features() {
+        let plan = plan(
+            r#"{
+                "image": "mcr.microsoft.com/devcontainers/base:ubuntu",
+                "features": { "ghcr.io/devcontainers/features/node:1": {} },
+                "forwardPorts": [3000],
+                "mounts": ["source=${localEnv:HOME}/.cargo,target=/cargo,type=bind"],
+                "containerEnv": { "PROJECT": "${localWorkspaceFolderBasename}" },
+                "runArgs": ["--cap-add=SYS_PTRACE"],
+                "postCreateCommand": "cargo fetch",
+            }"#,
+        );
+        let tag = plan.image_tag();
+        let engine = FakeContainerEngine::new(&[
+            ("image inspect", "vscode"),
+            ("run ", "abc123"),
+            ("inspect", ""),
+        ]);
+
+        let up = smol::block_on(devcontainer_up(
+            &engine,
+            &plan,
+            Some(Path::new("/data/features")),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            up,
+            DevContainerUp {
+                container_id: "abc123".into(),
+                remote_user: "root".into(),
+                remote_workspace_folder: "/workspaces/zed".into(),
+            }
+        );
+        assert_eq!(
+            engine.commands(),
+            vec![
+                "ps -a --filter label=devcontainer.local_folder=/code/zed --filter label=devcontainer.config_file=/code/zed/.devcontainer/devcontainer.json --format {{.ID}} {{.State}}".to_string(),
+                "pull mcr.microsoft.com/devcontainers/base:ubuntu".to_string(),
+                "image inspect --format {{.Config.User}} mcr.microsoft.com/devcontainers/base:ubuntu".to_string(),
+                format!("build -f /data/features/Dockerfile -t {tag}-features --build-arg _DEV_CONTAINERS_BASE_IMAGE=mcr.microsoft.com/devcontainers/base:ubuntu --build-arg _DEV_CONTAINERS_IMAGE_USER=vscode /data/features"),
+                format!("run -d --label devcontainer.local_folder=/code/zed --label devcontainer.config_file=/code/zed/.devcontainer/devcontainer.json --mount type=bind,source=/code/zed,target=/workspaces/zed -p 127.0.0.1:3000:3000 --mount source=/home/me/.cargo,target=/cargo,type=bind -e PROJECT=zed --cap-add=SYS_PTRACE --entrypoint /bin/sh {tag}-features -c {KEEP_ALIVE_SCRIPT}"),
+                "inspect --format {{.Config.User}} abc123".to_string(),
+                "exec -u root -w /workspaces/zed abc123 /bin/sh -c cargo fetch".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_up_dockerfile() {
+        let plan = plan(
+            r#"{
+                "build": { "dockerfile": "Dockerfile", "context": "..", "args": { "VARIANT": "bookworm" } },
+                "workspaceFolder": "/src",
+                "remoteUser": "dev",
+                "overrideCommand": false,
+            }"#,
+        );
+        let tag = plan.image_tag();
+        let engine = FakeContainerEngine::new(&[("run ", "abc123")]);
+
+        let up = smol::block_on(devcontainer_up(&engine, &plan, None)).unwrap();
+
+        assert_eq!(up.remote_user, "dev");
+        assert_eq!(up.remote_workspace_folder, "/src");
+        assert_eq!(
+            engine.commands()[1..],
+            [
+                format!(
+                    "build -f /code/zed/.devcontainer/Dockerfile -t {tag} --build-arg VARIANT=bookworm /code/zed/.devcontainer/.."
+                ),
+                format!(
+                    "run -d --label devcontainer.local_folder=/code/zed --label devcontainer.config_file=/code/zed/.devcontainer/devcontainer.json --mount type=bind,source=/code/zed,target=/src {tag}"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_up_reuses_existing_container() {
+        let plan = plan(
+            r#"{ "image": "ubuntu", "remoteUser": "dev", "postCreateCommand": "make setup" }"#,
+        );
+        let engine = FakeContainerEngine::new(&[("ps ", "abc123 exited\n")]);
+
+        let up = smol::block_on(devcontainer_up(&engine, &plan, None)).unwrap();
+
+        assert_eq!(up.container_id, "abc123");
+        assert_eq!(
+            engine.commands()[1..],
+            ["start abc123".to_string()],
+            "existing containers are started without running the postCreateCommand"
+        );
+    }
+
+    #[test]
+    fn test_up_compose() {
+        let plan = plan(
+            r#"{
+                "dockerComposeFile": ["../compose.yml", "compose.dev.yml"],
+                "service": "app",
+                "runServices": ["db"],
+                "workspaceFolder": "/workspace",
+                "remoteUser": "dev",
+                "postCreateCommand": ["npm", "install"],
+                "remoteEnv": { "EDITOR": "zed" },
+            }"#,
+        );
+        let engine = FakeContainerEngine::new(&[]);
+        assert!(smol::block_on(devcontainer_up(&engine, &plan, None)).is_err());
+
+        let compose = "compose -p zed_devcontainer -f /code/zed/.devcontainer/../compose.yml -f /code/zed/.devcontainer/compose.dev.yml";
+        let engine =
+            FakeContainerEngine::new(&[(&format!("{compose} up"), ""), ("compose", "def456")]);
+
+        let up = smol::block_on(devcontainer_up(&engine, &plan, None)).unwrap();
+
+        assert_eq!(up.container_id, "def456");
+        assert_eq!(
+            engine.commands(),
+            vec![
+                format!("{compose} ps -q app"),
+                format!("{compose} up -d app db"),
+                format!("{compose} ps -q app"),
+            ],
+            "the container already existed, so the postCreateCommand isn't run"
+        );
+    }
+}
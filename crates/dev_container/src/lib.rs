@@ -41,10 +41,13 @@ use futures::AsyncReadExt;
 use http::Request;
 use http_client::{AsyncBody, HttpClient};
 
+mod container_engine;
 mod devcontainer_api;
+mod devcontainer_json;
+mod devcontainer_up;
 
+use devcontainer_api::default_config_path;
 use devcontainer_api::ensure_devcontainer_cli;
-use devcontainer_api::read_devcontainer_configuration;
 
 use crate::devcontainer_api::DevContainerError;
 use crate::devcontainer_api::apply_dev_container_template;
@@ -58,6 +61,7 @@ pub struct DevContainerContext {
     pub project_directory: Arc<Path>,
     pub use_podman: bool,
     pub node_runtime: node_runtime::NodeRuntime,
+    pub http_client: Arc<dyn HttpClient>,
 }
 
 impl DevContainerContext {
@@ -69,6 +73,7 @@ impl DevContainerContext {
             project_directory,
             use_podman,
             node_runtime,
+            http_client: cx.http_client(),
         })
     }
 }
@@ -1480,7 +1485,7 @@ fn dispatch_apply_templates(
 
         {
             if check_for_existing
-                && read_devcontainer_configuration(&context, &cli, None)
+                && default_config_path(&context.project_directory)
                     .await
                     .is_ok()
             {
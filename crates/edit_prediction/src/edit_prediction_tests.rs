@@ -18,6 +18,7 @@ use gpui::{
 };
 use indoc::indoc;
 use language::{Anchor, Buffer, CursorShape, Operation, Point, Selection, SelectionGoal};
+use language_model::{LANGUAGE_MODEL_USAGE, LanguageModelUsageFeature};
 use lsp::LanguageServerId;
 use parking_lot::Mutex;
 use pretty_assertions::{assert_eq, assert_matches};
@@ -2047,6 +2048,95 @@ async fn test_edit_prediction_no_spurious_trailing_newline(cx: &mut TestAppConte
     });
 }
 
+#[gpui::test]
+async fn test_fim_prediction_records_usage(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings(cx, |settings| {
+                let edit_predictions = settings
+                    .project
+                    .all_languages
+                    .edit_predictions
+                    .get_or_insert_default();
+                edit_predictions.provider = Some(EditPredictionProvider::OpenAiCompatibleApi);
+                edit_predictions.open_ai_compatible_api =
+                    Some(settings::CustomEditPredictionProviderSettingsContent {
+                        api_url: Some("http://localhost:8080/v1/completions".into()),
+                        model: Some("fim-model".into()),
+                        ..Default::default()
+                    });
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/project"),
+        json!({ "main.rs": "fn main() {\n    \n}\n" }),
+    )
+    .await;
+    let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+    let (ep_store, _) = make_test_ep_store(&project, cx).await;
+    ep_store.update(cx, |ep_store, _| {
+        ep_store.set_edit_prediction_model(EditPredictionModel::Fim {
+            format: EditPredictionPromptFormat::Qwen,
+        })
+    });
+    cx.update(|cx| {
+        cx.set_http_client(FakeHttpClient::create(|_| async move {
+            Ok(http_client::Response::builder()
+                .status(200)
+                .body(
+                    json!({
+                        "id": "fim-request",
+                        "object": "text_completion",
+                        "created": 0,
+                        "model": "fim-model",
+                        "choices": [{ "text": "println!();", "finish_reason": "stop" }],
+                        "usage": {
+                            "prompt_tokens": 120,
+                            "completion_tokens": 4,
+                            "total_tokens": 124
+                        }
+                    })
+                    .to_string()
+                    .into(),
+                )
+                .unwrap())
+        }))
+    });
+    LANGUAGE_MODEL_USAGE.clear_usage().await.unwrap();
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            let path = project
+                .find_project_path(path!("/project/main.rs"), cx)
+                .unwrap();
+            project.open_buffer(path, cx)
+        })
+        .await
+        .unwrap();
+    let prediction = run_edit_prediction(&buffer, &project, &ep_store, cx).await;
+    assert_eq!(prediction.edits.len(), 1);
+    cx.run_until_parked();
+
+    let summaries = LANGUAGE_MODEL_USAGE.usage_summaries(1).unwrap();
+    assert_eq!(summaries.len(), 1);
+    let summary = &summaries[0];
+    assert_eq!(summary.provider.0.as_ref(), "open_ai_compatible_api");
+    assert_eq!(summary.model.0.as_ref(), "fim-model");
+    assert_eq!(
+        summary.feature,
+        Some(LanguageModelUsageFeature::EditPrediction)
+    );
+    assert_eq!(summary.requests, 1);
+    assert_eq!(
+        (summary.usage.input_tokens, summary.usage.output_tokens),
+        (120, 4)
+    );
+}
+
 fn init_test(cx: &mut TestAppContext) {
     cx.update(|cx| {
         let settings_store = SettingsStore::test(cx);
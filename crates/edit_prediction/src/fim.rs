@@ -1,6 +1,6 @@
 use crate::{
-    EditPredictionId, EditPredictionModelInput, cursor_excerpt, prediction::EditPredictionResult,
-    zeta,
+    EditPredictionId, EditPredictionModelInput, cursor_excerpt, ollama,
+    prediction::EditPredictionResult, zeta,
 };
 use anyhow::{Context as _, Result, anyhow};
 use gpui::{App, AppContext as _, Entity, Task};
@@ -8,11 +8,16 @@ use language::{
     Anchor, Buffer, BufferSnapshot, OffsetRangeExt as _, ToOffset, ToPoint as _,
     language_settings::all_language_settings,
 };
+use language_model::{
+    LanguageModelId, LanguageModelProviderId, LanguageModelUsageFeature, LanguageModelUsageTracker,
+};
 use settings::EditPredictionPromptFormat;
 use std::{path::Path, sync::Arc, time::Instant};
 use zeta_prompt::ZetaPromptInput;
 
 const FIM_CONTEXT_TOKENS: usize = 512;
+const OPEN_AI_COMPATIBLE_PROVIDER_ID: LanguageModelProviderId =
+    LanguageModelProviderId::new("open_ai_compatible_api");
 
 struct FimRequestOutput {
     request_id: String,
@@ -26,6 +31,7 @@ struct FimRequestOutput {
 
 pub fn request_prediction(
     EditPredictionModelInput {
+        project,
         buffer,
         snapshot,
         position,
@@ -48,15 +54,26 @@ pub fn request_prediction(
     let cursor_point = position.to_point(&snapshot);
     let buffer_snapshotted_at = Instant::now();
 
-    let Some(settings) = (match provider {
-        settings::EditPredictionProvider::Ollama => settings.ollama.clone(),
-        settings::EditPredictionProvider::OpenAiCompatibleApi => {
-            settings.open_ai_compatible_api.clone()
-        }
+    let Some((settings, provider_id)) = (match provider {
+        settings::EditPredictionProvider::Ollama => settings
+            .ollama
+            .clone()
+            .map(|settings| (settings, ollama::PROVIDER_ID)),
+        settings::EditPredictionProvider::OpenAiCompatibleApi => settings
+            .open_ai_compatible_api
+            .clone()
+            .map(|settings| (settings, OPEN_AI_COMPATIBLE_PROVIDER_ID)),
         _ => None,
     }) else {
         return Task::ready(Err(anyhow!("Unsupported edit prediction provider for FIM")));
     };
+    let usage_tracker = LanguageModelUsageTracker::new(
+        provider_id,
+        LanguageModelId::from(settings.model.clone()),
+        LanguageModelUsageFeature::EditPrediction,
+        project.read(cx).display_name(cx),
+        cx,
+    );
 
     let result = cx.background_spawn(async move {
         let (excerpt_range, _) = cursor_excerpt::editable_and_context_ranges_for_cursor_position(
@@ -92,7 +109,7 @@ pub fn request_prediction(
         let stop_tokens = get_fim_stop_tokens();
 
         let max_tokens = settings.max_output_tokens;
-        let (response_text, request_id) = zeta::send_custom_server_request(
+        let (response_text, request_id, usage) = zeta::send_custom_server_request(
             provider,
             &settings,
             prompt,
@@ -101,6 +118,7 @@ pub fn request_prediction(
             &http_client,
         )
         .await?;
+        usage_tracker.update(usage);
 
         let response_received_at = Instant::now();
 
@@ -11,7 +11,10 @@ use gpui::{
     http_client::{self, AsyncBody, HttpClient, Method},
 };
 use language::{OffsetRangeExt as _, ToOffset, ToPoint as _};
-use language_model::{ApiKeyState, EnvVar, env_var};
+use language_model::{
+    ApiKeyState, EnvVar, LanguageModelId, LanguageModelProviderId, LanguageModelUsageFeature,
+    LanguageModelUsageTracker, TokenUsage, env_var,
+};
 use release_channel::AppVersion;
 use serde::Serialize;
 use std::{mem, ops::Range, path::Path, sync::Arc, time::Instant};
@@ -19,6 +22,8 @@ use std::{mem, ops::Range, path::Path, sync::Arc, time::Instant};
 use zeta_prompt::ZetaPromptInput;
 
 const MERCURY_API_URL: &str = "https://api.inceptionlabs.ai/v1/edit/completions";
+const MERCURY_PROVIDER_ID: LanguageModelProviderId = LanguageModelProviderId::new("mercury");
+const MERCURY_MODEL: &str = "mercury-coder";
 const MAX_REWRITE_TOKENS: usize = 150;
 const MAX_CONTEXT_TOKENS: usize = 350;
 
@@ -36,6 +41,7 @@ impl Mercury {
     pub(crate) fn request_prediction(
         &self,
         EditPredictionModelInput {
+            project,
             buffer,
             snapshot,
             position,
@@ -62,6 +68,13 @@ impl Mercury {
         let cursor_point = position.to_point(&snapshot);
         let buffer_snapshotted_at = Instant::now();
         let active_buffer = buffer.clone();
+        let usage_tracker = LanguageModelUsageTracker::new(
+            MERCURY_PROVIDER_ID,
+            LanguageModelId::from(MERCURY_MODEL.to_string()),
+            LanguageModelUsageFeature::EditPrediction,
+            project.read(cx).display_name(cx),
+            cx,
+        );
 
         let result = cx.background_spawn(async move {
             let (editable_range, context_range) =
@@ -118,7 +131,7 @@ impl Mercury {
             }
 
             let request_body = open_ai::Request {
-                model: "mercury-coder".into(),
+                model: MERCURY_MODEL.into(),
                 messages: vec![open_ai::RequestMessage::User {
                     content: open_ai::MessageContent::Plain(prompt),
                 }],
@@ -168,6 +181,11 @@ impl Mercury {
 
             let mut response: open_ai::Response =
                 serde_json::from_slice(&body).context("Failed to parse response")?;
+            usage_tracker.update(TokenUsage {
+                input_tokens: response.usage.prompt_tokens,
+                output_tokens: response.usage.completion_tokens,
+                ..Default::default()
+            });
 
             let id = mem::take(&mut response.id);
             let response_str = text_from_response(response).unwrap_or_default();
@@ -33,9 +33,15 @@ pub(crate) struct OllamaGenerateOptions {
 pub(crate) struct OllamaGenerateResponse {
     pub created_at: String,
     pub response: String,
+    /// The number of tokens in the prompt.
+    #[serde(default)]
+    pub prompt_eval_count: u64,
+    /// The number of tokens in the response.
+    #[serde(default)]
+    pub eval_count: u64,
 }
 
-const PROVIDER_ID: LanguageModelProviderId = LanguageModelProviderId::new("ollama");
+pub(crate) const PROVIDER_ID: LanguageModelProviderId = LanguageModelProviderId::new("ollama");
 
 pub fn is_available(cx: &App) -> bool {
     LanguageModelRegistry::read_global(cx)
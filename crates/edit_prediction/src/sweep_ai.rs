@@ -1,7 +1,8 @@
 use crate::{
     CurrentEditPrediction, DebugEvent, EditPrediction, EditPredictionFinishedDebugEvent,
     EditPredictionId, EditPredictionModelInput, EditPredictionStartedDebugEvent,
-    EditPredictionStore, UserActionRecord, UserActionType, prediction::EditPredictionResult,
+    EditPredictionStore, UserActionRecord, UserActionType, cursor_excerpt::guess_token_count,
+    prediction::EditPredictionResult,
 };
 use anyhow::{Result, bail};
 use client::Client;
@@ -13,7 +14,10 @@ use gpui::{
 };
 use language::language_settings::all_language_settings;
 use language::{Anchor, Buffer, BufferSnapshot, Point, ToOffset as _};
-use language_model::{ApiKeyState, EnvVar, env_var};
+use language_model::{
+    ApiKeyState, EnvVar, LanguageModelId, LanguageModelProviderId, LanguageModelUsageFeature,
+    LanguageModelUsageTracker, TokenUsage, env_var,
+};
 use lsp::DiagnosticSeverity;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -26,6 +30,8 @@ use std::{
 
 const SWEEP_API_URL: &str = "https://autocomplete.sweep.dev/backend/next_edit_autocomplete";
 const SWEEP_METRICS_URL: &str = "https://backend.app.sweep.dev/backend/track_autocomplete_metrics";
+const SWEEP_PROVIDER_ID: LanguageModelProviderId = LanguageModelProviderId::new("sweep");
+const SWEEP_MODEL: &str = "next-edit-autocomplete";
 
 pub struct SweepAi {
     pub api_token: Entity<ApiKeyState>,
@@ -91,6 +97,13 @@ impl SweepAi {
             .collect::<Vec<_>>();
 
         let buffer_snapshotted_at = Instant::now();
+        let usage_tracker = LanguageModelUsageTracker::new(
+            SWEEP_PROVIDER_ID,
+            LanguageModelId::from(SWEEP_MODEL.to_string()),
+            LanguageModelUsageFeature::EditPrediction,
+            inputs.project.read(cx).display_name(cx),
+            cx,
+        );
 
         let result = cx.background_spawn(async move {
             let text = inputs.snapshot.text();
@@ -225,12 +238,9 @@ impl SweepAi {
                 can_collect_data: false,
             };
 
-            send_started_event(
-                &debug_tx,
-                &buffer,
-                inputs.position,
-                serde_json::to_string(&request_body).unwrap_or_default(),
-            );
+            let prompt = serde_json::to_string(&request_body).unwrap_or_default();
+            let prompt_len = prompt.len();
+            send_started_event(&debug_tx, &buffer, inputs.position, prompt);
 
             let request = http_client::Request::builder()
                 .uri(SWEEP_API_URL)
@@ -258,6 +268,13 @@ impl SweepAi {
             };
 
             let response: AutocompleteResponse = serde_json::from_str(&body)?;
+            // Sweep doesn't report the tokens a request used, so they're estimated from the
+            // size of the request and the completion.
+            usage_tracker.update(TokenUsage {
+                input_tokens: guess_token_count(prompt_len) as u64,
+                output_tokens: guess_token_count(response.completion.len()) as u64,
+                ..Default::default()
+            });
 
             send_finished_event(&debug_tx, &buffer, inputs.position, body);
 
@@ -12,6 +12,7 @@ use futures::AsyncReadExt as _;
 use gpui::{App, AppContext as _, Task, http_client, prelude::*};
 use language::language_settings::{OpenAiCompatibleEditPredictionSettings, all_language_settings};
 use language::{BufferSnapshot, OffsetRangeExt as _, ToOffset as _, ToPoint, text_diff};
+use language_model::TokenUsage;
 use release_channel::AppVersion;
 use text::{Anchor, Bias};
 
@@ -155,7 +156,7 @@ pub fn request_prediction_with_zeta(
                             format!("{EDITABLE_REGION_END_MARKER}\n\n\n"),
                         ];
 
-                        let (response_text, request_id) = send_custom_server_request(
+                        let (response_text, request_id, _) = send_custom_server_request(
                             provider,
                             custom_settings,
                             prompt,
@@ -174,7 +175,7 @@ pub fn request_prediction_with_zeta(
                         let prefill = get_prefill(&prompt_input, zeta_version);
                         let prompt = format!("{prompt}{prefill}");
 
-                        let (response_text, request_id) = send_custom_server_request(
+                        let (response_text, request_id, _) = send_custom_server_request(
                             provider,
                             custom_settings,
                             prompt,
@@ -412,13 +413,18 @@ pub(crate) async fn send_custom_server_request(
     max_tokens: u32,
     stop_tokens: Vec<String>,
     http_client: &Arc<dyn http_client::HttpClient>,
-) -> Result<(String, String)> {
+) -> Result<(String, String, TokenUsage)> {
     match provider {
         settings::EditPredictionProvider::Ollama => {
             let response =
                 ollama::make_request(settings.clone(), prompt, stop_tokens, http_client.clone())
                     .await?;
-            Ok((response.response, response.created_at))
+            let usage = TokenUsage {
+                input_tokens: response.prompt_eval_count,
+                output_tokens: response.eval_count,
+                ..Default::default()
+            };
+            Ok((response.response, response.created_at, usage))
         }
         _ => {
             let request = RawCompletionRequest {
@@ -460,7 +466,12 @@ pub(crate) async fn send_custom_server_request(
                 .next()
                 .map(|choice| choice.text)
                 .unwrap_or_default();
-            Ok((text, parsed.id))
+            let usage = TokenUsage {
+                input_tokens: parsed.usage.prompt_tokens as u64,
+                output_tokens: parsed.usage.completion_tokens as u64,
+                ..Default::default()
+            };
+            Ok((text, parsed.id, usage))
         }
     }
 }
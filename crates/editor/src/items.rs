@@ -334,6 +334,7 @@ impl FollowableItem for Editor {
                 }
                 _ => false,
             },
+            _ => false,
         }
     }
 
@@ -344,7 +345,9 @@ impl FollowableItem for Editor {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        let update_view::Variant::Editor(message) = message;
+        let update_view::Variant::Editor(message) = message else {
+            return Task::ready(Ok(()));
+        };
         let project = project.clone();
         cx.spawn_in(window, async move |this, cx| {
             update_editor_from_message(this, project, message, cx).await
@@ -533,7 +536,7 @@ fn serialize_selection(
     }
 }
 
-fn serialize_anchor(anchor: &Anchor, buffer: &MultiBufferSnapshot) -> proto::EditorAnchor {
+pub fn serialize_anchor(anchor: &Anchor, buffer: &MultiBufferSnapshot) -> proto::EditorAnchor {
     proto::EditorAnchor {
         excerpt_id: buffer.latest_excerpt_id(anchor.excerpt_id).to_proto(),
         anchor: Some(serialize_text_anchor(&anchor.text_anchor)),
@@ -573,7 +576,7 @@ fn deserialize_selection(selection: proto::Selection) -> Option<Selection<Anchor
     })
 }
 
-fn deserialize_anchor(anchor: proto::EditorAnchor) -> Option<Anchor> {
+pub fn deserialize_anchor(anchor: proto::EditorAnchor) -> Option<Anchor> {
     let excerpt_id = ExcerptId::from_proto(anchor.excerpt_id);
     Some(Anchor::in_buffer(
         excerpt_id,
@@ -21,6 +21,7 @@ use gpui::http_client::read_proxy_from_env;
 use gpui::{App, AppContext, AsyncApp, Entity, UpdateGlobal};
 use gpui_tokio::Tokio;
 use language::LanguageRegistry;
+use language_model::replay_provider::{LanguageModelRecordings, ReplayLanguageModelProvider};
 use language_model::{ConfiguredModel, LanguageModel, LanguageModelRegistry, SelectedModel};
 use node_runtime::{NodeBinaryOptions, NodeRuntime};
 use project::project_settings::ProjectSettings;
@@ -60,6 +61,12 @@ struct Args {
     /// Maximum number of examples to run concurrently.
     #[arg(long, default_value = "4")]
     concurrency: usize,
+    /// Record the agent's and the judges' completions to this directory, to replay them later with `--replay`.
+    #[arg(long, value_name = "DIR", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+    /// Replay the completions recorded with `--record` from this directory, instead of making requests to the models.
+    #[arg(long, value_name = "DIR")]
+    replay: Option<PathBuf>,
     /// Output current environment variables as JSON to stdout
     #[arg(long, hide = true)]
     printenv: bool,
@@ -143,9 +150,14 @@ fn main() {
 
         cx.spawn(async move |cx| {
             future::join_all(tasks).await;
+            let recording = match (args.record, args.replay) {
+                (Some(dir), _) => Some(ModelRecording::Record(LanguageModelRecordings::new(dir))),
+                (None, Some(dir)) => Some(ModelRecording::Replay(LanguageModelRecordings::new(dir))),
+                (None, None) => None,
+            };
             let judge_model = cx.update(|cx| {
-                let agent_model = load_model(&args.model, cx).unwrap();
-                let judge_model = load_model(&args.judge_model, cx).unwrap();
+                let agent_model = load_model(&args.model, recording.as_ref(), cx).unwrap();
+                let judge_model = load_model(&args.judge_model, recording.as_ref(), cx).unwrap();
                 LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
                     registry.set_default_model(Some(agent_model.clone()), cx);
                 });
@@ -483,7 +495,26 @@ pub fn find_model(
         })
 }
 
-pub fn load_model(model_name: &str, cx: &mut App) -> anyhow::Result<ConfiguredModel> {
+/// Whether the models' completions are recorded or replayed.
+pub enum ModelRecording {
+    Record(LanguageModelRecordings),
+    Replay(LanguageModelRecordings),
+}
+
+pub fn load_model(
+    model_name: &str,
+    recording: Option<&ModelRecording>,
+    cx: &mut App,
+) -> anyhow::Result<ConfiguredModel> {
+    if let Some(ModelRecording::Replay(recordings)) = recording {
+        let selected = SelectedModel::from_str(model_name).map_err(|e| anyhow::anyhow!(e))?;
+        let model = recordings.replay(&selected.provider, &selected.model)?;
+        return Ok(ConfiguredModel {
+            provider: Arc::new(ReplayLanguageModelProvider::new(vec![model.clone()])),
+            model,
+        });
+    }
+
     let model = {
         let model_registry = LanguageModelRegistry::read_global(cx);
         find_model(model_name, model_registry, cx)?
@@ -496,6 +527,10 @@ pub fn load_model(model_name: &str, cx: &mut App) -> anyhow::Result<ConfiguredMo
             .ok_or_else(|| anyhow::anyhow!("Provider not found: {}", model.provider_id()))?
     };
 
+    let model = match recording {
+        Some(ModelRecording::Record(recordings)) => recordings.record(model)?,
+        _ => model,
+    };
     Ok(ConfiguredModel {
         provider: provider.clone(),
         model: model.clone(),
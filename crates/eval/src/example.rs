@@ -328,6 +328,16 @@ impl ExampleContext {
                         "{}Bug: Tool confirmation should not be required in eval",
                         log_prefix
                     ),
+                    ThreadEvent::ToolCallElicitation(elicitation) => {
+                        println!(
+                            "{log_prefix} Declining input requested by {}",
+                            elicitation.elicitation.server_name()
+                        );
+                        elicitation
+                            .response
+                            .send(context_server::types::ElicitResult::decline())
+                            .ok();
+                    }
                     ThreadEvent::SubagentSpawned(session) => {
                         println!("{log_prefix} Got subagent spawn: {session:?}");
                     }
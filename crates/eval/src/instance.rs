@@ -653,6 +653,7 @@ impl agent::ThreadEnvironment for EvalThreadEnvironment {
     fn create_terminal(
         &self,
         command: String,
+        extra_env: Vec<acp::EnvVariable>,
         cwd: Option<PathBuf>,
         output_byte_limit: Option<u64>,
         cx: &mut AsyncApp,
@@ -662,8 +663,12 @@ impl agent::ThreadEnvironment for EvalThreadEnvironment {
             let language_registry =
                 project.read_with(cx, |project, _cx| project.languages().clone());
             let id = acp::TerminalId::new(uuid::Uuid::new_v4().to_string());
+            let env = extra_env
+                .into_iter()
+                .map(|variable| (variable.name, variable.value))
+                .collect();
             let terminal =
-                acp_thread::create_terminal_entity(command, &[], vec![], cwd.clone(), &project, cx)
+                acp_thread::create_terminal_entity(command, &[], env, cwd.clone(), &project, cx)
                     .await?;
             let terminal = cx.new(|cx| {
                 acp_thread::Terminal::new(
@@ -0,0 +1,1052 @@
+//! Asks the agent's model to review a branch diff, and shows its findings as comments anchored
+//! to the diff's hunks.
+//!
+//! Comments are persisted per repository and base ref, so that they survive restarts, and so
+//! that findings the user dismissed aren't shown again when the branch is reviewed again.
+
+use crate::git_panel::GitPanel;
+use agent_settings::AgentSettings;
+use anyhow::{Context as _, Result, anyhow};
+use cloud_llm_client::CompletionIntent;
+use collections::HashSet;
+use db::{
+    query,
+    sqlez::{
+        bindable::Column, domain::Domain, statement::Statement,
+        thread_safe_connection::ThreadSafeConnection,
+    },
+    sqlez_macros::sql,
+};
+use editor::{
+    Editor,
+    display_map::{BlockContext, BlockPlacement, BlockProperties, BlockStyle, CustomBlockId},
+};
+use futures::StreamExt as _;
+use git::repository::{DiffType, RepoPath};
+use gpui::{
+    AnyElement, App, Context, Entity, EventEmitter, SharedString, Subscription, Task, WeakEntity,
+};
+use language::{Buffer, BufferSnapshot, Point};
+use language_model::{
+    ConfiguredModel, LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
+    LanguageModelUsageFeature, LanguageModelUsageTracker, Role,
+};
+use multi_buffer::MultiBuffer;
+use project::{
+    Project,
+    git_store::branch_diff::{BranchDiff, DiffBase},
+};
+use serde::Deserialize;
+use settings::Settings as _;
+use std::{fmt::Write as _, ops::Range, ops::RangeInclusive, sync::Arc};
+use ui::{Tooltip, prelude::*};
+use util::ResultExt as _;
+
+const REVIEW_PROMPT: &str = include_str!("agent_review_prompt.txt");
+
+/// Diffs larger than this are compressed before being sent for review.
+const MAX_DIFF_BYTES: usize = 200_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ReviewSeverity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl ReviewSeverity {
+    /// The key the severity is stored under in the database.
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Info => "info",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        [Self::Error, Self::Warning, Self::Info]
+            .into_iter()
+            .find(|severity| severity.key() == key)
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Error => "Error",
+            Self::Warning => "Warning",
+            Self::Info => "Suggestion",
+        }
+    }
+
+    fn icon(&self) -> IconName {
+        match self {
+            Self::Error => IconName::XCircle,
+            Self::Warning => IconName::Warning,
+            Self::Info => IconName::Info,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Self::Error => Color::Error,
+            Self::Warning => Color::Warning,
+            Self::Info => Color::Info,
+        }
+    }
+}
+
+/// A finding, as written by the model.
+#[derive(Debug, PartialEq, Deserialize)]
+struct ReviewFinding {
+    path: String,
+    start_line: u32,
+    #[serde(default)]
+    end_line: Option<u32>,
+    severity: ReviewSeverity,
+    comment: String,
+    #[serde(default)]
+    suggestion: Option<String>,
+}
+
+/// A review comment, as stored in the database.
+#[derive(Clone, Debug, PartialEq)]
+struct SerializedReviewComment {
+    id: i64,
+    path: String,
+    /// The zero-based rows the comment is about, in the new version of the file.
+    start_row: u32,
+    end_row: u32,
+    severity: ReviewSeverity,
+    comment: String,
+    suggestion: Option<String>,
+    /// The text of the rows when the comment was first shown, used to find the rows again
+    /// after the file changed.
+    original_text: Option<String>,
+}
+
+struct ReviewComment {
+    id: i64,
+    path: RepoPath,
+    rows: RangeInclusive<u32>,
+    severity: ReviewSeverity,
+    comment: SharedString,
+    suggestion: Option<SharedString>,
+    original_text: Option<String>,
+    /// Where the comment is in its buffer, once the buffer was opened in the diff.
+    anchor: Option<(Entity<Buffer>, Range<language::Anchor>)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct ReviewKey {
+    work_directory: String,
+    base_ref: String,
+}
+
+/// The agent's review of a branch diff, shown as blocks in the diff's editor.
+pub struct AgentReview {
+    project: Entity<Project>,
+    branch_diff: Entity<BranchDiff>,
+    multibuffer: Entity<MultiBuffer>,
+    editor: WeakEntity<Editor>,
+    comments: Vec<ReviewComment>,
+    block_ids: HashSet<CustomBlockId>,
+    loaded_review: Option<ReviewKey>,
+    is_reviewing: bool,
+    _load_task: Task<()>,
+    _subscription: Subscription,
+}
+
+pub enum AgentReviewEvent {
+    CommentsChanged,
+}
+
+impl EventEmitter<AgentReviewEvent> for AgentReview {}
+
+impl AgentReview {
+    pub fn new(
+        project: Entity<Project>,
+        branch_diff: Entity<BranchDiff>,
+        multibuffer: Entity<MultiBuffer>,
+        editor: &Entity<Editor>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let subscription = cx.subscribe(&multibuffer, |this, _, event, cx| match event {
+            multi_buffer::Event::ExcerptsAdded { .. }
+            | multi_buffer::Event::ExcerptsRemoved { .. }
+            | multi_buffer::Event::ExcerptsExpanded { .. } => this.excerpts_changed(cx),
+            _ => {}
+        });
+        let mut this = Self {
+            project,
+            branch_diff,
+            multibuffer,
+            editor: editor.downgrade(),
+            comments: Vec::new(),
+            block_ids: HashSet::default(),
+            loaded_review: None,
+            is_reviewing: false,
+            _load_task: Task::ready(()),
+            _subscription: subscription,
+        };
+        this.excerpts_changed(cx);
+        this
+    }
+
+    pub fn is_reviewing(&self) -> bool {
+        self.is_reviewing
+    }
+
+    pub fn comment_count(&self) -> usize {
+        self.comments.len()
+    }
+
+    fn review_key(&self, cx: &App) -> Option<ReviewKey> {
+        let branch_diff = self.branch_diff.read(cx);
+        let DiffBase::Merge { base_ref } = branch_diff.diff_base() else {
+            return None;
+        };
+        let repo = branch_diff.repo()?;
+        Some(ReviewKey {
+            work_directory: repo
+                .read(cx)
+                .work_directory_abs_path
+                .to_string_lossy()
+                .into_owned(),
+            base_ref: base_ref.to_string(),
+        })
+    }
+
+    fn excerpts_changed(&mut self, cx: &mut Context<Self>) {
+        if let Some(key) = self.review_key(cx)
+            && self.loaded_review.as_ref() != Some(&key)
+        {
+            self.load(key, cx);
+        } else {
+            self.refresh_blocks(cx);
+        }
+    }
+
+    /// Loads the comments that were persisted for the branch diff's repository and base ref.
+    fn load(&mut self, key: ReviewKey, cx: &mut Context<Self>) {
+        self.loaded_review = Some(key.clone());
+        self.set_comments(Vec::new(), cx);
+        let comments = cx.background_spawn(async move {
+            AGENT_REVIEW_DB.comments(key.work_directory, key.base_ref)
+        });
+        self._load_task = cx.spawn(async move |this, cx| {
+            let Some(comments) = comments.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| this.set_comments(comments, cx))
+                .ok();
+        });
+    }
+
+    fn set_comments(&mut self, comments: Vec<SerializedReviewComment>, cx: &mut Context<Self>) {
+        self.comments = comments
+            .into_iter()
+            .filter_map(|comment| {
+                Some(ReviewComment {
+                    id: comment.id,
+                    path: RepoPath::new(&comment.path).log_err()?,
+                    rows: comment.start_row..=comment.end_row.max(comment.start_row),
+                    severity: comment.severity,
+                    comment: comment.comment.into(),
+                    suggestion: comment.suggestion.map(Into::into),
+                    original_text: comment.original_text,
+                    anchor: None,
+                })
+            })
+            .collect();
+        self.refresh_blocks(cx);
+        cx.emit(AgentReviewEvent::CommentsChanged);
+        cx.notify();
+    }
+
+    /// Asks the agent's model to review the branch diff, replacing the comments of any previous
+    /// review that weren't dismissed.
+    pub fn request_review(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        if self.is_reviewing {
+            return Task::ready(Ok(()));
+        }
+        if !AgentSettings::get_global(cx).enabled(cx) {
+            return Task::ready(Err(anyhow!("The agent is disabled")));
+        }
+        let Some(key) = self.review_key(cx) else {
+            return Task::ready(Err(anyhow!("There is no branch diff to review")));
+        };
+        let Some(repo) = self.branch_diff.read(cx).repo().cloned() else {
+            return Task::ready(Err(anyhow!("There is no branch diff to review")));
+        };
+        let Some(ConfiguredModel { provider, model }) =
+            LanguageModelRegistry::read_global(cx).default_model()
+        else {
+            return Task::ready(Err(anyhow!(
+                "No language model is configured for the agent"
+            )));
+        };
+
+        telemetry::event!("Git Branch Diff Agent Review Requested");
+
+        let diff = repo.update(cx, |repo, cx| {
+            repo.diff(
+                DiffType::MergeBase {
+                    base_ref: key.base_ref.clone().into(),
+                },
+                cx,
+            )
+        });
+        let temperature = AgentSettings::temperature_for_model(&model, cx);
+        let usage_tracker = LanguageModelUsageTracker::new(
+            model.provider_id(),
+            model.id(),
+            LanguageModelUsageFeature::DiffReview,
+            self.project.read(cx).display_name(cx),
+            cx,
+        );
+
+        self.is_reviewing = true;
+        cx.notify();
+        cx.spawn(async move |this, cx| {
+            let findings = async {
+                if let Some(task) = cx.update(|cx| {
+                    if !provider.is_authenticated(cx) {
+                        Some(provider.authenticate(cx))
+                    } else {
+                        None
+                    }
+                }) {
+                    task.await.log_err();
+                }
+
+                let diff_text = diff.await??;
+                let diff_text = GitPanel::compress_commit_diff(&diff_text, MAX_DIFF_BYTES);
+                let content = format!(
+                    "{REVIEW_PROMPT}\nHere is the diff against {}:\n{}",
+                    key.base_ref,
+                    annotate_diff(&diff_text)
+                );
+                let request = LanguageModelRequest {
+                    thread_id: None,
+                    prompt_id: None,
+                    intent: Some(CompletionIntent::UserPrompt),
+                    messages: vec![LanguageModelRequestMessage {
+                        role: Role::User,
+                        content: vec![content.into()],
+                        cache: false,
+                        reasoning_details: None,
+                    }],
+                    tools: Vec::new(),
+                    tool_choice: None,
+                    stop: Vec::new(),
+                    temperature,
+                    thinking_allowed: false,
+                    thinking_effort: None,
+                };
+
+                let stream = model.stream_completion_text(request, cx).await?;
+                let mut stream = usage_tracker.track_text(stream).stream;
+                let mut response = String::new();
+                while let Some(chunk) = stream.next().await {
+                    response.push_str(&chunk?);
+                }
+                parse_findings(&response)
+            }
+            .await;
+            this.update(cx, |this, cx| {
+                this.is_reviewing = false;
+                cx.notify();
+            })?;
+
+            let findings = findings?;
+            // Drop the findings about files that aren't in the diff, as they can't be shown.
+            let findings = this.read_with(cx, |this, cx| {
+                findings
+                    .into_iter()
+                    .filter(|finding| {
+                        let path = finding.path.strip_prefix("b/").unwrap_or(&finding.path);
+                        RepoPath::new(path)
+                            .ok()
+                            .and_then(|path| this.buffer_for_path(&path, cx))
+                            .is_some_and(|buffer| {
+                                let buffer_id = buffer.read(cx).remote_id();
+                                this.multibuffer.read(cx).buffer(buffer_id).is_some()
+                            })
+                    })
+                    .collect::<Vec<_>>()
+            })?;
+            let comments = findings
+                .into_iter()
+                .map(|finding| {
+                    let start_row = finding.start_line.saturating_sub(1);
+                    SerializedReviewComment {
+                        id: 0,
+                        path: finding
+                            .path
+                            .strip_prefix("b/")
+                            .unwrap_or(&finding.path)
+                            .to_string(),
+                        start_row,
+                        end_row: finding
+                            .end_line
+                            .map_or(start_row, |end_line| end_line.saturating_sub(1))
+                            .max(start_row),
+                        severity: finding.severity,
+                        comment: finding.comment,
+                        suggestion: finding.suggestion,
+                        original_text: None,
+                    }
+                })
+                .collect::<Vec<_>>();
+            let comments = cx
+                .background_spawn(async move {
+                    AGENT_REVIEW_DB
+                        .save_review(key.work_directory, key.base_ref, comments)
+                        .await
+                })
+                .await?;
+            this.update(cx, |this, cx| this.set_comments(comments, cx))
+        })
+    }
+
+    fn buffer_for_path(&self, path: &RepoPath, cx: &App) -> Option<Entity<Buffer>> {
+        buffer_for_path(&self.project, &self.branch_diff, path, cx)
+    }
+
+    /// Replaces the lines a comment is about with its suggestion, and dismisses the comment.
+    pub fn apply_suggestion(&mut self, id: i64, cx: &mut Context<Self>) {
+        let Some(comment) = self.comments.iter().find(|comment| comment.id == id) else {
+            return;
+        };
+        let (Some(suggestion), Some((buffer, range))) = (&comment.suggestion, &comment.anchor)
+        else {
+            return;
+        };
+        let suggestion = suggestion.trim_end_matches('\n').to_string();
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(range.clone(), suggestion)], None, cx);
+        });
+        self.dismiss(id, cx);
+    }
+
+    /// Hides a comment, and remembers that it was dismissed so that it isn't shown again.
+    pub fn dismiss(&mut self, id: i64, cx: &mut Context<Self>) {
+        let Some(ix) = self.comments.iter().position(|comment| comment.id == id) else {
+            return;
+        };
+        self.comments.remove(ix);
+        cx.background_spawn(async move { AGENT_REVIEW_DB.dismiss_comment(id).await })
+            .detach_and_log_err(cx);
+        self.refresh_blocks(cx);
+        cx.emit(AgentReviewEvent::CommentsChanged);
+        cx.notify();
+    }
+
+    /// Anchors the comments whose files are shown in the diff, and replaces the blocks that
+    /// display them.
+    fn refresh_blocks(&mut self, cx: &mut Context<Self>) {
+        let Some(editor) = self.editor.upgrade() else {
+            return;
+        };
+        let mut stale_comment_ids = Vec::new();
+        let mut captured_texts = Vec::new();
+        for comment in &mut self.comments {
+            if comment.anchor.is_some() {
+                continue;
+            }
+            let Some(buffer) = buffer_for_path(&self.project, &self.branch_diff, &comment.path, cx)
+            else {
+                continue;
+            };
+            let snapshot = buffer.read(cx).snapshot();
+            let Some(rows) = find_rows(&snapshot, &comment.rows, comment.original_text.as_deref())
+            else {
+                stale_comment_ids.push(comment.id);
+                continue;
+            };
+            let range = Point::new(*rows.start(), 0)
+                ..Point::new(*rows.end(), snapshot.line_len(*rows.end()));
+            if comment.original_text.is_none() {
+                let text = snapshot.text_for_range(range.clone()).collect::<String>();
+                captured_texts.push((comment.id, text.clone()));
+                comment.original_text = Some(text);
+            }
+            comment.rows = rows;
+            comment.anchor = Some((
+                buffer,
+                snapshot.anchor_after(range.start)..snapshot.anchor_before(range.end),
+            ));
+        }
+
+        if !stale_comment_ids.is_empty() {
+            self.comments
+                .retain(|comment| !stale_comment_ids.contains(&comment.id));
+            cx.emit(AgentReviewEvent::CommentsChanged);
+        }
+        if !stale_comment_ids.is_empty() || !captured_texts.is_empty() {
+            cx.background_spawn(async move {
+                for id in stale_comment_ids {
+                    AGENT_REVIEW_DB.delete_comment(id).await?;
+                }
+                for (id, original_text) in captured_texts {
+                    AGENT_REVIEW_DB.set_original_text(original_text, id).await?;
+                }
+                anyhow::Ok(())
+            })
+            .detach_and_log_err(cx);
+        }
+
+        let multibuffer = self.multibuffer.read(cx);
+        let this = cx.weak_entity();
+        let blocks = self
+            .comments
+            .iter()
+            .filter_map(|comment| {
+                let (buffer, range) = comment.anchor.as_ref()?;
+                let position = multibuffer.buffer_anchor_to_anchor(buffer, range.end, cx)?;
+                let height = 2
+                    + comment.comment.lines().count().max(1) as u32
+                    + comment
+                        .suggestion
+                        .as_ref()
+                        .map_or(0, |suggestion| suggestion.lines().count() as u32 + 1);
+                let id = comment.id;
+                let severity = comment.severity;
+                let text = comment.comment.clone();
+                let suggestion = comment.suggestion.clone();
+                let this = this.clone();
+                Some(BlockProperties {
+                    placement: BlockPlacement::Below(position),
+                    height: Some(height),
+                    style: BlockStyle::Sticky,
+                    render: Arc::new(move |cx| {
+                        render_review_comment(
+                            id,
+                            severity,
+                            text.clone(),
+                            suggestion.clone(),
+                            this.clone(),
+                            cx,
+                        )
+                    }),
+                    priority: 0,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let old_block_ids = std::mem::take(&mut self.block_ids);
+        self.block_ids = editor.update(cx, |editor, cx| {
+            editor.remove_blocks(old_block_ids, None, cx);
+            editor.insert_blocks(blocks, None, cx).into_iter().collect()
+        });
+    }
+}
+
+fn buffer_for_path(
+    project: &Entity<Project>,
+    branch_diff: &Entity<BranchDiff>,
+    path: &RepoPath,
+    cx: &App,
+) -> Option<Entity<Buffer>> {
+    let repo = branch_diff.read(cx).repo()?;
+    let project_path = repo.read(cx).repo_path_to_project_path(path, cx)?;
+    project.read(cx).get_open_buffer(&project_path, cx)
+}
+
+/// Finds the rows a comment is about. When the text of the rows changed since the comment was
+/// first shown, looks for the comment's original text elsewhere in the buffer, preferring the
+/// occurrence closest to where it used to be.
+fn find_rows(
+    snapshot: &BufferSnapshot,
+    rows: &RangeInclusive<u32>,
+    original_text: Option<&str>,
+) -> Option<RangeInclusive<u32>> {
+    let max_row = snapshot.max_point().row;
+    let Some(original_text) = original_text else {
+        let start = (*rows.start()).min(max_row);
+        return Some(start..=(*rows.end()).min(max_row));
+    };
+
+    if *rows.end() <= max_row {
+        let range =
+            Point::new(*rows.start(), 0)..Point::new(*rows.end(), snapshot.line_len(*rows.end()));
+        if snapshot.text_for_range(range).collect::<String>() == original_text {
+            return Some(rows.clone());
+        }
+    }
+
+    let row_count = rows.end() - rows.start();
+    let text = snapshot.text();
+    text.match_indices(original_text)
+        .map(|(offset, _)| snapshot.offset_to_point(offset))
+        .filter(|point| point.column == 0)
+        .min_by_key(|point| point.row.abs_diff(*rows.start()))
+        .map(|point| point.row..=point.row + row_count)
+}
+
+fn render_review_comment(
+    id: i64,
+    severity: ReviewSeverity,
+    comment: SharedString,
+    suggestion: Option<SharedString>,
+    review: WeakEntity<AgentReview>,
+    cx: &mut BlockContext,
+) -> AnyElement {
+    let colors = cx.theme().colors();
+    v_flex()
+        .id(cx.block_id)
+        .ml(cx.margins.gutter.width)
+        .mr_2()
+        .my_0p5()
+        .px_2()
+        .py_1()
+        .gap_1()
+        .rounded_sm()
+        .border_l_2()
+        .border_color(severity.color().color(cx))
+        .bg(colors.surface_background)
+        .child(
+            h_flex()
+                .gap_1()
+                .child(
+                    Icon::new(severity.icon())
+                        .size(IconSize::Small)
+                        .color(severity.color()),
+                )
+                .child(
+                    Label::new(severity.label())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(div().flex_1())
+                .when(suggestion.is_some(), |this| {
+                    this.child(
+                        Button::new("apply-suggestion", "Apply Suggestion")
+                            .label_size(LabelSize::Small)
+                            .icon(IconName::Check)
+                            .icon_position(IconPosition::Start)
+                            .icon_size(IconSize::Small)
+                            .on_click({
+                                let review = review.clone();
+                                move |_, _, cx| {
+                                    review
+                                        .update(cx, |review, cx| review.apply_suggestion(id, cx))
+                                        .ok();
+                                }
+                            }),
+                    )
+                })
+                .child(
+                    IconButton::new("dismiss", IconName::Close)
+                        .icon_size(IconSize::Small)
+                        .icon_color(Color::Muted)
+                        .tooltip(Tooltip::text("Dismiss"))
+                        .on_click(move |_, _, cx| {
+                            review.update(cx, |review, cx| review.dismiss(id, cx)).ok();
+                        }),
+                ),
+        )
+        .child(Label::new(comment).size(LabelSize::Small))
+        .when_some(suggestion, |this, suggestion| {
+            this.child(
+                div()
+                    .px_2()
+                    .py_0p5()
+                    .rounded_sm()
+                    .bg(colors.editor_background)
+                    .child(
+                        Label::new(suggestion)
+                            .size(LabelSize::Small)
+                            .buffer_font(cx),
+                    ),
+            )
+        })
+        .into_any()
+}
+
+/// Prefixes every line of the new version of each file in a unified diff with its line number,
+/// so that the model can refer to lines without counting them from the hunk headers.
+fn annotate_diff(diff: &str) -> String {
+    let mut annotated = String::with_capacity(diff.len() * 2);
+    let mut new_row = None;
+    for line in diff.lines() {
+        if line.starts_with("diff ") {
+            new_row = None;
+            annotated.push_str(line);
+        } else if line.starts_with("@@") {
+            new_row = parse_hunk_start(line);
+            annotated.push_str(line);
+        } else {
+            match (new_row, line.chars().next()) {
+                (Some(row), None | Some(' ' | '+')) => {
+                    write!(annotated, "{row:>5} {line}").ok();
+                    new_row = Some(row + 1);
+                }
+                (Some(_), Some('-')) => {
+                    write!(annotated, "      {line}").ok();
+                }
+                _ => annotated.push_str(line),
+            }
+        }
+        annotated.push('\n');
+    }
+    annotated
+}
+
+/// Returns the first line of the new version of the file in a hunk header like
+/// `@@ -10,3 +12,4 @@`.
+fn parse_hunk_start(header: &str) -> Option<u32> {
+    header
+        .split(' ')
+        .find_map(|part| part.strip_prefix('+'))?
+        .split(',')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Parses the model's findings, ignoring any text around the JSON array.
+///
+/// The array is the first one that parses, so brackets in the surrounding prose are skipped.
+fn parse_findings(response: &str) -> Result<Vec<ReviewFinding>> {
+    let mut error = None;
+    for (start, _) in response.match_indices('[') {
+        let mut findings = serde_json::Deserializer::from_str(&response[start..])
+            .into_iter::<Vec<ReviewFinding>>();
+        match findings.next() {
+            Some(Ok(findings)) => return Ok(findings),
+            Some(Err(parse_error)) => {
+                error.get_or_insert(parse_error);
+            }
+            None => {}
+        }
+    }
+    match error {
+        Some(error) => Err(error).context("Failed to parse the review's findings"),
+        None => anyhow::bail!("The review didn't contain any findings"),
+    }
+}
+
+impl Column for SerializedReviewComment {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (id, next_index): (i64, i32) = Column::column(statement, start_index)?;
+        let (path, next_index): (String, i32) = Column::column(statement, next_index)?;
+        let (start_row, next_index): (u32, i32) = Column::column(statement, next_index)?;
+        let (end_row, next_index): (u32, i32) = Column::column(statement, next_index)?;
+        let (severity, next_index): (String, i32) = Column::column(statement, next_index)?;
+        let (comment, next_index): (String, i32) = Column::column(statement, next_index)?;
+        let (suggestion, next_index): (Option<String>, i32) =
+            Column::column(statement, next_index)?;
+        let (original_text, next_index): (Option<String>, i32) =
+            Column::column(statement, next_index)?;
+        let comment = Self {
+            id,
+            path,
+            start_row,
+            end_row,
+            severity: ReviewSeverity::from_key(&severity)
+                .with_context(|| format!("Unknown review severity {severity:?}"))?,
+            comment,
+            suggestion,
+            original_text,
+        };
+        Ok((comment, next_index))
+    }
+}
+
+pub struct AgentReviewDb(ThreadSafeConnection);
+
+impl Domain for AgentReviewDb {
+    const NAME: &str = stringify!(AgentReviewDb);
+    const MIGRATIONS: &[&str] = &[sql!(
+        CREATE TABLE IF NOT EXISTS agent_review_comments(
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            work_directory TEXT NOT NULL,
+            base_ref TEXT NOT NULL,
+            path TEXT NOT NULL,
+            start_row INTEGER NOT NULL,
+            end_row INTEGER NOT NULL,
+            severity TEXT NOT NULL,
+            comment TEXT NOT NULL,
+            suggestion TEXT,
+            original_text TEXT,
+            dismissed INTEGER NOT NULL DEFAULT 0
+        ) STRICT;
+        CREATE INDEX IF NOT EXISTS agent_review_comments_review
+            ON agent_review_comments(work_directory, base_ref);
+    )];
+}
+
+db::static_connection!(AGENT_REVIEW_DB, AgentReviewDb, []);
+
+impl AgentReviewDb {
+    // Returns the comments of a review that weren't dismissed, in the order they were written.
+    query! {
+        fn comments(work_directory: String, base_ref: String) -> Result<Vec<SerializedReviewComment>> {
+            SELECT id, path, start_row, end_row, severity, comment, suggestion, original_text
+            FROM agent_review_comments
+            WHERE work_directory = (?) AND base_ref = (?) AND dismissed = 0
+            ORDER BY id
+        }
+    }
+
+    query! {
+        async fn dismiss_comment(id: i64) -> Result<()> {
+            UPDATE agent_review_comments SET dismissed = 1 WHERE id = (?)
+        }
+    }
+
+    query! {
+        async fn delete_comment(id: i64) -> Result<()> {
+            DELETE FROM agent_review_comments WHERE id = (?)
+        }
+    }
+
+    query! {
+        async fn set_original_text(original_text: String, id: i64) -> Result<()> {
+            UPDATE agent_review_comments SET original_text = (?) WHERE id = (?)
+        }
+    }
+
+    /// Replaces the comments of a review that weren't dismissed, skipping new comments that
+    /// repeat a dismissed one. Returns the comments that were saved, with their ids.
+    async fn save_review(
+        &self,
+        work_directory: String,
+        base_ref: String,
+        comments: Vec<SerializedReviewComment>,
+    ) -> Result<Vec<SerializedReviewComment>> {
+        self.write(move |connection| {
+            connection.with_savepoint("save_agent_review", || {
+                let dismissed =
+                    connection.select_bound::<(&str, &str), (String, String)>(sql!(
+                        SELECT path, comment FROM agent_review_comments
+                        WHERE work_directory = ? AND base_ref = ? AND dismissed = 1
+                    ))?((&work_directory, &base_ref))?;
+                connection.exec_bound::<(&str, &str)>(sql!(
+                    DELETE FROM agent_review_comments
+                    WHERE work_directory = ? AND base_ref = ? AND dismissed = 0
+                ))?((&work_directory, &base_ref))?;
+
+                let mut insert = connection.select_row_bound::<(
+                    &str,
+                    &str,
+                    &str,
+                    u32,
+                    u32,
+                    &str,
+                    &str,
+                    Option<&str>,
+                ), i64>(sql!(
+                    INSERT INTO agent_review_comments(
+                        work_directory,
+                        base_ref,
+                        path,
+                        start_row,
+                        end_row,
+                        severity,
+                        comment,
+                        suggestion
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                    RETURNING id
+                ))?;
+                let mut saved = Vec::new();
+                for mut comment in comments {
+                    let is_dismissed = dismissed
+                        .iter()
+                        .any(|(path, text)| *path == comment.path && *text == comment.comment);
+                    if is_dismissed {
+                        continue;
+                    }
+                    comment.id = insert((
+                        &work_directory,
+                        &base_ref,
+                        &comment.path,
+                        comment.start_row,
+                        comment.end_row,
+                        comment.severity.key(),
+                        &comment.comment,
+                        comment.suggestion.as_deref(),
+                    ))?
+                    .context("Failed to save review comment")?;
+                    saved.push(comment);
+                }
+                Ok(saved)
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{AppContext as _, TestAppContext};
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_annotate_diff() {
+        let diff = indoc! {"
+            diff --git a/src/lib.rs b/src/lib.rs
+            --- a/src/lib.rs
+            +++ b/src/lib.rs
+            @@ -10,3 +10,4 @@ fn main() {
+             let a = 1;
+            -let b = 2;
+            +let b = 3;
+            +let c = 4;
+             let d = 5;
+        "};
+        assert_eq!(
+            annotate_diff(diff),
+            indoc! {"
+                diff --git a/src/lib.rs b/src/lib.rs
+                --- a/src/lib.rs
+                +++ b/src/lib.rs
+                @@ -10,3 +10,4 @@ fn main() {
+                   10  let a = 1;
+                      -let b = 2;
+                   11 +let b = 3;
+                   12 +let c = 4;
+                   13  let d = 5;
+            "}
+        );
+    }
+
+    #[test]
+    fn test_parse_findings() {
+        let response = indoc! {r#"
+            Here is my review:
+            ```json
+            [
+              {
+                "path": "src/lib.rs",
+                "start_line": 11,
+                "end_line": 12,
+                "severity": "warning",
+                "comment": "`c` is never used.",
+                "suggestion": "let b = 3;"
+              },
+              {"path": "src/main.rs", "start_line": 3, "severity": "info", "comment": "Nice."}
+            ]
+            ```
+        "#};
+        assert_eq!(
+            parse_findings(response).unwrap(),
+            vec![
+                ReviewFinding {
+                    path: "src/lib.rs".into(),
+                    start_line: 11,
+                    end_line: Some(12),
+                    severity: ReviewSeverity::Warning,
+                    comment: "`c` is never used.".into(),
+                    suggestion: Some("let b = 3;".into()),
+                },
+                ReviewFinding {
+                    path: "src/main.rs".into(),
+                    start_line: 3,
+                    end_line: None,
+                    severity: ReviewSeverity::Info,
+                    comment: "Nice.".into(),
+                    suggestion: None,
+                },
+            ]
+        );
+        assert!(parse_findings("I couldn't review this diff.").is_err());
+
+        // Brackets in the prose around the findings are skipped.
+        let response = indoc! {r#"
+            Reviewed the change to `items[0]`:
+            [{"path": "src/main.rs", "start_line": 3, "severity": "info", "comment": "Nice."}]
+            Let me know if you need more [details].
+        "#};
+        assert_eq!(parse_findings(response).unwrap().len(), 1);
+        assert!(parse_findings("] before [").is_err());
+    }
+
+    #[gpui::test]
+    fn test_find_rows(cx: &mut TestAppContext) {
+        let buffer = cx.new(|cx| Buffer::local("one\ntwo\nthree\nfour\n", cx));
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+        assert_eq!(find_rows(&snapshot, &(1..=2), None), Some(1..=2));
+        assert_eq!(
+            find_rows(&snapshot, &(1..=2), Some("two\nthree")),
+            Some(1..=2)
+        );
+
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+        assert_eq!(
+            find_rows(&snapshot, &(1..=2), Some("two\nthree")),
+            Some(2..=3)
+        );
+        assert_eq!(find_rows(&snapshot, &(1..=2), Some("five")), None);
+    }
+
+    #[gpui::test]
+    async fn test_save_review() {
+        let db = AgentReviewDb::open_test_db("test_save_review").await;
+        let comment = |path: &str, comment: &str| SerializedReviewComment {
+            id: 0,
+            path: path.into(),
+            start_row: 1,
+            end_row: 2,
+            severity: ReviewSeverity::Error,
+            comment: comment.into(),
+            suggestion: None,
+            original_text: None,
+        };
+        let work_directory = "/repo".to_string();
+        let base_ref = "main".to_string();
+
+        let saved = db
+            .save_review(
+                work_directory.clone(),
+                base_ref.clone(),
+                vec![comment("a.rs", "first"), comment("b.rs", "second")],
+            )
+            .await
+            .unwrap();
+        assert_eq!(saved.len(), 2);
+        db.dismiss_comment(saved[0].id).await.unwrap();
+        db.set_original_text("text".into(), saved[1].id)
+            .await
+            .unwrap();
+        assert_eq!(
+            db.comments(work_directory.clone(), base_ref.clone())
+                .unwrap(),
+            vec![SerializedReviewComment {
+                id: saved[1].id,
+                original_text: Some("text".into()),
+                ..comment("b.rs", "second")
+            }]
+        );
+
+        // Reviewing again replaces the comments that weren't dismissed, and doesn't bring back
+        // the dismissed ones.
+        let saved = db
+            .save_review(
+                work_directory.clone(),
+                base_ref.clone(),
+                vec![comment("a.rs", "first"), comment("c.rs", "third")],
+            )
+            .await
+            .unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(
+            db.comments(work_directory.clone(), base_ref.clone())
+                .unwrap(),
+            vec![SerializedReviewComment {
+                id: saved[0].id,
+                ..comment("c.rs", "third")
+            }]
+        );
+        assert!(
+            db.comments(work_directory, "develop".into())
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
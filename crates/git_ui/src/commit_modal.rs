@@ -544,9 +544,9 @@ impl Render for CommitModal {
             .on_action(cx.listener(Self::on_commit))
             .on_action(cx.listener(Self::on_amend))
             .when(!DisableAiSettings::get_global(cx).disable_ai, |this| {
-                this.on_action(cx.listener(|this, _: &GenerateCommitMessage, _, cx| {
+                this.on_action(cx.listener(|this, _: &GenerateCommitMessage, window, cx| {
                     this.git_panel.update(cx, |panel, cx| {
-                        panel.generate_commit_message(cx);
+                        panel.generate_commit_message(window, cx);
                     })
                 }))
             })
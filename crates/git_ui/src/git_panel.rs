@@ -43,7 +43,8 @@ use gpui::{
 use itertools::Itertools;
 use language::{Buffer, File};
 use language_model::{
-    ConfiguredModel, LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage, Role,
+    ConfiguredModel, LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
+    LanguageModelUsageFeature, LanguageModelUsageTracker, Role, confirm_usage_budget,
 };
 use menu;
 use multi_buffer::ExcerptInfo;
@@ -2472,10 +2473,10 @@ impl GitPanel {
     fn generate_commit_message_action(
         &mut self,
         _: &git::GenerateCommitMessage,
-        _window: &mut Window,
+        window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        self.generate_commit_message(cx);
+        self.generate_commit_message(window, cx);
     }
 
     fn split_patch(patch: &str) -> Vec<String> {
@@ -2628,8 +2629,31 @@ impl GitPanel {
             .unwrap_or_else(|| BuiltInPrompt::CommitMessage.default_content().to_string())
     }
 
-    /// Generates a commit message using an LLM.
-    pub fn generate_commit_message(&mut self, cx: &mut Context<Self>) {
+    /// Generates a commit message using an LLM, after checking the user's usage budget.
+    pub fn generate_commit_message(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.can_commit() || !AgentSettings::get_global(cx).enabled(cx) {
+            return;
+        }
+
+        if let Some(confirm) = confirm_usage_budget(
+            AgentSettings::get_global(cx).usage_budget.clone(),
+            window,
+            cx,
+        ) {
+            cx.spawn_in(window, async move |this, cx| {
+                if confirm.await {
+                    this.update(cx, |this, cx| this.generate_commit_message_impl(cx))
+                        .ok();
+                }
+            })
+            .detach();
+            return;
+        }
+
+        self.generate_commit_message_impl(cx);
+    }
+
+    fn generate_commit_message_impl(&mut self, cx: &mut Context<Self>) {
         if !self.can_commit() || !AgentSettings::get_global(cx).enabled(cx) {
             return;
         }
@@ -2657,6 +2681,13 @@ impl GitPanel {
         let temperature = AgentSettings::temperature_for_model(&model, cx);
         let project = self.project.clone();
         let repo_work_dir = repo.read(cx).work_directory_abs_path.clone();
+        let usage_tracker = LanguageModelUsageTracker::new(
+            model.provider_id(),
+            model.id(),
+            LanguageModelUsageFeature::CommitMessage,
+            project.read(cx).display_name(cx),
+            cx,
+        );
 
         self.generate_commit_message_task = Some(cx.spawn(async move |this, mut cx| {
              async move {
@@ -2739,7 +2770,8 @@ impl GitPanel {
 
                 let stream = model.stream_completion_text(request, cx);
                 match stream.await {
-                    Ok(mut messages) => {
+                    Ok(messages) => {
+                        let mut messages = usage_tracker.track_text(messages);
                         if !text_empty {
                             this.update(cx, |this, cx| {
                                 this.commit_message_buffer(cx).update(cx, |buffer, cx| {
@@ -4002,8 +4034,8 @@ impl GitPanel {
                     }
                 })
                 .disabled(!can_commit || has_commit_model_configuration_error)
-                .on_click(cx.listener(move |this, _event, _window, cx| {
-                    this.generate_commit_message(cx);
+                .on_click(cx.listener(move |this, _event, window, cx| {
+                    this.generate_commit_message(window, cx);
                 }))
                 .into_any_element(),
         )
@@ -27,6 +27,7 @@ use zed_actions;
 
 use crate::{git_panel::GitPanel, text_diff_view::TextDiffView};
 
+mod agent_review;
 mod askpass_modal;
 pub mod branch_picker;
 mod commit_modal;
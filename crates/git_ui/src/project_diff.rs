@@ -1,4 +1,5 @@
 use crate::{
+    agent_review::AgentReview,
     conflict_view::ConflictAddon,
     git_panel::{GitPanel, GitPanelAddon, GitStatusEntry},
     git_panel_settings::GitPanelSettings,
@@ -26,7 +27,8 @@ use gpui::{
     Action, AnyElement, App, AppContext as _, AsyncWindowContext, Entity, EventEmitter,
     FocusHandle, Focusable, Render, Subscription, Task, WeakEntity, actions,
 };
-use language::{Anchor, Buffer, BufferId, Capability, OffsetRangeExt};
+use language::{Anchor, Buffer, BufferId, Capability, OffsetRangeExt, Selection, SelectionGoal};
+use language_model::confirm_usage_budget;
 use multi_buffer::{MultiBuffer, PathKey};
 use project::{
     Project, ProjectPath,
@@ -35,6 +37,7 @@ use project::{
         branch_diff::{self, BranchDiffEvent, DiffBase},
     },
 };
+use rpc::proto;
 use settings::{Settings, SettingsStore};
 use smol::future::yield_now;
 use std::any::{Any, TypeId};
@@ -43,9 +46,12 @@ use theme::ActiveTheme;
 use ui::{DiffStat, Divider, KeyBinding, Tooltip, prelude::*, vertical_divider};
 use util::{ResultExt as _, rel_path::RelPath};
 use workspace::{
-    CloseActiveItem, ItemNavHistory, SerializableItem, ToolbarItemEvent, ToolbarItemLocation,
-    ToolbarItemView, Workspace,
-    item::{Item, ItemEvent, ItemHandle, SaveOptions, TabContentParams},
+    CloseActiveItem, CollaboratorId, ItemNavHistory, SerializableItem, ToolbarItemEvent,
+    ToolbarItemLocation, ToolbarItemView, ViewId, Workspace,
+    item::{
+        Dedup, FollowEvent, FollowableItem, Item, ItemEvent, ItemHandle, SaveOptions,
+        TabContentParams,
+    },
     notifications::NotifyTaskExt,
     searchable::SearchableItemHandle,
 };
@@ -64,6 +70,9 @@ actions!(
         BranchDiff,
         /// Opens a new agent thread with the branch diff for review.
         ReviewDiff,
+        /// Asks the agent to review the branch diff, and shows its findings as comments on the
+        /// diff.
+        RequestAgentReview,
         LeaderAndFollower,
     ]
 );
@@ -75,11 +84,14 @@ pub struct ProjectDiff {
     editor: Entity<SplittableEditor>,
     buffer_diff_subscriptions: HashMap<Arc<RelPath>, (Entity<BufferDiff>, Subscription)>,
     workspace: WeakEntity<Workspace>,
+    remote_id: Option<ViewId>,
     focus_handle: FocusHandle,
     pending_scroll: Option<PathKey>,
     review_comment_count: usize,
+    agent_review: Option<Entity<AgentReview>>,
     _task: Task<Result<()>>,
     _subscription: Subscription,
+    _agent_review_subscription: Option<Subscription>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -98,10 +110,12 @@ impl ProjectDiff {
         workspace.register_action(Self::deploy);
         workspace.register_action(Self::deploy_branch_diff);
         workspace.register_action(Self::deploy_review_diff);
+        workspace.register_action(Self::deploy_agent_review);
         workspace.register_action(|workspace, _: &Add, window, cx| {
             Self::deploy(workspace, &Diff, window, cx);
         });
         workspace::register_serializable_item::<ProjectDiff>(cx);
+        workspace::FollowableViewRegistry::register::<ProjectDiff>(cx);
     }
 
     fn deploy(
@@ -201,6 +215,63 @@ impl ProjectDiff {
             .detach_and_notify_err(workspace_weak, window, cx);
     }
 
+    fn deploy_agent_review(
+        workspace: &mut Workspace,
+        _: &RequestAgentReview,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let existing = workspace
+            .items_of_type::<Self>(cx)
+            .find(|item| matches!(item.read(cx).diff_base(cx), DiffBase::Merge { .. }));
+        let project_diff = if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            Task::ready(Ok(existing))
+        } else {
+            let project = workspace.project().clone();
+            let workspace_handle = cx.entity();
+            window.spawn(cx, async move |cx| {
+                let project_diff = cx
+                    .update(|window, cx| {
+                        Self::new_with_default_branch(project, workspace_handle.clone(), window, cx)
+                    })?
+                    .await?;
+                workspace_handle.update_in(cx, |workspace, window, cx| {
+                    workspace.add_item_to_active_pane(
+                        Box::new(project_diff.clone()),
+                        None,
+                        true,
+                        window,
+                        cx,
+                    );
+                })?;
+                anyhow::Ok(project_diff)
+            })
+        };
+
+        let workspace_weak = cx.entity().downgrade();
+        window
+            .spawn(cx, async move |cx| {
+                let project_diff = project_diff.await?;
+                let confirm = cx.update(|window, cx| {
+                    confirm_usage_budget(
+                        AgentSettings::get_global(cx).usage_budget.clone(),
+                        window,
+                        cx,
+                    )
+                })?;
+                if let Some(confirm) = confirm
+                    && !confirm.await
+                {
+                    return Ok(());
+                }
+                project_diff
+                    .update(cx, |project_diff, cx| project_diff.request_agent_review(cx))
+                    .await
+            })
+            .detach_and_notify_err(workspace_weak, window, cx);
+    }
+
     pub fn deploy_at(
         workspace: &mut Workspace,
         entry: Option<GitStatusEntry>,
@@ -406,6 +477,22 @@ impl ProjectDiff {
                 }
             });
 
+        let agent_review = match branch_diff.read(cx).diff_base() {
+            DiffBase::Head => None,
+            DiffBase::Merge { .. } => Some(cx.new(|cx| {
+                AgentReview::new(
+                    project.clone(),
+                    branch_diff.clone(),
+                    multibuffer.clone(),
+                    &primary_editor,
+                    cx,
+                )
+            })),
+        };
+        let agent_review_subscription = agent_review
+            .as_ref()
+            .map(|agent_review| cx.observe(agent_review, |_, _, cx| cx.notify()));
+
         let branch_diff_subscription = cx.subscribe_in(
             &branch_diff,
             window,
@@ -449,6 +536,7 @@ impl ProjectDiff {
         Self {
             project,
             workspace: workspace.downgrade(),
+            remote_id: None,
             branch_diff,
             focus_handle,
             editor,
@@ -456,11 +544,13 @@ impl ProjectDiff {
             buffer_diff_subscriptions: Default::default(),
             pending_scroll: None,
             review_comment_count: 0,
+            agent_review,
             _task: task,
             _subscription: Subscription::join(
                 branch_diff_subscription,
                 Subscription::join(editor_subscription, review_comment_subscription),
             ),
+            _agent_review_subscription: agent_review_subscription,
         }
     }
 
@@ -522,6 +612,51 @@ impl ProjectDiff {
         })
     }
 
+    fn cursor_position(&self, cx: &App) -> Option<(ProjectPath, Anchor)> {
+        let editor = self.editor.read(cx).rhs_editor().read(cx);
+        let position = editor.selections.newest_anchor().head();
+        let (_, buffer, _) = editor.buffer().read(cx).excerpt_containing(position, cx)?;
+        let file = buffer.read(cx).file()?;
+        Some((
+            ProjectPath {
+                worktree_id: file.worktree_id(cx),
+                path: file.path().clone(),
+            },
+            position.text_anchor,
+        ))
+    }
+
+    fn show_leader_position(
+        &mut self,
+        project_path: &ProjectPath,
+        buffer: &Entity<Buffer>,
+        cursor: Option<Anchor>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(position) = cursor.and_then(|cursor| {
+            self.multibuffer
+                .read(cx)
+                .buffer_anchor_to_anchor(buffer, cursor, cx)
+        }) else {
+            self.move_to_project_path(project_path, window, cx);
+            return;
+        };
+        self.editor.update(cx, |editor, cx| {
+            editor.rhs_editor().update(cx, |editor, cx| {
+                let selection = Selection {
+                    id: 0,
+                    start: position,
+                    end: position,
+                    reversed: false,
+                    goal: SelectionGoal::None,
+                };
+                editor.set_selections_from_remote(vec![selection], None, window, cx);
+                editor.request_autoscroll_remotely(Autoscroll::newest(), cx);
+            })
+        });
+    }
+
     fn move_to_beginning(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         self.editor.update(cx, |editor, cx| {
             editor.rhs_editor().update(cx, |editor, cx| {
@@ -589,6 +724,29 @@ impl ProjectDiff {
         self.review_comment_count
     }
 
+    /// Whether the agent is reviewing the branch diff.
+    pub fn is_agent_reviewing(&self, cx: &App) -> bool {
+        self.agent_review
+            .as_ref()
+            .is_some_and(|agent_review| agent_review.read(cx).is_reviewing())
+    }
+
+    /// Returns the number of comments from the agent's review of the branch diff.
+    pub fn agent_review_comment_count(&self, cx: &App) -> usize {
+        self.agent_review
+            .as_ref()
+            .map_or(0, |agent_review| agent_review.read(cx).comment_count())
+    }
+
+    fn request_agent_review(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let Some(agent_review) = &self.agent_review else {
+            return Task::ready(Err(anyhow!(
+                "Only branch diffs can be reviewed by the agent"
+            )));
+        };
+        agent_review.update(cx, |agent_review, cx| agent_review.request_review(cx))
+    }
+
     /// Returns a reference to the splittable editor.
     pub fn editor(&self) -> &Entity<SplittableEditor> {
         &self.editor
@@ -665,6 +823,17 @@ impl ProjectDiff {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        match event {
+            EditorEvent::SelectionsChanged { local: true } => {
+                cx.emit(ProjectDiffEvent::CursorMoved);
+            }
+            EditorEvent::Edited { .. }
+            | EditorEvent::ScrollPositionChanged {
+                local: true,
+                autoscroll: false,
+            } => cx.emit(ProjectDiffEvent::MovedAwayFromLeader),
+            _ => {}
+        }
         match event {
             EditorEvent::SelectionsChanged { local: true } => {
                 let Some(project_path) = self.active_path(cx) else {
@@ -936,7 +1105,14 @@ fn sort_prefix(repo: &Repository, repo_path: &RepoPath, status: FileStatus, cx:
     }
 }
 
-impl EventEmitter<EditorEvent> for ProjectDiff {}
+/// Events that let collaborators follow the diff.
+pub enum ProjectDiffEvent {
+    CursorMoved,
+    /// The user edited or scrolled the diff, so it no longer follows its leader.
+    MovedAwayFromLeader,
+}
+
+impl EventEmitter<ProjectDiffEvent> for ProjectDiff {}
 
 impl Focusable for ProjectDiff {
     fn focus_handle(&self, cx: &App) -> FocusHandle {
@@ -949,15 +1125,13 @@ impl Focusable for ProjectDiff {
 }
 
 impl Item for ProjectDiff {
-    type Event = EditorEvent;
+    type Event = ProjectDiffEvent;
 
     fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
         Some(Icon::new(IconName::GitBranch).color(Color::Muted))
     }
 
-    fn to_item_events(event: &EditorEvent, f: &mut dyn FnMut(ItemEvent)) {
-        Editor::to_item_events(event, f)
-    }
+    fn to_item_events(_event: &ProjectDiffEvent, _f: &mut dyn FnMut(ItemEvent)) {}
 
     fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         self.editor.update(cx, |editor, cx| {
@@ -1136,6 +1310,152 @@ impl Item for ProjectDiff {
     }
 }
 
+impl FollowableItem for ProjectDiff {
+    fn remote_id(&self) -> Option<ViewId> {
+        self.remote_id
+    }
+
+    fn to_state_proto(&self, _window: &mut Window, cx: &mut App) -> Option<proto::view::Variant> {
+        if !matches!(self.diff_base(cx), DiffBase::Head) {
+            return None;
+        }
+        let position = self.cursor_position(cx);
+        Some(proto::view::Variant::ProjectDiff(
+            proto::view::ProjectDiff {
+                active_path: position.as_ref().map(|(path, _)| path.to_proto()),
+                cursor: position.map(|(_, cursor)| language::proto::serialize_anchor(&cursor)),
+            },
+        ))
+    }
+
+    fn from_state_proto(
+        workspace: Entity<Workspace>,
+        remote_id: ViewId,
+        state: &mut Option<proto::view::Variant>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        let Some(proto::view::Variant::ProjectDiff(_)) = state else {
+            return None;
+        };
+        let Some(proto::view::Variant::ProjectDiff(state)) = state.take() else {
+            unreachable!()
+        };
+
+        let project = workspace.read(cx).project().clone();
+        let project_diff = cx.new(|cx| {
+            let mut project_diff = Self::new(project.clone(), workspace, window, cx);
+            project_diff.remote_id = Some(remote_id);
+            project_diff
+        });
+        let apply_update = project_diff.update(cx, |project_diff, cx| {
+            project_diff.apply_update_proto(
+                &project,
+                proto::update_view::Variant::ProjectDiff(proto::update_view::ProjectDiff {
+                    active_path: state.active_path,
+                    cursor: state.cursor,
+                }),
+                window,
+                cx,
+            )
+        });
+        Some(window.spawn(cx, async move |_| {
+            apply_update.await?;
+            Ok(project_diff)
+        }))
+    }
+
+    fn to_follow_event(event: &ProjectDiffEvent) -> Option<FollowEvent> {
+        match event {
+            // The diff moves its own selections when it's refreshed, so only edits and
+            // scrolling stop following.
+            ProjectDiffEvent::MovedAwayFromLeader => Some(FollowEvent::Unfollow),
+            ProjectDiffEvent::CursorMoved => None,
+        }
+    }
+
+    fn add_event_to_update_proto(
+        &self,
+        event: &ProjectDiffEvent,
+        update: &mut Option<proto::update_view::Variant>,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> bool {
+        if !matches!(event, ProjectDiffEvent::CursorMoved)
+            || !matches!(self.diff_base(cx), DiffBase::Head)
+        {
+            return false;
+        }
+        let Some((project_path, cursor)) = self.cursor_position(cx) else {
+            return false;
+        };
+        *update = Some(proto::update_view::Variant::ProjectDiff(
+            proto::update_view::ProjectDiff {
+                active_path: Some(project_path.to_proto()),
+                cursor: Some(language::proto::serialize_anchor(&cursor)),
+            },
+        ));
+        true
+    }
+
+    fn apply_update_proto(
+        &mut self,
+        project: &Entity<Project>,
+        message: proto::update_view::Variant,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let proto::update_view::Variant::ProjectDiff(message) = message else {
+            return Task::ready(Ok(()));
+        };
+        let Some(project_path) = message.active_path.and_then(ProjectPath::from_proto) else {
+            return Task::ready(Ok(()));
+        };
+        let cursor = message.cursor.and_then(language::proto::deserialize_anchor);
+        let open_buffer = project.update(cx, |project, cx| {
+            project.open_buffer(project_path.clone(), cx)
+        });
+        cx.spawn_in(window, async move |this, cx| {
+            let buffer = open_buffer.await?;
+            if let Some(cursor) = cursor {
+                buffer
+                    .update(cx, |buffer, _| buffer.wait_for_anchors([cursor]))
+                    .await?;
+            }
+            this.update_in(cx, |this, window, cx| {
+                this.show_leader_position(&project_path, &buffer, cursor, window, cx)
+            })
+        })
+    }
+
+    fn is_project_item(&self, _window: &Window, _cx: &App) -> bool {
+        true
+    }
+
+    fn set_leader_id(
+        &mut self,
+        leader_id: Option<CollaboratorId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            editor
+                .rhs_editor()
+                .update(cx, |editor, cx| editor.set_leader_id(leader_id, window, cx))
+        });
+    }
+
+    fn dedup(&self, existing: &Self, _window: &Window, cx: &App) -> Option<Dedup> {
+        if matches!(self.diff_base(cx), DiffBase::Head)
+            && matches!(existing.diff_base(cx), DiffBase::Head)
+        {
+            Some(Dedup::KeepExisting)
+        } else {
+            None
+        }
+    }
+}
+
 impl Render for ProjectDiff {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let is_empty = self.multibuffer.read(cx).is_empty();
@@ -1669,6 +1989,8 @@ impl Render for BranchDiffToolbar {
         let is_ai_enabled = AgentSettings::get_global(cx).enabled(cx);
 
         let show_review_button = !is_multibuffer_empty && is_ai_enabled;
+        let is_agent_reviewing = project_diff.read(cx).is_agent_reviewing(cx);
+        let agent_review_comment_count = project_diff.read(cx).agent_review_comment_count(cx);
 
         h_group_xl()
             .my_neg_1()
@@ -1707,6 +2029,41 @@ impl Render for BranchDiffToolbar {
                         })),
                 )
             })
+            .when(show_review_button, |this| {
+                let focus_handle = focus_handle.clone();
+                let label = if is_agent_reviewing {
+                    "Reviewing…".to_string()
+                } else if agent_review_comment_count > 0 {
+                    format!("Agent Comments ({agent_review_comment_count})")
+                } else {
+                    "Agent Review".to_string()
+                };
+                this.child(
+                    Button::new("request-agent-review", label)
+                        .icon(IconName::Sparkle)
+                        .icon_position(IconPosition::Start)
+                        .icon_size(IconSize::Small)
+                        .icon_color(Color::Muted)
+                        .disabled(is_agent_reviewing)
+                        .key_binding(KeyBinding::for_action_in(
+                            &RequestAgentReview,
+                            &focus_handle,
+                            cx,
+                        ))
+                        .tooltip(move |_, cx| {
+                            Tooltip::with_meta_in(
+                                "Agent Review",
+                                Some(&RequestAgentReview),
+                                "Ask the agent to comment on this diff.",
+                                &focus_handle,
+                                cx,
+                            )
+                        })
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.dispatch_action(&RequestAgentReview, window, cx);
+                        })),
+                )
+            })
             .when(review_count > 0, |this| {
                 this.child(vertical_divider()).child(
                     render_send_review_to_agent_button(review_count, &focus_handle).on_click(
@@ -5,6 +5,9 @@ mod registry;
 mod request;
 mod role;
 mod telemetry;
+mod usage_budget;
+mod usage_log;
+pub mod replay_provider;
 pub mod tool_schema;
 
 #[cfg(any(test, feature = "test-support"))]
@@ -38,6 +41,8 @@ pub use crate::registry::*;
 pub use crate::request::*;
 pub use crate::role::*;
 pub use crate::telemetry::*;
+pub use crate::usage_budget::*;
+pub use crate::usage_log::*;
 pub use crate::tool_schema::LanguageModelToolSchemaFormat;
 pub use zed_env_vars::{EnvVar, env_var};
 
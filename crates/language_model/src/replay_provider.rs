@@ -0,0 +1,604 @@
+//! Records the completions of a language model to fixture files and replays them, so that
+//! agent tests and evals can run offline and deterministically.
+//!
+//! Recordings are stored in a directory per model, with one file per request, named after a
+//! hash of the request. When a prompt or tool definition changes, its hash changes too, and
+//! replaying the request fails with an error that says the recording needs to be updated.
+
+use crate::{
+    AuthenticateError, ConfigurationViewTargetAgent, LanguageModel,
+    LanguageModelCacheConfiguration, LanguageModelCompletionError, LanguageModelCompletionEvent,
+    LanguageModelEffortLevel, LanguageModelId, LanguageModelName, LanguageModelProvider,
+    LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
+    LanguageModelRequest, LanguageModelToolChoice, LanguageModelToolSchemaFormat,
+};
+use anyhow::{Context as _, Result, anyhow};
+use futures::{FutureExt, StreamExt, future::BoxFuture, stream::BoxStream};
+use gpui::{
+    AnyView, App, AppContext as _, AsyncApp, EmptyView, Entity, SharedString, Task, Window,
+};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use util::ResultExt as _;
+
+const MODEL_FILE_NAME: &str = "model.json";
+
+/// A directory of recorded language model completions.
+#[derive(Clone, Debug)]
+pub struct LanguageModelRecordings {
+    dir: PathBuf,
+}
+
+impl LanguageModelRecordings {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Wraps the model so that each of its completions is saved to the recordings, replacing
+    /// any earlier recording of the same request.
+    pub fn record(&self, model: Arc<dyn LanguageModel>) -> Result<Arc<dyn LanguageModel>> {
+        let dir = self.model_dir(&model.provider_id(), &model.id());
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("creating recordings directory {}", dir.display()))?;
+        let info = RecordedModel::new(model.as_ref());
+        std::fs::write(
+            dir.join(MODEL_FILE_NAME),
+            serde_json::to_string_pretty(&info)?,
+        )?;
+        Ok(Arc::new(RecordingLanguageModel { model, dir }))
+    }
+
+    /// Returns a model that serves the completions recorded for the given model, without
+    /// making any requests.
+    pub fn replay(
+        &self,
+        provider_id: &LanguageModelProviderId,
+        model_id: &LanguageModelId,
+    ) -> Result<Arc<dyn LanguageModel>> {
+        let dir = self.model_dir(provider_id, model_id);
+        let model_path = dir.join(MODEL_FILE_NAME);
+        let info = std::fs::read_to_string(&model_path).with_context(|| {
+            format!(
+                "no recordings of {}/{} in {}",
+                provider_id.0,
+                model_id.0,
+                self.dir.display()
+            )
+        })?;
+        let info = serde_json::from_str(&info)
+            .with_context(|| format!("parsing {}", model_path.display()))?;
+        Ok(Arc::new(ReplayLanguageModel { info, dir }))
+    }
+
+    fn model_dir(
+        &self,
+        provider_id: &LanguageModelProviderId,
+        model_id: &LanguageModelId,
+    ) -> PathBuf {
+        self.dir
+            .join(sanitize_file_name(&provider_id.0))
+            .join(sanitize_file_name(&model_id.0))
+    }
+}
+
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Returns the name of the file a request is recorded in.
+///
+/// The thread and prompt IDs are left out of the hash, since they're generated anew for
+/// each run.
+fn recording_file_name(request: &LanguageModelRequest) -> Result<String> {
+    let request = LanguageModelRequest {
+        thread_id: None,
+        prompt_id: None,
+        ..request.clone()
+    };
+    let digest = Sha256::digest(serde_json::to_vec(&request)?);
+    let mut name = String::with_capacity(digest.len() * 2 + 5);
+    for byte in digest {
+        write!(name, "{byte:02x}").ok();
+    }
+    name.push_str(".json");
+    Ok(name)
+}
+
+/// The properties of a recorded model that determine how requests to it are built.
+#[derive(Debug, Serialize, Deserialize)]
+struct RecordedModel {
+    id: LanguageModelId,
+    name: String,
+    provider_id: String,
+    provider_name: String,
+    telemetry_id: String,
+    supports_images: bool,
+    supports_tools: bool,
+    supported_tool_choices: Vec<LanguageModelToolChoice>,
+    supports_streaming_tools: bool,
+    supports_split_token_display: bool,
+    supports_thinking: bool,
+    effort_levels: Vec<RecordedEffortLevel>,
+    uses_json_schema_subset: bool,
+    max_token_count: u64,
+    max_output_tokens: Option<u64>,
+    cache_configuration: Option<LanguageModelCacheConfiguration>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RecordedEffortLevel {
+    name: String,
+    value: String,
+    is_default: bool,
+}
+
+impl RecordedModel {
+    fn new(model: &dyn LanguageModel) -> Self {
+        Self {
+            id: model.id(),
+            name: model.name().0.to_string(),
+            provider_id: model.provider_id().0.to_string(),
+            provider_name: model.provider_name().0.to_string(),
+            telemetry_id: model.telemetry_id(),
+            supports_images: model.supports_images(),
+            supports_tools: model.supports_tools(),
+            supported_tool_choices: [
+                LanguageModelToolChoice::Auto,
+                LanguageModelToolChoice::Any,
+                LanguageModelToolChoice::None,
+            ]
+            .into_iter()
+            .filter(|choice| model.supports_tool_choice(choice.clone()))
+            .collect(),
+            supports_streaming_tools: model.supports_streaming_tools(),
+            supports_split_token_display: model.supports_split_token_display(),
+            supports_thinking: model.supports_thinking(),
+            effort_levels: model
+                .supported_effort_levels()
+                .into_iter()
+                .map(|level| RecordedEffortLevel {
+                    name: level.name.to_string(),
+                    value: level.value.to_string(),
+                    is_default: level.is_default,
+                })
+                .collect(),
+            uses_json_schema_subset: model.tool_input_format()
+                == LanguageModelToolSchemaFormat::JsonSchemaSubset,
+            max_token_count: model.max_token_count(),
+            max_output_tokens: model.max_output_tokens(),
+            cache_configuration: model.cache_configuration(),
+        }
+    }
+}
+
+/// A recorded completion. Errors are recorded as their messages, so they're replayed as
+/// [`LanguageModelCompletionError::Other`].
+#[derive(Debug, Serialize, Deserialize)]
+struct Recording {
+    request: LanguageModelRequest,
+    response: Result<Vec<Result<LanguageModelCompletionEvent, String>>, String>,
+}
+
+impl Recording {
+    fn save(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(recording_file_name(&self.request)?);
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("writing recording {}", path.display()))
+    }
+}
+
+/// A model that saves each completion of the model it wraps to the recordings.
+pub struct RecordingLanguageModel {
+    model: Arc<dyn LanguageModel>,
+    dir: PathBuf,
+}
+
+impl LanguageModel for RecordingLanguageModel {
+    fn id(&self) -> LanguageModelId {
+        self.model.id()
+    }
+
+    fn name(&self) -> LanguageModelName {
+        self.model.name()
+    }
+
+    fn provider_id(&self) -> LanguageModelProviderId {
+        self.model.provider_id()
+    }
+
+    fn provider_name(&self) -> LanguageModelProviderName {
+        self.model.provider_name()
+    }
+
+    fn upstream_provider_id(&self) -> LanguageModelProviderId {
+        self.model.upstream_provider_id()
+    }
+
+    fn upstream_provider_name(&self) -> LanguageModelProviderName {
+        self.model.upstream_provider_name()
+    }
+
+    fn telemetry_id(&self) -> String {
+        self.model.telemetry_id()
+    }
+
+    fn api_key(&self, cx: &App) -> Option<String> {
+        self.model.api_key(cx)
+    }
+
+    fn supports_thinking(&self) -> bool {
+        self.model.supports_thinking()
+    }
+
+    fn supported_effort_levels(&self) -> Vec<LanguageModelEffortLevel> {
+        self.model.supported_effort_levels()
+    }
+
+    fn supports_images(&self) -> bool {
+        self.model.supports_images()
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.model.supports_tools()
+    }
+
+    fn supports_tool_choice(&self, choice: LanguageModelToolChoice) -> bool {
+        self.model.supports_tool_choice(choice)
+    }
+
+    fn supports_streaming_tools(&self) -> bool {
+        self.model.supports_streaming_tools()
+    }
+
+    fn supports_split_token_display(&self) -> bool {
+        self.model.supports_split_token_display()
+    }
+
+    fn tool_input_format(&self) -> LanguageModelToolSchemaFormat {
+        self.model.tool_input_format()
+    }
+
+    fn max_token_count(&self) -> u64 {
+        self.model.max_token_count()
+    }
+
+    fn max_output_tokens(&self) -> Option<u64> {
+        self.model.max_output_tokens()
+    }
+
+    fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        cx: &App,
+    ) -> BoxFuture<'static, Result<u64>> {
+        self.model.count_tokens(request, cx)
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AsyncApp,
+    ) -> BoxFuture<
+        'static,
+        Result<
+            BoxStream<'static, Result<LanguageModelCompletionEvent, LanguageModelCompletionError>>,
+            LanguageModelCompletionError,
+        >,
+    > {
+        let dir = self.dir.clone();
+        let completion = self.model.stream_completion(request.clone(), cx);
+        async move {
+            let events = match completion.await {
+                Ok(events) => events,
+                Err(error) => {
+                    let recording = Recording {
+                        request,
+                        response: Err(error.to_string()),
+                    };
+                    recording.save(&dir).log_err();
+                    return Err(error);
+                }
+            };
+
+            let recorded_events = Arc::new(Mutex::new(Vec::new()));
+            let events = events
+                .inspect({
+                    let recorded_events = recorded_events.clone();
+                    move |event| {
+                        recorded_events.lock().push(match event {
+                            Ok(event) => Ok(event.clone()),
+                            Err(error) => Err(error.to_string()),
+                        });
+                    }
+                })
+                .map(Some)
+                // Saves the recording once the stream has ended.
+                .chain(futures::stream::once(async move {
+                    let recording = Recording {
+                        request,
+                        response: Ok(std::mem::take(&mut *recorded_events.lock())),
+                    };
+                    recording.save(&dir).log_err();
+                    None
+                }))
+                .filter_map(futures::future::ready);
+            Ok(events.boxed())
+        }
+        .boxed()
+    }
+
+    fn cache_configuration(&self) -> Option<LanguageModelCacheConfiguration> {
+        self.model.cache_configuration()
+    }
+}
+
+/// A model that serves recorded completions.
+pub struct ReplayLanguageModel {
+    info: RecordedModel,
+    dir: PathBuf,
+}
+
+impl LanguageModel for ReplayLanguageModel {
+    fn id(&self) -> LanguageModelId {
+        self.info.id.clone()
+    }
+
+    fn name(&self) -> LanguageModelName {
+        LanguageModelName::from(self.info.name.clone())
+    }
+
+    fn provider_id(&self) -> LanguageModelProviderId {
+        LanguageModelProviderId::from(self.info.provider_id.clone())
+    }
+
+    fn provider_name(&self) -> LanguageModelProviderName {
+        LanguageModelProviderName::from(self.info.provider_name.clone())
+    }
+
+    fn telemetry_id(&self) -> String {
+        self.info.telemetry_id.clone()
+    }
+
+    fn supports_thinking(&self) -> bool {
+        self.info.supports_thinking
+    }
+
+    fn supported_effort_levels(&self) -> Vec<LanguageModelEffortLevel> {
+        self.info
+            .effort_levels
+            .iter()
+            .map(|level| LanguageModelEffortLevel {
+                name: SharedString::from(level.name.clone()),
+                value: SharedString::from(level.value.clone()),
+                is_default: level.is_default,
+            })
+            .collect()
+    }
+
+    fn supports_images(&self) -> bool {
+        self.info.supports_images
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.info.supports_tools
+    }
+
+    fn supports_tool_choice(&self, choice: LanguageModelToolChoice) -> bool {
+        self.info.supported_tool_choices.contains(&choice)
+    }
+
+    fn supports_streaming_tools(&self) -> bool {
+        self.info.supports_streaming_tools
+    }
+
+    fn supports_split_token_display(&self) -> bool {
+        self.info.supports_split_token_display
+    }
+
+    fn tool_input_format(&self) -> LanguageModelToolSchemaFormat {
+        if self.info.uses_json_schema_subset {
+            LanguageModelToolSchemaFormat::JsonSchemaSubset
+        } else {
+            LanguageModelToolSchemaFormat::JsonSchema
+        }
+    }
+
+    fn max_token_count(&self) -> u64 {
+        self.info.max_token_count
+    }
+
+    fn max_output_tokens(&self) -> Option<u64> {
+        self.info.max_output_tokens
+    }
+
+    fn count_tokens(&self, _: LanguageModelRequest, _: &App) -> BoxFuture<'static, Result<u64>> {
+        futures::future::ready(Ok(0)).boxed()
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+        _: &AsyncApp,
+    ) -> BoxFuture<
+        'static,
+        Result<
+            BoxStream<'static, Result<LanguageModelCompletionEvent, LanguageModelCompletionError>>,
+            LanguageModelCompletionError,
+        >,
+    > {
+        let recording = self.load_recording(&request);
+        async move {
+            match recording?.response {
+                Ok(events) => Ok(futures::stream::iter(events.into_iter().map(|event| {
+                    event.map_err(|error| LanguageModelCompletionError::Other(anyhow!(error)))
+                }))
+                .boxed()),
+                Err(error) => Err(LanguageModelCompletionError::Other(anyhow!(error))),
+            }
+        }
+        .boxed()
+    }
+
+    fn cache_configuration(&self) -> Option<LanguageModelCacheConfiguration> {
+        self.info.cache_configuration.clone()
+    }
+}
+
+impl ReplayLanguageModel {
+    fn load_recording(&self, request: &LanguageModelRequest) -> Result<Recording> {
+        let path = self.dir.join(recording_file_name(request)?);
+        let recording = std::fs::read_to_string(&path).map_err(|_| {
+            anyhow!(
+                "no recording of this request to {}/{} in {}. \
+                If the prompt, the tools or the conversation changed, the recordings need to be updated.",
+                self.info.provider_id,
+                self.info.id.0,
+                self.dir.display()
+            )
+        })?;
+        serde_json::from_str(&recording).with_context(|| format!("parsing {}", path.display()))
+    }
+}
+
+/// A provider for replayed models, to configure them as the default model.
+pub struct ReplayLanguageModelProvider {
+    models: Vec<Arc<dyn LanguageModel>>,
+}
+
+impl ReplayLanguageModelProvider {
+    pub fn new(models: Vec<Arc<dyn LanguageModel>>) -> Self {
+        Self { models }
+    }
+}
+
+impl LanguageModelProviderState for ReplayLanguageModelProvider {
+    type ObservableEntity = ();
+
+    fn observable_entity(&self) -> Option<Entity<Self::ObservableEntity>> {
+        None
+    }
+}
+
+impl LanguageModelProvider for ReplayLanguageModelProvider {
+    fn id(&self) -> LanguageModelProviderId {
+        self.models
+            .first()
+            .map(|model| model.provider_id())
+            .unwrap_or_else(|| LanguageModelProviderId::from("replay".to_string()))
+    }
+
+    fn name(&self) -> LanguageModelProviderName {
+        self.models
+            .first()
+            .map(|model| model.provider_name())
+            .unwrap_or_else(|| LanguageModelProviderName::from("Replay".to_string()))
+    }
+
+    fn default_model(&self, _cx: &App) -> Option<Arc<dyn LanguageModel>> {
+        self.models.first().cloned()
+    }
+
+    fn default_fast_model(&self, _cx: &App) -> Option<Arc<dyn LanguageModel>> {
+        self.models.first().cloned()
+    }
+
+    fn provided_models(&self, _: &App) -> Vec<Arc<dyn LanguageModel>> {
+        self.models.clone()
+    }
+
+    fn is_authenticated(&self, _: &App) -> bool {
+        true
+    }
+
+    fn authenticate(&self, _: &mut App) -> Task<Result<(), AuthenticateError>> {
+        Task::ready(Ok(()))
+    }
+
+    fn configuration_view(
+        &self,
+        _target_agent: ConfigurationViewTargetAgent,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> AnyView {
+        cx.new(|_| EmptyView).into()
+    }
+
+    fn reset_credentials(&self, _: &mut App) -> Task<Result<()>> {
+        Task::ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LanguageModelRequestMessage, Role, fake_provider::FakeLanguageModel};
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_record_and_replay(cx: &mut TestAppContext) {
+        let dir = tempfile::tempdir().unwrap();
+        let recordings = LanguageModelRecordings::new(dir.path());
+        let fake_model = Arc::new(FakeLanguageModel::default());
+        let model = recordings.record(fake_model.clone()).unwrap();
+
+        let request = LanguageModelRequest {
+            thread_id: Some("recorded-thread".into()),
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec!["Hello".into()],
+                cache: false,
+                reasoning_details: None,
+            }],
+            ..Default::default()
+        };
+        let completion = model.stream_completion(request.clone(), &cx.to_async());
+        fake_model.send_last_completion_stream_text_chunk("Hi there");
+        fake_model.end_last_completion_stream();
+        let events = completion.await.unwrap().collect::<Vec<_>>().await;
+        assert_eq!(events.len(), 1);
+
+        // Requests from another thread replay the same completion.
+        let model = recordings
+            .replay(&fake_model.provider_id(), &fake_model.id())
+            .unwrap();
+        let replayed_request = LanguageModelRequest {
+            thread_id: Some("replayed-thread".into()),
+            ..request.clone()
+        };
+        let events = model
+            .stream_completion(replayed_request, &cx.to_async())
+            .await
+            .unwrap()
+            .map(|event| event.unwrap())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(
+            events,
+            vec![LanguageModelCompletionEvent::Text("Hi there".into())]
+        );
+
+        // Changing the prompt invalidates the recording.
+        let mut changed_request = request;
+        changed_request.messages[0].content = vec!["Hello!".into()];
+        let error = model
+            .stream_completion(changed_request, &cx.to_async())
+            .await
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("no recording of this request"));
+    }
+}
@@ -0,0 +1,194 @@
+use crate::LANGUAGE_MODEL_USAGE;
+use chrono::{Datelike as _, Local, NaiveDate};
+use gpui::{App, Global, PromptLevel, Task, Window};
+use settings::UsageBudgetContent;
+use util::ResultExt as _;
+
+/// The budget periods the user chose to keep sending requests in, after being warned that
+/// the budget was reached.
+#[derive(Default)]
+struct ExceededUsageBudgets {
+    day: Option<NaiveDate>,
+    month: Option<(i32, u32)>,
+}
+
+impl Global for ExceededUsageBudgets {}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BudgetPeriod {
+    Day,
+    Month,
+}
+
+/// Checks the estimated cost of today's and this month's language model requests against the
+/// `usage_budget` setting, and asks the user whether to make another request anyway when a
+/// budget is reached.
+///
+/// Every feature that sends requests on the user's behalf should call this first. Returns
+/// `None` when there's no budget to check, so that the request can be made right away.
+/// Otherwise, the task resolves to whether the request should be made. The user is only
+/// warned once per period.
+pub fn confirm_usage_budget(
+    budget: UsageBudgetContent,
+    window: &mut Window,
+    cx: &mut App,
+) -> Option<Task<bool>> {
+    let today = Local::now().date_naive();
+    let this_month = (today.year(), today.month());
+    let exceeded = cx.default_global::<ExceededUsageBudgets>();
+    let daily_budget = budget.daily.filter(|_| exceeded.day != Some(today));
+    let monthly_budget = budget
+        .monthly
+        .filter(|_| exceeded.month != Some(this_month));
+    if daily_budget.is_none() && monthly_budget.is_none() {
+        return None;
+    }
+
+    Some(window.spawn(cx, async move |cx| {
+        let costs = cx
+            .background_spawn(async move {
+                anyhow::Ok((
+                    LANGUAGE_MODEL_USAGE.cost_today()?,
+                    LANGUAGE_MODEL_USAGE.cost_this_month()?,
+                ))
+            })
+            .await;
+        let Some((cost_today, cost_this_month)) = costs.log_err() else {
+            return true;
+        };
+        let Some((period, budget, cost)) =
+            reached_budget(daily_budget, monthly_budget, cost_today, cost_this_month)
+        else {
+            return true;
+        };
+
+        let (message, detail) = match period {
+            BudgetPeriod::Day => (
+                format!("You've reached your daily budget of ${budget:.2}"),
+                format!("Language model requests made today have an estimated cost of ${cost:.2}."),
+            ),
+            BudgetPeriod::Month => (
+                format!("You've reached your monthly budget of ${budget:.2}"),
+                format!(
+                    "Language model requests made this month have an estimated cost of ${cost:.2}."
+                ),
+            ),
+        };
+        let Ok(answer) = cx.update(|window, cx| {
+            window.prompt(
+                PromptLevel::Warning,
+                &message,
+                Some(&detail),
+                &["Send Anyway", "Cancel"],
+                cx,
+            )
+        }) else {
+            return false;
+        };
+        if answer.await != Ok(0) {
+            return false;
+        }
+
+        cx.update(|_, cx| {
+            let exceeded = cx.default_global::<ExceededUsageBudgets>();
+            match period {
+                BudgetPeriod::Day => exceeded.day = Some(today),
+                BudgetPeriod::Month => exceeded.month = Some(this_month),
+            }
+        })
+        .ok();
+        true
+    }))
+}
+
+/// Returns the period whose budget the costs have reached, along with the budget and the
+/// period's cost. The daily budget is checked first.
+fn reached_budget(
+    daily_budget: Option<f64>,
+    monthly_budget: Option<f64>,
+    cost_today: f64,
+    cost_this_month: f64,
+) -> Option<(BudgetPeriod, f64, f64)> {
+    if let Some(budget) = daily_budget
+        && cost_today >= budget
+    {
+        Some((BudgetPeriod::Day, budget, cost_today))
+    } else if let Some(budget) = monthly_budget
+        && cost_this_month >= budget
+    {
+        Some((BudgetPeriod::Month, budget, cost_this_month))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LanguageModelUsageFeature, usage_log::record_test_usage};
+    use gpui::TestAppContext;
+
+    #[test]
+    fn test_reached_budget() {
+        assert_eq!(reached_budget(None, None, 10., 100.), None);
+        assert_eq!(reached_budget(Some(20.), Some(200.), 10., 100.), None);
+        assert_eq!(
+            reached_budget(Some(10.), Some(50.), 10., 100.),
+            Some((BudgetPeriod::Day, 10., 10.))
+        );
+        assert_eq!(
+            reached_budget(Some(20.), Some(50.), 10., 100.),
+            Some((BudgetPeriod::Month, 50., 100.))
+        );
+        assert_eq!(
+            reached_budget(None, Some(50.), 10., 100.),
+            Some((BudgetPeriod::Month, 50., 100.))
+        );
+    }
+
+    #[gpui::test]
+    async fn test_confirm_usage_budget(cx: &mut TestAppContext) {
+        LANGUAGE_MODEL_USAGE.clear_usage().await.unwrap();
+        record_test_usage(LanguageModelUsageFeature::InlineAssistant, 5.).await;
+        let cx = cx.add_empty_window();
+        let budget = |daily: Option<f64>| UsageBudgetContent {
+            daily,
+            monthly: None,
+        };
+
+        // Without a budget, requests are made right away.
+        assert!(
+            cx.update(|window, cx| confirm_usage_budget(budget(None), window, cx))
+                .is_none()
+        );
+
+        // Below the budget, requests are made without asking.
+        let confirm = cx
+            .update(|window, cx| confirm_usage_budget(budget(Some(10.)), window, cx))
+            .unwrap();
+        assert!(confirm.await);
+        assert!(!cx.has_pending_prompt());
+
+        // Once the budget is reached, the user is asked first.
+        let confirm = cx
+            .update(|window, cx| confirm_usage_budget(budget(Some(5.)), window, cx))
+            .unwrap();
+        cx.run_until_parked();
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer("Cancel");
+        assert!(!confirm.await);
+
+        let confirm = cx
+            .update(|window, cx| confirm_usage_budget(budget(Some(5.)), window, cx))
+            .unwrap();
+        cx.run_until_parked();
+        cx.simulate_prompt_answer("Send Anyway");
+        assert!(confirm.await);
+
+        // The user isn't asked again for the rest of the day.
+        assert!(
+            cx.update(|window, cx| confirm_usage_budget(budget(Some(5.)), window, cx))
+                .is_none()
+        );
+    }
+}
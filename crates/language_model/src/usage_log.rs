@@ -0,0 +1,538 @@
+//! A local log of the tokens used by language model requests, so that users can see how much
+//! each project and feature costs them.
+//!
+//! Usage is recorded per request, along with a cost estimated from the `model_pricing`
+//! setting at the time of the request.
+
+use crate::{
+    LanguageModelCompletionEvent, LanguageModelId, LanguageModelProviderId,
+    LanguageModelTextStream, TokenUsage,
+};
+use anyhow::{Context as _, Result};
+use db::{
+    query,
+    sqlez::{
+        bindable::Column, domain::Domain, statement::Statement,
+        thread_safe_connection::ThreadSafeConnection,
+    },
+    sqlez_macros::sql,
+};
+use futures::{Stream, StreamExt as _, stream::BoxStream};
+use gpui::{App, BackgroundExecutor, Global};
+use indoc::indoc;
+use parking_lot::Mutex;
+use settings::LanguageModelPricing;
+use std::sync::Arc;
+
+/// A feature that makes language model requests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LanguageModelUsageFeature {
+    Agent,
+    ThreadSummary,
+    InlineAssistant,
+    TerminalInlineAssistant,
+    CommitMessage,
+    TextThread,
+    EditPrediction,
+    DiffReview,
+}
+
+impl LanguageModelUsageFeature {
+    pub const ALL: [Self; 8] = [
+        Self::Agent,
+        Self::ThreadSummary,
+        Self::InlineAssistant,
+        Self::TerminalInlineAssistant,
+        Self::CommitMessage,
+        Self::TextThread,
+        Self::EditPrediction,
+        Self::DiffReview,
+    ];
+
+    /// The key the feature is stored under in the usage log.
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Agent => "agent",
+            Self::ThreadSummary => "thread_summary",
+            Self::InlineAssistant => "inline_assistant",
+            Self::TerminalInlineAssistant => "terminal_inline_assistant",
+            Self::CommitMessage => "commit_message",
+            Self::TextThread => "text_thread",
+            Self::EditPrediction => "edit_prediction",
+            Self::DiffReview => "diff_review",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|feature| feature.key() == key)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Agent => "Agent",
+            Self::ThreadSummary => "Thread Summaries",
+            Self::InlineAssistant => "Inline Assistant",
+            Self::TerminalInlineAssistant => "Terminal Inline Assistant",
+            Self::CommitMessage => "Commit Messages",
+            Self::TextThread => "Text Threads",
+            Self::EditPrediction => "Edit Predictions",
+            Self::DiffReview => "Diff Reviews",
+        }
+    }
+}
+
+/// The prices used to estimate the cost of requests, from the `model_pricing` setting.
+#[derive(Default)]
+struct GlobalLanguageModelPricing(Vec<LanguageModelPricing>);
+
+impl Global for GlobalLanguageModelPricing {}
+
+/// Sets the prices used to estimate the cost of subsequent requests.
+pub fn set_language_model_pricing(pricing: Vec<LanguageModelPricing>, cx: &mut App) {
+    cx.set_global(GlobalLanguageModelPricing(pricing));
+}
+
+fn pricing_for_model(
+    provider: &LanguageModelProviderId,
+    model: &LanguageModelId,
+    cx: &App,
+) -> Option<LanguageModelPricing> {
+    let pricing = cx.try_global::<GlobalLanguageModelPricing>()?;
+    pricing
+        .0
+        .iter()
+        .rev()
+        .find(|pricing| {
+            pricing
+                .provider
+                .as_ref()
+                .is_none_or(|setting| setting.0 == provider.0.as_ref())
+                && pricing
+                    .model
+                    .as_ref()
+                    .is_none_or(|setting| *setting == model.0.as_ref())
+        })
+        .cloned()
+}
+
+/// Estimates the cost of a request in US dollars, with prices given per million tokens.
+/// Cached input tokens cost as much as other input tokens unless they have prices of their own.
+pub fn estimate_cost(pricing: &LanguageModelPricing, usage: &TokenUsage) -> f64 {
+    let input = pricing.input.unwrap_or(0.);
+    let output = pricing.output.unwrap_or(0.);
+    let cache_creation_input = pricing.cache_creation_input.unwrap_or(input);
+    let cache_read_input = pricing.cache_read_input.unwrap_or(input);
+    (usage.input_tokens as f64 * input
+        + usage.output_tokens as f64 * output
+        + usage.cache_creation_input_tokens as f64 * cache_creation_input
+        + usage.cache_read_input_tokens as f64 * cache_read_input)
+        / 1_000_000.
+}
+
+/// Tracks the token usage of a single request, and records it in the usage log when dropped.
+///
+/// Providers report the usage of a request so far with each
+/// [`LanguageModelCompletionEvent::UsageUpdate`], so only the latest update is kept. The
+/// usage is recorded even when the request fails or is canceled partway through.
+pub struct LanguageModelUsageTracker {
+    provider: LanguageModelProviderId,
+    model: LanguageModelId,
+    feature: LanguageModelUsageFeature,
+    project: Option<String>,
+    pricing: Option<LanguageModelPricing>,
+    usage: Arc<Mutex<TokenUsage>>,
+    executor: BackgroundExecutor,
+}
+
+impl LanguageModelUsageTracker {
+    /// Creates a tracker for a request made by the given feature. The project is the names of
+    /// the project's worktrees, if the request was made on behalf of one.
+    pub fn new(
+        provider: LanguageModelProviderId,
+        model: LanguageModelId,
+        feature: LanguageModelUsageFeature,
+        project: Option<String>,
+        cx: &App,
+    ) -> Self {
+        Self {
+            pricing: pricing_for_model(&provider, &model, cx),
+            provider,
+            model,
+            feature,
+            project,
+            usage: Arc::default(),
+            executor: cx.background_executor().clone(),
+        }
+    }
+
+    /// Sets the usage of the request so far.
+    pub fn update(&self, usage: TokenUsage) {
+        *self.usage.lock() = usage;
+    }
+
+    /// Wraps a completion stream, updating the usage with each usage update in the stream.
+    pub fn track<E: Send + 'static>(
+        self,
+        stream: impl Stream<Item = Result<LanguageModelCompletionEvent, E>> + Send + 'static,
+    ) -> BoxStream<'static, Result<LanguageModelCompletionEvent, E>> {
+        stream
+            .inspect(move |event| {
+                if let Ok(LanguageModelCompletionEvent::UsageUpdate(usage)) = event {
+                    self.update(*usage);
+                }
+            })
+            .boxed()
+    }
+
+    /// Wraps a text stream, taking the usage from the stream's last token usage.
+    pub fn track_text(mut self, stream: LanguageModelTextStream) -> LanguageModelTextStream {
+        self.usage = stream.last_token_usage.clone();
+        LanguageModelTextStream {
+            stream: stream
+                .stream
+                .inspect(move |_| {
+                    // The usage is recorded when the tracker is dropped, so it must live as
+                    // long as the stream.
+                    let _ = &self;
+                })
+                .boxed(),
+            ..stream
+        }
+    }
+}
+
+impl Drop for LanguageModelUsageTracker {
+    fn drop(&mut self) {
+        let usage = *self.usage.lock();
+        if usage.total_tokens() == 0 {
+            return;
+        }
+
+        let entry = UsageEntry {
+            project: self.project.take(),
+            feature: self.feature.key(),
+            provider: self.provider.0.to_string(),
+            model: self.model.0.to_string(),
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cache_creation_input_tokens: usage.cache_creation_input_tokens,
+            cache_read_input_tokens: usage.cache_read_input_tokens,
+            cost: self
+                .pricing
+                .as_ref()
+                .map(|pricing| estimate_cost(pricing, &usage)),
+        };
+        self.executor
+            .spawn(async move {
+                if let Err(error) = LANGUAGE_MODEL_USAGE.record_usage(entry).await {
+                    log::error!("failed to record language model usage: {error:?}");
+                }
+            })
+            .detach();
+    }
+}
+
+struct UsageEntry {
+    project: Option<String>,
+    feature: &'static str,
+    provider: String,
+    model: String,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_input_tokens: u64,
+    cache_read_input_tokens: u64,
+    cost: Option<f64>,
+}
+
+/// The usage of one model by one feature in one project, on one day.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LanguageModelUsageSummary {
+    /// The local date of the requests, formatted as `YYYY-MM-DD`.
+    pub day: String,
+    pub project: Option<String>,
+    pub feature: Option<LanguageModelUsageFeature>,
+    pub provider: LanguageModelProviderId,
+    pub model: LanguageModelId,
+    pub requests: u64,
+    pub usage: TokenUsage,
+    /// The estimated cost of the requests in US dollars, or `None` if none of them had a price.
+    pub cost: Option<f64>,
+}
+
+impl Column for LanguageModelUsageSummary {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (day, next_index): (String, i32) = Column::column(statement, start_index)?;
+        let (project, next_index): (Option<String>, i32) = Column::column(statement, next_index)?;
+        let (feature, next_index): (String, i32) = Column::column(statement, next_index)?;
+        let (provider, next_index): (String, i32) = Column::column(statement, next_index)?;
+        let (model, next_index): (String, i32) = Column::column(statement, next_index)?;
+        let (requests, next_index): (u64, i32) = Column::column(statement, next_index)?;
+        let (input_tokens, next_index): (u64, i32) = Column::column(statement, next_index)?;
+        let (output_tokens, next_index): (u64, i32) = Column::column(statement, next_index)?;
+        let (cache_creation_input_tokens, next_index): (u64, i32) =
+            Column::column(statement, next_index)?;
+        let (cache_read_input_tokens, next_index): (u64, i32) =
+            Column::column(statement, next_index)?;
+        let (cost, next_index): (Option<f64>, i32) = Column::column(statement, next_index)?;
+        let summary = Self {
+            day,
+            project,
+            feature: LanguageModelUsageFeature::from_key(&feature),
+            provider: LanguageModelProviderId::from(provider),
+            model: LanguageModelId::from(model),
+            requests,
+            usage: TokenUsage {
+                input_tokens,
+                output_tokens,
+                cache_creation_input_tokens,
+                cache_read_input_tokens,
+            },
+            cost,
+        };
+        Ok((summary, next_index))
+    }
+}
+
+pub struct LanguageModelUsageDb(ThreadSafeConnection);
+
+impl Domain for LanguageModelUsageDb {
+    const NAME: &str = stringify!(LanguageModelUsageDb);
+    const MIGRATIONS: &[&str] = &[sql!(
+        CREATE TABLE IF NOT EXISTS language_model_usage(
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp INTEGER DEFAULT (unixepoch()) NOT NULL,
+            project TEXT,
+            feature TEXT NOT NULL,
+            provider TEXT NOT NULL,
+            model TEXT NOT NULL,
+            input_tokens INTEGER NOT NULL,
+            output_tokens INTEGER NOT NULL,
+            cache_creation_input_tokens INTEGER NOT NULL,
+            cache_read_input_tokens INTEGER NOT NULL,
+            cost REAL
+        ) STRICT;
+        CREATE INDEX IF NOT EXISTS language_model_usage_timestamp ON language_model_usage(timestamp);
+    )];
+}
+
+db::static_connection!(LANGUAGE_MODEL_USAGE, LanguageModelUsageDb, []);
+
+impl LanguageModelUsageDb {
+    async fn record_usage(&self, entry: UsageEntry) -> Result<()> {
+        self.insert_usage(
+            entry.project,
+            entry.feature.to_string(),
+            entry.provider,
+            entry.model,
+            entry.input_tokens,
+            entry.output_tokens,
+            entry.cache_creation_input_tokens,
+            entry.cache_read_input_tokens,
+            entry.cost,
+        )
+        .await
+    }
+
+    query! {
+        async fn insert_usage(
+            project: Option<String>,
+            feature: String,
+            provider: String,
+            model: String,
+            input_tokens: u64,
+            output_tokens: u64,
+            cache_creation_input_tokens: u64,
+            cache_read_input_tokens: u64,
+            cost: Option<f64>
+        ) -> Result<()> {
+            INSERT INTO language_model_usage (
+                project,
+                feature,
+                provider,
+                model,
+                input_tokens,
+                output_tokens,
+                cache_creation_input_tokens,
+                cache_read_input_tokens,
+                cost
+            ) VALUES ((?), (?), (?), (?), (?), (?), (?), (?), (?))
+        }
+    }
+
+    /// Returns the usage of the last `days` days, including today, grouped by day, project,
+    /// feature and model, with the most recent days first.
+    pub fn usage_summaries(&self, days: u32) -> Result<Vec<LanguageModelUsageSummary>> {
+        self.select_bound::<u32, LanguageModelUsageSummary>(indoc! {"
+            SELECT
+                date(timestamp, 'unixepoch', 'localtime') AS day,
+                project,
+                feature,
+                provider,
+                model,
+                COUNT(1),
+                SUM(input_tokens),
+                SUM(output_tokens),
+                SUM(cache_creation_input_tokens),
+                SUM(cache_read_input_tokens),
+                SUM(cost)
+            FROM language_model_usage
+            WHERE date(timestamp, 'unixepoch', 'localtime') > date('now', 'localtime', printf('-%d days', (?)))
+            GROUP BY day, project, feature, provider, model
+            ORDER BY day DESC, SUM(cost) DESC, SUM(input_tokens + output_tokens) DESC
+        "})?(days)
+        .context("Failed to read language model usage")
+    }
+
+    /// Returns the estimated cost of today's requests, in US dollars.
+    pub fn cost_today(&self) -> Result<f64> {
+        self.select_row::<f64>(indoc! {"
+            SELECT COALESCE(SUM(cost), 0)
+            FROM language_model_usage
+            WHERE date(timestamp, 'unixepoch', 'localtime') = date('now', 'localtime')
+        "})?()
+        .map(Option::unwrap_or_default)
+        .context("Failed to read today's language model cost")
+    }
+
+    /// Returns the estimated cost of this calendar month's requests, in US dollars.
+    pub fn cost_this_month(&self) -> Result<f64> {
+        self.select_row::<f64>(indoc! {"
+            SELECT COALESCE(SUM(cost), 0)
+            FROM language_model_usage
+            WHERE date(timestamp, 'unixepoch', 'localtime') >= date('now', 'localtime', 'start of month')
+        "})?()
+        .map(Option::unwrap_or_default)
+        .context("Failed to read this month's language model cost")
+    }
+
+    query! {
+        pub async fn clear_usage() -> Result<()> {
+            DELETE FROM language_model_usage
+        }
+    }
+}
+
+/// Records a request with the given cost in the usage log.
+#[cfg(test)]
+pub(crate) async fn record_test_usage(feature: LanguageModelUsageFeature, cost: f64) {
+    LANGUAGE_MODEL_USAGE
+        .record_usage(UsageEntry {
+            project: None,
+            feature: feature.key(),
+            provider: "test".into(),
+            model: "test".into(),
+            input_tokens: 100,
+            output_tokens: 10,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+            cost: Some(cost),
+        })
+        .await
+        .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use settings::LanguageModelProviderSetting;
+
+    #[test]
+    fn test_estimate_cost() {
+        let pricing = LanguageModelPricing {
+            provider: None,
+            model: None,
+            input: Some(3.),
+            output: Some(15.),
+            cache_creation_input: None,
+            cache_read_input: Some(0.3),
+        };
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_creation_input_tokens: 200_000,
+            cache_read_input_tokens: 1_000_000,
+        };
+        assert!((estimate_cost(&pricing, &usage) - (3. + 1.5 + 0.6 + 0.3)).abs() < 1e-9);
+    }
+
+    #[gpui::test]
+    fn test_pricing_for_model(cx: &mut TestAppContext) {
+        let pricing =
+            |provider: Option<&str>, model: Option<&str>, input: f64| LanguageModelPricing {
+                provider: provider.map(|provider| LanguageModelProviderSetting(provider.into())),
+                model: model.map(Into::into),
+                input: Some(input),
+                output: None,
+                cache_creation_input: None,
+                cache_read_input: None,
+            };
+        cx.update(|cx| {
+            set_language_model_pricing(
+                vec![
+                    pricing(Some("anthropic"), None, 1.),
+                    pricing(Some("anthropic"), Some("claude-opus"), 2.),
+                    pricing(Some("openai"), Some("gpt"), 3.),
+                ],
+                cx,
+            );
+
+            let input_price = |provider: &str, model: &str| {
+                pricing_for_model(
+                    &LanguageModelProviderId::from(provider.to_string()),
+                    &LanguageModelId::from(model.to_string()),
+                    cx,
+                )
+                .and_then(|pricing| pricing.input)
+            };
+            assert_eq!(input_price("anthropic", "claude-sonnet"), Some(1.));
+            assert_eq!(input_price("anthropic", "claude-opus"), Some(2.));
+            assert_eq!(input_price("openai", "gpt"), Some(3.));
+            assert_eq!(input_price("openai", "o3"), None);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_usage_summaries() {
+        let db = LanguageModelUsageDb::open_test_db("test_usage_summaries").await;
+        let entry = |project: Option<&str>, model: &str, input_tokens, cost| UsageEntry {
+            project: project.map(Into::into),
+            feature: LanguageModelUsageFeature::Agent.key(),
+            provider: "anthropic".into(),
+            model: model.into(),
+            input_tokens,
+            output_tokens: 10,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+            cost,
+        };
+        db.record_usage(entry(Some("zed"), "claude-opus", 100, Some(1.)))
+            .await
+            .unwrap();
+        db.record_usage(entry(Some("zed"), "claude-opus", 200, Some(2.)))
+            .await
+            .unwrap();
+        db.record_usage(entry(None, "claude-sonnet", 50, None))
+            .await
+            .unwrap();
+
+        let summaries = db.usage_summaries(1).unwrap();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].project.as_deref(), Some("zed"));
+        assert_eq!(summaries[0].feature, Some(LanguageModelUsageFeature::Agent));
+        assert_eq!(summaries[0].requests, 2);
+        assert_eq!(summaries[0].usage.input_tokens, 300);
+        assert_eq!(summaries[0].usage.output_tokens, 20);
+        assert_eq!(summaries[0].cost, Some(3.));
+        assert_eq!(summaries[1].project, None);
+        assert_eq!(summaries[1].model.0.as_ref(), "claude-sonnet");
+        assert_eq!(summaries[1].cost, None);
+
+        assert_eq!(db.cost_today().unwrap(), 3.);
+        assert_eq!(db.cost_this_month().unwrap(), 3.);
+
+        db.clear_usage().await.unwrap();
+        assert!(db.usage_summaries(1).unwrap().is_empty());
+        assert_eq!(db.cost_today().unwrap(), 0.);
+    }
+}
@@ -0,0 +1,284 @@
+use fuzzy::{StringMatch, StringMatchCandidate, match_strings};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Render, Task,
+    WeakEntity, Window,
+};
+use picker::{Picker, PickerDelegate};
+use std::sync::Arc;
+use ui::{HighlightedLabel, IconButtonShape, ListItem, ListItemSpacing, Tooltip, prelude::*};
+use util::ResultExt;
+use workspace::{ModalView, Workspace, notifications::DetachAndPromptErr};
+use zed_actions::layout_selector::Toggle;
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(LayoutSelector::register).detach();
+}
+
+pub struct LayoutSelector {
+    picker: Entity<Picker<LayoutSelectorDelegate>>,
+}
+
+impl LayoutSelector {
+    fn register(
+        workspace: &mut Workspace,
+        _window: Option<&mut Window>,
+        _: &mut Context<Workspace>,
+    ) {
+        workspace.register_action(|_, _: &Toggle, window, cx| {
+            let layout_names = workspace::saved_layout_names(cx);
+            cx.spawn_in(window, async move |workspace, cx| {
+                let layout_names = layout_names.await.log_err().unwrap_or_default();
+                workspace.update_in(cx, |workspace, window, cx| {
+                    let weak_workspace = cx.weak_entity();
+                    workspace.toggle_modal(window, cx, move |window, cx| {
+                        LayoutSelector::new(weak_workspace, layout_names, window, cx)
+                    });
+                })
+            })
+            .detach_and_log_err(cx);
+        });
+    }
+
+    fn new(
+        workspace: WeakEntity<Workspace>,
+        layout_names: Vec<String>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate =
+            LayoutSelectorDelegate::new(cx.entity().downgrade(), workspace, layout_names);
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        Self { picker }
+    }
+}
+
+impl Render for LayoutSelector {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("LayoutSelector")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+impl Focusable for LayoutSelector {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for LayoutSelector {}
+impl ModalView for LayoutSelector {}
+
+pub struct LayoutSelectorDelegate {
+    selector: WeakEntity<LayoutSelector>,
+    workspace: WeakEntity<Workspace>,
+    layout_names: Vec<String>,
+    matches: Vec<StringMatch>,
+    /// The name to save the current layout under, offered as the first entry when
+    /// the query doesn't name an existing layout.
+    new_layout_name: Option<String>,
+    selected_index: usize,
+}
+
+impl LayoutSelectorDelegate {
+    fn new(
+        selector: WeakEntity<LayoutSelector>,
+        workspace: WeakEntity<Workspace>,
+        layout_names: Vec<String>,
+    ) -> Self {
+        Self {
+            selector,
+            workspace,
+            layout_names,
+            matches: Vec::new(),
+            new_layout_name: None,
+            selected_index: 0,
+        }
+    }
+
+    fn layout_match(&self, ix: usize) -> Option<&StringMatch> {
+        let offset = self.new_layout_name.is_some() as usize;
+        self.matches.get(ix.checked_sub(offset)?)
+    }
+
+    fn delete_layout(&mut self, name: String, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.layout_names.retain(|layout_name| *layout_name != name);
+        workspace::delete_layout(name, cx).detach_and_prompt_err(
+            "Failed to delete layout",
+            window,
+            cx,
+            |_, _, _| None,
+        );
+    }
+}
+
+impl PickerDelegate for LayoutSelectorDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Apply a layout, or type a name to save the current one…".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("No saved layouts".into())
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len() + self.new_layout_name.is_some() as usize
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .layout_names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| StringMatchCandidate::new(id, name))
+            .collect::<Vec<_>>();
+        let query = query.trim().to_string();
+        let new_layout_name = (!query.is_empty()
+            && !self.layout_names.iter().any(|name| *name == query))
+        .then(|| query.clone());
+
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    true,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.new_layout_name = new_layout_name;
+                delegate.selected_index = 0;
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+
+        if let Some(name) = self
+            .new_layout_name
+            .clone()
+            .filter(|_| self.selected_index == 0)
+        {
+            workspace.update(cx, |workspace, cx| {
+                workspace
+                    .save_layout(name, window, cx)
+                    .detach_and_prompt_err("Failed to save layout", window, cx, |_, _, _| None);
+            });
+        } else if let Some(layout_match) = self.layout_match(self.selected_index) {
+            let name = layout_match.string.clone();
+            workspace.update(cx, |workspace, cx| {
+                // Secondary confirmation overwrites the selected layout with the current one.
+                if secondary {
+                    workspace
+                        .save_layout(name, window, cx)
+                        .detach_and_prompt_err("Failed to save layout", window, cx, |_, _, _| None);
+                } else {
+                    workspace
+                        .apply_saved_layout(name, window, cx)
+                        .detach_and_prompt_err("Failed to apply layout", window, cx, |_, _, _| {
+                            None
+                        });
+                }
+            });
+        } else {
+            return;
+        }
+
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.selector
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let item = ListItem::new(ix)
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(selected);
+
+        if ix == 0
+            && let Some(name) = &self.new_layout_name
+        {
+            return Some(
+                item.start_slot(Icon::new(IconName::Plus).color(Color::Muted))
+                    .child(Label::new(format!("Save current layout as \"{name}\""))),
+            );
+        }
+
+        let layout_match = self.layout_match(ix)?;
+        let name = layout_match.string.clone();
+        let delete_button = IconButton::new("delete-layout", IconName::Trash)
+            .shape(IconButtonShape::Square)
+            .icon_color(Color::Muted)
+            .icon_size(IconSize::XSmall)
+            .on_click(cx.listener(move |picker, _, window, cx| {
+                cx.stop_propagation();
+                window.prevent_default();
+                picker.delegate.delete_layout(name.clone(), window, cx);
+                picker.refresh(window, cx);
+            }))
+            .tooltip(Tooltip::text("Delete Layout"));
+
+        Some(
+            item.child(HighlightedLabel::new(
+                layout_match.string.clone(),
+                layout_match.positions.clone(),
+            ))
+            .end_hover_slot(delete_button),
+        )
+    }
+}
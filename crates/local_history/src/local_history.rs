@@ -0,0 +1,555 @@
+mod local_history_view;
+mod persistence;
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use fs::Fs;
+use gpui::{
+    App, AppContext as _, Context, Entity, EntityId, Global, Subscription, Task, WeakEntity,
+    actions,
+};
+use language::{Buffer, BufferEvent};
+use persistence::LOCAL_HISTORY_DB;
+use project::{Project, ProjectPath, buffer_store::BufferStoreEvent};
+use std::{path::PathBuf, sync::Arc, time::Duration};
+use time::OffsetDateTime;
+use util::ResultExt as _;
+use workspace::Workspace;
+use worktree::PathChange;
+
+pub use local_history_view::LocalHistoryView;
+
+actions!(
+    local_history,
+    [
+        /// Shows the local history of the active file.
+        ShowFileHistory,
+        /// Replaces the file's contents with the selected snapshot.
+        RestoreSnapshot,
+        /// Toggles between comparing the selected snapshot with the current contents
+        /// of the file, and with the snapshot before it.
+        ToggleCompareWithPrevious
+    ]
+);
+
+/// How often snapshots of files with unsaved changes are taken.
+const UNSAVED_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const MAX_SNAPSHOTS_PER_FILE: i64 = 100;
+const MAX_SNAPSHOT_AGE: time::Duration = time::Duration::days(30);
+/// Larger files aren't kept in the local history.
+const MAX_SNAPSHOT_LEN: usize = 1024 * 1024;
+/// The oldest snapshots are deleted once all snapshots add up to more than this.
+const MAX_TOTAL_SNAPSHOTS_LEN: i64 = 256 * 1024 * 1024;
+/// How often the whole history is pruned, in addition to when it's loaded.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub type SnapshotId = i64;
+
+pub fn init(cx: &mut App) {
+    let local_history = cx.new(LocalHistory::new);
+    cx.set_global(GlobalLocalHistory(local_history));
+
+    cx.observe_new(
+        |workspace: &mut Workspace, _window, cx: &mut Context<Workspace>| {
+            let project = workspace.project().clone();
+            LocalHistory::global(cx).update(cx, |local_history, cx| {
+                local_history.track_project(&project, cx)
+            });
+            workspace.register_action(|workspace, _: &ShowFileHistory, window, cx| {
+                LocalHistoryView::open_for_active_item(workspace, window, cx);
+            });
+        },
+    )
+    .detach();
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotKind {
+    /// The file was saved.
+    Saved,
+    /// The file had unsaved changes, captured periodically.
+    Unsaved,
+    /// The file changed on disk, outside of the editor.
+    External,
+    /// The contents the file had on disk before it was first edited.
+    Opened,
+}
+
+impl SnapshotKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SnapshotKind::Saved => "saved",
+            SnapshotKind::Unsaved => "unsaved",
+            SnapshotKind::External => "external",
+            SnapshotKind::Opened => "opened",
+        }
+    }
+
+    fn from_str(kind: &str) -> Option<Self> {
+        match kind {
+            "saved" => Some(SnapshotKind::Saved),
+            "unsaved" => Some(SnapshotKind::Unsaved),
+            "external" => Some(SnapshotKind::External),
+            "opened" => Some(SnapshotKind::Opened),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SnapshotKind::Saved => "Saved",
+            SnapshotKind::Unsaved => "Unsaved changes",
+            SnapshotKind::External => "Changed on disk",
+            SnapshotKind::Opened => "Opened",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    pub id: SnapshotId,
+    pub kind: SnapshotKind,
+    pub timestamp: OffsetDateTime,
+}
+
+struct GlobalLocalHistory(Entity<LocalHistory>);
+
+impl Global for GlobalLocalHistory {}
+
+/// Records snapshots of the files in local projects, keyed by their absolute path,
+/// whenever they're saved or change on disk, and periodically while they have
+/// unsaved changes.
+pub struct LocalHistory {
+    projects: HashMap<EntityId, Vec<Subscription>>,
+    buffers: HashMap<EntityId, TrackedBuffer>,
+    _snapshot_unsaved_buffers: Task<()>,
+    _prune_snapshots: Task<()>,
+}
+
+struct TrackedBuffer {
+    buffer: WeakEntity<Buffer>,
+    last_snapshot_version: Option<clock::Global>,
+    recorded_initial_contents: bool,
+    _subscription: Subscription,
+}
+
+impl LocalHistory {
+    pub fn global(cx: &App) -> Entity<Self> {
+        cx.global::<GlobalLocalHistory>().0.clone()
+    }
+
+    fn new(cx: &mut Context<Self>) -> Self {
+        let snapshot_unsaved_buffers = cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor()
+                    .timer(UNSAVED_SNAPSHOT_INTERVAL)
+                    .await;
+                if this
+                    .update(cx, |this, cx| this.snapshot_unsaved_buffers(cx))
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        // Files are only pruned as they're snapshotted, so the history of files that
+        // aren't edited anymore is pruned here.
+        let executor = cx.background_executor().clone();
+        let prune_snapshots = cx.background_spawn(async move {
+            loop {
+                prune_all_snapshots().await.log_err();
+                executor.timer(PRUNE_INTERVAL).await;
+            }
+        });
+
+        Self {
+            projects: HashMap::default(),
+            buffers: HashMap::default(),
+            _snapshot_unsaved_buffers: snapshot_unsaved_buffers,
+            _prune_snapshots: prune_snapshots,
+        }
+    }
+
+    fn track_project(&mut self, project: &Entity<Project>, cx: &mut Context<Self>) {
+        if !project.read(cx).is_local() || self.projects.contains_key(&project.entity_id()) {
+            return;
+        }
+
+        let fs = project.read(cx).fs().clone();
+        let buffer_store = project.read(cx).buffer_store().clone();
+        let buffers = buffer_store.read(cx).buffers().collect::<Vec<_>>();
+        for buffer in buffers {
+            self.track_buffer(buffer, fs.clone(), cx);
+        }
+
+        let project_id = project.entity_id();
+        let subscriptions = vec![
+            cx.subscribe(&buffer_store, move |this, _, event, cx| {
+                if let BufferStoreEvent::BufferAdded(buffer) = event {
+                    this.track_buffer(buffer.clone(), fs.clone(), cx);
+                }
+            }),
+            cx.subscribe(project, |this, project, event, cx| {
+                if let project::Event::WorktreeUpdatedEntries(worktree_id, changes) = event {
+                    for (path, _, change) in changes.iter() {
+                        if matches!(change, PathChange::Updated | PathChange::AddedOrUpdated) {
+                            let project_path = ProjectPath {
+                                worktree_id: *worktree_id,
+                                path: path.clone(),
+                            };
+                            this.file_changed_on_disk(&project, project_path, cx);
+                        }
+                    }
+                }
+            }),
+        ];
+        cx.observe_release(project, move |this, _, _| {
+            this.projects.remove(&project_id);
+        })
+        .detach();
+        self.projects.insert(project_id, subscriptions);
+    }
+
+    fn track_buffer(&mut self, buffer: Entity<Buffer>, fs: Arc<dyn Fs>, cx: &mut Context<Self>) {
+        let buffer_id = buffer.entity_id();
+        if self.buffers.contains_key(&buffer_id) {
+            return;
+        }
+
+        let subscription = cx.subscribe(&buffer, move |this, buffer, event, cx| match event {
+            BufferEvent::Saved => this.snapshot_buffer(&buffer, SnapshotKind::Saved, cx),
+            BufferEvent::Reloaded => this.snapshot_buffer(&buffer, SnapshotKind::External, cx),
+            BufferEvent::DirtyChanged => {
+                // Before the first edit, record the contents the file was opened with,
+                // so they can always be recovered.
+                let Some(tracked) = this.buffers.get_mut(&buffer_id) else {
+                    return;
+                };
+                if tracked.recorded_initial_contents || !buffer.read(cx).is_dirty() {
+                    return;
+                }
+                tracked.recorded_initial_contents = true;
+                if let Some(abs_path) = abs_path_for_buffer(buffer.read(cx), cx) {
+                    let fs = fs.clone();
+                    cx.background_spawn(record_file_snapshot(
+                        fs,
+                        abs_path,
+                        SnapshotKind::Opened,
+                        false,
+                    ))
+                    .detach_and_log_err(cx);
+                }
+            }
+            _ => {}
+        });
+        cx.observe_release(&buffer, move |this, _, _| {
+            this.buffers.remove(&buffer_id);
+        })
+        .detach();
+        self.buffers.insert(
+            buffer_id,
+            TrackedBuffer {
+                buffer: buffer.downgrade(),
+                last_snapshot_version: None,
+                recorded_initial_contents: false,
+                _subscription: subscription,
+            },
+        );
+
+        // Capture changes that were made to the file while it wasn't open.
+        if let Some(abs_path) = abs_path_for_buffer(buffer.read(cx), cx) {
+            cx.background_spawn(record_file_snapshot(
+                fs,
+                abs_path,
+                SnapshotKind::External,
+                true,
+            ))
+            .detach_and_log_err(cx);
+        }
+    }
+
+    /// Snapshots a file that changed on disk while it isn't open. Open buffers take
+    /// their own snapshot when they're reloaded.
+    fn file_changed_on_disk(
+        &mut self,
+        project: &Entity<Project>,
+        project_path: ProjectPath,
+        cx: &mut Context<Self>,
+    ) {
+        let project = project.read(cx);
+        if project.get_open_buffer(&project_path, cx).is_some() {
+            return;
+        }
+        let Some(abs_path) = project.absolute_path(&project_path, cx) else {
+            return;
+        };
+        cx.background_spawn(record_file_snapshot(
+            project.fs().clone(),
+            abs_path,
+            SnapshotKind::External,
+            true,
+        ))
+        .detach_and_log_err(cx);
+    }
+
+    fn snapshot_unsaved_buffers(&mut self, cx: &mut Context<Self>) {
+        let buffers = self
+            .buffers
+            .values()
+            .filter_map(|tracked| tracked.buffer.upgrade())
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            let buffer_ref = buffer.read(cx);
+            if !buffer_ref.is_dirty() {
+                continue;
+            }
+            let unchanged = self
+                .buffers
+                .get(&buffer.entity_id())
+                .and_then(|tracked| tracked.last_snapshot_version.as_ref())
+                .is_some_and(|version| !buffer_ref.version().changed_since(version));
+            if !unchanged {
+                self.snapshot_buffer(&buffer, SnapshotKind::Unsaved, cx);
+            }
+        }
+    }
+
+    fn snapshot_buffer(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        kind: SnapshotKind,
+        cx: &mut Context<Self>,
+    ) {
+        let buffer_id = buffer.entity_id();
+        let buffer = buffer.read(cx);
+        let Some(abs_path) = abs_path_for_buffer(buffer, cx) else {
+            return;
+        };
+        if let Some(tracked) = self.buffers.get_mut(&buffer_id) {
+            tracked.last_snapshot_version = Some(buffer.version());
+        }
+        let text = buffer.text();
+        cx.background_spawn(record_snapshot(abs_path, kind, text))
+            .detach_and_log_err(cx);
+    }
+
+    /// Returns the snapshots of the file at the given path, most recent first.
+    pub fn snapshots(&self, abs_path: PathBuf, cx: &App) -> Task<Result<Vec<Snapshot>>> {
+        cx.background_spawn(async move {
+            LOCAL_HISTORY_DB
+                .snapshots(&abs_path)?
+                .into_iter()
+                .filter_map(|(id, kind, timestamp)| {
+                    Some(Ok(Snapshot {
+                        id,
+                        kind: SnapshotKind::from_str(&kind)?,
+                        timestamp: match OffsetDateTime::from_unix_timestamp(timestamp) {
+                            Ok(timestamp) => timestamp,
+                            Err(error) => return Some(Err(error.into())),
+                        },
+                    }))
+                })
+                .collect()
+        })
+    }
+
+    pub fn snapshot_text(&self, id: SnapshotId, cx: &App) -> Task<Result<String>> {
+        cx.background_spawn(async move {
+            LOCAL_HISTORY_DB
+                .snapshot_text(id)?
+                .with_context(|| format!("snapshot {id} does not exist"))
+        })
+    }
+}
+
+async fn record_snapshot(abs_path: PathBuf, kind: SnapshotKind, text: String) -> Result<()> {
+    if text.len() > MAX_SNAPSHOT_LEN {
+        return Ok(());
+    }
+    if LOCAL_HISTORY_DB
+        .latest_snapshot_text(&abs_path)?
+        .is_some_and(|latest_text| latest_text == text)
+    {
+        return Ok(());
+    }
+
+    let now = OffsetDateTime::now_utc();
+    LOCAL_HISTORY_DB
+        .insert_snapshot(
+            abs_path.clone(),
+            kind.as_str().to_string(),
+            text,
+            now.unix_timestamp(),
+        )
+        .await?;
+    LOCAL_HISTORY_DB
+        .prune_snapshots(
+            abs_path,
+            (now - MAX_SNAPSHOT_AGE).unix_timestamp(),
+            MAX_SNAPSHOTS_PER_FILE,
+        )
+        .await
+        .log_err();
+    Ok(())
+}
+
+/// Deletes the snapshots of all files that are too old, and the oldest ones beyond the
+/// total size limit.
+async fn prune_all_snapshots() -> Result<()> {
+    let cutoff = OffsetDateTime::now_utc() - MAX_SNAPSHOT_AGE;
+    LOCAL_HISTORY_DB
+        .delete_snapshots_before(cutoff.unix_timestamp())
+        .await?;
+    LOCAL_HISTORY_DB
+        .delete_snapshots_beyond_len(MAX_TOTAL_SNAPSHOTS_LEN)
+        .await
+}
+
+/// Records the file's contents on disk. Files without a history are skipped when
+/// `only_if_followed` is set, so that unrelated files, like build artifacts, aren't
+/// recorded when they change.
+async fn record_file_snapshot(
+    fs: Arc<dyn Fs>,
+    abs_path: PathBuf,
+    kind: SnapshotKind,
+    only_if_followed: bool,
+) -> Result<()> {
+    if only_if_followed && !LOCAL_HISTORY_DB.has_snapshots(&abs_path)? {
+        return Ok(());
+    }
+    let text = fs.load(&abs_path).await?;
+    record_snapshot(abs_path, kind, text).await
+}
+
+fn abs_path_for_buffer(buffer: &Buffer, cx: &App) -> Option<PathBuf> {
+    buffer
+        .file()
+        .and_then(|file| file.as_local())
+        .map(|file| file.abs_path(cx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use persistence::LocalHistoryDb;
+    use serde_json::json;
+    use std::path::Path;
+    use util::path;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_snapshots_are_recorded(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let fs = app_state.fs.as_fake();
+        fs.insert_tree(
+            path!("/test_snapshots_are_recorded"),
+            json!({ "file.txt": "one" }),
+        )
+        .await;
+        let project = Project::test(
+            app_state.fs.clone(),
+            [path!("/test_snapshots_are_recorded").as_ref()],
+            cx,
+        )
+        .await;
+        let local_history = cx.update(|cx| LocalHistory::global(cx));
+        local_history.update(cx, |local_history, cx| {
+            local_history.track_project(&project, cx)
+        });
+
+        let abs_path = PathBuf::from(path!("/test_snapshots_are_recorded/file.txt"));
+        let buffer = project
+            .update(cx, |project, cx| project.open_local_buffer(&abs_path, cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        // Files without a history don't get one just by being opened.
+        assert!(!LOCAL_HISTORY_DB.has_snapshots(&abs_path).unwrap());
+
+        buffer.update(cx, |buffer, cx| buffer.set_text("two", cx));
+        cx.run_until_parked();
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        let snapshots = local_history
+            .read_with(cx, |local_history, cx| {
+                local_history.snapshots(abs_path.clone(), cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            snapshots
+                .iter()
+                .map(|snapshot| snapshot.kind)
+                .collect::<Vec<_>>(),
+            [SnapshotKind::Saved, SnapshotKind::Opened]
+        );
+        let mut texts = Vec::new();
+        for snapshot in &snapshots {
+            let text = local_history
+                .read_with(cx, |local_history, cx| {
+                    local_history.snapshot_text(snapshot.id, cx)
+                })
+                .await
+                .unwrap();
+            texts.push(text);
+        }
+        assert_eq!(texts, ["two", "one"]);
+    }
+
+    #[gpui::test]
+    async fn test_prune_snapshots() {
+        let db = LocalHistoryDb::open_test_db("test_prune_snapshots").await;
+        let edited = PathBuf::from(path!("/edited.txt"));
+        let abandoned = PathBuf::from(path!("/abandoned.txt"));
+        for (path, text, timestamp) in [
+            (&abandoned, "abandoned", 10),
+            (&edited, "one", 100),
+            (&edited, "two", 200),
+            (&edited, "three", 300),
+        ] {
+            db.insert_snapshot(path.clone(), "saved".into(), text.into(), timestamp)
+                .await
+                .unwrap();
+        }
+        let timestamps = |path: &Path| {
+            db.snapshots(path)
+                .unwrap()
+                .into_iter()
+                .map(|(_, _, timestamp)| timestamp)
+                .collect::<Vec<_>>()
+        };
+
+        // Files are pruned to their most recent snapshots as they're snapshotted.
+        db.prune_snapshots(edited.clone(), 0, 2).await.unwrap();
+        assert_eq!(timestamps(&edited), [300, 200]);
+        assert_eq!(timestamps(&abandoned), [10]);
+
+        // Old snapshots are deleted for all files, even ones that aren't snapshotted
+        // anymore.
+        db.delete_snapshots_before(50).await.unwrap();
+        assert_eq!(timestamps(&edited), [300, 200]);
+        assert_eq!(timestamps(&abandoned), Vec::<i64>::new());
+
+        // Beyond the total size limit, the oldest snapshots are deleted first.
+        db.delete_snapshots_beyond_len("three".len() as i64 + 1)
+            .await
+            .unwrap();
+        assert_eq!(timestamps(&edited), [300]);
+    }
+
+    pub(crate) fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let app_state = AppState::test(cx);
+            editor::init(cx);
+            init(cx);
+            app_state
+        })
+    }
+}
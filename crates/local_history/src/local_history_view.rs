@@ -0,0 +1,506 @@
+use anyhow::Context as _;
+use buffer_diff::BufferDiff;
+use editor::{Editor, EditorEvent, MultiBuffer};
+use gpui::{
+    AnyElement, App, Context, Entity, EventEmitter, FocusHandle, Focusable, Render, Subscription,
+    Task, UniformListScrollHandle, Window, uniform_list,
+};
+use language::{Buffer, BufferEvent, Capability};
+use std::{path::PathBuf, sync::Arc, time::Duration};
+use time::{OffsetDateTime, UtcOffset};
+use ui::{ListItem, ListItemSpacing, Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::{
+    Workspace,
+    item::{Item, TabContentParams},
+    notifications::DetachAndPromptErr as _,
+};
+
+use crate::{
+    LocalHistory, RestoreSnapshot, Snapshot, ToggleCompareWithPrevious, abs_path_for_buffer,
+};
+
+/// Snapshots are recorded in the background, so the list is reloaded shortly after
+/// the file is saved or reloaded.
+const RELOAD_SNAPSHOTS_DELAY: Duration = Duration::from_millis(250);
+
+/// Lists the snapshots in a file's local history, showing the file as of the selected
+/// snapshot, diffed against its current contents or against the snapshot before it.
+pub struct LocalHistoryView {
+    buffer: Entity<Buffer>,
+    abs_path: PathBuf,
+    snapshots: Vec<Snapshot>,
+    selected_snapshot: Option<usize>,
+    compare_with_previous: bool,
+    snapshot_text: Option<String>,
+    previous_snapshot_text: Option<String>,
+    snapshot_buffer: Entity<Buffer>,
+    diff: Entity<BufferDiff>,
+    editor: Entity<Editor>,
+    scroll_handle: UniformListScrollHandle,
+    local_timezone: UtcOffset,
+    focus_handle: FocusHandle,
+    load_snapshots_task: Task<()>,
+    load_snapshot_task: Task<()>,
+    update_diff_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl LocalHistoryView {
+    pub fn open_for_active_item(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(project_path) = workspace
+            .active_item(cx)
+            .and_then(|item| item.project_path(cx))
+        else {
+            return;
+        };
+        let open_buffer = workspace
+            .project()
+            .update(cx, |project, cx| project.open_buffer(project_path, cx));
+        cx.spawn_in(window, async move |workspace, cx| {
+            let buffer = open_buffer.await?;
+            workspace.update_in(cx, |workspace, window, cx| {
+                Self::open(buffer, workspace, window, cx)
+            })?
+        })
+        .detach_and_prompt_err("Failed to open local history", window, cx, |_, _, _| None);
+    }
+
+    pub fn open(
+        buffer: Entity<Buffer>,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> anyhow::Result<()> {
+        let abs_path = abs_path_for_buffer(buffer.read(cx), cx)
+            .context("only local files have a local history")?;
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items_of_type::<Self>()
+            .find(|view| view.read(cx).abs_path == abs_path);
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            return Ok(());
+        }
+
+        let view = cx.new(|cx| Self::new(buffer, abs_path, window, cx));
+        workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+        Ok(())
+    }
+
+    fn new(
+        buffer: Entity<Buffer>,
+        abs_path: PathBuf,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let language = buffer.read(cx).language().cloned();
+        let snapshot_buffer = cx.new(|cx| {
+            let mut buffer = Buffer::local("", cx);
+            buffer.set_language(language, cx);
+            buffer.set_capability(Capability::ReadOnly, cx);
+            buffer
+        });
+        let diff = cx.new(|cx| BufferDiff::new(&snapshot_buffer.read(cx).text_snapshot(), cx));
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer = MultiBuffer::singleton(snapshot_buffer.clone(), cx);
+            multibuffer.add_diff(diff.clone(), cx);
+            multibuffer
+        });
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer, None, window, cx);
+            editor.set_read_only(true);
+            editor.start_temporary_diff_override();
+            editor.disable_diagnostics(cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor.set_render_diff_hunk_controls(
+                Arc::new(|_, _, _, _, _, _, _, _| gpui::Empty.into_any_element()),
+                cx,
+            );
+            editor
+        });
+
+        let subscriptions = vec![
+            cx.subscribe(&editor, |_, _, event: &EditorEvent, cx| {
+                cx.emit(event.clone())
+            }),
+            cx.subscribe(&buffer, |this, _, event, cx| match event {
+                BufferEvent::Edited => {
+                    if !this.compare_with_previous {
+                        this.update_diff(cx);
+                    }
+                }
+                BufferEvent::Saved | BufferEvent::Reloaded => this.load_snapshots(true, cx),
+                _ => {}
+            }),
+        ];
+
+        let local_offset = chrono::Local::now().offset().local_minus_utc();
+        let mut this = Self {
+            buffer,
+            abs_path,
+            snapshots: Vec::new(),
+            selected_snapshot: None,
+            compare_with_previous: false,
+            snapshot_text: None,
+            previous_snapshot_text: None,
+            snapshot_buffer,
+            diff,
+            editor,
+            scroll_handle: UniformListScrollHandle::new(),
+            local_timezone: UtcOffset::from_whole_seconds(local_offset).unwrap(),
+            focus_handle: cx.focus_handle(),
+            load_snapshots_task: Task::ready(()),
+            load_snapshot_task: Task::ready(()),
+            update_diff_task: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        this.load_snapshots(false, cx);
+        this
+    }
+
+    fn load_snapshots(&mut self, wait_for_new_snapshot: bool, cx: &mut Context<Self>) {
+        let abs_path = self.abs_path.clone();
+        self.load_snapshots_task = cx.spawn(async move |this, cx| {
+            if wait_for_new_snapshot {
+                cx.background_executor().timer(RELOAD_SNAPSHOTS_DELAY).await;
+            }
+            let Some(load_snapshots) = this
+                .update(cx, |_, cx| {
+                    LocalHistory::global(cx).read(cx).snapshots(abs_path, cx)
+                })
+                .ok()
+            else {
+                return;
+            };
+            let Some(snapshots) = load_snapshots.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                let selected_id = this
+                    .selected_snapshot
+                    .and_then(|ix| this.snapshots.get(ix))
+                    .map(|snapshot| snapshot.id);
+                this.snapshots = snapshots;
+                let selected_ix = selected_id
+                    .and_then(|id| this.snapshots.iter().position(|snapshot| snapshot.id == id))
+                    .unwrap_or(0);
+                this.select_snapshot(selected_ix, cx);
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn select_snapshot(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let Some(snapshot) = self.snapshots.get(ix) else {
+            return;
+        };
+        self.selected_snapshot = Some(ix);
+        self.snapshot_text = None;
+        self.previous_snapshot_text = None;
+
+        // Snapshots are listed most recent first.
+        let local_history = LocalHistory::global(cx);
+        let load_text = local_history.read(cx).snapshot_text(snapshot.id, cx);
+        let load_previous_text = self
+            .snapshots
+            .get(ix + 1)
+            .map(|previous| local_history.read(cx).snapshot_text(previous.id, cx));
+        self.load_snapshot_task = cx.spawn(async move |this, cx| {
+            let Some(text) = load_text.await.log_err() else {
+                return;
+            };
+            let previous_text = match load_previous_text {
+                Some(load_previous_text) => load_previous_text.await.log_err(),
+                None => None,
+            };
+            this.update(cx, |this, cx| {
+                this.snapshot_buffer
+                    .update(cx, |buffer, cx| buffer.set_text(text.as_str(), cx));
+                this.snapshot_text = Some(text);
+                this.previous_snapshot_text = previous_text;
+                this.update_diff(cx);
+                cx.notify();
+            })
+            .ok();
+        });
+        cx.notify();
+    }
+
+    fn update_diff(&mut self, cx: &mut Context<Self>) {
+        let base_text = if self.compare_with_previous {
+            self.previous_snapshot_text.clone()
+        } else {
+            Some(self.buffer.read(cx).text())
+        };
+        let language = self.snapshot_buffer.read(cx).language().cloned();
+        let snapshot = self.snapshot_buffer.read(cx).text_snapshot();
+        let update = self.diff.update(cx, |diff, cx| {
+            diff.set_base_text(base_text.map(Into::into), language, snapshot, cx)
+        });
+        self.update_diff_task = cx.background_spawn(async move {
+            update.await.ok();
+        });
+    }
+
+    fn toggle_compare_with_previous(
+        &mut self,
+        _: &ToggleCompareWithPrevious,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.compare_with_previous = !self.compare_with_previous;
+        self.update_diff(cx);
+        cx.notify();
+    }
+
+    fn restore_snapshot(&mut self, _: &RestoreSnapshot, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(text) = self.snapshot_text.clone() else {
+            return;
+        };
+        // Restoring is an ordinary edit, so it can be undone and isn't saved until the
+        // file is.
+        self.buffer.update(cx, |buffer, cx| {
+            let edits = language::text_diff(&buffer.text(), &text);
+            buffer.edit(edits, None, cx);
+        });
+    }
+
+    fn render_snapshot(&self, ix: usize, cx: &Context<Self>) -> AnyElement {
+        let snapshot = &self.snapshots[ix];
+        let now = OffsetDateTime::now_utc();
+        let relative_timestamp = time_format::format_localized_timestamp(
+            snapshot.timestamp,
+            now,
+            self.local_timezone,
+            time_format::TimestampFormat::Relative,
+        );
+        let absolute_timestamp = time_format::format_localized_timestamp(
+            snapshot.timestamp,
+            now,
+            self.local_timezone,
+            time_format::TimestampFormat::Absolute,
+        );
+
+        ListItem::new(ix)
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(self.selected_snapshot == Some(ix))
+            .child(
+                v_flex().child(Label::new(relative_timestamp)).child(
+                    Label::new(snapshot.kind.label())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+            )
+            .tooltip(Tooltip::text(absolute_timestamp))
+            .on_click(cx.listener(move |this, _, _, cx| this.select_snapshot(ix, cx)))
+            .into_any_element()
+    }
+}
+
+impl EventEmitter<EditorEvent> for LocalHistoryView {}
+
+impl Focusable for LocalHistoryView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for LocalHistoryView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let snapshot_count = self.snapshots.len();
+        let can_compare_with_previous = self
+            .selected_snapshot
+            .is_some_and(|ix| ix + 1 < snapshot_count);
+        h_flex()
+            .key_context("LocalHistoryView")
+            .size_full()
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::restore_snapshot))
+            .on_action(cx.listener(Self::toggle_compare_with_previous))
+            .child(
+                v_flex()
+                    .w(rems(16.))
+                    .h_full()
+                    .border_r_1()
+                    .border_color(cx.theme().colors().border)
+                    .when(snapshot_count == 0, |this| {
+                        this.child(
+                            div()
+                                .p_2()
+                                .child(Label::new("No local history yet").color(Color::Muted)),
+                        )
+                    })
+                    .child(
+                        uniform_list(
+                            "local-history-snapshots",
+                            snapshot_count,
+                            cx.processor(|this, range: std::ops::Range<usize>, _, cx| {
+                                range.map(|ix| this.render_snapshot(ix, cx)).collect()
+                            }),
+                        )
+                        .flex_grow()
+                        .track_scroll(&self.scroll_handle),
+                    ),
+            )
+            .child(
+                v_flex()
+                    .size_full()
+                    .child(
+                        h_flex()
+                            .p_1()
+                            .gap_1()
+                            .justify_end()
+                            .border_b_1()
+                            .border_color(cx.theme().colors().border)
+                            .child(
+                                Button::new(
+                                    "compare-with-previous",
+                                    if self.compare_with_previous {
+                                        "Compare with Current"
+                                    } else {
+                                        "Compare with Previous"
+                                    },
+                                )
+                                .disabled(!self.compare_with_previous && !can_compare_with_previous)
+                                .on_click(|_, window, cx| {
+                                    window.dispatch_action(Box::new(ToggleCompareWithPrevious), cx)
+                                }),
+                            )
+                            .child(
+                                Button::new("restore-snapshot", "Restore This Version")
+                                    .disabled(
+                                        self.snapshot_text.is_none()
+                                            || self.buffer.read(cx).read_only(),
+                                    )
+                                    .tooltip(Tooltip::text(
+                                        "Replace the file's contents with this version",
+                                    ))
+                                    .on_click(|_, window, cx| {
+                                        window.dispatch_action(Box::new(RestoreSnapshot), cx)
+                                    }),
+                            ),
+                    )
+                    .child(self.editor.clone()),
+            )
+    }
+}
+
+impl Item for LocalHistoryView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _: &Window, _: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun).color(Color::Muted))
+    }
+
+    fn tab_content_text(&self, _detail: usize, _: &App) -> SharedString {
+        match self.abs_path.file_name() {
+            Some(file_name) => format!("{} - local history", file_name.to_string_lossy()).into(),
+            None => "Local history".into(),
+        }
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(self.abs_path.to_string_lossy().into_owned().into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, _: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(0, cx))
+            .color(params.text_color())
+            .into_any_element()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn to_item_events(event: &EditorEvent, f: &mut dyn FnMut(workspace::item::ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::init_test;
+    use gpui::TestAppContext;
+    use project::Project;
+    use serde_json::json;
+    use util::path;
+    use workspace::MultiWorkspace;
+
+    #[gpui::test]
+    async fn test_restore_snapshot(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(
+                path!("/test_restore_snapshot"),
+                json!({ "file.txt": "one" }),
+            )
+            .await;
+        let project = Project::test(
+            app_state.fs.clone(),
+            [path!("/test_restore_snapshot").as_ref()],
+            cx,
+        )
+        .await;
+        let (multi_workspace, cx) =
+            cx.add_window_view(|window, cx| MultiWorkspace::test_new(project.clone(), window, cx));
+        let workspace =
+            multi_workspace.read_with(cx, |multi_workspace, _| multi_workspace.workspace().clone());
+
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/test_restore_snapshot/file.txt"), cx)
+            })
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| buffer.set_text("two", cx));
+        cx.run_until_parked();
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| buffer.set_text("three", cx));
+        cx.run_until_parked();
+
+        workspace
+            .update_in(cx, |workspace, window, cx| {
+                LocalHistoryView::open(buffer.clone(), workspace, window, cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+        let view = workspace.read_with(cx, |workspace, cx| {
+            workspace
+                .active_pane()
+                .read(cx)
+                .items_of_type::<LocalHistoryView>()
+                .next()
+                .unwrap()
+        });
+        view.read_with(cx, |view, _| {
+            assert_eq!(view.snapshots.len(), 2);
+            assert_eq!(view.snapshot_text.as_deref(), Some("two"));
+        });
+
+        // Restoring the snapshot the file was opened with edits the buffer back to it,
+        // without saving it.
+        view.update(cx, |view, cx| view.select_snapshot(1, cx));
+        cx.run_until_parked();
+        view.update_in(cx, |view, window, cx| {
+            view.restore_snapshot(&RestoreSnapshot, window, cx)
+        });
+        buffer.read_with(cx, |buffer, _| {
+            assert_eq!(buffer.text(), "one");
+            assert!(buffer.is_dirty());
+        });
+    }
+}
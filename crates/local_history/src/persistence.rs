@@ -0,0 +1,125 @@
+use std::path::{Path, PathBuf};
+
+use db::{
+    query,
+    sqlez::{domain::Domain, thread_safe_connection::ThreadSafeConnection},
+    sqlez_macros::sql,
+};
+
+use crate::SnapshotId;
+
+pub struct LocalHistoryDb(ThreadSafeConnection);
+
+impl Domain for LocalHistoryDb {
+    const NAME: &str = stringify!(LocalHistoryDb);
+
+    const MIGRATIONS: &[&str] = &[sql!(
+        CREATE TABLE local_history_snapshots(
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            abs_path BLOB NOT NULL,
+            kind TEXT NOT NULL,
+            text TEXT NOT NULL,
+            timestamp INTEGER NOT NULL
+        ) STRICT;
+
+        CREATE INDEX local_history_snapshots_by_path
+        ON local_history_snapshots(abs_path, timestamp);
+    )];
+}
+
+db::static_connection!(LOCAL_HISTORY_DB, LocalHistoryDb, []);
+
+impl LocalHistoryDb {
+    query! {
+        pub async fn insert_snapshot(
+            abs_path: PathBuf,
+            kind: String,
+            text: String,
+            timestamp: i64
+        ) -> Result<()> {
+            INSERT INTO local_history_snapshots(abs_path, kind, text, timestamp)
+            VALUES (?, ?, ?, ?)
+        }
+    }
+
+    query! {
+        pub fn latest_snapshot_text(abs_path: &Path) -> Result<Option<String>> {
+            SELECT text
+            FROM local_history_snapshots
+            WHERE abs_path = ?
+            ORDER BY timestamp DESC, id DESC
+            LIMIT 1
+        }
+    }
+
+    query! {
+        pub fn has_snapshots(abs_path: &Path) -> Result<bool> {
+            SELECT EXISTS(
+                SELECT 1
+                FROM local_history_snapshots
+                WHERE abs_path = ?
+            )
+        }
+    }
+
+    query! {
+        pub fn snapshots(abs_path: &Path) -> Result<Vec<(SnapshotId, String, i64)>> {
+            SELECT id, kind, timestamp
+            FROM local_history_snapshots
+            WHERE abs_path = ?
+            ORDER BY timestamp DESC, id DESC
+        }
+    }
+
+    query! {
+        pub fn snapshot_text(id: SnapshotId) -> Result<Option<String>> {
+            SELECT text
+            FROM local_history_snapshots
+            WHERE id = ?
+        }
+    }
+
+    // Deletes the snapshots of a file that are older than the cutoff, or beyond the
+    // most recent ones.
+    query! {
+        pub async fn prune_snapshots(abs_path: PathBuf, cutoff: i64, max_snapshots: i64) -> Result<()> {
+            DELETE FROM local_history_snapshots
+            WHERE abs_path = ?1 AND (
+                timestamp < ?2 OR id NOT IN (
+                    SELECT id
+                    FROM local_history_snapshots
+                    WHERE abs_path = ?1
+                    ORDER BY timestamp DESC, id DESC
+                    LIMIT ?3
+                )
+            )
+        }
+    }
+
+    query! {
+        pub async fn delete_snapshots_before(cutoff: i64) -> Result<()> {
+            DELETE FROM local_history_snapshots
+            WHERE timestamp < ?
+        }
+    }
+
+    // Deletes the oldest snapshots of all files, until the text of the remaining ones
+    // adds up to at most the given number of bytes.
+    query! {
+        pub async fn delete_snapshots_beyond_len(max_len: i64) -> Result<()> {
+            DELETE FROM local_history_snapshots
+            WHERE id IN (
+                SELECT id
+                FROM (
+                    SELECT
+                        id,
+                        SUM(length(CAST(text AS BLOB))) OVER (
+                            ORDER BY timestamp DESC, id DESC
+                        ) AS len
+                    FROM local_history_snapshots
+                )
+                WHERE len > ?
+            )
+        }
+    }
+}
@@ -405,6 +405,12 @@ pub fn debug_adapters_dir() -> &'static PathBuf {
     DEBUG_ADAPTERS_DIR.get_or_init(|| data_dir().join("debug_adapters"))
 }
 
+/// Returns the path to the directory containing recorded debug adapter sessions.
+pub fn debug_recordings_dir() -> &'static PathBuf {
+    static DEBUG_RECORDINGS_DIR: OnceLock<PathBuf> = OnceLock::new();
+    DEBUG_RECORDINGS_DIR.get_or_init(|| logs_dir().join("debug_recordings"))
+}
+
 /// Returns the path to the external agents directory
 ///
 /// This is where agent servers are downloaded to
@@ -448,6 +454,13 @@ pub fn local_settings_folder_name() -> &'static str {
     ".zed"
 }
 
+/// Returns the relative path to a `breakpoints.json` file within a project.
+pub fn local_breakpoints_file_relative_path() -> &'static RelPath {
+    static CACHED: LazyLock<&'static RelPath> =
+        LazyLock::new(|| RelPath::unix(".zed/breakpoints.json").unwrap());
+    *CACHED
+}
+
 /// Returns the relative path to a `.vscode` folder within a project.
 pub fn local_vscode_folder_name() -> &'static str {
     ".vscode"
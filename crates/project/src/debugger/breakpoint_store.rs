@@ -1,21 +1,28 @@
 //! Module for managing breakpoints in a project.
 //!
 //! Breakpoints are separate from a session because they're not associated with any particular debug session. They can also be set up without a session running.
-use anyhow::{Context as _, Result};
+//!
+//! Named sets of breakpoints can be saved to and loaded from a `.zed/breakpoints.json` file in each worktree,
+//! so that they can be shared through version control.
+use anyhow::{Context as _, Result, anyhow};
 pub use breakpoints_in_file::{BreakpointSessionState, BreakpointWithPosition};
 use breakpoints_in_file::{BreakpointsInFile, StatefulBreakpoint};
 use collections::{BTreeMap, HashMap};
 use dap::{StackFrameId, client::SessionId};
 use gpui::{App, AppContext, AsyncApp, Context, Entity, EventEmitter, Subscription, Task};
 use itertools::Itertools;
-use language::{Buffer, BufferSnapshot, proto::serialize_anchor as serialize_text_anchor};
+use language::{
+    Buffer, BufferSnapshot, LineEnding, proto::serialize_anchor as serialize_text_anchor,
+};
 use rpc::{
     AnyProtoClient, TypedEnvelope,
     proto::{self},
 };
+use serde::{Deserialize, Serialize};
 use std::{hash::Hash, ops::Range, path::Path, sync::Arc, u32};
 use text::{Point, PointUtf16};
-use util::maybe;
+use util::{maybe, rel_path::RelPath};
+use worktree::Worktree;
 
 use crate::{ProjectPath, buffer_store::BufferStore, worktree_store::WorktreeStore};
 
@@ -154,10 +161,17 @@ pub struct BreakpointStore {
     breakpoints: BTreeMap<Arc<Path>, BreakpointsInFile>,
     downstream_client: Option<(AnyProtoClient, u64)>,
     active_stack_frame: Option<ActiveStackFrame>,
+    breakpoint_sets: BTreeMap<String, BreakpointSetState>,
     // E.g ssh
     mode: BreakpointStoreMode,
 }
 
+#[derive(Default)]
+struct BreakpointSetState {
+    /// The breakpoints placed from this set, or `None` if it hasn't been loaded.
+    members: Option<Vec<(Arc<Path>, text::Anchor)>>,
+}
+
 impl BreakpointStore {
     pub fn init(client: &AnyProtoClient) {
         client.add_entity_request_handler(Self::handle_toggle_breakpoint);
@@ -171,6 +185,7 @@ impl BreakpointStore {
             worktree_store,
             downstream_client: None,
             active_stack_frame: Default::default(),
+            breakpoint_sets: Default::default(),
         }
     }
 
@@ -190,6 +205,7 @@ impl BreakpointStore {
             worktree_store,
             downstream_client: None,
             active_stack_frame: Default::default(),
+            breakpoint_sets: Default::default(),
         }
     }
 
@@ -605,6 +621,9 @@ impl BreakpointStore {
     pub fn clear_breakpoints(&mut self, cx: &mut Context<Self>) {
         let breakpoint_paths = self.breakpoints.keys().cloned().collect();
         self.breakpoints.clear();
+        for state in self.breakpoint_sets.values_mut() {
+            state.members = None;
+        }
         cx.emit(BreakpointStoreEvent::BreakpointsCleared(breakpoint_paths));
     }
 
@@ -858,12 +877,383 @@ impl BreakpointStore {
         }
     }
 
+    /// Returns the names of the known breakpoint sets, and whether each set is active,
+    /// i.e. it has been loaded and some of its breakpoints are enabled.
+    pub fn breakpoint_sets(&self) -> Vec<(String, bool)> {
+        self.breakpoint_sets
+            .iter()
+            .map(|(name, state)| {
+                let is_active = state.members.iter().flatten().any(|(path, position)| {
+                    self.breakpoints.get(path).is_some_and(|breakpoints| {
+                        breakpoints.breakpoints.iter().any(|breakpoint| {
+                            breakpoint.position() == position && breakpoint.bp.bp.is_enabled()
+                        })
+                    })
+                });
+                (name.clone(), is_active)
+            })
+            .collect()
+    }
+
+    /// Reads the names of the breakpoint sets defined in the project's worktrees.
+    pub fn refresh_breakpoint_sets(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let worktrees = self
+            .worktree_store
+            .read(cx)
+            .visible_worktrees(cx)
+            .collect::<Vec<_>>();
+        let files = worktrees
+            .iter()
+            .map(|worktree| load_breakpoint_sets_file(worktree, cx))
+            .collect::<Vec<_>>();
+        cx.spawn(async move |this, cx| {
+            let files = futures::future::join_all(files).await;
+            this.update(cx, |this, cx| {
+                for file in files {
+                    for name in file?.unwrap_or_default().sets.into_keys() {
+                        this.breakpoint_sets.entry(name).or_default();
+                    }
+                }
+                cx.notify();
+                Ok(())
+            })?
+        })
+    }
+
+    /// Saves all breakpoints in the project as the set `name`, in the `.zed/breakpoints.json`
+    /// file of each worktree containing them.
+    pub fn save_breakpoint_set(
+        &mut self,
+        name: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        if !matches!(self.mode, BreakpointStoreMode::Local) {
+            return Task::ready(Err(anyhow!(
+                "breakpoint sets can only be saved in local projects"
+            )));
+        }
+
+        let mut members = Vec::new();
+        let mut breakpoints_by_worktree = BTreeMap::<_, (Entity<Worktree>, Vec<_>)>::new();
+        for (abs_path, breakpoints_in_file) in &self.breakpoints {
+            let Some((worktree, path)) = self.worktree_store.read(cx).find_worktree(abs_path, cx)
+            else {
+                continue;
+            };
+            let snapshot = breakpoints_in_file.buffer.read(cx).snapshot();
+            let (_, shared_breakpoints) = breakpoints_by_worktree
+                .entry(worktree.read(cx).id())
+                .or_insert_with(|| (worktree.clone(), Vec::new()));
+            for breakpoint in &breakpoints_in_file.breakpoints {
+                let row = breakpoint.position().summary::<Point>(&snapshot).row;
+                shared_breakpoints.push(SharedBreakpoint::new(
+                    &path,
+                    row,
+                    &snapshot,
+                    &breakpoint.bp.bp,
+                ));
+                members.push((abs_path.clone(), *breakpoint.position()));
+            }
+        }
+        self.breakpoint_sets.insert(
+            name.clone(),
+            BreakpointSetState {
+                members: Some(members),
+            },
+        );
+        cx.notify();
+
+        let writes = breakpoints_by_worktree
+            .into_values()
+            .map(|(worktree, shared_breakpoints)| {
+                let file = load_breakpoint_sets_file(&worktree, cx);
+                let name = name.clone();
+                cx.spawn(async move |_, cx| {
+                    let mut file = file.await?.unwrap_or_default();
+                    file.sets.insert(name, shared_breakpoints);
+                    let text = serde_json::to_string_pretty(&file)?;
+                    worktree
+                        .update(cx, |worktree, cx| {
+                            worktree.write_file(
+                                paths::local_breakpoints_file_relative_path().into_arc(),
+                                text.into(),
+                                LineEnding::Unix,
+                                encoding_rs::UTF_8,
+                                false,
+                                cx,
+                            )
+                        })
+                        .await?;
+                    anyhow::Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+        cx.background_spawn(async move {
+            for write in writes {
+                write.await?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Places the breakpoints of the set `name` from every worktree defining it. Breakpoints
+    /// are re-anchored to their saved line's contents when the file has changed since.
+    pub fn load_breakpoint_set(
+        &mut self,
+        name: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        if !matches!(self.mode, BreakpointStoreMode::Local) {
+            return Task::ready(Err(anyhow!(
+                "breakpoint sets can only be loaded in local projects"
+            )));
+        }
+
+        let worktrees = self
+            .worktree_store
+            .read(cx)
+            .visible_worktrees(cx)
+            .collect::<Vec<_>>();
+        let files = worktrees
+            .iter()
+            .map(|worktree| {
+                let worktree_id = worktree.read(cx).id();
+                let file = load_breakpoint_sets_file(worktree, cx);
+                async move { (worktree_id, file.await) }
+            })
+            .collect::<Vec<_>>();
+        let buffer_store = self.buffer_store.downgrade();
+        cx.spawn(async move |this, cx| {
+            let mut members = Vec::new();
+            for (worktree_id, file) in futures::future::join_all(files).await {
+                let Some(shared_breakpoints) =
+                    file?.and_then(|mut file| file.sets.remove(&name))
+                else {
+                    continue;
+                };
+                let breakpoints_by_path = shared_breakpoints
+                    .into_iter()
+                    .into_group_map_by(|breakpoint| breakpoint.path.clone());
+                for (path, shared_breakpoints) in breakpoints_by_path {
+                    let path = RelPath::unix(&path)?.into_arc();
+                    let buffer = buffer_store
+                        .update(cx, |buffer_store, cx| {
+                            buffer_store.open_buffer(ProjectPath { worktree_id, path }, cx)
+                        })?
+                        .await?;
+                    let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+                    let text = snapshot.text();
+                    let lines = text.lines().collect::<Vec<_>>();
+
+                    this.update(cx, |this, cx| {
+                        let Some(abs_path) = Self::abs_path_from_buffer(&buffer, cx) else {
+                            return;
+                        };
+                        let breakpoints_in_file = this
+                            .breakpoints
+                            .entry(abs_path.clone())
+                            .or_insert_with(|| BreakpointsInFile::new(buffer.clone(), cx));
+                        for shared_breakpoint in shared_breakpoints {
+                            let Some(row) = reanchor_breakpoint_row(
+                                &lines,
+                                shared_breakpoint.line.saturating_sub(1),
+                                &shared_breakpoint.line_text,
+                            ) else {
+                                log::warn!(
+                                    "skipping breakpoint at {}:{} from set {name}, as the line no longer exists",
+                                    shared_breakpoint.path,
+                                    shared_breakpoint.line
+                                );
+                                continue;
+                            };
+                            let position = snapshot.anchor_after(Point::new(row, 0));
+                            breakpoints_in_file.breakpoints.retain(|breakpoint| {
+                                breakpoint.position().summary::<Point>(&snapshot).row != row
+                            });
+                            breakpoints_in_file
+                                .breakpoints
+                                .push(StatefulBreakpoint::new(BreakpointWithPosition {
+                                    position,
+                                    bp: shared_breakpoint.into_breakpoint(),
+                                }));
+                            members.push((abs_path.clone(), position));
+                        }
+                        cx.emit(BreakpointStoreEvent::BreakpointsUpdated(
+                            abs_path,
+                            BreakpointUpdatedReason::Toggled,
+                        ));
+                    })?;
+                }
+            }
+
+            this.update(cx, |this, cx| {
+                this.breakpoint_sets.insert(
+                    name,
+                    BreakpointSetState {
+                        members: Some(members),
+                    },
+                );
+                cx.notify();
+            })
+        })
+    }
+
+    /// Loads the breakpoint set `name` if none of its breakpoints are placed, and otherwise
+    /// enables or disables all of them.
+    pub fn toggle_breakpoint_set(
+        &mut self,
+        name: &str,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let members_by_path = self
+            .breakpoint_sets
+            .get(name)
+            .and_then(|state| state.members.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .into_group_map();
+        let has_placed_members = members_by_path.iter().any(|(path, positions)| {
+            self.breakpoints.get(path).is_some_and(|breakpoints| {
+                breakpoints
+                    .breakpoints
+                    .iter()
+                    .any(|breakpoint| positions.contains(breakpoint.position()))
+            })
+        });
+        if !has_placed_members {
+            return self.load_breakpoint_set(name.to_string(), cx);
+        }
+
+        let is_active = self
+            .breakpoint_sets()
+            .into_iter()
+            .any(|(set_name, is_active)| set_name == name && is_active);
+        let state = if is_active {
+            BreakpointState::Disabled
+        } else {
+            BreakpointState::Enabled
+        };
+
+        for (path, positions) in members_by_path {
+            let Some(breakpoints_in_file) = self.breakpoints.get_mut(&path) else {
+                continue;
+            };
+            for breakpoint in &mut breakpoints_in_file.breakpoints {
+                if positions.contains(breakpoint.position()) {
+                    breakpoint.bp.bp.state = state;
+                }
+            }
+            cx.emit(BreakpointStoreEvent::BreakpointsUpdated(
+                path,
+                BreakpointUpdatedReason::Toggled,
+            ));
+        }
+        cx.notify();
+        Task::ready(Ok(()))
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub(crate) fn breakpoint_paths(&self) -> Vec<Arc<Path>> {
         self.breakpoints.keys().cloned().collect()
     }
 }
 
+/// The contents of a `.zed/breakpoints.json` file.
+#[derive(Default, Serialize, Deserialize)]
+struct BreakpointSetsFile {
+    #[serde(default)]
+    sets: BTreeMap<String, Vec<SharedBreakpoint>>,
+}
+
+/// A breakpoint in a `.zed/breakpoints.json` file.
+#[derive(Serialize, Deserialize)]
+struct SharedBreakpoint {
+    /// The path of the file, relative to the worktree root.
+    path: String,
+    /// The 1-based line number of the breakpoint.
+    line: u32,
+    /// The contents of the line when the breakpoint was saved, used to find it again
+    /// after the file has been edited.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    line_text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    condition: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hit_condition: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    log_message: Option<String>,
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl SharedBreakpoint {
+    fn new(path: &RelPath, row: u32, snapshot: &BufferSnapshot, breakpoint: &Breakpoint) -> Self {
+        let line_text = snapshot
+            .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+            .collect::<String>();
+        Self {
+            path: path.as_unix_str().to_string(),
+            line: row + 1,
+            line_text: line_text.trim().to_string(),
+            condition: breakpoint.condition.as_deref().map(ToString::to_string),
+            hit_condition: breakpoint.hit_condition.as_deref().map(ToString::to_string),
+            log_message: breakpoint.message.as_deref().map(ToString::to_string),
+            enabled: breakpoint.is_enabled(),
+        }
+    }
+
+    fn into_breakpoint(self) -> Breakpoint {
+        Breakpoint {
+            message: self.log_message.map(Into::into),
+            hit_condition: self.hit_condition.map(Into::into),
+            condition: self.condition.map(Into::into),
+            state: if self.enabled {
+                BreakpointState::Enabled
+            } else {
+                BreakpointState::Disabled
+            },
+        }
+    }
+}
+
+fn load_breakpoint_sets_file(
+    worktree: &Entity<Worktree>,
+    cx: &mut App,
+) -> Task<Result<Option<BreakpointSetsFile>>> {
+    let path = paths::local_breakpoints_file_relative_path();
+    if worktree.read(cx).entry_for_path(path).is_none() {
+        return Task::ready(Ok(None));
+    }
+    let load = worktree.update(cx, |worktree, cx| worktree.load_file(path, cx));
+    cx.background_spawn(async move {
+        let file = load.await?;
+        let contents = serde_json::from_str(&file.text)
+            .with_context(|| format!("parsing {}", path.as_unix_str()))?;
+        Ok(Some(contents))
+    })
+}
+
+/// Finds the row for a breakpoint saved at `row` on a line containing `line_text`: the
+/// nearest row with the same contents, or `row` itself if no line matches.
+fn reanchor_breakpoint_row(lines: &[&str], row: u32, line_text: &str) -> Option<u32> {
+    let line_text = line_text.trim();
+    let saved_row = (row as usize) < lines.len().max(1);
+    if line_text.is_empty() {
+        return saved_row.then_some(row);
+    }
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim() == line_text)
+        .min_by_key(|(ix, _)| ix.abs_diff(row as usize))
+        .map(|(ix, _)| ix as u32)
+        .or(saved_row.then_some(row))
+}
+
 #[derive(Clone, Copy)]
 pub enum BreakpointUpdatedReason {
     Toggled,
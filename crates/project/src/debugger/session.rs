@@ -1798,6 +1798,14 @@ impl Session {
         self.active_snapshot.thread_states.thread_status(thread_id)
     }
 
+    /// The threads fetched by the last call to [`Session::threads`], without fetching them again.
+    pub fn fetched_threads(&self) -> impl Iterator<Item = &dap::Thread> {
+        self.session_state()
+            .threads
+            .values()
+            .map(|thread| &thread.dap)
+    }
+
     pub fn threads(&mut self, cx: &mut Context<Self>) -> Vec<(dap::Thread, ThreadStatus)> {
         self.fetch(
             dap_command::ThreadsCommand,
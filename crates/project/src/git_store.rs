@@ -188,6 +188,17 @@ pub struct GitStoreCheckpoint {
     checkpoints_by_work_dir_abs_path: HashMap<Arc<Path>, GitRepositoryCheckpoint>,
 }
 
+impl GitStoreCheckpoint {
+    /// Returns the checkpoint of the repository with the given working directory.
+    pub fn repository_checkpoint(
+        &self,
+        work_directory_abs_path: &Path,
+    ) -> Option<&GitRepositoryCheckpoint> {
+        self.checkpoints_by_work_dir_abs_path
+            .get(work_directory_abs_path)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct StatusEntry {
     pub repo_path: RepoPath,
@@ -226,6 +226,56 @@ pub enum LspFormatTarget {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct OpenLspBufferHandle(Entity<OpenLspBuffer>);
 
+/// A rename proposed by a language server, which hasn't been applied yet.
+#[derive(Debug)]
+pub struct RenamePreview {
+    pub edit: lsp::WorkspaceEdit,
+    server_id: LanguageServerId,
+}
+
+impl RenamePreview {
+    /// Returns the absolute paths of the files the rename edits, creates, renames or
+    /// deletes.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let mut uris = Vec::new();
+        if let Some(changes) = &self.edit.changes {
+            uris.extend(changes.keys());
+        }
+        match &self.edit.document_changes {
+            Some(lsp::DocumentChanges::Edits(edits)) => {
+                uris.extend(edits.iter().map(|edit| &edit.text_document.uri));
+            }
+            Some(lsp::DocumentChanges::Operations(operations)) => {
+                for operation in operations {
+                    match operation {
+                        lsp::DocumentChangeOperation::Edit(edit) => {
+                            uris.push(&edit.text_document.uri)
+                        }
+                        lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Create(op)) => {
+                            uris.push(&op.uri)
+                        }
+                        lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Rename(op)) => {
+                            uris.extend([&op.old_uri, &op.new_uri])
+                        }
+                        lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Delete(op)) => {
+                            uris.push(&op.uri)
+                        }
+                    }
+                }
+            }
+            None => {}
+        }
+
+        let mut paths = uris
+            .into_iter()
+            .filter_map(|uri| uri.to_file_path().ok())
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+}
+
 struct OpenLspBuffer(Entity<Buffer>);
 
 impl FormatTrigger {
@@ -5297,6 +5347,85 @@ impl LspStore {
         }
     }
 
+    /// Asks the first capable language server how it would rename the symbol at
+    /// `position`, without applying the edit, so that callers can check the files it
+    /// touches first. Returns `None` in remote projects, where the rename has to be
+    /// performed with [`Project::perform_rename`] instead.
+    pub fn preview_rename(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: PointUtf16,
+        new_name: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Option<RenamePreview>>> {
+        if !self.mode.is_local() {
+            return Task::ready(Ok(None));
+        }
+
+        let server = buffer.update(cx, |buffer, cx| {
+            let abs_path = File::from_dyn(buffer.file())?.abs_path(cx);
+            let request_timeout = ProjectSettings::get_global(cx)
+                .global_lsp_settings
+                .get_request_timeout();
+            let server = self
+                .running_language_servers_for_local_buffer(buffer, cx)
+                .find(|(_, server)| {
+                    server
+                        .capabilities()
+                        .rename_provider
+                        .is_some_and(|capability| capability != OneOf::Left(false))
+                })
+                .map(|(_, server)| server.clone())?;
+            Some((abs_path, server, request_timeout))
+        });
+        let Some((abs_path, server, request_timeout)) = server else {
+            return Task::ready(Err(anyhow!("No language server can rename this symbol")));
+        };
+        let text_document_position = match make_lsp_text_document_position(&abs_path, position) {
+            Ok(text_document_position) => text_document_position,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        cx.background_spawn(async move {
+            let edit = server
+                .request::<lsp::request::Rename>(
+                    lsp::RenameParams {
+                        text_document_position,
+                        new_name,
+                        work_done_progress_params: Default::default(),
+                    },
+                    request_timeout,
+                )
+                .await
+                .into_response()?;
+            Ok(Some(RenamePreview {
+                edit: edit.unwrap_or_default(),
+                server_id: server.server_id(),
+            }))
+        })
+    }
+
+    /// Applies a rename returned by [`Self::preview_rename`].
+    pub fn apply_rename_preview(
+        &self,
+        preview: RenamePreview,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        let Some(server) = self.language_server_for_id(preview.server_id) else {
+            return Task::ready(Err(anyhow!("The language server has stopped")));
+        };
+        cx.spawn(async move |this, cx| {
+            LocalLspStore::deserialize_workspace_edit(
+                this.upgrade().context("no app present")?,
+                preview.edit,
+                true,
+                server,
+                cx,
+            )
+            .await
+        })
+    }
+
     pub fn apply_code_action(
         &self,
         buffer_handle: Entity<Buffer>,
@@ -21,6 +21,7 @@ pub mod telemetry_snapshot;
 pub mod terminals;
 pub mod toolchain_store;
 pub mod trusted_worktrees;
+pub mod upload_store;
 pub mod worktree_store;
 
 mod environment;
@@ -80,6 +81,7 @@ use futures::{
 };
 pub use image_store::{ImageItem, ImageStore};
 use image_store::{ImageItemEvent, ImageStoreEvent};
+use upload_store::UploadStore;
 
 use ::git::{blame::Blame, status::FileStatus};
 use gpui::{
@@ -158,7 +160,7 @@ pub use buffer_store::ProjectTransaction;
 pub use lsp_store::{
     DiagnosticSummary, InvalidationStrategy, LanguageServerLogType, LanguageServerProgress,
     LanguageServerPromptRequest, LanguageServerStatus, LanguageServerToQuery, LspStore,
-    LspStoreEvent, ProgressToken, SERVER_PROGRESS_THROTTLE_TIMEOUT,
+    LspStoreEvent, ProgressToken, RenamePreview, SERVER_PROGRESS_THROTTLE_TIMEOUT,
 };
 pub use toolchain_store::{ToolchainStore, Toolchains};
 const MAX_PROJECT_SEARCH_HISTORY_SIZE: usize = 500;
@@ -226,6 +228,7 @@ pub struct Project {
     buffer_store: Entity<BufferStore>,
     context_server_store: Entity<ContextServerStore>,
     image_store: Entity<ImageStore>,
+    upload_store: Entity<UploadStore>,
     lsp_store: Entity<LspStore>,
     _subscriptions: Vec<gpui::Subscription>,
     buffers_needing_diff: HashSet<WeakEntity<Buffer>>,
@@ -242,6 +245,7 @@ pub struct Project {
     toolchain_store: Option<Entity<ToolchainStore>>,
     agent_location: Option<AgentLocation>,
     downloading_files: Arc<Mutex<HashMap<(WorktreeId, String), DownloadingFile>>>,
+    listening_ports: Vec<ListeningPort>,
 }
 
 struct DownloadingFile {
@@ -251,6 +255,14 @@ struct DownloadingFile {
     file_id: Option<u64>, // Set when we receive the State message
 }
 
+/// A port listened on by a process started from a terminal or task on the remote host.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ListeningPort {
+    pub port: u16,
+    pub pid: Option<u32>,
+    pub process_name: Option<String>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct AgentLocation {
     pub buffer: WeakEntity<Buffer>,
@@ -348,6 +360,7 @@ pub enum Event {
     HideToast {
         notification_id: SharedString,
     },
+    ListeningPortsChanged,
     LanguageServerPrompt(LanguageServerPromptRequest),
     LanguageNotFound(Entity<Buffer>),
     ActiveEntryChanged(Option<ProjectEntryId>),
@@ -1123,6 +1136,7 @@ impl Project {
         client.add_entity_request_handler(Self::handle_find_search_candidates_chunk);
         client.add_entity_message_handler(Self::handle_find_search_candidates_cancel);
         client.add_entity_message_handler(Self::handle_create_file_for_peer);
+        client.add_entity_request_handler(Self::handle_write_to_shared_terminal);
 
         WorktreeStore::init(&client);
         BufferStore::init(&client);
@@ -1215,6 +1229,8 @@ impl Project {
             let image_store = cx.new(|cx| ImageStore::local(worktree_store.clone(), cx));
             cx.subscribe(&image_store, Self::on_image_store_event)
                 .detach();
+            let upload_store =
+                cx.new(|_| UploadStore::new(fs.clone(), worktree_store.clone(), None));
 
             let prettier_store = cx.new(|cx| {
                 PrettierStore::new(
@@ -1295,6 +1311,7 @@ impl Project {
                 worktree_store,
                 buffer_store,
                 image_store,
+                upload_store,
                 lsp_store,
                 context_server_store,
                 join_project_response_message_id: 0,
@@ -1319,6 +1336,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    shared: HashMap::default(),
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -1332,6 +1350,7 @@ impl Project {
 
                 agent_location: None,
                 downloading_files: Default::default(),
+                listening_ports: Vec::new(),
             }
         })
     }
@@ -1400,6 +1419,13 @@ impl Project {
                     cx,
                 )
             });
+            let upload_store = cx.new(|cx| {
+                UploadStore::new(
+                    fs.clone(),
+                    worktree_store.clone(),
+                    Some((remote.read(cx).proto_client(), REMOTE_SERVER_PROJECT_ID)),
+                )
+            });
             cx.subscribe(&buffer_store, Self::on_buffer_store_event)
                 .detach();
             let toolchain_store = cx.new(|cx| {
@@ -1510,6 +1536,7 @@ impl Project {
                 worktree_store,
                 buffer_store,
                 image_store,
+                upload_store,
                 lsp_store,
                 context_server_store,
                 breakpoint_store,
@@ -1551,6 +1578,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    shared: HashMap::default(),
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -1563,6 +1591,7 @@ impl Project {
                 toolchain_store: Some(toolchain_store),
                 agent_location: None,
                 downloading_files: Default::default(),
+                listening_ports: Vec::new(),
             };
 
             // remote server -> local machine handlers
@@ -1584,6 +1613,7 @@ impl Project {
             remote_proto.add_entity_message_handler(Self::handle_toast);
             remote_proto.add_entity_request_handler(Self::handle_language_server_prompt_request);
             remote_proto.add_entity_message_handler(Self::handle_hide_toast);
+            remote_proto.add_entity_message_handler(Self::handle_listening_ports_updated);
             remote_proto.add_entity_request_handler(Self::handle_update_buffer_from_remote_server);
             remote_proto.add_entity_request_handler(Self::handle_trust_worktrees);
             remote_proto.add_entity_request_handler(Self::handle_restrict_worktrees);
@@ -1686,6 +1716,7 @@ impl Project {
         let image_store = cx.new(|cx| {
             ImageStore::remote(worktree_store.clone(), client.clone().into(), remote_id, cx)
         });
+        let upload_store = cx.new(|_| UploadStore::new(fs.clone(), worktree_store.clone(), None));
 
         let environment =
             cx.new(|cx| ProjectEnvironment::new(None, worktree_store.downgrade(), None, true, cx));
@@ -1799,6 +1830,7 @@ impl Project {
                 buffer_ordered_messages_tx: tx,
                 buffer_store: buffer_store.clone(),
                 image_store,
+                upload_store,
                 worktree_store: worktree_store.clone(),
                 lsp_store: lsp_store.clone(),
                 context_server_store,
@@ -1829,6 +1861,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    shared: HashMap::default(),
                 },
                 node: None,
                 search_history: Self::new_search_history(),
@@ -1839,6 +1872,7 @@ impl Project {
                 toolchain_store: None,
                 agent_location: None,
                 downloading_files: Default::default(),
+                listening_ports: Vec::new(),
             };
             project.set_role(role, cx);
             for worktree in worktrees {
@@ -2073,6 +2107,10 @@ impl Project {
         self.worktree_store.clone()
     }
 
+    pub fn upload_store(&self) -> &Entity<UploadStore> {
+        &self.upload_store
+    }
+
     #[inline]
     pub fn context_server_store(&self) -> Entity<ContextServerStore> {
         self.context_server_store.clone()
@@ -2294,6 +2332,12 @@ impl Project {
             .map(|tree| tree.read(cx).root_name().as_unix_str())
     }
 
+    /// The names of the project's visible worktrees joined by commas, or `None` if it has none.
+    pub fn display_name(&self, cx: &App) -> Option<String> {
+        let names = self.worktree_root_names(cx).collect::<Vec<_>>();
+        (!names.is_empty()).then(|| names.join(", "))
+    }
+
     #[inline]
     pub fn worktree_for_id(&self, id: WorktreeId, cx: &App) -> Option<Entity<Worktree>> {
         self.worktree_store.read(cx).worktree_for_id(id, cx)
@@ -2686,6 +2730,7 @@ impl Project {
             self.breakpoint_store.update(cx, |breakpoint_store, cx| {
                 breakpoint_store.unshared(cx);
             });
+            self.terminals.shared.clear();
             self.dap_store.update(cx, |dap_store, cx| {
                 dap_store.unshared(cx);
             });
@@ -4303,6 +4348,31 @@ impl Project {
         )
     }
 
+    /// Asks a language server how it would rename a symbol, without applying the edit.
+    /// Returns `None` in remote projects.
+    pub fn preview_rename<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        position: T,
+        new_name: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Option<RenamePreview>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.preview_rename(buffer, position, new_name, cx)
+        })
+    }
+
+    pub fn apply_rename_preview(
+        &mut self,
+        preview: RenamePreview,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.apply_rename_preview(preview, cx)
+        })
+    }
+
     pub fn on_type_format<T: ToPointUtf16>(
         &mut self,
         buffer: Entity<Buffer>,
@@ -5033,6 +5103,29 @@ impl Project {
         })
     }
 
+    async fn handle_listening_ports_updated(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::ListeningPortsUpdated>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| {
+            this.listening_ports = envelope
+                .payload
+                .ports
+                .into_iter()
+                .filter_map(|port| {
+                    Some(ListeningPort {
+                        port: u16::try_from(port.port).ok()?,
+                        pid: port.pid,
+                        process_name: port.process_name,
+                    })
+                })
+                .collect();
+            cx.emit(Event::ListeningPortsChanged);
+        });
+        Ok(())
+    }
+
     async fn handle_language_server_prompt_request(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::LanguageServerPromptRequest>,
@@ -5913,6 +6006,26 @@ impl Project {
         self.agent_location.clone()
     }
 
+    /// The ports listened on by processes started from terminals and tasks on the remote
+    /// host, for projects opened via a remote server.
+    pub fn listening_ports(&self) -> &[ListeningPort] {
+        &self.listening_ports
+    }
+
+    /// Asks the remote server to start or stop reporting [`Self::listening_ports`].
+    pub fn watch_listening_ports(&self, watch: bool, cx: &App) {
+        if let Some(remote) = &self.remote_client {
+            remote
+                .read(cx)
+                .proto_client()
+                .send(proto::WatchListeningPorts {
+                    project_id: REMOTE_SERVER_PROJECT_ID,
+                    watch,
+                })
+                .log_err();
+        }
+    }
+
     pub fn path_style(&self, cx: &App) -> PathStyle {
         self.worktree_store.read(cx).path_style()
     }
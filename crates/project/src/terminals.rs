@@ -1,11 +1,12 @@
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use collections::HashMap;
-use gpui::{App, AppContext as _, Context, Entity, Task, WeakEntity};
+use gpui::{App, AppContext as _, AsyncApp, Context, Entity, Task, WeakEntity};
 
 use futures::{FutureExt, future::Shared};
 use itertools::Itertools as _;
 use language::LanguageName;
 use remote::RemoteClient;
+use rpc::{TypedEnvelope, proto};
 use settings::{Settings, SettingsLocation};
 use smol::channel::bounded;
 use std::{
@@ -23,6 +24,13 @@ use crate::{Project, ProjectPath};
 
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakEntity<terminal::Terminal>>,
+    /// Terminals shared with collaborators, by the id they were shared with.
+    pub(crate) shared: HashMap<u64, SharedTerminal>,
+}
+
+pub(crate) struct SharedTerminal {
+    terminal: WeakEntity<Terminal>,
+    input_allowed: bool,
 }
 
 impl Project {
@@ -579,6 +587,77 @@ impl Project {
         &self.terminals.local_handles
     }
 
+    /// Shares a terminal with the project's collaborators, returning the id they can
+    /// refer to it by. Unless `input_allowed` is set, guests can only watch it.
+    pub fn share_terminal(
+        &mut self,
+        terminal: &Entity<Terminal>,
+        input_allowed: bool,
+    ) -> Result<u64> {
+        anyhow::ensure!(
+            matches!(self.client_state, crate::ProjectClientState::Shared { .. }),
+            "only the host of a shared project can share terminals"
+        );
+        let terminal_id = terminal.entity_id().as_u64();
+        self.terminals.shared.insert(
+            terminal_id,
+            SharedTerminal {
+                terminal: terminal.downgrade(),
+                input_allowed,
+            },
+        );
+        self.terminals
+            .shared
+            .retain(|_, shared| shared.terminal.upgrade().is_some());
+        Ok(terminal_id)
+    }
+
+    pub fn unshare_terminal(&mut self, terminal_id: u64) {
+        self.terminals.shared.remove(&terminal_id);
+    }
+
+    /// Sends input to a terminal shared by the host of this project.
+    pub fn write_to_shared_terminal(
+        &self,
+        terminal_id: u64,
+        input: Vec<u8>,
+        cx: &App,
+    ) -> Task<Result<()>> {
+        let Some(project_id) = self.remote_id() else {
+            return Task::ready(Err(anyhow::anyhow!("project is not shared")));
+        };
+        let request = self.collab_client.request(proto::WriteToSharedTerminal {
+            project_id,
+            terminal_id,
+            input,
+        });
+        cx.background_spawn(async move {
+            request.await?;
+            Ok(())
+        })
+    }
+
+    pub(crate) async fn handle_write_to_shared_terminal(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::WriteToSharedTerminal>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |this, cx| {
+            let shared = this
+                .terminals
+                .shared
+                .get(&envelope.payload.terminal_id)
+                .context("terminal is not shared")?;
+            anyhow::ensure!(
+                shared.input_allowed,
+                "terminal is shared without input permission"
+            );
+            let terminal = shared.terminal.upgrade().context("terminal was closed")?;
+            terminal.update(cx, |terminal, _| terminal.input(envelope.payload.input));
+            Ok(proto::Ack {})
+        })
+    }
+
     fn resolve_directory_environment(
         &self,
         shell: &str,
@@ -0,0 +1,388 @@
+//! Uploads of local files and directories into the worktrees of remote projects, and
+//! local folders mirrored to a remote directory as they change.
+
+use crate::{ProjectPath, worktree_store::WorktreeStore};
+use anyhow::{Context as _, Result, anyhow};
+use fs::{Fs, read_dir_items};
+use futures::StreamExt as _;
+use gpui::{Context, Entity, SharedString, Task};
+use rpc::{AnyProtoClient, proto};
+use std::{
+    io::Read as _,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+use util::{ResultExt as _, paths::PathStyle, rel_path::RelPath};
+use worktree::WorktreeId;
+
+const UPLOAD_CHUNK_SIZE: usize = 1024 * 1024;
+const SYNCED_FOLDER_LATENCY: Duration = Duration::from_millis(500);
+
+static NEXT_UPLOAD_ID: AtomicU64 = AtomicU64::new(1);
+
+pub struct UploadStore {
+    fs: Arc<dyn Fs>,
+    worktree_store: Entity<WorktreeStore>,
+    /// The remote server client and project id uploads are sent to, if the project
+    /// is remote.
+    upstream: Option<(AnyProtoClient, u64)>,
+    uploads: Vec<Upload>,
+    synced_folders: Vec<SyncedFolder>,
+}
+
+/// An upload in progress.
+#[derive(Clone, Debug)]
+pub struct Upload {
+    pub id: u64,
+    pub label: SharedString,
+    pub total_bytes: u64,
+    pub uploaded_bytes: u64,
+}
+
+/// A local folder whose contents are mirrored to a directory of a remote worktree.
+pub struct SyncedFolder {
+    pub local_path: Arc<Path>,
+    pub destination: ProjectPath,
+    _sync: Task<()>,
+}
+
+#[derive(Debug, PartialEq)]
+struct FileToUpload {
+    abs_path: PathBuf,
+    destination: Arc<RelPath>,
+    is_dir: bool,
+    len: u64,
+}
+
+impl UploadStore {
+    pub fn new(
+        fs: Arc<dyn Fs>,
+        worktree_store: Entity<WorktreeStore>,
+        upstream: Option<(AnyProtoClient, u64)>,
+    ) -> Self {
+        Self {
+            fs,
+            worktree_store,
+            upstream,
+            uploads: Vec::new(),
+            synced_folders: Vec::new(),
+        }
+    }
+
+    pub fn uploads(&self) -> &[Upload] {
+        &self.uploads
+    }
+
+    pub fn synced_folders(&self) -> &[SyncedFolder] {
+        &self.synced_folders
+    }
+
+    pub fn synced_folder(&self, destination: &ProjectPath) -> Option<&SyncedFolder> {
+        self.synced_folders
+            .iter()
+            .find(|folder| &folder.destination == destination)
+    }
+
+    /// Uploads local files and directories into `destination_dir`, replacing any
+    /// existing files with the same paths.
+    pub fn upload(
+        &mut self,
+        sources: Vec<PathBuf>,
+        destination_dir: ProjectPath,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let fs = self.fs.clone();
+        cx.spawn(async move |this, cx| {
+            let mut files = Vec::new();
+            for source in &sources {
+                let file_name = source
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(|name| RelPath::unix(name).ok())
+                    .with_context(|| format!("invalid upload source {source:?}"))?;
+                let destination = destination_dir.path.join(file_name);
+                files.extend(files_to_upload(fs.as_ref(), source, &destination).await?);
+            }
+            let label = match sources.as_slice() {
+                [source] => format!(
+                    "Uploading {}",
+                    source.file_name().unwrap_or_default().to_string_lossy()
+                ),
+                _ => format!("Uploading {} items", sources.len()),
+            };
+            this.update(cx, |this, cx| {
+                this.upload_files(destination_dir.worktree_id, files, label, cx)
+            })?
+            .await
+        })
+    }
+
+    /// Uploads the contents of `local_path` into `destination_dir`, then keeps
+    /// uploading files as they change locally and deleting them remotely as they're
+    /// removed, until [`Self::stop_syncing_folder`] is called.
+    pub fn sync_folder(
+        &mut self,
+        local_path: PathBuf,
+        destination_dir: ProjectPath,
+        cx: &mut Context<Self>,
+    ) {
+        self.stop_syncing_folder(&destination_dir, cx);
+
+        let fs = self.fs.clone();
+        let sync = cx.spawn({
+            let local_path = local_path.clone();
+            let destination_dir = destination_dir.clone();
+            async move |this, cx| {
+                let (mut events, _watcher) = fs.watch(&local_path, SYNCED_FOLDER_LATENCY).await;
+
+                let label = format!(
+                    "Syncing {}",
+                    local_path.file_name().unwrap_or_default().to_string_lossy()
+                );
+                let initial_upload = async {
+                    let files = files_to_upload(fs.as_ref(), &local_path, &destination_dir.path)
+                        .await?
+                        .into_iter()
+                        .filter(|file| file.destination != destination_dir.path)
+                        .collect();
+                    this.update(cx, |this, cx| {
+                        this.upload_files(destination_dir.worktree_id, files, label.clone(), cx)
+                    })?
+                    .await
+                };
+                initial_upload.await.log_err();
+
+                while let Some(events) = events.next().await {
+                    let mut changed_files = Vec::new();
+                    let mut removed_paths = Vec::new();
+                    for event in events {
+                        let Some(relative_path) = event
+                            .path
+                            .strip_prefix(&local_path)
+                            .ok()
+                            .and_then(|path| RelPath::new(path, PathStyle::local()).ok())
+                        else {
+                            continue;
+                        };
+                        if relative_path.is_empty() {
+                            continue;
+                        }
+                        let destination = destination_dir.path.join(&relative_path);
+                        match fs.metadata(&event.path).await {
+                            Ok(Some(metadata)) if !metadata.is_dir => {
+                                changed_files.push(FileToUpload {
+                                    abs_path: event.path,
+                                    destination,
+                                    is_dir: false,
+                                    len: metadata.len,
+                                })
+                            }
+                            Ok(None) => removed_paths.push(destination),
+                            _ => {}
+                        }
+                    }
+                    changed_files.sort_by(|a, b| a.destination.cmp(&b.destination));
+                    changed_files.dedup_by(|a, b| a.destination == b.destination);
+                    removed_paths.sort();
+                    removed_paths.dedup();
+
+                    if !changed_files.is_empty() {
+                        let Ok(upload) = this.update(cx, |this, cx| {
+                            this.upload_files(
+                                destination_dir.worktree_id,
+                                changed_files,
+                                label.clone(),
+                                cx,
+                            )
+                        }) else {
+                            return;
+                        };
+                        upload.await.log_err();
+                    }
+
+                    let Ok(deletions) = this.update(cx, |this, cx| {
+                        let Some(worktree) = this
+                            .worktree_store
+                            .read(cx)
+                            .worktree_for_id(destination_dir.worktree_id, cx)
+                        else {
+                            return Vec::new();
+                        };
+                        removed_paths
+                            .iter()
+                            .filter_map(|path| {
+                                let entry_id = worktree.read(cx).entry_for_path(path)?.id;
+                                worktree.update(cx, |worktree, cx| {
+                                    worktree.delete_entry(entry_id, false, cx)
+                                })
+                            })
+                            .collect()
+                    }) else {
+                        return;
+                    };
+                    for deletion in deletions {
+                        deletion.await.log_err();
+                    }
+                }
+            }
+        });
+
+        self.synced_folders.push(SyncedFolder {
+            local_path: local_path.into(),
+            destination: destination_dir,
+            _sync: sync,
+        });
+        cx.notify();
+    }
+
+    pub fn stop_syncing_folder(&mut self, destination: &ProjectPath, cx: &mut Context<Self>) {
+        let len = self.synced_folders.len();
+        self.synced_folders
+            .retain(|folder| &folder.destination != destination);
+        if self.synced_folders.len() != len {
+            cx.notify();
+        }
+    }
+
+    fn upload_files(
+        &mut self,
+        worktree_id: WorktreeId,
+        files: Vec<FileToUpload>,
+        label: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some((client, project_id)) = self.upstream.clone() else {
+            return Task::ready(Err(anyhow!(
+                "uploading files is only supported in remote projects"
+            )));
+        };
+        let Some(worktree) = self
+            .worktree_store
+            .read(cx)
+            .worktree_for_id(worktree_id, cx)
+        else {
+            return Task::ready(Err(anyhow!("worktree not found")));
+        };
+
+        let id = NEXT_UPLOAD_ID.fetch_add(1, Ordering::SeqCst);
+        self.uploads.push(Upload {
+            id,
+            label: label.into(),
+            total_bytes: files.iter().map(|file| file.len).sum(),
+            uploaded_bytes: 0,
+        });
+        cx.notify();
+
+        let fs = self.fs.clone();
+        cx.spawn(async move |this, cx| {
+            let result = async {
+                for file in files {
+                    if file.is_dir {
+                        worktree
+                            .update(cx, |worktree, cx| {
+                                worktree.create_entry(file.destination.clone(), true, None, cx)
+                            })
+                            .await?;
+                        continue;
+                    }
+
+                    let file_upload_id = NEXT_UPLOAD_ID.fetch_add(1, Ordering::SeqCst);
+                    let mut reader = fs.open_sync(&file.abs_path).await?;
+                    let mut offset = 0;
+                    loop {
+                        let (chunk, returned_reader) = cx
+                            .background_spawn(async move {
+                                let mut chunk = Vec::with_capacity(UPLOAD_CHUNK_SIZE);
+                                (&mut reader)
+                                    .take(UPLOAD_CHUNK_SIZE as u64)
+                                    .read_to_end(&mut chunk)?;
+                                anyhow::Ok((chunk, reader))
+                            })
+                            .await
+                            .with_context(|| format!("reading {:?}", file.abs_path))?;
+                        reader = returned_reader;
+
+                        let len = chunk.len();
+                        let is_last = len < UPLOAD_CHUNK_SIZE;
+                        client
+                            .request(proto::UploadFileChunk {
+                                project_id,
+                                worktree_id: worktree_id.to_proto(),
+                                path: file.destination.to_proto(),
+                                upload_id: file_upload_id,
+                                data: chunk,
+                                is_last,
+                                offset,
+                            })
+                            .await?;
+                        offset += len as u64;
+                        this.update(cx, |this, cx| {
+                            if let Some(upload) =
+                                this.uploads.iter_mut().find(|upload| upload.id == id)
+                            {
+                                upload.uploaded_bytes += len as u64;
+                                cx.notify();
+                            }
+                        })?;
+                        if is_last {
+                            break;
+                        }
+                    }
+                }
+                anyhow::Ok(())
+            }
+            .await;
+
+            this.update(cx, |this, cx| {
+                this.uploads.retain(|upload| upload.id != id);
+                cx.notify();
+            })
+            .ok();
+            result
+        })
+    }
+}
+
+/// Lists the files under `source` (or `source` itself, if it's a file) along with the
+/// path each is uploaded to, when `source` is uploaded to `destination`. Directories
+/// are only listed when they're empty, as the others are created along with their
+/// files.
+async fn files_to_upload(
+    fs: &dyn Fs,
+    source: &Path,
+    destination: &Arc<RelPath>,
+) -> Result<Vec<FileToUpload>> {
+    let mut files = Vec::new();
+    for (abs_path, is_dir) in read_dir_items(fs, source).await? {
+        let relative_path = abs_path
+            .strip_prefix(source)
+            .map_err(anyhow::Error::from)
+            .and_then(|path| RelPath::new(path, PathStyle::local()))?;
+        let len = if is_dir {
+            0
+        } else {
+            fs.metadata(&abs_path)
+                .await?
+                .map_or(0, |metadata| metadata.len)
+        };
+        files.push(FileToUpload {
+            destination: destination.join(&relative_path),
+            abs_path,
+            is_dir,
+            len,
+        });
+    }
+
+    let non_empty_dirs = files
+        .iter()
+        .filter_map(|file| file.destination.parent())
+        .flat_map(|parent| parent.ancestors())
+        .map(|dir| dir.into_arc())
+        .collect::<collections::HashSet<_>>();
+    files.retain(|file| !file.is_dir || !non_empty_dirs.contains(&file.destination));
+    Ok(files)
+}
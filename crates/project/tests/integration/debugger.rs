@@ -291,3 +291,199 @@ mod memory {
         assert_eq!(actual, expected);
     }
 }
+
+mod breakpoint_sets {
+    use fs::{FakeFs, Fs as _};
+    use gpui::TestAppContext;
+    use language::Point;
+    use project::{
+        Project,
+        debugger::breakpoint_store::{
+            Breakpoint, BreakpointEditAction, BreakpointState, BreakpointWithPosition,
+        },
+    };
+    use serde_json::json;
+    use std::path::Path;
+    use util::path;
+
+    use crate::init_test;
+
+    #[gpui::test]
+    async fn test_breakpoint_sets_round_trip(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({
+                "main.rs": "fn main() {\n    let x = 1;\n    println!(\"{x}\");\n}\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let breakpoint_store = project.read_with(cx, |project, _| project.breakpoint_store());
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/dir/main.rs"), cx)
+            })
+            .await
+            .unwrap();
+
+        breakpoint_store.update(cx, |store, cx| {
+            let position = buffer.read(cx).anchor_after(Point::new(2, 0));
+            store.toggle_breakpoint(
+                buffer.clone(),
+                BreakpointWithPosition {
+                    position,
+                    bp: Breakpoint {
+                        condition: Some("x > 0".into()),
+                        ..Breakpoint::new_log("x is {x}")
+                    },
+                },
+                BreakpointEditAction::Toggle,
+                cx,
+            );
+        });
+        breakpoint_store
+            .update(cx, |store, cx| {
+                store.save_breakpoint_set("logging".to_string(), cx)
+            })
+            .await
+            .unwrap();
+
+        let saved: serde_json::Value = serde_json::from_str(
+            &fs.load(Path::new(path!("/dir/.zed/breakpoints.json")))
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            saved,
+            json!({
+                "sets": {
+                    "logging": [{
+                        "path": "main.rs",
+                        "line": 3,
+                        "line_text": "println!(\"{x}\");",
+                        "condition": "x > 0",
+                        "log_message": "x is {x}",
+                        "enabled": true,
+                    }]
+                }
+            })
+        );
+
+        // Lines drift after the set was saved; the breakpoint follows the line's contents.
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit(
+                [(Point::new(1, 0)..Point::new(1, 0), "    // a comment\n")],
+                None,
+                cx,
+            )
+        });
+        breakpoint_store.update(cx, |store, cx| store.clear_breakpoints(cx));
+        breakpoint_store
+            .update(cx, |store, cx| store.toggle_breakpoint_set("logging", cx))
+            .await
+            .unwrap();
+
+        let breakpoints = breakpoint_store.read_with(cx, |store, cx| {
+            store
+                .all_source_breakpoints(cx)
+                .into_values()
+                .flatten()
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(breakpoints.len(), 1);
+        assert_eq!(breakpoints[0].row, 3);
+        assert_eq!(breakpoints[0].condition.as_deref(), Some("x > 0"));
+        assert_eq!(breakpoints[0].message.as_deref(), Some("x is {x}"));
+        assert_eq!(
+            breakpoint_store.read_with(cx, |store, _| store.breakpoint_sets()),
+            vec![("logging".to_string(), true)]
+        );
+
+        // Toggling a loaded set disables its breakpoints as a group.
+        breakpoint_store
+            .update(cx, |store, cx| store.toggle_breakpoint_set("logging", cx))
+            .await
+            .unwrap();
+        let state = breakpoint_store.read_with(cx, |store, cx| {
+            store
+                .all_source_breakpoints(cx)
+                .into_values()
+                .flatten()
+                .map(|breakpoint| breakpoint.state)
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(state, vec![BreakpointState::Disabled]);
+    }
+
+    #[gpui::test]
+    async fn test_toggle_breakpoint_set_across_files(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({
+                "a.rs": "fn a() {\n    let a = 1;\n}\n",
+                "b.rs": "fn b() {\n    let b = 2;\n}\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let breakpoint_store = project.read_with(cx, |project, _| project.breakpoint_store());
+        for path in [path!("/dir/a.rs"), path!("/dir/b.rs")] {
+            let buffer = project
+                .update(cx, |project, cx| project.open_local_buffer(path, cx))
+                .await
+                .unwrap();
+            breakpoint_store.update(cx, |store, cx| {
+                let position = buffer.read(cx).anchor_after(Point::new(1, 0));
+                store.toggle_breakpoint(
+                    buffer.clone(),
+                    BreakpointWithPosition {
+                        position,
+                        bp: Breakpoint::new_standard(),
+                    },
+                    BreakpointEditAction::Toggle,
+                    cx,
+                );
+            });
+        }
+        breakpoint_store
+            .update(cx, |store, cx| {
+                store.save_breakpoint_set("both".to_string(), cx)
+            })
+            .await
+            .unwrap();
+
+        let states = |cx: &mut TestAppContext| {
+            breakpoint_store.read_with(cx, |store, cx| {
+                store
+                    .all_source_breakpoints(cx)
+                    .into_values()
+                    .flatten()
+                    .map(|breakpoint| breakpoint.state)
+                    .collect::<Vec<_>>()
+            })
+        };
+        breakpoint_store
+            .update(cx, |store, cx| store.toggle_breakpoint_set("both", cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            states(cx),
+            vec![BreakpointState::Disabled, BreakpointState::Disabled]
+        );
+        breakpoint_store
+            .update(cx, |store, cx| store.toggle_breakpoint_set("both", cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            states(cx),
+            vec![BreakpointState::Enabled, BreakpointState::Enabled]
+        );
+    }
+}
@@ -325,6 +325,12 @@ actions!(
         Paste,
         /// Downloads the selected remote file
         DownloadFromRemote,
+        /// Uploads local files or directories into the selected remote directory.
+        UploadToRemote,
+        /// Keeps the selected remote directory in sync with a local folder.
+        SyncLocalFolder,
+        /// Stops syncing the selected remote directory with a local folder.
+        StopSyncingFolder,
         /// Renames the selected file or directory.
         Rename,
         /// Opens the selected file in the editor.
@@ -1115,6 +1121,16 @@ impl ProjectPanel {
             let is_unfoldable = auto_fold_dirs && self.is_unfoldable(entry, worktree);
             let is_read_only = project.is_read_only(cx);
             let is_remote = project.is_remote();
+            let is_via_remote_server = project.is_via_remote_server();
+            let is_synced_folder = is_dir
+                && project
+                    .upload_store()
+                    .read(cx)
+                    .synced_folder(&ProjectPath {
+                        worktree_id,
+                        path: entry.path.clone(),
+                    })
+                    .is_some();
             let is_collab = project.is_via_collab();
             let is_local = project.is_local() || project.is_via_wsl_with_host_interop(cx);
 
@@ -1192,6 +1208,16 @@ impl ProjectPanel {
                                 menu.separator()
                                     .action("Download...", Box::new(DownloadFromRemote))
                             })
+                            .when(is_via_remote_server, |menu| {
+                                menu.action("Upload...", Box::new(UploadToRemote))
+                            })
+                            .when(is_via_remote_server && is_dir, |menu| {
+                                if is_synced_folder {
+                                    menu.action("Stop Syncing Folder", Box::new(StopSyncingFolder))
+                                } else {
+                                    menu.action("Sync Local Folder...", Box::new(SyncLocalFolder))
+                                }
+                            })
                             .separator()
                             .action("Copy Path", Box::new(zed_actions::workspace::CopyPath))
                             .action(
@@ -3289,6 +3315,138 @@ impl ProjectPanel {
         .detach();
     }
 
+    /// The directory that uploads land in: the selected directory, or the parent of
+    /// the selected file.
+    fn upload_destination(&self, cx: &App) -> Option<ProjectPath> {
+        let (worktree, entry) = self.selected_sub_entry(cx)?;
+        let path = if entry.is_dir() {
+            entry.path.clone()
+        } else {
+            entry.path.parent()?.into()
+        };
+        Some(ProjectPath {
+            worktree_id: worktree.read(cx).id(),
+            path,
+        })
+    }
+
+    fn upload_to_remote(
+        &mut self,
+        _: &UploadToRemote,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(destination_dir) = self.upload_destination(cx) else {
+            return;
+        };
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: true,
+            multiple: true,
+            prompt: Some("Upload".into()),
+        });
+        cx.spawn_in(window, async move |this, cx| {
+            if let Ok(Ok(Some(paths))) = paths.await {
+                this.update_in(cx, |this, window, cx| {
+                    let paths = paths.into_iter().map(Arc::from).collect();
+                    this.upload_paths(paths, destination_dir, window, cx);
+                })
+                .ok();
+            }
+        })
+        .detach();
+    }
+
+    /// Uploads local paths into a directory of a remote worktree, asking first
+    /// whether to replace the entries they'd overwrite.
+    fn upload_paths(
+        &mut self,
+        paths: Vec<Arc<Path>>,
+        destination_dir: ProjectPath,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(worktree) = self
+            .project
+            .read(cx)
+            .worktree_for_id(destination_dir.worktree_id, cx)
+        else {
+            return;
+        };
+        let paths_to_replace = paths_to_replace(&paths, &destination_dir.path, worktree.read(cx));
+        let upload_store = self.project.read(cx).upload_store().clone();
+        let task = cx.spawn_in(window, async move |_, cx| {
+            let paths = confirm_replacements(paths, paths_to_replace, cx).await?;
+            if paths.is_empty() {
+                return Ok(());
+            }
+            upload_store
+                .update(cx, |upload_store, cx| {
+                    let paths = paths.iter().map(|path| path.to_path_buf()).collect();
+                    upload_store.upload(paths, destination_dir, cx)
+                })
+                .await
+                .context("failed to upload files")
+        });
+        task.detach_and_notify_err(self.workspace.clone(), window, cx);
+    }
+
+    fn sync_local_folder(
+        &mut self,
+        _: &SyncLocalFolder,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some((worktree, entry)) = self.selected_sub_entry(cx) else {
+            return;
+        };
+        if !entry.is_dir() {
+            return;
+        }
+        let destination_dir = ProjectPath {
+            worktree_id: worktree.read(cx).id(),
+            path: entry.path.clone(),
+        };
+        let local_path = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+            prompt: Some("Sync".into()),
+        });
+        let upload_store = self.project.read(cx).upload_store().clone();
+        cx.spawn(async move |_, cx| {
+            if let Ok(Ok(Some(mut paths))) = local_path.await
+                && let Some(local_path) = paths.pop()
+            {
+                upload_store.update(cx, |upload_store, cx| {
+                    upload_store.sync_folder(local_path, destination_dir, cx)
+                });
+            }
+        })
+        .detach();
+    }
+
+    fn stop_syncing_folder(
+        &mut self,
+        _: &StopSyncingFolder,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some((worktree, entry)) = self.selected_sub_entry(cx) else {
+            return;
+        };
+        let destination_dir = ProjectPath {
+            worktree_id: worktree.read(cx).id(),
+            path: entry.path.clone(),
+        };
+        self.project
+            .read(cx)
+            .upload_store()
+            .update(cx, |upload_store, cx| {
+                upload_store.stop_syncing_folder(&destination_dir, cx)
+            });
+    }
+
     fn duplicate(&mut self, _: &Duplicate, window: &mut Window, cx: &mut Context<Self>) {
         self.copy(&Copy {}, window, cx);
         self.paste(&Paste {}, window, cx);
@@ -4133,7 +4291,7 @@ impl ProjectPanel {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let mut paths: Vec<Arc<Path>> = paths.iter().map(|path| Arc::from(path.clone())).collect();
+        let paths: Vec<Arc<Path>> = paths.iter().map(|path| Arc::from(path.clone())).collect();
 
         let open_file_after_drop = paths.len() == 1 && paths[0].is_file();
 
@@ -4153,48 +4311,20 @@ impl ProjectPanel {
             return;
         };
 
-        let mut paths_to_replace = Vec::new();
-        for path in &paths {
-            if let Some(name) = path.file_name()
-                && let Some(name) = name.to_str()
-            {
-                let target_path = target_directory.join(RelPath::unix(name).unwrap());
-                if worktree.read(cx).entry_for_path(&target_path).is_some() {
-                    paths_to_replace.push((name.to_string(), path.clone()));
-                }
-            }
+        if self.project.read(cx).is_via_remote_server() {
+            let destination_dir = ProjectPath {
+                worktree_id: worktree.read(cx).id(),
+                path: target_directory,
+            };
+            self.upload_paths(paths, destination_dir, window, cx);
+            return;
         }
 
+        let paths_to_replace = paths_to_replace(&paths, &target_directory, worktree.read(cx));
+
         cx.spawn_in(window, async move |this, cx| {
             async move {
-                for (filename, original_path) in &paths_to_replace {
-                    let prompt_message = format!(
-                        concat!(
-                            "A file or folder with name {} ",
-                            "already exists in the destination folder. ",
-                            "Do you want to replace it?"
-                        ),
-                        filename
-                    );
-                    let answer = cx
-                        .update(|window, cx| {
-                            window.prompt(
-                                PromptLevel::Info,
-                                &prompt_message,
-                                None,
-                                &["Replace", "Cancel"],
-                                cx,
-                            )
-                        })?
-                        .await?;
-
-                    if answer == 1
-                        && let Some(item_idx) = paths.iter().position(|p| p == original_path)
-                    {
-                        paths.remove(item_idx);
-                    }
-                }
-
+                let paths = confirm_replacements(paths, paths_to_replace, cx).await?;
                 if paths.is_empty() {
                     return Ok(());
                 }
@@ -6364,6 +6494,9 @@ impl Render for ProjectPanel {
                 .when(project.is_via_remote_server(), |el| {
                     el.on_action(cx.listener(Self::open_in_terminal))
                         .on_action(cx.listener(Self::download_from_remote))
+                        .on_action(cx.listener(Self::upload_to_remote))
+                        .on_action(cx.listener(Self::sync_local_folder))
+                        .on_action(cx.listener(Self::stop_syncing_folder))
                 })
                 .track_focus(&self.focus_handle(cx))
                 .child(
@@ -6955,6 +7088,64 @@ impl ClipboardEntry {
     }
 }
 
+/// Returns the names of the `paths` that already exist in `target_directory`, along
+/// with the paths themselves.
+fn paths_to_replace(
+    paths: &[Arc<Path>],
+    target_directory: &RelPath,
+    worktree: &Worktree,
+) -> Vec<(String, Arc<Path>)> {
+    let mut paths_to_replace = Vec::new();
+    for path in paths {
+        if let Some(name) = path.file_name()
+            && let Some(name) = name.to_str()
+        {
+            let target_path = target_directory.join(RelPath::unix(name).unwrap());
+            if worktree.entry_for_path(&target_path).is_some() {
+                paths_to_replace.push((name.to_string(), path.clone()));
+            }
+        }
+    }
+    paths_to_replace
+}
+
+/// Asks whether to replace each existing entry, returning the `paths` that should
+/// still be copied.
+async fn confirm_replacements(
+    mut paths: Vec<Arc<Path>>,
+    paths_to_replace: Vec<(String, Arc<Path>)>,
+    cx: &mut AsyncWindowContext,
+) -> Result<Vec<Arc<Path>>> {
+    for (filename, original_path) in &paths_to_replace {
+        let prompt_message = format!(
+            concat!(
+                "A file or folder with name {} ",
+                "already exists in the destination folder. ",
+                "Do you want to replace it?"
+            ),
+            filename
+        );
+        let answer = cx
+            .update(|window, cx| {
+                window.prompt(
+                    PromptLevel::Info,
+                    &prompt_message,
+                    None,
+                    &["Replace", "Cancel"],
+                    cx,
+                )
+            })?
+            .await?;
+
+        if answer == 1
+            && let Some(item_idx) = paths.iter().position(|p| p == original_path)
+        {
+            paths.remove(item_idx);
+        }
+    }
+    Ok(paths)
+}
+
 #[inline]
 fn cmp_directories_first(a: &Entry, b: &Entry) -> cmp::Ordering {
     util::paths::compare_rel_paths((&a.path, a.is_file()), (&b.path, b.is_file()))
@@ -142,6 +142,10 @@ messages!(
     (JoinChannel, Foreground),
     (JoinChannelBuffer, Foreground),
     (JoinChannelBufferResponse, Foreground),
+    (GetChannelBufferHistory, Background),
+    (GetChannelBufferHistoryResponse, Background),
+    (GetChannelBufferRevision, Background),
+    (GetChannelBufferRevisionResponse, Background),
     (JoinChannelChat, Foreground),
     (JoinChannelChatResponse, Foreground),
     (JoinProject, Foreground),
@@ -362,7 +366,11 @@ messages!(
     (SpawnKernel, Background),
     (SpawnKernelResponse, Background),
     (KillKernel, Background),
+    (WriteToSharedTerminal, Foreground),
     (GetRemoteProfilingData, Background),
+    (ListeningPortsUpdated, Background),
+    (WatchListeningPorts, Background),
+    (UploadFileChunk, Background),
     (GetRemoteProfilingDataResponse, Background),
 );
 
@@ -385,6 +393,7 @@ request_messages!(
     (DeleteChannel, Ack),
     (DeleteProjectEntry, ProjectEntryResponse),
     (DownloadFileByPath, DownloadFileResponse),
+    (UploadFileChunk, Ack),
     (ExpandProjectEntry, ExpandProjectEntryResponse),
     (ExpandAllForProjectEntry, ExpandAllForProjectEntryResponse),
     (Follow, FollowResponse),
@@ -419,6 +428,8 @@ request_messages!(
     (InviteChannelMember, Ack),
     (JoinChannel, JoinRoomResponse),
     (JoinChannelBuffer, JoinChannelBufferResponse),
+    (GetChannelBufferHistory, GetChannelBufferHistoryResponse),
+    (GetChannelBufferRevision, GetChannelBufferRevisionResponse),
     (JoinChannelChat, JoinChannelChatResponse),
     (JoinProject, JoinProjectResponse),
     (JoinRoom, JoinRoomResponse),
@@ -562,6 +573,7 @@ request_messages!(
     (FindSearchCandidatesChunk, Ack),
     (SpawnKernel, SpawnKernelResponse),
     (KillKernel, Ack),
+    (WriteToSharedTerminal, Ack),
     (GetRemoteProfilingData, GetRemoteProfilingDataResponse),
 );
 
@@ -631,6 +643,7 @@ entity_messages!(
     LeaveProject,
     SpawnKernel,
     KillKernel,
+    WriteToSharedTerminal,
     LinkedEditingRange,
     LoadCommitDiff,
     LspQuery,
@@ -752,7 +765,10 @@ entity_messages!(
     FindSearchCandidatesChunk,
     FindSearchCandidatesCancelled,
     DownloadFileByPath,
-    GetRemoteProfilingData
+    GetRemoteProfilingData,
+    ListeningPortsUpdated,
+    WatchListeningPorts,
+    UploadFileChunk
 );
 
 entity_messages!(
@@ -0,0 +1,595 @@
+//! A panel listing the ports listened on by processes in terminals and tasks of a
+//! remote project, which can be forwarded to localhost with a click.
+//!
+//! Forwarded ports are remembered per remote project and forwarded again when the
+//! project is reopened.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use anyhow::{Context as _, Result};
+use db::kvp::KEY_VALUE_STORE;
+use futures::{FutureExt as _, select_biased};
+use gpui::{
+    Action, App, AsyncWindowContext, Context, Entity, EventEmitter, FocusHandle, Focusable, Pixels,
+    Subscription, Task, WeakEntity, Window, actions, px,
+};
+use project::{ListeningPort, Project};
+use serde::{Deserialize, Serialize};
+use settings::Settings as _;
+use ui::{ListItem, Tooltip, prelude::*};
+use util::{
+    ResultExt as _,
+    command::{Child, Stdio},
+};
+use workspace::{
+    Workspace,
+    dock::{DockPosition, Panel, PanelEvent},
+};
+
+use crate::RemoteSettings;
+
+const PORTS_PANEL_KEY: &str = "PortsPanel";
+/// How long a forwarder has to fail to bind its local port before the port counts
+/// as forwarded.
+const FORWARD_STARTUP_TIMEOUT: Duration = Duration::from_secs(2);
+/// How many local ports are tried before giving up on forwarding a remote port.
+const MAX_FORWARD_ATTEMPTS: usize = 3;
+
+actions!(
+    ports_panel,
+    [
+        /// Toggles focus on the ports panel.
+        ToggleFocus
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
+            workspace.toggle_panel_focus::<PortsPanel>(window, cx);
+        });
+    })
+    .detach();
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedPortsPanel {
+    width: Option<Pixels>,
+    position: Option<settings::DockPosition>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct SerializedForwardedPort {
+    remote_port: u16,
+    local_port: u16,
+}
+
+struct ForwardedPort {
+    local_port: u16,
+    /// The process forwarding the port, which is killed when dropped.
+    _process: Child,
+}
+
+pub struct PortsPanel {
+    project: Entity<Project>,
+    focus_handle: FocusHandle,
+    width: Option<Pixels>,
+    position: DockPosition,
+    /// The key forwarded ports are remembered under, known once the project's
+    /// worktrees have loaded.
+    forwarded_ports_key: Option<String>,
+    forwarded_ports: BTreeMap<u16, ForwardedPort>,
+    pending_forwards: BTreeMap<u16, Task<()>>,
+    pending_serialization: Task<Option<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl PortsPanel {
+    pub fn load(
+        workspace: WeakEntity<Workspace>,
+        cx: AsyncWindowContext,
+    ) -> Task<Result<Entity<Self>>> {
+        cx.spawn(async move |cx| {
+            let serialized_panel = if let Some(panel) = cx
+                .background_spawn(async move { KEY_VALUE_STORE.read_kvp(PORTS_PANEL_KEY) })
+                .await
+                .log_err()
+                .flatten()
+            {
+                Some(serde_json::from_str::<SerializedPortsPanel>(&panel)?)
+            } else {
+                None
+            };
+
+            workspace.update_in(cx, |workspace, _, cx| {
+                let project = workspace.project().clone();
+                cx.new(|cx| Self::new(project, serialized_panel, cx))
+            })
+        })
+    }
+
+    fn new(
+        project: Entity<Project>,
+        serialized_panel: Option<SerializedPortsPanel>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        // The remote server only polls for listening ports while a panel shows them.
+        project.read(cx).watch_listening_ports(true, cx);
+        let subscriptions = vec![
+            cx.subscribe(&project, |this, _, event, cx| match event {
+                project::Event::ListeningPortsChanged => cx.notify(),
+                project::Event::WorktreeAdded(_) | project::Event::WorktreeRemoved(_) => {
+                    this.restore_forwarded_ports(cx)
+                }
+                _ => {}
+            }),
+            cx.on_release(|this, cx| this.project.read(cx).watch_listening_ports(false, cx)),
+        ];
+        let mut this = Self {
+            project,
+            focus_handle: cx.focus_handle(),
+            width: serialized_panel
+                .as_ref()
+                .and_then(|panel| panel.width.map(|width| width.round())),
+            position: serialized_panel
+                .as_ref()
+                .and_then(|panel| panel.position)
+                .map_or(DockPosition::Bottom, Into::into),
+            forwarded_ports_key: None,
+            forwarded_ports: BTreeMap::new(),
+            pending_forwards: BTreeMap::new(),
+            pending_serialization: Task::ready(None),
+            _subscriptions: subscriptions,
+        };
+        this.restore_forwarded_ports(cx);
+        this
+    }
+
+    fn serialize(&mut self, cx: &mut Context<Self>) {
+        let serialized_panel = SerializedPortsPanel {
+            width: self.width,
+            position: Some(self.position.into()),
+        };
+        self.pending_serialization = cx.background_spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        PORTS_PANEL_KEY.into(),
+                        serde_json::to_string(&serialized_panel)?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    /// The key the forwarded ports of the remote project are remembered under, or
+    /// `None` until its worktrees have loaded.
+    fn compute_forwarded_ports_key(&self, cx: &App) -> Option<String> {
+        let project = self.project.read(cx);
+        let connection = project.remote_connection_options(cx)?;
+        let paths = project
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).abs_path().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        if paths.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "{PORTS_PANEL_KEY}-forwarded-{}-{}",
+            connection.display_name(),
+            paths.join(",")
+        ))
+    }
+
+    /// Forwards the ports remembered for the project's current worktrees, which is
+    /// redone as worktrees are added and removed.
+    fn restore_forwarded_ports(&mut self, cx: &mut Context<Self>) {
+        let key = self.compute_forwarded_ports_key(cx);
+        if key == self.forwarded_ports_key {
+            return;
+        }
+        self.forwarded_ports_key = key.clone();
+        let Some(key) = key else {
+            return;
+        };
+        cx.spawn(async move |this, cx| {
+            let Some(forwarded_ports) = cx
+                .background_spawn(async move { KEY_VALUE_STORE.read_kvp(&key) })
+                .await?
+            else {
+                return Ok(());
+            };
+            let forwarded_ports =
+                serde_json::from_str::<Vec<SerializedForwardedPort>>(&forwarded_ports)?;
+            this.update(cx, |this, cx| {
+                for port in forwarded_ports {
+                    this.forward_port(port.remote_port, Some(port.local_port), cx);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn serialize_forwarded_ports(&self, cx: &mut Context<Self>) {
+        let Some(key) = self.forwarded_ports_key.clone() else {
+            return;
+        };
+        let forwarded_ports = self
+            .forwarded_ports
+            .iter()
+            .map(|(remote_port, forwarded)| SerializedForwardedPort {
+                remote_port: *remote_port,
+                local_port: forwarded.local_port,
+            })
+            .collect::<Vec<_>>();
+        cx.background_spawn(async move {
+            KEY_VALUE_STORE
+                .write_kvp(key, serde_json::to_string(&forwarded_ports)?)
+                .await
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Forwards `remote_port` to `local_port` on localhost, or to the same port if
+    /// there's none. The forwarder binds the local port itself and exits if it can't,
+    /// in which case another free port is tried.
+    fn forward_port(&mut self, remote_port: u16, local_port: Option<u16>, cx: &mut Context<Self>) {
+        if self.forwarded_ports.contains_key(&remote_port)
+            || self.pending_forwards.contains_key(&remote_port)
+        {
+            return;
+        }
+        let Some(remote_client) = self.project.read(cx).remote_client() else {
+            return;
+        };
+        let task = cx.spawn(async move |this, cx| {
+            let result = async {
+                let mut local_port = local_port.unwrap_or(remote_port);
+                let mut attempts = 0;
+                loop {
+                    attempts += 1;
+                    let command = remote_client.read_with(cx, |remote_client, _| {
+                        remote_client.build_forward_ports_command(vec![(
+                            local_port,
+                            "localhost".to_string(),
+                            remote_port,
+                        )])
+                    })?;
+                    let mut process = util::command::new_command(&command.program)
+                        .args(&command.args)
+                        .envs(&command.env)
+                        .stdin(Stdio::null())
+                        .stdout(Stdio::null())
+                        .stderr(Stdio::null())
+                        .kill_on_drop(true)
+                        .spawn()
+                        .with_context(|| format!("failed to forward port {remote_port}"))?;
+                    let exit_status = select_biased! {
+                        status = process.status().fuse() => Some(status),
+                        _ = cx.background_executor().timer(FORWARD_STARTUP_TIMEOUT).fuse() => None,
+                    };
+                    let Some(exit_status) = exit_status else {
+                        return anyhow::Ok(ForwardedPort {
+                            local_port,
+                            _process: process,
+                        });
+                    };
+                    if attempts == MAX_FORWARD_ATTEMPTS {
+                        anyhow::bail!(
+                            "failed to forward port {remote_port}: forwarder exited with {exit_status:?}"
+                        );
+                    }
+                    log::warn!(
+                        "failed to forward port {remote_port} to local port {local_port}, retrying on another port"
+                    );
+                    local_port = unused_local_port()?;
+                }
+            }
+            .await;
+            this.update(cx, |this, cx| {
+                this.pending_forwards.remove(&remote_port);
+                if let Some(forwarded) = result.log_err() {
+                    this.forwarded_ports.insert(remote_port, forwarded);
+                    this.serialize_forwarded_ports(cx);
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+        self.pending_forwards.insert(remote_port, task);
+        cx.notify();
+    }
+
+    fn toggle_forwarding(&mut self, remote_port: u16, cx: &mut Context<Self>) {
+        if self.forwarded_ports.remove(&remote_port).is_some() {
+            self.serialize_forwarded_ports(cx);
+            cx.notify();
+        } else {
+            self.forward_port(remote_port, None, cx);
+        }
+    }
+
+    fn render_port(
+        &self,
+        remote_port: u16,
+        listening_port: Option<&ListeningPort>,
+        labels: &BTreeMap<u16, String>,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let forwarded_to = self
+            .forwarded_ports
+            .get(&remote_port)
+            .map(|forwarded| forwarded.local_port);
+        let forwarding = self.pending_forwards.contains_key(&remote_port);
+        let description = labels.get(&remote_port).cloned().or_else(|| {
+            listening_port.and_then(|port| {
+                let name = port.process_name.clone()?;
+                Some(match port.pid {
+                    Some(pid) => format!("{name} ({pid})"),
+                    None => name,
+                })
+            })
+        });
+
+        ListItem::new(("port", remote_port as usize)).child(
+            h_flex()
+                .w_full()
+                .gap_2()
+                .child(Label::new(remote_port.to_string()))
+                .when_some(description, |this, description| {
+                    this.child(Label::new(description).color(Color::Muted).truncate())
+                })
+                .when(listening_port.is_none(), |this| {
+                    this.child(
+                        Label::new("not listening")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                })
+                .child(div().flex_1())
+                .when_some(forwarded_to, |this, local_port| {
+                    this.child(
+                        Button::new(
+                            ("open-port", remote_port as usize),
+                            format!("localhost:{local_port}"),
+                        )
+                        .icon(IconName::ArrowUpRight)
+                        .icon_position(IconPosition::End)
+                        .icon_size(IconSize::XSmall)
+                        .tooltip(Tooltip::text("Open in Browser"))
+                        .on_click(move |_, _, cx| {
+                            cx.open_url(&format!("http://localhost:{local_port}"))
+                        }),
+                    )
+                })
+                .child(
+                    Button::new(
+                        ("forward-port", remote_port as usize),
+                        if forwarded_to.is_some() {
+                            "Stop Forwarding"
+                        } else if forwarding {
+                            "Forwarding…"
+                        } else {
+                            "Forward"
+                        },
+                    )
+                    .disabled(forwarding)
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        this.toggle_forwarding(remote_port, cx);
+                    })),
+                ),
+        )
+    }
+}
+
+/// Returns a port on localhost that's free right now. It can be taken again before the
+/// forwarder binds it, which the forwarder then reports by exiting.
+fn unused_local_port() -> Result<u16> {
+    let listener = std::net::TcpListener::bind(("127.0.0.1", 0))
+        .context("failed to find a free local port")?;
+    Ok(listener.local_addr()?.port())
+}
+
+impl Focusable for PortsPanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for PortsPanel {}
+
+impl Render for PortsPanel {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let labels = RemoteSettings::get_global(cx).port_labels.clone();
+        let listening_ports = self.project.read(cx).listening_ports().to_vec();
+        let mut ports = listening_ports
+            .iter()
+            .map(|port| (port.port, Some(port)))
+            .collect::<BTreeMap<_, _>>();
+        for remote_port in self.forwarded_ports.keys() {
+            ports.entry(*remote_port).or_insert(None);
+        }
+        let rows = ports
+            .into_iter()
+            .map(|(remote_port, listening_port)| {
+                self.render_port(remote_port, listening_port, &labels, cx)
+                    .into_any_element()
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .key_context("PortsPanel")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .p_1()
+            .map(|this| {
+                if rows.is_empty() {
+                    this.justify_center().items_center().child(
+                        Label::new("Ports listened on by terminals and tasks will appear here")
+                            .color(Color::Muted),
+                    )
+                } else {
+                    this.children(rows)
+                }
+            })
+    }
+}
+
+impl Panel for PortsPanel {
+    fn persistent_name() -> &'static str {
+        "PortsPanel"
+    }
+
+    fn panel_key() -> &'static str {
+        PORTS_PANEL_KEY
+    }
+
+    fn position(&self, _: &Window, _: &App) -> DockPosition {
+        self.position
+    }
+
+    fn position_is_valid(&self, _: DockPosition) -> bool {
+        true
+    }
+
+    fn set_position(&mut self, position: DockPosition, _: &mut Window, cx: &mut Context<Self>) {
+        self.position = position;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn size(&self, _: &Window, _: &App) -> Pixels {
+        self.width.unwrap_or(px(300.))
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, _: &mut Window, cx: &mut Context<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, _: &Window, _: &App) -> Option<IconName> {
+        Some(IconName::Server)
+    }
+
+    fn icon_tooltip(&self, _: &Window, _: &App) -> Option<&'static str> {
+        Some("Ports Panel")
+    }
+
+    fn icon_label(&self, _: &Window, cx: &App) -> Option<String> {
+        match self.project.read(cx).listening_ports().len() {
+            0 => None,
+            count => Some(count.to_string()),
+        }
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn activation_priority(&self) -> u32 {
+        11
+    }
+
+    fn enabled(&self, cx: &App) -> bool {
+        self.project.read(cx).is_via_remote_server()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use extension::ExtensionHostProxy;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use http_client::BlockedHttpClient;
+    use node_runtime::NodeRuntime;
+    use remote::RemoteClient;
+    use remote_server::{HeadlessAppState, HeadlessProject};
+    use serde_json::json;
+    use std::sync::Arc;
+    use util::path;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_forwarded_ports_key_waits_for_worktrees(
+        cx: &mut TestAppContext,
+        server_cx: &mut TestAppContext,
+    ) {
+        let app_state = cx.update(|cx| {
+            let app_state = AppState::test(cx);
+            release_channel::init(semver::Version::new(0, 0, 0), cx);
+            Project::init(&app_state.client, cx);
+            app_state
+        });
+        server_cx.update(|cx| {
+            release_channel::init(semver::Version::new(0, 0, 0), cx);
+            HeadlessProject::init(cx);
+        });
+
+        let (opts, server_session, _) = RemoteClient::fake_server(cx, server_cx);
+        let remote_fs = FakeFs::new(server_cx.executor());
+        remote_fs
+            .insert_tree(path!("/project"), json!({ "README.md": "# Project" }))
+            .await;
+        let languages = Arc::new(language::LanguageRegistry::new(server_cx.executor()));
+        let _headless = server_cx.new(|cx| {
+            HeadlessProject::new(
+                HeadlessAppState {
+                    session: server_session,
+                    fs: remote_fs.clone(),
+                    http_client: Arc::new(BlockedHttpClient),
+                    node_runtime: NodeRuntime::unavailable(),
+                    languages,
+                    extension_host_proxy: Arc::new(ExtensionHostProxy::new()),
+                    startup_time: std::time::Instant::now(),
+                },
+                false,
+                cx,
+            )
+        });
+
+        let remote_client = RemoteClient::connect_mock(opts, cx).await;
+        let project = cx.update(|cx| {
+            Project::remote(
+                remote_client,
+                app_state.client.clone(),
+                app_state.node_runtime.clone(),
+                app_state.user_store.clone(),
+                app_state.languages.clone(),
+                app_state.fs.clone(),
+                false,
+                cx,
+            )
+        });
+
+        // The panel is loaded before the remote project's worktrees are.
+        let panel = cx.new(|cx| PortsPanel::new(project.clone(), None, cx));
+        panel.read_with(cx, |panel, _| assert_eq!(panel.forwarded_ports_key, None));
+
+        project
+            .update(cx, |project, cx| {
+                project.find_or_create_worktree(path!("/project"), true, cx)
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        let key = panel.read_with(cx, |panel, cx| {
+            let key = panel.forwarded_ports_key.clone().unwrap();
+            assert_eq!(Some(&key), panel.compute_forwarded_ports_key(cx).as_ref());
+            assert!(key.ends_with(path!("/project")), "unexpected key {key}");
+            key
+        });
+
+        // Forwarded ports are saved under the key they're restored from.
+        panel.update(cx, |panel, cx| panel.serialize_forwarded_ports(cx));
+        cx.run_until_parked();
+        assert_eq!(
+            KEY_VALUE_STORE.read_kvp(&key).unwrap().as_deref(),
+            Some("[]")
+        );
+    }
+}
@@ -1,5 +1,6 @@
 mod dev_container_suggest;
 pub mod disconnected_overlay;
+pub mod ports_panel;
 mod remote_connections;
 mod remote_servers;
 mod ssh_config;
@@ -212,6 +213,8 @@ fn get_branch_for_worktree(
 }
 
 pub fn init(cx: &mut App) {
+    ports_panel::init(cx);
+
     #[cfg(target_os = "windows")]
     cx.on_action(|open_wsl: &zed_actions::wsl_actions::OpenFolderInWsl, cx| {
         let create_new_window = open_wsl.create_new_window;
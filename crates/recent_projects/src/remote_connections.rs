@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeMap,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -35,6 +36,8 @@ pub struct RemoteSettings {
     pub wsl_connections: ExtendingVec<WslConnection>,
     /// Whether to read ~/.ssh/config for ssh connection sources.
     pub read_ssh_config: bool,
+    /// Labels to show for ports in the ports panel, by port.
+    pub port_labels: BTreeMap<u16, String>,
 }
 
 impl RemoteSettings {
@@ -122,6 +125,7 @@ impl Settings for RemoteSettings {
             ssh_connections: remote.ssh_connections.clone().unwrap_or_default().into(),
             wsl_connections: remote.wsl_connections.clone().unwrap_or_default().into(),
             read_ssh_config: remote.read_ssh_config.unwrap(),
+            port_labels: remote.remote_port_labels.clone().unwrap_or_default(),
         }
     }
 }
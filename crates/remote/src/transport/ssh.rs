@@ -342,6 +342,9 @@ impl RemoteConnection for SshRemoteConnection {
         let Self { socket, .. } = self;
         let mut args = socket.ssh_command_options();
         args.push("-N".into());
+        // Exit rather than keep running without a forward, e.g. when the local port is taken.
+        args.push("-o".into());
+        args.push("ExitOnForwardFailure=yes".into());
         for (local_port, host, remote_port) in forwards {
             args.push("-L".into());
             args.push(format!("{local_port}:{host}:{remote_port}"));
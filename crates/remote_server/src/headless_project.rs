@@ -7,8 +7,9 @@ use lsp::LanguageServerId;
 
 use extension::ExtensionHostProxy;
 use extension_host::headless_host::HeadlessExtensionStore;
-use fs::Fs;
-use gpui::{App, AppContext as _, AsyncApp, Context, Entity, PromptLevel};
+use fs::{Fs, RemoveOptions, RenameOptions};
+use futures::{FutureExt as _, TryStreamExt as _, channel::mpsc, future::Shared};
+use gpui::{App, AppContext as _, AsyncApp, Context, Entity, PromptLevel, Task};
 use http_client::HttpClient;
 use language::{Buffer, BufferEvent, LanguageRegistry, proto::serialize_operation};
 use node_runtime::NodeRuntime;
@@ -38,11 +39,12 @@ use settings::initial_server_settings_content;
 use std::{
     num::NonZeroU64,
     path::{Path, PathBuf},
+    pin::Pin,
     sync::{
         Arc,
         atomic::{AtomicU64, AtomicUsize, Ordering},
     },
-    time::Instant,
+    time::{Duration, Instant},
 };
 use sysinfo::{ProcessRefreshKind, RefreshKind, System, UpdateKind};
 use util::{ResultExt, paths::PathStyle, rel_path::RelPath};
@@ -70,8 +72,30 @@ pub struct HeadlessProject {
     // Local variant is used within LSP store, but that's a separate entity.
     pub _toolchain_store: Entity<ToolchainStore>,
     pub kernels: HashMap<String, Child>,
+    pending_uploads: HashMap<u64, PendingUpload>,
+    /// Reports listening ports to the client while it watches them.
+    watch_listening_ports: Option<Task<()>>,
 }
 
+/// A file being uploaded by the client, whose chunks are streamed into a temporary
+/// file next to its destination until the last one arrives.
+struct PendingUpload {
+    chunks: mpsc::UnboundedSender<std::io::Result<Vec<u8>>>,
+    write: Shared<Task<Result<(), Arc<anyhow::Error>>>>,
+    /// The number of bytes received so far, where the next chunk starts.
+    received: u64,
+    /// Abandons the upload when no chunk arrives for [`UPLOAD_IDLE_TIMEOUT`].
+    _idle_timeout: Task<()>,
+}
+
+/// How often listening ports are polled after they change. The interval doubles each
+/// time they're found unchanged, up to [`LISTENING_PORTS_MAX_POLL_INTERVAL`].
+const LISTENING_PORTS_MIN_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const LISTENING_PORTS_MAX_POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// How long an upload can go without receiving a chunk, e.g. because the client
+/// disconnected, before it's abandoned and its temporary file removed.
+pub(crate) const UPLOAD_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
 pub struct HeadlessAppState {
     pub session: AnyProtoClient,
     pub fs: Arc<dyn Fs>,
@@ -82,6 +106,75 @@ pub struct HeadlessAppState {
     pub startup_time: Instant,
 }
 
+impl PendingUpload {
+    fn start(
+        upload_id: u64,
+        fs: Arc<dyn Fs>,
+        abs_path: PathBuf,
+        cx: &mut Context<HeadlessProject>,
+    ) -> Self {
+        let (chunks, reader) = mpsc::unbounded();
+        let write = cx.background_spawn(async move {
+            let result: Result<()> = async {
+                let file_name = abs_path
+                    .file_name()
+                    .context("upload destination has no file name")?
+                    .to_string_lossy();
+                let temp_path = abs_path.with_file_name(format!(".{file_name}.zed-upload"));
+                if let Some(parent) = abs_path.parent() {
+                    fs.create_dir(parent).await?;
+                }
+                let mut reader = reader.into_async_read();
+                let result = async {
+                    fs.create_file_with(&temp_path, Pin::new(&mut reader))
+                        .await?;
+                    fs.rename(
+                        &temp_path,
+                        &abs_path,
+                        RenameOptions {
+                            overwrite: true,
+                            ..Default::default()
+                        },
+                    )
+                    .await
+                }
+                .await;
+                if result.is_err() {
+                    fs.remove_file(
+                        &temp_path,
+                        RemoveOptions {
+                            ignore_if_not_exists: true,
+                            ..Default::default()
+                        },
+                    )
+                    .await
+                    .log_err();
+                }
+                result
+            }
+            .await;
+            result.map_err(Arc::new)
+        });
+        Self {
+            chunks,
+            write: write.shared(),
+            received: 0,
+            _idle_timeout: Self::abandon_when_idle(upload_id, cx),
+        }
+    }
+
+    fn abandon_when_idle(upload_id: u64, cx: &mut Context<HeadlessProject>) -> Task<()> {
+        cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(UPLOAD_IDLE_TIMEOUT).await;
+            this.update(cx, |this, cx| {
+                log::warn!("abandoning upload {upload_id}, which received no data for a while");
+                this.abandon_upload(upload_id, cx);
+            })
+            .ok();
+        })
+    }
+}
+
 impl HeadlessProject {
     pub fn init(cx: &mut App) {
         settings::init(cx);
@@ -304,10 +397,12 @@ impl HeadlessProject {
         session.add_entity_request_handler(Self::handle_open_server_settings);
         session.add_entity_request_handler(Self::handle_get_directory_environment);
         session.add_entity_message_handler(Self::handle_toggle_lsp_logs);
+        session.add_entity_message_handler(Self::handle_watch_listening_ports);
         session.add_entity_request_handler(Self::handle_open_image_by_path);
         session.add_entity_request_handler(Self::handle_trust_worktrees);
         session.add_entity_request_handler(Self::handle_restrict_worktrees);
         session.add_entity_request_handler(Self::handle_download_file_by_path);
+        session.add_entity_request_handler(Self::handle_upload_file_chunk);
 
         session.add_entity_message_handler(Self::handle_find_search_candidates_cancel);
         session.add_entity_request_handler(BufferStore::handle_update_buffer);
@@ -358,9 +453,41 @@ impl HeadlessProject {
             profiling_collector: gpui::ProfilingCollector::new(startup_time),
             _toolchain_store: toolchain_store,
             kernels: Default::default(),
+            pending_uploads: Default::default(),
+            watch_listening_ports: None,
         }
     }
 
+    /// Reports the ports listened on by processes in Zed terminals to the client
+    /// now, and then whenever they change.
+    fn watch_listening_ports(cx: &mut Context<Self>) -> Task<()> {
+        cx.spawn(async move |this, cx| {
+            let mut reported_ports = None;
+            let mut poll_interval = LISTENING_PORTS_MIN_POLL_INTERVAL;
+            loop {
+                let ports = cx
+                    .background_spawn(async move { crate::listening_ports::listening_ports() })
+                    .await;
+                if reported_ports.as_ref() == Some(&ports) {
+                    poll_interval = (poll_interval * 2).min(LISTENING_PORTS_MAX_POLL_INTERVAL);
+                } else {
+                    let Ok(session) = this.read_with(cx, |this, _| this.session.clone()) else {
+                        break;
+                    };
+                    session
+                        .send(proto::ListeningPortsUpdated {
+                            project_id: REMOTE_SERVER_PROJECT_ID,
+                            ports: ports.clone(),
+                        })
+                        .log_err();
+                    reported_ports = Some(ports);
+                    poll_interval = LISTENING_PORTS_MIN_POLL_INTERVAL;
+                }
+                cx.background_executor().timer(poll_interval).await;
+            }
+        })
+    }
+
     fn on_buffer_event(
         &mut self,
         buffer: Entity<Buffer>,
@@ -795,6 +922,67 @@ impl HeadlessProject {
         Ok(proto::DownloadFileResponse { file_id })
     }
 
+    pub async fn handle_upload_file_chunk(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::UploadFileChunk>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let path = RelPath::from_proto(&envelope.payload.path)?;
+        let upload_id = envelope.payload.upload_id;
+
+        let (fs, worktree) = this.read_with(&cx, |this, cx| {
+            (
+                this.fs.clone(),
+                this.worktree_store
+                    .read(cx)
+                    .worktree_for_id(worktree_id, cx),
+            )
+        });
+        let worktree = worktree.context("worktree not found")?;
+        let abs_path = worktree.read_with(&cx, |worktree, _| worktree.absolutize(&path));
+
+        let offset = envelope.payload.offset;
+        let len = envelope.payload.data.len() as u64;
+        let (chunks, write) = this.update(&mut cx, |this, cx| {
+            let received = this
+                .pending_uploads
+                .get(&upload_id)
+                .map_or(0, |upload| upload.received);
+            if offset != received {
+                this.abandon_upload(upload_id, cx);
+                anyhow::bail!("upload {upload_id} is missing data before offset {offset}");
+            }
+            let upload = this
+                .pending_uploads
+                .entry(upload_id)
+                .or_insert_with(|| PendingUpload::start(upload_id, fs, abs_path, cx));
+            upload.received += len;
+            upload._idle_timeout = PendingUpload::abandon_when_idle(upload_id, cx);
+            anyhow::Ok((upload.chunks.clone(), upload.write.clone()))
+        })?;
+        chunks.unbounded_send(Ok(envelope.payload.data)).ok();
+
+        if envelope.payload.is_last {
+            drop(chunks);
+            this.update(&mut cx, |this, _| this.pending_uploads.remove(&upload_id));
+            write.await.map_err(|error| anyhow!("{error:#}"))?;
+        }
+        Ok(proto::Ack {})
+    }
+
+    /// Stops writing an upload that won't be completed, removing its temporary file.
+    fn abandon_upload(&mut self, upload_id: u64, cx: &mut Context<Self>) {
+        if let Some(upload) = self.pending_uploads.remove(&upload_id) {
+            upload
+                .chunks
+                .unbounded_send(Err(std::io::Error::other("upload was abandoned")))
+                .ok();
+            // Keep the write running until it has failed and cleaned up after itself.
+            cx.background_spawn(upload.write).detach();
+        }
+    }
+
     pub async fn handle_open_new_buffer(
         this: Entity<Self>,
         _message: TypedEnvelope<proto::OpenNewBuffer>,
@@ -821,6 +1009,20 @@ impl HeadlessProject {
         })
     }
 
+    async fn handle_watch_listening_ports(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::WatchListeningPorts>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| {
+            this.watch_listening_ports = envelope
+                .payload
+                .watch
+                .then(|| Self::watch_listening_ports(cx));
+        });
+        Ok(())
+    }
+
     async fn handle_toggle_lsp_logs(
         _: Entity<Self>,
         envelope: TypedEnvelope<proto::ToggleLspLogs>,
@@ -0,0 +1,179 @@
+//! Detection of the TCP ports listened on by processes started from Zed terminals and
+//! tasks, so that the client can offer to forward them.
+//!
+//! Terminals on the remote host are spawned with `ZED_TERM=true` in their environment,
+//! which their descendants inherit, so that's how their processes are recognized.
+
+use collections::{HashMap, HashSet};
+use rpc::proto;
+use sysinfo::{ProcessRefreshKind, RefreshKind, System, UpdateKind};
+
+/// Returns the ports listened on by processes running in Zed terminals, sorted by port.
+pub(crate) fn listening_ports() -> Vec<proto::ListeningPort> {
+    let system = System::new_with_specifics(
+        RefreshKind::nothing().with_processes(
+            ProcessRefreshKind::nothing()
+                .without_tasks()
+                .with_environ(UpdateKind::Always),
+        ),
+    );
+    let own_pid = std::process::id();
+    let process_names = system
+        .processes()
+        .values()
+        .filter(|process| {
+            process.pid().as_u32() != own_pid
+                && process
+                    .environ()
+                    .iter()
+                    .any(|variable| variable.as_encoded_bytes() == b"ZED_TERM=true")
+        })
+        .map(|process| {
+            (
+                process.pid().as_u32(),
+                process.name().to_string_lossy().into_owned(),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+    if process_names.is_empty() {
+        return Vec::new();
+    }
+
+    let mut seen_ports = HashSet::default();
+    let mut ports = listening_sockets(&process_names.keys().copied().collect())
+        .into_iter()
+        .filter(|(port, _)| seen_ports.insert(*port))
+        .map(|(port, pid)| proto::ListeningPort {
+            port: port as u32,
+            pid: Some(pid),
+            process_name: process_names.get(&pid).cloned(),
+        })
+        .collect::<Vec<_>>();
+    ports.sort_by_key(|port| port.port);
+    ports
+}
+
+/// Returns the `(port, pid)` pairs of the listening TCP sockets owned by `pids`.
+#[cfg(target_os = "linux")]
+fn listening_sockets(pids: &HashSet<u32>) -> Vec<(u16, u32)> {
+    let mut ports_by_inode = HashMap::default();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        if let Ok(contents) = std::fs::read_to_string(table) {
+            ports_by_inode.extend(parse_proc_net_tcp(&contents));
+        }
+    }
+
+    let mut sockets = Vec::new();
+    for pid in pids {
+        let Ok(fds) = std::fs::read_dir(format!("/proc/{pid}/fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(target) = std::fs::read_link(fd.path()) else {
+                continue;
+            };
+            if let Some(port) = target
+                .to_str()
+                .and_then(|target| target.strip_prefix("socket:["))
+                .and_then(|inode| inode.strip_suffix(']'))
+                .and_then(|inode| inode.parse::<u64>().ok())
+                .and_then(|inode| ports_by_inode.get(&inode))
+            {
+                sockets.push((*port, *pid));
+            }
+        }
+    }
+    sockets
+}
+
+#[cfg(target_os = "macos")]
+fn listening_sockets(pids: &HashSet<u32>) -> Vec<(u16, u32)> {
+    let output = std::process::Command::new("lsof")
+        .args(["-nP", "-iTCP", "-sTCP:LISTEN", "-Fpn"])
+        .output();
+    match output {
+        Ok(output) => parse_lsof_output(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .filter(|(_, pid)| pids.contains(pid))
+            .collect(),
+        Err(error) => {
+            log::error!("failed to run lsof: {error}");
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn listening_sockets(_pids: &HashSet<u32>) -> Vec<(u16, u32)> {
+    Vec::new()
+}
+
+/// Parses `/proc/net/tcp` or `/proc/net/tcp6`, returning the ports of the listening
+/// sockets by inode.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_proc_net_tcp(contents: &str) -> HashMap<u64, u16> {
+    const TCP_LISTEN: &str = "0A";
+
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            if fields.get(3) != Some(&TCP_LISTEN) {
+                return None;
+            }
+            let (_, port) = fields.get(1)?.rsplit_once(':')?;
+            let port = u16::from_str_radix(port, 16).ok()?;
+            let inode = fields.get(9)?.parse().ok()?;
+            Some((inode, port))
+        })
+        .collect()
+}
+
+/// Parses the output of `lsof -Fpn`, where each process's `p<pid>` line is followed
+/// by `n<address>:<port>` lines for its sockets.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_lsof_output(output: &str) -> Vec<(u16, u32)> {
+    let mut sockets = Vec::new();
+    let mut pid = None;
+    for line in output.lines() {
+        if let Some(value) = line.strip_prefix('p') {
+            pid = value.parse().ok();
+        } else if let Some(address) = line.strip_prefix('n')
+            && let Some(pid) = pid
+            && let Some(port) = address
+                .rsplit_once(':')
+                .and_then(|(_, port)| port.parse().ok())
+        {
+            sockets.push((port, pid));
+        }
+    }
+    sockets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_net_tcp() {
+        let contents = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41234 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41235 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:A1B2 0100007F:0BB8 01 00000000:00000000 00:00000000 00000000  1000        0 41236 1 0000000000000000 20 4 30 10 -1
+";
+        assert_eq!(
+            parse_proc_net_tcp(contents),
+            HashMap::from_iter([(41234, 3000), (41235, 8080)])
+        );
+    }
+
+    #[test]
+    fn test_parse_lsof_output() {
+        let output = "p123\nn*:3000\nn[::1]:3000\np456\nn127.0.0.1:5173\n";
+        assert_eq!(
+            parse_lsof_output(output),
+            vec![(3000, 123), (3000, 123), (5173, 456)]
+        );
+    }
+}
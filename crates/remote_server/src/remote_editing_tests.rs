@@ -23,7 +23,7 @@ use lsp::{
 };
 use node_runtime::NodeRuntime;
 use project::{
-    ProgressToken, Project,
+    ProgressToken, Project, ProjectPath,
     agent_server_store::AgentServerCommand,
     search::{SearchQuery, SearchResult},
 };
@@ -34,6 +34,7 @@ use smol::stream::StreamExt;
 use std::{
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 use unindent::Unindent as _;
 use util::{path, paths::PathMatcher, rel_path::rel_path};
@@ -1539,6 +1540,238 @@ async fn test_copy_file_into_remote_project(
     );
 }
 
+#[gpui::test]
+async fn test_upload_files_into_remote_project(
+    cx: &mut TestAppContext,
+    server_cx: &mut TestAppContext,
+) {
+    let remote_fs = FakeFs::new(server_cx.executor());
+    remote_fs
+        .insert_tree(
+            path!("/code"),
+            json!({
+                "project1": {
+                    "src": {
+                        "main.rs": "",
+                        "file1": "old content",
+                    },
+                    "data": {},
+                },
+            }),
+        )
+        .await;
+
+    let (project, _) = init_test(&remote_fs, cx, server_cx).await;
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree(path!("/code/project1"), true, cx)
+        })
+        .await
+        .unwrap();
+    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+    cx.run_until_parked();
+
+    let local_fs = project
+        .read_with(cx, |project, _| project.fs().clone())
+        .as_fake();
+    local_fs
+        .insert_tree(
+            path!("/local"),
+            json!({
+                "file1": "new content",
+                "dir1": {
+                    "file2": "file 2 content",
+                    "dir2": {},
+                },
+                "dataset": {
+                    "a.csv": "1,2,3",
+                    "nested": {
+                        "b.csv": "4,5,6",
+                    },
+                },
+            }),
+        )
+        .await;
+
+    let upload_store = project.read_with(cx, |project, _| project.upload_store().clone());
+    upload_store
+        .update(cx, |upload_store, cx| {
+            upload_store.upload(
+                vec![
+                    PathBuf::from(path!("/local/file1")),
+                    PathBuf::from(path!("/local/dir1")),
+                ],
+                ProjectPath {
+                    worktree_id,
+                    path: rel_path("src").into(),
+                },
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    cx.run_until_parked();
+
+    assert_eq!(
+        remote_fs
+            .load(path!("/code/project1/src/file1").as_ref())
+            .await
+            .unwrap(),
+        "new content"
+    );
+    assert_eq!(
+        remote_fs
+            .load(path!("/code/project1/src/dir1/file2").as_ref())
+            .await
+            .unwrap(),
+        "file 2 content"
+    );
+    assert!(
+        remote_fs
+            .is_dir(path!("/code/project1/src/dir1/dir2").as_ref())
+            .await
+    );
+    upload_store.read_with(cx, |upload_store, _| {
+        assert!(upload_store.uploads().is_empty())
+    });
+
+    let data_dir = ProjectPath {
+        worktree_id,
+        path: rel_path("data").into(),
+    };
+    upload_store.update(cx, |upload_store, cx| {
+        upload_store.sync_folder(PathBuf::from(path!("/local/dataset")), data_dir.clone(), cx)
+    });
+    cx.run_until_parked();
+    assert_eq!(
+        remote_fs
+            .load(path!("/code/project1/data/nested/b.csv").as_ref())
+            .await
+            .unwrap(),
+        "4,5,6"
+    );
+
+    local_fs
+        .insert_file(path!("/local/dataset/a.csv"), b"7,8,9".to_vec())
+        .await;
+    local_fs
+        .remove_file(
+            path!("/local/dataset/nested/b.csv").as_ref(),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.run_until_parked();
+
+    assert_eq!(
+        remote_fs
+            .load(path!("/code/project1/data/a.csv").as_ref())
+            .await
+            .unwrap(),
+        "7,8,9"
+    );
+    assert!(
+        !remote_fs
+            .is_file(path!("/code/project1/data/nested/b.csv").as_ref())
+            .await
+    );
+
+    upload_store.update(cx, |upload_store, cx| {
+        upload_store.stop_syncing_folder(&data_dir, cx)
+    });
+    assert!(upload_store.read_with(cx, |upload_store, _| {
+        upload_store.synced_folders().is_empty()
+    }));
+}
+
+#[gpui::test]
+async fn test_abandoned_uploads_are_cleaned_up(
+    cx: &mut TestAppContext,
+    server_cx: &mut TestAppContext,
+) {
+    let remote_fs = FakeFs::new(server_cx.executor());
+    remote_fs
+        .insert_tree(path!("/code"), json!({ "project1": { "src": {} } }))
+        .await;
+
+    let (project, _) = init_test(&remote_fs, cx, server_cx).await;
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree(path!("/code/project1"), true, cx)
+        })
+        .await
+        .unwrap();
+    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+    cx.run_until_parked();
+
+    let client = project.read_with(cx, |project, cx| {
+        project.remote_client().unwrap().read(cx).proto_client()
+    });
+    let chunk = |upload_id: u64, path: &str, data: &str, offset: u64, is_last: bool| {
+        rpc::proto::UploadFileChunk {
+            project_id: rpc::proto::REMOTE_SERVER_PROJECT_ID,
+            worktree_id: worktree_id.to_proto(),
+            path: rel_path(path).to_proto(),
+            upload_id,
+            data: data.as_bytes().to_vec(),
+            is_last,
+            offset,
+        }
+    };
+
+    // An upload whose client goes away is abandoned once it stops receiving data.
+    client
+        .request(chunk(1, "src/abandoned", "hello", 0, false))
+        .await
+        .unwrap();
+    server_cx
+        .executor()
+        .advance_clock(crate::headless_project::UPLOAD_IDLE_TIMEOUT);
+    server_cx.run_until_parked();
+    cx.run_until_parked();
+    assert!(
+        client
+            .request(chunk(1, "src/abandoned", " world", 5, true))
+            .await
+            .is_err()
+    );
+
+    // A chunk that doesn't continue where the upload left off abandons it too.
+    client
+        .request(chunk(2, "src/skipped", "hello", 0, false))
+        .await
+        .unwrap();
+    assert!(
+        client
+            .request(chunk(2, "src/skipped", "world", 10, true))
+            .await
+            .is_err()
+    );
+
+    client
+        .request(chunk(3, "src/complete", "hello", 0, false))
+        .await
+        .unwrap();
+    client
+        .request(chunk(3, "src/complete", " world", 5, true))
+        .await
+        .unwrap();
+    server_cx.run_until_parked();
+
+    assert_eq!(
+        remote_fs
+            .load(path!("/code/project1/src/complete").as_ref())
+            .await
+            .unwrap(),
+        "hello world"
+    );
+    assert_eq!(
+        remote_fs.files(),
+        [PathBuf::from(path!("/code/project1/src/complete"))]
+    );
+}
+
 #[gpui::test]
 async fn test_remote_git_diffs(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
     let text_2 = "
@@ -1,4 +1,5 @@
 mod headless_project;
+mod listening_ports;
 
 #[cfg(test)]
 mod remote_editing_tests;
@@ -0,0 +1,132 @@
+use anyhow::{Context as _, Result, bail};
+use landlock::{
+    ABI, Access, AccessFs, PathBeneath, PathFd, Ruleset, RulesetAttr, RulesetCreatedAttr,
+    RulesetStatus,
+};
+use std::{io, path::PathBuf};
+
+use crate::SandboxPolicy;
+
+const LANDLOCK_ABI: ABI = ABI::V3;
+
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: u32 = 0xC000_003E;
+#[cfg(target_arch = "aarch64")]
+const AUDIT_ARCH: u32 = 0xC000_00B7;
+
+/// Syscall numbers at or above this are x32 syscalls, which would otherwise bypass the
+/// filter on x86_64.
+const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+
+// Offsets into `struct seccomp_data`.
+const SECCOMP_DATA_NR_OFFSET: u32 = 0;
+const SECCOMP_DATA_ARCH_OFFSET: u32 = 4;
+const SECCOMP_DATA_ARG0_OFFSET: u32 = 16;
+
+const BPF_LD: u16 = 0x00;
+const BPF_W: u16 = 0x00;
+const BPF_ABS: u16 = 0x20;
+const BPF_JMP: u16 = 0x05;
+const BPF_JEQ: u16 = 0x10;
+const BPF_JGE: u16 = 0x30;
+const BPF_K: u16 = 0x00;
+const BPF_RET: u16 = 0x06;
+
+const SECCOMP_RET_KILL_PROCESS: u32 = 0x8000_0000;
+const SECCOMP_RET_ERRNO: u32 = 0x0005_0000;
+const SECCOMP_RET_ALLOW: u32 = 0x7fff_0000;
+
+/// Restricts the current process, and everything it later executes, to the policy.
+pub(crate) fn apply(policy: &SandboxPolicy) -> Result<()> {
+    // Required to install a seccomp filter without privileges, and keeps setuid
+    // binaries from escaping the sandbox.
+    if unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) } != 0 {
+        return Err(io::Error::last_os_error()).context("failed to set no_new_privs");
+    }
+
+    restrict_file_system(&policy.read_only_paths, &policy.read_write_paths)
+        .context("failed to restrict file system access")?;
+    if !policy.allow_network {
+        disable_network().context("failed to disable network access")?;
+    }
+    Ok(())
+}
+
+fn restrict_file_system(read_only_paths: &[PathBuf], read_write_paths: &[PathBuf]) -> Result<()> {
+    let mut ruleset = Ruleset::default()
+        .handle_access(AccessFs::from_all(LANDLOCK_ABI))?
+        .create()?;
+    let rules = read_only_paths
+        .iter()
+        .map(|path| (path, AccessFs::from_read(LANDLOCK_ABI)))
+        .chain(
+            read_write_paths
+                .iter()
+                .map(|path| (path, AccessFs::from_all(LANDLOCK_ABI))),
+        );
+    for (path, access) in rules {
+        // Not every system has every default path, like `/lib32` or `/nix`.
+        let Ok(fd) = PathFd::new(path) else {
+            continue;
+        };
+        ruleset = ruleset.add_rule(PathBeneath::new(fd, access))?;
+    }
+
+    let status = ruleset.restrict_self()?;
+    if status.ruleset == RulesetStatus::NotEnforced {
+        bail!("Landlock is not supported by this kernel");
+    }
+    Ok(())
+}
+
+/// Installs a seccomp filter that fails attempts to create IPv4, IPv6 and Unix sockets.
+/// Unix sockets are included because they can reach services that have network access,
+/// like ssh-agent or the Docker daemon. Connected socket pairs are still allowed.
+fn disable_network() -> Result<()> {
+    let deny = SECCOMP_RET_ERRNO | libc::EACCES as u32;
+    let mut filter = [
+        statement(BPF_LD | BPF_W | BPF_ABS, SECCOMP_DATA_ARCH_OFFSET),
+        jump(BPF_JMP | BPF_JEQ | BPF_K, AUDIT_ARCH, 1, 0),
+        statement(BPF_RET | BPF_K, SECCOMP_RET_KILL_PROCESS),
+        statement(BPF_LD | BPF_W | BPF_ABS, SECCOMP_DATA_NR_OFFSET),
+        jump(BPF_JMP | BPF_JGE | BPF_K, X32_SYSCALL_BIT, 6, 0),
+        // io_uring can create sockets without going through the `socket` syscall.
+        jump(
+            BPF_JMP | BPF_JEQ | BPF_K,
+            libc::SYS_io_uring_setup as u32,
+            5,
+            0,
+        ),
+        jump(BPF_JMP | BPF_JEQ | BPF_K, libc::SYS_socket as u32, 0, 5),
+        statement(BPF_LD | BPF_W | BPF_ABS, SECCOMP_DATA_ARG0_OFFSET),
+        jump(BPF_JMP | BPF_JEQ | BPF_K, libc::AF_INET as u32, 2, 0),
+        jump(BPF_JMP | BPF_JEQ | BPF_K, libc::AF_INET6 as u32, 1, 0),
+        jump(BPF_JMP | BPF_JEQ | BPF_K, libc::AF_UNIX as u32, 0, 1),
+        statement(BPF_RET | BPF_K, deny),
+        statement(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+    ];
+    let program = libc::sock_fprog {
+        len: filter.len() as u16,
+        filter: filter.as_mut_ptr(),
+    };
+
+    let result = unsafe {
+        libc::prctl(
+            libc::PR_SET_SECCOMP,
+            libc::SECCOMP_MODE_FILTER,
+            &program as *const libc::sock_fprog,
+        )
+    };
+    if result != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+fn statement(code: u16, k: u32) -> libc::sock_filter {
+    jump(code, k, 0, 0)
+}
+
+fn jump(code: u16, k: u32, jt: u8, jf: u8) -> libc::sock_filter {
+    libc::sock_filter { code, jt, jf, k }
+}
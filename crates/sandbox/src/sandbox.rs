@@ -0,0 +1,265 @@
+//! Runs commands with restricted access to the file system and network.
+//!
+//! Zed re-executes itself with `--sandbox-policy`, which applies the policy to its own
+//! process and then executes the command, so the restrictions are inherited by
+//! everything the command spawns. This is only supported on Linux on x86_64 and
+//! aarch64, where it uses Landlock to restrict file system access and seccomp to
+//! disable networking.
+
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+mod linux;
+
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    fs::OpenOptions,
+    io::Write as _,
+    path::{Path, PathBuf},
+};
+use util::shell::ShellKind;
+
+/// The exit code of the sandbox when it fails to apply its policy, before running the
+/// command. Commands can exit with it too, so use [`SetupErrorFile`] to tell the two
+/// apart.
+pub const SETUP_FAILED_EXIT_CODE: i32 = 125;
+
+/// System directories that sandboxed commands can always read and execute from.
+const DEFAULT_READ_ONLY_PATHS: &[&str] = &[
+    "/bin", "/etc", "/lib", "/lib32", "/lib64", "/nix", "/opt", "/proc", "/run", "/sbin", "/sys",
+    "/usr",
+];
+
+/// Files and directories that sandboxed commands can always write to. Only the devices
+/// commands commonly need are listed, rather than all of `/dev`.
+const DEFAULT_READ_WRITE_PATHS: &[&str] = &[
+    "/dev/full",
+    "/dev/null",
+    "/dev/ptmx",
+    "/dev/pts",
+    "/dev/random",
+    "/dev/shm",
+    "/dev/tty",
+    "/dev/urandom",
+    "/dev/zero",
+    "/tmp",
+];
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SandboxPolicy {
+    pub read_only_paths: Vec<PathBuf>,
+    pub read_write_paths: Vec<PathBuf>,
+    pub allow_network: bool,
+}
+
+impl SandboxPolicy {
+    /// Creates a policy that allows access to the given paths, in addition to the
+    /// system directories every command needs.
+    pub fn new(
+        read_only_paths: impl IntoIterator<Item = PathBuf>,
+        read_write_paths: impl IntoIterator<Item = PathBuf>,
+        allow_network: bool,
+    ) -> Self {
+        Self {
+            read_only_paths: DEFAULT_READ_ONLY_PATHS
+                .iter()
+                .map(PathBuf::from)
+                .chain(read_only_paths)
+                .collect(),
+            read_write_paths: DEFAULT_READ_WRITE_PATHS
+                .iter()
+                .map(PathBuf::from)
+                .chain(read_write_paths)
+                .collect(),
+            allow_network,
+        }
+    }
+}
+
+/// What Zed is given with `--sandbox-policy`.
+#[derive(Serialize, Deserialize)]
+struct SandboxRequest {
+    policy: SandboxPolicy,
+    setup_error_path: PathBuf,
+}
+
+/// A file where the sandbox reports that it failed to apply its policy, so that this
+/// isn't mistaken for the command failing on its own. The file is deleted when this is
+/// dropped.
+pub struct SetupErrorFile(tempfile::NamedTempFile);
+
+impl SetupErrorFile {
+    pub fn new() -> Result<Self> {
+        let file = tempfile::Builder::new()
+            .prefix("zed-sandbox-")
+            .tempfile()
+            .context("failed to create the sandbox's error file")?;
+        Ok(Self(file))
+    }
+
+    pub fn path(&self) -> &Path {
+        self.0.path()
+    }
+
+    /// Returns why the sandbox failed to start, if it did, in which case the command
+    /// never ran.
+    pub fn error(&self) -> Option<String> {
+        let error = std::fs::read_to_string(self.path()).ok()?;
+        let error = error.trim();
+        (!error.is_empty()).then(|| error.to_string())
+    }
+}
+
+/// Returns whether commands can be sandboxed on this platform.
+pub fn is_supported() -> bool {
+    cfg!(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))
+}
+
+/// Returns a shell command line that runs `command` with the given shell, inside a
+/// sandbox applying `policy`. If the sandbox fails to start, it writes why to
+/// `setup_error`.
+pub fn wrap_command(
+    sandbox_program: &Path,
+    policy: &SandboxPolicy,
+    setup_error: &SetupErrorFile,
+    shell: &str,
+    command: &str,
+) -> Result<String> {
+    let request = serde_json::to_string(&SandboxRequest {
+        policy: policy.clone(),
+        setup_error_path: setup_error.path().to_path_buf(),
+    })?;
+    let quote = |arg: &str| {
+        ShellKind::Posix
+            .try_quote(arg)
+            .map(Cow::into_owned)
+            .with_context(|| format!("failed to quote {arg:?} for the sandbox"))
+    };
+    Ok(format!(
+        "{} --sandbox-policy {} -- {} -c {}",
+        quote(&sandbox_program.to_string_lossy())?,
+        quote(&request)?,
+        quote(shell)?,
+        quote(command)?
+    ))
+}
+
+/// The main function for when Zed is running a command in a sandbox.
+pub fn main(request: &str, command: &[String]) -> ! {
+    let error = run(request, command).unwrap_err();
+    eprintln!("zed sandbox: {error:#}");
+    std::process::exit(SETUP_FAILED_EXIT_CODE);
+}
+
+/// Applies the policy and executes the command, only returning if either fails.
+fn run(request: &str, command: &[String]) -> Result<std::convert::Infallible> {
+    let request: SandboxRequest =
+        serde_json::from_str(request).context("failed to parse sandbox policy")?;
+    // Opened before applying the policy, which might not allow writing to it.
+    let mut setup_error = OpenOptions::new()
+        .write(true)
+        .open(&request.setup_error_path)
+        .context("failed to open the sandbox's error file")?;
+    let error = apply_and_exec(&request.policy, command).unwrap_err();
+    write!(setup_error, "{error:#}").ok();
+    Err(error)
+}
+
+fn apply_and_exec(policy: &SandboxPolicy, command: &[String]) -> Result<std::convert::Infallible> {
+    let (program, args) = command.split_first().context("no command to run")?;
+
+    #[cfg(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    {
+        use std::os::unix::process::CommandExt as _;
+
+        linux::apply(policy)?;
+        let mut command = std::process::Command::new(program);
+        command.args(args);
+        if !policy.allow_network {
+            // Unix sockets are blocked along with the network, so ssh-agent can't be used.
+            command.env_remove("SSH_AUTH_SOCK");
+        }
+        let error = command.exec();
+        Err(error).with_context(|| format!("failed to run {program:?}"))
+    }
+
+    #[cfg(not(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    )))]
+    {
+        _ = (policy, program, args);
+        anyhow::bail!("sandboxing commands is only supported on Linux on x86_64 and aarch64")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_command() {
+        let policy = SandboxPolicy {
+            read_only_paths: vec![],
+            read_write_paths: vec![PathBuf::from("/home/user/project")],
+            allow_network: false,
+        };
+        let setup_error = SetupErrorFile::new().unwrap();
+        let command = wrap_command(
+            Path::new("/usr/bin/zed"),
+            &policy,
+            &setup_error,
+            "bash",
+            "echo 'hello' && ls ~/.ssh",
+        )
+        .unwrap();
+        let args = ShellKind::Posix.split(&command).unwrap();
+        assert_eq!(args[..2], ["/usr/bin/zed", "--sandbox-policy"]);
+        assert_eq!(args[3..], ["--", "bash", "-c", "echo 'hello' && ls ~/.ssh"]);
+
+        let request: SandboxRequest = serde_json::from_str(&args[2]).unwrap();
+        assert_eq!(request.policy, policy);
+        assert_eq!(request.setup_error_path, setup_error.path());
+    }
+
+    #[test]
+    fn test_setup_errors_are_written_to_file() {
+        let setup_error = SetupErrorFile::new().unwrap();
+        assert_eq!(setup_error.error(), None);
+
+        let request = serde_json::to_string(&SandboxRequest {
+            policy: SandboxPolicy::default(),
+            setup_error_path: setup_error.path().to_path_buf(),
+        })
+        .unwrap();
+        assert!(run(&request, &[]).is_err());
+        assert_eq!(setup_error.error().as_deref(), Some("no command to run"));
+    }
+
+    #[test]
+    fn test_policy_includes_system_paths() {
+        let policy = SandboxPolicy::new([], [PathBuf::from("/home/user/project")], false);
+        assert!(policy.read_only_paths.contains(&PathBuf::from("/usr")));
+        assert!(policy.read_write_paths.contains(&PathBuf::from("/tmp")));
+        assert!(
+            policy
+                .read_write_paths
+                .contains(&PathBuf::from("/dev/null"))
+        );
+        assert!(!policy.read_write_paths.contains(&PathBuf::from("/dev")));
+        assert!(
+            policy
+                .read_write_paths
+                .contains(&PathBuf::from("/home/user/project"))
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use db::{
+    query,
+    sqlez::{domain::Domain, thread_safe_connection::ThreadSafeConnection},
+    sqlez_macros::sql,
+};
+use workspace::{ItemId, WorkspaceDb, WorkspaceId};
+
+use crate::ScratchBufferId;
+
+pub struct ScratchDb(ThreadSafeConnection);
+
+impl Domain for ScratchDb {
+    const NAME: &str = stringify!(ScratchDb);
+
+    const MIGRATIONS: &[&str] = &[sql!(
+        CREATE TABLE scratch_buffers(
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            language TEXT,
+            text TEXT NOT NULL,
+            updated_at INTEGER NOT NULL
+        ) STRICT;
+
+        CREATE TABLE scratch_buffer_items(
+            workspace_id INTEGER,
+            item_id INTEGER UNIQUE,
+            scratch_buffer_id INTEGER NOT NULL,
+
+            PRIMARY KEY(workspace_id, item_id),
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE,
+            FOREIGN KEY(scratch_buffer_id) REFERENCES scratch_buffers(id)
+            ON DELETE CASCADE
+        ) STRICT;
+    )];
+}
+
+db::static_connection!(SCRATCH_DB, ScratchDb, [WorkspaceDb]);
+
+impl ScratchDb {
+    query! {
+        pub async fn create_scratch_buffer(
+            language: Option<String>,
+            text: String,
+            updated_at: i64
+        ) -> Result<Option<ScratchBufferId>> {
+            INSERT INTO scratch_buffers(language, text, updated_at)
+            VALUES (?, ?, ?)
+            RETURNING id
+        }
+    }
+
+    query! {
+        pub async fn save_scratch_buffer(
+            id: ScratchBufferId,
+            language: Option<String>,
+            text: String,
+            updated_at: i64
+        ) -> Result<()> {
+            UPDATE scratch_buffers
+            SET language = ?2, text = ?3, updated_at = ?4
+            WHERE id = ?1
+        }
+    }
+
+    query! {
+        pub fn scratch_buffer(id: ScratchBufferId) -> Result<Option<(Option<String>, String)>> {
+            SELECT language, text
+            FROM scratch_buffers
+            WHERE id = ?
+        }
+    }
+
+    query! {
+        pub fn scratch_buffers() -> Result<Vec<(ScratchBufferId, Option<String>, String, i64)>> {
+            SELECT id, language, substr(text, 1, 256), updated_at
+            FROM scratch_buffers
+            ORDER BY updated_at DESC
+        }
+    }
+
+    query! {
+        pub async fn delete_scratch_buffer(id: ScratchBufferId) -> Result<()> {
+            DELETE FROM scratch_buffers
+            WHERE id = ?
+        }
+    }
+
+    query! {
+        pub async fn save_scratch_buffer_item(
+            item_id: ItemId,
+            workspace_id: WorkspaceId,
+            scratch_buffer_id: ScratchBufferId
+        ) -> Result<()> {
+            INSERT OR REPLACE INTO scratch_buffer_items(item_id, workspace_id, scratch_buffer_id)
+            VALUES (?, ?, ?)
+        }
+    }
+
+    query! {
+        pub fn scratch_buffer_for_item(
+            item_id: ItemId,
+            workspace_id: WorkspaceId
+        ) -> Result<Option<ScratchBufferId>> {
+            SELECT scratch_buffer_id
+            FROM scratch_buffer_items
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+}
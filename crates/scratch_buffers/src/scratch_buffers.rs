@@ -0,0 +1,405 @@
+mod persistence;
+mod scratch_picker;
+mod scratch_view;
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use gpui::{
+    App, AppContext as _, Context, Entity, EventEmitter, Global, Subscription, Task, WeakEntity,
+    actions,
+};
+use language::{Buffer, BufferEvent, Language, LanguageName, LanguageRegistry};
+use persistence::SCRATCH_DB;
+use std::{sync::Arc, time::Duration};
+use time::OffsetDateTime;
+use util::ResultExt as _;
+use workspace::{AppState, Workspace};
+
+pub use scratch_picker::ScratchPicker;
+pub use scratch_view::ScratchView;
+
+actions!(
+    scratch_buffers,
+    [
+        /// Creates a new scratch buffer, which is kept across restarts without a file on disk.
+        NewScratchBuffer,
+        /// Lists the scratch buffers, to open or delete them.
+        OpenScratchBuffers
+    ]
+);
+
+const SAVE_DEBOUNCE_INTERVAL: Duration = Duration::from_millis(300);
+
+pub type ScratchBufferId = i64;
+
+pub fn init(app_state: Arc<AppState>, cx: &mut App) {
+    let scratch_store = cx.new(|cx| ScratchStore::new(app_state.languages.clone(), cx));
+    cx.set_global(GlobalScratchStore(scratch_store));
+    workspace::register_serializable_item::<ScratchView>(cx);
+
+    cx.observe_new(
+        |workspace: &mut Workspace, window, cx: &mut Context<Workspace>| {
+            workspace.register_action(|workspace, _: &NewScratchBuffer, window, cx| {
+                ScratchView::new_scratch_buffer(workspace, window, cx);
+            });
+            workspace.register_action(|workspace, _: &OpenScratchBuffers, window, cx| {
+                ScratchPicker::toggle(workspace, window, cx);
+            });
+
+            let Some(window) = window else {
+                return;
+            };
+            cx.subscribe_in(
+                &ScratchStore::global(cx),
+                window,
+                |workspace, _, event, window, cx| match event {
+                    ScratchStoreEvent::Deleted(id) => {
+                        ScratchView::close_all(*id, workspace, window, cx)
+                    }
+                },
+            )
+            .detach();
+        },
+    )
+    .detach();
+}
+
+/// A scratch buffer, as listed in the scratch picker.
+#[derive(Clone, Debug)]
+pub struct ScratchBufferEntry {
+    pub id: ScratchBufferId,
+    pub language: Option<LanguageName>,
+    /// The beginning of the buffer's text.
+    pub preview: String,
+    pub updated_at: OffsetDateTime,
+}
+
+pub enum ScratchStoreEvent {
+    /// The scratch buffer was deleted, and views of it should be closed.
+    Deleted(ScratchBufferId),
+}
+
+struct GlobalScratchStore(Entity<ScratchStore>);
+
+impl Global for GlobalScratchStore {}
+
+/// Keeps track of the scratch buffers that are open in any window, so they can be shared
+/// between windows, and saves their contents and language to the database as they change.
+pub struct ScratchStore {
+    languages: Arc<LanguageRegistry>,
+    open_buffers: HashMap<ScratchBufferId, OpenScratchBuffer>,
+    _quit_subscription: Subscription,
+}
+
+struct OpenScratchBuffer {
+    buffer: WeakEntity<Buffer>,
+    save_task: Option<Task<()>>,
+    _subscription: Subscription,
+}
+
+impl EventEmitter<ScratchStoreEvent> for ScratchStore {}
+
+impl ScratchStore {
+    pub fn global(cx: &App) -> Entity<Self> {
+        cx.global::<GlobalScratchStore>().0.clone()
+    }
+
+    fn new(languages: Arc<LanguageRegistry>, cx: &mut Context<Self>) -> Self {
+        Self {
+            languages,
+            open_buffers: HashMap::default(),
+            _quit_subscription: cx.on_app_quit(Self::save_all),
+        }
+    }
+
+    pub fn create(
+        &mut self,
+        language: Option<LanguageName>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<(ScratchBufferId, Entity<Buffer>)>> {
+        let language_name = language.as_ref().map(|language| language.to_string());
+        let updated_at = OffsetDateTime::now_utc().unix_timestamp();
+        cx.spawn(async move |this, cx| {
+            let id = SCRATCH_DB
+                .create_scratch_buffer(language_name, String::new(), updated_at)
+                .await?
+                .context("failed to create scratch buffer")?;
+            let buffer = this
+                .update(cx, |this, cx| {
+                    this.open_loaded(id, language, String::new(), cx)
+                })?
+                .await?;
+            Ok((id, buffer))
+        })
+    }
+
+    /// Opens the scratch buffer with the given id, reusing it if it's already open
+    /// in another window.
+    pub fn open(
+        &mut self,
+        id: ScratchBufferId,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Entity<Buffer>>> {
+        if let Some(buffer) = self
+            .open_buffers
+            .get(&id)
+            .and_then(|open_buffer| open_buffer.buffer.upgrade())
+        {
+            return Task::ready(Ok(buffer));
+        }
+
+        cx.spawn(async move |this, cx| {
+            let (language, text) = cx
+                .background_spawn(async move { SCRATCH_DB.scratch_buffer(id) })
+                .await?
+                .with_context(|| format!("scratch buffer {id} does not exist"))?;
+            this.update(cx, |this, cx| {
+                this.open_loaded(
+                    id,
+                    language.map(|language| LanguageName::new(&language)),
+                    text,
+                    cx,
+                )
+            })?
+            .await
+        })
+    }
+
+    fn open_loaded(
+        &mut self,
+        id: ScratchBufferId,
+        language: Option<LanguageName>,
+        text: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Entity<Buffer>>> {
+        let language = language.map(|language| self.languages.language_for_name(language.as_ref()));
+        cx.spawn(async move |this, cx| {
+            let language = match language {
+                Some(language) => language.await.log_err(),
+                None => None,
+            };
+            this.update(cx, |this, cx| this.register_buffer(id, language, text, cx))
+        })
+    }
+
+    fn register_buffer(
+        &mut self,
+        id: ScratchBufferId,
+        language: Option<Arc<Language>>,
+        text: String,
+        cx: &mut Context<Self>,
+    ) -> Entity<Buffer> {
+        // Another window may have opened the buffer while it was being loaded.
+        if let Some(buffer) = self
+            .open_buffers
+            .get(&id)
+            .and_then(|open_buffer| open_buffer.buffer.upgrade())
+        {
+            return buffer;
+        }
+
+        let languages = self.languages.clone();
+        let buffer = cx.new(|cx| {
+            let mut buffer = Buffer::local(text, cx);
+            buffer.set_language_registry(languages);
+            buffer.set_language(language, cx);
+            buffer
+        });
+        let subscription = cx.subscribe(&buffer, move |this, _, event, cx| match event {
+            BufferEvent::Edited | BufferEvent::LanguageChanged(_) => this.schedule_save(id, cx),
+            _ => {}
+        });
+        cx.observe_release(&buffer, move |this, buffer, cx| {
+            // Make sure the final edits are saved before forgetting about the buffer.
+            if let Some(open_buffer) = this.open_buffers.remove(&id)
+                && open_buffer.save_task.is_some()
+            {
+                Self::save(id, buffer, cx).detach_and_log_err(cx);
+            }
+        })
+        .detach();
+        self.open_buffers.insert(
+            id,
+            OpenScratchBuffer {
+                buffer: buffer.downgrade(),
+                save_task: None,
+                _subscription: subscription,
+            },
+        );
+        buffer
+    }
+
+    pub fn list(&self, cx: &App) -> Task<Result<Vec<ScratchBufferEntry>>> {
+        cx.background_spawn(async move {
+            SCRATCH_DB
+                .scratch_buffers()?
+                .into_iter()
+                .map(|(id, language, preview, updated_at)| {
+                    Ok(ScratchBufferEntry {
+                        id,
+                        language: language.map(|language| LanguageName::new(&language)),
+                        preview,
+                        updated_at: OffsetDateTime::from_unix_timestamp(updated_at)?,
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Deletes the scratch buffer, closing its views in every window.
+    pub fn delete(&mut self, id: ScratchBufferId, cx: &mut Context<Self>) -> Task<Result<()>> {
+        self.open_buffers.remove(&id);
+        cx.emit(ScratchStoreEvent::Deleted(id));
+        cx.background_spawn(async move { SCRATCH_DB.delete_scratch_buffer(id).await })
+    }
+
+    fn schedule_save(&mut self, id: ScratchBufferId, cx: &mut Context<Self>) {
+        let Some(open_buffer) = self.open_buffers.get_mut(&id) else {
+            return;
+        };
+        let buffer = open_buffer.buffer.clone();
+        open_buffer.save_task = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(SAVE_DEBOUNCE_INTERVAL).await;
+            let Some(save) = this
+                .update(cx, |this, cx| {
+                    if let Some(open_buffer) = this.open_buffers.get_mut(&id) {
+                        open_buffer.save_task.take();
+                    }
+                    let buffer = buffer.upgrade()?;
+                    Some(Self::save(id, buffer.read(cx), cx))
+                })
+                .ok()
+                .flatten()
+            else {
+                return;
+            };
+            save.await.log_err();
+        }));
+    }
+
+    fn save(id: ScratchBufferId, buffer: &Buffer, cx: &App) -> Task<Result<()>> {
+        let text = buffer.text();
+        let language = buffer
+            .language()
+            .map(|language| language.name().to_string());
+        let updated_at = OffsetDateTime::now_utc().unix_timestamp();
+        cx.background_spawn(async move {
+            SCRATCH_DB
+                .save_scratch_buffer(id, language, text, updated_at)
+                .await
+        })
+    }
+
+    fn save_all(&mut self, cx: &mut Context<Self>) -> impl Future<Output = ()> + use<> {
+        let saves = self
+            .open_buffers
+            .iter_mut()
+            .filter_map(|(id, open_buffer)| {
+                open_buffer.save_task.take()?;
+                let buffer = open_buffer.buffer.upgrade()?;
+                Some(Self::save(*id, buffer.read(cx), cx))
+            })
+            .collect::<Vec<_>>();
+        async move {
+            for save in saves {
+                save.await.log_err();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::Project;
+    use workspace::MultiWorkspace;
+
+    #[gpui::test]
+    async fn test_scratch_buffers_are_saved(cx: &mut TestAppContext) {
+        init_test(cx);
+        let store = cx.update(|cx| ScratchStore::global(cx));
+
+        let (id, buffer) = store
+            .update(cx, |store, cx| store.create(None, cx))
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(0..0, "fn main() {}")], None, cx)
+        });
+        cx.executor().advance_clock(SAVE_DEBOUNCE_INTERVAL);
+        cx.run_until_parked();
+        assert_eq!(
+            SCRATCH_DB.scratch_buffer(id).unwrap(),
+            Some((None, "fn main() {}".to_string()))
+        );
+        let entries = store
+            .read_with(cx, |store, cx| store.list(cx))
+            .await
+            .unwrap();
+        assert!(
+            entries
+                .iter()
+                .any(|entry| entry.id == id && entry.preview == "fn main() {}")
+        );
+
+        // The buffer is shared while it's open, and loaded from the database once it's not.
+        let reopened = store
+            .update(cx, |store, cx| store.open(id, cx))
+            .await
+            .unwrap();
+        assert_eq!(reopened, buffer);
+        drop((buffer, reopened));
+        cx.update(|_| {});
+        store.read_with(cx, |store, _| assert!(store.open_buffers.is_empty()));
+        let reloaded = store
+            .update(cx, |store, cx| store.open(id, cx))
+            .await
+            .unwrap();
+        reloaded.read_with(cx, |buffer, _| assert_eq!(buffer.text(), "fn main() {}"));
+    }
+
+    #[gpui::test]
+    async fn test_deleting_scratch_buffer_closes_its_views(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (multi_workspace, cx) =
+            cx.add_window_view(|window, cx| MultiWorkspace::test_new(project, window, cx));
+        let workspace =
+            multi_workspace.read_with(cx, |multi_workspace, _| multi_workspace.workspace().clone());
+
+        cx.dispatch_action(NewScratchBuffer);
+        cx.run_until_parked();
+        let id = workspace.read_with(cx, |workspace, cx| {
+            let views = workspace
+                .active_pane()
+                .read(cx)
+                .items_of_type::<ScratchView>()
+                .collect::<Vec<_>>();
+            assert_eq!(views.len(), 1);
+            views[0].read(cx).id()
+        });
+        assert!(SCRATCH_DB.scratch_buffer(id).unwrap().is_some());
+
+        let store = cx.update(|_, cx| ScratchStore::global(cx));
+        store
+            .update(cx, |store, cx| store.delete(id, cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        workspace.read_with(cx, |workspace, cx| {
+            assert_eq!(workspace.active_pane().read(cx).items_len(), 0);
+        });
+        assert_eq!(SCRATCH_DB.scratch_buffer(id).unwrap(), None);
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let app_state = AppState::test(cx);
+            editor::init(cx);
+            init(app_state.clone(), cx);
+            app_state
+        })
+    }
+}
@@ -0,0 +1,282 @@
+use fuzzy::{StringMatch, StringMatchCandidate, match_strings};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Render, Task,
+    WeakEntity, Window,
+};
+use picker::{Picker, PickerDelegate};
+use std::sync::Arc;
+use time::OffsetDateTime;
+use ui::{HighlightedLabel, IconButtonShape, ListItem, ListItemSpacing, Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::{ModalView, Workspace, notifications::DetachAndPromptErr as _};
+
+use crate::{NewScratchBuffer, ScratchBufferEntry, ScratchStore, ScratchView};
+
+/// A modal listing the scratch buffers, most recently edited first.
+pub struct ScratchPicker {
+    picker: Entity<Picker<ScratchPickerDelegate>>,
+}
+
+impl ScratchPicker {
+    pub fn toggle(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+        let entries = ScratchStore::global(cx).read(cx).list(cx);
+        cx.spawn_in(window, async move |workspace, cx| {
+            let entries = entries.await?;
+            workspace.update_in(cx, |workspace, window, cx| {
+                let weak_workspace = cx.weak_entity();
+                workspace.toggle_modal(window, cx, move |window, cx| {
+                    Self::new(weak_workspace, entries, window, cx)
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn new(
+        workspace: WeakEntity<Workspace>,
+        entries: Vec<ScratchBufferEntry>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate = ScratchPickerDelegate {
+            picker: cx.entity().downgrade(),
+            workspace,
+            entries,
+            matches: Vec::new(),
+            selected_index: 0,
+        };
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        Self { picker }
+    }
+}
+
+impl Render for ScratchPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("ScratchPicker")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+impl Focusable for ScratchPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for ScratchPicker {}
+impl ModalView for ScratchPicker {}
+
+pub struct ScratchPickerDelegate {
+    picker: WeakEntity<ScratchPicker>,
+    workspace: WeakEntity<Workspace>,
+    entries: Vec<ScratchBufferEntry>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl ScratchPickerDelegate {
+    fn delete_entry(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry_ix) = self.matches.get(ix).map(|mat| mat.candidate_id) else {
+            return;
+        };
+        let entry = self.entries.remove(entry_ix);
+        ScratchStore::global(cx)
+            .update(cx, |store, cx| store.delete(entry.id, cx))
+            .detach_and_prompt_err("Failed to delete scratch buffer", window, cx, |_, _, _| {
+                None
+            });
+    }
+}
+
+fn entry_title(entry: &ScratchBufferEntry) -> String {
+    entry
+        .preview
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("Empty scratch buffer")
+        .to_string()
+}
+
+impl PickerDelegate for ScratchPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search scratch buffers…".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("No scratch buffers".into())
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(id, entry)| StringMatchCandidate::new(id, &entry_title(entry)))
+            .collect::<Vec<_>>();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    true,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(cx, |this, cx| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = 0;
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self
+            .matches
+            .get(self.selected_index)
+            .map(|mat| &self.entries[mat.candidate_id])
+        else {
+            return;
+        };
+        let id = entry.id;
+        self.workspace
+            .update(cx, |workspace, cx| {
+                ScratchView::open(id, workspace, window, cx);
+            })
+            .log_err();
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let entry = self.entries.get(mat.candidate_id)?;
+        let updated_at = time_format::format_local_timestamp(
+            entry.updated_at,
+            OffsetDateTime::now_utc(),
+            time_format::TimestampFormat::Relative,
+        );
+
+        let delete_button = IconButton::new("delete-scratch-buffer", IconName::Trash)
+            .shape(IconButtonShape::Square)
+            .icon_color(Color::Muted)
+            .icon_size(IconSize::XSmall)
+            .on_click(cx.listener(move |picker, _, window, cx| {
+                cx.stop_propagation();
+                window.prevent_default();
+                picker.delegate.delete_entry(ix, window, cx);
+                picker.refresh(window, cx);
+            }))
+            .tooltip(Tooltip::text("Delete Scratch Buffer"));
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(HighlightedLabel::new(
+                            mat.string.clone(),
+                            mat.positions.clone(),
+                        ))
+                        .when_some(entry.language.clone(), |this, language| {
+                            this.child(
+                                Label::new(language.0)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                        }),
+                )
+                .end_slot(
+                    Label::new(updated_at)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .end_hover_slot(delete_button),
+        )
+    }
+
+    fn render_footer(
+        &self,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<gpui::AnyElement> {
+        Some(
+            h_flex()
+                .p_2()
+                .w_full()
+                .justify_end()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    Button::new("new-scratch-buffer", "New Scratch Buffer")
+                        .icon(IconName::Plus)
+                        .icon_position(IconPosition::Start)
+                        .icon_size(IconSize::Small)
+                        .icon_color(Color::Muted)
+                        .on_click(|_, window, cx| {
+                            window.dispatch_action(Box::new(NewScratchBuffer), cx);
+                        }),
+                )
+                .into_any_element(),
+        )
+    }
+}
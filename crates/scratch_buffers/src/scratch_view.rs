@@ -0,0 +1,318 @@
+use anyhow::{Context as _, Result};
+use editor::{Editor, EditorEvent};
+use gpui::{
+    AnyEntity, App, AppContext as _, Context, Entity, EventEmitter, FocusHandle, Focusable, Pixels,
+    Point, Render, Subscription, Task, WeakEntity, Window,
+};
+use language::Buffer;
+use project::Project;
+use std::{
+    any::{Any, TypeId},
+    sync::Arc,
+};
+use ui::prelude::*;
+use workspace::{
+    ItemId, ItemNavHistory, SaveIntent, Workspace, WorkspaceId, delete_unloaded_items,
+    item::{Item, ItemEvent, SerializableItem},
+    notifications::DetachAndPromptErr as _,
+    searchable::SearchableItemHandle,
+};
+
+use crate::{ScratchBufferId, ScratchStore, persistence::SCRATCH_DB};
+
+const MAX_TITLE_LENGTH: usize = 32;
+
+/// An editor for a scratch buffer.
+pub struct ScratchView {
+    id: ScratchBufferId,
+    editor: Entity<Editor>,
+    project: Entity<Project>,
+    _editor_subscription: Subscription,
+}
+
+impl ScratchView {
+    pub fn new(
+        id: ScratchBufferId,
+        buffer: Entity<Buffer>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let editor = cx.new(|cx| Editor::for_buffer(buffer, Some(project.clone()), window, cx));
+        let editor_subscription = cx.subscribe(&editor, |_, _, event: &EditorEvent, cx| {
+            cx.emit(event.clone())
+        });
+        Self {
+            id,
+            editor,
+            project,
+            _editor_subscription: editor_subscription,
+        }
+    }
+
+    pub fn id(&self) -> ScratchBufferId {
+        self.id
+    }
+
+    pub fn new_scratch_buffer(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let create = ScratchStore::global(cx).update(cx, |store, cx| store.create(None, cx));
+        cx.spawn_in(window, async move |workspace, cx| {
+            let (id, buffer) = create.await?;
+            workspace.update_in(cx, |workspace, window, cx| {
+                Self::open_in_workspace(id, buffer, workspace, window, cx);
+            })
+        })
+        .detach_and_prompt_err(
+            "Failed to create scratch buffer",
+            window,
+            cx,
+            |_, _, _| None,
+        );
+    }
+
+    pub fn open(
+        id: ScratchBufferId,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let open = ScratchStore::global(cx).update(cx, |store, cx| store.open(id, cx));
+        cx.spawn_in(window, async move |workspace, cx| {
+            let buffer = open.await?;
+            workspace.update_in(cx, |workspace, window, cx| {
+                Self::open_in_workspace(id, buffer, workspace, window, cx);
+            })
+        })
+        .detach_and_prompt_err("Failed to open scratch buffer", window, cx, |_, _, _| None);
+    }
+
+    fn open_in_workspace(
+        id: ScratchBufferId,
+        buffer: Entity<Buffer>,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items_of_type::<Self>()
+            .find(|view| view.read(cx).id == id);
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            return;
+        }
+
+        let project = workspace.project().clone();
+        let view = cx.new(|cx| Self::new(id, buffer, project, window, cx));
+        workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+    }
+
+    /// Closes the views of the scratch buffer in all of the workspace's panes.
+    pub(crate) fn close_all(
+        id: ScratchBufferId,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        for pane in workspace.panes().to_vec() {
+            let item_ids = pane
+                .read(cx)
+                .items_of_type::<Self>()
+                .filter(|view| view.read(cx).id == id)
+                .map(|view| view.entity_id())
+                .collect::<Vec<_>>();
+            if item_ids.is_empty() {
+                continue;
+            }
+            pane.update(cx, |pane, cx| {
+                pane.close_items(window, cx, SaveIntent::Skip, &|item_id| {
+                    item_ids.contains(&item_id)
+                })
+                .detach_and_log_err(cx);
+            });
+        }
+    }
+
+    fn title(&self, cx: &App) -> SharedString {
+        let text = self.editor.read(cx).text(cx);
+        let Some(first_line) = text.lines().map(str::trim).find(|line| !line.is_empty()) else {
+            return "Scratch".into();
+        };
+        util::truncate_and_trailoff(first_line, MAX_TITLE_LENGTH).into()
+    }
+}
+
+impl EventEmitter<EditorEvent> for ScratchView {}
+
+impl Render for ScratchView {
+    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        div().size_full().child(self.editor.clone())
+    }
+}
+
+impl Focusable for ScratchView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.read(cx).focus_handle(cx)
+    }
+}
+
+impl Item for ScratchView {
+    type Event = EditorEvent;
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyEntity> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.clone().into())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.clone().into())
+        } else {
+            None
+        }
+    }
+
+    fn tab_icon(&self, _: &Window, _: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Notepad))
+    }
+
+    fn tab_content_text(&self, _detail: usize, cx: &App) -> SharedString {
+        self.title(cx)
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some("Scratch buffer".into())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn can_split(&self) -> bool {
+        true
+    }
+
+    fn clone_on_split(
+        &self,
+        _: Option<WorkspaceId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Option<Entity<Self>>> {
+        let buffer = self.editor.read(cx).buffer().read(cx).as_singleton();
+        Task::ready(buffer.map(|buffer| {
+            cx.new(|cx| Self::new(self.id, buffer, self.project.clone(), window, cx))
+        }))
+    }
+
+    fn navigate(
+        &mut self,
+        data: Arc<dyn Any + Send>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.navigate(data, window, cx))
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx))
+    }
+
+    fn set_nav_history(
+        &mut self,
+        history: ItemNavHistory,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            Item::set_nav_history(editor, history, window, cx)
+        })
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>, _: &App) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn show_toolbar(&self) -> bool {
+        true
+    }
+
+    fn pixel_position_of_cursor(&self, cx: &App) -> Option<Point<Pixels>> {
+        self.editor.read(cx).pixel_position_of_cursor(cx)
+    }
+
+    fn to_item_events(event: &EditorEvent, f: &mut dyn FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+}
+
+impl SerializableItem for ScratchView {
+    fn serialized_item_kind() -> &'static str {
+        "ScratchView"
+    }
+
+    fn cleanup(
+        workspace_id: WorkspaceId,
+        alive_items: Vec<ItemId>,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<()>> {
+        delete_unloaded_items(
+            alive_items,
+            workspace_id,
+            "scratch_buffer_items",
+            &SCRATCH_DB,
+            cx,
+        )
+    }
+
+    fn deserialize(
+        project: Entity<Project>,
+        _workspace: WeakEntity<Workspace>,
+        workspace_id: WorkspaceId,
+        item_id: ItemId,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        window.spawn(cx, async move |cx| {
+            let id = SCRATCH_DB
+                .scratch_buffer_for_item(item_id, workspace_id)?
+                .context("no scratch buffer found for item")?;
+            let buffer = cx
+                .update(|_, cx| {
+                    ScratchStore::global(cx).update(cx, |store, cx| store.open(id, cx))
+                })?
+                .await?;
+            cx.update(|window, cx| cx.new(|cx| Self::new(id, buffer, project, window, cx)))
+        })
+    }
+
+    fn serialize(
+        &mut self,
+        workspace: &mut Workspace,
+        item_id: ItemId,
+        _closing: bool,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Task<Result<()>>> {
+        let workspace_id = workspace.database_id()?;
+        let id = self.id;
+        Some(cx.background_spawn(async move {
+            SCRATCH_DB
+                .save_scratch_buffer_item(item_id, workspace_id, id)
+                .await
+        }))
+    }
+
+    fn should_serialize(&self, _event: &Self::Event) -> bool {
+        false
+    }
+}
@@ -11,10 +11,10 @@ use crate::{
 use anyhow::Context as _;
 use collections::HashMap;
 use editor::{
-    Anchor, Editor, EditorEvent, EditorSettings, MAX_TAB_TITLE_LEN, MultiBuffer, PathKey,
-    SelectionEffects,
+    Anchor, Editor, EditorEvent, EditorSettings, ExcerptId, MAX_TAB_TITLE_LEN, MultiBuffer,
+    PathKey, SelectionEffects,
     actions::{Backtab, FoldAll, SelectAll, Tab, UnfoldAll},
-    items::active_match_index,
+    items::{active_match_index, deserialize_anchor, serialize_anchor},
     multibuffer_context_lines,
     scroll::Autoscroll,
 };
@@ -32,6 +32,7 @@ use project::{
     search::{SearchInputKind, SearchQuery},
     search_history::SearchHistoryCursor,
 };
+use rpc::proto;
 use settings::Settings;
 use std::{
     any::{Any, TypeId},
@@ -46,9 +47,9 @@ use ui::{
 };
 use util::{ResultExt as _, paths::PathMatcher, rel_path::RelPath};
 use workspace::{
-    DeploySearch, ItemNavHistory, NewSearch, ToolbarItemEvent, ToolbarItemLocation,
-    ToolbarItemView, Workspace, WorkspaceId,
-    item::{Item, ItemEvent, ItemHandle, SaveOptions},
+    CollaboratorId, DeploySearch, ItemNavHistory, NewSearch, ToolbarItemEvent, ToolbarItemLocation,
+    ToolbarItemView, ViewId, Workspace, WorkspaceId,
+    item::{Dedup, FollowEvent, FollowableItem, Item, ItemEvent, ItemHandle, SaveOptions},
     searchable::{Direction, SearchEvent, SearchToken, SearchableItem, SearchableItemHandle},
 };
 
@@ -101,6 +102,7 @@ impl Global for ActiveSettings {}
 
 pub fn init(cx: &mut App) {
     cx.set_global(ActiveSettings::default());
+    workspace::FollowableViewRegistry::register::<ProjectSearchView>(cx);
     cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
         register_workspace_action(workspace, move |search_bar, _: &Deploy, window, cx| {
             search_bar.focus_search(window, cx);
@@ -251,6 +253,7 @@ enum InputPanel {
 
 pub struct ProjectSearchView {
     workspace: WeakEntity<Workspace>,
+    remote_id: Option<ViewId>,
     focus_handle: FocusHandle,
     entity: Entity<ProjectSearch>,
     query_editor: Entity<Editor>,
@@ -445,6 +448,7 @@ pub enum ViewEvent {
     UpdateTab,
     Activate,
     EditorEvent(editor::EditorEvent),
+    MatchesChanged,
     Dismiss,
 }
 
@@ -693,6 +697,231 @@ impl Item for ProjectSearchView {
     }
 }
 
+impl FollowableItem for ProjectSearchView {
+    fn remote_id(&self) -> Option<ViewId> {
+        self.remote_id
+    }
+
+    fn to_state_proto(&self, window: &mut Window, cx: &mut App) -> Option<proto::view::Variant> {
+        let editor = self
+            .results_editor
+            .update(cx, |editor, cx| editor.to_state_proto(window, cx));
+        let Some(proto::view::Variant::Editor(editor)) = editor else {
+            return None;
+        };
+        let search = self.entity.read(cx);
+        Some(proto::view::Variant::ProjectSearch(
+            proto::view::ProjectSearch {
+                query: search.last_search_query_text.clone(),
+                editor: Some(editor),
+                matches: matches_to_proto(search, cx),
+            },
+        ))
+    }
+
+    fn from_state_proto(
+        workspace: Entity<Workspace>,
+        remote_id: ViewId,
+        state: &mut Option<proto::view::Variant>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<Task<anyhow::Result<Entity<Self>>>> {
+        let Some(proto::view::Variant::ProjectSearch(_)) = state else {
+            return None;
+        };
+        let Some(proto::view::Variant::ProjectSearch(state)) = state.take() else {
+            unreachable!()
+        };
+
+        let project = workspace.read(cx).project().clone();
+        let search = cx.new(|cx| ProjectSearch::new(project.clone(), cx));
+        let view = cx.new(|cx| {
+            let mut view = ProjectSearchView::new(workspace.downgrade(), search, window, cx, None);
+            view.remote_id = Some(remote_id);
+            view
+        });
+
+        // Mirror the leader's results by inserting its excerpts in order, keeping their ids so
+        // that subsequent updates can refer to them.
+        let editor = state.editor.unwrap_or_default();
+        let mut previous_excerpt_id = ExcerptId::min().to_proto();
+        let inserted_excerpts = editor
+            .excerpts
+            .into_iter()
+            .map(|excerpt| {
+                let excerpt_id = excerpt.id;
+                let insertion = proto::ExcerptInsertion {
+                    excerpt: Some(excerpt),
+                    previous_excerpt_id: Some(previous_excerpt_id),
+                };
+                previous_excerpt_id = excerpt_id;
+                insertion
+            })
+            .collect();
+        let update =
+            proto::update_view::Variant::ProjectSearch(proto::update_view::ProjectSearch {
+                query: state.query,
+                editor: Some(proto::update_view::Editor {
+                    inserted_excerpts,
+                    selections: editor.selections,
+                    pending_selection: editor.pending_selection,
+                    scroll_top_anchor: editor.scroll_top_anchor,
+                    scroll_x: editor.scroll_x,
+                    scroll_y: editor.scroll_y,
+                    ..Default::default()
+                }),
+                matches: state.matches,
+                matches_changed: true,
+            });
+        let apply_update = view.update(cx, |view, cx| {
+            view.apply_update_proto(&project, update, window, cx)
+        });
+
+        Some(window.spawn(cx, async move |_| {
+            apply_update.await?;
+            Ok(view)
+        }))
+    }
+
+    fn to_follow_event(event: &Self::Event) -> Option<FollowEvent> {
+        match event {
+            ViewEvent::EditorEvent(event) => Editor::to_follow_event(event),
+            _ => None,
+        }
+    }
+
+    fn add_event_to_update_proto(
+        &self,
+        event: &Self::Event,
+        update: &mut Option<proto::update_view::Variant>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> bool {
+        let update = update
+            .get_or_insert_with(|| proto::update_view::Variant::ProjectSearch(Default::default()));
+        let proto::update_view::Variant::ProjectSearch(update) = update else {
+            return false;
+        };
+
+        match event {
+            ViewEvent::EditorEvent(event) => {
+                let mut editor_update = update
+                    .editor
+                    .take()
+                    .map(proto::update_view::Variant::Editor);
+                let changed = self.results_editor.update(cx, |editor, cx| {
+                    editor.add_event_to_update_proto(event, &mut editor_update, window, cx)
+                });
+                if let Some(proto::update_view::Variant::Editor(editor_update)) = editor_update {
+                    update.editor = Some(editor_update);
+                }
+                changed
+            }
+            ViewEvent::MatchesChanged => {
+                let search = self.entity.read(cx);
+                update.query = search.last_search_query_text.clone();
+                update.matches = matches_to_proto(search, cx);
+                update.matches_changed = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn apply_update_proto(
+        &mut self,
+        project: &Entity<Project>,
+        message: proto::update_view::Variant,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        let proto::update_view::Variant::ProjectSearch(message) = message else {
+            return Task::ready(Ok(()));
+        };
+
+        if let Some(query) = message.query {
+            self.entity.update(cx, |search, _| {
+                search.last_search_query_text = Some(query);
+            });
+            cx.emit(ViewEvent::UpdateTab);
+        }
+        let apply_editor_update = message.editor.map(|update| {
+            self.results_editor.update(cx, |editor, cx| {
+                editor.apply_update_proto(
+                    project,
+                    proto::update_view::Variant::Editor(update),
+                    window,
+                    cx,
+                )
+            })
+        });
+        let match_ranges = message.matches_changed.then(|| {
+            message
+                .matches
+                .into_iter()
+                .filter_map(|range| {
+                    Some(deserialize_anchor(range.start?)?..deserialize_anchor(range.end?)?)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let search = self.entity.clone();
+        cx.spawn(async move |_, cx| {
+            if let Some(apply_editor_update) = apply_editor_update {
+                apply_editor_update.await?;
+            }
+            if let Some(match_ranges) = match_ranges {
+                let excerpts = search.read_with(cx, |search, _| search.excerpts.clone());
+                excerpts
+                    .update(cx, |excerpts, cx| {
+                        excerpts.wait_for_anchors(
+                            match_ranges
+                                .iter()
+                                .flat_map(|range| [range.start, range.end]),
+                            cx,
+                        )
+                    })
+                    .await?;
+                search.update(cx, |search, cx| {
+                    search.match_ranges = match_ranges;
+                    cx.notify();
+                });
+            }
+            Ok(())
+        })
+    }
+
+    fn is_project_item(&self, _window: &Window, _cx: &App) -> bool {
+        true
+    }
+
+    fn set_leader_id(
+        &mut self,
+        leader_id: Option<CollaboratorId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.results_editor
+            .update(cx, |editor, cx| editor.set_leader_id(leader_id, window, cx));
+    }
+
+    fn dedup(&self, _existing: &Self, _window: &Window, _cx: &App) -> Option<Dedup> {
+        None
+    }
+}
+
+fn matches_to_proto(search: &ProjectSearch, cx: &App) -> Vec<proto::ProjectSearchMatch> {
+    let snapshot = search.excerpts.read(cx).snapshot(cx);
+    search
+        .match_ranges
+        .iter()
+        .map(|range| proto::ProjectSearchMatch {
+            start: Some(serialize_anchor(&range.start, &snapshot)),
+            end: Some(serialize_anchor(&range.end, &snapshot)),
+        })
+        .collect()
+}
+
 impl ProjectSearchView {
     pub fn get_matches(&self, cx: &App) -> Vec<Range<Anchor>> {
         self.entity.read(cx).match_ranges.clone()
@@ -968,6 +1197,7 @@ impl ProjectSearchView {
         // Check if Worktrees have all been previously indexed
         let mut this = ProjectSearchView {
             workspace,
+            remote_id: None,
             focus_handle,
             replacement_editor,
             search_id: entity.read(cx).search_id,
@@ -1519,6 +1749,7 @@ impl ProjectSearchView {
             }
         }
 
+        cx.emit(ViewEvent::MatchesChanged);
         cx.emit(ViewEvent::UpdateTab);
         cx.notify();
     }
@@ -122,6 +122,36 @@ pub struct AgentSettingsContent {
     /// `always_confirm`) match against the tool's text input (command, path,
     /// URL, etc.).
     pub tool_permissions: Option<ToolPermissionsContent>,
+    /// Shell commands to run around the agent's activity, such as formatting
+    /// files after they're edited or blocking edits to generated files.
+    ///
+    /// Each hook receives a JSON payload describing the event on stdin. A hook
+    /// can print a JSON object to stdout with any of these fields:
+    /// - `decision`: `"block"` to veto a tool call (`pre_tool_call` only)
+    /// - `reason`: why the tool call was blocked, shown to the model
+    /// - `input`: a replacement input for the tool call (`pre_tool_call` only)
+    /// - `message`: feedback to add to the tool's result
+    ///
+    /// Any other output is treated as a message. A hook that exits with a
+    /// non-zero status blocks a `pre_tool_call`, and otherwise has its output
+    /// added to the tool's result.
+    pub hooks: Option<AgentHooksContent>,
+    /// Restricts what the commands run by the agent's terminal tool can access.
+    /// Only supported for local projects on Linux.
+    ///
+    /// Profiles can override these settings.
+    pub terminal_sandbox: Option<TerminalSandboxContent>,
+    /// Prices used to estimate the cost of language model requests. The price
+    /// of a request is taken from the last entry in this list that matches the
+    /// model's provider and name. In each entry, both provider and model are
+    /// optional, so that you can set prices for either one.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub model_pricing: Vec<LanguageModelPricing>,
+    /// Soft limits on the estimated cost of language model requests. Once a
+    /// limit is reached, a warning is shown before making another request.
+    pub usage_budget: Option<UsageBudgetContent>,
 }
 
 impl AgentSettingsContent {
@@ -208,6 +238,12 @@ pub struct AgentProfileContent {
     pub context_servers: IndexMap<Arc<str>, ContextServerPresetContent>,
     /// The default language model selected when using this profile.
     pub default_model: Option<LanguageModelSelection>,
+    /// Overrides the terminal sandbox settings when using this profile. Paths are
+    /// added to the ones from `agent.terminal_sandbox`.
+    pub terminal_sandbox: Option<TerminalSandboxContent>,
+    /// Tools that run a shell command, keyed by the tool's name.
+    #[serde(default)]
+    pub command_tools: IndexMap<Arc<str>, CommandToolContent>,
 }
 
 #[with_fallible_options]
@@ -216,6 +252,34 @@ pub struct ContextServerPresetContent {
     pub tools: IndexMap<Arc<str>, bool>,
 }
 
+/// A tool that runs a shell command and returns its output to the agent.
+#[with_fallible_options]
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct CommandToolContent {
+    /// What the tool does and when the agent should use it.
+    #[serde(default)]
+    pub description: String,
+    /// A JSON schema describing the tool's parameters.
+    ///
+    /// Default: an object with no properties
+    pub parameters: Option<serde_json::Value>,
+    /// The command to run. `{name}` is replaced with the shell-quoted value of the
+    /// parameter called `name`, or removed if that parameter is left out.
+    #[serde(default)]
+    pub command: String,
+    /// The directory to run the command in, either absolute or relative to the
+    /// project's first worktree.
+    ///
+    /// Default: the project's first worktree
+    pub cwd: Option<PathBuf>,
+    /// Environment variables to set for the command.
+    pub env: Option<IndexMap<String, String>>,
+    /// How long to wait for the command to finish, in seconds.
+    ///
+    /// Default: no timeout
+    pub timeout: Option<u64>,
+}
+
 #[derive(Copy, Clone, Default, Debug, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
 #[serde(rename_all = "snake_case")]
 pub enum DefaultAgentView {
@@ -264,6 +328,38 @@ pub struct LanguageModelParameters {
     pub temperature: Option<f32>,
 }
 
+#[with_fallible_options]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq)]
+pub struct LanguageModelPricing {
+    pub provider: Option<LanguageModelProviderSetting>,
+    pub model: Option<String>,
+    /// The price of one million input tokens, in US dollars.
+    pub input: Option<f64>,
+    /// The price of one million output tokens, in US dollars.
+    pub output: Option<f64>,
+    /// The price of one million input tokens written to the prompt cache, in
+    /// US dollars. Defaults to the input price.
+    pub cache_creation_input: Option<f64>,
+    /// The price of one million input tokens read from the prompt cache, in
+    /// US dollars. Defaults to the input price.
+    pub cache_read_input: Option<f64>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct UsageBudgetContent {
+    /// The estimated cost, in US dollars, of the requests that can be made
+    /// each day before a warning is shown.
+    ///
+    /// Default: null
+    pub daily: Option<f64>,
+    /// The estimated cost, in US dollars, of the requests that can be made
+    /// each calendar month before a warning is shown.
+    ///
+    /// Default: null
+    pub monthly: Option<f64>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, MergeFrom)]
 pub struct LanguageModelProviderSetting(pub String);
 
@@ -513,6 +609,68 @@ pub enum CustomAgentServerSettings {
     },
 }
 
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct TerminalSandboxContent {
+    /// Whether to run the terminal tool's commands in a sandbox, which can only
+    /// write to the project's worktrees and read system directories.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// Whether sandboxed commands can access the network.
+    ///
+    /// Default: false
+    pub allow_network: Option<bool>,
+    /// Additional directories sandboxed commands can read, such as `~/.cargo`.
+    ///
+    /// Default: []
+    pub read_only_paths: Option<Vec<PathBuf>>,
+    /// Additional directories sandboxed commands can read and write.
+    ///
+    /// Default: []
+    pub read_write_paths: Option<Vec<PathBuf>>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct AgentHooksContent {
+    /// Hooks to run before a tool is called, which can block the call or
+    /// rewrite its input. `matcher` is matched against the tool's name.
+    ///
+    /// Default: []
+    pub pre_tool_call: Option<Vec<AgentHookContent>>,
+    /// Hooks to run after a tool call completes. `matcher` is matched against
+    /// the tool's name.
+    ///
+    /// Default: []
+    pub post_tool_call: Option<Vec<AgentHookContent>>,
+    /// Hooks to run after the agent edits a file. `matcher` is matched against
+    /// the file's absolute path.
+    ///
+    /// Default: []
+    pub post_edit: Option<Vec<AgentHookContent>>,
+    /// Hooks to run when the agent finishes its turn. A hook that blocks has
+    /// its reason sent to the model, which continues the turn.
+    ///
+    /// Default: []
+    pub turn_complete: Option<Vec<AgentHookContent>>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct AgentHookContent {
+    /// The shell command to run.
+    #[serde(default)]
+    pub command: String,
+    /// A regex restricting which tools or files the hook runs for. When unset,
+    /// the hook runs for all of them.
+    pub matcher: Option<String>,
+    /// How long to wait for the command to finish, in seconds.
+    ///
+    /// Default: 60
+    pub timeout: Option<u64>,
+}
+
 #[with_fallible_options]
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
 pub struct ToolPermissionsContent {
@@ -489,6 +489,11 @@ pub struct DebuggerSettingsContent {
     ///
     /// Default: true
     pub format_dap_log_messages: Option<bool>,
+    /// Whether to record the messages of each debug session to a trace file,
+    /// which can be inspected with `dev: open debug adapter recording`.
+    ///
+    /// Default: false
+    pub record_sessions: Option<bool>,
     /// The dock position of the debug panel
     ///
     /// Default: Bottom
@@ -1057,6 +1062,8 @@ pub struct RemoteSettingsContent {
     pub dev_container_connections: Option<Vec<DevContainerConnection>>,
     pub read_ssh_config: Option<bool>,
     pub use_podman: Option<bool>,
+    /// Labels to show for ports in the ports panel of remote projects, by port.
+    pub remote_port_labels: Option<collections::BTreeMap<u16, String>>,
 }
 
 #[with_fallible_options]
@@ -5427,7 +5427,7 @@ fn panels_page() -> SettingsPage {
 }
 
 fn debugger_page() -> SettingsPage {
-    fn general_section() -> [SettingsPageItem; 6] {
+    fn general_section() -> [SettingsPageItem; 7] {
         [
             SettingsPageItem::SectionHeader("General"),
             SettingsPageItem::SettingItem(SettingItem {
@@ -5531,6 +5531,28 @@ fn debugger_page() -> SettingsPage {
                 metadata: None,
                 files: USER,
             }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Record Sessions",
+                description: "Whether to record the messages of each debug session to a trace file.",
+                field: Box::new(SettingField {
+                    json_path: Some("debugger.record_sessions"),
+                    pick: |settings_content| {
+                        settings_content
+                            .debugger
+                            .as_ref()?
+                            .record_sessions
+                            .as_ref()
+                    },
+                    write: |settings_content, value| {
+                        settings_content
+                            .debugger
+                            .get_or_insert_default()
+                            .record_sessions = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
         ]
     }
 
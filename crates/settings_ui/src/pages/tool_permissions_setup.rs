@@ -1399,17 +1399,29 @@ mod tests {
         //   2. Add it to this list with a comment explaining why it's excluded.
         const EXCLUDED_TOOLS: &[&str] = &[
             // Read-only / low-risk tools that don't call decide_permission_from_settings
+            "code_actions",
             "diagnostics",
             "find_path",
+            "find_references",
+            "go_to_definition",
             "grep",
             "list_directory",
+            "list_tasks",
             "now",
             "open",
             "read_file",
             "thinking",
+            "workspace_symbols",
             // streaming_edit_file uses "edit_file" for permission lookups,
             // so its rules are configured under the edit_file entry.
             "streaming_edit_file",
+            // rename_symbol and apply_code_action edit files through language
+            // servers, so their rules are also configured under edit_file.
+            "rename_symbol",
+            "apply_code_action",
+            // run_task runs the task's command in a terminal, so its rules are
+            // configured under terminal.
+            "run_task",
             // Subagent permission checks happen at the level of individual
             // tool calls within the subagent, not at the spawning level.
             "spawn_agent",
@@ -4,6 +4,7 @@ pub use alacritty_terminal;
 
 mod pty_info;
 mod terminal_hyperlinks;
+mod terminal_screen;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -47,6 +48,7 @@ use settings::Settings;
 use smol::channel::{Receiver, Sender};
 use task::{HideStrategy, Shell, SpawnInTerminal};
 use terminal_hyperlinks::RegexSearches;
+pub use terminal_screen::{TerminalScreen, TerminalScreenUpdate};
 use terminal_settings::{AlternateScroll, CursorShape, TerminalSettings};
 use theme::{ActiveTheme, Theme};
 use urlencoding;
@@ -412,6 +414,7 @@ impl TerminalBuilder {
                 window_id,
             },
             child_exited: None,
+            forwarded_input_tx: None,
             event_loop_task: Task::ready(Ok(())),
             background_executor: background_executor.clone(),
             path_style,
@@ -643,6 +646,7 @@ impl TerminalBuilder {
                     window_id,
                 },
                 child_exited: None,
+                forwarded_input_tx: None,
                 event_loop_task: Task::ready(Ok(())),
                 background_executor,
                 path_style,
@@ -867,6 +871,8 @@ pub struct Terminal {
     template: CopyTemplate,
     activation_script: Vec<String>,
     child_exited: Option<ExitStatus>,
+    /// Where input is sent for display-only terminals mirroring a terminal elsewhere.
+    forwarded_input_tx: Option<UnboundedSender<Vec<u8>>>,
     event_loop_task: Task<Result<(), anyhow::Error>>,
     background_executor: BackgroundExecutor,
     path_style: PathStyle,
@@ -1283,6 +1289,25 @@ impl Terminal {
         self.term.lock().set_options(self.term_config.clone());
     }
 
+    /// Returns a snapshot of the visible screen, which can be shown in another terminal
+    /// with [`Terminal::show_screen`].
+    pub fn screen(&self) -> TerminalScreen {
+        TerminalScreen::from_term(&self.term.lock_unfair())
+    }
+
+    /// Replaces the contents of a display-only terminal with the given screen.
+    pub fn show_screen(&mut self, screen: &TerminalScreen, cx: &mut Context<Self>) {
+        self.write_output(&screen.to_output(), cx);
+    }
+
+    /// Stops sending input to this terminal's process, and returns a receiver for it
+    /// instead. Used for terminals mirroring a terminal running elsewhere.
+    pub fn forward_input(&mut self) -> UnboundedReceiver<Vec<u8>> {
+        let (tx, rx) = unbounded();
+        self.forwarded_input_tx = Some(tx);
+        rx
+    }
+
     pub fn write_output(&mut self, bytes: &[u8], cx: &mut Context<Self>) {
         // Inject bytes directly into the terminal emulator and refresh the UI.
         // This bypasses the PTY/event loop for display-only terminals.
@@ -1433,7 +1458,11 @@ impl Terminal {
     /// Write the Input payload to the PTY, if applicable.
     /// (This is a no-op for display-only terminals.)
     fn write_to_pty(&self, input: impl Into<Cow<'static, [u8]>>) {
-        if let TerminalType::Pty { pty_tx, .. } = &self.terminal_type {
+        if let Some(forwarded_input_tx) = &self.forwarded_input_tx {
+            forwarded_input_tx
+                .unbounded_send(input.into().into_owned())
+                .ok();
+        } else if let TerminalType::Pty { pty_tx, .. } = &self.terminal_type {
             let input = input.into();
             if log::log_enabled!(log::Level::Debug) {
                 if let Ok(str) = str::from_utf8(&input) {
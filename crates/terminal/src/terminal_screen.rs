@@ -0,0 +1,268 @@
+use std::fmt::Write as _;
+
+use alacritty_terminal::{
+    Term,
+    grid::Dimensions as _,
+    index::{Column, Line},
+    term::cell::{Cell, Flags},
+    vte::ansi::{Color, NamedColor},
+};
+
+/// A snapshot of the visible part of a terminal, used to mirror it in a display-only
+/// terminal, e.g. for collaborators following a shared terminal.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TerminalScreen {
+    /// The visible lines, with SGR escape sequences for their colors and styles.
+    pub lines: Vec<String>,
+    pub cursor_line: u32,
+    pub cursor_column: u32,
+}
+
+/// The changes between two [`TerminalScreen`]s, so that only the lines that changed need
+/// to be sent to the terminal's followers.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TerminalScreenUpdate {
+    pub line_count: u32,
+    /// The lines that changed, by their index on the screen.
+    pub changed_lines: Vec<(u32, String)>,
+    pub cursor_line: u32,
+    pub cursor_column: u32,
+}
+
+impl TerminalScreenUpdate {
+    /// Combines this update with one that follows it.
+    pub fn merge(&mut self, newer: TerminalScreenUpdate) {
+        self.changed_lines.retain(|(ix, _)| {
+            *ix < newer.line_count
+                && !newer
+                    .changed_lines
+                    .iter()
+                    .any(|(newer_ix, _)| newer_ix == ix)
+        });
+        self.changed_lines.extend(newer.changed_lines);
+        self.changed_lines.sort_by_key(|(ix, _)| *ix);
+        self.line_count = newer.line_count;
+        self.cursor_line = newer.cursor_line;
+        self.cursor_column = newer.cursor_column;
+    }
+}
+
+const STYLE_FLAGS: Flags = Flags::BOLD
+    .union(Flags::DIM)
+    .union(Flags::ITALIC)
+    .union(Flags::UNDERLINE)
+    .union(Flags::INVERSE)
+    .union(Flags::STRIKEOUT);
+
+impl TerminalScreen {
+    pub(crate) fn from_term<T>(term: &Term<T>) -> Self {
+        let grid = term.grid();
+        let display_offset = grid.display_offset() as i32;
+        let lines = (0..grid.screen_lines() as i32)
+            .map(|line| {
+                let row = &grid[Line(line - display_offset)];
+                let cells = (0..grid.columns())
+                    .map(|column| &row[Column(column)])
+                    .collect::<Vec<_>>();
+                encode_line(&cells)
+            })
+            .collect();
+        let cursor = grid.cursor.point;
+        Self {
+            lines,
+            cursor_line: (cursor.line.0 + display_offset).max(0) as u32,
+            cursor_column: cursor.column.0 as u32,
+        }
+    }
+
+    /// Returns the changes from this screen to `new`, or `None` if they're the same.
+    pub fn diff(&self, new: &TerminalScreen) -> Option<TerminalScreenUpdate> {
+        if self == new {
+            return None;
+        }
+        let changed_lines = new
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(ix, line)| self.lines.get(*ix) != Some(*line))
+            .map(|(ix, line)| (ix as u32, line.clone()))
+            .collect();
+        Some(TerminalScreenUpdate {
+            line_count: new.lines.len() as u32,
+            changed_lines,
+            cursor_line: new.cursor_line,
+            cursor_column: new.cursor_column,
+        })
+    }
+
+    pub fn apply(&mut self, update: &TerminalScreenUpdate) {
+        self.lines.resize(update.line_count as usize, String::new());
+        for (ix, line) in &update.changed_lines {
+            if let Some(existing_line) = self.lines.get_mut(*ix as usize) {
+                existing_line.clone_from(line);
+            }
+        }
+        self.cursor_line = update.cursor_line;
+        self.cursor_column = update.cursor_column;
+    }
+
+    /// Returns the bytes that redraw this screen when written to a terminal.
+    pub(crate) fn to_output(&self) -> Vec<u8> {
+        let mut output = String::from("\x1b[0m\x1b[H\x1b[2J\x1b[3J");
+        output.push_str(&self.lines.join("\n"));
+        write!(
+            output,
+            "\x1b[{};{}H",
+            self.cursor_line + 1,
+            self.cursor_column + 1
+        )
+        .ok();
+        output.into_bytes()
+    }
+}
+
+fn encode_line(cells: &[&Cell]) -> String {
+    let is_blank = |cell: &Cell| {
+        cell.c == ' ' && is_default_color(cell.bg) && !cell.flags.intersects(STYLE_FLAGS)
+    };
+    let len = cells
+        .iter()
+        .rposition(|cell| !is_blank(cell))
+        .map_or(0, |ix| ix + 1);
+
+    let mut line = String::new();
+    let mut current_style = None;
+    for cell in &cells[..len] {
+        if cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+            continue;
+        }
+        let style = (cell.fg, cell.bg, cell.flags & STYLE_FLAGS);
+        if current_style != Some(style) {
+            line.push_str(&sgr_sequence(cell));
+            current_style = Some(style);
+        }
+        line.push(cell.c);
+        if let Some(zerowidth) = cell.zerowidth() {
+            line.extend(zerowidth);
+        }
+    }
+    if current_style.is_some() {
+        line.push_str("\x1b[0m");
+    }
+    line
+}
+
+fn sgr_sequence(cell: &Cell) -> String {
+    let mut sgr = String::from("\x1b[0");
+    for (flag, code) in [
+        (Flags::BOLD, 1),
+        (Flags::DIM, 2),
+        (Flags::ITALIC, 3),
+        (Flags::UNDERLINE, 4),
+        (Flags::INVERSE, 7),
+        (Flags::STRIKEOUT, 9),
+    ] {
+        if cell.flags.contains(flag) {
+            write!(sgr, ";{code}").ok();
+        }
+    }
+    write_color(&mut sgr, cell.fg, 30);
+    write_color(&mut sgr, cell.bg, 40);
+    sgr.push('m');
+    sgr
+}
+
+/// Appends the SGR parameters for `color`, where `base` is 30 for the foreground and
+/// 40 for the background.
+fn write_color(sgr: &mut String, color: Color, base: u8) {
+    match color {
+        Color::Named(named) => {
+            let ix = named as usize;
+            if ix < 8 {
+                write!(sgr, ";{}", base as usize + ix).ok();
+            } else if ix < 16 {
+                write!(sgr, ";{}", base as usize + 60 + ix - 8).ok();
+            }
+        }
+        Color::Indexed(ix) => {
+            write!(sgr, ";{};5;{ix}", base + 8).ok();
+        }
+        Color::Spec(rgb) => {
+            write!(sgr, ";{};2;{};{};{}", base + 8, rgb.r, rgb.g, rgb.b).ok();
+        }
+    }
+}
+
+fn is_default_color(color: Color) -> bool {
+    matches!(
+        color,
+        Color::Named(NamedColor::Background | NamedColor::Foreground)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_line_styles_and_trailing_blanks() {
+        let cell = |c| {
+            let mut cell = Cell::default();
+            cell.c = c;
+            cell
+        };
+        let mut bold_red = cell('a');
+        bold_red.fg = Color::Named(NamedColor::Red);
+        bold_red.flags.insert(Flags::BOLD);
+        let plain = cell('b');
+        let blank = cell(' ');
+
+        assert_eq!(
+            encode_line(&[&bold_red, &plain, &blank, &blank]),
+            "\x1b[0;1;31ma\x1b[0mb\x1b[0m"
+        );
+        assert_eq!(encode_line(&[&blank, &blank]), "");
+    }
+
+    #[test]
+    fn test_diff_and_apply() {
+        let screen = |lines: &[&str], cursor_line| TerminalScreen {
+            lines: lines.iter().map(ToString::to_string).collect(),
+            cursor_line,
+            cursor_column: 0,
+        };
+        let old = screen(&["$ ls", "a.txt", ""], 2);
+        assert_eq!(old.diff(&old), None);
+
+        let new = screen(&["$ ls", "a.txt", "$ pwd"], 2);
+        let update = old.diff(&new).unwrap();
+        assert_eq!(update.changed_lines, vec![(2, "$ pwd".to_string())]);
+        let mut follower = old.clone();
+        follower.apply(&update);
+        assert_eq!(follower, new);
+
+        // Updates that weren't sent yet are merged, keeping the newest contents of each line.
+        let newer = screen(&["$ ls", "b.txt", "/home"], 1);
+        let mut merged = update.clone();
+        merged.merge(new.diff(&newer).unwrap());
+        assert_eq!(
+            merged.changed_lines,
+            vec![(1, "b.txt".to_string()), (2, "/home".to_string())]
+        );
+        let mut follower = old.clone();
+        follower.apply(&merged);
+        assert_eq!(follower, newer);
+
+        // The screen shrinks when the terminal is resized.
+        let smaller = screen(&["/home"], 0);
+        let mut merged_with_resize = merged.clone();
+        merged_with_resize.merge(newer.diff(&smaller).unwrap());
+        assert_eq!(
+            merged_with_resize.changed_lines,
+            vec![(0, "/home".to_string())]
+        );
+        let mut follower = old;
+        follower.apply(&merged_with_resize);
+        assert_eq!(follower, smaller);
+    }
+}
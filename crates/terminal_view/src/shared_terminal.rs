@@ -0,0 +1,410 @@
+//! Sharing terminals with collaborators.
+//!
+//! The host of a shared project can share a terminal, which makes it followable: guests
+//! following the host see a display-only mirror of the terminal's screen, and can type
+//! into it if the host allowed input. After the initial screen, followers are only sent
+//! the lines that changed, at most every [`FOLLOWER_UPDATE_INTERVAL`].
+
+use std::{cell::Cell, time::Duration};
+
+use anyhow::Result;
+use futures::StreamExt as _;
+use gpui::{App, AppContext as _, Context, Entity, Task, Window, actions};
+use project::Project;
+use rpc::proto;
+use settings::Settings as _;
+use terminal::{
+    TerminalBuilder, TerminalScreen, TerminalScreenUpdate,
+    terminal_settings::{AlternateScroll, TerminalSettings},
+};
+use util::{ResultExt as _, paths::PathStyle};
+use workspace::{
+    CollaboratorId, ViewId, Workspace,
+    item::{Dedup, FollowEvent, FollowableItem, Item as _, ItemEvent},
+};
+
+use crate::TerminalView;
+
+actions!(
+    terminal,
+    [
+        /// Shares the terminal with the project's collaborators, who can watch it.
+        ShareTerminal,
+        /// Shares the terminal with the project's collaborators, who can watch and type into it.
+        ShareTerminalWithInput,
+        /// Stops sharing the terminal with the project's collaborators.
+        StopSharingTerminal,
+    ]
+);
+
+const FOLLOWER_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+pub(crate) enum TerminalSharing {
+    /// The terminal is shared with the guests of the project.
+    Host {
+        terminal_id: u64,
+        input_allowed: bool,
+        /// The title and screen that followers have been sent.
+        sent_title: String,
+        sent_screen: TerminalScreen,
+        /// The changes that the workspace hasn't sent to followers yet.
+        pending_update: Cell<Option<(String, TerminalScreenUpdate)>>,
+        follower_update_task: Option<Task<()>>,
+    },
+    /// The terminal mirrors a terminal shared by the host of the project.
+    Guest {
+        terminal_id: u64,
+        screen: TerminalScreen,
+        _forward_input: Task<()>,
+    },
+}
+
+impl TerminalView {
+    pub(crate) fn can_share(&self, cx: &App) -> bool {
+        self.sharing.is_none()
+            && self
+                .project
+                .upgrade()
+                .is_some_and(|project| project.read(cx).is_shared() && project.read(cx).is_local())
+    }
+
+    pub(crate) fn is_shared_by_host(&self) -> bool {
+        matches!(self.sharing, Some(TerminalSharing::Host { .. }))
+    }
+
+    pub(crate) fn share_terminal(
+        &mut self,
+        _: &ShareTerminal,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.share(false, cx);
+    }
+
+    pub(crate) fn share_terminal_with_input(
+        &mut self,
+        _: &ShareTerminalWithInput,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.share(true, cx);
+    }
+
+    fn share(&mut self, input_allowed: bool, cx: &mut Context<Self>) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let Some(terminal_id) = project
+            .update(cx, |project, _| {
+                project.share_terminal(&self.terminal, input_allowed)
+            })
+            .log_err()
+        else {
+            return;
+        };
+        self.sharing = Some(TerminalSharing::Host {
+            terminal_id,
+            input_allowed,
+            sent_title: self.tab_content_text(0, cx).to_string(),
+            sent_screen: self.terminal.read(cx).screen(),
+            pending_update: Cell::new(None),
+            follower_update_task: None,
+        });
+        cx.emit(ItemEvent::UpdateTab);
+        cx.notify();
+    }
+
+    /// Sends the changes to the screen to followers once the interval has passed.
+    pub(crate) fn schedule_follower_update(&mut self, cx: &mut Context<Self>) {
+        let Some(TerminalSharing::Host {
+            follower_update_task,
+            ..
+        }) = &mut self.sharing
+        else {
+            return;
+        };
+        if follower_update_task.is_some() {
+            return;
+        }
+        *follower_update_task = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor()
+                .timer(FOLLOWER_UPDATE_INTERVAL)
+                .await;
+            this.update(cx, |this, cx| this.update_followers(cx)).ok();
+        }));
+    }
+
+    fn update_followers(&mut self, cx: &mut Context<Self>) {
+        let title = self.tab_content_text(0, cx).to_string();
+        let screen = self.terminal.read(cx).screen();
+        let Some(TerminalSharing::Host {
+            sent_title,
+            sent_screen,
+            pending_update,
+            follower_update_task,
+            ..
+        }) = &mut self.sharing
+        else {
+            return;
+        };
+        *follower_update_task = None;
+
+        let screen_update = sent_screen.diff(&screen);
+        if screen_update.is_none() && *sent_title == title {
+            return;
+        }
+        let screen_update = screen_update.unwrap_or_else(|| TerminalScreenUpdate {
+            line_count: screen.lines.len() as u32,
+            changed_lines: Vec::new(),
+            cursor_line: screen.cursor_line,
+            cursor_column: screen.cursor_column,
+        });
+        let update = match pending_update.take() {
+            Some((_, mut pending)) => {
+                pending.merge(screen_update);
+                pending
+            }
+            None => screen_update,
+        };
+        pending_update.set(Some((title.clone(), update)));
+        *sent_title = title;
+        *sent_screen = screen;
+        cx.emit(ItemEvent::UpdateTab);
+    }
+
+    pub(crate) fn stop_sharing_terminal(
+        &mut self,
+        _: &StopSharingTerminal,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(TerminalSharing::Host { terminal_id, .. }) = self.sharing.take() {
+            self.project
+                .update(cx, |project, _| project.unshare_terminal(terminal_id))
+                .ok();
+            cx.emit(ItemEvent::UpdateTab);
+            cx.notify();
+        }
+    }
+}
+
+fn screen_update_to_proto(
+    title: String,
+    update: TerminalScreenUpdate,
+) -> proto::update_view::Terminal {
+    proto::update_view::Terminal {
+        title,
+        line_count: update.line_count,
+        changed_lines: update
+            .changed_lines
+            .into_iter()
+            .map(|(index, text)| proto::TerminalLine { index, text })
+            .collect(),
+        cursor_line: update.cursor_line,
+        cursor_column: update.cursor_column,
+    }
+}
+
+fn screen_update_from_proto(message: &proto::update_view::Terminal) -> TerminalScreenUpdate {
+    TerminalScreenUpdate {
+        line_count: message.line_count,
+        changed_lines: message
+            .changed_lines
+            .iter()
+            .map(|line| (line.index, line.text.clone()))
+            .collect(),
+        cursor_line: message.cursor_line,
+        cursor_column: message.cursor_column,
+    }
+}
+
+fn screen_to_proto(screen: TerminalScreen) -> proto::TerminalScreen {
+    proto::TerminalScreen {
+        lines: screen.lines,
+        cursor_line: screen.cursor_line,
+        cursor_column: screen.cursor_column,
+    }
+}
+
+fn screen_from_proto(screen: proto::TerminalScreen) -> TerminalScreen {
+    TerminalScreen {
+        lines: screen.lines,
+        cursor_line: screen.cursor_line,
+        cursor_column: screen.cursor_column,
+    }
+}
+
+impl FollowableItem for TerminalView {
+    fn remote_id(&self) -> Option<ViewId> {
+        self.remote_id
+    }
+
+    fn to_state_proto(&self, _window: &mut Window, _cx: &mut App) -> Option<proto::view::Variant> {
+        let Some(TerminalSharing::Host {
+            terminal_id,
+            input_allowed,
+            sent_title,
+            sent_screen,
+            ..
+        }) = &self.sharing
+        else {
+            return None;
+        };
+        // Send what other followers were sent, so that the updates that follow apply to it.
+        Some(proto::view::Variant::Terminal(proto::view::Terminal {
+            terminal_id: *terminal_id,
+            title: sent_title.clone(),
+            input_allowed: *input_allowed,
+            screen: Some(screen_to_proto(sent_screen.clone())),
+        }))
+    }
+
+    fn from_state_proto(
+        workspace: Entity<Workspace>,
+        remote_id: ViewId,
+        state: &mut Option<proto::view::Variant>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        let Some(proto::view::Variant::Terminal(_)) = state else {
+            return None;
+        };
+        let Some(proto::view::Variant::Terminal(state)) = state.take() else {
+            unreachable!()
+        };
+
+        let settings = TerminalSettings::get_global(cx);
+        let builder = match TerminalBuilder::new_display_only(
+            settings.cursor_shape,
+            AlternateScroll::On,
+            None,
+            workspace.entity_id().as_u64(),
+            cx.background_executor(),
+            PathStyle::local(),
+        ) {
+            Ok(builder) => builder,
+            Err(error) => return Some(Task::ready(Err(error))),
+        };
+        let terminal = cx.new(|cx| builder.subscribe(cx));
+        let screen = state.screen.map(screen_from_proto).unwrap_or_default();
+        terminal.update(cx, |terminal, cx| terminal.show_screen(&screen, cx));
+
+        let project = workspace.read(cx).project().clone();
+        let workspace_id = workspace.read(cx).database_id();
+        let view = cx.new(|cx| {
+            let mut view = TerminalView::new(
+                terminal.clone(),
+                workspace.downgrade(),
+                workspace_id,
+                project.downgrade(),
+                window,
+                cx,
+            );
+            view.remote_id = Some(remote_id);
+            view.custom_title = Some(state.title);
+
+            let forward_input = if state.input_allowed {
+                let mut input_rx = terminal.update(cx, |terminal, _| terminal.forward_input());
+                let project = project.downgrade();
+                let terminal_id = state.terminal_id;
+                cx.spawn(async move |_, cx| {
+                    while let Some(input) = input_rx.next().await {
+                        let Ok(write) = project.update(cx, |project, cx| {
+                            project.write_to_shared_terminal(terminal_id, input, cx)
+                        }) else {
+                            break;
+                        };
+                        write.await.log_err();
+                    }
+                })
+            } else {
+                Task::ready(())
+            };
+            view.sharing = Some(TerminalSharing::Guest {
+                terminal_id: state.terminal_id,
+                screen,
+                _forward_input: forward_input,
+            });
+            view
+        });
+        Some(Task::ready(Ok(view)))
+    }
+
+    fn to_follow_event(_event: &Self::Event) -> Option<FollowEvent> {
+        None
+    }
+
+    fn add_event_to_update_proto(
+        &self,
+        event: &ItemEvent,
+        update: &mut Option<proto::update_view::Variant>,
+        _window: &mut Window,
+        _cx: &mut App,
+    ) -> bool {
+        let Some(TerminalSharing::Host { pending_update, .. }) = &self.sharing else {
+            return false;
+        };
+        if !matches!(event, ItemEvent::UpdateTab) {
+            return false;
+        }
+        let Some((title, mut screen_update)) = pending_update.take() else {
+            return false;
+        };
+        // Keep the changes from an update that hasn't been sent yet.
+        if let Some(proto::update_view::Variant::Terminal(unsent)) = update.take() {
+            let mut unsent_update = screen_update_from_proto(&unsent);
+            unsent_update.merge(screen_update);
+            screen_update = unsent_update;
+        }
+        *update = Some(proto::update_view::Variant::Terminal(
+            screen_update_to_proto(title, screen_update),
+        ));
+        true
+    }
+
+    fn apply_update_proto(
+        &mut self,
+        _project: &Entity<Project>,
+        message: proto::update_view::Variant,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let proto::update_view::Variant::Terminal(message) = message else {
+            return Task::ready(Ok(()));
+        };
+        let Some(TerminalSharing::Guest { screen, .. }) = &mut self.sharing else {
+            return Task::ready(Ok(()));
+        };
+        screen.apply(&screen_update_from_proto(&message));
+        self.terminal
+            .update(cx, |terminal, cx| terminal.show_screen(screen, cx));
+        self.custom_title = Some(message.title);
+        cx.emit(ItemEvent::UpdateTab);
+        Task::ready(Ok(()))
+    }
+
+    fn is_project_item(&self, _window: &Window, _cx: &App) -> bool {
+        true
+    }
+
+    fn set_leader_id(
+        &mut self,
+        _leader_id: Option<CollaboratorId>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) {
+    }
+
+    fn dedup(&self, existing: &Self, _window: &Window, _cx: &App) -> Option<Dedup> {
+        match (&self.sharing, &existing.sharing) {
+            (
+                Some(TerminalSharing::Guest { terminal_id, .. }),
+                Some(TerminalSharing::Guest {
+                    terminal_id: existing_id,
+                    ..
+                }),
+            ) if terminal_id == existing_id => Some(Dedup::KeepExisting),
+            _ => None,
+        }
+    }
+}
@@ -17,6 +17,7 @@ use gpui::{
 };
 use itertools::Itertools;
 use project::{Fs, Project, ProjectEntryId};
+use rpc::proto;
 
 use settings::{Settings, TerminalDockPosition};
 use task::{RevealStrategy, RevealTarget, Shell, ShellBuilder, SpawnInTerminal, TaskId};
@@ -1776,6 +1777,10 @@ impl Panel for TerminalPanel {
         Some("Terminal Panel")
     }
 
+    fn remote_id() -> Option<proto::PanelId> {
+        Some(proto::PanelId::TerminalPanel)
+    }
+
     fn toggle_action(&self) -> Box<dyn gpui::Action> {
         Box::new(ToggleFocus)
     }
@@ -1,4 +1,5 @@
 mod persistence;
+mod shared_terminal;
 pub mod terminal_element;
 pub mod terminal_panel;
 mod terminal_path_like_target;
@@ -19,6 +20,8 @@ use project::{Project, search::SearchQuery};
 use schemars::JsonSchema;
 use serde::Deserialize;
 use settings::{Settings, SettingsStore, TerminalBlink, WorkingDirectory};
+use shared_terminal::TerminalSharing;
+pub use shared_terminal::{ShareTerminal, ShareTerminalWithInput, StopSharingTerminal};
 use std::{
     cmp,
     ops::{Range, RangeInclusive},
@@ -100,6 +103,7 @@ pub fn init(cx: &mut App) {
     terminal_panel::init(cx);
 
     register_serializable_item::<TerminalView>(cx);
+    workspace::FollowableViewRegistry::register::<TerminalView>(cx);
 
     cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
         workspace.register_action(TerminalView::deploy);
@@ -145,6 +149,8 @@ pub struct TerminalView {
     self_handle: WeakEntity<Self>,
     rename_editor: Option<Entity<Editor>>,
     rename_editor_subscription: Option<Subscription>,
+    remote_id: Option<workspace::ViewId>,
+    sharing: Option<TerminalSharing>,
     _subscriptions: Vec<Subscription>,
     _terminal_subscriptions: Vec<Subscription>,
 }
@@ -291,6 +297,8 @@ impl TerminalView {
             self_handle: cx.entity().downgrade(),
             rename_editor: None,
             rename_editor_subscription: None,
+            remote_id: None,
+            sharing: None,
             _subscriptions: subscriptions,
             _terminal_subscriptions: terminal_subscriptions,
         }
@@ -504,6 +512,8 @@ impl TerminalView {
             .selection_text
             .as_ref()
             .is_some_and(|text| !text.is_empty());
+        let can_share = self.can_share(cx);
+        let is_shared_by_host = self.is_shared_by_host();
         let context_menu = ContextMenu::build(window, cx, |menu, _, _| {
             menu.context(self.focus_handle.clone())
                 .action("New Terminal", Box::new(NewTerminal::default()))
@@ -519,6 +529,18 @@ impl TerminalView {
                             menu.action("Add to Agent Thread", Box::new(AddSelectionToThread))
                         })
                 })
+                .when(can_share, |menu| {
+                    menu.separator()
+                        .action("Share Terminal", Box::new(ShareTerminal))
+                        .action(
+                            "Share Terminal with Input",
+                            Box::new(ShareTerminalWithInput),
+                        )
+                })
+                .when(is_shared_by_host, |menu| {
+                    menu.separator()
+                        .action("Stop Sharing Terminal", Box::new(StopSharingTerminal))
+                })
                 .separator()
                 .action(
                     "Close Terminal Tab",
@@ -995,6 +1017,7 @@ fn subscribe_for_terminal_events(
                     cx.emit(Event::Wakeup);
                     cx.emit(ItemEvent::UpdateTab);
                     cx.emit(SearchEvent::MatchesInvalidated);
+                    terminal_view.schedule_follower_update(cx);
                 }
 
                 Event::Bell => {
@@ -1023,6 +1046,7 @@ fn subscribe_for_terminal_events(
 
                 Event::TitleChanged => {
                     cx.emit(ItemEvent::UpdateTab);
+                    terminal_view.schedule_follower_update(cx);
                 }
 
                 Event::NewNavigationTarget(maybe_navigation_target) => {
@@ -1210,6 +1234,9 @@ impl Render for TerminalView {
             .track_focus(&self.focus_handle(cx))
             .key_context(self.dispatch_context(cx))
             .on_action(cx.listener(TerminalView::send_text))
+            .on_action(cx.listener(TerminalView::share_terminal))
+            .on_action(cx.listener(TerminalView::share_terminal_with_input))
+            .on_action(cx.listener(TerminalView::stop_sharing_terminal))
             .on_action(cx.listener(TerminalView::send_keystroke))
             .on_action(cx.listener(TerminalView::copy))
             .on_action(cx.listener(TerminalView::paste))
@@ -1554,7 +1581,7 @@ impl SerializableItem for TerminalView {
         cx: &mut Context<Self>,
     ) -> Option<Task<anyhow::Result<()>>> {
         let terminal = self.terminal().read(cx);
-        if terminal.task().is_some() {
+        if terminal.task().is_some() || self.remote_id.is_some() {
             return None;
         }
 
@@ -1,5 +1,5 @@
 use crate::persistence::model::DockData;
-use crate::{DraggedDock, Event, ModalLayer, Pane};
+use crate::{DraggedDock, Event, ModalLayer, Pane, item::ItemHandle};
 use crate::{Workspace, status_bar::StatusItemView};
 use anyhow::Context as _;
 use client::proto;
@@ -52,6 +52,11 @@ pub trait Panel: Focusable + EventEmitter<PanelEvent> + Render + Sized {
     fn pane(&self) -> Option<Entity<Pane>> {
         None
     }
+    /// The item that collaborators following this panel should see, for panels that show
+    /// their content without a [`Pane`].
+    fn followable_item(&self, _cx: &App) -> Option<Box<dyn ItemHandle>> {
+        None
+    }
     fn remote_id() -> Option<proto::PanelId> {
         None
     }
@@ -73,6 +78,7 @@ pub trait PanelHandle: Send + Sync {
     fn set_active(&self, active: bool, window: &mut Window, cx: &mut App);
     fn remote_id(&self) -> Option<proto::PanelId>;
     fn pane(&self, cx: &App) -> Option<Entity<Pane>>;
+    fn followable_item(&self, cx: &App) -> Option<Box<dyn ItemHandle>>;
     fn size(&self, window: &Window, cx: &App) -> Pixels;
     fn set_size(&self, size: Option<Pixels>, window: &mut Window, cx: &mut App);
     fn icon(&self, window: &Window, cx: &App) -> Option<ui::IconName>;
@@ -144,6 +150,10 @@ where
         self.read(cx).pane()
     }
 
+    fn followable_item(&self, cx: &App) -> Option<Box<dyn ItemHandle>> {
+        self.read(cx).followable_item(cx)
+    }
+
     fn remote_id(&self) -> Option<PanelId> {
         T::remote_id()
     }
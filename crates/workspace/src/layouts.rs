@@ -0,0 +1,572 @@
+//! Named layouts: snapshots of how a workspace's panes and docks are arranged, which
+//! can be saved, listed and applied to any workspace.
+
+use crate::{
+    ItemId, Member, Pane, PaneAxis, PaneGroup, SerializableItemRegistry, Workspace, WorkspaceId,
+    dock::Dock, item::ItemHandle, persistence::DB,
+};
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use gpui::{Action, App, Axis, Context, Entity, Task, Window, px};
+use project::ProjectPath;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::{ResultExt as _, rel_path::RelPath};
+
+/// Applies a saved layout to the workspace, rearranging its panes and docks.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, JsonSchema, Action)]
+#[action(namespace = workspace)]
+#[serde(deny_unknown_fields)]
+pub struct ApplyLayout {
+    /// The name the layout was saved under.
+    pub name: String,
+}
+
+/// Saves the current arrangement of the workspace's panes and docks as a layout.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, JsonSchema, Action)]
+#[action(namespace = workspace)]
+#[serde(deny_unknown_fields)]
+pub struct SaveLayout {
+    /// The name to save the layout under, replacing any layout with the same name.
+    pub name: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceLayout {
+    center: LayoutPaneGroup,
+    left_dock: LayoutDock,
+    right_dock: LayoutDock,
+    bottom_dock: LayoutDock,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum LayoutPaneGroup {
+    Group {
+        axis: Axis,
+        flexes: Vec<f32>,
+        children: Vec<LayoutPaneGroup>,
+    },
+    Pane(LayoutPane),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct LayoutPane {
+    items: Vec<LayoutItem>,
+    active_item: Option<usize>,
+    active: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum LayoutItem {
+    /// A file in one of the project's worktrees, reopened by its path.
+    File {
+        worktree_root_name: String,
+        path: String,
+    },
+    /// Any other item that can be serialized, such as a terminal or a project diff.
+    /// Open items of the same kind are reused when the layout is applied. The item's
+    /// row is kept when the workspace cleans up items it didn't load, as long as a
+    /// layout refers to it.
+    Serialized {
+        kind: String,
+        workspace_id: WorkspaceId,
+        item_id: ItemId,
+    },
+}
+
+impl LayoutPaneGroup {
+    fn items(&self) -> Vec<&LayoutItem> {
+        match self {
+            LayoutPaneGroup::Group { children, .. } => {
+                children.iter().flat_map(|child| child.items()).collect()
+            }
+            LayoutPaneGroup::Pane(pane) => pane.items.iter().collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct LayoutDock {
+    visible: bool,
+    active_panel: Option<String>,
+    size: Option<f32>,
+}
+
+/// Returns the names of all saved layouts, sorted alphabetically.
+pub fn saved_layout_names(cx: &App) -> Task<Result<Vec<String>>> {
+    cx.background_spawn(async move { DB.layout_names() })
+}
+
+pub fn delete_layout(name: String, cx: &App) -> Task<Result<()>> {
+    cx.background_spawn(async move { DB.delete_layout(name).await })
+}
+
+/// Returns the ids of the workspace's serialized items that saved layouts refer to, by
+/// item kind.
+pub(crate) fn saved_layout_item_ids(
+    workspace_id: WorkspaceId,
+) -> Result<HashMap<String, Vec<ItemId>>> {
+    let mut item_ids = HashMap::<String, Vec<ItemId>>::default();
+    for layout in DB.layouts()? {
+        let Some(layout) = serde_json::from_str::<WorkspaceLayout>(&layout).log_err() else {
+            continue;
+        };
+        for item in layout.center.items() {
+            if let LayoutItem::Serialized {
+                kind,
+                workspace_id: item_workspace_id,
+                item_id,
+            } = item
+                && *item_workspace_id == workspace_id
+            {
+                item_ids.entry(kind.clone()).or_default().push(*item_id);
+            }
+        }
+    }
+    Ok(item_ids)
+}
+
+impl Workspace {
+    pub fn capture_layout(&self, window: &Window, cx: &App) -> WorkspaceLayout {
+        let capture_dock = |dock: &Entity<Dock>| {
+            let dock = dock.read(cx);
+            let active_panel = dock.active_panel();
+            LayoutDock {
+                visible: dock.is_open(),
+                active_panel: active_panel.map(|panel| panel.persistent_name().to_string()),
+                size: active_panel.map(|panel| panel.size(window, cx).as_f32()),
+            }
+        };
+
+        WorkspaceLayout {
+            center: self.capture_pane_group(&self.center.root, cx),
+            left_dock: capture_dock(&self.left_dock),
+            right_dock: capture_dock(&self.right_dock),
+            bottom_dock: capture_dock(&self.bottom_dock),
+        }
+    }
+
+    fn capture_pane_group(&self, member: &Member, cx: &App) -> LayoutPaneGroup {
+        match member {
+            Member::Axis(PaneAxis {
+                axis,
+                members,
+                flexes,
+                ..
+            }) => LayoutPaneGroup::Group {
+                axis: *axis,
+                flexes: flexes.lock().clone(),
+                children: members
+                    .iter()
+                    .map(|member| self.capture_pane_group(member, cx))
+                    .collect(),
+            },
+            Member::Pane(pane) => {
+                let pane_state = pane.read(cx);
+                let mut items = Vec::new();
+                let mut active_item = None;
+                for (ix, item) in pane_state.items().enumerate() {
+                    let Some(layout_item) = self.capture_item(item.as_ref(), cx) else {
+                        continue;
+                    };
+                    if ix == pane_state.active_item_index() {
+                        active_item = Some(items.len());
+                    }
+                    items.push(layout_item);
+                }
+                LayoutPaneGroup::Pane(LayoutPane {
+                    items,
+                    active_item,
+                    active: pane == &self.active_pane,
+                })
+            }
+        }
+    }
+
+    fn capture_item(&self, item: &dyn ItemHandle, cx: &App) -> Option<LayoutItem> {
+        if let Some(project_path) = item.project_path(cx) {
+            let worktree = self
+                .project
+                .read(cx)
+                .worktree_for_id(project_path.worktree_id, cx)?;
+            return Some(LayoutItem::File {
+                worktree_root_name: worktree.read(cx).root_name_str().to_string(),
+                path: project_path.path.as_unix_str().to_string(),
+            });
+        }
+
+        let item = item.to_serializable_item_handle(cx)?;
+        Some(LayoutItem::Serialized {
+            kind: item.serialized_item_kind().to_string(),
+            workspace_id: self.database_id()?,
+            item_id: item.item_id().as_u64(),
+        })
+    }
+
+    pub fn save_layout(
+        &mut self,
+        name: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let layout = self.capture_layout(window, cx);
+        // Make sure the serialized items referenced by the layout are persisted.
+        let serialize_workspace = self.flush_serialization(window, cx);
+        cx.background_spawn(async move {
+            serialize_workspace.await;
+            let layout = serde_json::to_string(&layout)?;
+            DB.save_layout(name, layout).await
+        })
+    }
+
+    pub fn apply_saved_layout(
+        &mut self,
+        name: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let load_layout = cx.background_spawn(async move {
+            let layout = DB
+                .layout(&name)?
+                .with_context(|| format!("no layout named {name:?}"))?;
+            anyhow::Ok(serde_json::from_str::<WorkspaceLayout>(&layout)?)
+        });
+        cx.spawn_in(window, async move |this, cx| {
+            let layout = load_layout.await?;
+            this.update_in(cx, |this, window, cx| this.apply_layout(layout, window, cx))?
+                .await
+        })
+    }
+
+    /// Rearranges the workspace to match the layout. Items the layout refers to are
+    /// reused if they're already open and reopened otherwise. Other items are
+    /// closed, unless they have unsaved changes.
+    pub fn apply_layout(
+        &mut self,
+        layout: WorkspaceLayout,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        for (dock, layout_dock) in [
+            (&self.left_dock, &layout.left_dock),
+            (&self.right_dock, &layout.right_dock),
+            (&self.bottom_dock, &layout.bottom_dock),
+        ] {
+            dock.update(cx, |dock, cx| {
+                if let Some(panel_ix) = layout_dock
+                    .active_panel
+                    .as_deref()
+                    .and_then(|name| dock.panel_index_for_persistent_name(name, cx))
+                {
+                    dock.activate_panel(panel_ix, window, cx);
+                }
+                if let Some(size) = layout_dock.size {
+                    dock.resize_active_panel(Some(px(size)), window, cx);
+                }
+                dock.set_open(layout_dock.visible, window, cx);
+            });
+        }
+
+        // Detach all items from the current panes, so they can be reused.
+        let mut open_items = Vec::new();
+        for pane in self.center.panes().into_iter().cloned().collect::<Vec<_>>() {
+            pane.update(cx, |pane, cx| {
+                let items = pane.items().cloned().collect::<Vec<_>>();
+                for item in items {
+                    pane.remove_item(item.item_id(), false, false, window, cx);
+                    open_items.push(item);
+                }
+            });
+        }
+
+        let mut panes_to_load = Vec::new();
+        let (root, active_pane) =
+            self.build_layout_pane_group(layout.center, &mut panes_to_load, window, cx);
+        let active_pane = active_pane.unwrap_or_else(|| panes_to_load[0].0.clone());
+        self.remove_panes(self.center.root.clone(), window, cx);
+        self.center = PaneGroup::with_root(root);
+        self.center.set_is_center(true);
+        self.center.mark_positions(cx);
+        self.set_active_pane(&active_pane, window, cx);
+        cx.notify();
+
+        cx.spawn_in(window, async move |this, cx| {
+            for (pane, items, active_item) in panes_to_load {
+                let mut active_item_id = None;
+                for (ix, layout_item) in items.into_iter().enumerate() {
+                    let item = this.update_in(cx, |this, window, cx| {
+                        this.load_layout_item(layout_item, &pane, &mut open_items, window, cx)
+                    })?;
+                    let Some(item) = item.await.log_err() else {
+                        continue;
+                    };
+                    if Some(ix) == active_item {
+                        active_item_id = Some(item.item_id());
+                    }
+                    pane.update_in(cx, |pane, window, cx| {
+                        if pane.index_for_item(item.as_ref()).is_none() {
+                            pane.add_item(item, true, false, None, window, cx);
+                        }
+                    })?;
+                }
+                pane.update_in(cx, |pane, window, cx| {
+                    if let Some(ix) = active_item_id
+                        .and_then(|id| pane.items().position(|item| item.item_id() == id))
+                    {
+                        pane.activate_item(ix, false, false, window, cx);
+                    }
+                })?;
+            }
+
+            // Keep items with unsaved changes around, rather than discarding their changes.
+            this.update_in(cx, |this, window, cx| {
+                let active_pane = this.active_pane.clone();
+                for item in open_items {
+                    if item.is_dirty(cx) {
+                        active_pane.update(cx, |pane, cx| {
+                            pane.add_item(item, false, false, None, window, cx);
+                        });
+                    }
+                }
+                this.serialize_workspace(window, cx);
+            })
+        })
+    }
+
+    fn build_layout_pane_group(
+        &mut self,
+        group: LayoutPaneGroup,
+        panes_to_load: &mut Vec<(Entity<Pane>, Vec<LayoutItem>, Option<usize>)>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> (Member, Option<Entity<Pane>>) {
+        match group {
+            LayoutPaneGroup::Group {
+                axis,
+                flexes,
+                children,
+            } if !children.is_empty() => {
+                let mut active_pane = None;
+                let members = children
+                    .into_iter()
+                    .map(|child| {
+                        let (member, active) =
+                            self.build_layout_pane_group(child, panes_to_load, window, cx);
+                        active_pane = active_pane.take().or(active);
+                        member
+                    })
+                    .collect::<Vec<_>>();
+                let flexes = (flexes.len() == members.len()).then_some(flexes);
+                (
+                    Member::Axis(PaneAxis::load(axis, members, flexes)),
+                    active_pane,
+                )
+            }
+            LayoutPaneGroup::Group { .. } => {
+                let pane = self.add_pane(window, cx);
+                panes_to_load.push((pane.clone(), Vec::new(), None));
+                (Member::Pane(pane), None)
+            }
+            LayoutPaneGroup::Pane(layout_pane) => {
+                let pane = self.add_pane(window, cx);
+                let active = layout_pane.active.then(|| pane.clone());
+                panes_to_load.push((pane.clone(), layout_pane.items, layout_pane.active_item));
+                (Member::Pane(pane), active)
+            }
+        }
+    }
+
+    fn load_layout_item(
+        &mut self,
+        layout_item: LayoutItem,
+        pane: &Entity<Pane>,
+        open_items: &mut Vec<Box<dyn ItemHandle>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Box<dyn ItemHandle>>> {
+        match layout_item {
+            LayoutItem::File {
+                worktree_root_name,
+                path,
+            } => {
+                let project = self.project.read(cx);
+                let Some(project_path) = RelPath::unix(&path).ok().and_then(|path| {
+                    let worktree = project
+                        .visible_worktrees(cx)
+                        .find(|worktree| worktree.read(cx).root_name_str() == worktree_root_name)
+                        .or_else(|| project.visible_worktrees(cx).next())?;
+                    Some(ProjectPath {
+                        worktree_id: worktree.read(cx).id(),
+                        path: path.into_arc(),
+                    })
+                }) else {
+                    return Task::ready(Err(anyhow::anyhow!("invalid layout path {path:?}")));
+                };
+
+                if let Some(ix) = open_items
+                    .iter()
+                    .position(|item| item.project_path(cx).as_ref() == Some(&project_path))
+                {
+                    return Task::ready(Ok(open_items.remove(ix)));
+                }
+                self.open_path(project_path, Some(pane.downgrade()), false, window, cx)
+            }
+            LayoutItem::Serialized {
+                kind,
+                workspace_id,
+                item_id,
+            } => {
+                if let Some(ix) = open_items.iter().position(|item| {
+                    item.to_serializable_item_handle(cx)
+                        .is_some_and(|item| item.serialized_item_kind() == kind)
+                }) {
+                    return Task::ready(Ok(open_items.remove(ix)));
+                }
+                let project = self.project.clone();
+                let workspace = cx.weak_entity();
+                pane.update(cx, |_, cx| {
+                    SerializableItemRegistry::deserialize(
+                        &kind,
+                        project,
+                        workspace,
+                        workspace_id,
+                        item_id,
+                        window,
+                        cx,
+                    )
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SplitDirection, item::test::TestItem, tests::init_test};
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use project::Project;
+
+    #[gpui::test]
+    async fn test_apply_layout(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project, window, cx));
+
+        let clean_item = cx.new(TestItem::new);
+        let dirty_item = cx.new(|cx| TestItem::new(cx).with_dirty(true));
+        let split_layout = workspace.update_in(cx, |workspace, window, cx| {
+            workspace.add_item_to_active_pane(Box::new(clean_item), None, true, window, cx);
+            workspace.split_pane(
+                workspace.active_pane().clone(),
+                SplitDirection::Right,
+                window,
+                cx,
+            );
+            workspace.add_item_to_active_pane(Box::new(dirty_item.clone()), None, true, window, cx);
+            workspace.capture_layout(window, cx)
+        });
+        assert!(matches!(
+            &split_layout.center,
+            LayoutPaneGroup::Group { axis: Axis::Horizontal, children, .. } if children.len() == 2
+        ));
+
+        let single_pane_layout = WorkspaceLayout {
+            center: LayoutPaneGroup::Pane(LayoutPane {
+                items: Vec::new(),
+                active_item: None,
+                active: true,
+            }),
+            left_dock: LayoutDock::default(),
+            right_dock: LayoutDock::default(),
+            bottom_dock: LayoutDock::default(),
+        };
+        workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.apply_layout(single_pane_layout, window, cx)
+            })
+            .await
+            .unwrap();
+        workspace.read_with(cx, |workspace, cx| {
+            assert_eq!(workspace.panes().len(), 1);
+            // Items the layout doesn't mention are closed, unless they have unsaved changes.
+            let item_ids = workspace
+                .active_pane()
+                .read(cx)
+                .items()
+                .map(|item| item.item_id())
+                .collect::<Vec<_>>();
+            assert_eq!(item_ids, vec![dirty_item.entity_id()]);
+        });
+
+        workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.apply_layout(split_layout.clone(), window, cx)
+            })
+            .await
+            .unwrap();
+        workspace.update_in(cx, |workspace, window, cx| {
+            assert_eq!(workspace.panes().len(), 2);
+            assert_eq!(workspace.capture_layout(window, cx), split_layout);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_saved_layout_item_ids() {
+        let workspace_id = WorkspaceId::from_i64(9001);
+        let other_workspace_id = WorkspaceId::from_i64(9002);
+        let pane = |items| {
+            LayoutPaneGroup::Pane(LayoutPane {
+                items,
+                active_item: None,
+                active: false,
+            })
+        };
+        let layout = WorkspaceLayout {
+            center: LayoutPaneGroup::Group {
+                axis: Axis::Horizontal,
+                flexes: vec![1., 1.],
+                children: vec![
+                    pane(vec![
+                        LayoutItem::File {
+                            worktree_root_name: "root".to_string(),
+                            path: "src/main.rs".to_string(),
+                        },
+                        LayoutItem::Serialized {
+                            kind: "Terminal".to_string(),
+                            workspace_id,
+                            item_id: 1,
+                        },
+                    ]),
+                    pane(vec![LayoutItem::Serialized {
+                        kind: "Terminal".to_string(),
+                        workspace_id: other_workspace_id,
+                        item_id: 2,
+                    }]),
+                ],
+            },
+            left_dock: LayoutDock::default(),
+            right_dock: LayoutDock::default(),
+            bottom_dock: LayoutDock::default(),
+        };
+        DB.save_layout(
+            "test_saved_layout_item_ids".to_string(),
+            serde_json::to_string(&layout).unwrap(),
+        )
+        .await
+        .unwrap();
+
+        // The workspace keeps the rows of the items its saved layouts refer to.
+        let item_ids = saved_layout_item_ids(workspace_id).unwrap();
+        assert_eq!(item_ids.len(), 1);
+        assert_eq!(item_ids["Terminal"], vec![1]);
+        assert_eq!(
+            saved_layout_item_ids(other_workspace_id).unwrap()["Terminal"],
+            vec![2]
+        );
+    }
+}
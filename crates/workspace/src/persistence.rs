@@ -970,6 +970,12 @@ impl Domain for WorkspaceDb {
         sql!(
             ALTER TABLE remote_connections ADD COLUMN use_podman BOOLEAN;
         ),
+        sql!(
+            CREATE TABLE workspace_layouts(
+                name TEXT NOT NULL PRIMARY KEY,
+                layout TEXT NOT NULL
+            ) STRICT;
+        ),
     ];
 
     // Allow recovering from bad migration that was initially shipped to nightly
@@ -2161,6 +2167,43 @@ impl WorkspaceDb {
         }
     }
 
+    query! {
+        pub(crate) async fn save_layout(name: String, layout: String) -> Result<()> {
+            INSERT OR REPLACE INTO workspace_layouts(name, layout)
+            VALUES (?1, ?2)
+        }
+    }
+
+    query! {
+        pub(crate) fn layout(name: &str) -> Result<Option<String>> {
+            SELECT layout
+            FROM workspace_layouts
+            WHERE name = ?
+        }
+    }
+
+    query! {
+        pub(crate) fn layouts() -> Result<Vec<String>> {
+            SELECT layout
+            FROM workspace_layouts
+        }
+    }
+
+    query! {
+        pub(crate) fn layout_names() -> Result<Vec<String>> {
+            SELECT name
+            FROM workspace_layouts
+            ORDER BY name
+        }
+    }
+
+    query! {
+        pub(crate) async fn delete_layout(name: String) -> Result<()> {
+            DELETE FROM workspace_layouts
+            WHERE name = ?
+        }
+    }
+
     pub(crate) async fn toolchains(
         &self,
         workspace_id: WorkspaceId,
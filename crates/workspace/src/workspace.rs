@@ -2,6 +2,7 @@ pub mod dock;
 pub mod history_manager;
 pub mod invalid_item_view;
 pub mod item;
+mod layouts;
 mod modal_layer;
 mod multi_workspace;
 pub mod notifications;
@@ -22,6 +23,7 @@ mod workspace_settings;
 
 pub use crate::notifications::NotificationFrame;
 pub use dock::Panel;
+pub use layouts::{ApplyLayout, SaveLayout, WorkspaceLayout, delete_layout, saved_layout_names};
 pub use multi_workspace::{
     DraggedSidebar, FocusWorkspaceSidebar, MultiWorkspace, NewWorkspaceInWindow,
     NextWorkspaceInWindow, PreviousWorkspaceInWindow, Sidebar, SidebarEvent, SidebarHandle,
@@ -5628,12 +5630,18 @@ impl Workspace {
         for dock in self.all_docks() {
             if dock.focus_handle(cx).contains_focused(window, cx)
                 && let Some(panel) = dock.read(cx).active_panel()
-                && let Some(pane) = panel.pane(cx)
-                && let Some(item) = pane.read(cx).active_item()
             {
-                active_item = Some(item);
-                panel_id = panel.remote_id();
-                break;
+                if let Some(pane) = panel.pane(cx) {
+                    if let Some(item) = pane.read(cx).active_item() {
+                        active_item = Some(item);
+                        panel_id = panel.remote_id();
+                        break;
+                    }
+                } else if let Some(item) = panel.followable_item(cx) {
+                    // Followers show the items of panels without a pane in their center pane.
+                    active_item = Some(item);
+                    break;
+                }
             }
         }
 
@@ -5643,6 +5651,37 @@ impl Workspace {
         (active_item, panel_id)
     }
 
+    /// Sends an update of an item shown by a panel without a [`Pane`] to the followers.
+    /// Items in panes are tracked by the workspace, but these have to report their changes.
+    pub fn update_followers_for_panel_item(
+        &self,
+        item: &dyn FollowableItemHandle,
+        update: proto::update_view::Variant,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        if !item.item_focus_handle(cx).contains_focused(window, cx) {
+            return;
+        }
+        let Some(id) = item
+            .remote_id(&self.app_state.client, window, cx)
+            .and_then(|id| id.to_proto())
+        else {
+            return;
+        };
+        let is_project_item = item.is_project_item(window, cx);
+        self.update_followers(
+            is_project_item,
+            proto::update_followers::Variant::UpdateView(proto::UpdateView {
+                id: Some(id),
+                leader_id: None,
+                variant: Some(update),
+            }),
+            window,
+            cx,
+        );
+    }
+
     fn update_followers(
         &self,
         project_only: bool,
@@ -6293,6 +6332,19 @@ impl Workspace {
             // the database filling up, we delete items that haven't been loaded now.
             //
             // The items that have been loaded, have been saved after they've been added to the workspace.
+            // Items that saved layouts refer to are kept as well, so the layouts can reopen them.
+            let workspace_id = serialized_workspace.id;
+            let layout_item_ids = cx
+                .background_spawn(async move { layouts::saved_layout_item_ids(workspace_id) })
+                .await
+                .log_err()
+                .unwrap_or_default();
+            for (item_kind, loaded_items) in &mut item_ids_by_kind {
+                if let Some(item_ids) = layout_item_ids.get(*item_kind) {
+                    loaded_items.extend(item_ids);
+                }
+            }
+
             let clean_up_tasks = workspace.update_in(cx, |_, window, cx| {
                 item_ids_by_kind
                     .into_iter()
@@ -6468,6 +6520,22 @@ impl Workspace {
                 }),
             )
             .on_action(cx.listener(Self::toggle_all_docks))
+            .on_action(cx.listener(
+                |workspace: &mut Workspace, action: &SaveLayout, window, cx| {
+                    workspace
+                        .save_layout(action.name.clone(), window, cx)
+                        .detach_and_prompt_err("Failed to save layout", window, cx, |_, _, _| None);
+                },
+            ))
+            .on_action(cx.listener(
+                |workspace: &mut Workspace, action: &ApplyLayout, window, cx| {
+                    workspace
+                        .apply_saved_layout(action.name.clone(), window, cx)
+                        .detach_and_prompt_err("Failed to apply layout", window, cx, |_, _, _| {
+                            None
+                        });
+                },
+            ))
             .on_action(cx.listener(
                 |workspace: &mut Workspace, _: &ClearAllNotifications, _, cx| {
                     workspace.clear_all_notifications(cx);
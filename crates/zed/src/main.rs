@@ -191,6 +191,11 @@ fn main() {
         return;
     }
 
+    // `zed --sandbox-policy` Makes zed run a command in a sandbox, for the agent's terminal tool
+    if let Some(policy) = &args.sandbox_policy {
+        sandbox::main(policy, &args.paths_or_urls);
+    }
+
     // `zed --crash-handler` Makes zed operate in minidump crash handler mode
     if let Some(socket) = &args.crash_handler {
         crashes::crash_server(socket.as_path());
@@ -699,8 +704,11 @@ fn main() {
         vim::init(cx);
         terminal_view::init(cx);
         journal::init(app_state.clone(), cx);
+        scratch_buffers::init(app_state.clone(), cx);
         encoding_selector::init(cx);
         language_selector::init(cx);
+        layout_selector::init(cx);
+        local_history::init(cx);
         line_ending_selector::init(cx);
         toolchain_selector::init(cx);
         theme_selector::init(cx);
@@ -1617,6 +1625,11 @@ struct Args {
     #[arg(hide = true)]
     askpass: Option<String>,
 
+    /// Used by the agent's terminal tool to run the command following `--` in a sandbox
+    /// with the given JSON policy.
+    #[arg(long, hide = true)]
+    sandbox_policy: Option<String>,
+
     #[arg(long, hide = true)]
     dump_all_actions: bool,
 
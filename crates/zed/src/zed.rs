@@ -2,6 +2,7 @@ mod app_menus;
 pub mod edit_prediction_registry;
 #[cfg(target_os = "macos")]
 pub(crate) mod mac_only_instance;
+mod mcp_server;
 mod migrate;
 mod open_listener;
 mod open_url_modal;
@@ -632,6 +633,8 @@ fn initialize_panels(
             workspace_handle.clone(),
             cx.clone(),
         );
+        let ports_panel =
+            recent_projects::ports_panel::PortsPanel::load(workspace_handle.clone(), cx.clone());
         let debug_panel = DebugPanel::load(workspace_handle.clone(), cx);
 
         async fn add_panel_when_ready(
@@ -657,6 +660,7 @@ fn initialize_panels(
             add_panel_when_ready(channels_panel, workspace_handle.clone(), cx.clone()),
             add_panel_when_ready(notification_panel, workspace_handle.clone(), cx.clone()),
             add_panel_when_ready(debug_panel, workspace_handle.clone(), cx.clone()),
+            add_panel_when_ready(ports_panel, workspace_handle.clone(), cx.clone()),
             initialize_agent_panel(workspace_handle, prompt_builder, cx.clone()).map(|r| r.log_err()),
         );
 
@@ -4833,8 +4837,10 @@ mod tests {
                 "keymap_editor",
                 "keystroke_input",
                 "language_selector",
+                "layout_selector",
                 "welcome",
                 "line_ending_selector",
+                "local_history",
                 "lsp_tool",
                 "markdown",
                 "menu",
@@ -4856,6 +4862,7 @@ mod tests {
                 "remote_debug",
                 "repl",
                 "rules_library",
+                "scratch_buffers",
                 "search",
                 "settings_editor",
                 "settings_profile_selector",
@@ -0,0 +1,941 @@
+//! Serves an open project over the Model Context Protocol, so that external
+//! agents launched through `zed --mcp` can use the project's buffers, language
+//! servers, and search.
+
+use anyhow::{Context as _, Result, anyhow};
+use cli::{CliRequest, CliResponse, ipc::IpcSender};
+use context_server::{
+    listener::{McpHttpServer, McpServer, McpServerTool, ToolResponse},
+    types::{
+        Implementation, InitializeResponse, LATEST_PROTOCOL_VERSION, ProtocolVersion,
+        ServerCapabilities, ToolAnnotations, ToolResponseContent, ToolsCapabilities,
+        requests::{Initialize, Ping},
+    },
+};
+use futures::{FutureExt as _, StreamExt as _, channel::mpsc, select_biased};
+use gpui::{App, AppContext as _, AsyncApp, Entity, Task, WeakEntity};
+use language::{Buffer, DiagnosticSeverity, Location, OffsetRangeExt as _, Point, ToPoint as _};
+use project::{
+    Project, ProjectPath, SearchResults, WorktreeSettings,
+    search::{SearchQuery, SearchResult},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings as _;
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+use util::{ResultExt as _, paths::PathMatcher};
+use workspace::MultiWorkspace;
+
+const MAX_SEARCH_RESULTS: usize = 100;
+
+pub async fn serve_cli_connection(
+    cwd: PathBuf,
+    http_port: Option<u16>,
+    mut requests: mpsc::Receiver<CliRequest>,
+    responses: &IpcSender<CliResponse>,
+    cx: &mut AsyncApp,
+) -> Result<()> {
+    let project = cx.update(|cx| project_for_path(&cwd, cx))?;
+    let server = start_server(project.downgrade(), cx).await?;
+    let mut connection = server.connect(cx);
+
+    if let Some(port) = http_port {
+        let http_server = McpHttpServer::new(connection, port, cx)?;
+        responses.send(CliResponse::Stdout {
+            message: format!(
+                "Serving MCP on http://{}. Requests must include the header \"Authorization: Bearer {}\".",
+                http_server.address(),
+                http_server.token()
+            ),
+        })?;
+        // Keep serving until the CLI disconnects.
+        while requests.next().await.is_some() {}
+        return Ok(());
+    }
+
+    loop {
+        select_biased! {
+            message = connection.outgoing.next().fuse() => {
+                let Some(message) = message else {
+                    break;
+                };
+                responses.send(CliResponse::McpMessage { message })?;
+            }
+            request = requests.next().fuse() => {
+                match request {
+                    Some(CliRequest::McpMessage { message }) => {
+                        connection.incoming.unbounded_send(message)?;
+                    }
+                    Some(request) => log::error!("unexpected CLI request: {request:?}"),
+                    None => break,
+                }
+            }
+        }
+    }
+
+    // Once the CLI closes its stdin, stop accepting messages, but send the responses to
+    // the requests that are still being handled before ending the session.
+    drop(connection.incoming);
+    while let Some(message) = connection.outgoing.next().await {
+        responses.send(CliResponse::McpMessage { message })?;
+    }
+
+    Ok(())
+}
+
+/// Starts an MCP server exposing the project's tools.
+async fn start_server(project: WeakEntity<Project>, cx: &mut AsyncApp) -> Result<McpServer> {
+    let mut server = McpServer::new(cx).await?;
+    server.handle_request::<Initialize>(|_, cx| {
+        Task::ready(Ok(InitializeResponse {
+            protocol_version: ProtocolVersion(LATEST_PROTOCOL_VERSION.to_string()),
+            capabilities: ServerCapabilities {
+                experimental: None,
+                logging: None,
+                completions: None,
+                prompts: None,
+                resources: None,
+                tools: Some(ToolsCapabilities {
+                    list_changed: Some(false),
+                }),
+            },
+            server_info: Implementation {
+                name: "zed".to_string(),
+                version: release_channel::AppVersion::global(cx).to_string(),
+            },
+            meta: None,
+        }))
+    });
+    server.handle_request::<Ping>(|_, _| Task::ready(Ok(())));
+
+    server.add_tool(ReadFileTool {
+        project: project.clone(),
+    });
+    server.add_tool(GrepTool {
+        project: project.clone(),
+    });
+    server.add_tool(DiagnosticsTool {
+        project: project.clone(),
+    });
+    server.add_tool(OutlineTool {
+        project: project.clone(),
+    });
+    server.add_tool(GoToDefinitionTool {
+        project: project.clone(),
+    });
+    server.add_tool(FindReferencesTool {
+        project: project.clone(),
+    });
+    server.add_tool(OpenBuffersTool { project });
+
+    Ok(server)
+}
+
+/// Returns the open project with a visible worktree containing `path`.
+fn project_for_path(path: &Path, cx: &App) -> Result<Entity<Project>> {
+    cx.windows()
+        .into_iter()
+        .filter_map(|window| window.downcast::<MultiWorkspace>())
+        .filter_map(|window| window.read(cx).log_err())
+        .flat_map(|multi_workspace| multi_workspace.workspaces().to_vec())
+        .map(|workspace| workspace.read(cx).project().clone())
+        .find(|project| {
+            project
+                .read(cx)
+                .visible_worktrees(cx)
+                .any(|worktree| path.starts_with(worktree.read(cx).abs_path()))
+        })
+        .with_context(|| format!("no project containing {} is open in Zed", path.display()))
+}
+
+async fn open_buffer(
+    project: &WeakEntity<Project>,
+    path: &str,
+    cx: &mut AsyncApp,
+) -> Result<Entity<Buffer>> {
+    let task = project.update(cx, |project, cx| {
+        let project_path = project
+            .find_project_path(path, cx)
+            .with_context(|| format!("Could not find path {path} in project"))?;
+        check_path_is_shared(&project_path, cx)
+            .map_err(|reason| anyhow!("Cannot read {path} because {reason}"))?;
+        anyhow::Ok(project.open_buffer(project_path, cx))
+    })??;
+    task.await
+}
+
+/// Fails for paths matching the `file_scan_exclusions` or `private_files`
+/// settings, whose contents must not be shared with external agents.
+fn check_path_is_shared(project_path: &ProjectPath, cx: &App) -> Result<()> {
+    let global_settings = WorktreeSettings::get_global(cx);
+    let worktree_settings = WorktreeSettings::get(Some(project_path.into()), cx);
+    for (settings, scope) in [(global_settings, "global"), (worktree_settings, "worktree")] {
+        if settings.is_path_excluded(&project_path.path) {
+            anyhow::bail!("its path matches the {scope} `file_scan_exclusions` setting");
+        }
+        if settings.is_path_private(&project_path.path) {
+            anyhow::bail!("its path matches the {scope} `private_files` setting");
+        }
+    }
+    Ok(())
+}
+
+/// Formats a location as `path:line: text`, or returns `None` if its file
+/// can't be shared with external agents.
+fn format_location(location: &Location, cx: &App) -> Option<String> {
+    let buffer = location.buffer.read(cx);
+    let file = buffer.file()?;
+    let project_path = ProjectPath {
+        worktree_id: file.worktree_id(cx),
+        path: file.path().clone(),
+    };
+    check_path_is_shared(&project_path, cx).ok()?;
+    let path = file.full_path(cx);
+    let start = location.range.start.to_point(buffer);
+    let line = buffer.text_for_range(
+        Point::new(start.row, 0)..Point::new(start.row, buffer.line_len(start.row)),
+    );
+    Some(format!(
+        "{}:{}: {}",
+        path.display(),
+        start.row + 1,
+        line.collect::<String>().trim()
+    ))
+}
+
+fn text_response(text: String) -> ToolResponse<()> {
+    ToolResponse {
+        content: vec![ToolResponseContent::Text { text }],
+        structured_content: (),
+    }
+}
+
+fn read_only_annotations(title: &str) -> ToolAnnotations {
+    ToolAnnotations {
+        title: Some(title.to_string()),
+        read_only_hint: Some(true),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(false),
+    }
+}
+
+/// Reads the contents of a file in the project, including any unsaved changes
+/// made in Zed.
+#[derive(Deserialize, JsonSchema)]
+struct ReadFileInput {
+    /// The path of the file to read, starting with the name of one of the project's root directories.
+    path: String,
+    /// Optional line number to start reading from (1-based).
+    #[serde(default)]
+    start_line: Option<u32>,
+    /// Optional line number to end reading at (1-based, inclusive).
+    #[serde(default)]
+    end_line: Option<u32>,
+}
+
+#[derive(Clone)]
+struct ReadFileTool {
+    project: WeakEntity<Project>,
+}
+
+impl McpServerTool for ReadFileTool {
+    type Input = ReadFileInput;
+    type Output = ();
+
+    const NAME: &'static str = "read_file";
+
+    fn annotations(&self) -> ToolAnnotations {
+        read_only_annotations("Read File")
+    }
+
+    async fn run(&self, input: Self::Input, cx: &mut AsyncApp) -> Result<ToolResponse<()>> {
+        let buffer = open_buffer(&self.project, &input.path, cx).await?;
+        let text = buffer.read_with(cx, |buffer, _| {
+            let max_row = buffer.max_point().row;
+            let start_row = input.start_line.unwrap_or(1).saturating_sub(1).min(max_row);
+            let end_row = input
+                .end_line
+                .map_or(max_row, |end_line| end_line.saturating_sub(1).min(max_row))
+                .max(start_row);
+            buffer
+                .text_for_range(
+                    Point::new(start_row, 0)..Point::new(end_row, buffer.line_len(end_row)),
+                )
+                .collect::<String>()
+        });
+        Ok(text_response(text))
+    }
+}
+
+/// Searches the contents of files in the project with a regular expression,
+/// returning matching lines as `path:line: text`.
+#[derive(Deserialize, JsonSchema)]
+struct GrepInput {
+    /// A regex pattern to search for, parsed by the Rust `regex` crate.
+    regex: String,
+    /// A glob pattern for the paths of files to include in the search, matched
+    /// against the full path including the project root directory.
+    #[serde(default)]
+    include_pattern: Option<String>,
+    /// Whether the regex is case-sensitive.
+    #[serde(default)]
+    case_sensitive: bool,
+}
+
+#[derive(Clone)]
+struct GrepTool {
+    project: WeakEntity<Project>,
+}
+
+impl McpServerTool for GrepTool {
+    type Input = GrepInput;
+    type Output = ();
+
+    const NAME: &'static str = "grep";
+
+    fn annotations(&self) -> ToolAnnotations {
+        read_only_annotations("Search Project")
+    }
+
+    async fn run(&self, input: Self::Input, cx: &mut AsyncApp) -> Result<ToolResponse<()>> {
+        let SearchResults { rx, _task_handle } = self.project.update(cx, |project, cx| {
+            let path_style = project.path_style(cx);
+            let include_matcher = match input.include_pattern {
+                Some(pattern) => PathMatcher::new([pattern], path_style)?,
+                None => PathMatcher::default(),
+            };
+            let query = SearchQuery::regex(
+                &input.regex,
+                false,
+                input.case_sensitive,
+                false,
+                true,
+                include_matcher,
+                PathMatcher::default(),
+                true,
+                None,
+            )?;
+            anyhow::Ok(project.search(query, cx))
+        })??;
+        futures::pin_mut!(rx);
+
+        let mut output = String::new();
+        let mut match_count = 0;
+        'outer: while let Some(result) = rx.next().await {
+            let SearchResult::Buffer { buffer, ranges } = result else {
+                break;
+            };
+            for range in ranges {
+                if match_count == MAX_SEARCH_RESULTS {
+                    writeln!(output, "(results truncated)").ok();
+                    break 'outer;
+                }
+                let location = Location {
+                    buffer: buffer.clone(),
+                    range,
+                };
+                if let Some(line) = cx.update(|cx| format_location(&location, cx)) {
+                    writeln!(output, "{line}").ok();
+                    match_count += 1;
+                }
+            }
+        }
+
+        if output.is_empty() {
+            output = "No matches found".to_string();
+        }
+        Ok(text_response(output))
+    }
+}
+
+/// Returns the errors and warnings reported by language servers, either for a
+/// single file or summarized for the whole project.
+#[derive(Deserialize, JsonSchema)]
+struct DiagnosticsInput {
+    /// The path of a file to get diagnostics for. If omitted, returns a summary
+    /// of the diagnostics in the project.
+    #[serde(default)]
+    path: Option<String>,
+}
+
+#[derive(Clone)]
+struct DiagnosticsTool {
+    project: WeakEntity<Project>,
+}
+
+impl McpServerTool for DiagnosticsTool {
+    type Input = DiagnosticsInput;
+    type Output = ();
+
+    const NAME: &'static str = "diagnostics";
+
+    fn annotations(&self) -> ToolAnnotations {
+        read_only_annotations("Diagnostics")
+    }
+
+    async fn run(&self, input: Self::Input, cx: &mut AsyncApp) -> Result<ToolResponse<()>> {
+        let mut output = String::new();
+        match input.path.filter(|path| !path.is_empty()) {
+            Some(path) => {
+                let buffer = open_buffer(&self.project, &path, cx).await?;
+                let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+                for (_, group) in snapshot.diagnostic_groups(None) {
+                    let entry = &group.entries[group.primary_ix];
+                    let severity = match entry.diagnostic.severity {
+                        DiagnosticSeverity::ERROR => "error",
+                        DiagnosticSeverity::WARNING => "warning",
+                        _ => continue,
+                    };
+                    let range = entry.range.to_point(&snapshot);
+                    writeln!(
+                        output,
+                        "{severity} at line {}: {}",
+                        range.start.row + 1,
+                        entry.diagnostic.message
+                    )
+                    .ok();
+                }
+            }
+            None => {
+                self.project.read_with(cx, |project, cx| {
+                    for (project_path, _, summary) in project.diagnostic_summaries(true, cx) {
+                        if (summary.error_count == 0 && summary.warning_count == 0)
+                            || check_path_is_shared(&project_path, cx).is_err()
+                        {
+                            continue;
+                        }
+                        let Some(worktree) = project.worktree_for_id(project_path.worktree_id, cx)
+                        else {
+                            continue;
+                        };
+                        writeln!(
+                            output,
+                            "{}: {} error(s), {} warning(s)",
+                            worktree.read(cx).full_path(&project_path.path).display(),
+                            summary.error_count,
+                            summary.warning_count
+                        )
+                        .ok();
+                    }
+                })?;
+            }
+        }
+
+        if output.is_empty() {
+            output = "No errors or warnings found".to_string();
+        }
+        Ok(text_response(output))
+    }
+}
+
+/// Returns the outline of a file: the symbols it defines, with their line numbers.
+#[derive(Deserialize, JsonSchema)]
+struct OutlineInput {
+    /// The path of the file, starting with the name of one of the project's root directories.
+    path: String,
+}
+
+#[derive(Clone)]
+struct OutlineTool {
+    project: WeakEntity<Project>,
+}
+
+impl McpServerTool for OutlineTool {
+    type Input = OutlineInput;
+    type Output = ();
+
+    const NAME: &'static str = "outline";
+
+    fn annotations(&self) -> ToolAnnotations {
+        read_only_annotations("File Outline")
+    }
+
+    async fn run(&self, input: Self::Input, cx: &mut AsyncApp) -> Result<ToolResponse<()>> {
+        let buffer = open_buffer(&self.project, &input.path, cx).await?;
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+        let mut output = String::new();
+        for item in snapshot.outline(None).items {
+            let range = item.to_point(&snapshot).range;
+            writeln!(
+                output,
+                "{}{} [L{}-{}]",
+                "  ".repeat(item.depth),
+                item.text,
+                range.start.row + 1,
+                range.end.row + 1
+            )
+            .ok();
+        }
+
+        if output.is_empty() {
+            output = "No symbols found".to_string();
+        }
+        Ok(text_response(output))
+    }
+}
+
+/// Identifies a symbol by the line it appears on.
+#[derive(Deserialize, JsonSchema)]
+struct SymbolInput {
+    /// The path of the file containing the symbol.
+    path: String,
+    /// The line containing the symbol (1-based).
+    line: u32,
+    /// The symbol's name, as it appears on the line.
+    symbol: String,
+}
+
+async fn symbol_position(
+    project: &WeakEntity<Project>,
+    input: &SymbolInput,
+    cx: &mut AsyncApp,
+) -> Result<(Entity<Buffer>, Point)> {
+    let buffer = open_buffer(project, &input.path, cx).await?;
+    let position = buffer.read_with(cx, |buffer, _| {
+        let row = input.line.saturating_sub(1);
+        anyhow::ensure!(
+            row <= buffer.max_point().row,
+            "line {} is out of range",
+            input.line
+        );
+        let line = buffer
+            .text_for_range(Point::new(row, 0)..Point::new(row, buffer.line_len(row)))
+            .collect::<String>();
+        let column = line
+            .find(&input.symbol)
+            .with_context(|| format!("symbol {} not found on line {}", input.symbol, input.line))?;
+        Ok(Point::new(row, column as u32))
+    })?;
+    Ok((buffer, position))
+}
+
+/// Finds where a symbol is defined, using the project's language servers.
+#[derive(Deserialize, JsonSchema)]
+struct GoToDefinitionInput {
+    #[serde(flatten)]
+    symbol: SymbolInput,
+}
+
+#[derive(Clone)]
+struct GoToDefinitionTool {
+    project: WeakEntity<Project>,
+}
+
+impl McpServerTool for GoToDefinitionTool {
+    type Input = GoToDefinitionInput;
+    type Output = ();
+
+    const NAME: &'static str = "go_to_definition";
+
+    fn annotations(&self) -> ToolAnnotations {
+        read_only_annotations("Go to Definition")
+    }
+
+    async fn run(&self, input: Self::Input, cx: &mut AsyncApp) -> Result<ToolResponse<()>> {
+        let (buffer, position) = symbol_position(&self.project, &input.symbol, cx).await?;
+        let definitions = self
+            .project
+            .update(cx, |project, cx| project.definitions(&buffer, position, cx))?
+            .await?
+            .unwrap_or_default();
+        let output = cx.update(|cx| {
+            definitions
+                .iter()
+                .filter_map(|definition| format_location(&definition.target, cx))
+                .collect::<Vec<_>>()
+                .join("\n")
+        });
+        if output.is_empty() {
+            return Err(anyhow!("no definition found for {}", input.symbol.symbol));
+        }
+        Ok(text_response(output))
+    }
+}
+
+/// Finds all references to a symbol, using the project's language servers.
+#[derive(Deserialize, JsonSchema)]
+struct FindReferencesInput {
+    #[serde(flatten)]
+    symbol: SymbolInput,
+}
+
+#[derive(Clone)]
+struct FindReferencesTool {
+    project: WeakEntity<Project>,
+}
+
+impl McpServerTool for FindReferencesTool {
+    type Input = FindReferencesInput;
+    type Output = ();
+
+    const NAME: &'static str = "find_references";
+
+    fn annotations(&self) -> ToolAnnotations {
+        read_only_annotations("Find References")
+    }
+
+    async fn run(&self, input: Self::Input, cx: &mut AsyncApp) -> Result<ToolResponse<()>> {
+        let (buffer, position) = symbol_position(&self.project, &input.symbol, cx).await?;
+        let references = self
+            .project
+            .update(cx, |project, cx| project.references(&buffer, position, cx))?
+            .await?
+            .unwrap_or_default();
+        let output = cx.update(|cx| {
+            references
+                .iter()
+                .take(MAX_SEARCH_RESULTS)
+                .filter_map(|reference| format_location(reference, cx))
+                .collect::<Vec<_>>()
+                .join("\n")
+        });
+        if output.is_empty() {
+            return Err(anyhow!("no references found for {}", input.symbol.symbol));
+        }
+        Ok(text_response(output))
+    }
+}
+
+/// Lists the files open in Zed, marking those with unsaved changes.
+#[derive(Deserialize, JsonSchema)]
+struct OpenBuffersInput {}
+
+#[derive(Clone)]
+struct OpenBuffersTool {
+    project: WeakEntity<Project>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct OpenBuffer {
+    path: PathBuf,
+    dirty: bool,
+}
+
+impl McpServerTool for OpenBuffersTool {
+    type Input = OpenBuffersInput;
+    type Output = Vec<OpenBuffer>;
+
+    const NAME: &'static str = "open_buffers";
+
+    fn annotations(&self) -> ToolAnnotations {
+        read_only_annotations("Open Buffers")
+    }
+
+    async fn run(
+        &self,
+        _input: Self::Input,
+        cx: &mut AsyncApp,
+    ) -> Result<ToolResponse<Vec<OpenBuffer>>> {
+        let buffers = self.project.read_with(cx, |project, cx| {
+            project
+                .opened_buffers(cx)
+                .into_iter()
+                .filter_map(|buffer| {
+                    let buffer = buffer.read(cx);
+                    Some(OpenBuffer {
+                        path: buffer.file()?.full_path(cx),
+                        dirty: buffer.is_dirty(),
+                    })
+                })
+                .collect::<Vec<_>>()
+        })?;
+        let text = buffers
+            .iter()
+            .map(|buffer| {
+                if buffer.dirty {
+                    format!("{} (unsaved changes)", buffer.path.display())
+                } else {
+                    buffer.path.display().to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok(ToolResponse {
+            content: vec![ToolResponseContent::Text { text }],
+            structured_content: buffers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zed::tests::init_test;
+    use context_server::listener::McpConnection;
+    use gpui::TestAppContext;
+    use language::{
+        Diagnostic, DiagnosticEntry, DiagnosticSourceKind, LanguageServerId, PointUtf16, Unclipped,
+    };
+    use serde_json::{Value, json};
+    use util::path;
+
+    #[gpui::test]
+    async fn test_mcp_server_handshake_and_tools(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(
+                path!("/root"),
+                json!({
+                    "src": {
+                        "main.rs": "fn main() {\n    println!(\"hello\");\n}\n",
+                    },
+                }),
+            )
+            .await;
+        let project = Project::test(app_state.fs.clone(), [path!("/root").as_ref()], cx).await;
+
+        let mut async_cx = cx.to_async();
+        let server = start_server(project.downgrade(), &mut async_cx)
+            .await
+            .unwrap();
+        let mut connection = server.connect(&mut async_cx);
+
+        let response = request(
+            &mut connection,
+            1,
+            "initialize",
+            json!({
+                "protocolVersion": LATEST_PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": { "name": "test", "version": "1.0" },
+            }),
+        )
+        .await;
+        assert_eq!(response["result"]["serverInfo"]["name"], "zed");
+        assert_eq!(
+            response["result"]["capabilities"]["tools"]["listChanged"],
+            false
+        );
+
+        let response = request(&mut connection, 2, "ping", json!({})).await;
+        assert_eq!(response["id"], 2);
+        assert!(response.get("error").is_none());
+
+        let response = request(&mut connection, 3, "tools/list", json!({})).await;
+        let mut tool_names = response["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        tool_names.sort();
+        assert_eq!(
+            tool_names,
+            [
+                "diagnostics",
+                "find_references",
+                "go_to_definition",
+                "grep",
+                "open_buffers",
+                "outline",
+                "read_file",
+            ]
+        );
+
+        let response = request(
+            &mut connection,
+            4,
+            "tools/call",
+            json!({
+                "name": "read_file",
+                "arguments": { "path": "root/src/main.rs", "start_line": 2, "end_line": 2 },
+            }),
+        )
+        .await;
+        assert_eq!(response["result"]["isError"], false);
+        assert_eq!(
+            response["result"]["content"][0]["text"],
+            "    println!(\"hello\");"
+        );
+
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/root/src/main.rs"), cx)
+            })
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(0..0, "// edited\n")], None, cx)
+        });
+        let response = request(
+            &mut connection,
+            5,
+            "tools/call",
+            json!({
+                "name": "open_buffers",
+                "arguments": {},
+            }),
+        )
+        .await;
+        assert_eq!(
+            response["result"]["structuredContent"],
+            json!([{ "path": path!("root/src/main.rs"), "dirty": true }])
+        );
+
+        let response = request(
+            &mut connection,
+            6,
+            "tools/call",
+            json!({
+                "name": "read_file",
+                "arguments": { "path": "root/src/missing.rs" },
+            }),
+        )
+        .await;
+        assert_eq!(response["result"]["isError"], true);
+
+        let response = request(
+            &mut connection,
+            7,
+            "tools/call",
+            json!({
+                "name": "write_file",
+                "arguments": {},
+            }),
+        )
+        .await;
+        assert_eq!(response["error"]["message"], "Tool not found: write_file");
+    }
+
+    #[gpui::test]
+    async fn test_mcp_server_refuses_private_files(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(
+                path!("/root"),
+                json!({
+                    ".env": "SECRET_TOKEN=hunter2\n",
+                    "config.txt": "TOKEN_NAME=SECRET_TOKEN\n",
+                }),
+            )
+            .await;
+        let project = Project::test(app_state.fs.clone(), [path!("/root").as_ref()], cx).await;
+
+        let mut async_cx = cx.to_async();
+        let server = start_server(project.downgrade(), &mut async_cx)
+            .await
+            .unwrap();
+        let mut connection = server.connect(&mut async_cx);
+
+        let response = request(
+            &mut connection,
+            1,
+            "tools/call",
+            json!({
+                "name": "read_file",
+                "arguments": { "path": "root/.env" },
+            }),
+        )
+        .await;
+        assert_eq!(response["result"]["isError"], true);
+        assert_eq!(
+            response["result"]["content"][0]["text"],
+            "Cannot read root/.env because its path matches the global `private_files` setting"
+        );
+
+        let response = request(
+            &mut connection,
+            2,
+            "tools/call",
+            json!({
+                "name": "grep",
+                "arguments": { "regex": "SECRET_TOKEN" },
+            }),
+        )
+        .await;
+        assert_eq!(
+            response["result"]["content"][0]["text"],
+            format!("{}:1: TOKEN_NAME=SECRET_TOKEN\n", path!("root/config.txt"))
+        );
+
+        let lsp_store = project.read_with(cx, |project, _| project.lsp_store());
+        lsp_store.update(cx, |lsp_store, cx| {
+            for file in [path!("/root/.env"), path!("/root/config.txt")] {
+                lsp_store
+                    .update_diagnostic_entries(
+                        LanguageServerId(0),
+                        PathBuf::from(file),
+                        None,
+                        None,
+                        vec![DiagnosticEntry {
+                            range: Unclipped(PointUtf16::new(0, 0))
+                                ..Unclipped(PointUtf16::new(0, 1)),
+                            diagnostic: Diagnostic {
+                                severity: DiagnosticSeverity::ERROR,
+                                is_primary: true,
+                                message: "unexpected token".to_string(),
+                                source_kind: DiagnosticSourceKind::Pushed,
+                                ..Diagnostic::default()
+                            },
+                        }],
+                        cx,
+                    )
+                    .unwrap();
+            }
+        });
+        let response = request(
+            &mut connection,
+            3,
+            "tools/call",
+            json!({
+                "name": "diagnostics",
+                "arguments": {},
+            }),
+        )
+        .await;
+        assert_eq!(
+            response["result"]["content"][0]["text"],
+            format!("{}: 1 error(s), 0 warning(s)\n", path!("root/config.txt"))
+        );
+    }
+
+    #[gpui::test]
+    async fn test_project_for_path(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(path!("/root"), json!({ "a.txt": "" }))
+            .await;
+        let project = Project::test(app_state.fs.clone(), [path!("/root").as_ref()], cx).await;
+        cx.add_window(|window, cx| MultiWorkspace::test_new(project.clone(), window, cx));
+
+        cx.update(|cx| {
+            assert_eq!(
+                project_for_path(Path::new(path!("/root/src")), cx).unwrap(),
+                project
+            );
+            assert!(project_for_path(Path::new(path!("/other")), cx).is_err());
+        });
+    }
+
+    async fn request(
+        connection: &mut McpConnection,
+        id: u64,
+        method: &str,
+        params: Value,
+    ) -> Value {
+        connection
+            .incoming
+            .unbounded_send(
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": method,
+                    "params": params,
+                })
+                .to_string(),
+            )
+            .unwrap();
+        let response = connection.outgoing.next().await.unwrap();
+        serde_json::from_str(&response).unwrap()
+    }
+}
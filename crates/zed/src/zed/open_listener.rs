@@ -1,5 +1,6 @@
 use crate::handle_open_request;
 use crate::restore_or_create_workspace;
+use crate::zed::mcp_server;
 use anyhow::{Context as _, Result, anyhow};
 use cli::{CliRequest, CliResponse, ipc::IpcSender};
 use cli::{IpcHandshake, ipc};
@@ -453,6 +454,31 @@ pub async fn handle_cli_connection(
                 let status = if open_workspace_result.is_err() { 1 } else { 0 };
                 responses.send(CliResponse::Exit { status }).log_err();
             }
+            CliRequest::McpServer { cwd, http_port } => {
+                let status = match mcp_server::serve_cli_connection(
+                    PathBuf::from(cwd),
+                    http_port,
+                    requests,
+                    &responses,
+                    cx,
+                )
+                .await
+                {
+                    Ok(()) => 0,
+                    Err(error) => {
+                        responses
+                            .send(CliResponse::Stderr {
+                                message: format!("{error:#}"),
+                            })
+                            .log_err();
+                        1
+                    }
+                };
+                responses.send(CliResponse::Exit { status }).log_err();
+            }
+            CliRequest::McpMessage { .. } => {
+                log::error!("received an MCP message before starting an MCP server");
+            }
         }
     }
 }
@@ -185,6 +185,7 @@ fn send_args_to_instance(args: &Args) -> anyhow::Result<()> {
                             exit_status.lock().replace(status);
                             return Ok(());
                         }
+                        CliResponse::McpMessage { .. } => {}
                     }
                 }
                 Ok(())
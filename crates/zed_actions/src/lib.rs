@@ -355,6 +355,18 @@ pub mod icon_theme_selector {
     }
 }
 
+pub mod layout_selector {
+    use gpui::actions;
+
+    actions!(
+        layout_selector,
+        [
+            /// Toggles the layout selector, for saving and applying workspace layouts.
+            Toggle,
+        ]
+    );
+}
+
 pub mod search {
     use gpui::actions;
     actions!(